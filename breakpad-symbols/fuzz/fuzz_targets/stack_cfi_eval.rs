@@ -0,0 +1,76 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use breakpad_symbols::fuzzing_private_exports::{walk_with_stack_cfi_with_limits, ExprLimits};
+use breakpad_symbols::{CfiRules, FrameWalker, SymbolLimits};
+use std::collections::HashMap;
+
+fuzz_target!(|data: ([u64; 8], &str, Vec<&str>)| {
+    test_stack_cfi(data.0, data.1, &data.2);
+});
+
+// Eugh, need this to memoize register names to static
+static STATIC_REGS: [&str; 8] = ["cfa", "ra", "rsp", "rip", "rbp", "rax", "x11", "x12"];
+
+struct TestFrameWalker {
+    callee_regs: HashMap<&'static str, u64>,
+    caller_regs: HashMap<&'static str, u64>,
+    stack: Vec<u8>,
+}
+
+impl FrameWalker for TestFrameWalker {
+    fn get_instruction(&self) -> u64 {
+        0xF1CEFA32
+    }
+    fn has_grand_callee(&self) -> bool {
+        true
+    }
+    fn get_grand_callee_parameter_size(&self) -> u32 {
+        4
+    }
+    fn get_register_at_address(&self, address: u64) -> Option<u64> {
+        let addr = address as usize;
+        let bytes = self.stack.get(addr..addr.checked_add(8)?)?;
+        let mut buf = [0; 8];
+        buf.copy_from_slice(bytes);
+        Some(u64::from_le_bytes(buf))
+    }
+    fn get_callee_register(&self, name: &str) -> Option<u64> {
+        self.callee_regs.get(name).copied()
+    }
+    fn set_caller_register(&mut self, name: &str, val: u64) -> Option<()> {
+        let &reg = STATIC_REGS.iter().find(|&&reg| reg == name)?;
+        self.caller_regs.insert(reg, val);
+        Some(())
+    }
+    fn clear_caller_register(&mut self, name: &str) {
+        self.caller_regs.remove(name);
+    }
+    fn set_cfa(&mut self, val: u64) -> Option<()> {
+        self.caller_regs.insert("cfa", val);
+        Some(())
+    }
+    fn set_ra(&mut self, val: u64) -> Option<()> {
+        self.caller_regs.insert("ra", val);
+        Some(())
+    }
+}
+
+fn test_stack_cfi(regs: [u64; 8], init: &str, additional: &[&str]) {
+    let callee_regs = STATIC_REGS.iter().copied().zip(regs).collect();
+    let mut walker = TestFrameWalker {
+        callee_regs,
+        caller_regs: HashMap::new(),
+        stack: vec![0; 1600],
+    };
+
+    let rules = |rules: &str| CfiRules {
+        address: 0,
+        rules: rules.to_owned(),
+    };
+    let init = rules(init);
+    let additional: Vec<_> = additional.iter().map(|&r| rules(r)).collect();
+
+    let mut limits = ExprLimits::new(&SymbolLimits::default());
+    let _ = walk_with_stack_cfi_with_limits(&init, &additional, &mut walker, &mut limits);
+}
@@ -1,6 +1,14 @@
 #![no_main]
 use libfuzzer_sys::fuzz_target;
 
+use breakpad_symbols::{SymbolFile, SymbolLimits};
+
 fuzz_target!(|data: &[u8]| {
-    let _ = breakpad_symbols::SymbolFile::from_bytes(data);
+    let _ = SymbolFile::from_bytes(data);
+
+    // Small limits, so that the truncation paths get exercised too
+    let mut limits = SymbolLimits::default();
+    limits.max_file_size = 4096;
+    limits.max_records = 16;
+    let _ = SymbolFile::parse_with_limits(data, &limits, |_| ());
 });
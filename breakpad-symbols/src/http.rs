@@ -1,27 +1,200 @@
 //! Contains HTTP symbol retrieval specific functionality
 
 use crate::*;
-use reqwest::{redirect, Client, Url};
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::{redirect, Client, NoProxy, Proxy, RequestBuilder, Response, StatusCode, Url};
+use std::fmt;
+use std::future::Future;
 use std::io::{self, Write};
 use std::path::Path;
 use std::str::FromStr;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tempfile::NamedTempFile;
 use tracing::{debug, trace, warn};
 
 /// A key that uniquely identifies a File associated with a module
 type FileKey = (ModuleKey, FileKind);
 
+/// Options for how an [`HttpSymbolSupplier`] talks to symbol servers.
+#[derive(Clone)]
+pub struct HttpOptions {
+    /// The maximum amount of time a single request (including the download of the
+    /// response) is allowed to take.
+    pub timeout: Duration,
+    /// The maximum amount of time spent fetching a single file from a single
+    /// server, including all retries and the download of the response. `None`
+    /// means no limit beyond `timeout`.
+    pub total_timeout: Option<Duration>,
+    /// How many times to retry a request that failed with a server error (5xx or
+    /// 429 Too Many Requests) or couldn't reach the server at all.
+    ///
+    /// Any other error response (such as 404 Not Found) means the file doesn't exist
+    /// and is never retried.
+    ///
+    /// Defaults to 0, so failed requests aren't retried unless asked for.
+    pub retries: u32,
+    /// How long to wait before the first retry. The delay doubles after each retry.
+    pub retry_backoff: Duration,
+    /// A proxy to send all requests through.
+    ///
+    /// If this is `None`, the `HTTP_PROXY`, `HTTPS_PROXY`, and `ALL_PROXY`
+    /// environment variables are used instead. `NO_PROXY` is honored either way.
+    /// An invalid proxy url is an error when creating the [`HttpSymbolSupplier`].
+    pub proxy: Option<String>,
+    /// Treat a symbol file whose MODULE line has a different debug id than the
+    /// module as not found, and keep looking in the remaining local paths and urls.
+    ///
+    /// See [`SimpleSymbolSupplier::reject_debug_id_mismatch`].
+    pub reject_debug_id_mismatch: bool,
+    /// The limits enforced on the symbol files, both local and downloaded.
+    pub limits: SymbolLimits,
+    /// Headers to send with the requests, e.g. to authenticate with the servers.
+    pub headers: Vec<SymbolServerHeader>,
+    /// Signs the url of every request, see [`UrlSigner`].
+    pub url_signer: Option<Arc<dyn UrlSigner>>,
+}
+
+impl fmt::Debug for HttpOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpOptions")
+            .field("timeout", &self.timeout)
+            .field("total_timeout", &self.total_timeout)
+            .field("retries", &self.retries)
+            .field("retry_backoff", &self.retry_backoff)
+            .field("proxy", &self.proxy)
+            .field("reject_debug_id_mismatch", &self.reject_debug_id_mismatch)
+            .field("limits", &self.limits)
+            .field("headers", &self.headers)
+            .field("url_signer", &self.url_signer.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+/// An HTTP header to send with the requests to some (or all) symbol servers.
+///
+/// Its `Debug` output doesn't include the value, which is often a secret (like
+/// an `Authorization` header's token), so it can be logged.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SymbolServerHeader {
+    /// Only send the header with requests whose url starts with this (e.g. the
+    /// base url of one of the servers), or with every request if `None`.
+    pub url_prefix: Option<String>,
+    pub name: String,
+    pub value: String,
+}
+
+impl SymbolServerHeader {
+    fn applies_to(&self, url: &Url) -> bool {
+        match &self.url_prefix {
+            Some(prefix) => url.as_str().starts_with(prefix.as_str()),
+            None => true,
+        }
+    }
+}
+
+impl fmt::Debug for SymbolServerHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SymbolServerHeader")
+            .field("url_prefix", &self.url_prefix)
+            .field("name", &self.name)
+            .field("value", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Signs the urls of the requests to symbol servers, e.g. to make presigned
+/// urls for S3 buckets (or other storage that authenticates with the url
+/// instead of headers).
+///
+/// It's called for every request (retries included, so urls can expire
+/// quickly), with the unsigned url. Only unsigned urls are logged and reported
+/// as where symbols came from.
+///
+/// Implemented for closures, so `Arc::new(|url: &Url| Ok(sign(url)))` works.
+pub trait UrlSigner: Send + Sync {
+    /// The url to request instead of `url`.
+    fn sign_url(&self, url: &Url) -> io::Result<Url>;
+}
+
+impl<F> UrlSigner for F
+where
+    F: Fn(&Url) -> io::Result<Url> + Send + Sync,
+{
+    fn sign_url(&self, url: &Url) -> io::Result<Url> {
+        self(url)
+    }
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(1000),
+            total_timeout: None,
+            retries: 0,
+            retry_backoff: Duration::from_secs(1),
+            proxy: None,
+            reject_debug_id_mismatch: false,
+            limits: SymbolLimits::default(),
+            headers: Vec::new(),
+            url_signer: None,
+        }
+    }
+}
+
+impl HttpOptions {
+    fn client_builder(&self) -> io::Result<reqwest::ClientBuilder> {
+        let builder = Client::builder();
+        match &self.proxy {
+            Some(proxy) => {
+                let parsed = Proxy::all(proxy).map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("invalid proxy {proxy}: {e}"),
+                    )
+                })?;
+                Ok(builder.proxy(parsed.no_proxy(NoProxy::from_env())))
+            }
+            None => Ok(builder),
+        }
+    }
+
+    /// A GET request of `url` with `client`, signed and with the headers meant for it.
+    fn get(&self, client: &Client, url: &Url) -> io::Result<RequestBuilder> {
+        let mut request = match &self.url_signer {
+            Some(signer) => {
+                let signed = signer
+                    .sign_url(url)
+                    .map_err(|e| io::Error::other(format!("failed to sign {url}: {e}")))?;
+                client.get(signed)
+            }
+            None => client.get(url.clone()),
+        };
+        for header in self.headers.iter().filter(|header| header.applies_to(url)) {
+            let name = HeaderName::from_bytes(header.name.as_bytes()).map_err(io::Error::other)?;
+            let mut value = HeaderValue::from_str(&header.value).map_err(|_| {
+                io::Error::other(format!("invalid value for the {} header", header.name))
+            })?;
+            // Keeps reqwest from logging it.
+            value.set_sensitive(true);
+            request = request.header(name, value);
+        }
+        Ok(request)
+    }
+}
+
 /// An implementation of `SymbolSupplier` that loads Breakpad text-format
 /// symbols from HTTP URLs.
 ///
-/// See [`crate::breakpad_sym_lookup`] for details on how paths are searched.
+/// See [`crate::breakpad_sym_lookups`] for details on how paths are searched.
 pub struct HttpSymbolSupplier {
     /// File paths that are known to be in the cache
     #[allow(clippy::type_complexity)]
     cached_file_paths: CacheMap<FileKey, CachedAsyncResult<(PathBuf, Option<Url>), FileError>>,
     /// HTTP Client to use for fetching symbols.
     client: Client,
+    /// HTTP Client to use for code info lookups, which must not follow redirects.
+    no_redirects_client: Client,
+    options: HttpOptions,
     /// URLs to search for symbols.
     urls: Vec<Url>,
     /// A `SimpleSymbolSupplier` to use for local symbol paths.
@@ -51,10 +224,38 @@ impl HttpSymbolSupplier {
         urls: Vec<String>,
         cache: PathBuf,
         tmp: PathBuf,
-        mut local_paths: Vec<PathBuf>,
+        local_paths: Vec<PathBuf>,
         timeout: Duration,
     ) -> HttpSymbolSupplier {
-        let client = Client::builder().timeout(timeout).build().unwrap();
+        let options = HttpOptions {
+            timeout,
+            ..HttpOptions::default()
+        };
+        Self::with_options(urls, cache, tmp, local_paths, options)
+            .expect("the default options are valid")
+    }
+
+    /// Create a new `HttpSymbolSupplier` with the given [`HttpOptions`].
+    ///
+    /// See [`HttpSymbolSupplier::new`] for details. This fails if the options
+    /// are invalid, e.g. if [`HttpOptions::proxy`] isn't a valid url.
+    pub fn with_options(
+        urls: Vec<String>,
+        cache: PathBuf,
+        tmp: PathBuf,
+        mut local_paths: Vec<PathBuf>,
+        options: HttpOptions,
+    ) -> io::Result<HttpSymbolSupplier> {
+        let client = options
+            .client_builder()?
+            .timeout(options.timeout)
+            .build()
+            .map_err(io::Error::other)?;
+        let no_redirects_client = options
+            .client_builder()?
+            .redirect(redirect::Policy::none())
+            .build()
+            .map_err(io::Error::other)?;
         let urls = urls
             .into_iter()
             .filter_map(|mut u| {
@@ -65,16 +266,20 @@ impl HttpSymbolSupplier {
             })
             .collect();
         local_paths.push(cache.clone());
-        let local = SimpleSymbolSupplier::new(local_paths);
+        let local = SimpleSymbolSupplier::new(local_paths)
+            .reject_debug_id_mismatch(options.reject_debug_id_mismatch)
+            .limits(options.limits);
         let cached_file_paths = Default::default();
-        HttpSymbolSupplier {
+        Ok(HttpSymbolSupplier {
             client,
+            no_redirects_client,
+            options,
             cached_file_paths,
             urls,
             local,
             cache,
             tmp,
-        }
+        })
     }
 
     #[tracing::instrument(level = "trace", skip(self, module), fields(module = crate::basename(&module.code_file())))]
@@ -98,8 +303,17 @@ impl HttpSymbolSupplier {
                 // unlikely to get multiple hits... this might actually be ok!
                 if let Some(lookup) = lookup(module, file_kind) {
                     for url in &self.urls {
-                        let fetch =
-                            fetch_lookup(&self.client, url, &lookup, &self.cache, &self.tmp).await;
+                        let fetch = fetch_lookup(
+                            &self.client,
+                            &self.options,
+                            url,
+                            &lookup,
+                            &self.cache,
+                            &self.tmp,
+                        );
+                        let fetch = within_total_timeout(&self.options, url, fetch)
+                            .await
+                            .unwrap_or_else(|| Err(total_timeout_error(url)));
 
                         if let Ok((path, url)) = fetch {
                             return Ok((path, url));
@@ -111,12 +325,15 @@ impl HttpSymbolSupplier {
                         for url in &self.urls {
                             let fetch = fetch_cab_lookup(
                                 &self.client,
+                                &self.options,
                                 url,
                                 &lookup,
                                 &self.cache,
                                 &self.tmp,
-                            )
-                            .await;
+                            );
+                            let fetch = within_total_timeout(&self.options, url, fetch)
+                                .await
+                                .unwrap_or(Err(FileError::NotFound));
 
                             if let Ok((path, url)) = fetch {
                                 return Ok((path, url));
@@ -136,6 +353,86 @@ fn file_key(module: &(dyn Module + Sync), file_kind: FileKind) -> FileKey {
     (module_key(module), file_kind)
 }
 
+/// Whether a request that got this response is worth retrying.
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// GET `url`, retrying server errors and connection failures as configured by `options`.
+///
+/// Yields `SymbolError::NotFound` if the server says the file doesn't exist, and
+/// `SymbolError::LoadError` if we gave up on getting an answer out of the server.
+async fn get_with_retries(
+    client: &Client,
+    options: &HttpOptions,
+    url: &Url,
+) -> Result<Response, SymbolError> {
+    let deadline = options
+        .total_timeout
+        .map(|timeout| Instant::now() + timeout);
+    let mut backoff = options.retry_backoff;
+    let mut attempt = 0;
+    loop {
+        let result = options
+            .get(client, url)
+            .map_err(SymbolError::LoadError)?
+            .send()
+            .await;
+        let failure = match result {
+            Ok(res) if res.status().is_success() => return Ok(res),
+            Ok(res) if !is_retryable(res.status()) => {
+                debug!("{} responded with {}", url, res.status());
+                return Err(SymbolError::NotFound);
+            }
+            Ok(res) => format!("server responded with {}", res.status()),
+            Err(e) => e.to_string(),
+        };
+
+        let out_of_time = deadline.is_some_and(|deadline| Instant::now() + backoff >= deadline);
+        if attempt >= options.retries || out_of_time {
+            warn!(
+                "Giving up on {} after {} attempts: {}",
+                url,
+                attempt + 1,
+                failure
+            );
+            return Err(SymbolError::LoadError(io::Error::other(format!(
+                "failed to fetch {url}: {failure}"
+            ))));
+        }
+        debug!("Retrying {} in {:?}: {}", url, backoff, failure);
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+        attempt += 1;
+    }
+}
+
+/// Run `fetch`, the fetching of a file from `base_url` (all its requests and the
+/// download of the response), within `options.total_timeout` if there is one.
+///
+/// Yields `None` if it ran out of time.
+async fn within_total_timeout<F: Future>(
+    options: &HttpOptions,
+    base_url: &Url,
+    fetch: F,
+) -> Option<F::Output> {
+    let Some(timeout) = options.total_timeout else {
+        return Some(fetch.await);
+    };
+    let result = tokio::time::timeout(timeout, fetch).await.ok();
+    if result.is_none() {
+        warn!("Giving up on {}: total timeout exceeded", base_url);
+    }
+    result
+}
+
+fn total_timeout_error(base_url: &Url) -> SymbolError {
+    SymbolError::LoadError(io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("failed to fetch from {base_url}: total timeout exceeded"),
+    ))
+}
+
 fn create_cache_file(tmp_path: &Path, final_path: &Path) -> io::Result<NamedTempFile> {
     // Use tempfile to save things to our cache to ensure proper
     // atomicity of writes. We may want multiple instances of rust-minidump
@@ -181,7 +478,11 @@ fn commit_cache_file(mut temp: NamedTempFile, final_path: &Path, url: &Url) -> i
 }
 
 /// Perform a code_file/code_identifier lookup for a specific symbol server.
+///
+/// `no_redirects_client` should not follow redirects--we want the next url if there is one
 async fn individual_lookup_debug_info_by_code_info(
+    no_redirects_client: &Client,
+    options: &HttpOptions,
     base_url: &Url,
     lookup_path: &str,
 ) -> Option<DebugInfoResult> {
@@ -189,13 +490,7 @@ async fn individual_lookup_debug_info_by_code_info(
 
     debug!("Trying code file / code identifier lookup: {}", url);
 
-    // This should not follow redirects--we want the next url if there is one
-    let no_redirects_client = Client::builder()
-        .redirect(redirect::Policy::none())
-        .build()
-        .ok()?;
-
-    let response = no_redirects_client.get(url.clone()).send().await;
+    let response = options.get(no_redirects_client, &url).ok()?.send().await;
     if let Ok(res) = response {
         let res_status = res.status();
         if res_status == reqwest::StatusCode::FOUND
@@ -239,14 +534,21 @@ async fn individual_lookup_debug_info_by_code_info(
 /// This returns a DebugInfoResult with the new debug file and debug identifier
 /// or None.
 async fn lookup_debug_info_by_code_info(
+    no_redirects_client: &Client,
+    options: &HttpOptions,
     symbol_urls: &Vec<Url>,
     module: &(dyn Module + Sync),
 ) -> Option<DebugInfoResult> {
     let lookup_path = code_info_breakpad_sym_lookup(module)?;
 
     for base_url in symbol_urls {
-        if let Some(result) =
-            individual_lookup_debug_info_by_code_info(base_url, &lookup_path).await
+        if let Some(result) = individual_lookup_debug_info_by_code_info(
+            no_redirects_client,
+            options,
+            base_url,
+            &lookup_path,
+        )
+        .await
         {
             return Some(result);
         }
@@ -262,10 +564,13 @@ async fn lookup_debug_info_by_code_info(
 
 /// Fetch a symbol file from the URL made by combining `base_url` and `rel_path` using `client`,
 /// save the file contents under `cache` + `rel_path` and also return them.
+#[tracing::instrument(name = "fetch_symbols", level = "trace", skip_all, fields(server = %base_url, path = sym_lookup.server_rel.as_str()))]
 async fn fetch_symbol_file(
     client: &Client,
+    options: &HttpOptions,
     base_url: &Url,
     module: &(dyn Module + Sync),
+    sym_lookup: &FileLookup,
     cache: &Path,
     tmp: &Path,
 ) -> Result<SymbolFile, SymbolError> {
@@ -280,7 +585,6 @@ async fn fetch_symbol_file(
     // give up on caching but let the parse+download continue.
 
     // First try to GET the file from a server
-    let sym_lookup = breakpad_sym_lookup(module).ok_or(SymbolError::MissingDebugFileOrId)?;
     let mut url = base_url
         .join(&sym_lookup.server_rel)
         .map_err(|_| SymbolError::NotFound)?;
@@ -289,15 +593,10 @@ async fn fetch_symbol_file(
         .append_pair("code_file", crate::basename(&module.code_file()))
         .append_pair("code_id", code_id.as_str());
     debug!("Trying {}", url);
-    let res = client
-        .get(url.clone())
-        .send()
-        .await
-        .and_then(|res| res.error_for_status())
-        .map_err(|_| SymbolError::NotFound)?;
+    let res = get_with_retries(client, options, &url).await?;
 
     // Now try to create the temp cache file (not yet in the cache)
-    let final_cache_path = cache.join(sym_lookup.cache_rel);
+    let final_cache_path = cache.join(&sym_lookup.cache_rel);
     let mut temp = create_cache_file(tmp, &final_cache_path)
         .map_err(|e| {
             warn!("Failed to save symbol file in local disk cache: {}", e);
@@ -305,7 +604,7 @@ async fn fetch_symbol_file(
         .ok();
 
     // Now stream parse the file as it downloads.
-    let mut symbol_file = SymbolFile::parse_async(res, |data| {
+    let mut symbol_file = SymbolFile::parse_async_with_limits(res, &options.limits, |data| {
         // While we're downloading+parsing, save this data to the the disk cache too
         if let Some(file) = temp.as_mut() {
             if let Err(e) = file.write_all(data) {
@@ -335,6 +634,7 @@ async fn fetch_symbol_file(
 /// The returned value is the path to the downloaded file and the url it was downloaded from.
 async fn fetch_lookup(
     client: &Client,
+    options: &HttpOptions,
     base_url: &Url,
     lookup: &FileLookup,
     cache: &Path,
@@ -345,12 +645,7 @@ async fn fetch_lookup(
         .join(&lookup.server_rel)
         .map_err(|_| SymbolError::NotFound)?;
     debug!("Trying {}", url);
-    let mut res = client
-        .get(url.clone())
-        .send()
-        .await
-        .and_then(|res| res.error_for_status())
-        .map_err(|_| SymbolError::NotFound)?;
+    let mut res = get_with_retries(client, options, &url).await?;
 
     // Now try to create the temp cache file (not yet in the cache)
     let final_cache_path = cache.join(&lookup.cache_rel);
@@ -377,6 +672,7 @@ async fn fetch_lookup(
 #[cfg(feature = "mozilla_cab_symbols")]
 async fn fetch_cab_lookup(
     client: &Client,
+    options: &HttpOptions,
     base_url: &Url,
     lookup: &FileLookup,
     cache: &Path,
@@ -388,11 +684,8 @@ async fn fetch_cab_lookup(
         .join(&cab_lookup.server_rel)
         .map_err(|_| FileError::NotFound)?;
     debug!("Trying {}", url);
-    let res = client
-        .get(url.clone())
-        .send()
+    let res = get_with_retries(client, options, &url)
         .await
-        .and_then(|res| res.error_for_status())
         .map_err(|_| FileError::NotFound)?;
 
     let cab_bytes = res.bytes().await.map_err(|_| FileError::NotFound)?;
@@ -407,6 +700,7 @@ async fn fetch_cab_lookup(
 #[cfg(not(feature = "mozilla_cab_symbols"))]
 async fn fetch_cab_lookup(
     _client: &Client,
+    _options: &HttpOptions,
     _base_url: &Url,
     _lookup: &FileLookup,
     _cache: &Path,
@@ -478,7 +772,13 @@ impl SymbolSupplier for HttpSymbolSupplier {
 
         if missing_debug_info {
             debug!("Missing debug file or debug identifier--trying lookup with code info");
-            extra_debug_info = lookup_debug_info_by_code_info(&self.urls, module).await;
+            extra_debug_info = lookup_debug_info_by_code_info(
+                &self.no_redirects_client,
+                &self.options,
+                &self.urls,
+                module,
+            )
+            .await;
             if let Some(debug_info_result) = &extra_debug_info {
                 debug_file = Some(debug_info_result.debug_file.clone());
                 debug_id = Some(debug_info_result.debug_identifier);
@@ -507,27 +807,52 @@ impl SymbolSupplier for HttpSymbolSupplier {
         }
         trace!("HttpSymbolSupplier search (SimpleSymbolSupplier found nothing)");
 
-        // Second: try to directly download sym files
+        // Second: try to directly download sym files, from each server at each
+        // path they may be stored at (debug info first, then code info).
+        let sym_lookups = breakpad_sym_lookups(&lookup_module);
+        let mut load_error = None;
         for url in &self.urls {
-            // First, try to get a breakpad .sym file from the symbol server
-            let sym =
-                fetch_symbol_file(&self.client, url, &lookup_module, &self.cache, &self.tmp).await;
-            match sym {
-                Ok(symbols) => {
-                    trace!("HttpSymbolSupplier parsed file!");
-                    return Ok(LocateSymbolsResult {
-                        symbols,
-                        extra_debug_info,
-                    });
-                }
-                Err(e) => {
-                    trace!("HttpSymbolSupplier failed: {}", e);
+            for sym_lookup in &sym_lookups {
+                let sym = fetch_symbol_file(
+                    &self.client,
+                    &self.options,
+                    url,
+                    &lookup_module,
+                    sym_lookup,
+                    &self.cache,
+                    &self.tmp,
+                );
+                let sym = within_total_timeout(&self.options, url, sym)
+                    .await
+                    .unwrap_or_else(|| Err(total_timeout_error(url)));
+                match sym {
+                    Ok(symbols)
+                        if self.options.reject_debug_id_mismatch
+                            && debug_id_mismatch(lookup_module.debug_identifier(), &symbols)
+                                .is_some() =>
+                    {
+                        debug!("HttpSymbolSupplier rejected symbols from {}", url);
+                    }
+                    Ok(symbols) => {
+                        trace!("HttpSymbolSupplier parsed file!");
+                        return Ok(LocateSymbolsResult {
+                            symbols,
+                            extra_debug_info,
+                        });
+                    }
+                    Err(e) => {
+                        trace!("HttpSymbolSupplier failed: {}", e);
+                        if let SymbolError::LoadError(_) = e {
+                            load_error.get_or_insert(e);
+                        }
+                    }
                 }
             }
         }
 
-        // If we get this far, we have failed to find anything
-        Err(SymbolError::NotFound)
+        // If we get this far, we have failed to find anything. If a server couldn't
+        // give us an answer, report that instead, because the file may well exist.
+        Err(load_error.unwrap_or(SymbolError::NotFound))
     }
 
     async fn locate_file(
@@ -539,4 +864,328 @@ impl SymbolSupplier for HttpSymbolSupplier {
             .await
             .map(|(path, _url)| path)
     }
+
+    fn name(&self) -> String {
+        let urls: Vec<&str> = self.urls.iter().map(Url::as_str).collect();
+        urls.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Start a symbol server on localhost that responds with `failure` to the
+    /// first `failures` requests, and serves a symbol file for every request after.
+    ///
+    /// Returns the url of the server and the number of requests it has received.
+    async fn mock_server(failures: usize, failure: &'static str) -> (String, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let url = mock_server_with(move |_| {
+            (counter.fetch_add(1, Ordering::SeqCst) < failures).then_some(failure)
+        })
+        .await;
+        (url, requests)
+    }
+
+    /// Start a symbol server on localhost that responds to each request (its
+    /// request line and headers) with the status `respond` gives it, or serves a
+    /// symbol file if that's `None`.
+    ///
+    /// Returns the url of the server.
+    async fn mock_server_with(
+        respond: impl Fn(&str) -> Option<&'static str> + Send + 'static,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                // Read the request headers (there is no body)
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let len = socket.read(&mut buf).await.unwrap();
+                    if len == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..len]);
+                }
+
+                let request = String::from_utf8_lossy(&request);
+                let response = if let Some(failure) = respond(&request) {
+                    format!("HTTP/1.1 {failure}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                } else {
+                    let body = "MODULE Linux x86 abcd1234 foo\nFUNC 1000 30 10 some func\n";
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                };
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.unwrap();
+            }
+        });
+        url
+    }
+
+    fn supplier(url: String, cache: &Path, retries: u32) -> HttpSymbolSupplier {
+        let options = HttpOptions {
+            retries,
+            ..test_options()
+        };
+        supplier_with_options(url, cache, options)
+    }
+
+    fn test_options() -> HttpOptions {
+        HttpOptions {
+            timeout: Duration::from_secs(10),
+            retry_backoff: Duration::from_millis(1),
+            ..HttpOptions::default()
+        }
+    }
+
+    fn supplier_with_options(
+        url: String,
+        cache: &Path,
+        options: HttpOptions,
+    ) -> HttpSymbolSupplier {
+        HttpSymbolSupplier::with_options(
+            vec![url],
+            cache.join("cache"),
+            cache.to_owned(),
+            vec![],
+            options,
+        )
+        .unwrap()
+    }
+
+    fn module() -> SimpleModule {
+        let debug_id = DebugId::from_str("abcd1234-abcd-1234-abcd-abcd12345678-a").unwrap();
+        SimpleModule::from_basic_info(
+            Some("foo.pdb".to_owned()),
+            Some(debug_id),
+            Some("foo.dll".to_owned()),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_retry_server_errors() {
+        let t = tempfile::tempdir().unwrap();
+        let (url, requests) = mock_server(2, "503 Service Unavailable").await;
+        let supplier = supplier(url, t.path(), 2);
+
+        let result = supplier.locate_symbols(&module()).await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        assert!(result.symbols.functions.get(0x1010).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_retries_exhausted() {
+        let t = tempfile::tempdir().unwrap();
+        let (url, requests) = mock_server(usize::MAX, "502 Bad Gateway").await;
+        let symbolizer = Symbolizer::new(supplier(url, t.path(), 1));
+
+        let debug_id = DebugId::from_str("abcd1234-abcd-1234-abcd-abcd12345678-a").unwrap();
+        assert_eq!(
+            symbolizer
+                .get_symbol_at_address("foo.pdb", debug_id, 0x1010)
+                .await,
+            None
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        // The symbols might exist, we just couldn't get them
+        let stats = symbolizer.stats();
+        let stats = stats.values().next().unwrap();
+        assert!(!stats.loaded_symbols);
+        assert!(stats.load_error.as_ref().unwrap().contains("502"));
+    }
+
+    #[tokio::test]
+    async fn test_not_found_is_not_retried() {
+        let t = tempfile::tempdir().unwrap();
+        let (url, requests) = mock_server(usize::MAX, "404 Not Found").await;
+        let supplier = supplier(url, t.path(), 2);
+
+        assert_eq!(
+            supplier.locate_symbols(&module()).await.unwrap_err(),
+            SymbolError::NotFound
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_total_timeout_covers_download() {
+        // A server that sends the start of a symbol file and then stalls.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0; 1024];
+                let _ = socket.read(&mut buf).await.unwrap();
+                let response = "HTTP/1.1 200 OK\r\nContent-Length: 1000\r\n\r\nMODULE Linux";
+                socket.write_all(response.as_bytes()).await.unwrap();
+                sockets.push(socket);
+            }
+        });
+
+        let t = tempfile::tempdir().unwrap();
+        let options = HttpOptions {
+            total_timeout: Some(Duration::from_millis(200)),
+            ..test_options()
+        };
+        let supplier = supplier_with_options(url, t.path(), options);
+        let result =
+            tokio::time::timeout(Duration::from_secs(5), supplier.locate_symbols(&module()))
+                .await
+                .expect("the total timeout should stop the stalled download");
+        match result {
+            Err(SymbolError::LoadError(e)) => assert_eq!(e.kind(), io::ErrorKind::TimedOut),
+            _ => panic!("expected a timeout"),
+        }
+    }
+
+    /// A server that only serves symbols to requests with `Authorization: Bearer secret`.
+    async fn auth_mock_server() -> String {
+        mock_server_with(|request| {
+            let authorized = request
+                .lines()
+                .any(|line| line.eq_ignore_ascii_case("authorization: Bearer secret"));
+            (!authorized).then_some("401 Unauthorized")
+        })
+        .await
+    }
+
+    fn auth_header(url_prefix: Option<String>) -> SymbolServerHeader {
+        SymbolServerHeader {
+            url_prefix,
+            name: String::from("Authorization"),
+            value: String::from("Bearer secret"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_headers() {
+        let t = tempfile::tempdir().unwrap();
+        let url = auth_mock_server().await;
+        let options = HttpOptions {
+            headers: vec![auth_header(None)],
+            ..test_options()
+        };
+        let supplier = supplier_with_options(url, t.path(), options);
+
+        let result = supplier.locate_symbols(&module()).await.unwrap();
+        assert!(result.symbols.functions.get(0x1010).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_headers_missing() {
+        let t = tempfile::tempdir().unwrap();
+        let url = auth_mock_server().await;
+        let supplier = supplier(url, t.path(), 0);
+
+        assert_eq!(
+            supplier.locate_symbols(&module()).await.unwrap_err(),
+            SymbolError::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_headers_for_other_server() {
+        let t = tempfile::tempdir().unwrap();
+        let url = auth_mock_server().await;
+        let options = HttpOptions {
+            headers: vec![auth_header(Some(String::from("https://example.com/")))],
+            ..test_options()
+        };
+        let supplier = supplier_with_options(url, t.path(), options);
+
+        assert_eq!(
+            supplier.locate_symbols(&module()).await.unwrap_err(),
+            SymbolError::NotFound
+        );
+    }
+
+    #[test]
+    fn test_headers_redacted() {
+        let options = HttpOptions {
+            headers: vec![auth_header(None)],
+            ..HttpOptions::default()
+        };
+        let debug = format!("{options:?}");
+        assert!(debug.contains("Authorization"), "{}", debug);
+        assert!(!debug.contains("secret"), "{}", debug);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_proxy() {
+        let t = tempfile::tempdir().unwrap();
+        let options = HttpOptions {
+            proxy: Some("http://[::1".to_owned()),
+            ..test_options()
+        };
+        let result = HttpSymbolSupplier::with_options(
+            vec![],
+            t.path().join("cache"),
+            t.path().to_owned(),
+            vec![],
+            options,
+        );
+        let e = result.err().unwrap();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert!(e.to_string().contains("http://[::1"), "{}", e);
+    }
+
+    #[tokio::test]
+    async fn test_url_signer() {
+        let t = tempfile::tempdir().unwrap();
+        let url = mock_server_with(|request| {
+            let signed = request.lines().next().unwrap().contains("&signature=abc ");
+            (!signed).then_some("403 Forbidden")
+        })
+        .await;
+        let signer = |url: &Url| {
+            let mut url = url.clone();
+            url.query_pairs_mut().append_pair("signature", "abc");
+            Ok(url)
+        };
+        let options = HttpOptions {
+            url_signer: Some(Arc::new(signer)),
+            ..test_options()
+        };
+        let supplier = supplier_with_options(url, t.path(), options);
+
+        let result = supplier.locate_symbols(&module()).await.unwrap();
+        // The signature is nobody else's business.
+        let symbols_url = result.symbols.url.unwrap();
+        assert!(!symbols_url.contains("signature"), "{}", symbols_url);
+    }
+
+    #[tokio::test]
+    async fn test_url_signer_failure() {
+        let t = tempfile::tempdir().unwrap();
+        let (url, requests) = mock_server(0, "").await;
+        let signer = |_: &Url| Err(io::Error::other("no credentials"));
+        let options = HttpOptions {
+            url_signer: Some(Arc::new(signer)),
+            ..test_options()
+        };
+        let supplier = supplier_with_options(url, t.path(), options);
+
+        match supplier.locate_symbols(&module()).await.unwrap_err() {
+            SymbolError::LoadError(e) => {
+                assert!(e.to_string().contains("no credentials"), "{}", e)
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+    }
 }
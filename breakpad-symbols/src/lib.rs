@@ -41,22 +41,26 @@ use async_trait::async_trait;
 use cachemap2::CacheMap;
 use debugid::{CodeId, DebugId};
 use futures_util::lock::Mutex as FutMutex;
-use tracing::trace;
+use tracing::{debug, trace, trace_span, warn, Instrument};
 
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use std::{borrow::Cow, sync::Arc};
 
 pub use minidump_common::{traits::Module, utils::basename};
 pub use sym_file::walker;
 
-pub use crate::sym_file::{CfiRules, SymbolFile};
+pub use crate::sym_file::{CfiRules, SymbolFile, SymbolLimits};
+pub use crate::symbol_cache::{SymbolCache, SymbolCacheStats};
+pub use crate::vcs::{Vcs, VcsSourceFile};
 
 #[cfg(feature = "http")]
 pub mod http;
 mod sym_file;
+mod symbol_cache;
+mod vcs;
 
 #[cfg(feature = "http")]
 pub use http::*;
@@ -65,7 +69,9 @@ pub use http::*;
 #[doc(hidden)]
 #[cfg(feature = "fuzz")]
 pub mod fuzzing_private_exports {
-    pub use crate::sym_file::walker::{eval_win_expr_for_fuzzer, walk_with_stack_cfi};
+    pub use crate::sym_file::walker::{
+        eval_win_expr_for_fuzzer, walk_with_stack_cfi, walk_with_stack_cfi_with_limits, ExprLimits,
+    };
     pub use crate::sym_file::{StackInfoWin, WinStackThing};
 }
 
@@ -78,8 +84,22 @@ pub struct SymbolStats {
     pub loaded_symbols: bool,
     /// If we tried to parse the symbols, but failed.
     pub corrupt_symbols: bool,
+    /// If we tried to load the symbols, but failed for a reason other than them
+    /// not existing (e.g. the symbol server was down), this is why.
+    pub load_error: Option<String>,
     /// If the module's debug info had to be looked up, this is the debug info used.
     pub extra_debug_info: Option<DebugInfoResult>,
+    /// If the loaded symbols are for a different build of the module: the debug id
+    /// in the symbol file's MODULE line doesn't match the module's debug id.
+    pub debug_id_mismatch: bool,
+    /// If `debug_id_mismatch`, this is the debug id the symbol file was for.
+    pub symbols_debug_id: Option<String>,
+    /// The relative paths the symbols were looked for at, in the order they
+    /// were tried (see [`breakpad_sym_lookups`]).
+    pub attempted_paths: Vec<String>,
+    /// If the symbols were found, the name of what they were found by (see
+    /// [`Symbolizer::name`]), to tell apart several sources of symbols.
+    pub provider: Option<String>,
 }
 
 /// Statistics on pending symbols.
@@ -176,6 +196,23 @@ fn leafname(path: &str) -> &str {
         .unwrap_or(path)
 }
 
+/// Make `name` safe to use as one component of a relative path.
+///
+/// Module names come from the minidump, so they can contain characters that
+/// aren't allowed in file names on some platforms (or in urls), or be `.`/`..`.
+/// Those are replaced with `_`.
+fn sanitize_path_component(name: &str) -> Cow<'_, str> {
+    let is_unsafe =
+        |c: char| c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*');
+    if name == "." || name == ".." {
+        Cow::Owned(name.replace('.', "_"))
+    } else if name.contains(is_unsafe) {
+        Cow::Owned(name.replace(is_unsafe, "_"))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
 /// If `filename` ends with `match_extension`, remove it. Append `new_extension` to the result.
 fn replace_or_add_extension(filename: &str, match_extension: &str, new_extension: &str) -> String {
     let mut bits = filename.split('.').collect::<Vec<_>>();
@@ -231,6 +268,46 @@ pub fn breakpad_sym_lookup(module: &(dyn Module + Sync)) -> Option<FileLookup> {
     })
 }
 
+/// Like [`breakpad_sym_lookup`], but with a debug file name that's safe to use
+/// in a path, and nothing if there isn't one.
+fn sanitized_breakpad_sym_lookup(module: &(dyn Module + Sync)) -> Option<FileLookup> {
+    let debug_file = module.debug_file()?;
+    let debug_id = module.debug_identifier()?;
+
+    let leaf = sanitize_path_component(leafname(&debug_file));
+    if leaf.is_empty() {
+        return None;
+    }
+    let filename = replace_or_add_extension(&leaf, "pdb", "sym");
+    let rel_path = [&leaf[..], &debug_id.breakpad().to_string(), &filename[..]].join("/");
+    Some(FileLookup {
+        cache_rel: rel_path.clone(),
+        server_rel: rel_path,
+        debug_id: debug_id.breakpad().to_string(),
+        debug_file: filename,
+    })
+}
+
+/// All the relative symbol paths at which symbols for `module` may be found,
+/// in the order they should be tried.
+///
+/// That's the path from the debug info (as [`breakpad_sym_lookup`] makes it)
+/// if there is any, followed by the path from the code info
+/// ([`code_info_breakpad_sym_file_lookup`]). Module names that aren't safe to
+/// use in a path are sanitized in both.
+pub fn breakpad_sym_lookups(module: &(dyn Module + Sync)) -> Vec<FileLookup> {
+    let mut lookups: Vec<FileLookup> = sanitized_breakpad_sym_lookup(module).into_iter().collect();
+    if let Some(code_lookup) = code_info_breakpad_sym_file_lookup(module) {
+        if lookups
+            .iter()
+            .all(|lookup| lookup.cache_rel != code_lookup.cache_rel)
+        {
+            lookups.push(code_lookup);
+        }
+    }
+    lookups
+}
+
 /// Get a relative symbol path at which to locate symbols for `module` using
 /// the code file and code identifier. This is helpful for Microsoft modules
 /// where we don't have a valid debug filename and debug id to retrieve the
@@ -261,6 +338,35 @@ pub fn code_info_breakpad_sym_lookup(module: &(dyn Module + Sync)) -> Option<Str
     Some(rel_path)
 }
 
+/// Like [`code_info_breakpad_sym_lookup`], but a complete lookup, with a code
+/// file name that's safe to use in a path.
+///
+/// ELF modules from stripped builds often have no debug file or debug
+/// identifier, but they still have a build id, and Mozilla's symbol server
+/// stores their symbols at this path too. The lookup's `debug_id` is still the
+/// module's debug identifier (if any), since that's what the symbols are for.
+pub fn code_info_breakpad_sym_file_lookup(module: &(dyn Module + Sync)) -> Option<FileLookup> {
+    let code_file = module.code_file();
+    let code_id = module.code_identifier()?;
+
+    let leaf = sanitize_path_component(leafname(&code_file));
+    let code_id = code_id.as_str().to_uppercase();
+    if leaf.is_empty() || code_id.is_empty() {
+        return None;
+    }
+    let filename = replace_or_add_extension(&leaf, "dll", "sym");
+    let rel_path = [&leaf[..], &code_id[..], &filename[..]].join("/");
+    Some(FileLookup {
+        cache_rel: rel_path.clone(),
+        server_rel: rel_path,
+        debug_id: module
+            .debug_identifier()
+            .map(|debug_id| debug_id.breakpad().to_string())
+            .unwrap_or_default(),
+        debug_file: filename,
+    })
+}
+
 /// Returns a lookup for this module's extra debuginfo (pdb)
 pub fn extra_debuginfo_lookup(module: &(dyn Module + Sync)) -> Option<FileLookup> {
     let debug_file = module.debug_file()?;
@@ -314,6 +420,14 @@ pub fn lookup(module: &(dyn Module + Sync), file_kind: FileKind) -> Option<FileL
     }
 }
 
+/// Like [`lookup`], but with every lookup worth trying for `file_kind`, in order.
+fn lookups(module: &(dyn Module + Sync), file_kind: FileKind) -> Vec<FileLookup> {
+    match file_kind {
+        FileKind::BreakpadSym => breakpad_sym_lookups(module),
+        _ => lookup(module, file_kind).into_iter().collect(),
+    }
+}
+
 /// Possible results of locating symbols for a module.
 ///
 /// Because symbols may be found from different sources, symbol providers
@@ -382,6 +496,38 @@ pub struct FillSymbolError {
     // (this is used as a heuristic for stack scanning).
 }
 
+/// Why walk_frame couldn't use CFI to find the caller of a frame.
+///
+/// The stack walker falls back to frame pointers and stack scanning when this
+/// happens, and records it on the caller's frame to explain why.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WalkFrameError {
+    /// No CFI covers the instruction (possibly because there are no symbols
+    /// for the module at all).
+    #[error("no cfi covers the instruction")]
+    NoCfi,
+    /// There is CFI for the instruction, but it needs the value of a register
+    /// which the callee doesn't have.
+    #[error("callee register {0} is unavailable")]
+    MissingRegister(String),
+    /// There is CFI for the instruction, but it is malformed, reads memory
+    /// that isn't in the minidump, or is too complex to evaluate.
+    #[error("cfi evaluation failed")]
+    EvalFailed,
+}
+
+impl WalkFrameError {
+    /// A short, stable name for the kind of error (`no_cfi`, `missing_register`,
+    /// or `eval_failed`), as reported in minidump-processor's JSON output.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            WalkFrameError::NoCfi => "no_cfi",
+            WalkFrameError::MissingRegister(_) => "missing_register",
+            WalkFrameError::EvalFailed => "eval_failed",
+        }
+    }
+}
+
 impl PartialEq for SymbolError {
     fn eq(&self, other: &SymbolError) -> bool {
         matches!(
@@ -408,6 +554,22 @@ pub struct LocateSymbolsResult {
     pub extra_debug_info: Option<DebugInfoResult>,
 }
 
+/// Check the debug id in the MODULE line of `symbols` against `debug_id`.
+///
+/// Returns the debug id of the symbols if they don't match. Symbols without a
+/// MODULE line can't be checked, and are assumed to match.
+fn debug_id_mismatch(debug_id: Option<DebugId>, symbols: &SymbolFile) -> Option<String> {
+    let debug_id = debug_id?;
+    if symbols.module_id.is_empty()
+        || symbols
+            .module_id
+            .eq_ignore_ascii_case(&debug_id.breakpad().to_string())
+    {
+        return None;
+    }
+    Some(symbols.module_id.clone())
+}
+
 /// A trait for things that can locate symbols for a given module.
 #[async_trait]
 pub trait SymbolSupplier {
@@ -429,21 +591,89 @@ pub trait SymbolSupplier {
         module: &(dyn Module + Sync),
         file_kind: FileKind,
     ) -> Result<PathBuf, FileError>;
+
+    /// A name for where this supplier gets symbols from, e.g. the symbol server's url.
+    ///
+    /// This ends up in [`SymbolStats::provider`]. The default is the type's name.
+    fn name(&self) -> String {
+        std::any::type_name::<Self>().to_owned()
+    }
 }
 
 /// An implementation of `SymbolSupplier` that loads Breakpad text-format symbols from local disk
 /// paths.
 ///
-/// See [`breakpad_sym_lookup`] for details on how paths are searched.
+/// See [`breakpad_sym_lookups`] for details on how paths are searched.
 pub struct SimpleSymbolSupplier {
     /// Local disk paths in which to search for symbols.
     paths: Vec<PathBuf>,
+    /// Whether symbol files for a different debug id are treated as not found.
+    reject_debug_id_mismatch: bool,
+    /// The limits enforced on the symbol files.
+    limits: SymbolLimits,
 }
 
 impl SimpleSymbolSupplier {
     /// Instantiate a new `SimpleSymbolSupplier` that will search in `paths`.
     pub fn new(paths: Vec<PathBuf>) -> SimpleSymbolSupplier {
-        SimpleSymbolSupplier { paths }
+        SimpleSymbolSupplier {
+            paths,
+            reject_debug_id_mismatch: false,
+            limits: SymbolLimits::default(),
+        }
+    }
+
+    /// Enforce `limits` on the symbol files, instead of the default ones.
+    pub fn limits(mut self, limits: SymbolLimits) -> SimpleSymbolSupplier {
+        self.limits = limits;
+        self
+    }
+
+    fn parse_file(&self, path: &Path) -> Result<SymbolFile, SymbolError> {
+        let file = fs::File::open(path)?;
+        SymbolFile::parse_with_limits(file, &self.limits, |_| ())
+    }
+
+    /// Treat a symbol file whose MODULE line has a different debug id than the
+    /// module as [`SymbolError::NotFound`], and keep looking in the remaining paths.
+    ///
+    /// By default such a file is used, and the mismatch is recorded in
+    /// [`SymbolStats::debug_id_mismatch`].
+    pub fn reject_debug_id_mismatch(mut self, reject: bool) -> SimpleSymbolSupplier {
+        self.reject_debug_id_mismatch = reject;
+        self
+    }
+
+    /// All the files in `paths` that match the lookup for `module`, in order.
+    fn candidate_files<'a>(
+        &'a self,
+        module: &(dyn Module + Sync),
+        file_kind: FileKind,
+    ) -> impl Iterator<Item = PathBuf> + 'a {
+        let lookups = lookups(module, file_kind);
+        if lookups.is_empty() {
+            trace!("SimpleSymbolSupplier could not build symbol_path");
+        }
+        lookups.into_iter().flat_map(move |lookup| {
+            self.paths.iter().filter_map(move |path| {
+                if path.is_file() && file_kind == FileKind::BreakpadSym {
+                    if let Ok(sf) = self.parse_file(path) {
+                        if sf.module_id == lookup.debug_id {
+                            trace!("SimpleSymbolSupplier found file {}", path.display());
+                            return Some(path.to_path_buf());
+                        }
+                    }
+                } else if path.is_dir() {
+                    let test_path = path.join(&lookup.cache_rel);
+                    if fs::metadata(&test_path).ok().map_or(false, |m| m.is_file()) {
+                        trace!("SimpleSymbolSupplier found file {}", test_path.display());
+                        return Some(test_path);
+                    }
+                    trace!("SimpleSymbolSupplier tried {}", test_path.display());
+                }
+                None
+            })
+        })
     }
 }
 
@@ -454,19 +684,28 @@ impl SymbolSupplier for SimpleSymbolSupplier {
         &self,
         module: &(dyn Module + Sync),
     ) -> Result<LocateSymbolsResult, SymbolError> {
-        let file_path = self
-            .locate_file(module, FileKind::BreakpadSym)
-            .await
-            .map_err(|_| SymbolError::NotFound)?;
-        let symbols = SymbolFile::from_file(&file_path).map_err(|e| {
-            trace!("SimpleSymbolSupplier failed: {}", e);
-            e
-        })?;
-        trace!("SimpleSymbolSupplier parsed file!");
-        Ok(LocateSymbolsResult {
-            symbols,
-            extra_debug_info: None,
-        })
+        for file_path in self.candidate_files(module, FileKind::BreakpadSym) {
+            let symbols = self.parse_file(&file_path).map_err(|e| {
+                trace!("SimpleSymbolSupplier failed: {}", e);
+                e
+            })?;
+            if self.reject_debug_id_mismatch {
+                if let Some(found) = debug_id_mismatch(module.debug_identifier(), &symbols) {
+                    debug!(
+                        "SimpleSymbolSupplier rejected {} (debug id {})",
+                        file_path.display(),
+                        found
+                    );
+                    continue;
+                }
+            }
+            trace!("SimpleSymbolSupplier parsed file!");
+            return Ok(LocateSymbolsResult {
+                symbols,
+                extra_debug_info: None,
+            });
+        }
+        Err(SymbolError::NotFound)
     }
 
     #[tracing::instrument(level = "trace", skip(self, module), fields(module = crate::basename(&module.code_file())))]
@@ -476,27 +715,18 @@ impl SymbolSupplier for SimpleSymbolSupplier {
         file_kind: FileKind,
     ) -> Result<PathBuf, FileError> {
         trace!("SimpleSymbolSupplier search");
-        if let Some(lookup) = lookup(module, file_kind) {
-            for path in self.paths.iter() {
-                if path.is_file() && file_kind == FileKind::BreakpadSym {
-                    if let Ok(sf) = SymbolFile::from_file(path) {
-                        if sf.module_id == lookup.debug_id {
-                            trace!("SimpleSymbolSupplier found file {}", path.display());
-                            return Ok(path.to_path_buf());
-                        }
-                    }
-                } else if path.is_dir() {
-                    let test_path = path.join(lookup.cache_rel.clone());
-                    if fs::metadata(&test_path).ok().map_or(false, |m| m.is_file()) {
-                        trace!("SimpleSymbolSupplier found file {}", test_path.display());
-                        return Ok(test_path);
-                    }
-                }
-            }
-        } else {
-            trace!("SimpleSymbolSupplier could not build symbol_path");
-        }
-        Err(FileError::NotFound)
+        self.candidate_files(module, file_kind)
+            .next()
+            .ok_or(FileError::NotFound)
+    }
+
+    fn name(&self) -> String {
+        let paths: Vec<String> = self
+            .paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        paths.join(", ")
     }
 }
 
@@ -507,6 +737,7 @@ impl SymbolSupplier for SimpleSymbolSupplier {
 pub struct StringSymbolSupplier {
     modules: HashMap<String, String>,
     code_info_to_debug_info: HashMap<String, DebugInfoResult>,
+    limits: SymbolLimits,
 }
 
 impl StringSymbolSupplier {
@@ -515,9 +746,16 @@ impl StringSymbolSupplier {
         Self {
             modules,
             code_info_to_debug_info: HashMap::new(),
+            limits: SymbolLimits::default(),
         }
     }
 
+    /// Enforce `limits` on the symbol files, instead of the default ones.
+    pub fn limits(mut self, limits: SymbolLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     /// Perform a code_file/code_identifier lookup for a specific symbol server.
     async fn lookup_debug_info_by_code_info(
         &self,
@@ -538,7 +776,7 @@ impl SymbolSupplier for StringSymbolSupplier {
         trace!("StringSymbolSupplier search");
         if let Some(symbols) = self.modules.get(&*module.code_file()) {
             trace!("StringSymbolSupplier found file");
-            let file = SymbolFile::from_bytes(symbols.as_bytes())?;
+            let file = SymbolFile::parse_with_limits(symbols.as_bytes(), &self.limits, |_| ())?;
             trace!("StringSymbolSupplier parsed file!");
             return Ok(LocateSymbolsResult {
                 symbols: file,
@@ -559,6 +797,27 @@ impl SymbolSupplier for StringSymbolSupplier {
     }
 }
 
+/// What kind of record the function of a frame was found in, see
+/// [`FrameSymbolizer::set_symbol_quality`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    /// A FUNC record, which covers the address.
+    Func,
+    /// A PUBLIC record before the address. PUBLIC records don't say where they
+    /// end, so the address may actually be in a later function without symbols.
+    Public,
+}
+
+impl SymbolKind {
+    /// The name of the kind, e.g. "func".
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymbolKind::Func => "func",
+            SymbolKind::Public => "public",
+        }
+    }
+}
+
 /// A trait for setting symbol information on something like a stack frame.
 pub trait FrameSymbolizer {
     /// Get the program counter value for this frame.
@@ -566,6 +825,12 @@ pub trait FrameSymbolizer {
     /// Set the name, base address, and parameter size of the function in
     /// which this frame is executing.
     fn set_function(&mut self, name: &str, base: u64, parameter_size: u32);
+    /// Set the parameter size of a frame that isn't in any known function,
+    /// but whose stack frame is still described (e.g. by a STACK WIN record).
+    fn set_parameter_size(&mut self, _parameter_size: u32) {}
+    /// Set what kind of record the function set with `set_function` came
+    /// from, and how far the instruction is past its start.
+    fn set_symbol_quality(&mut self, _kind: SymbolKind, _offset_from_symbol: u64) {}
     /// Set the source file and (1-based) line number this frame represents.
     fn set_source_file(&mut self, file: &str, line: u32, base: u64);
     /// Add an inline frame. This method can be called multiple times, in the
@@ -614,6 +879,10 @@ pub struct SimpleFrame {
     pub source_line: Option<u32>,
     /// The offset of the start of `source_line` from the function base.
     pub source_line_base: Option<u64>,
+    /// What kind of record `function` came from.
+    pub symbol_kind: Option<SymbolKind>,
+    /// How far `instruction` is past the start of `function`.
+    pub offset_from_symbol: Option<u64>,
 }
 
 impl SimpleFrame {
@@ -635,6 +904,13 @@ impl FrameSymbolizer for SimpleFrame {
         self.function_base = Some(base);
         self.parameter_size = Some(parameter_size);
     }
+    fn set_parameter_size(&mut self, parameter_size: u32) {
+        self.parameter_size = Some(parameter_size);
+    }
+    fn set_symbol_quality(&mut self, kind: SymbolKind, offset_from_symbol: u64) {
+        self.symbol_kind = Some(kind);
+        self.offset_from_symbol = Some(offset_from_symbol);
+    }
     fn set_source_file(&mut self, file: &str, line: u32, base: u64) {
         self.source_file = Some(String::from(file));
         self.source_line = Some(line);
@@ -690,10 +966,19 @@ impl<T, E> CachedAsyncResult<T, E> {
     where
         F: FnOnce() -> Fut + 'a,
         Fut: std::future::Future<Output = Result<T, E>> + 'a,
+    {
+        self.get_shared(|| async { Arc::new(f().await) }).await
+    }
+
+    /// `get`, but `f` may return a result that's already shared.
+    pub async fn get_shared<'a, F, Fut>(&self, f: F) -> Arc<Result<T, E>>
+    where
+        F: FnOnce() -> Fut + 'a,
+        Fut: std::future::Future<Output = Arc<Result<T, E>>> + 'a,
     {
         let mut guard = self.inner.lock().await;
         if guard.is_none() {
-            *guard = Some(Arc::new(f().await));
+            *guard = Some(f().await);
         }
         guard.as_ref().unwrap().clone()
     }
@@ -729,6 +1014,10 @@ pub struct Symbolizer {
     symbols: CacheMap<ModuleKey, CachedAsyncResult<SymbolFile, SymbolError>>,
     pending_stats: Mutex<PendingSymbolStats>,
     stats: Mutex<HashMap<String, SymbolStats>>,
+    /// Overrides the supplier's name, see [`Symbolizer::with_name`].
+    name: Option<String>,
+    /// Symbols shared with other `Symbolizer`s, see [`Symbolizer::with_cache`].
+    cache: Option<Arc<SymbolCache>>,
 }
 
 impl Symbolizer {
@@ -739,6 +1028,30 @@ impl Symbolizer {
             symbols: CacheMap::default(),
             pending_stats: Mutex::default(),
             stats: Mutex::default(),
+            name: None,
+            cache: None,
+        }
+    }
+
+    /// Use `name` instead of the supplier's [name][SymbolSupplier::name] in
+    /// [`SymbolStats::provider`].
+    pub fn with_name(mut self, name: impl Into<String>) -> Symbolizer {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Share the symbols this `Symbolizer` loads with the others using `cache`,
+    /// and reuse theirs, instead of loading every module's symbols again.
+    pub fn with_cache(mut self, cache: Arc<SymbolCache>) -> Symbolizer {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// The name of where the symbols come from, e.g. the symbol server's url.
+    pub fn name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => self.supplier.name(),
         }
     }
 
@@ -832,14 +1145,37 @@ impl Symbolizer {
         module: &(dyn Module + Sync),
         walker: &mut (dyn FrameWalker + Send),
     ) -> Option<()> {
+        self.try_walk_frame(module, walker).await.ok()
+    }
+
+    /// `walk_frame`, but says why the frame couldn't be walked.
+    pub async fn try_walk_frame(
+        &self,
+        module: &(dyn Module + Sync),
+        walker: &mut (dyn FrameWalker + Send),
+    ) -> Result<(), WalkFrameError> {
         let cached_sym = self.get_symbols(module).await;
         let sym = cached_sym.as_ref();
         if let Ok(sym) = sym {
             trace!("found symbols for address, searching for cfi entries");
-            sym.walk_frame(module, walker)
+            let mut limits = walker::ExprLimits::new(&sym.limits);
+            let result = sym.walk_frame_with_limits(module, walker, &mut limits);
+            if limits.exceeded() {
+                let key = leafname(module.code_file().as_ref()).to_string();
+                if let Some(stats) = self.stats.lock().unwrap().get_mut(&key) {
+                    if !stats.corrupt_symbols {
+                        warn!(
+                            "symbols for {} have an expression that exceeds the evaluation limits",
+                            module.code_file()
+                        );
+                        stats.corrupt_symbols = true;
+                    }
+                }
+            }
+            result
         } else {
             trace!("couldn't find symbols for address, cannot use cfi");
-            None
+            Err(WalkFrameError::NoCfi)
         }
     }
 
@@ -850,40 +1186,101 @@ impl Symbolizer {
         &self,
         module: &(dyn Module + Sync),
     ) -> Arc<Result<SymbolFile, SymbolError>> {
+        let key = module_key(module);
         self.symbols
-            .cache_default(module_key(module))
-            .get(|| async {
-                trace!("locating symbols for module {}", module.code_file());
-                self.pending_stats.lock().unwrap().symbols_requested += 1;
-                let result = self.supplier.locate_symbols(module).await;
-                self.pending_stats.lock().unwrap().symbols_processed += 1;
-
-                let mut stats = SymbolStats::default();
-                match &result {
-                    Ok(res) => {
-                        stats.symbol_url.clone_from(&res.symbols.url);
-                        stats.loaded_symbols = true;
-                        stats.corrupt_symbols = false;
-                        stats.extra_debug_info.clone_from(&res.extra_debug_info);
+            .cache_default(key.clone())
+            .get_shared(|| {
+                let span = trace_span!(
+                    "load_symbols",
+                    module = crate::basename(&module.code_file()),
+                    debug_id = %module.debug_identifier().unwrap_or_default().breakpad(),
+                );
+                async {
+                    trace!("locating symbols for module {}", module.code_file());
+                    self.pending_stats.lock().unwrap().symbols_requested += 1;
+                    if let Some((symbols, stats)) = self.cache.as_ref().and_then(|c| c.get(&key)) {
+                        trace!(
+                            "found symbols for module {} in the cache",
+                            module.code_file()
+                        );
+                        self.pending_stats.lock().unwrap().symbols_processed += 1;
+                        let leaf = leafname(module.code_file().as_ref()).to_string();
+                        self.stats.lock().unwrap().insert(leaf, stats);
+                        return symbols;
                     }
-                    Err(SymbolError::NotFound) => {
-                        stats.loaded_symbols = false;
+                    let result = self.supplier.locate_symbols(module).await;
+                    self.pending_stats.lock().unwrap().symbols_processed += 1;
+
+                    let mut stats = SymbolStats::default();
+                    let extra_debug_info = result
+                        .as_ref()
+                        .ok()
+                        .and_then(|res| res.extra_debug_info.as_ref());
+                    stats.attempted_paths = match extra_debug_info {
+                        // The lookups were done with the debug info we were given
+                        Some(info) => breakpad_sym_lookups(&SimpleModule::from_basic_info(
+                            Some(info.debug_file.clone()),
+                            Some(info.debug_identifier),
+                            Some(module.code_file().into_owned()),
+                            module.code_identifier(),
+                        )),
+                        None => breakpad_sym_lookups(module),
                     }
-                    Err(SymbolError::MissingDebugFileOrId) => {
-                        stats.loaded_symbols = false;
-                    }
-                    Err(SymbolError::LoadError(_)) => {
-                        stats.loaded_symbols = false;
+                    .into_iter()
+                    .map(|lookup| lookup.cache_rel)
+                    .collect();
+                    match &result {
+                        Ok(res) => {
+                            stats.symbol_url.clone_from(&res.symbols.url);
+                            stats.provider = Some(self.name());
+                            stats.loaded_symbols = true;
+                            stats.corrupt_symbols = res.symbols.truncated;
+                            stats.extra_debug_info.clone_from(&res.extra_debug_info);
+                            let debug_id = match &res.extra_debug_info {
+                                Some(info) => Some(info.debug_identifier),
+                                None => module.debug_identifier(),
+                            };
+                            stats.symbols_debug_id = debug_id_mismatch(debug_id, &res.symbols);
+                            if let Some(found) = &stats.symbols_debug_id {
+                                warn!(
+                                    "symbols for {} are for debug id {}, not {}",
+                                    module.code_file(),
+                                    found,
+                                    debug_id.unwrap_or_default().breakpad()
+                                );
+                                stats.debug_id_mismatch = true;
+                            }
+                        }
+                        Err(SymbolError::NotFound) => {
+                            debug!(
+                                "no symbols found for {} (tried {})",
+                                module.code_file(),
+                                stats.attempted_paths.join(", ")
+                            );
+                            stats.loaded_symbols = false;
+                        }
+                        Err(SymbolError::MissingDebugFileOrId) => {
+                            stats.loaded_symbols = false;
+                        }
+                        Err(SymbolError::LoadError(e)) => {
+                            stats.loaded_symbols = false;
+                            stats.load_error = Some(e.to_string());
+                        }
+                        Err(SymbolError::ParseError(..)) => {
+                            stats.loaded_symbols = true;
+                            stats.corrupt_symbols = true;
+                        }
                     }
-                    Err(SymbolError::ParseError(..)) => {
-                        stats.loaded_symbols = true;
-                        stats.corrupt_symbols = true;
+                    let symbols = Arc::new(result.map(|r| r.symbols));
+                    if let Some(cache) = &self.cache {
+                        cache.insert(key.clone(), symbols.clone(), stats.clone());
                     }
-                }
-                let key = leafname(module.code_file().as_ref()).to_string();
-                self.stats.lock().unwrap().insert(key, stats);
+                    let leaf = leafname(module.code_file().as_ref()).to_string();
+                    self.stats.lock().unwrap().insert(leaf, stats);
 
-                result.map(|r| r.symbols)
+                    symbols
+                }
+                .instrument(span)
             })
             .await
     }
@@ -1037,6 +1434,149 @@ mod test {
         assert!(code_info_breakpad_sym_lookup(&bad3).is_none());
     }
 
+    #[tokio::test]
+    async fn test_code_info_breakpad_sym_file_lookup() {
+        let m = SimpleModule {
+            code_file: Some("foo.dll".to_string()),
+            code_identifier: Some(CodeId::from_str("64E782C570C4000").unwrap()),
+            ..SimpleModule::default()
+        };
+        let lookup = code_info_breakpad_sym_file_lookup(&m).unwrap();
+        assert_eq!(lookup.cache_rel, "foo.dll/64E782C570C4000/foo.sym");
+        assert_eq!(
+            lookup.server_rel,
+            code_info_breakpad_sym_lookup(&m).unwrap()
+        );
+        // The debug id of the lookup is still the module's debug id
+        assert_eq!(lookup.debug_id, "");
+        let debug_id = DebugId::from_str("abcd1234-abcd-1234-abcd-abcd12345678-a").unwrap();
+        let m = SimpleModule {
+            debug_id: Some(debug_id),
+            ..m
+        };
+        assert_eq!(
+            code_info_breakpad_sym_file_lookup(&m).unwrap().debug_id,
+            "ABCD1234ABCD1234ABCDABCD12345678a"
+        );
+
+        let bad = SimpleModule {
+            code_file: Some("foo".to_string()),
+            ..SimpleModule::default()
+        };
+        assert!(code_info_breakpad_sym_file_lookup(&bad).is_none());
+
+        let bad2 = SimpleModule {
+            code_identifier: Some(CodeId::from_str("64E782C570C4000").unwrap()),
+            ..SimpleModule::default()
+        };
+        assert!(code_info_breakpad_sym_file_lookup(&bad2).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_breakpad_sym_lookups() {
+        let debug_id = DebugId::from_str("abcd1234-abcd-1234-abcd-abcd12345678-a").unwrap();
+        let code_id = CodeId::from_str("b7dc60e91588d8a54c4c44205044ba03").unwrap();
+        let paths = |m: &SimpleModule| {
+            breakpad_sym_lookups(m)
+                .into_iter()
+                .map(|lookup| lookup.cache_rel)
+                .collect::<Vec<_>>()
+        };
+
+        // Debug info present: try it first, then the code info
+        let m = SimpleModule::from_basic_info(
+            Some("libfoo.so".to_string()),
+            Some(debug_id),
+            Some("/system/lib/libfoo.so".to_string()),
+            Some(code_id.clone()),
+        );
+        assert_eq!(
+            paths(&m),
+            [
+                "libfoo.so/ABCD1234ABCD1234ABCDABCD12345678a/libfoo.so.sym",
+                "libfoo.so/B7DC60E91588D8A54C4C44205044BA03/libfoo.so.sym",
+            ]
+        );
+
+        // A nil debug id still makes a path, with the code info after it
+        let m = SimpleModule::from_basic_info(
+            Some("libfoo.so".to_string()),
+            Some(DebugId::nil()),
+            Some("/system/lib/libfoo.so".to_string()),
+            Some(code_id.clone()),
+        );
+        assert_eq!(
+            &breakpad_sym_lookup(&m).unwrap().cache_rel,
+            "libfoo.so/000000000000000000000000000000000/libfoo.so.sym"
+        );
+        assert_eq!(
+            paths(&m),
+            [
+                "libfoo.so/000000000000000000000000000000000/libfoo.so.sym",
+                "libfoo.so/B7DC60E91588D8A54C4C44205044BA03/libfoo.so.sym",
+            ]
+        );
+
+        // Only the code id present (as in stripped Android builds)
+        let m = SimpleModule::from_basic_info(
+            Some(String::new()),
+            None,
+            Some("/system/lib/libfoo.so".to_string()),
+            Some(code_id.clone()),
+        );
+        assert_eq!(
+            paths(&m),
+            ["libfoo.so/B7DC60E91588D8A54C4C44205044BA03/libfoo.so.sym"]
+        );
+
+        // Neither present
+        let m = SimpleModule::from_basic_info(
+            Some(String::new()),
+            None,
+            Some("/system/lib/libfoo.so".to_string()),
+            None,
+        );
+        assert!(paths(&m).is_empty());
+        assert!(paths(&SimpleModule::default()).is_empty());
+
+        // Names that aren't safe as file names
+        let m = SimpleModule::from_basic_info(
+            Some("c:\\build\\foo<1>?.pdb".to_string()),
+            Some(debug_id),
+            Some("/tmp/..".to_string()),
+            Some(code_id),
+        );
+        assert_eq!(
+            paths(&m),
+            [
+                "foo_1__.pdb/ABCD1234ABCD1234ABCDABCD12345678a/foo_1__.sym",
+                "__/B7DC60E91588D8A54C4C44205044BA03/__.sym",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_simple_symbol_supplier_code_info() {
+        let t = tempfile::tempdir().unwrap();
+        let paths = mksubdirs(t.path(), &["one"]);
+        let supplier = SimpleSymbolSupplier::new(paths.clone());
+
+        let m = SimpleModule::from_basic_info(
+            None,
+            None,
+            Some("/system/lib/libfoo.so".to_string()),
+            Some(CodeId::from_str("b7dc60e91588d8a54c4c44205044ba03").unwrap()),
+        );
+        assert_eq!(
+            supplier.locate_symbols(&m).await,
+            Err(SymbolError::NotFound)
+        );
+        write_good_symbol_file(
+            &paths[0].join("libfoo.so/B7DC60E91588D8A54C4C44205044BA03/libfoo.so.sym"),
+        );
+        assert!(supplier.locate_symbols(&m).await.is_ok());
+    }
+
     fn mksubdirs(path: &Path, dirs: &[&str]) -> Vec<PathBuf> {
         dirs.iter()
             .map(|dir| {
@@ -1190,6 +1730,66 @@ FUNC 1000 30 10 another func
             .is_none());
     }
 
+    #[test]
+    fn test_symbolizer_is_send_sync() {
+        // Symbolizers are shared by minidumps that are processed in parallel.
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Symbolizer>();
+    }
+
+    #[tokio::test]
+    async fn test_debug_id_mismatch() {
+        let t = tempfile::tempdir().unwrap();
+        let paths = mksubdirs(t.path(), &["stale", "good"]);
+        let debug_id = DebugId::from_str("abcd1234-abcd-1234-abcd-abcd12345678-a").unwrap();
+        let module = SimpleModule::from_basic_info(
+            Some(String::from("foo.pdb")),
+            Some(debug_id),
+            Some(String::from("foo.pdb")),
+            None,
+        );
+        let sym = "foo.pdb/ABCD1234ABCD1234ABCDABCD12345678a/foo.sym";
+        write_symbol_file(
+            &paths[0].join(sym),
+            b"MODULE Linux x86 FFFF0000000000000000ABCD12345678a foo\n",
+        );
+        write_symbol_file(
+            &paths[1].join(sym),
+            b"MODULE Linux x86 abcd1234abcd1234abcdabcd12345678a foo\n",
+        );
+
+        // By default the stale symbols are used, but the mismatch is recorded.
+        let symbolizer = Symbolizer::new(SimpleSymbolSupplier::new(paths.clone()));
+        let mut frame = SimpleFrame::with_instruction(0x1010);
+        let _ = symbolizer.fill_symbol(&module, &mut frame).await;
+        let stats = symbolizer.stats();
+        let stats = stats.get("foo.pdb").unwrap();
+        assert!(stats.loaded_symbols);
+        assert!(stats.debug_id_mismatch);
+        assert_eq!(
+            stats.symbols_debug_id.as_deref(),
+            Some("FFFF0000000000000000ABCD12345678a")
+        );
+
+        // Rejecting the mismatch moves on to the next path (the ids are
+        // compared case-insensitively).
+        let supplier = SimpleSymbolSupplier::new(paths.clone()).reject_debug_id_mismatch(true);
+        let symbolizer = Symbolizer::new(supplier);
+        let _ = symbolizer.fill_symbol(&module, &mut frame).await;
+        let stats = symbolizer.stats();
+        let stats = stats.get("foo.pdb").unwrap();
+        assert!(stats.loaded_symbols);
+        assert!(!stats.debug_id_mismatch);
+        assert_eq!(stats.symbols_debug_id, None);
+
+        let supplier =
+            SimpleSymbolSupplier::new(paths[..1].to_vec()).reject_debug_id_mismatch(true);
+        assert_eq!(
+            supplier.locate_symbols(&module).await,
+            Err(SymbolError::NotFound)
+        );
+    }
+
     #[tokio::test]
     async fn test_extra_debug_info() {
         let debug_info = DebugInfoResult {
@@ -1197,10 +1797,7 @@ FUNC 1000 30 10 another func
             debug_identifier: DebugId::from_str("abcd1234-abcd-1234-abcd-abcd12345678-a").unwrap(),
         };
 
-        let mut supplier = StringSymbolSupplier {
-            modules: HashMap::new(),
-            code_info_to_debug_info: HashMap::new(),
-        };
+        let mut supplier = StringSymbolSupplier::new(HashMap::new());
         supplier.modules.insert(
             String::from_str("foo.pdb").unwrap(),
             String::from_str(
@@ -1237,4 +1834,191 @@ FUNC 1000 30 10 some func
         let stats = sym_stats.get("foo.pdb").unwrap();
         assert_eq!(stats.extra_debug_info, Some(debug_info));
     }
+
+    #[tokio::test]
+    async fn test_symbol_limits() {
+        struct Walker;
+        impl FrameWalker for Walker {
+            fn get_instruction(&self) -> u64 {
+                0x1010
+            }
+            fn has_grand_callee(&self) -> bool {
+                false
+            }
+            fn get_grand_callee_parameter_size(&self) -> u32 {
+                0
+            }
+            fn get_register_at_address(&self, _address: u64) -> Option<u64> {
+                Some(0)
+            }
+            fn get_callee_register(&self, _name: &str) -> Option<u64> {
+                Some(0x100)
+            }
+            fn set_caller_register(&mut self, _name: &str, _val: u64) -> Option<()> {
+                Some(())
+            }
+            fn clear_caller_register(&mut self, _name: &str) {}
+            fn set_cfa(&mut self, _val: u64) -> Option<()> {
+                Some(())
+            }
+            fn set_ra(&mut self, _val: u64) -> Option<()> {
+                Some(())
+            }
+        }
+
+        let modules = [
+            (
+                "foo.pdb",
+                "MODULE Linux x86 ABCD1234ABCD1234ABCDABCD12345678a foo
+FUNC 1000 30 10 some func
+STACK CFI INIT 1000 30 .cfa: $rsp 8 + 8 + .ra: .cfa 8 - ^
+",
+            ),
+            (
+                "bar.pdb",
+                "MODULE Linux x86 ABCD1234ABCD1234ABCDABCD12345678a bar
+FUNC 1000 30 10 some func
+FUNC 2000 30 10 another func
+FUNC 3000 30 10 yet another func
+",
+            ),
+        ];
+        let limits = SymbolLimits {
+            max_records: 3,
+            max_expr_steps: 4,
+            ..SymbolLimits::default()
+        };
+        let supplier = StringSymbolSupplier::new(
+            modules
+                .iter()
+                .map(|&(name, sym)| (name.to_owned(), sym.to_owned()))
+                .collect(),
+        )
+        .limits(limits);
+        let symbolizer = Symbolizer::new(supplier);
+        let module =
+            |name: &str| SimpleModule::from_basic_info(None, None, Some(name.to_owned()), None);
+
+        // The .cfa expression is too long to evaluate
+        let foo = module("foo.pdb");
+        let mut frame = SimpleFrame::with_instruction(0x1010);
+        symbolizer.fill_symbol(&foo, &mut frame).await.unwrap();
+        assert!(!symbolizer.stats()["foo.pdb"].corrupt_symbols);
+        assert!(symbolizer.walk_frame(&foo, &mut Walker).await.is_none());
+        assert!(symbolizer.stats()["foo.pdb"].corrupt_symbols);
+
+        // The last FUNC is past the record limit
+        let bar = module("bar.pdb");
+        let mut frame = SimpleFrame::with_instruction(0x2010);
+        symbolizer.fill_symbol(&bar, &mut frame).await.unwrap();
+        assert_eq!(frame.function.unwrap(), "another func");
+        let mut frame = SimpleFrame::with_instruction(0x3010);
+        assert!(symbolizer.fill_symbol(&bar, &mut frame).await.is_ok());
+        assert!(frame.function.is_none());
+        let stats = &symbolizer.stats()["bar.pdb"];
+        assert!(stats.loaded_symbols);
+        assert!(stats.corrupt_symbols);
+    }
+
+    #[tokio::test]
+    async fn test_symbol_cache() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Counts how many times symbols were loaded (and so parsed).
+        struct CountingSupplier {
+            inner: StringSymbolSupplier,
+            loads: Arc<AtomicUsize>,
+        }
+        #[async_trait]
+        impl SymbolSupplier for CountingSupplier {
+            async fn locate_symbols(
+                &self,
+                module: &(dyn Module + Sync),
+            ) -> Result<LocateSymbolsResult, SymbolError> {
+                self.loads.fetch_add(1, Ordering::Relaxed);
+                self.inner.locate_symbols(module).await
+            }
+            async fn locate_file(
+                &self,
+                module: &(dyn Module + Sync),
+                file_kind: FileKind,
+            ) -> Result<PathBuf, FileError> {
+                self.inner.locate_file(module, file_kind).await
+            }
+        }
+
+        let symbols = |name: &str| {
+            format!(
+                "MODULE Linux x86 ABCD1234ABCD1234ABCDABCD12345678a {name}\nFUNC 1000 30 10 {name}_func\n"
+            )
+        };
+        let loads = Arc::new(AtomicUsize::new(0));
+        let symbolizer = |cache: &Arc<SymbolCache>| {
+            let modules = ["foo", "bar", "baz", "qux"]
+                .iter()
+                .map(|name| (format!("{name}.pdb"), symbols(name)))
+                .collect();
+            let supplier = CountingSupplier {
+                inner: StringSymbolSupplier::new(modules),
+                loads: loads.clone(),
+            };
+            Symbolizer::new(supplier).with_cache(cache.clone())
+        };
+        async fn function(symbolizer: &Symbolizer, name: &str) -> Option<String> {
+            let module = SimpleModule::from_basic_info(None, None, Some(name.to_owned()), None);
+            let mut frame = SimpleFrame::with_instruction(0x1010);
+            symbolizer.fill_symbol(&module, &mut frame).await.ok()?;
+            frame.function
+        }
+
+        // The second symbolizer gets the symbols the first one parsed.
+        let cache = Arc::new(SymbolCache::new(1 << 20));
+        let first = symbolizer(&cache);
+        let second = symbolizer(&cache);
+        assert_eq!(function(&first, "foo.pdb").await.unwrap(), "foo_func");
+        assert_eq!(function(&second, "foo.pdb").await.unwrap(), "foo_func");
+        assert_eq!(loads.load(Ordering::Relaxed), 1);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+        assert!(second.stats()["foo.pdb"].loaded_symbols);
+
+        // Symbols that weren't found aren't cached.
+        assert!(function(&first, "missing.pdb").await.is_none());
+        assert!(function(&second, "missing.pdb").await.is_none());
+        assert_eq!(loads.load(Ordering::Relaxed), 3);
+        assert_eq!(cache.stats().entries, 1);
+
+        // With room for two symbol files, the ones no symbolizer is using
+        // anymore make way for new ones, but the ones in use don't.
+        let size = SymbolFile::from_bytes(symbols("foo").as_bytes())
+            .unwrap()
+            .memory_size();
+        let cache = Arc::new(SymbolCache::new(size * 2));
+        loads.store(0, Ordering::Relaxed);
+        let first = symbolizer(&cache);
+        function(&first, "foo.pdb").await.unwrap();
+        function(&first, "bar.pdb").await.unwrap();
+        function(&first, "baz.pdb").await.unwrap();
+        // They're all still in use.
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.evictions), (3, 0));
+        assert_eq!(stats.size, size * 3);
+        drop(first);
+
+        let second = symbolizer(&cache);
+        function(&second, "foo.pdb").await.unwrap();
+        function(&second, "baz.pdb").await.unwrap();
+        assert_eq!(loads.load(Ordering::Relaxed), 3);
+        let third = symbolizer(&cache);
+        function(&third, "qux.pdb").await.unwrap();
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.evictions), (3, 1));
+        assert_eq!(stats.size, size * 3);
+        // bar.pdb is gone, but the others are still there.
+        function(&third, "foo.pdb").await.unwrap();
+        function(&third, "baz.pdb").await.unwrap();
+        assert_eq!(loads.load(Ordering::Relaxed), 4);
+        function(&third, "bar.pdb").await.unwrap();
+        assert_eq!(loads.load(Ordering::Relaxed), 5);
+    }
 }
@@ -1,14 +1,16 @@
 // Copyright 2015 Ted Mielczarek. See the COPYRIGHT
 // file at the top-level directory of this distribution.
-use crate::{FrameSymbolizer, FrameWalker, Module, SymbolError};
+use crate::{FrameSymbolizer, FrameWalker, Module, SymbolError, SymbolKind, WalkFrameError};
 
 pub use crate::sym_file::types::*;
 pub use parser::SymbolParser;
 use std::fs::File;
 use std::io::Read;
-use std::ops::Deref;
+use std::mem::size_of;
+use std::ops::{Deref, Range};
 use std::path::Path;
-use tracing::trace;
+use tracing::{trace, warn};
+use walker::ExprLimits;
 
 mod parser;
 mod types;
@@ -70,6 +72,14 @@ pub mod walker;
 static MAX_BUFFER_CAPACITY: usize = 1024 * 160;
 static INITIAL_BUFFER_CAPACITY: usize = 1024 * 10;
 
+fn file_too_large(limits: &SymbolLimits) -> SymbolError {
+    warn!(
+        "symbol file is larger than the limit of {} bytes, rejecting it",
+        limits.max_file_size
+    );
+    SymbolError::ParseError("symbol file is too large", 0)
+}
+
 impl SymbolFile {
     /// Parse a SymbolFile from the given Reader.
     ///
@@ -81,11 +91,22 @@ impl SymbolFile {
     /// The reader is wrapped in a buffer reader so you shouldn't
     /// buffer the input yourself.
     pub fn parse<R: Read>(
+        input_reader: R,
+        callback: impl FnMut(&[u8]),
+    ) -> Result<SymbolFile, SymbolError> {
+        Self::parse_with_limits(input_reader, &SymbolLimits::default(), callback)
+    }
+
+    /// `parse`, enforcing `limits` instead of the default ones.
+    #[tracing::instrument(name = "parse_symbols", level = "trace", skip_all)]
+    pub fn parse_with_limits<R: Read>(
         mut input_reader: R,
+        limits: &SymbolLimits,
         mut callback: impl FnMut(&[u8]),
     ) -> Result<SymbolFile, SymbolError> {
         let mut buf = circular::Buffer::with_capacity(INITIAL_BUFFER_CAPACITY);
-        let mut parser = SymbolParser::new();
+        let mut parser = SymbolParser::with_limits(*limits);
+        let mut total_read = 0u64;
         let mut fully_consumed = false;
         let mut tried_to_grow = false;
         let mut in_panic_recovery = false;
@@ -123,6 +144,10 @@ impl SymbolFile {
             // Read the data in, and tell the circular buffer about the new data
             let size = input_reader.read(buf.space())?;
             buf.fill(size);
+            total_read += size as u64;
+            if total_read > limits.max_file_size {
+                return Err(file_too_large(limits));
+            }
 
             if size == 0 {
                 // If the reader returned no more bytes, this can be either mean
@@ -193,14 +218,34 @@ impl SymbolFile {
     /// `parse` but async
     #[cfg(feature = "http")]
     pub async fn parse_async(
+        response: reqwest::Response,
+        callback: impl FnMut(&[u8]),
+    ) -> Result<SymbolFile, SymbolError> {
+        Self::parse_async_with_limits(response, &SymbolLimits::default(), callback).await
+    }
+
+    /// `parse_async`, enforcing `limits` instead of the default ones.
+    #[cfg(feature = "http")]
+    #[tracing::instrument(name = "parse_symbols", level = "trace", skip_all)]
+    pub async fn parse_async_with_limits(
         mut response: reqwest::Response,
+        limits: &SymbolLimits,
         mut callback: impl FnMut(&[u8]),
     ) -> Result<SymbolFile, SymbolError> {
+        // Don't bother downloading a file that's known to be too big
+        if response
+            .content_length()
+            .is_some_and(|len| len > limits.max_file_size)
+        {
+            return Err(file_too_large(limits));
+        }
+
         let mut chunk;
         let mut slice = &[][..];
         let mut input_reader = &mut slice;
         let mut buf = circular::Buffer::with_capacity(INITIAL_BUFFER_CAPACITY);
-        let mut parser = SymbolParser::new();
+        let mut parser = SymbolParser::with_limits(*limits);
+        let mut total_read = 0u64;
 
         let mut fully_consumed = false;
         let mut tried_to_grow = false;
@@ -255,6 +300,10 @@ impl SymbolFile {
             // Read the data in, and tell the circular buffer about the new data
             let size = input_reader.read(buf.space())?;
             buf.fill(size);
+            total_read += size as u64;
+            if total_read > limits.max_file_size {
+                return Err(file_too_large(limits));
+            }
 
             if size == 0 {
                 // If the reader returned no more bytes, this can be either mean
@@ -340,28 +389,27 @@ impl SymbolFile {
             return;
         }
         let addr = frame.get_instruction() - module.base_address();
-        if let Some(func) = self.functions.get(addr) {
-            // TODO: although FUNC records have a parameter size, it appears that
-            // they aren't to be trusted? The STACK WIN records are more reliable
-            // when available. This is important precisely because these values
-            // are used to unwind subsequent STACK WIN frames (because certain
-            // calling conventions have the caller push the callee's arguments,
-            // which affects the the stack's size!).
-            //
-            // Need to spend more time thinking about if this is the right approach
-            let parameter_size = if let Some(info) = self.win_stack_framedata_info.get(addr) {
-                info.parameter_size
-            } else if let Some(info) = self.win_stack_fpo_info.get(addr) {
-                info.parameter_size
-            } else {
-                func.parameter_size
-            };
 
+        // Although FUNC and PUBLIC records have a parameter size, the STACK WIN
+        // records are more reliable when available (this is also what breakpad
+        // does). This is important precisely because these values are used to
+        // unwind subsequent STACK WIN frames (because certain calling conventions
+        // have the callee pop its arguments, which affects the stack's size!),
+        // so a frame gets the parameter size of its STACK WIN record even if no
+        // function covers it.
+        let stack_win_parameter_size = self
+            .win_stack_framedata_info
+            .get(addr)
+            .or_else(|| self.win_stack_fpo_info.get(addr))
+            .map(|info| info.parameter_size);
+
+        if let Some(func) = self.functions.get(addr) {
             frame.set_function(
                 &func.name,
                 func.address + module.base_address(),
-                parameter_size,
+                stack_win_parameter_size.unwrap_or(func.parameter_size),
             );
+            frame.set_symbol_quality(SymbolKind::Func, addr - func.address);
 
             // See if there's source line and inline info as well.
             //
@@ -471,41 +519,64 @@ impl SymbolFile {
                 .and_then(|idx| idx.checked_sub(1))
                 .and_then(|idx| funcs_slice.get(idx));
 
-            if let Some(prev_func) = prev_func {
-                if public.address <= prev_func.1.address {
-                    // This PUBLIC is truncated by a FUNC before it gets to `addr`,
-                    // so we shouldn't use it.
-                    return;
+            // A PUBLIC that's very far away is just as unlikely to apply (it's
+            // probably followed by code without any symbols), so we don't use
+            // those either.
+            let truncated =
+                prev_func.is_some_and(|prev_func| public.address <= prev_func.1.address);
+            let distance = addr - public.address;
+            if truncated || distance > self.limits.max_public_distance {
+                if let Some(parameter_size) = stack_win_parameter_size {
+                    frame.set_parameter_size(parameter_size);
                 }
+                return;
             }
 
             // Settle for a PUBLIC.
             frame.set_function(
                 &public.name,
                 public.address + module.base_address(),
-                public.parameter_size,
+                stack_win_parameter_size.unwrap_or(public.parameter_size),
             );
+            frame.set_symbol_quality(SymbolKind::Public, distance);
+        } else if let Some(parameter_size) = stack_win_parameter_size {
+            frame.set_parameter_size(parameter_size);
         }
     }
 
     pub fn walk_frame(&self, module: &dyn Module, walker: &mut dyn FrameWalker) -> Option<()> {
+        self.walk_frame_with_limits(module, walker, &mut ExprLimits::new(&self.limits))
+            .ok()
+    }
+
+    /// `walk_frame`, evaluating the frame's expressions within `limits`, which
+    /// remembers if they were exceeded.
+    ///
+    /// The error says why the frame couldn't be walked.
+    pub fn walk_frame_with_limits(
+        &self,
+        module: &dyn Module,
+        walker: &mut dyn FrameWalker,
+        limits: &mut ExprLimits,
+    ) -> Result<(), WalkFrameError> {
         if walker.get_instruction() < module.base_address() {
-            return None;
+            return Err(WalkFrameError::NoCfi);
         }
         let addr = walker.get_instruction() - module.base_address();
 
         // Preferentially use framedata over fpo, because if both are present,
         // the former tends to be more precise (breakpad heuristic).
         let win_stack_result = if let Some(info) = self.win_stack_framedata_info.get(addr) {
-            walker::walk_with_stack_win_framedata(info, walker)
+            walker::try_walk_with_stack_win_framedata(info, walker, limits)
         } else if let Some(info) = self.win_stack_fpo_info.get(addr) {
-            walker::walk_with_stack_win_fpo(info, walker)
+            walker::try_walk_with_stack_win_fpo(info, walker)
         } else {
-            None
+            Err(WalkFrameError::NoCfi)
         };
 
-        // If STACK WIN failed, try STACK CFI
-        win_stack_result.or_else(|| {
+        // If STACK WIN failed, try STACK CFI. If that's missing too, the
+        // STACK WIN failure is the more interesting one.
+        win_stack_result.or_else(|win_stack_error| {
             if let Some(info) = self.cfi_stack_info.get(addr) {
                 // Don't use add_rules that come after this address
                 let mut count = 0;
@@ -514,9 +585,14 @@ impl SymbolFile {
                     count += 1;
                 }
 
-                walker::walk_with_stack_cfi(&info.init, &info.add_rules[0..count], walker)
+                walker::try_walk_with_stack_cfi(
+                    &info.init,
+                    &info.add_rules[0..count],
+                    walker,
+                    limits,
+                )
             } else {
-                None
+                Err(win_stack_error)
             }
         })
     }
@@ -525,6 +601,68 @@ impl SymbolFile {
     pub fn find_nearest_public(&self, addr: u64) -> Option<&PublicSymbol> {
         self.publics.iter().rev().find(|&p| p.address <= addr)
     }
+
+    /// An estimate of how many bytes of memory this file takes up.
+    ///
+    /// This counts the records and the strings they own, but not the
+    /// overhead of the allocator or of the maps' unused capacity.
+    pub fn memory_size(&self) -> u64 {
+        fn string(s: &str) -> usize {
+            size_of::<String>() + s.len()
+        }
+        fn strings<'a>(iter: impl Iterator<Item = &'a String>) -> usize {
+            iter.map(|s| string(s)).sum::<usize>()
+        }
+        fn cfi_rules(rules: &CfiRules) -> usize {
+            size_of::<CfiRules>() + rules.rules.len()
+        }
+        fn win(info: &StackInfoWin) -> usize {
+            size_of::<StackInfoWin>()
+                + match &info.program_string_or_base_pointer {
+                    WinStackThing::ProgramString(s) => s.len(),
+                    WinStackThing::AllocatesBasePointer(_) => 0,
+                }
+        }
+
+        let mut size = size_of::<SymbolFile>()
+            + self.module_id.len()
+            + self.debug_file.len()
+            + self.url.as_ref().map_or(0, |url| url.len());
+        size += strings(self.files.values()) + self.files.len() * size_of::<u32>();
+        size +=
+            strings(self.inline_origins.values()) + self.inline_origins.len() * size_of::<u32>();
+        size += self
+            .publics
+            .iter()
+            .map(|public| size_of::<PublicSymbol>() + public.name.len())
+            .sum::<usize>();
+        size += self
+            .functions
+            .ranges_values()
+            .map(|(_, func)| {
+                size_of::<(Range<u64>, Function)>()
+                    + func.name.len()
+                    + func.lines.ranges_values().len() * size_of::<(Range<u64>, SourceLine)>()
+                    + func.inlinees.len() * size_of::<Inlinee>()
+            })
+            .sum::<usize>();
+        size += self
+            .cfi_stack_info
+            .ranges_values()
+            .map(|(_, info)| {
+                size_of::<(Range<u64>, StackInfoCfi)>()
+                    + info.init.rules.len()
+                    + info.add_rules.iter().map(cfi_rules).sum::<usize>()
+            })
+            .sum::<usize>();
+        size += self
+            .win_stack_framedata_info
+            .ranges_values()
+            .chain(self.win_stack_fpo_info.ranges_values())
+            .map(|(_, info)| size_of::<Range<u64>>() + win(info))
+            .sum::<usize>();
+        size as u64
+    }
 }
 
 #[cfg(test)]
@@ -602,6 +740,89 @@ mod test {
         assert_eq!(sym.functions.get(0x1001).unwrap().name, "another func");
     }
 
+    #[test]
+    fn test_symbolfile_limits() {
+        let bytes = b"MODULE Linux x86 ffff0000 bar
+FILE 53 bar.c
+FUNC 1000 30 10 some func
+1000 30 7 53
+FUNC 2000 30 10 another func
+2000 30 8 53
+";
+        let sym = SymbolFile::from_bytes(bytes).unwrap();
+        assert!(!sym.truncated);
+        assert_eq!(sym.functions.ranges_values().count(), 2);
+
+        // Too many records: the rest of the file is discarded
+        let limits = SymbolLimits {
+            max_records: 4,
+            ..SymbolLimits::default()
+        };
+        let sym = SymbolFile::parse_with_limits(&bytes[..], &limits, |_| ()).unwrap();
+        assert!(sym.truncated);
+        assert_eq!(sym.functions.ranges_values().count(), 1);
+        assert_eq!(sym.functions.get(0x1000).unwrap().name, "some func");
+        assert_eq!(sym.limits, limits);
+
+        // Too big: the file is rejected
+        let mut limits = SymbolLimits {
+            max_file_size: bytes.len() as u64 - 1,
+            ..SymbolLimits::default()
+        };
+        assert!(matches!(
+            SymbolFile::parse_with_limits(&bytes[..], &limits, |_| ()),
+            Err(SymbolError::ParseError(..))
+        ));
+        limits.max_file_size = bytes.len() as u64;
+        assert!(SymbolFile::parse_with_limits(&bytes[..], &limits, |_| ()).is_ok());
+    }
+
+    #[test]
+    fn test_public_distance_limit() {
+        let bytes = b"MODULE Linux x86 ffff0000 bar
+FILE 53 bar.c
+FUNC 1000 30 10 some func
+1000 30 7 53
+PUBLIC 2000 4 lone public
+";
+        let sym = SymbolFile::from_bytes(bytes).unwrap();
+        let module = crate::SimpleModule::default();
+        let fill = |sym: &SymbolFile, addr| {
+            let mut frame = crate::SimpleFrame::with_instruction(addr);
+            sym.fill_symbol(&module, &mut frame);
+            frame
+        };
+
+        let frame = fill(&sym, 0x1010);
+        assert_eq!(frame.function.as_deref(), Some("some func"));
+        assert_eq!(frame.symbol_kind, Some(SymbolKind::Func));
+        assert_eq!(frame.offset_from_symbol, Some(0x10));
+
+        let frame = fill(&sym, 0x2100);
+        assert_eq!(frame.function.as_deref(), Some("lone public"));
+        assert_eq!(frame.symbol_kind, Some(SymbolKind::Public));
+        assert_eq!(frame.offset_from_symbol, Some(0x100));
+
+        // Right at the limit is still close enough, past it isn't.
+        let max_distance = SymbolLimits::default().max_public_distance;
+        let frame = fill(&sym, 0x2000 + max_distance);
+        assert_eq!(frame.function.as_deref(), Some("lone public"));
+        assert_eq!(frame.offset_from_symbol, Some(max_distance));
+        let frame = fill(&sym, 0x2000 + max_distance + 1);
+        assert_eq!(frame.function, None);
+        assert_eq!(frame.symbol_kind, None);
+        assert_eq!(frame.parameter_size, None);
+
+        // The limit is configurable.
+        let limits = SymbolLimits {
+            max_public_distance: 0x100,
+            ..SymbolLimits::default()
+        };
+        let sym = SymbolFile::parse_with_limits(&bytes[..], &limits, |_| ()).unwrap();
+        assert_eq!(fill(&sym, 0x2100).function.as_deref(), Some("lone public"));
+        assert_eq!(fill(&sym, 0x2101).function, None);
+    }
+
     #[test]
     fn test_symbolfile_from_bytes_with_lf() {
         test_symbolfile_from_bytes(
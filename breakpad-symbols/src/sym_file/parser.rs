@@ -430,12 +430,17 @@ pub struct SymbolParser {
     url: Option<String>,
     pub lines: u64,
     cur_item: Option<Line>,
+    limits: SymbolLimits,
+    truncated: bool,
 }
 
 impl SymbolParser {
-    /// Creates a new SymbolParser.
-    pub fn new() -> Self {
-        Self::default()
+    /// Creates a new SymbolParser that enforces `limits`.
+    pub fn with_limits(limits: SymbolLimits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
     }
 
     /// Parses as much of the input as it can, and then returns
@@ -464,6 +469,19 @@ impl SymbolParser {
                 return Ok(orig_input.len());
             }
 
+            // Past the record limit, everything else is discarded (but still
+            // consumed, so that the caller keeps feeding us until EOF).
+            if self.lines >= self.limits.max_records {
+                if !self.truncated {
+                    warn!(
+                        "symbol file has more than {} records, discarding the rest",
+                        self.limits.max_records
+                    );
+                    self.truncated = true;
+                }
+                return Ok(orig_input.len());
+            }
+
             // First check if we're currently processing sublines of a
             // multi-line item like `FUNC` and `STACK CFI INIT`.
             // If we are, parse the next line as its subline format.
@@ -713,6 +731,8 @@ impl SymbolParser {
             cfi_stack_info: into_rangemap_safe(self.cfi_stack_info),
             win_stack_framedata_info: into_rangemap_safe(self.win_stack_framedata_info),
             win_stack_fpo_info: into_rangemap_safe(self.win_stack_fpo_info),
+            limits: self.limits,
+            truncated: self.truncated,
             // Will get filled in by the caller
             url: self.url,
             ambiguities_repaired: 0,
@@ -243,6 +243,52 @@ impl StackInfoWin {
     }
 }
 
+/// Limits on the symbol files that are parsed, and on the work done with them.
+///
+/// Symbol files are often downloaded from servers we don't control, so a
+/// malicious or corrupt file mustn't be able to exhaust memory or hang the
+/// stack walk. Exceeding a limit never fails processing: the symbols are
+/// flagged as [corrupt][crate::SymbolStats::corrupt_symbols] and a warning is
+/// logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SymbolLimits {
+    /// The largest symbol file that is parsed, in bytes. A larger file is
+    /// rejected as corrupt.
+    pub max_file_size: u64,
+    /// The most records (lines) that are parsed from a symbol file. The rest
+    /// of a longer file is discarded, and the file is marked as
+    /// [truncated][SymbolFile::truncated].
+    pub max_records: u64,
+    /// The most tokens a single STACK CFI or STACK WIN expression may have.
+    pub max_expr_steps: usize,
+    /// The most values the stack of a STACK CFI or STACK WIN expression may
+    /// hold.
+    pub max_expr_depth: usize,
+    /// The farthest an address may be past a PUBLIC symbol (with no FUNC
+    /// covering it) to be attributed to it, in bytes.
+    ///
+    /// PUBLIC records don't say where they end, so without a limit, every
+    /// address in a stripped region would get the name of the last symbol
+    /// before it, however far away that is.
+    pub max_public_distance: u64,
+}
+
+impl Default for SymbolLimits {
+    fn default() -> Self {
+        Self {
+            // The biggest symbol files in the wild are a couple of gigabytes
+            max_file_size: 8 << 30,
+            max_records: 500_000_000,
+            // Real expressions have a few dozen tokens at most
+            max_expr_steps: 1000,
+            max_expr_depth: 100,
+            // Functions are hardly ever this big
+            max_public_distance: 1 << 20,
+        }
+    }
+}
+
 /// A parsed .sym file containing debug symbols.
 #[derive(Debug, PartialEq, Eq)]
 pub struct SymbolFile {
@@ -262,6 +308,12 @@ pub struct SymbolFile {
     pub win_stack_framedata_info: RangeMap<u64, StackInfoWin>,
     /// Windows unwind information (FPO data).
     pub win_stack_fpo_info: RangeMap<u64, StackInfoWin>,
+    /// The limits the file was parsed with, which also bound the evaluation of
+    /// its STACK CFI and STACK WIN expressions.
+    pub limits: SymbolLimits,
+    /// If the file had more than [`SymbolLimits::max_records`] records, and
+    /// the rest were discarded.
+    pub truncated: bool,
 
     // Statistics which are strictly best-effort. Generally this
     // means we might undercount in situations where we forgot to
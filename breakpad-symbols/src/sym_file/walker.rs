@@ -484,17 +484,88 @@
 //!
 //! Giving a final output of `ebp=(*16)`, `esp=24`, `eip=(*20)`.
 
-use super::{CfiRules, StackInfoWin, WinStackThing};
-use crate::FrameWalker;
+use super::{CfiRules, StackInfoWin, SymbolLimits, WinStackThing};
+use crate::{FrameWalker, WalkFrameError};
 use std::collections::HashMap;
 use std::str::FromStr;
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
+
+/// Bounds on the evaluation of STACK CFI and STACK WIN expressions.
+///
+/// An expression that's too long, or that piles up too many values on its
+/// stack, fails to evaluate. The limits remember that this happened, so that
+/// the symbols can be flagged as corrupt.
+#[derive(Debug, Clone)]
+pub struct ExprLimits {
+    max_steps: usize,
+    max_depth: usize,
+    exceeded: bool,
+}
+
+impl ExprLimits {
+    /// The expression limits of `limits`.
+    pub fn new(limits: &SymbolLimits) -> Self {
+        Self {
+            max_steps: limits.max_expr_steps,
+            max_depth: limits.max_expr_depth,
+            exceeded: false,
+        }
+    }
+
+    /// Whether an evaluation was cut short by these limits.
+    pub fn exceeded(&self) -> bool {
+        self.exceeded
+    }
+
+    /// Check that an evaluation which is about to take its `step`th step, with
+    /// `depth` values on its stack, may continue.
+    fn check(&mut self, step: usize, depth: usize) -> Option<()> {
+        if step < self.max_steps && depth <= self.max_depth {
+            return Some(());
+        }
+        if !self.exceeded {
+            warn!(
+                "expression evaluation exceeded its limits ({} steps, {} values)",
+                self.max_steps, self.max_depth
+            );
+            self.exceeded = true;
+        }
+        None
+    }
+}
+
+impl Default for ExprLimits {
+    fn default() -> Self {
+        Self::new(&SymbolLimits::default())
+    }
+}
 
 pub fn walk_with_stack_cfi(
     init: &CfiRules,
     additional: &[CfiRules],
     walker: &mut dyn FrameWalker,
 ) -> Option<()> {
+    walk_with_stack_cfi_with_limits(init, additional, walker, &mut ExprLimits::default())
+}
+
+/// `walk_with_stack_cfi`, evaluating the expressions within `limits`.
+pub fn walk_with_stack_cfi_with_limits(
+    init: &CfiRules,
+    additional: &[CfiRules],
+    walker: &mut dyn FrameWalker,
+    limits: &mut ExprLimits,
+) -> Option<()> {
+    try_walk_with_stack_cfi(init, additional, walker, limits).ok()
+}
+
+/// `walk_with_stack_cfi_with_limits`, but the error says why the frame
+/// couldn't be walked.
+pub fn try_walk_with_stack_cfi(
+    init: &CfiRules,
+    additional: &[CfiRules],
+    walker: &mut dyn FrameWalker,
+    limits: &mut ExprLimits,
+) -> Result<(), WalkFrameError> {
     trace!("trying STACK CFI exprs");
     trace!("  {}", init.rules);
     for line in additional {
@@ -505,25 +576,31 @@ pub fn walk_with_stack_cfi(
     // If a REG occurs twice, we prefer the one that comes later. This allows
     // STACK CFI records to apply incremental updates to the instructions.
     let mut exprs = HashMap::new();
-    parse_cfi_exprs(&init.rules, &mut exprs)?;
+    parse_cfi_exprs(&init.rules, &mut exprs).ok_or(WalkFrameError::EvalFailed)?;
     for line in additional {
-        parse_cfi_exprs(&line.rules, &mut exprs)?;
+        parse_cfi_exprs(&line.rules, &mut exprs).ok_or(WalkFrameError::EvalFailed)?;
     }
     trace!("STACK CFI parse successful");
 
     // These two are special and *must* always be present
-    let cfa_expr = exprs.remove(&CfiReg::Cfa)?;
-    let ra_expr = exprs.remove(&CfiReg::Ra)?;
+    let cfa_expr = exprs
+        .remove(&CfiReg::Cfa)
+        .ok_or(WalkFrameError::EvalFailed)?;
+    let ra_expr = exprs
+        .remove(&CfiReg::Ra)
+        .ok_or(WalkFrameError::EvalFailed)?;
     trace!("STACK CFI seems reasonable, evaluating");
 
     // Evaluating the CFA cannot itself use the CFA
-    let cfa = eval_cfi_expr(cfa_expr, walker, None)?;
+    let cfa = eval_cfi_expr(cfa_expr, walker, None, limits)
+        .ok_or_else(|| cfi_expr_failure(cfa_expr, walker, limits))?;
     trace!("successfully evaluated .cfa (frame address)");
-    let ra = eval_cfi_expr(ra_expr, walker, Some(cfa))?;
+    let ra = eval_cfi_expr(ra_expr, walker, Some(cfa), limits)
+        .ok_or_else(|| cfi_expr_failure(ra_expr, walker, limits))?;
     trace!("successfully evaluated .ra (return address)");
 
-    walker.set_cfa(cfa)?;
-    walker.set_ra(ra)?;
+    walker.set_cfa(cfa).ok_or(WalkFrameError::EvalFailed)?;
+    walker.set_ra(ra).ok_or(WalkFrameError::EvalFailed)?;
 
     for (reg, expr) in exprs {
         if let CfiReg::Other(reg) = reg {
@@ -531,7 +608,7 @@ pub fn walk_with_stack_cfi(
             // and keep going on. It's fine to lose some general purpose regs,
             // but make sure to clear it in case it would have been implicitly
             // forwarded from the callee.
-            match eval_cfi_expr(expr, walker, Some(cfa)) {
+            match eval_cfi_expr(expr, walker, Some(cfa), limits) {
                 Some(val) => {
                     walker.set_caller_register(reg, val);
                     trace!("successfully evaluated {}", reg);
@@ -547,7 +624,38 @@ pub fn walk_with_stack_cfi(
         }
     }
 
-    Some(())
+    Ok(())
+}
+
+/// Why `expr` failed to evaluate: the first register it reads that the callee
+/// doesn't have, if any.
+fn cfi_expr_failure(expr: &str, walker: &dyn FrameWalker, limits: &ExprLimits) -> WalkFrameError {
+    if limits.exceeded() {
+        return WalkFrameError::EvalFailed;
+    }
+    for token in expr.split_ascii_whitespace() {
+        // Same as eval_cfi_expr: a `$reg`, or an arm-style register without the `$`
+        let reg = match token.split_once('$') {
+            Some((_, reg)) => reg,
+            None if is_cfi_operator(token) || i64::from_str(token).is_ok() => continue,
+            None => token,
+        };
+        if walker.get_callee_register(reg).is_none() {
+            trace!(
+                "STACK CFI needs callee register {}, which is unavailable",
+                reg
+            );
+            return WalkFrameError::MissingRegister(reg.to_owned());
+        }
+    }
+    WalkFrameError::EvalFailed
+}
+
+fn is_cfi_operator(token: &str) -> bool {
+    matches!(
+        token,
+        "+" | "-" | "*" | "/" | "%" | "@" | "^" | ".cfa" | ".undef"
+    )
 }
 
 fn parse_cfi_exprs<'a>(input: &'a str, output: &mut HashMap<CfiReg<'a>, &'a str>) -> Option<()> {
@@ -611,10 +719,16 @@ fn parse_cfi_exprs<'a>(input: &'a str, output: &mut HashMap<CfiReg<'a>, &'a str>
     Some(())
 }
 
-fn eval_cfi_expr(expr: &str, walker: &mut dyn FrameWalker, cfa: Option<u64>) -> Option<u64> {
+fn eval_cfi_expr(
+    expr: &str,
+    walker: &mut dyn FrameWalker,
+    cfa: Option<u64>,
+    limits: &mut ExprLimits,
+) -> Option<u64> {
     // FIXME: this should be an ArrayVec or something, most exprs are simple.
     let mut stack: Vec<u64> = Vec::new();
-    for token in expr.split_ascii_whitespace() {
+    for (step, token) in expr.split_ascii_whitespace().enumerate() {
+        limits.check(step, stack.len())?;
         match token {
             // FIXME?: not sure what overflow/sign semantics are, but haven't run into
             // something where it actually matters (I wouldn't expect it to come up
@@ -743,7 +857,17 @@ pub fn eval_win_expr_for_fuzzer(
     eval_win_expr(expr, info, walker)
 }
 
+#[cfg(any(test, feature = "fuzz"))]
 fn eval_win_expr(expr: &str, info: &StackInfoWin, walker: &mut dyn FrameWalker) -> Option<()> {
+    eval_win_expr_with_limits(expr, info, walker, &mut ExprLimits::default())
+}
+
+fn eval_win_expr_with_limits(
+    expr: &str,
+    info: &StackInfoWin,
+    walker: &mut dyn FrameWalker,
+    limits: &mut ExprLimits,
+) -> Option<()> {
     // TODO?: do a bunch of heuristics to make this more robust.
     // So far I haven't encountered an in-the-wild example that needs the
     // extra heuristics that breakpad uses, so leaving them out until they
@@ -809,7 +933,8 @@ fn eval_win_expr(expr: &str, info: &StackInfoWin, walker: &mut dyn FrameWalker)
 
     // Evaluate the expressions
 
-    for token in tokens {
+    for (step, token) in tokens.enumerate() {
+        limits.check(step, stack.len())?;
         match token {
             // FIXME: not sure what overflow/sign semantics are
             "+" => {
@@ -960,75 +1085,136 @@ pub fn walk_with_stack_win_framedata(
     info: &StackInfoWin,
     walker: &mut dyn FrameWalker,
 ) -> Option<()> {
+    walk_with_stack_win_framedata_with_limits(info, walker, &mut ExprLimits::default())
+}
+
+/// `walk_with_stack_win_framedata`, evaluating the expression within `limits`.
+pub fn walk_with_stack_win_framedata_with_limits(
+    info: &StackInfoWin,
+    walker: &mut dyn FrameWalker,
+    limits: &mut ExprLimits,
+) -> Option<()> {
+    try_walk_with_stack_win_framedata(info, walker, limits).ok()
+}
+
+/// `walk_with_stack_win_framedata_with_limits`, but the error says why the
+/// frame couldn't be walked.
+pub fn try_walk_with_stack_win_framedata(
+    info: &StackInfoWin,
+    walker: &mut dyn FrameWalker,
+    limits: &mut ExprLimits,
+) -> Result<(), WalkFrameError> {
     if let WinStackThing::ProgramString(ref expr) = info.program_string_or_base_pointer {
         trace!("trying STACK WIN framedata -- {}", expr);
+        check_stack_win_callee_registers(walker, &["esp", "ebp"])?;
         clear_stack_win_caller_registers(walker);
-        eval_win_expr(expr, info, walker)
+        eval_win_expr_with_limits(expr, info, walker, limits).ok_or(WalkFrameError::EvalFailed)
     } else {
         unreachable!()
     }
 }
 
 pub fn walk_with_stack_win_fpo(info: &StackInfoWin, walker: &mut dyn FrameWalker) -> Option<()> {
+    try_walk_with_stack_win_fpo(info, walker).ok()
+}
+
+/// `walk_with_stack_win_fpo`, but the error says why the frame couldn't be
+/// walked.
+pub fn try_walk_with_stack_win_fpo(
+    info: &StackInfoWin,
+    walker: &mut dyn FrameWalker,
+) -> Result<(), WalkFrameError> {
     if let WinStackThing::AllocatesBasePointer(allocates_base_pointer) =
         info.program_string_or_base_pointer
     {
         // FIXME: do a bunch of heuristics to make this more robust.
         // Haven't needed the heuristics breakpad uses yet.
         trace!("trying STACK WIN fpo");
+        if allocates_base_pointer {
+            check_stack_win_callee_registers(walker, &["esp"])?;
+        } else {
+            check_stack_win_callee_registers(walker, &["esp", "ebp"])?;
+        }
         clear_stack_win_caller_registers(walker);
+        eval_win_fpo(info, allocates_base_pointer, walker).ok_or(WalkFrameError::EvalFailed)
+    } else {
+        unreachable!()
+    }
+}
 
-        let grand_callee_param_size = walker.get_grand_callee_parameter_size();
-        let frame_size = win_frame_size(info, grand_callee_param_size) as u64;
+fn eval_win_fpo(
+    info: &StackInfoWin,
+    allocates_base_pointer: bool,
+    walker: &mut dyn FrameWalker,
+) -> Option<()> {
+    let grand_callee_param_size = walker.get_grand_callee_parameter_size();
+    let frame_size = win_frame_size(info, grand_callee_param_size) as u64;
+
+    let callee_esp = walker.get_callee_register("esp")?;
+    let mut eip_address = callee_esp + frame_size;
+    let mut caller_eip = walker.get_register_at_address(eip_address)?;
+
+    // Check for a "leftover return address": in some pathological cases the return address isn't popped off the stack
+    // after a return instruction. According to breakpad, this can happen for "frame-pointer-optimized
+    // system calls", which implies that the callee must be a context frame.
+    //
+    // To detect these cases, we check whether
+    // 1. we are in a context frame. We approximate this by checking whether there's a grand-callee.
+    // 2. the caller's eip (aka the return address) is the same as the callee's eip.
+    //
+    // If we detect a leftover return address, we skip it and try again one word
+    // further down the stack.
+    let callee_is_context_frame = !walker.has_grand_callee();
+    if callee_is_context_frame && caller_eip == walker.get_callee_register("eip")? {
+        eip_address += 4;
+        caller_eip = walker.get_register_at_address(eip_address)?;
+    }
+    let caller_esp = eip_address + 4;
 
-        let callee_esp = walker.get_callee_register("esp")?;
-        let mut eip_address = callee_esp + frame_size;
-        let mut caller_eip = walker.get_register_at_address(eip_address)?;
+    trace!("found caller $eip and $esp");
 
-        // Check for a "leftover return address": in some pathological cases the return address isn't popped off the stack
-        // after a return instruction. According to breakpad, this can happen for "frame-pointer-optimized
-        // system calls", which implies that the callee must be a context frame.
-        //
-        // To detect these cases, we check whether
-        // 1. we are in a context frame. We approximate this by checking whether there's a grand-callee.
-        // 2. the caller's eip (aka the return address) is the same as the callee's eip.
-        //
-        // If we detect a leftover return address, we skip it and try again one word
-        // further down the stack.
-        let callee_is_context_frame = !walker.has_grand_callee();
-        if callee_is_context_frame && caller_eip == walker.get_callee_register("eip")? {
-            eip_address += 4;
-            caller_eip = walker.get_register_at_address(eip_address)?;
+    let caller_ebp = if allocates_base_pointer {
+        let ebp_address =
+            callee_esp + grand_callee_param_size as u64 + info.saved_register_size as u64 - 8;
+        walker.get_register_at_address(ebp_address)?
+    } else {
+        // Per Breakpad: We also propagate %ebx through, as it is commonly unmodifed after
+        // calling simple forwarding functions in ntdll (that are this non-EBP
+        // using type). It's not clear that this is always correct, but it is
+        // important for some functions to get a correct walk.
+        if let Some(callee_ebx) = walker.get_callee_register("ebx") {
+            walker.set_caller_register("ebx", callee_ebx)?;
         }
-        let caller_esp = eip_address + 4;
 
-        trace!("found caller $eip and $esp");
-
-        let caller_ebp = if allocates_base_pointer {
-            let ebp_address =
-                callee_esp + grand_callee_param_size as u64 + info.saved_register_size as u64 - 8;
-            walker.get_register_at_address(ebp_address)?
-        } else {
-            // Per Breakpad: We also propagate %ebx through, as it is commonly unmodifed after
-            // calling simple forwarding functions in ntdll (that are this non-EBP
-            // using type). It's not clear that this is always correct, but it is
-            // important for some functions to get a correct walk.
-            if let Some(callee_ebx) = walker.get_callee_register("ebx") {
-                walker.set_caller_register("ebx", callee_ebx)?;
-            }
+        walker.get_callee_register("ebp")?
+    };
+    trace!("found caller $ebp");
 
-            walker.get_callee_register("ebp")?
-        };
-        trace!("found caller $ebp");
+    walker.set_caller_register("eip", caller_eip)?;
+    walker.set_caller_register("esp", caller_esp)?;
+    walker.set_caller_register("ebp", caller_ebp)?;
 
-        walker.set_caller_register("eip", caller_eip)?;
-        walker.set_caller_register("esp", caller_esp)?;
-        walker.set_caller_register("ebp", caller_ebp)?;
+    trace!("STACK WIN fpo eval succeeded!");
+    Some(())
+}
 
-        trace!("STACK WIN fpo eval succeeded!");
-        Some(())
-    } else {
-        unreachable!()
+/// STACK WIN can't do anything without these callee registers
+fn check_stack_win_callee_registers(
+    walker: &dyn FrameWalker,
+    regs: &[&str],
+) -> Result<(), WalkFrameError> {
+    match regs
+        .iter()
+        .find(|reg| walker.get_callee_register(reg).is_none())
+    {
+        Some(reg) => {
+            trace!(
+                "STACK WIN needs callee register {}, which is unavailable",
+                reg
+            );
+            Err(WalkFrameError::MissingRegister((*reg).to_owned()))
+        }
+        None => Ok(()),
     }
 }
 
@@ -1042,9 +1228,13 @@ fn clear_stack_win_caller_registers(walker: &mut dyn FrameWalker) {
 
 #[cfg(test)]
 mod test {
-    use super::super::types::{CfiRules, StackInfoWin, WinStackThing};
-    use super::{eval_win_expr, walk_with_stack_cfi, walk_with_stack_win_fpo};
-    use crate::FrameWalker;
+    use super::super::types::{CfiRules, StackInfoWin, SymbolLimits, WinStackThing};
+    use super::{
+        eval_win_expr, eval_win_expr_with_limits, try_walk_with_stack_cfi,
+        try_walk_with_stack_win_fpo, walk_with_stack_cfi, walk_with_stack_cfi_with_limits,
+        walk_with_stack_win_fpo, ExprLimits,
+    };
+    use crate::{FrameWalker, WalkFrameError};
     use std::collections::HashMap;
 
     // Eugh, need this to memoize register names to static
@@ -1712,6 +1902,94 @@ mod test {
         assert!(walk_with_stack_cfi(&init, &additional, &mut walker).is_none());
     }
 
+    #[test]
+    fn test_expr_limits() {
+        let input = vec![("rsp", 32u64), ("rip", 1600)].into_iter().collect();
+        let stack = vec![0; 1600];
+        let mut walker = TestFrameWalker::new(stack, input);
+        let symbol_limits = SymbolLimits {
+            max_expr_steps: 8,
+            max_expr_depth: 3,
+            ..SymbolLimits::default()
+        };
+
+        // Within the limits
+        let mut limits = ExprLimits::new(&symbol_limits);
+        let (init, additional) = build_cfi_rules(".cfa: 1 2 + 3 + .ra: 8", &[]);
+        walk_with_stack_cfi_with_limits(&init, &additional, &mut walker, &mut limits).unwrap();
+        assert!(!limits.exceeded());
+
+        // Too many steps
+        let (init, additional) = build_cfi_rules(".cfa: 1 2 + 3 + 4 + 5 + .ra: 8", &[]);
+        assert!(
+            walk_with_stack_cfi_with_limits(&init, &additional, &mut walker, &mut limits).is_none()
+        );
+        assert!(limits.exceeded());
+
+        // Too deep
+        let mut limits = ExprLimits::new(&symbol_limits);
+        let (init, additional) = build_cfi_rules(".cfa: 1 2 3 4 5 + + + + .ra: 8", &[]);
+        assert!(
+            walk_with_stack_cfi_with_limits(&init, &additional, &mut walker, &mut limits).is_none()
+        );
+        assert!(limits.exceeded());
+
+        // STACK WIN has the same limits
+        let input = vec![("esp", 32u32), ("ebp", 1600)].into_iter().collect();
+        let mut walker = TestFrameWalker::new(vec![0; 1600], input);
+        let info = whatever_win_info();
+        let mut limits = ExprLimits::new(&symbol_limits);
+        eval_win_expr_with_limits("$esp 1 = $ebp 2 =", &info, &mut walker, &mut limits).unwrap();
+        assert!(!limits.exceeded());
+        assert!(eval_win_expr_with_limits(
+            "$esp 1 = $ebp 2 = $eip 3 =",
+            &info,
+            &mut walker,
+            &mut limits
+        )
+        .is_none());
+        assert!(limits.exceeded());
+    }
+
+    #[test]
+    fn test_walk_frame_errors() {
+        let input = vec![("rsp", 32u64), ("rip", 1600)].into_iter().collect();
+        let mut walker = TestFrameWalker::new(vec![0; 1600], input);
+
+        // The callee doesn't have $rbp
+        let (init, additional) = build_cfi_rules(".cfa: $rbp 16 + .ra: .cfa -8 + ^", &[]);
+        assert_eq!(
+            try_walk_with_stack_cfi(&init, &additional, &mut walker, &mut ExprLimits::default()),
+            Err(WalkFrameError::MissingRegister("rbp".to_owned()))
+        );
+        let (init, additional) = build_cfi_rules(".cfa: $rsp 16 + .ra: .cfa $rbx + ^", &[]);
+        assert_eq!(
+            try_walk_with_stack_cfi(&init, &additional, &mut walker, &mut ExprLimits::default()),
+            Err(WalkFrameError::MissingRegister("rbx".to_owned()))
+        );
+
+        // The registers are all there, but the expression is bad
+        let (init, additional) = build_cfi_rules(".cfa: $rsp 0 / .ra: 8", &[]);
+        assert_eq!(
+            try_walk_with_stack_cfi(&init, &additional, &mut walker, &mut ExprLimits::default()),
+            Err(WalkFrameError::EvalFailed)
+        );
+        let (init, additional) = build_cfi_rules(".cfa: $rsp 8 +", &[]);
+        assert_eq!(
+            try_walk_with_stack_cfi(&init, &additional, &mut walker, &mut ExprLimits::default()),
+            Err(WalkFrameError::EvalFailed)
+        );
+
+        // STACK WIN needs $esp and $ebp
+        let input = vec![("esp", 32u32)].into_iter().collect();
+        let mut walker = TestFrameWalker::new(vec![0; 1600], input);
+        let info = whatever_win_info();
+        assert_eq!(
+            try_walk_with_stack_win_fpo(&info, &mut walker),
+            Err(WalkFrameError::MissingRegister("ebp".to_owned()))
+        );
+    }
+
     #[test]
     fn test_stack_cfi_corners() {
         // Checking various issues that we should bail on
@@ -0,0 +1,172 @@
+//! A cache of parsed symbol files that several [`Symbolizer`]s can share.
+//!
+//! [`Symbolizer`]: crate::Symbolizer
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tracing::trace;
+
+use crate::{ModuleKey, SymbolError, SymbolFile, SymbolStats};
+
+/// Statistics on a [`SymbolCache`].
+///
+/// Fetched with [`SymbolCache::stats`].
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct SymbolCacheStats {
+    /// The number of times a `Symbolizer` found a module's symbols in the cache.
+    pub hits: u64,
+    /// The number of times a `Symbolizer` didn't, and had to load them itself.
+    pub misses: u64,
+    /// The number of symbol files that were dropped from the cache to make room.
+    pub evictions: u64,
+    /// The number of symbol files in the cache.
+    pub entries: u64,
+    /// The (estimated, see [`SymbolFile::memory_size`]) bytes of memory the
+    /// symbol files in the cache take up.
+    pub size: u64,
+}
+
+/// A cache of parsed symbol files, shared by the [`Symbolizer`]s it's given to
+/// with [`Symbolizer::with_cache`].
+///
+/// Each `Symbolizer` already loads the symbols of a module only once, but
+/// that's lost with the `Symbolizer`. When many minidumps of the same build
+/// are processed, one `Symbolizer` per minidump, a `SymbolCache` lets them
+/// reuse each other's symbol files instead of fetching and parsing them again.
+///
+/// The cache holds at most `max_size` bytes of symbol files (as estimated by
+/// [`SymbolFile::memory_size`]), and makes room for new ones by dropping the
+/// least recently used. Symbol files that a `Symbolizer` is still using aren't
+/// dropped, as that wouldn't free their memory anyway, so the cache can go over
+/// `max_size` while there are more of them than fit.
+///
+/// Only symbol files that were successfully loaded are cached: a module whose
+/// symbols weren't found (or failed to load) is tried again by the next
+/// `Symbolizer`, in case they've become available since.
+///
+/// [`Symbolizer`]: crate::Symbolizer
+/// [`Symbolizer::with_cache`]: crate::Symbolizer::with_cache
+#[derive(Debug)]
+pub struct SymbolCache {
+    max_size: u64,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<ModuleKey, CacheEntry>,
+    /// The sum of the entries' sizes.
+    size: u64,
+    /// Incremented on every access, to order the entries by when they were last used.
+    clock: u64,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    symbols: Arc<Result<SymbolFile, SymbolError>>,
+    /// The stats of the `Symbolizer` that loaded the symbols, for the others to report.
+    stats: SymbolStats,
+    size: u64,
+    last_used: u64,
+}
+
+impl SymbolCache {
+    /// Create a `SymbolCache` that holds up to `max_size` bytes of symbol files.
+    pub fn new(max_size: u64) -> SymbolCache {
+        SymbolCache {
+            max_size,
+            state: Mutex::default(),
+        }
+    }
+
+    /// The number of bytes of symbol files the cache holds at most.
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    /// Collect statistics on the cache.
+    pub fn stats(&self) -> SymbolCacheStats {
+        let state = self.state.lock().unwrap();
+        SymbolCacheStats {
+            hits: state.hits,
+            misses: state.misses,
+            evictions: state.evictions,
+            entries: state.entries.len() as u64,
+            size: state.size,
+        }
+    }
+
+    /// Get the symbols of a module, and the stats of loading them.
+    pub(crate) fn get(
+        &self,
+        key: &ModuleKey,
+    ) -> Option<(Arc<Result<SymbolFile, SymbolError>>, SymbolStats)> {
+        let mut state = self.state.lock().unwrap();
+        state.clock += 1;
+        let clock = state.clock;
+        match state.entries.get_mut(key) {
+            Some(entry) => {
+                entry.last_used = clock;
+                let found = (entry.symbols.clone(), entry.stats.clone());
+                state.hits += 1;
+                Some(found)
+            }
+            None => {
+                state.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Add the symbols of a module, making room for them if needed.
+    pub(crate) fn insert(
+        &self,
+        key: ModuleKey,
+        symbols: Arc<Result<SymbolFile, SymbolError>>,
+        stats: SymbolStats,
+    ) {
+        let size = match symbols.as_ref() {
+            Ok(symbols) => symbols.memory_size(),
+            Err(_) => return,
+        };
+        if size > self.max_size {
+            trace!("symbols for {} are too large to cache", key.0);
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.clock += 1;
+        let entry = CacheEntry {
+            symbols,
+            stats,
+            size,
+            last_used: state.clock,
+        };
+        // Two `Symbolizer`s may have loaded the same symbols at the same time.
+        if let Some(old) = state.entries.insert(key, entry) {
+            state.size -= old.size;
+        }
+        state.size += size;
+
+        while state.size > self.max_size {
+            // Symbol files only the cache has a reference to are free to go.
+            let lru = state
+                .entries
+                .iter()
+                .filter(|(_, entry)| Arc::strong_count(&entry.symbols) == 1)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            let Some(lru) = lru else {
+                break;
+            };
+            let evicted = state.entries.remove(&lru).unwrap();
+            trace!("evicting symbols for {} from the cache", lru.0);
+            state.size -= evicted.size;
+            state.evictions += 1;
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Source files whose paths say where they are in version control.
+//!
+//! When symbols are source indexed (like `dump_syms` does for Mozilla's
+//! builds), the paths of the FILE records aren't the paths the files had on the
+//! build machine, but name the repository and revision the file came from:
+//!
+//! ```text
+//! hg:hg.mozilla.org/mozilla-central:dom/base/Element.cpp:1a2b3c4d5e6f
+//! git:github.com/rust-lang/rust:library/core/src/option.rs:0123456789abcdef0123456789abcdef01234567
+//! ```
+//!
+//! That is `<vcs>:<repository>:<path>:<revision>`.
+
+use std::fmt;
+
+/// A version control system that source indexed paths can refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Vcs {
+    /// Mercurial
+    Hg,
+    /// Git
+    Git,
+}
+
+impl Vcs {
+    /// The prefix of source indexed paths of this version control system.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Vcs::Hg => "hg",
+            Vcs::Git => "git",
+        }
+    }
+
+    fn from_prefix(prefix: &str) -> Option<Vcs> {
+        match prefix {
+            "hg" => Some(Vcs::Hg),
+            "git" => Some(Vcs::Git),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Vcs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Where a source file is in version control, parsed from a source indexed path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VcsSourceFile {
+    /// The version control system of the repository.
+    pub vcs: Vcs,
+    /// The repository, as its host and path (e.g. "hg.mozilla.org/mozilla-central").
+    pub repo: String,
+    /// The path of the file in the repository.
+    pub path: String,
+    /// The revision (changeset or commit hash) the file is from.
+    pub revision: String,
+}
+
+impl VcsSourceFile {
+    /// Parse a source indexed path, see the [module docs][self].
+    ///
+    /// Returns `None` for anything else (like plain local paths), so those
+    /// can be used as they are.
+    pub fn parse(file: &str) -> Option<VcsSourceFile> {
+        let (prefix, rest) = file.split_once(':')?;
+        let vcs = Vcs::from_prefix(prefix)?;
+        let (repo, rest) = rest.split_once(':')?;
+        let (path, revision) = rest.rsplit_once(':')?;
+
+        let valid_repo = repo.contains('/')
+            && !repo.starts_with('/')
+            && !repo.contains(|c: char| c.is_whitespace() || c == '\\');
+        let valid_revision = revision.len() >= 7 && revision.chars().all(|c| c.is_ascii_hexdigit());
+        if !valid_repo || path.is_empty() || !valid_revision {
+            return None;
+        }
+
+        Some(VcsSourceFile {
+            vcs,
+            repo: repo.to_owned(),
+            path: path.to_owned(),
+            revision: revision.to_owned(),
+        })
+    }
+
+    /// The host of the repository (e.g. "github.com").
+    pub fn host(&self) -> &str {
+        self.repo.split('/').next().unwrap_or(&self.repo)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hg() {
+        assert_eq!(
+            VcsSourceFile::parse(
+                "hg:hg.mozilla.org/mozilla-central:dom/base/Element.cpp:1a2b3c4d5e6f"
+            ),
+            Some(VcsSourceFile {
+                vcs: Vcs::Hg,
+                repo: "hg.mozilla.org/mozilla-central".to_owned(),
+                path: "dom/base/Element.cpp".to_owned(),
+                revision: "1a2b3c4d5e6f".to_owned(),
+            })
+        );
+        let file = VcsSourceFile::parse(
+            "hg:hg.mozilla.org/releases/mozilla-release:js/src/vm/Interpreter.cpp:0123456789abcdef0123456789abcdef01234567",
+        )
+        .unwrap();
+        assert_eq!(file.repo, "hg.mozilla.org/releases/mozilla-release");
+        assert_eq!(file.path, "js/src/vm/Interpreter.cpp");
+        assert_eq!(file.host(), "hg.mozilla.org");
+    }
+
+    #[test]
+    fn test_git() {
+        let file = VcsSourceFile::parse(
+            "git:github.com/rust-lang/rust:library/core/src/option.rs:fdca237d5194bf8a1c9b437ebd2114d1c2ba6195",
+        )
+        .unwrap();
+        assert_eq!(file.vcs, Vcs::Git);
+        assert_eq!(file.repo, "github.com/rust-lang/rust");
+        assert_eq!(file.path, "library/core/src/option.rs");
+        assert_eq!(file.revision, "fdca237d5194bf8a1c9b437ebd2114d1c2ba6195");
+        assert_eq!(file.host(), "github.com");
+    }
+
+    #[test]
+    fn test_not_vcs() {
+        // Plain paths
+        assert_eq!(
+            VcsSourceFile::parse("/builds/worker/dom/base/Element.cpp"),
+            None
+        );
+        assert_eq!(
+            VcsSourceFile::parse(r"c:\builds\dom\base\Element.cpp"),
+            None
+        );
+        assert_eq!(VcsSourceFile::parse("src/main.rs"), None);
+        // Malformed: no revision, a revision that isn't a hash, no path, or no repository
+        assert_eq!(
+            VcsSourceFile::parse("hg:hg.mozilla.org/mozilla-central:dom/base/Element.cpp"),
+            None
+        );
+        assert_eq!(
+            VcsSourceFile::parse("git:github.com/rust-lang/rust:library/core/src/option.rs:main"),
+            None
+        );
+        assert_eq!(
+            VcsSourceFile::parse("hg:hg.mozilla.org/mozilla-central::1a2b3c4d5e6f"),
+            None
+        );
+        assert_eq!(
+            VcsSourceFile::parse("git:rust:library/core/src/option.rs:1a2b3c4d5e6f"),
+            None
+        );
+        assert_eq!(
+            VcsSourceFile::parse("svn:svn.example.com/repo:file.c:1a2b3c4d5e6f"),
+            None
+        );
+    }
+}
@@ -28,12 +28,62 @@ pub enum ExceptionCodeMac {
     EXC_SYSCALL = 7,
     EXC_MACH_SYSCALL = 8,
     EXC_RPC_ALERT = 9,
+    /// Abnormal process exit, code has the original exception and the signal
+    /// (see [`ExceptionCodeMacSignal`])
+    EXC_CRASH = 10,
     EXC_RESOURCE = 11,
     EXC_GUARD = 12,
+    /// A corpse of the process was made for crash reporting
+    EXC_CORPSE_NOTIFY = 13,
     /// Fake exception code used by Crashpad's SimulateCrash ('CPsx')
     SIMULATED = 0x43507378,
 }
 
+/// Mac/iOS signals, which are part of the code of [`ExceptionCodeMac::EXC_CRASH`]
+///
+/// The code of an `EXC_CRASH` is made by `proc_prepareexit` in
+/// [bsd/kern/kern_exit.c][kern_exit]: its bits 24-31 are the signal that
+/// killed the process, bits 20-23 the original exception (0 if the signal
+/// didn't come from one) and bits 0-19 the low bits of the original code.
+/// The signal numbers are those of [bsd/sys/signal.h][header].
+///
+/// [kern_exit]: https://github.com/apple/darwin-xnu/blob/2ff845c2e033bd0ff64b5b6aa6063a1f8f65aa32/bsd/kern/kern_exit.c
+/// [header]: https://github.com/apple/darwin-xnu/blob/2ff845c2e033bd0ff64b5b6aa6063a1f8f65aa32/bsd/sys/signal.h
+#[derive(Copy, Clone, PartialEq, Eq, Debug, FromPrimitive)]
+pub enum ExceptionCodeMacSignal {
+    SIGHUP = 1,
+    SIGINT = 2,
+    SIGQUIT = 3,
+    SIGILL = 4,
+    SIGTRAP = 5,
+    SIGABRT = 6,
+    SIGEMT = 7,
+    SIGFPE = 8,
+    SIGKILL = 9,
+    SIGBUS = 10,
+    SIGSEGV = 11,
+    SIGSYS = 12,
+    SIGPIPE = 13,
+    SIGALRM = 14,
+    SIGTERM = 15,
+    SIGURG = 16,
+    SIGSTOP = 17,
+    SIGTSTP = 18,
+    SIGCONT = 19,
+    SIGCHLD = 20,
+    SIGTTIN = 21,
+    SIGTTOU = 22,
+    SIGIO = 23,
+    SIGXCPU = 24,
+    SIGXFSZ = 25,
+    SIGVTALRM = 26,
+    SIGPROF = 27,
+    SIGWINCH = 28,
+    SIGINFO = 29,
+    SIGUSR1 = 30,
+    SIGUSR2 = 31,
+}
+
 /// Mac/iOS Kernel Bad Access Exceptions
 ///
 /// These are the relevant kern_return_t values from [osfmk/mach/kern_return.h][header]
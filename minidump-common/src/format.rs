@@ -909,6 +909,36 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Flags available for use in [`CONTEXT_MIPS.context_flags`] of a 32-bit context
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ContextFlagsMips: u32 {
+        /// The general purpose registers, mdhi, mdlo, epc, badvaddr, status, and cause
+        const CONTEXT_MIPS_INTEGER = 0x00000002 | ContextFlagsCpu::CONTEXT_MIPS.bits();
+        /// The floating point registers, fpcsr, and fir
+        const CONTEXT_MIPS_FLOATING_POINT = 0x00000004 | ContextFlagsCpu::CONTEXT_MIPS.bits();
+        /// hi, lo, and dsp_control
+        const CONTEXT_MIPS_DSP = 0x00000008 | ContextFlagsCpu::CONTEXT_MIPS.bits();
+        const CONTEXT_MIPS_FULL = Self::CONTEXT_MIPS_INTEGER.bits() | Self::CONTEXT_MIPS_FLOATING_POINT.bits();
+        const CONTEXT_MIPS_ALL = Self::CONTEXT_MIPS_FULL.bits() | Self::CONTEXT_MIPS_DSP.bits();
+    }
+}
+
+bitflags! {
+    /// Flags available for use in [`CONTEXT_MIPS.context_flags`] of a 64-bit context
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ContextFlagsMips64: u32 {
+        /// The general purpose registers, mdhi, mdlo, epc, badvaddr, status, and cause
+        const CONTEXT_MIPS64_INTEGER = 0x00000002 | ContextFlagsCpu::CONTEXT_MIPS64.bits();
+        /// The floating point registers, fpcsr, and fir
+        const CONTEXT_MIPS64_FLOATING_POINT = 0x00000004 | ContextFlagsCpu::CONTEXT_MIPS64.bits();
+        /// hi, lo, and dsp_control
+        const CONTEXT_MIPS64_DSP = 0x00000008 | ContextFlagsCpu::CONTEXT_MIPS64.bits();
+        const CONTEXT_MIPS64_FULL = Self::CONTEXT_MIPS64_INTEGER.bits() | Self::CONTEXT_MIPS64_FLOATING_POINT.bits();
+        const CONTEXT_MIPS64_ALL = Self::CONTEXT_MIPS64_FULL.bits() | Self::CONTEXT_MIPS64_DSP.bits();
+    }
+}
+
 /// Possible contents of [`CONTEXT_AMD64::float_save`].
 ///
 /// This struct matches the definition of the struct with the same name from WinNT.h.
@@ -1207,6 +1237,16 @@ pub struct CONTEXT_MIPS {
     pub float_save: FLOATING_SAVE_AREA_MIPS,
 }
 
+impl CONTEXT_MIPS {
+    /// Whether this context came from a 64-bit MIPS process.
+    ///
+    /// The registers of 32-bit contexts are still stored as 64-bit values, but only
+    /// the low 32 bits are meaningful.
+    pub fn is_mips64(&self) -> bool {
+        ContextFlagsCpu::from_flags(self.context_flags).contains(ContextFlagsCpu::CONTEXT_MIPS64)
+    }
+}
+
 /// Offsets into [`CONTEXT_MIPS::iregs`] for registers with a dedicated or conventional purpose
 #[repr(usize)]
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -1245,7 +1285,7 @@ impl MipsRegisterNumbers {
 }
 
 /// PPC floating point state
-#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+#[derive(Debug, Default, Clone, Pread, Pwrite, SizeWith)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct FLOATING_SAVE_AREA_PPC {
     pub fpregs: [u64; 32],
@@ -1254,7 +1294,7 @@ pub struct FLOATING_SAVE_AREA_PPC {
 }
 
 /// PPC vector state
-#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+#[derive(Debug, Default, Clone, Pread, Pwrite, SizeWith)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct VECTOR_SAVE_AREA_PPC {
     pub save_vr: [u128; 32],
@@ -1267,7 +1307,7 @@ pub struct VECTOR_SAVE_AREA_PPC {
 /// A PPC CPU context
 ///
 /// This is a Breakpad extension, as there is no definition of `CONTEXT` for PPC in WinNT.h.
-#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+#[derive(Debug, Default, Clone, Pread, Pwrite, SizeWith)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct CONTEXT_PPC {
     pub context_flags: u32,
@@ -1294,7 +1334,7 @@ pub enum PpcRegisterNumbers {
 /// A PPC64 CPU context
 ///
 /// This is a Breakpad extension, as there is no definition of `CONTEXT` for PPC64 in WinNT.h.
-#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+#[derive(Debug, Default, Clone, Pread, Pwrite, SizeWith)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct CONTEXT_PPC64 {
     pub context_flags: u64,
@@ -1695,12 +1735,20 @@ multi_structs! {
 /// * The following features are already contained inside of [`CONTEXT_AMD64`]:
 ///    * LEGACY_FLOATING_POINT
 ///    * LEGACY_SSE
-///    * GSSE_AND_AVX
+///    * The low halves of the GSSE_AND_AVX registers (which are the SSE registers)
 ///
 /// * If there are XSTATE entries that *actually* map outside of the context's
 /// normal memory range, then the context's [`context_flags`](`CONTEXT_AMD64::context_flags`)
 /// will have bit 0x40 set ([`CONTEXT_HAS_XSTATE`]).
 ///
+/// * Those entries are in the XSAVE area of the "extended context" that
+///   immediately follows the classic context (see [`CONTEXT_EX`]). The XSAVE
+///   area starts with an [`XSAVE_AREA_HEADER`], and the offsets of the entries
+///   are relative to the legacy area that (in the XSAVE format, but not in the
+///   minidump) precedes the header, so subtract 512 from the offset to get the
+///   offset from the header. If the header's `compaction_mask` has bit 63 set,
+///   the entries are instead packed right after the header in order.
+///
 /// * [`ContextFlagsCpu::from_flags`] will mask out the [`CONTEXT_HAS_XSTATE`] bit.
 /// If you want to check for that bit, check the raw value of
 /// [`context_flags`](`CONTEXT_AMD64::context_flags`).
@@ -1769,6 +1817,9 @@ pub enum XstateFeatureIndex {
     AVX512_ZMM_H = 6,
     ACK512_ZMM = 7,
     XSTATE_IPT = 8,
+    XSTATE_PKRU = 9,
+    /// The user-mode CET (shadow stack) state
+    XSTATE_CET_U = 11,
     XSTATE_LWP = 62,
 }
 
@@ -1785,6 +1836,8 @@ impl XstateFeatureIndex {
             6 => Some(AVX512_ZMM_H),
             7 => Some(ACK512_ZMM),
             8 => Some(XSTATE_IPT),
+            9 => Some(XSTATE_PKRU),
+            11 => Some(XSTATE_CET_U),
             62 => Some(XSTATE_LWP),
             _ => None,
         }
@@ -1797,7 +1850,8 @@ pub struct XSTATE_FEATURE {
     /// This entry's offset from the start of the context (in bytes).
     ///
     /// NOTE: THIS VALUE IS A LIE. At least on AMD64 you need to add 256
-    /// to this! See the docs of [`XSTATE_CONFIG_FEATURE_MSC_INFO`].
+    /// to this for the legacy features, and the other features aren't in the
+    /// classic context at all! See the docs of [`XSTATE_CONFIG_FEATURE_MSC_INFO`].
     pub offset: u32,
     /// This entry's size (in bytes).
     pub size: u32,
@@ -1811,6 +1865,40 @@ impl From<u8> for XSTATE_FEATURE {
     }
 }
 
+/// The location of a part of an extended context, see [`CONTEXT_EX`].
+#[derive(Clone, Copy, Debug, Default, Pread, Pwrite, SizeWith, PartialEq, Eq)]
+pub struct CONTEXT_CHUNK {
+    /// The offset of the part, relative to the [`CONTEXT_EX`].
+    pub offset: i32,
+    /// The size of the part (in bytes).
+    pub length: u32,
+}
+
+/// The header of an extended context.
+///
+/// Contexts with [`CONTEXT_HAS_XSTATE`] set are immediately followed by this,
+/// which says where the parts of the whole context are. See the docs of
+/// [`XSTATE_CONFIG_FEATURE_MSC_INFO`].
+#[derive(Clone, Copy, Debug, Default, Pread, Pwrite, SizeWith, PartialEq, Eq)]
+pub struct CONTEXT_EX {
+    /// The whole context, starting at the classic context.
+    pub all: CONTEXT_CHUNK,
+    /// The classic context (e.g. [`CONTEXT_AMD64`]).
+    pub legacy: CONTEXT_CHUNK,
+    /// The XSAVE area, starting with an [`XSAVE_AREA_HEADER`].
+    pub xstate: CONTEXT_CHUNK,
+}
+
+/// The header of the XSAVE area of an extended context, see [`CONTEXT_EX`].
+#[derive(Clone, Copy, Debug, Default, Pread, Pwrite, SizeWith, PartialEq, Eq)]
+pub struct XSAVE_AREA_HEADER {
+    /// The bit `mask[i]` indicates that the state of feature `i` is saved.
+    pub mask: u64,
+    /// If bit 63 is set, the saved features are packed right after this header.
+    pub compaction_mask: u64,
+    pub reserved: [u64; 6],
+}
+
 bitflags! {
     /// Known flags for `MINIDUMP_MISC_INFO*.flags1`
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -1827,6 +1915,238 @@ bitflags! {
     }
 }
 
+/// The system's basic information, part of [`MINIDUMP_SYSTEM_MEMORY_INFO_1`]
+///
+/// This struct matches the Microsoft struct of the same name, from minidumpapiset.h.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_SYSTEM_BASIC_INFORMATION {
+    pub timer_resolution: u32,
+    /// The size of a page in bytes
+    pub page_size: u32,
+    pub number_of_physical_pages: u32,
+    pub lowest_physical_page_number: u32,
+    pub highest_physical_page_number: u32,
+    pub allocation_granularity: u32,
+    pub minimum_user_mode_address: u64,
+    pub maximum_user_mode_address: u64,
+    pub active_processors_affinity_mask: u64,
+    pub number_of_processors: u32,
+}
+
+/// The state of the system's file cache, part of [`MINIDUMP_SYSTEM_MEMORY_INFO_1`]
+///
+/// This struct matches the Microsoft struct of the same name, from minidumpapiset.h.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_SYSTEM_FILECACHE_INFORMATION {
+    pub current_size: u64,
+    pub peak_size: u64,
+    pub page_fault_count: u32,
+    pub minimum_working_set: u64,
+    pub maximum_working_set: u64,
+    pub current_size_including_transition_in_pages: u64,
+    pub peak_size_including_transition_in_pages: u64,
+    pub transition_re_purpose_count: u32,
+    pub flags: u32,
+}
+
+/// The system's commit charge and available memory, in pages, part of
+/// [`MINIDUMP_SYSTEM_MEMORY_INFO_1`]
+///
+/// This struct matches the Microsoft struct of the same name, from minidumpapiset.h.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_SYSTEM_BASIC_PERFORMANCE_INFORMATION {
+    pub available_pages: u64,
+    pub committed_pages: u64,
+    pub commit_limit: u64,
+    pub peak_commitment: u64,
+}
+
+/// The system's performance counters, part of [`MINIDUMP_SYSTEM_MEMORY_INFO_1`]
+///
+/// This struct matches the Microsoft struct of the same name, from minidumpapiset.h.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_SYSTEM_PERFORMANCE_INFORMATION {
+    pub idle_process_time: u64,
+    pub io_read_transfer_count: u64,
+    pub io_write_transfer_count: u64,
+    pub io_other_transfer_count: u64,
+    pub io_read_operation_count: u32,
+    pub io_write_operation_count: u32,
+    pub io_other_operation_count: u32,
+    /// The number of available physical pages (when `BasicPerfInfo` isn't valid)
+    pub available_pages: u32,
+    /// The number of committed pages (when `BasicPerfInfo` isn't valid)
+    pub committed_pages: u32,
+    /// The commit limit in pages (when `BasicPerfInfo` isn't valid)
+    pub commit_limit: u32,
+    /// The peak commitment in pages (when `BasicPerfInfo` isn't valid)
+    pub peak_commitment: u32,
+    pub page_fault_count: u32,
+    pub copy_on_write_count: u32,
+    pub transition_count: u32,
+    pub cache_transition_count: u32,
+    pub demand_zero_count: u32,
+    pub page_read_count: u32,
+    pub page_read_io_count: u32,
+    pub cache_read_count: u32,
+    pub cache_io_count: u32,
+    pub dirty_pages_write_count: u32,
+    pub dirty_write_io_count: u32,
+    pub mapped_pages_write_count: u32,
+    pub mapped_write_io_count: u32,
+    pub paged_pool_pages: u32,
+    pub non_paged_pool_pages: u32,
+    pub paged_pool_allocs: u32,
+    pub paged_pool_frees: u32,
+    pub non_paged_pool_allocs: u32,
+    pub non_paged_pool_frees: u32,
+    pub free_system_ptes: u32,
+    pub resident_system_code_page: u32,
+    pub total_system_driver_pages: u32,
+    pub total_system_code_pages: u32,
+    pub non_paged_pool_lookaside_hits: u32,
+    pub paged_pool_lookaside_hits: u32,
+    pub available_paged_pool_pages: u32,
+    pub resident_system_cache_page: u32,
+    pub resident_paged_pool_page: u32,
+    pub resident_system_driver_page: u32,
+    pub cc_fast_read_no_wait: u32,
+    pub cc_fast_read_wait: u32,
+    pub cc_fast_read_resource_miss: u32,
+    pub cc_fast_read_not_possible: u32,
+    pub cc_fast_mdl_read_no_wait: u32,
+    pub cc_fast_mdl_read_wait: u32,
+    pub cc_fast_mdl_read_resource_miss: u32,
+    pub cc_fast_mdl_read_not_possible: u32,
+    pub cc_map_data_no_wait: u32,
+    pub cc_map_data_wait: u32,
+    pub cc_map_data_no_wait_miss: u32,
+    pub cc_map_data_wait_miss: u32,
+    pub cc_pin_mapped_data_count: u32,
+    pub cc_pin_read_no_wait: u32,
+    pub cc_pin_read_wait: u32,
+    pub cc_pin_read_no_wait_miss: u32,
+    pub cc_pin_read_wait_miss: u32,
+    pub cc_copy_read_no_wait: u32,
+    pub cc_copy_read_wait: u32,
+    pub cc_copy_read_no_wait_miss: u32,
+    pub cc_copy_read_wait_miss: u32,
+    pub cc_mdl_read_no_wait: u32,
+    pub cc_mdl_read_wait: u32,
+    pub cc_mdl_read_no_wait_miss: u32,
+    pub cc_mdl_read_wait_miss: u32,
+    pub cc_read_ahead_ios: u32,
+    pub cc_lazy_write_ios: u32,
+    pub cc_lazy_write_pages: u32,
+    pub cc_data_flushes: u32,
+    pub cc_data_pages: u32,
+    pub context_switches: u32,
+    pub first_level_tb_fills: u32,
+    pub second_level_tb_fills: u32,
+    pub system_calls: u32,
+    pub cc_total_dirty_pages: u64,
+    pub cc_dirty_page_threshold: u64,
+    pub resident_available_pages: i64,
+    pub shared_committed_pages: u64,
+}
+
+/// The state of the system's memory when the minidump was written
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream`]. It matches the
+/// Microsoft struct of the same name, from minidumpapiset.h.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_SYSTEM_MEMORY_INFO_1 {
+    /// The version of the struct, 1
+    pub revision: u16,
+    /// Which of the fields are valid, see [`SystemMemoryInfoFlags`]
+    pub flags: u16,
+    pub basic_info: MINIDUMP_SYSTEM_BASIC_INFORMATION,
+    pub file_cache_info: MINIDUMP_SYSTEM_FILECACHE_INFORMATION,
+    pub basic_perf_info: MINIDUMP_SYSTEM_BASIC_PERFORMANCE_INFORMATION,
+    pub perf_info: MINIDUMP_SYSTEM_PERFORMANCE_INFORMATION,
+}
+
+bitflags! {
+    /// Known flags for `MINIDUMP_SYSTEM_MEMORY_INFO_1.flags`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct SystemMemoryInfoFlags: u16 {
+        const MINIDUMP_SYSMEMINFO1_FILECACHE_TRANSITIONREPURPOSECOUNT_FLAGS = 0x0001;
+        const MINIDUMP_SYSMEMINFO1_BASICPERF = 0x0002;
+        const MINIDUMP_SYSMEMINFO1_PERF_CCTOTALDIRTYPAGES_CCDIRTYPAGETHRESHOLD = 0x0004;
+        const MINIDUMP_SYSMEMINFO1_PERF_RESIDENTAVAILABLEPAGES_SHAREDCOMMITPAGES = 0x0008;
+    }
+}
+
+/// The memory counters of the process when the minidump was written
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::ProcessVmCountersStream`], if its
+/// `revision` is 1. It matches the Microsoft struct of the same name, from minidumpapiset.h.
+/// Sizes are in bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_PROCESS_VM_COUNTERS_1 {
+    pub revision: u16,
+    /// The struct is packed to align 4, so `page_fault_count` is preceded by padding
+    pub _padding: u16,
+    pub page_fault_count: u32,
+    pub peak_working_set_size: u64,
+    pub working_set_size: u64,
+    pub quota_peak_paged_pool_usage: u64,
+    pub quota_paged_pool_usage: u64,
+    pub quota_peak_non_paged_pool_usage: u64,
+    pub quota_non_paged_pool_usage: u64,
+    pub pagefile_usage: u64,
+    pub peak_pagefile_usage: u64,
+    pub private_usage: u64,
+}
+
+/// The memory counters of the process when the minidump was written
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::ProcessVmCountersStream`], if its
+/// `revision` is 2. It matches the Microsoft struct of the same name, from minidumpapiset.h.
+/// Sizes are in bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_PROCESS_VM_COUNTERS_2 {
+    pub revision: u16,
+    /// Which of the fields are valid, see [`ProcessVmCountersFlags`]
+    pub flags: u16,
+    pub page_fault_count: u32,
+    pub peak_working_set_size: u64,
+    pub working_set_size: u64,
+    pub quota_peak_paged_pool_usage: u64,
+    pub quota_paged_pool_usage: u64,
+    pub quota_peak_non_paged_pool_usage: u64,
+    pub quota_non_paged_pool_usage: u64,
+    pub pagefile_usage: u64,
+    pub peak_pagefile_usage: u64,
+    pub peak_virtual_size: u64,
+    pub virtual_size: u64,
+    pub private_usage: u64,
+    pub private_working_set_size: u64,
+    pub shared_commit_usage: u64,
+    pub job_shared_commit_usage: u64,
+    pub job_private_commit_usage: u64,
+    pub job_peak_private_commit_usage: u64,
+    pub job_private_commit_limit: u64,
+    pub job_total_commit_limit: u64,
+}
+
+bitflags! {
+    /// Known flags for `MINIDUMP_PROCESS_VM_COUNTERS_2.flags`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ProcessVmCountersFlags: u16 {
+        /// The fields up to `peak_pagefile_usage`
+        const MINIDUMP_PROCESS_VM_COUNTERS = 0x0001;
+        /// `peak_virtual_size` and `virtual_size`
+        const MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE = 0x0002;
+        /// `private_usage`
+        const MINIDUMP_PROCESS_VM_COUNTERS_EX = 0x0004;
+        /// `private_working_set_size` and `shared_commit_usage`
+        const MINIDUMP_PROCESS_VM_COUNTERS_EX2 = 0x0008;
+        /// The `job_*` fields
+        const MINIDUMP_PROCESS_VM_COUNTERS_JOB = 0x0010;
+    }
+}
+
 /// A list of memory regions in a minidump
 ///
 /// This is the format of the [`MINIDUMP_STREAM_TYPE::MemoryInfoListStream`]. The individual
@@ -2042,6 +2362,55 @@ pub struct DSO_DEBUG_64 {
     pub dynamic: u64,
 }
 
+/// Known types of the entries of the `LinuxAuxv` stream, the auxiliary vector
+/// the kernel passes to a new process (the `AT_*` constants of <elf.h>)
+///
+/// Each entry is a pair of pointer-sized values: its type and its value. The
+/// vector ends with an `AT_NULL` entry.
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, FromPrimitive)]
+pub enum AuxvType {
+    AT_NULL = 0,
+    AT_IGNORE = 1,
+    AT_EXECFD = 2,
+    /// The address of the program headers of the executable
+    AT_PHDR = 3,
+    AT_PHENT = 4,
+    AT_PHNUM = 5,
+    AT_PAGESZ = 6,
+    /// The address the dynamic linker is loaded at
+    AT_BASE = 7,
+    AT_FLAGS = 8,
+    /// The entry point of the executable
+    AT_ENTRY = 9,
+    AT_NOTELF = 10,
+    AT_UID = 11,
+    AT_EUID = 12,
+    AT_GID = 13,
+    AT_EGID = 14,
+    /// The address of a string naming the platform (like "x86_64")
+    AT_PLATFORM = 15,
+    /// The CPU's features, as a bitmask specific to the architecture
+    AT_HWCAP = 16,
+    AT_CLKTCK = 17,
+    AT_SECURE = 23,
+    AT_BASE_PLATFORM = 24,
+    /// The address of 16 random bytes
+    AT_RANDOM = 25,
+    AT_HWCAP2 = 26,
+    AT_RSEQ_FEATURE_SIZE = 27,
+    AT_RSEQ_ALIGN = 28,
+    AT_HWCAP3 = 29,
+    AT_HWCAP4 = 30,
+    /// The address of the path the executable was run with
+    AT_EXECFN = 31,
+    /// The address of the vdso's system call entry point (32-bit x86 only)
+    AT_SYSINFO = 32,
+    /// The address the vdso is loaded at
+    AT_SYSINFO_EHDR = 33,
+    AT_MINSIGSTKSZ = 51,
+}
+
 /// A variable-length UTF-8-encoded string carried within a minidump file.
 ///
 /// See <https://crashpad.chromium.org/doxygen/structcrashpad_1_1MinidumpUTF8String.html>
@@ -2466,6 +2835,122 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Possible values of [`X86CpuInfo::feature_information`]
+    ///
+    /// These are the feature bits `cpuid` returns in `edx` for leaf 1.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct X86CpuFeatures: u32 {
+        const FPU    = (1 << 0);
+        const VME    = (1 << 1);
+        const DE     = (1 << 2);
+        const PSE    = (1 << 3);
+        const TSC    = (1 << 4);
+        const MSR    = (1 << 5);
+        const PAE    = (1 << 6);
+        const MCE    = (1 << 7);
+        const CX8    = (1 << 8);
+        const APIC   = (1 << 9);
+        const SEP    = (1 << 11);
+        const MTRR   = (1 << 12);
+        const PGE    = (1 << 13);
+        const MCA    = (1 << 14);
+        const CMOV   = (1 << 15);
+        const PAT    = (1 << 16);
+        const PSE36  = (1 << 17);
+        const PSN    = (1 << 18);
+        const CLFSH  = (1 << 19);
+        const DS     = (1 << 21);
+        const ACPI   = (1 << 22);
+        const MMX    = (1 << 23);
+        const FXSR   = (1 << 24);
+        const SSE    = (1 << 25);
+        const SSE2   = (1 << 26);
+        const SS     = (1 << 27);
+        const HTT    = (1 << 28);
+        const TM     = (1 << 29);
+        const PBE    = (1 << 31);
+    }
+}
+
+bitflags! {
+    /// Possible values of [`X86CpuInfo::amd_extended_cpu_features`]
+    ///
+    /// These are the feature bits `cpuid` returns in `edx` for leaf 0x80000001
+    /// on AMD processors.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct X86AmdExtendedFeatures: u32 {
+        const SYSCALL  = (1 << 11);
+        const NX       = (1 << 20);
+        const MMXEXT   = (1 << 22);
+        const FXSR_OPT = (1 << 25);
+        const PDPE1GB  = (1 << 26);
+        const RDTSCP   = (1 << 27);
+        const LM       = (1 << 29);
+        const _3DNOWEXT = (1 << 30);
+        const _3DNOW   = (1 << 31);
+    }
+}
+
+bitflags! {
+    /// Possible values of the first element of [`OtherCpuInfo::processor_features`]
+    ///
+    /// Bit `n` is set if `IsProcessorFeaturePresent(n)` returned true on the
+    /// machine that wrote the minidump, so these match the `PF_*` definitions
+    /// from [winnt.h][msdn].
+    ///
+    /// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/processthreadsapi/nf-processthreadsapi-isprocessorfeaturepresent
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ProcessorFeatures: u64 {
+        const PF_FLOATING_POINT_PRECISION_ERRATA       = (1 << 0);
+        const PF_FLOATING_POINT_EMULATED               = (1 << 1);
+        const PF_COMPARE_EXCHANGE_DOUBLE               = (1 << 2);
+        const PF_MMX_INSTRUCTIONS_AVAILABLE            = (1 << 3);
+        const PF_PPC_MOVEMEM_64BIT_OK                  = (1 << 4);
+        const PF_ALPHA_BYTE_INSTRUCTIONS               = (1 << 5);
+        const PF_XMMI_INSTRUCTIONS_AVAILABLE           = (1 << 6);
+        const PF_3DNOW_INSTRUCTIONS_AVAILABLE          = (1 << 7);
+        const PF_RDTSC_INSTRUCTION_AVAILABLE           = (1 << 8);
+        const PF_PAE_ENABLED                           = (1 << 9);
+        const PF_XMMI64_INSTRUCTIONS_AVAILABLE         = (1 << 10);
+        const PF_SSE_DAZ_MODE_AVAILABLE                = (1 << 11);
+        const PF_NX_ENABLED                            = (1 << 12);
+        const PF_SSE3_INSTRUCTIONS_AVAILABLE           = (1 << 13);
+        const PF_COMPARE_EXCHANGE128                   = (1 << 14);
+        const PF_COMPARE64_EXCHANGE128                 = (1 << 15);
+        const PF_CHANNELS_ENABLED                      = (1 << 16);
+        const PF_XSAVE_ENABLED                         = (1 << 17);
+        const PF_ARM_VFP_32_REGISTERS_AVAILABLE        = (1 << 18);
+        const PF_ARM_NEON_INSTRUCTIONS_AVAILABLE       = (1 << 19);
+        const PF_SECOND_LEVEL_ADDRESS_TRANSLATION      = (1 << 20);
+        const PF_VIRT_FIRMWARE_ENABLED                 = (1 << 21);
+        const PF_RDWRFSGSBASE_AVAILABLE                = (1 << 22);
+        const PF_FASTFAIL_AVAILABLE                    = (1 << 23);
+        const PF_ARM_DIVIDE_INSTRUCTION_AVAILABLE      = (1 << 24);
+        const PF_ARM_64BIT_LOADSTORE_ATOMIC            = (1 << 25);
+        const PF_ARM_EXTERNAL_CACHE_AVAILABLE          = (1 << 26);
+        const PF_ARM_FMAC_INSTRUCTIONS_AVAILABLE       = (1 << 27);
+        const PF_RDRAND_INSTRUCTION_AVAILABLE          = (1 << 28);
+        const PF_ARM_V8_INSTRUCTIONS_AVAILABLE         = (1 << 29);
+        const PF_ARM_V8_CRYPTO_INSTRUCTIONS_AVAILABLE  = (1 << 30);
+        const PF_ARM_V8_CRC32_INSTRUCTIONS_AVAILABLE   = (1 << 31);
+        const PF_RDTSCP_INSTRUCTION_AVAILABLE          = (1 << 32);
+        const PF_RDPID_INSTRUCTION_AVAILABLE           = (1 << 33);
+        const PF_ARM_V81_ATOMIC_INSTRUCTIONS_AVAILABLE = (1 << 34);
+        const PF_MONITORX_INSTRUCTION_AVAILABLE        = (1 << 35);
+        const PF_SSSE3_INSTRUCTIONS_AVAILABLE          = (1 << 36);
+        const PF_SSE4_1_INSTRUCTIONS_AVAILABLE         = (1 << 37);
+        const PF_SSE4_2_INSTRUCTIONS_AVAILABLE         = (1 << 38);
+        const PF_AVX_INSTRUCTIONS_AVAILABLE            = (1 << 39);
+        const PF_AVX2_INSTRUCTIONS_AVAILABLE           = (1 << 40);
+        const PF_AVX512F_INSTRUCTIONS_AVAILABLE        = (1 << 41);
+        const PF_ERMS_AVAILABLE                        = (1 << 42);
+        const PF_ARM_V82_DP_INSTRUCTIONS_AVAILABLE     = (1 << 43);
+        const PF_ARM_V83_JSCVT_INSTRUCTIONS_AVAILABLE  = (1 << 44);
+        const PF_ARM_V83_LRCPC_INSTRUCTIONS_AVAILABLE  = (1 << 45);
+    }
+}
+
 #[repr(u32)]
 #[derive(Copy, Clone, PartialEq, Eq, Debug, FromPrimitive)]
 pub enum MINIDUMP_HANDLE_OBJECT_INFORMATION_TYPE {
@@ -2553,11 +3038,174 @@ pub struct MINIDUMP_HANDLE_DATA_STREAM {
     pub reserved: u32,
 }
 
+/// The header of the handle operation list stream, the handle trace of the
+/// process (Windows only; recorded when handle tracing is enabled, e.g. by
+/// Application Verifier).
+///
+/// The header is followed by `number_of_entries` [`AVRF_HANDLE_OPERATION`]s
+/// (`size_of_entry` bytes each).
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_HANDLE_OPERATION_LIST {
+    /// The size of this header, in bytes.
+    pub size_of_header: u32,
+    /// The size of each entry, in bytes.
+    pub size_of_entry: u32,
+    /// The number of entries in the stream.
+    pub number_of_entries: u32,
+    /// Reserved for future use; must be zero.
+    pub reserved: u32,
+}
+
+/// The maximum number of return addresses in an [`AVRF_BACKTRACE_INFORMATION`].
+pub const AVRF_MAX_TRACES: usize = 32;
+
+/// The stack of an operation in an [`AVRF_HANDLE_OPERATION`].
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct AVRF_BACKTRACE_INFORMATION {
+    /// The number of valid return addresses.
+    pub depth: u32,
+    /// The index of the stack in the trace database.
+    pub index: u32,
+    /// The return addresses, innermost first.
+    pub return_addresses: [u64; 32], // AVRF_MAX_TRACES
+}
+
+/// An operation on a handle in a [`MINIDUMP_HANDLE_OPERATION_LIST`].
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct AVRF_HANDLE_OPERATION {
+    /// The handle the operation was on.
+    pub handle: u64,
+    /// The process that did the operation.
+    pub process_id: u32,
+    /// The thread that did the operation.
+    pub thread_id: u32,
+    /// What the operation was, see [`HandleTraceOperation`].
+    pub operation_type: u32,
+    pub spare0: u32,
+    /// Where the operation was done.
+    pub back_trace_information: AVRF_BACKTRACE_INFORMATION,
+}
+
+/// The kinds of operations of an [`AVRF_HANDLE_OPERATION`]
+/// (`eHANDLE_TRACE_OPERATIONS`)
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, FromPrimitive)]
+pub enum HandleTraceOperation {
+    OperationDbUnused = 0,
+    /// The handle was opened (created or duplicated).
+    OperationDbOPEN = 1,
+    /// The handle was closed.
+    OperationDbCLOSE = 2,
+    /// An invalid handle was used.
+    OperationDbBADREF = 3,
+}
+
+/// The header of the token stream, the access tokens of the process (Windows
+/// only; written with `MiniDumpWithTokenInformation`).
+///
+/// The header is followed by `token_list_entries` tokens, each made of a
+/// [`MINIDUMP_TOKEN_INFO_LIST`] and the token's data.
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_TOKEN_INFO_HEADER {
+    /// The size of the whole list of tokens, in bytes (this header included).
+    pub token_list_size: u32,
+    /// The number of tokens in the stream.
+    pub token_list_entries: u32,
+    /// The size of this header, in bytes.
+    pub list_header_size: u32,
+    /// The size of each [`MINIDUMP_TOKEN_INFO_LIST`], in bytes.
+    pub element_header_size: u32,
+}
+
+/// Describes a token in a [`MINIDUMP_TOKEN_INFO_HEADER`].
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_TOKEN_INFO_LIST {
+    /// The size of the token, in bytes (this header included).
+    pub token_size: u32,
+    /// The id of the token.
+    pub token_id: u32,
+    /// The handle of the token in the process.
+    pub token_handle: u64,
+}
+
+/// The mandatory integrity levels of Windows (the `SECURITY_MANDATORY_*_RID`
+/// constants of <winnt.h>), the relative ids of the integrity SIDs (`S-1-16-*`)
+///
+/// Processes started normally run at medium integrity, elevated ones (with
+/// UAC) at high integrity, and services at system integrity.
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, FromPrimitive)]
+pub enum MandatoryLevel {
+    SECURITY_MANDATORY_UNTRUSTED_RID = 0,
+    SECURITY_MANDATORY_LOW_RID = 0x1000,
+    SECURITY_MANDATORY_MEDIUM_RID = 0x2000,
+    SECURITY_MANDATORY_MEDIUM_PLUS_RID = 0x2100,
+    SECURITY_MANDATORY_HIGH_RID = 0x3000,
+    SECURITY_MANDATORY_SYSTEM_RID = 0x4000,
+    SECURITY_MANDATORY_PROTECTED_PROCESS_RID = 0x5000,
+}
+
+/// The header of the function table stream, the dynamic function tables of
+/// the process (registered with e.g. `RtlAddFunctionTable` on Windows, mostly
+/// to describe the code of JITs).
+///
+/// The header is followed by `number_of_descriptors` tables, each made of a
+/// [`MINIDUMP_FUNCTION_TABLE_DESCRIPTOR`], the OS's own descriptor of the table
+/// (`size_of_native_descriptor` bytes), and the table's entries
+/// (`size_of_function_entry` bytes each, e.g. [`IMAGE_AMD64_RUNTIME_FUNCTION_ENTRY`]).
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_FUNCTION_TABLE_STREAM {
+    /// The size of this header, in bytes.
+    pub size_of_header: u32,
+    /// The size of each [`MINIDUMP_FUNCTION_TABLE_DESCRIPTOR`], in bytes.
+    pub size_of_descriptor: u32,
+    /// The size of each native descriptor, in bytes.
+    pub size_of_native_descriptor: u32,
+    /// The size of each function entry, in bytes.
+    pub size_of_function_entry: u32,
+    /// The number of tables in the stream.
+    pub number_of_descriptors: u32,
+    /// The size of the padding between this header and the first table, in bytes.
+    pub size_of_align_pad: u32,
+}
+
+/// Describes a dynamic function table in a [`MINIDUMP_FUNCTION_TABLE_STREAM`].
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_FUNCTION_TABLE_DESCRIPTOR {
+    /// The lowest address of the code the table describes.
+    pub minimum_address: u64,
+    /// The end of the code the table describes (exclusive).
+    pub maximum_address: u64,
+    /// The address the entries of the table are relative to.
+    pub base_address: u64,
+    /// The number of entries in the table.
+    pub entry_count: u32,
+    /// The size of the padding after the table's entries, in bytes.
+    pub size_of_align_pad: u32,
+}
+
+/// A function in an x86-64 function table (`RUNTIME_FUNCTION`), as in the
+/// exception directory of PE files and in dynamic function tables.
+///
+/// The addresses are relative to the base address of the table (the image base
+/// for PE files).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct IMAGE_AMD64_RUNTIME_FUNCTION_ENTRY {
+    /// The start of the function.
+    pub begin_address: u32,
+    /// The end of the function (exclusive).
+    pub end_address: u32,
+    /// The function's unwind info (`UNWIND_INFO`).
+    pub unwind_info_address: u32,
+}
+
 #[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
 pub struct MINIDUMP_THREAD_INFO {
     /// Thread identifier
     pub thread_id: u32,
     /// Thread state flags
+    ///
+    /// See [`ThreadInfoDumpFlags`] for valid values.
     pub dump_flags: u32,
     /// HRESULT value of dump status
     pub dump_error: u32,
@@ -2576,3 +3224,26 @@ pub struct MINIDUMP_THREAD_INFO {
     /// Processor affinity mask
     pub affinity: u64,
 }
+
+bitflags! {
+    /// Potential values for [`MINIDUMP_THREAD_INFO::dump_flags`]
+    ///
+    /// See [Microsoft's documentation][msdn] for details.
+    ///
+    /// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_thread_info
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ThreadInfoDumpFlags: u32 {
+        /// A placeholder for a thread that couldn't be written (see `dump_error`).
+        const MINIDUMP_THREAD_INFO_ERROR_THREAD    = 0x01;
+        /// The thread that wrote the minidump.
+        const MINIDUMP_THREAD_INFO_WRITING_THREAD  = 0x02;
+        /// The thread had exited (but its handle was still open).
+        const MINIDUMP_THREAD_INFO_EXITED_THREAD   = 0x04;
+        /// Only the thread id and `dump_flags` are valid.
+        const MINIDUMP_THREAD_INFO_INVALID_INFO    = 0x08;
+        /// The thread's context couldn't be read.
+        const MINIDUMP_THREAD_INFO_INVALID_CONTEXT = 0x10;
+        /// The thread's TEB couldn't be read.
+        const MINIDUMP_THREAD_INFO_INVALID_TEB     = 0x20;
+    }
+}
@@ -0,0 +1,111 @@
+/*
+ * A C interface to minidump-processor.
+ *
+ * Every object created by one of these functions is owned by the caller, and
+ * must be freed with the matching `_free` function (and nothing else). Strings
+ * passed in are borrowed for the duration of the call, and must be valid,
+ * NUL-terminated UTF-8.
+ *
+ * All functions may be called from any thread. A symbolizer may be used by
+ * several threads at once.
+ */
+
+#ifndef MINIDUMP_PROCESSOR_H
+#define MINIDUMP_PROCESSOR_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The result of a call. */
+typedef enum MinidumpStatus {
+  /* The call succeeded. */
+  MINIDUMP_STATUS_OK = 0,
+  /* An argument was NULL, or a string wasn't valid UTF-8. */
+  MINIDUMP_STATUS_INVALID_ARGUMENT = 1,
+  /* The minidump couldn't be opened or read. */
+  MINIDUMP_STATUS_READ_FAILED = 2,
+  /* The minidump was read, but couldn't be processed. */
+  MINIDUMP_STATUS_PROCESS_FAILED = 3,
+  /* The processing was cancelled with a cancel handle. */
+  MINIDUMP_STATUS_CANCELLED = 4,
+  /* Something went wrong inside the library (a bug). */
+  MINIDUMP_STATUS_INTERNAL_ERROR = 5,
+} MinidumpStatus;
+
+/* Where a symbolizer gets symbols from. Every field may be NULL/0. */
+typedef struct MinidumpSymbolizerConfig {
+  /* Local directories of breakpad symbols, searched first. */
+  const char *const *symbols_paths;
+  size_t symbols_paths_len;
+  /* Symbol servers to download breakpad symbols from. */
+  const char *const *symbols_urls;
+  size_t symbols_urls_len;
+  /* Where to keep downloaded symbols (default: <tmp>/rust-minidump-cache). */
+  const char *symbols_cache;
+  /* Where to write downloads in progress (default: the temp directory). */
+  const char *symbols_tmp;
+  /* How long a single download may take, in seconds (0 for the default). */
+  uint64_t symbols_download_timeout_secs;
+} MinidumpSymbolizerConfig;
+
+/* Processes minidumps (and caches their symbols). */
+typedef struct MinidumpSymbolizer MinidumpSymbolizer;
+
+/* Cancels the processing of a minidump from another thread. */
+typedef struct MinidumpCancelHandle MinidumpCancelHandle;
+
+/*
+ * The message of the last error on the calling thread, or NULL if there wasn't
+ * one. The string is owned by the library, and is only valid until the next
+ * call into the library on the same thread.
+ */
+const char *minidump_last_error_message(void);
+
+/*
+ * Create a symbolizer. `config` may be NULL, for a symbolizer without any
+ * symbols. Returns NULL on failure (see minidump_last_error_message).
+ */
+MinidumpSymbolizer *minidump_symbolizer_new(const MinidumpSymbolizerConfig *config);
+
+/* Free a symbolizer. NULL is ignored. */
+void minidump_symbolizer_free(MinidumpSymbolizer *symbolizer);
+
+/* Create a cancel handle. */
+MinidumpCancelHandle *minidump_cancel_handle_new(void);
+
+/*
+ * Cancel every processing using `handle`, now and in the future. May be called
+ * from any thread, while the processing is running.
+ */
+void minidump_cancel_handle_cancel(const MinidumpCancelHandle *handle);
+
+/* Free a cancel handle, which must not be in use anymore. NULL is ignored. */
+void minidump_cancel_handle_free(MinidumpCancelHandle *handle);
+
+/*
+ * Process the minidump at `path`, producing its report in the JSON schema of
+ * minidump-stackwalk's stable output.
+ *
+ * `cancel` may be NULL. On success, `*out_json` is set to a NUL-terminated
+ * UTF-8 string to be freed with minidump_json_free, and `*out_len` (if it
+ * isn't NULL) to its length without the NUL. On failure they're set to NULL
+ * and 0, and minidump_last_error_message describes the error.
+ */
+MinidumpStatus minidump_process_minidump_file(const MinidumpSymbolizer *symbolizer,
+                                              const char *path,
+                                              const MinidumpCancelHandle *cancel,
+                                              char **out_json,
+                                              size_t *out_len);
+
+/* Free a report returned by minidump_process_minidump_file. NULL is ignored. */
+void minidump_json_free(char *json);
+
+#ifdef __cplusplus
+}  /* extern "C" */
+#endif
+
+#endif  /* MINIDUMP_PROCESSOR_H */
@@ -0,0 +1,580 @@
+//! A C interface to [minidump-processor][minidump_processor], for processing
+//! minidumps from languages other than Rust without going through
+//! minidump-stackwalk's command line.
+//!
+//! The interface is declared in `include/minidump_processor.h`, which also
+//! documents how to use it. In short:
+//!
+//! * [`minidump_symbolizer_new`] creates a symbolizer, which gets symbols from
+//!   local directories and symbol servers (and caches them between dumps).
+//! * [`minidump_process_minidump_file`] processes a minidump with a symbolizer,
+//!   and returns the report as JSON (in the schema of minidump-stackwalk's
+//!   `--json` output, with the stable options).
+//! * [`minidump_cancel_handle_new`] creates a handle that can cancel the
+//!   processing from another thread.
+//! * Functions return a [`MinidumpStatus`], or NULL for the ones that create
+//!   something, and [`minidump_last_error_message`] describes what went wrong.
+//!
+//! Everything the library returns is owned by the caller and freed with the
+//! matching `_free` function. No panic crosses the interface: they're caught
+//! and reported as [`MinidumpStatus::InternalError`].
+
+#![allow(clippy::missing_safety_doc)]
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::ptr;
+use std::time::Duration;
+
+use minidump::Minidump;
+use minidump_processor::ProcessorOptions;
+use minidump_unwind::{
+    http_symbol_supplier_with_options, simple_symbol_supplier, HttpOptions, MultiSymbolProvider,
+    Symbolizer,
+};
+use tokio::runtime::Runtime;
+use tokio::sync::watch;
+
+/// The result of a call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinidumpStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// An argument was NULL, or a string wasn't valid UTF-8.
+    InvalidArgument = 1,
+    /// The minidump couldn't be opened or read.
+    ReadFailed = 2,
+    /// The minidump was read, but couldn't be processed.
+    ProcessFailed = 3,
+    /// The processing was cancelled with a cancel handle.
+    Cancelled = 4,
+    /// Something went wrong inside the library (a bug).
+    InternalError = 5,
+}
+
+/// Where a symbolizer gets symbols from.
+#[repr(C)]
+#[derive(Debug)]
+pub struct MinidumpSymbolizerConfig {
+    /// Local directories of breakpad symbols, searched first.
+    pub symbols_paths: *const *const c_char,
+    pub symbols_paths_len: usize,
+    /// Symbol servers to download breakpad symbols from.
+    pub symbols_urls: *const *const c_char,
+    pub symbols_urls_len: usize,
+    /// Where to keep downloaded symbols (NULL for `<tmp>/rust-minidump-cache`).
+    pub symbols_cache: *const c_char,
+    /// Where to write downloads in progress (NULL for the temp directory).
+    pub symbols_tmp: *const c_char,
+    /// How long a single download may take, in seconds (0 for the default).
+    pub symbols_download_timeout_secs: u64,
+}
+
+/// Processes minidumps, and caches their symbols.
+pub struct MinidumpSymbolizer {
+    runtime: Runtime,
+    provider: MultiSymbolProvider,
+}
+
+/// Cancels the processing of minidumps from another thread.
+pub struct MinidumpCancelHandle {
+    cancelled: watch::Sender<bool>,
+}
+
+/// An error to report to the caller.
+struct Error {
+    status: MinidumpStatus,
+    message: String,
+}
+
+impl Error {
+    fn new(status: MinidumpStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: Option<String>) {
+    // The message can't have a NUL in the middle, so cut it off there.
+    let message = message.map(|message| {
+        let mut bytes = message.into_bytes();
+        bytes.truncate(bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len()));
+        CString::new(bytes).unwrap_or_default()
+    });
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = message);
+}
+
+/// Run the body of an exported function: errors (and panics) are saved as the
+/// last error, and turned into `on_error`.
+fn ffi_boundary<T>(
+    on_error: impl FnOnce(MinidumpStatus) -> T,
+    f: impl FnOnce() -> Result<T, Error>,
+) -> T {
+    set_last_error(None);
+    let error = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => return value,
+        Ok(Err(error)) => error,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown panic");
+            Error::new(
+                MinidumpStatus::InternalError,
+                format!("internal error: {}", message),
+            )
+        }
+    };
+    set_last_error(Some(error.message));
+    on_error(error.status)
+}
+
+/// Borrow a C string argument.
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, Error> {
+    if ptr.is_null() {
+        return Err(Error::new(
+            MinidumpStatus::InvalidArgument,
+            format!("{} is NULL", name),
+        ));
+    }
+    CStr::from_ptr(ptr).to_str().map_err(|_| {
+        Error::new(
+            MinidumpStatus::InvalidArgument,
+            format!("{} isn't valid UTF-8", name),
+        )
+    })
+}
+
+/// Borrow an optional C string argument.
+unsafe fn opt_str_arg<'a>(ptr: *const c_char, name: &str) -> Result<Option<&'a str>, Error> {
+    if ptr.is_null() {
+        Ok(None)
+    } else {
+        str_arg(ptr, name).map(Some)
+    }
+}
+
+/// Borrow an array of C strings argument.
+unsafe fn str_array_arg<'a>(
+    ptr: *const *const c_char,
+    len: usize,
+    name: &str,
+) -> Result<Vec<&'a str>, Error> {
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    if ptr.is_null() {
+        return Err(Error::new(
+            MinidumpStatus::InvalidArgument,
+            format!("{} is NULL", name),
+        ));
+    }
+    std::slice::from_raw_parts(ptr, len)
+        .iter()
+        .enumerate()
+        .map(|(idx, &string)| str_arg(string, &format!("{}[{}]", name, idx)))
+        .collect()
+}
+
+/// The message of the last error on the calling thread, or NULL if there
+/// wasn't one.
+///
+/// The string is only valid until the next call into the library on the same
+/// thread.
+#[no_mangle]
+pub extern "C" fn minidump_last_error_message() -> *const c_char {
+    panic::catch_unwind(|| {
+        LAST_ERROR.with(|last_error| {
+            last_error
+                .borrow()
+                .as_ref()
+                .map_or(ptr::null(), |message| message.as_ptr())
+        })
+    })
+    .unwrap_or(ptr::null())
+}
+
+/// Create a symbolizer, or return NULL on failure.
+///
+/// `config` may be NULL, for a symbolizer without any symbols.
+#[no_mangle]
+pub unsafe extern "C" fn minidump_symbolizer_new(
+    config: *const MinidumpSymbolizerConfig,
+) -> *mut MinidumpSymbolizer {
+    ffi_boundary(
+        |_| ptr::null_mut(),
+        || {
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| {
+                    Error::new(
+                        MinidumpStatus::InternalError,
+                        format!("failed to start the runtime: {}", e),
+                    )
+                })?;
+
+            let mut provider = MultiSymbolProvider::new();
+            if let Some(config) = config.as_ref() {
+                let symbols_paths: Vec<PathBuf> = str_array_arg(
+                    config.symbols_paths,
+                    config.symbols_paths_len,
+                    "symbols_paths",
+                )?
+                .into_iter()
+                .map(PathBuf::from)
+                .collect();
+                let symbols_urls: Vec<String> =
+                    str_array_arg(config.symbols_urls, config.symbols_urls_len, "symbols_urls")?
+                        .into_iter()
+                        .map(String::from)
+                        .collect();
+                let temp_dir = std::env::temp_dir();
+                let symbols_cache = opt_str_arg(config.symbols_cache, "symbols_cache")?
+                    .map_or_else(|| temp_dir.join("rust-minidump-cache"), PathBuf::from);
+                let symbols_tmp =
+                    opt_str_arg(config.symbols_tmp, "symbols_tmp")?.map_or(temp_dir, PathBuf::from);
+
+                if !symbols_urls.is_empty() {
+                    let mut http_options = HttpOptions::default();
+                    if config.symbols_download_timeout_secs != 0 {
+                        http_options.timeout =
+                            Duration::from_secs(config.symbols_download_timeout_secs);
+                    }
+                    // The HTTP client has to be created within the runtime.
+                    let _guard = runtime.enter();
+                    let supplier = http_symbol_supplier_with_options(
+                        symbols_paths,
+                        symbols_urls,
+                        symbols_cache,
+                        symbols_tmp,
+                        http_options,
+                    )
+                    .map_err(|e| {
+                        Error::new(
+                            MinidumpStatus::InternalError,
+                            format!("failed to create the HTTP client: {}", e),
+                        )
+                    })?;
+                    provider.add(Box::new(Symbolizer::new(supplier)));
+                } else if !symbols_paths.is_empty() {
+                    provider.add(Box::new(Symbolizer::new(simple_symbol_supplier(
+                        symbols_paths,
+                    ))));
+                }
+            }
+
+            Ok(Box::into_raw(Box::new(MinidumpSymbolizer {
+                runtime,
+                provider,
+            })))
+        },
+    )
+}
+
+/// Free a symbolizer. NULL is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn minidump_symbolizer_free(symbolizer: *mut MinidumpSymbolizer) {
+    ffi_boundary(
+        |_| (),
+        || {
+            if !symbolizer.is_null() {
+                drop(Box::from_raw(symbolizer));
+            }
+            Ok(())
+        },
+    )
+}
+
+/// Create a cancel handle.
+#[no_mangle]
+pub extern "C" fn minidump_cancel_handle_new() -> *mut MinidumpCancelHandle {
+    ffi_boundary(
+        |_| ptr::null_mut(),
+        || {
+            let (cancelled, _) = watch::channel(false);
+            Ok(Box::into_raw(Box::new(MinidumpCancelHandle { cancelled })))
+        },
+    )
+}
+
+/// Cancel every processing using `handle`, now and in the future.
+#[no_mangle]
+pub unsafe extern "C" fn minidump_cancel_handle_cancel(handle: *const MinidumpCancelHandle) {
+    ffi_boundary(
+        |_| (),
+        || {
+            if let Some(handle) = handle.as_ref() {
+                handle.cancelled.send_replace(true);
+            }
+            Ok(())
+        },
+    )
+}
+
+/// Free a cancel handle, which must not be in use anymore. NULL is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn minidump_cancel_handle_free(handle: *mut MinidumpCancelHandle) {
+    ffi_boundary(
+        |_| (),
+        || {
+            if !handle.is_null() {
+                drop(Box::from_raw(handle));
+            }
+            Ok(())
+        },
+    )
+}
+
+/// Process the minidump at `path`, and return its JSON report in `out_json`
+/// (and its length in `out_len`, if it isn't NULL).
+///
+/// `cancel` may be NULL. The report must be freed with [`minidump_json_free`].
+#[no_mangle]
+pub unsafe extern "C" fn minidump_process_minidump_file(
+    symbolizer: *const MinidumpSymbolizer,
+    path: *const c_char,
+    cancel: *const MinidumpCancelHandle,
+    out_json: *mut *mut c_char,
+    out_len: *mut usize,
+) -> MinidumpStatus {
+    if !out_json.is_null() {
+        *out_json = ptr::null_mut();
+    }
+    if !out_len.is_null() {
+        *out_len = 0;
+    }
+    ffi_boundary(
+        |status| status,
+        || {
+            let symbolizer = symbolizer
+                .as_ref()
+                .ok_or_else(|| Error::new(MinidumpStatus::InvalidArgument, "symbolizer is NULL"))?;
+            let path = str_arg(path, "path")?;
+            if out_json.is_null() {
+                return Err(Error::new(
+                    MinidumpStatus::InvalidArgument,
+                    "out_json is NULL",
+                ));
+            }
+
+            let json = process_minidump_file(symbolizer, path, cancel.as_ref())?;
+            let len = json.as_bytes().len();
+            *out_json = json.into_raw();
+            if !out_len.is_null() {
+                *out_len = len;
+            }
+            Ok(MinidumpStatus::Ok)
+        },
+    )
+}
+
+fn process_minidump_file(
+    symbolizer: &MinidumpSymbolizer,
+    path: &str,
+    cancel: Option<&MinidumpCancelHandle>,
+) -> Result<CString, Error> {
+    let dump = Minidump::read_path(path).map_err(|e| {
+        Error::new(
+            MinidumpStatus::ReadFailed,
+            format!("{} - Error reading dump: {}", e.name(), e),
+        )
+    })?;
+
+    let process = minidump_processor::process_minidump_with_options(
+        &dump,
+        &symbolizer.provider,
+        ProcessorOptions::stable_basic(),
+    );
+    let result = symbolizer.runtime.block_on(async {
+        let Some(cancel) = cancel else {
+            return Some(process.await);
+        };
+        let mut cancelled = cancel.cancelled.subscribe();
+        // Check for cancellation first, so that a dump isn't processed at all
+        // if it was cancelled before it started.
+        tokio::select! {
+            biased;
+            Ok(_) = cancelled.wait_for(|&cancelled| cancelled) => None,
+            result = process => Some(result),
+        }
+    });
+    let state = result
+        .ok_or_else(|| Error::new(MinidumpStatus::Cancelled, "processing was cancelled"))?
+        .map_err(|e| {
+            Error::new(
+                MinidumpStatus::ProcessFailed,
+                format!("{} - Error processing dump: {}", e.name(), e),
+            )
+        })?;
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).map_err(|e| {
+        Error::new(
+            MinidumpStatus::InternalError,
+            format!("failed to write the report: {}", e),
+        )
+    })?;
+    CString::new(json).map_err(|_| {
+        Error::new(
+            MinidumpStatus::InternalError,
+            "the report contains a NUL byte",
+        )
+    })
+}
+
+/// Free a report returned by [`minidump_process_minidump_file`]. NULL is
+/// ignored.
+#[no_mangle]
+pub unsafe extern "C" fn minidump_json_free(json: *mut c_char) {
+    ffi_boundary(
+        |_| (),
+        || {
+            if !json.is_null() {
+                drop(CString::from_raw(json));
+            }
+            Ok(())
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn last_error() -> Option<String> {
+        let message = minidump_last_error_message();
+        if message.is_null() {
+            None
+        } else {
+            Some(
+                unsafe { CStr::from_ptr(message) }
+                    .to_str()
+                    .unwrap()
+                    .to_owned(),
+            )
+        }
+    }
+
+    unsafe fn process(
+        symbolizer: *const MinidumpSymbolizer,
+        path: &str,
+        cancel: *const MinidumpCancelHandle,
+    ) -> (MinidumpStatus, Option<String>) {
+        let path = CString::new(path).unwrap();
+        let mut json = ptr::null_mut();
+        let mut len = 0;
+        let status =
+            minidump_process_minidump_file(symbolizer, path.as_ptr(), cancel, &mut json, &mut len);
+        if json.is_null() {
+            assert_eq!(len, 0);
+            return (status, None);
+        }
+        let report = CStr::from_ptr(json).to_str().unwrap().to_owned();
+        assert_eq!(report.len(), len);
+        minidump_json_free(json);
+        (status, Some(report))
+    }
+
+    #[test]
+    fn test_process() {
+        unsafe {
+            let symbolizer = minidump_symbolizer_new(ptr::null());
+            assert!(!symbolizer.is_null());
+
+            let (status, report) = process(symbolizer, "../testdata/test.dmp", ptr::null());
+            assert_eq!(status, MinidumpStatus::Ok);
+            assert_eq!(last_error(), None);
+            let report: serde_json::Value = serde_json::from_str(&report.unwrap()).unwrap();
+            assert_eq!(
+                report["crash_info"]["type"],
+                "EXCEPTION_ACCESS_VIOLATION_WRITE"
+            );
+
+            let (status, report) = process(symbolizer, "../testdata/missing.dmp", ptr::null());
+            assert_eq!(status, MinidumpStatus::ReadFailed);
+            assert_eq!(report, None);
+            assert!(last_error().unwrap().contains("Error reading dump"));
+
+            minidump_symbolizer_free(symbolizer);
+        }
+    }
+
+    #[test]
+    fn test_cancel() {
+        unsafe {
+            let symbolizer = minidump_symbolizer_new(ptr::null());
+            let cancel = minidump_cancel_handle_new();
+
+            let (status, _) = process(symbolizer, "../testdata/test.dmp", cancel);
+            assert_eq!(status, MinidumpStatus::Ok);
+
+            minidump_cancel_handle_cancel(cancel);
+            let (status, report) = process(symbolizer, "../testdata/test.dmp", cancel);
+            assert_eq!(status, MinidumpStatus::Cancelled);
+            assert_eq!(report, None);
+            assert_eq!(last_error().unwrap(), "processing was cancelled");
+
+            minidump_cancel_handle_free(cancel);
+            minidump_symbolizer_free(symbolizer);
+        }
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        unsafe {
+            let mut json = ptr::null_mut();
+            let path = CString::new("../testdata/test.dmp").unwrap();
+            let status = minidump_process_minidump_file(
+                ptr::null(),
+                path.as_ptr(),
+                ptr::null(),
+                &mut json,
+                ptr::null_mut(),
+            );
+            assert_eq!(status, MinidumpStatus::InvalidArgument);
+            assert_eq!(last_error().unwrap(), "symbolizer is NULL");
+            assert!(json.is_null());
+
+            let paths = [b"symbols\0".as_ptr().cast(), ptr::null()];
+            let config = MinidumpSymbolizerConfig {
+                symbols_paths: paths.as_ptr(),
+                symbols_paths_len: paths.len(),
+                symbols_urls: ptr::null(),
+                symbols_urls_len: 0,
+                symbols_cache: ptr::null(),
+                symbols_tmp: ptr::null(),
+                symbols_download_timeout_secs: 0,
+            };
+            assert!(minidump_symbolizer_new(&config).is_null());
+            assert_eq!(last_error().unwrap(), "symbols_paths[1] is NULL");
+
+            // Freeing NULL is fine
+            minidump_symbolizer_free(ptr::null_mut());
+            minidump_cancel_handle_free(ptr::null_mut());
+            minidump_json_free(ptr::null_mut());
+            assert_eq!(last_error(), None);
+        }
+    }
+
+    #[test]
+    fn test_panics_are_caught() {
+        let status = ffi_boundary(
+            |status| status,
+            || -> Result<MinidumpStatus, Error> { panic!("oh no") },
+        );
+        assert_eq!(status, MinidumpStatus::InternalError);
+        assert_eq!(last_error().unwrap(), "internal error: oh no");
+    }
+}
@@ -0,0 +1,89 @@
+/*
+ * Exercises the C interface: `test_c_api <minidump> <symbols dir> <missing file>`.
+ * Exits with 0 if everything worked.
+ */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "minidump_processor.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                   \
+  do {                                                                \
+    if (!(cond)) {                                                    \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, \
+              #cond);                                                 \
+      failures++;                                                     \
+    }                                                                 \
+  } while (0)
+
+int main(int argc, char **argv) {
+  if (argc != 4) {
+    fprintf(stderr, "usage: %s <minidump> <symbols dir> <missing file>\n", argv[0]);
+    return 2;
+  }
+
+  const char *symbols_paths[] = {argv[2]};
+  MinidumpSymbolizerConfig config;
+  memset(&config, 0, sizeof(config));
+  config.symbols_paths = symbols_paths;
+  config.symbols_paths_len = 1;
+
+  MinidumpSymbolizer *symbolizer = minidump_symbolizer_new(&config);
+  CHECK(symbolizer != NULL);
+  if (symbolizer == NULL) {
+    fprintf(stderr, "error: %s\n", minidump_last_error_message());
+    return 1;
+  }
+
+  /* The happy path */
+  char *json = NULL;
+  size_t len = 0;
+  MinidumpStatus status =
+      minidump_process_minidump_file(symbolizer, argv[1], NULL, &json, &len);
+  CHECK(status == MINIDUMP_STATUS_OK);
+  CHECK(json != NULL);
+  if (json != NULL) {
+    CHECK(len == strlen(json));
+    CHECK(json[0] == '{');
+    CHECK(strstr(json, "\"crash_info\"") != NULL);
+    CHECK(strstr(json, "EXCEPTION_ACCESS_VIOLATION_WRITE") != NULL);
+  }
+  CHECK(minidump_last_error_message() == NULL);
+  minidump_json_free(json);
+
+  /* A minidump that doesn't exist */
+  json = (char *)"not touched";
+  len = 42;
+  status = minidump_process_minidump_file(symbolizer, argv[3], NULL, &json, &len);
+  CHECK(status == MINIDUMP_STATUS_READ_FAILED);
+  CHECK(json == NULL);
+  CHECK(len == 0);
+  const char *message = minidump_last_error_message();
+  CHECK(message != NULL && strstr(message, "Error reading dump") != NULL);
+
+  /* Bad arguments */
+  status = minidump_process_minidump_file(symbolizer, NULL, NULL, &json, NULL);
+  CHECK(status == MINIDUMP_STATUS_INVALID_ARGUMENT);
+  message = minidump_last_error_message();
+  CHECK(message != NULL && strcmp(message, "path is NULL") == 0);
+
+  /* Cancelling */
+  MinidumpCancelHandle *cancel = minidump_cancel_handle_new();
+  CHECK(cancel != NULL);
+  minidump_cancel_handle_cancel(cancel);
+  status = minidump_process_minidump_file(symbolizer, argv[1], cancel, &json, &len);
+  CHECK(status == MINIDUMP_STATUS_CANCELLED);
+  CHECK(json == NULL);
+  minidump_cancel_handle_free(cancel);
+
+  minidump_symbolizer_free(symbolizer);
+
+  if (failures != 0) {
+    fprintf(stderr, "%d checks failed\n", failures);
+    return 1;
+  }
+  return 0;
+}
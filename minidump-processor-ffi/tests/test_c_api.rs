@@ -0,0 +1,50 @@
+//! Builds and runs `c/test_c_api.c` against the library, with the system's C
+//! compiler (`$CC`, or `cc`).
+
+#![cfg(unix)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The directory with the library, next to this test.
+fn library_dir() -> PathBuf {
+    std::env::current_exe()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .to_owned()
+}
+
+#[test]
+fn test_c_api() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let library_dir = library_dir();
+    let test_exe = library_dir.join("test_c_api");
+
+    let cc = std::env::var("CC").unwrap_or_else(|_| String::from("cc"));
+    let status = Command::new(&cc)
+        .arg(manifest_dir.join("tests/c/test_c_api.c"))
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg("-L")
+        .arg(&library_dir)
+        .arg(format!("-Wl,-rpath,{}", library_dir.display()))
+        .arg("-lminidump_processor_ffi")
+        .arg("-o")
+        .arg(&test_exe)
+        .status()
+        .unwrap_or_else(|e| panic!("couldn't run the C compiler ({}): {}", cc, e));
+    assert!(status.success(), "compiling the C test failed");
+
+    let testdata = manifest_dir.join("../testdata");
+    let output = Command::new(&test_exe)
+        .arg(testdata.join("test.dmp"))
+        .arg(testdata.join("symbols"))
+        .arg(testdata.join("missing.dmp"))
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+}
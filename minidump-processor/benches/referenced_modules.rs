@@ -0,0 +1,108 @@
+//! Scanning a 4 MB stack for references to 500 modules.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use minidump::format::MemoryProtection;
+use minidump::system_info::PointerWidth;
+use minidump::{Minidump, MinidumpMemoryInfoList, MinidumpModuleList, UnifiedMemoryInfoList};
+use minidump_processor::referenced_modules::{find_referenced_modules, ModuleRangeIndex};
+use minidump_synth::{DumpString, Memory, MemoryInfo, Module, SynthMinidump};
+use test_assembler::{Endian, Section};
+
+const MODULE_COUNT: u64 = 500;
+const MODULES_BASE: u64 = 0x7ff0_0000_0000;
+/// Every module has this much code, followed by as much read-only data.
+const MODULE_CODE_SIZE: u64 = 0x4_0000;
+const MODULE_STRIDE: u64 = 0x10_0000;
+const STACK_BASE: u64 = 0x7ffd_0000_0000;
+const STACK_SIZE: u64 = 4 * 1024 * 1024;
+
+/// A minidump with the modules, their memory info, and a stack of a mix of
+/// the values stacks have: small integers, pointers into the stack and the
+/// heap, and pointers into modules' code and data.
+fn synth_dump() -> Vec<u8> {
+    let mut dump = SynthMinidump::with_endian(Endian::Little);
+    for i in 0..MODULE_COUNT {
+        let base = MODULES_BASE + i * MODULE_STRIDE;
+        let name = DumpString::new(&format!("module{i}.dll"), Endian::Little);
+        let module = Module::new(
+            Endian::Little,
+            base,
+            2 * MODULE_CODE_SIZE as u32,
+            &name,
+            0,
+            0,
+            None,
+        );
+        let code = MemoryInfo::new(
+            Endian::Little,
+            base,
+            base,
+            0,
+            MODULE_CODE_SIZE,
+            0,
+            MemoryProtection::PAGE_EXECUTE_READ.bits(),
+            0,
+        );
+        let data = MemoryInfo::new(
+            Endian::Little,
+            base + MODULE_CODE_SIZE,
+            base,
+            0,
+            MODULE_CODE_SIZE,
+            0,
+            MemoryProtection::PAGE_READONLY.bits(),
+            0,
+        );
+        dump = dump
+            .add_module(module)
+            .add(name)
+            .add_memory_info(code)
+            .add_memory_info(data);
+    }
+
+    // xorshift, to get the same stack every time
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut random = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    let mut stack = Section::with_endian(Endian::Little);
+    for _ in 0..STACK_SIZE / 8 {
+        let r = random();
+        let module_base = MODULES_BASE + (r >> 32) % MODULE_COUNT * MODULE_STRIDE;
+        let value = match r % 20 {
+            0..=7 => r % 0x1000,
+            8..=11 => STACK_BASE + (r >> 16) % STACK_SIZE,
+            12..=14 => 0x5555_0000_0000 + (r >> 16) % 0x1_0000_0000,
+            15..=17 => module_base + (r >> 16) % MODULE_CODE_SIZE,
+            _ => module_base + MODULE_CODE_SIZE + (r >> 16) % MODULE_CODE_SIZE,
+        };
+        stack = stack.D64(value);
+    }
+    dump = dump.add_memory(Memory::with_section(stack, STACK_BASE));
+    dump.finish().unwrap()
+}
+
+fn bench_referenced_modules(c: &mut Criterion) {
+    let dump = Minidump::read(synth_dump()).unwrap();
+    let modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+    let memory_info =
+        UnifiedMemoryInfoList::new(dump.get_stream::<MinidumpMemoryInfoList>().ok(), None).unwrap();
+    let memory = dump.get_memory().unwrap();
+    let stack = memory.memory_at_address(STACK_BASE).unwrap();
+
+    c.bench_function("index 500 modules", |b| {
+        b.iter(|| ModuleRangeIndex::new(&modules, &memory_info))
+    });
+    let index = ModuleRangeIndex::new(&modules, &memory_info);
+    c.bench_function("scan 4 MB stack", |b| {
+        b.iter(|| {
+            find_referenced_modules(STACK_BASE, &stack, &modules, &index, PointerWidth::Bits64)
+        })
+    });
+}
+
+criterion_group!(benches, bench_referenced_modules);
+criterion_main!(benches);
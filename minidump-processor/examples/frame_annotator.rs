@@ -0,0 +1,71 @@
+//! Tag frames with the team that owns their module, and print the JSON output.
+//!
+//! Usage: `cargo run --example frame_annotator -- <minidump> [symbols-path...]`
+
+use std::path::PathBuf;
+
+use minidump::{Minidump, Module};
+use minidump_common::utils::basename;
+use minidump_processor::{FrameAnnotator, ProcessorOptions};
+use minidump_unwind::{simple_symbol_supplier, CallStack, StackFrame, Symbolizer};
+
+/// Which team owns which modules.
+const OWNERS: &[(&str, &str)] = &[
+    ("ntdll.dll", "platform"),
+    ("kernel32.dll", "platform"),
+    ("test_app.exe", "app"),
+];
+
+struct OwnerAnnotator;
+
+impl FrameAnnotator for OwnerAnnotator {
+    fn annotate_frame(&self, frame: &mut StackFrame, module: Option<&dyn Module>) {
+        let Some(module) = module else {
+            return;
+        };
+        let name = basename(&module.code_file()).to_lowercase();
+        if let Some(&(_, owner)) = OWNERS.iter().find(|&&(module, _)| module == name) {
+            frame
+                .annotations
+                .insert(String::from("owner"), String::from(owner));
+        }
+    }
+
+    fn annotate_thread(&self, thread: &mut CallStack) {
+        // Mark the outermost frame owned by the app, if there is one
+        if let Some(frame) = thread
+            .frames
+            .iter_mut()
+            .rev()
+            .find(|frame| frame.annotations.get("owner").map(String::as_str) == Some("app"))
+        {
+            frame
+                .annotations
+                .insert(String::from("entry_point"), String::from("true"));
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let mut args = std::env::args_os().skip(1);
+    let Some(dump_path) = args.next() else {
+        eprintln!("usage: frame_annotator <minidump> [symbols-path...]");
+        std::process::exit(1);
+    };
+    let symbol_paths: Vec<PathBuf> = args.map(PathBuf::from).collect();
+
+    let dump = Minidump::read_path(&dump_path).expect("couldn't read the minidump");
+    let provider = Symbolizer::new(simple_symbol_supplier(symbol_paths));
+
+    let annotator = OwnerAnnotator;
+    let mut options = ProcessorOptions::default();
+    options.frame_annotator = Some(&annotator);
+
+    let state = minidump_processor::process_minidump_with_options(&dump, &provider, options)
+        .await
+        .expect("couldn't process the minidump");
+    state
+        .print_json(&mut std::io::stdout(), true)
+        .expect("couldn't write the JSON output");
+}
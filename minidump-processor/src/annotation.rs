@@ -0,0 +1,64 @@
+//! Attaching your own information to the frames of a backtrace.
+//!
+//! A [`FrameAnnotator`] passed in
+//! [`ProcessorOptions::frame_annotator`][crate::ProcessorOptions::frame_annotator]
+//! is shown every frame of every thread once the stack walk is done and the
+//! frames are symbolicated. It can add key/value pairs to
+//! [`StackFrame::annotations`] (e.g. the team that owns the frame's module),
+//! which are printed with the frame and emitted as `frames.N.annotations` in
+//! the JSON output.
+//!
+//! ```
+//! use minidump::Module;
+//! use minidump_processor::FrameAnnotator;
+//! use minidump_unwind::StackFrame;
+//!
+//! struct OwnerAnnotator;
+//!
+//! impl FrameAnnotator for OwnerAnnotator {
+//!     fn annotate_frame(&self, frame: &mut StackFrame, module: Option<&dyn Module>) {
+//!         if let Some(module) = module {
+//!             if module.code_file().ends_with("plugin.dll") {
+//!                 frame
+//!                     .annotations
+//!                     .insert("owner".to_owned(), "plugins-team".to_owned());
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::fmt;
+
+use minidump::Module;
+use minidump_unwind::{CallStack, StackFrame};
+
+/// Product-specific post-processing of the frames of a backtrace.
+///
+/// Both methods do nothing by default, so implement whichever is convenient.
+/// See the [module documentation][self] for an example.
+pub trait FrameAnnotator: Sync {
+    /// Called for every frame of every thread, with the module the frame's
+    /// instruction is in (if any).
+    fn annotate_frame(&self, _frame: &mut StackFrame, _module: Option<&dyn Module>) {}
+
+    /// Called for every thread, after [`FrameAnnotator::annotate_frame`] was
+    /// called for all of its frames.
+    fn annotate_thread(&self, _thread: &mut CallStack) {}
+}
+
+impl fmt::Debug for dyn FrameAnnotator + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FrameAnnotator")
+    }
+}
+
+/// Run `annotator` over every frame of `thread`, then over `thread` itself.
+pub(crate) fn annotate_call_stack(annotator: &dyn FrameAnnotator, thread: &mut CallStack) {
+    for frame in &mut thread.frames {
+        // The annotator gets the frame mutably, so it gets its own copy of the module
+        let module = frame.module.clone();
+        annotator.annotate_frame(frame, module.as_ref().map(|module| module as &dyn Module));
+    }
+    annotator.annotate_thread(thread);
+}
@@ -1,5 +1,7 @@
 use minidump::{CpuContext, MinidumpRawContext, UnifiedMemory};
-use minidump_unwind::{CallStack, CallingConvention, FunctionArg, FunctionArgs};
+use minidump_unwind::{
+    CallStack, CallingConvention, FunctionArg, FunctionArgs, HeuristicParameter,
+};
 
 // # Recovering x86 function arguments
 //
@@ -167,6 +169,83 @@ pub fn fill_arguments(call_stack: &mut CallStack, stack_memory: Option<UnifiedMe
     }
 }
 
+// # Reading x86 parameters by their size
+//
+// Breakpad symbols record how many bytes of parameters every FUNC (and STACK
+// WIN record) takes, which on x86 is the size of the arguments the caller
+// pushed before the CALL. That avoids guessing from the function name: the
+// arguments are the `parameter_size / 4` dwords right above the return address.
+//
+// Finding the return address is the hard part, since where it is relative to
+// the frame's own registers depends on how the function was compiled:
+//
+// * With a frame pointer, the function starts with `push ebp; mov ebp, esp`,
+//   so the return address is at `ebp + 4` and the arguments start at `ebp + 8`.
+// * With frame pointer omission (FPO), `ebp` is just another register, and the
+//   return address is `local_size + saved_register_size` (and the size of any
+//   arguments pushed for the next call) above `esp`, as described by the
+//   STACK WIN record.
+//
+// The stack walker already solved this to find the caller: however it was
+// found (frame pointer, STACK WIN/CFI or scanning), the caller's stack pointer
+// is the address right above the callee's return address. So the arguments of
+// a frame start at the stack pointer of the next frame, whichever kind of frame
+// it was, as long as there is a next frame. (That's still true for stdcall
+// functions, which pop their own arguments: the walker reports the stack
+// pointer as it was right after the return address was popped.)
+//
+// There's no way to find the return address of the outermost frame, so it
+// never gets any parameters.
+
+/// The most parameter bytes we'll believe a function has. Anything bigger is
+/// more likely to be a corrupt symbol file than a real function.
+const MAX_PARAMETER_SIZE: u32 = 64 * 4;
+
+/// Read the parameters of every symbolized x86 frame from the stack, according
+/// to their parameter size.
+///
+/// The values aren't symbolized, see [`HeuristicParameter::symbol`].
+pub fn fill_parameters(call_stack: &mut CallStack, stack_memory: Option<UnifiedMemory>) {
+    const POINTER_WIDTH: u64 = 4;
+
+    let Some(mem) = stack_memory else {
+        return;
+    };
+    let caller_stack_pointers: Vec<Option<u64>> = call_stack
+        .frames
+        .iter()
+        .skip(1)
+        .map(|caller| Some(caller.context.get_stack_pointer()))
+        .chain(std::iter::once(None))
+        .collect();
+
+    for (frame, caller_sp) in call_stack.frames.iter_mut().zip(caller_stack_pointers) {
+        let (Some(caller_sp), Some(parameter_size), MinidumpRawContext::X86(_)) =
+            (caller_sp, frame.parameter_size, &frame.context.raw)
+        else {
+            continue;
+        };
+        if parameter_size == 0 || parameter_size > MAX_PARAMETER_SIZE {
+            continue;
+        }
+
+        let count = u64::from(parameter_size).div_ceil(POINTER_WIDTH);
+        let params = (0..count)
+            .map(|idx| {
+                let value = caller_sp
+                    .checked_add(idx * POINTER_WIDTH)
+                    .and_then(|address| mem.get_memory_at_address::<u32>(address))
+                    .map(u64::from);
+                HeuristicParameter {
+                    value,
+                    symbol: None,
+                }
+            })
+            .collect();
+        frame.heuristic_parameters = Some(params);
+    }
+}
+
 fn parse_x86_arg_list(func_name: &str) -> Option<(CallingConvention, Vec<&str>)> {
     if let Some((func_name, arg_list)) = func_name.split_once('(') {
         if let Some((arg_list, _junk)) = arg_list.rsplit_once(')') {
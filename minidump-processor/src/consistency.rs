@@ -0,0 +1,112 @@
+use std::collections::{HashMap, HashSet};
+
+use minidump::{
+    MinidumpLinuxDsoDebug, MinidumpModuleList, MinidumpThreadList, UnifiedMemory, UnifiedMemoryList,
+};
+use minidump_unwind::CallStack;
+
+use crate::process_state::ConsistencyWarning;
+
+// # Consistency checks
+//
+// Corrupt (or carelessly stitched-together) minidumps can have streams that
+// are each fine on their own but disagree with each other. We can't tell which
+// side is right, so these checks only point the disagreements out:
+//
+// * the exception is on a thread that isn't in the thread list (so we don't
+//   know which thread crashed),
+// * several threads have the same id,
+// * a thread's stack pointer isn't in any memory of the minidump (so its
+//   stack can't be walked),
+// * every module was built after the minidump was written (so the minidump's
+//   time is wrong). Looking at single modules doesn't work, because the
+//   timestamps of reproducible builds (like Windows' own libraries) are hashes,
+//   not times.
+// * the dynamic linker has loaded an object that isn't in the module list (so
+//   frames in it can't be symbolicated). The module list of a Linux minidump
+//   comes from the memory mappings, which miss objects mapped in unusual ways.
+
+/// How far a module's timestamp may be ahead of the dump's time before it's
+/// considered to be after it, to allow for clocks that are a bit off.
+const MODULE_TIMESTAMP_SLACK_SECS: u64 = 24 * 60 * 60;
+
+/// Check the parts of the minidump against each other.
+///
+/// `threads` are the (unwalked) stacks of the `thread_list`, in the same order.
+pub fn check_consistency(
+    dump_time: u32,
+    crashing_thread_id: Option<u32>,
+    thread_list: &MinidumpThreadList,
+    threads: &[CallStack],
+    modules: &MinidumpModuleList,
+    memory_list: &UnifiedMemoryList,
+    dso_debug: Option<&MinidumpLinuxDsoDebug>,
+) -> Vec<ConsistencyWarning> {
+    let mut warnings = Vec::new();
+
+    if let Some(thread_id) = crashing_thread_id {
+        if !threads.iter().any(|thread| thread.thread_id == thread_id) {
+            warnings.push(ConsistencyWarning::ExceptionThreadNotFound { thread_id });
+        }
+    }
+
+    let mut thread_id_counts = HashMap::<u32, usize>::new();
+    for thread in threads {
+        *thread_id_counts.entry(thread.thread_id).or_default() += 1;
+    }
+    let mut reported_thread_ids = HashSet::new();
+    for thread in threads {
+        let count = thread_id_counts[&thread.thread_id];
+        if count > 1 && reported_thread_ids.insert(thread.thread_id) {
+            warnings.push(ConsistencyWarning::DuplicateThreadId {
+                thread_id: thread.thread_id,
+                count,
+            });
+        }
+    }
+
+    for (thread, stack) in thread_list.threads.iter().zip(threads) {
+        let Some(frame) = stack.frames.first() else {
+            continue;
+        };
+        let stack_pointer = frame.context.get_stack_pointer();
+        // A stack pointer right at the end of the memory is an empty stack.
+        let in_memory = |memory: Option<UnifiedMemory>| {
+            memory.is_some_and(|memory| {
+                memory.base_address() <= stack_pointer
+                    && stack_pointer - memory.base_address() <= memory.size()
+            })
+        };
+        if !in_memory(thread.stack_memory(memory_list))
+            && !in_memory(memory_list.memory_at_address(stack_pointer))
+        {
+            warnings.push(ConsistencyWarning::StackPointerNotInMemory {
+                thread_id: stack.thread_id,
+                stack_pointer,
+            });
+        }
+    }
+
+    let module_times: Vec<u64> = modules
+        .iter()
+        .map(|module| u64::from(module.raw.time_date_stamp))
+        .filter(|&time| time != 0)
+        .collect();
+    let latest_build_time = u64::from(dump_time) + MODULE_TIMESTAMP_SLACK_SECS;
+    if !module_times.is_empty() && module_times.iter().all(|&time| time > latest_build_time) {
+        warnings.push(ConsistencyWarning::ModulesNewerThanDump {
+            modules: module_times.len(),
+        });
+    }
+
+    if let Some(dso_debug) = dso_debug {
+        for link_map in dso_debug.unlisted_objects(modules) {
+            warnings.push(ConsistencyWarning::ObjectNotInModuleList {
+                name: link_map.name.clone().unwrap_or_default(),
+                load_bias: link_map.load_bias,
+            });
+        }
+    }
+
+    warnings
+}
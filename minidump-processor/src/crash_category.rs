@@ -0,0 +1,245 @@
+//! Classifying crashes by what they imply about the underlying bug.
+//!
+//! Some crashes aren't really caused by the code that crashed: the allocator
+//! noticed that the heap was corrupted, someone freed a bad pointer, or the
+//! process ran out of memory. The bug (or the memory pressure) happened earlier
+//! in time, so these crashes are worth telling apart from a random SIGSEGV.
+//!
+//! The category is determined from the exception code where that's unambiguous
+//! (e.g. `STATUS_HEAP_CORRUPTION` on Windows), and otherwise by looking for the
+//! well-known allocator failure entry points on the crashing thread. The top
+//! frames of the crashing thread are normalized like they are for signatures
+//! (see [`normalize_frame`]) and matched against [`CrashCategoryOptions::rules`]
+//! in order, starting from the top of the stack. Rule patterns may contain `*`
+//! wildcards.
+//!
+//! Custom rules can be added to the defaults to recognize other allocators or
+//! runtimes, optionally with a [`CrashCategory::Other`] category.
+
+use std::fmt;
+
+use minidump::CrashReason;
+use minidump_common::errors::{ExceptionCodeWindows, NtStatusWindows};
+
+use crate::signature::{glob_match, normalize_frame};
+use crate::ProcessState;
+
+/// The default number of frames that are matched against the rules.
+pub const DEFAULT_CATEGORY_FRAMES: usize = 16;
+
+/// What a crash implies about the underlying bug.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CrashCategory {
+    /// The allocator detected that the heap was corrupted, or was passed a
+    /// pointer it never allocated (double frees, frees of invalid pointers...).
+    HeapCorruption,
+    /// The allocator aborted the process after failing one of its own
+    /// safety checks.
+    AllocAbort,
+    /// The process ran out of memory.
+    OutOfMemory,
+    /// A category defined by a custom rule.
+    Other(String),
+}
+
+impl CrashCategory {
+    /// The name of the category, e.g. "HeapCorruption" or "OOM".
+    pub fn name(&self) -> &str {
+        match self {
+            CrashCategory::HeapCorruption => "HeapCorruption",
+            CrashCategory::AllocAbort => "AllocAbort",
+            CrashCategory::OutOfMemory => "OOM",
+            CrashCategory::Other(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for CrashCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A frame pattern that implies a [`CrashCategory`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashCategoryRule {
+    /// Matched against normalized frames. `*` matches any sequence of characters.
+    pub pattern: String,
+    /// The category of crashes with a matching frame.
+    pub category: CrashCategory,
+}
+
+impl CrashCategoryRule {
+    pub fn new(pattern: impl Into<String>, category: CrashCategory) -> Self {
+        Self {
+            pattern: pattern.into(),
+            category,
+        }
+    }
+}
+
+/// Rules that are used by default.
+pub const DEFAULT_CATEGORY_RULES: &[(&str, CrashCategory)] = &[
+    // Windows heap
+    ("RtlReportCriticalFailure", CrashCategory::HeapCorruption),
+    ("RtlpReportHeapFailure", CrashCategory::HeapCorruption),
+    ("RtlpHeapHandleError", CrashCategory::HeapCorruption),
+    ("RtlpHpHeapHandleError", CrashCategory::HeapCorruption),
+    ("RtlpLogHeapFailure", CrashCategory::HeapCorruption),
+    // glibc malloc ("free(): invalid pointer", "corrupted size vs. prev_size"...)
+    ("malloc_printerr", CrashCategory::HeapCorruption),
+    // macOS malloc ("pointer being freed was not allocated"...)
+    ("malloc_zone_error", CrashCategory::HeapCorruption),
+    ("malloc_vreport", CrashCategory::HeapCorruption),
+    ("malloc_report", CrashCategory::HeapCorruption),
+    // PartitionAlloc
+    (
+        "partition_alloc::internal::*DoubleFreeOrCorruptionDetected",
+        CrashCategory::HeapCorruption,
+    ),
+    // jemalloc
+    ("je_safety_check_fail*", CrashCategory::AllocAbort),
+    ("safety_check_fail*", CrashCategory::AllocAbort),
+    ("mozalloc_abort", CrashCategory::AllocAbort),
+    // Rust allocation failures
+    (
+        "alloc::alloc::handle_alloc_error*",
+        CrashCategory::OutOfMemory,
+    ),
+    (
+        "std::alloc::default_alloc_error_hook",
+        CrashCategory::OutOfMemory,
+    ),
+    ("std::alloc::rust_oom", CrashCategory::OutOfMemory),
+    ("__rust_alloc_error_handler", CrashCategory::OutOfMemory),
+    ("__rg_oom", CrashCategory::OutOfMemory),
+    ("__rdl_oom", CrashCategory::OutOfMemory),
+    // C++ allocation failures (throwing std::bad_alloc)
+    ("std::__throw_bad_alloc", CrashCategory::OutOfMemory),
+    ("std::_Xbad_alloc", CrashCategory::OutOfMemory),
+    ("__scrt_throw_std_bad_alloc", CrashCategory::OutOfMemory),
+    // Browser allocation failures
+    ("mozalloc_handle_oom", CrashCategory::OutOfMemory),
+    ("NS_ABORT_OOM*", CrashCategory::OutOfMemory),
+    ("base::internal::OnNoMemory*", CrashCategory::OutOfMemory),
+    (
+        "partition_alloc::internal::OnNoMemory*",
+        CrashCategory::OutOfMemory,
+    ),
+];
+
+/// Configuration for categorizing crashes.
+///
+/// The default is [`DEFAULT_CATEGORY_FRAMES`] frames with [`DEFAULT_CATEGORY_RULES`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashCategoryOptions {
+    /// The maximum number of frames from the top of the crashing thread to look at.
+    pub max_frames: usize,
+    /// The rules, in order of priority for any given frame.
+    pub rules: Vec<CrashCategoryRule>,
+}
+
+impl Default for CrashCategoryOptions {
+    fn default() -> Self {
+        Self {
+            max_frames: DEFAULT_CATEGORY_FRAMES,
+            rules: DEFAULT_CATEGORY_RULES
+                .iter()
+                .map(|(pattern, category)| CrashCategoryRule::new(*pattern, category.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl CrashCategoryOptions {
+    /// The category implied by a normalized frame, if any.
+    pub fn category_of_frame(&self, normalized_frame: &str) -> Option<&CrashCategory> {
+        self.rules
+            .iter()
+            .find(|rule| glob_match(&rule.pattern, normalized_frame))
+            .map(|rule| &rule.category)
+    }
+}
+
+/// Categorize the crash described by `state`.
+///
+/// Returns `None` if nothing about the crash implies a category.
+pub fn categorize_crash(
+    state: &ProcessState,
+    options: &CrashCategoryOptions,
+) -> Option<CrashCategory> {
+    if let Some(category) = state
+        .exception_info
+        .as_ref()
+        .and_then(|info| category_of_reason(info.reason))
+    {
+        return Some(category);
+    }
+
+    let thread = state.threads.get(state.requesting_thread?)?;
+    thread
+        .frames
+        .iter()
+        .take(options.max_frames)
+        .find_map(|frame| options.category_of_frame(&normalize_frame(frame)).cloned())
+}
+
+fn category_of_reason(reason: CrashReason) -> Option<CrashCategory> {
+    match reason {
+        CrashReason::WindowsNtStatus(NtStatusWindows::STATUS_HEAP_CORRUPTION) => {
+            Some(CrashCategory::HeapCorruption)
+        }
+        CrashReason::WindowsNtStatus(NtStatusWindows::STATUS_NO_MEMORY)
+        | CrashReason::WindowsNtStatus(NtStatusWindows::STATUS_COMMITMENT_LIMIT)
+        | CrashReason::WindowsGeneral(ExceptionCodeWindows::OUT_OF_MEMORY) => {
+            Some(CrashCategory::OutOfMemory)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_category_of_frame() {
+        let options = CrashCategoryOptions::default();
+        let cases = [
+            (
+                "RtlReportCriticalFailure",
+                Some(CrashCategory::HeapCorruption),
+            ),
+            ("malloc_printerr", Some(CrashCategory::HeapCorruption)),
+            (
+                "je_safety_check_fail_sized_dealloc",
+                Some(CrashCategory::AllocAbort),
+            ),
+            (
+                "alloc::alloc::handle_alloc_error",
+                Some(CrashCategory::OutOfMemory),
+            ),
+            ("mozalloc_handle_oom", Some(CrashCategory::OutOfMemory)),
+            ("std::_Xbad_alloc", Some(CrashCategory::OutOfMemory)),
+            ("malloc", None),
+            ("my_malloc_printerr_wrapper", None),
+        ];
+        for (frame, category) in cases {
+            assert_eq!(
+                options.category_of_frame(frame),
+                category.as_ref(),
+                "{frame}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_category_names() {
+        assert_eq!(CrashCategory::HeapCorruption.to_string(), "HeapCorruption");
+        assert_eq!(CrashCategory::OutOfMemory.to_string(), "OOM");
+        assert_eq!(
+            CrashCategory::Other(String::from("GpuReset")).to_string(),
+            "GpuReset"
+        );
+    }
+}
@@ -0,0 +1,317 @@
+use std::convert::TryFrom;
+
+use minidump::system_info::PointerWidth;
+use minidump::UnifiedMemoryList;
+use minidump_unwind::CallStack;
+
+// # Recovering panic and assertion messages
+//
+// When a Rust program panics with `panic = "abort"` (or the panic escapes to an
+// `extern "C"` boundary), or a C program trips an `assert`, all a minidump
+// really tells us is that the process received SIGABRT. The message explaining
+// *why* is usually still sitting in memory though, we just have to find it.
+//
+// This is a heuristic analysis in the same spirit as argument recovery: we look
+// for frames belonging to the panic/assertion machinery on the crashing thread
+// and then treat every register and stack slot near that frame as a potential
+// pointer to the message. Any candidate that points to something that *looks*
+// like the message we're after is accepted. If the memory isn't in the dump
+// (very common, minidumps usually only contain stacks) we just give up.
+//
+//
+//
+// ## Rust panics
+//
+// `core::panicking::panic_fmt` takes a `fmt::Arguments`, which begins with a
+// `&[&str]` containing the literal pieces of the format string. For the
+// extremely common `panic!("some literal")` (and `unwrap`/`expect` messages)
+// that's one piece which *is* the message. So we look for a pointer to
+// `(pieces_ptr, pieces_len)` where every piece is a valid `&str`.
+//
+// The formatted arguments themselves can't be recovered (they're type-erased
+// `Display` impls), so they're represented as `{}` between pieces.
+//
+// Failing that, `begin_panic` and friends take a plain `&str`, which we look for
+// as an adjacent `(ptr, len)` pair of stack slots.
+//
+//
+//
+// ## Assertions
+//
+// glibc's `__assert_fail(assertion, file, line, function)` (and macOS's
+// `__assert_rtn`) take C strings, and `__assert_fail_base` formats them into a
+// heap-allocated "prog: file:line: func: Assertion `expr' failed." message. We
+// look for pointers to NUL-terminated strings, preferring one that contains
+// "Assertion", and otherwise settling for the first one we find.
+
+/// The maximum length of a recovered message, in bytes.
+///
+/// Longer messages are truncated.
+pub const MAX_MESSAGE_LEN: usize = 1024;
+
+/// How many stack slots around a matching frame we're willing to inspect.
+const MAX_SCAN_SLOTS: u64 = 256;
+
+/// How many frames from the top of the stack we're willing to look through.
+const MAX_SCAN_FRAMES: usize = 16;
+
+/// The most pieces a `fmt::Arguments` can plausibly have.
+const MAX_FORMAT_PIECES: u64 = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MessageKind {
+    RustPanic,
+    Assertion,
+}
+
+fn message_kind(function_name: &str) -> Option<MessageKind> {
+    // Strip any argument list or template parameters
+    let name = function_name
+        .split(['(', '<'])
+        .next()
+        .unwrap_or(function_name);
+
+    if name.starts_with("core::panicking::panic")
+        || name == "rust_panic"
+        || name == "rust_begin_unwind"
+        || name == "std::panicking::rust_panic"
+        || name == "std::panicking::rust_panic_with_hook"
+        || name.starts_with("std::panicking::begin_panic")
+    {
+        Some(MessageKind::RustPanic)
+    } else if matches!(
+        name,
+        "__assert_fail" | "__assert_fail_base" | "__assert_rtn"
+    ) {
+        Some(MessageKind::Assertion)
+    } else {
+        None
+    }
+}
+
+/// Try to recover the message of a Rust panic or a failed assertion from the
+/// crashing thread.
+///
+/// Returns `None` if the thread doesn't look like it's panicking/asserting, or
+/// if the message couldn't be found in the minidump's memory.
+pub fn recover_crash_message(
+    call_stack: &CallStack,
+    memory_list: &UnifiedMemoryList,
+    pointer_width: PointerWidth,
+) -> Option<String> {
+    let reader = MemoryReader {
+        memory_list,
+        word_size: match pointer_width {
+            PointerWidth::Bits32 => 4,
+            PointerWidth::Bits64 => 8,
+            PointerWidth::Unknown => return None,
+        },
+    };
+
+    let frames = &call_stack.frames;
+    let (frame_idx, kind) =
+        frames
+            .iter()
+            .take(MAX_SCAN_FRAMES)
+            .enumerate()
+            .find_map(|(idx, frame)| {
+                let kind = message_kind(frame.function_name.as_deref()?)?;
+                Some((idx, kind))
+            })?;
+
+    // The message was passed into the matching frame, so it's either still in the
+    // registers of the frame, spilled into the frame's own stack, or spilled into
+    // the stack of whatever it called.
+    let frame = &frames[frame_idx];
+    let callee = frame_idx.checked_sub(1).map(|idx| &frames[idx]);
+
+    let mut candidates: Vec<u64> = callee
+        .into_iter()
+        .chain(Some(frame))
+        .flat_map(|frame| frame.context.valid_registers().map(|(_, val)| val))
+        .collect();
+
+    let scan_start = callee.unwrap_or(frame).context.get_stack_pointer();
+    let scan_end = frames
+        .get(frame_idx + 1)
+        .map(|caller| caller.context.get_stack_pointer())
+        .unwrap_or(u64::MAX)
+        .min(scan_start.saturating_add(MAX_SCAN_SLOTS * reader.word_size));
+    let stack_slots: Vec<u64> = (scan_start..scan_end)
+        .step_by(reader.word_size as usize)
+        .map_while(|addr| reader.read_word(addr))
+        .collect();
+    candidates.extend(&stack_slots);
+
+    match kind {
+        MessageKind::RustPanic => candidates
+            .iter()
+            .find_map(|&ptr| reader.read_fmt_arguments(ptr))
+            .or_else(|| {
+                stack_slots
+                    .windows(2)
+                    .find_map(|pair| reader.read_str(pair[0], pair[1]))
+            }),
+        MessageKind::Assertion => {
+            let strings: Vec<String> = candidates
+                .iter()
+                .filter_map(|&ptr| reader.read_cstr(ptr))
+                .collect();
+            strings
+                .iter()
+                .find(|s| s.contains("Assertion"))
+                .or_else(|| strings.first())
+                .cloned()
+        }
+    }
+}
+
+struct MemoryReader<'a, 'mdmp> {
+    memory_list: &'a UnifiedMemoryList<'mdmp>,
+    word_size: u64,
+}
+
+impl MemoryReader<'_, '_> {
+    fn read_word(&self, addr: u64) -> Option<u64> {
+        let memory = self.memory_list.memory_at_address(addr)?;
+        match self.word_size {
+            4 => memory.get_u32_at(addr).map(u64::from),
+            _ => memory.get_u64_at(addr),
+        }
+    }
+
+    /// Get up to `max_len` bytes starting at `addr`, without crossing the end
+    /// of the memory region.
+    fn read_bytes(&self, addr: u64, max_len: usize) -> Option<&[u8]> {
+        self.memory_list
+            .memory_at_address(addr)?
+            .get_bytes_at(addr, max_len)
+    }
+
+    /// Interpret `(ptr, len)` as a `&str`.
+    fn read_str(&self, ptr: u64, len: u64) -> Option<String> {
+        if len == 0 {
+            return None;
+        }
+        let len = usize::try_from(len).ok()?;
+        let truncated_len = len.min(MAX_MESSAGE_LEN);
+        let bytes = self.read_bytes(ptr, truncated_len)?;
+        if bytes.len() != truncated_len {
+            return None;
+        }
+        let text = if len > truncated_len {
+            // We may have cut a multi-byte character in half
+            utf8_prefix(bytes)
+        } else {
+            std::str::from_utf8(bytes).ok()?
+        };
+        looks_like_text(text).then(|| text.to_owned())
+    }
+
+    /// Interpret `ptr` as a NUL-terminated C string.
+    fn read_cstr(&self, ptr: u64) -> Option<String> {
+        let bytes = self.read_bytes(ptr, MAX_MESSAGE_LEN + 1)?;
+        let text = match bytes.iter().position(|&b| b == 0) {
+            Some(nul) => std::str::from_utf8(&bytes[..nul]).ok()?,
+            // The string is too long, so truncate it
+            None if bytes.len() > MAX_MESSAGE_LEN => utf8_prefix(&bytes[..MAX_MESSAGE_LEN]),
+            // The string runs off the end of the memory region
+            None => return None,
+        };
+        // Very short strings are more likely to be random bytes than a message
+        (text.len() >= 4 && looks_like_text(text)).then(|| text.to_owned())
+    }
+
+    /// Interpret `ptr` as a pointer to a `fmt::Arguments`.
+    fn read_fmt_arguments(&self, ptr: u64) -> Option<String> {
+        let pieces_ptr = self.read_word(ptr)?;
+        let pieces_len = self.read_word(ptr.checked_add(self.word_size)?)?;
+        if pieces_len == 0 || pieces_len > MAX_FORMAT_PIECES {
+            return None;
+        }
+
+        let mut message = String::new();
+        for idx in 0..pieces_len {
+            let piece_addr = pieces_ptr.checked_add(idx * 2 * self.word_size)?;
+            let str_ptr = self.read_word(piece_addr)?;
+            let str_len = self.read_word(piece_addr.checked_add(self.word_size)?)?;
+            if idx > 0 {
+                message.push_str("{}");
+            }
+            // Pieces are allowed to be empty (e.g. `panic!("{}", x)`)
+            if str_len > 0 {
+                message.push_str(&self.read_str(str_ptr, str_len)?);
+            }
+            if message.len() >= MAX_MESSAGE_LEN {
+                message.truncate(utf8_prefix(&message.as_bytes()[..MAX_MESSAGE_LEN]).len());
+                break;
+            }
+        }
+
+        // A message that's entirely placeholders isn't worth reporting
+        (message.len() > 2 * (pieces_len as usize - 1)).then_some(message)
+    }
+}
+
+/// The longest prefix of `bytes` that is valid UTF-8.
+fn utf8_prefix(bytes: &[u8]) -> &str {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default(),
+    }
+}
+
+fn looks_like_text(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_message_kind() {
+        assert_eq!(
+            message_kind("core::panicking::panic_fmt"),
+            Some(MessageKind::RustPanic)
+        );
+        assert_eq!(
+            message_kind("core::panicking::panic"),
+            Some(MessageKind::RustPanic)
+        );
+        assert_eq!(
+            message_kind("std::panicking::rust_panic_with_hook"),
+            Some(MessageKind::RustPanic)
+        );
+        assert_eq!(
+            message_kind("std::panicking::begin_panic<&str>"),
+            Some(MessageKind::RustPanic)
+        );
+        assert_eq!(message_kind("rust_panic"), Some(MessageKind::RustPanic));
+        assert_eq!(message_kind("__assert_fail"), Some(MessageKind::Assertion));
+        assert_eq!(message_kind("__assert_rtn"), Some(MessageKind::Assertion));
+        assert_eq!(message_kind("std::panicking::try"), None);
+        assert_eq!(message_kind("abort"), None);
+        assert_eq!(message_kind("my_rust_panic_handler"), None);
+    }
+
+    #[test]
+    fn test_looks_like_text() {
+        assert!(looks_like_text(
+            "called `Option::unwrap()` on a `None` value"
+        ));
+        assert!(looks_like_text("multi\nline"));
+        assert!(!looks_like_text(""));
+        assert!(!looks_like_text("\u{1}\u{2}"));
+    }
+
+    #[test]
+    fn test_utf8_prefix() {
+        assert_eq!(utf8_prefix(b"hello"), "hello");
+        // A truncated multi-byte character
+        assert_eq!(utf8_prefix(&"h\u{e9}".as_bytes()[..2]), "h");
+    }
+}
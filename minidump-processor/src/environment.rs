@@ -0,0 +1,237 @@
+use minidump::system_info::{Os, PointerWidth};
+use minidump::{MinidumpLinuxCmdLine, MinidumpLinuxEnviron, MinidumpThreadList, UnifiedMemoryList};
+use minidump_unwind::SystemInfo;
+
+use crate::signature::glob_match;
+use crate::ProcessEnvironment;
+
+// # Reconstructing the environment of the process
+//
+// Linux minidumps have the command line and environment of the process in
+// their LinuxCmdLine and LinuxEnviron streams, which are just copies of
+// /proc/self/cmdline and /proc/self/environ.
+//
+// Windows minidumps don't have a stream for them, but they're in the memory
+// of the process:
+//
+// * every TEB points to the PEB (`TEB.ProcessEnvironmentBlock`),
+// * the PEB has the address the executable was loaded at (`PEB.ImageBaseAddress`),
+//   and points to the `RTL_USER_PROCESS_PARAMETERS` (`PEB.ProcessParameters`),
+// * which has the `ImagePathName` and `CommandLine` as `UNICODE_STRING`s, and
+//   points to the `Environment` block: NUL-terminated UTF-16 `NAME=value`
+//   strings, ending with an empty one.
+//
+// Dumps only have that memory if the dump writer captured it (full memory
+// dumps do, and so does anything that explicitly saves the PEB), so every
+// step may fail, and we just return whatever we found until then. Strings
+// that are cut off by the end of a memory region are returned truncated.
+//
+// Environment variables easily contain secrets (tokens, passwords, private
+// paths), so only the ones matching an allow-list are kept.
+
+/// The offsets of the fields we need, for the pointer width of the process.
+struct WindowsLayout {
+    pointer_size: u64,
+    teb_peb: u64,
+    peb_image_base_address: u64,
+    peb_process_parameters: u64,
+    params_image_path_name: u64,
+    params_command_line: u64,
+    params_environment: u64,
+    unicode_string_buffer: u64,
+}
+
+const WINDOWS_LAYOUT_32: WindowsLayout = WindowsLayout {
+    pointer_size: 4,
+    teb_peb: 0x30,
+    peb_image_base_address: 0x08,
+    peb_process_parameters: 0x10,
+    params_image_path_name: 0x38,
+    params_command_line: 0x40,
+    params_environment: 0x48,
+    unicode_string_buffer: 4,
+};
+
+const WINDOWS_LAYOUT_64: WindowsLayout = WindowsLayout {
+    pointer_size: 8,
+    teb_peb: 0x60,
+    peb_image_base_address: 0x10,
+    peb_process_parameters: 0x20,
+    params_image_path_name: 0x60,
+    params_command_line: 0x70,
+    params_environment: 0x80,
+    unicode_string_buffer: 8,
+};
+
+/// The most bytes of the Windows environment block we'll read.
+const MAX_WINDOWS_ENVIRONMENT_SIZE: usize = 64 * 1024;
+
+pub fn process_environment(
+    system_info: &SystemInfo,
+    cmdline: Option<&MinidumpLinuxCmdLine>,
+    environ: Option<&MinidumpLinuxEnviron>,
+    threads: &MinidumpThreadList,
+    memory: &UnifiedMemoryList,
+    allowed_variables: &[String],
+) -> Option<ProcessEnvironment> {
+    let ignore_case = system_info.os == Os::Windows;
+    let allowed = |name: &str| {
+        if ignore_case {
+            let name = name.to_uppercase();
+            allowed_variables
+                .iter()
+                .any(|pattern| glob_match(&pattern.to_uppercase(), &name))
+        } else {
+            allowed_variables
+                .iter()
+                .any(|pattern| glob_match(pattern, name))
+        }
+    };
+
+    let mut environment = ProcessEnvironment::default();
+
+    if let Some(cmdline) = cmdline {
+        let args: Vec<_> = cmdline.args().map(|arg| arg.to_string_lossy()).collect();
+        if !args.is_empty() {
+            environment.command_line = Some(args.join(" "));
+        }
+    }
+    if let Some(environ) = environ {
+        for (name, value) in environ.iter() {
+            let name = name.to_string_lossy();
+            if allowed(&name) {
+                environment
+                    .variables
+                    .insert(name.into_owned(), value.to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    let params = find_peb(system_info, threads, memory).and_then(|(layout, peb)| {
+        read_pointer(memory, layout, peb, layout.peb_process_parameters)
+            .map(|params| (layout, params))
+    });
+    if let Some((layout, params)) = params {
+        environment.executable_path =
+            read_unicode_string(memory, layout, params, layout.params_image_path_name);
+        environment.command_line = environment
+            .command_line
+            .or_else(|| read_unicode_string(memory, layout, params, layout.params_command_line));
+        if let Some(block) = read_pointer(memory, layout, params, layout.params_environment) {
+            for entry in read_environment_block(memory, block) {
+                // Names starting with `=` are the hidden per-drive working
+                // directories (`=C:=C:\foo`), so the separator is the next `=`.
+                let Some(split) = entry.get(1..).and_then(|rest| rest.find('=')) else {
+                    continue;
+                };
+                let (name, value) = (&entry[..split + 1], &entry[split + 2..]);
+                if allowed(name) {
+                    environment
+                        .variables
+                        .insert(name.to_owned(), value.to_owned());
+                }
+            }
+        }
+    }
+
+    (!environment.is_empty()).then_some(environment)
+}
+
+/// The address the executable of the process was loaded at, according to the PEB.
+pub fn process_image_base(
+    system_info: &SystemInfo,
+    threads: &MinidumpThreadList,
+    memory: &UnifiedMemoryList,
+) -> Option<u64> {
+    let (layout, peb) = find_peb(system_info, threads, memory)?;
+    read_pointer(memory, layout, peb, layout.peb_image_base_address)
+}
+
+/// The layout of a Windows process's structures and the address of its PEB.
+fn find_peb(
+    system_info: &SystemInfo,
+    threads: &MinidumpThreadList,
+    memory: &UnifiedMemoryList,
+) -> Option<(&'static WindowsLayout, u64)> {
+    if system_info.os != Os::Windows {
+        return None;
+    }
+    let layout = match system_info.cpu.pointer_width() {
+        PointerWidth::Bits32 => &WINDOWS_LAYOUT_32,
+        PointerWidth::Bits64 => &WINDOWS_LAYOUT_64,
+        PointerWidth::Unknown => return None,
+    };
+    // All threads point to the same PEB, but not all TEBs may have been captured.
+    let peb = threads
+        .threads
+        .iter()
+        .find_map(|thread| read_pointer(memory, layout, thread.raw.teb, layout.teb_peb))?;
+    Some((layout, peb))
+}
+
+/// Read the pointer at `base + offset`.
+fn read_pointer(
+    memory: &UnifiedMemoryList,
+    layout: &WindowsLayout,
+    base: u64,
+    offset: u64,
+) -> Option<u64> {
+    if base == 0 {
+        return None;
+    }
+    let addr = base.checked_add(offset)?;
+    let region = memory.memory_at_address(addr)?;
+    let pointer = if layout.pointer_size == 4 {
+        u64::from(region.get_u32_at(addr)?)
+    } else {
+        region.get_u64_at(addr)?
+    };
+    (pointer != 0).then_some(pointer)
+}
+
+/// The bytes of memory from `addr` to the end of its region, at most `max_len` of them.
+fn read_bytes<'a>(
+    memory: &'a UnifiedMemoryList<'a>,
+    addr: u64,
+    max_len: usize,
+) -> Option<&'a [u8]> {
+    memory.memory_at_address(addr)?.get_bytes_at(addr, max_len)
+}
+
+fn utf16_units(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .collect()
+}
+
+/// Read the `UNICODE_STRING` at `base + offset`.
+fn read_unicode_string(
+    memory: &UnifiedMemoryList,
+    layout: &WindowsLayout,
+    base: u64,
+    offset: u64,
+) -> Option<String> {
+    let addr = base.checked_add(offset)?;
+    // The length (in bytes, without a terminator) is the first field.
+    let len: u16 = memory
+        .memory_at_address(addr)?
+        .get_memory_at_address(addr)?;
+    let buffer = read_pointer(memory, layout, addr, layout.unicode_string_buffer)?;
+    let string = memory
+        .memory_at_address(buffer)?
+        .get_utf16_string_at(buffer, len as usize)?;
+    (!string.is_empty()).then_some(string)
+}
+
+/// Read the strings of the environment block at `addr`.
+fn read_environment_block(memory: &UnifiedMemoryList, addr: u64) -> Vec<String> {
+    let Some(bytes) = read_bytes(memory, addr, MAX_WINDOWS_ENVIRONMENT_SIZE) else {
+        return Vec::new();
+    };
+    utf16_units(bytes)
+        .split(|&unit| unit == 0)
+        .take_while(|entry| !entry.is_empty())
+        .map(String::from_utf16_lossy)
+        .collect()
+}
@@ -0,0 +1,229 @@
+//! Crash annotations from `.extra` files.
+//!
+//! Breakpad-based crash reporters (like Firefox's) write the annotations of a
+//! crash into a `.extra` file next to the minidump, rather than into the
+//! minidump itself. Newer ones write a JSON object of (mostly string) values:
+//!
+//! ```text
+//! {"ProductName":"Firefox","TotalVirtualMemory":"4294836224","MemoryReport":"1"}
+//! ```
+//!
+//! Older ones wrote a `key=value` pair per line, with newlines in values
+//! escaped as `\n` and backslashes as `\\`. [`parse_extra`] reads either, and
+//! the result can be passed to the processor as
+//! [`ProcessorOptions::extra_annotations`][crate::ProcessorOptions::extra_annotations].
+//! The annotations are then copied into the report as they are, and a few of
+//! them are used to process the crash:
+//!
+//! * [`MEMORY_REPORT`]: a [`CrashNote::MemoryReport`] is added.
+//! * [`TOTAL_VIRTUAL_MEMORY`]: the size of the address space, used to decide
+//!   whether [`MemoryUsage::likely_oom`][crate::MemoryUsage::likely_oom] (even if
+//!   the memory info list has no free regions to look at).
+//! * [`ASYNC_SHUTDOWN_TIMEOUT`]: a [`CrashNote::AsyncShutdownTimeout`] is added,
+//!   and a dump without an exception is a [`DumpKind::Hang`].
+//!
+//! Keys are matched ASCII case-insensitively.
+
+use serde_json::{Map, Value};
+
+use crate::process_state::{CrashNote, DumpKind};
+
+/// Present if a memory report was saved along with the crash.
+pub const MEMORY_REPORT: &str = "MemoryReport";
+/// The size (in bytes) of the process's address space.
+pub const TOTAL_VIRTUAL_MEMORY: &str = "TotalVirtualMemory";
+/// Present (with the phase and the blockers, as a JSON string) if the process
+/// was killed for taking too long to shut down.
+pub const ASYNC_SHUTDOWN_TIMEOUT: &str = "AsyncShutdownTimeout";
+
+/// Why a `.extra` file couldn't be parsed.
+#[derive(Debug, thiserror::Error)]
+pub enum ExtraError {
+    #[error("the .extra file isn't valid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("line {0} of the .extra file isn't a key=value pair")]
+    MalformedLine(usize),
+}
+
+/// Parse the contents of a `.extra` file, either JSON or `key=value` lines
+/// (see the [module docs][self]).
+///
+/// The values of `key=value` lines are all strings. Empty lines are ignored.
+pub fn parse_extra(contents: &str) -> Result<Map<String, Value>, ExtraError> {
+    if contents.trim_start().starts_with('{') {
+        return Ok(serde_json::from_str(contents)?);
+    }
+
+    let mut annotations = Map::new();
+    for (idx, line) in contents.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or(ExtraError::MalformedLine(idx + 1))?;
+        annotations.insert(key.to_owned(), Value::String(unescape(value)));
+    }
+    Ok(annotations)
+}
+
+/// Undo the escaping of the values of `key=value` lines.
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('\\') => unescaped.push('\\'),
+            // Not an escape after all
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// The annotation named `key`, ignoring ASCII case.
+fn annotation<'a>(annotations: &'a Map<String, Value>, key: &str) -> Option<&'a Value> {
+    annotations
+        .get(key)
+        .or_else(|| {
+            annotations
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value)
+        })
+        .filter(|value| !value.is_null())
+}
+
+/// The size of the address space, from [`TOTAL_VIRTUAL_MEMORY`].
+pub(crate) fn total_virtual_memory(annotations: &Map<String, Value>) -> Option<u64> {
+    match annotation(annotations, TOTAL_VIRTUAL_MEMORY)? {
+        Value::String(string) => string.trim().parse().ok(),
+        value => value.as_u64(),
+    }
+}
+
+/// Whether the process was killed for hanging during shutdown.
+fn is_shutdown_hang(annotations: &Map<String, Value>) -> bool {
+    annotation(annotations, ASYNC_SHUTDOWN_TIMEOUT).is_some()
+}
+
+/// The notes the annotations add to the crash.
+pub(crate) fn crash_notes(annotations: &Map<String, Value>) -> Vec<CrashNote> {
+    let mut notes = Vec::new();
+    if annotation(annotations, MEMORY_REPORT).is_some() {
+        notes.push(CrashNote::MemoryReport);
+    }
+    if let Some(timeout) = annotation(annotations, ASYNC_SHUTDOWN_TIMEOUT) {
+        // Firefox's is a JSON object in a string: {"phase":"...","conditions":[...]}
+        let phase = match timeout {
+            Value::String(string) => serde_json::from_str::<Value>(string).ok(),
+            value => Some(value.clone()),
+        }
+        .and_then(|timeout| timeout.get("phase")?.as_str().map(String::from));
+        notes.push(CrashNote::AsyncShutdownTimeout { phase });
+    }
+    notes
+}
+
+/// The kind of the dump, taking the annotations into account.
+pub(crate) fn dump_kind(dump_kind: DumpKind, annotations: &Map<String, Value>) -> DumpKind {
+    if dump_kind == DumpKind::Requested && is_shutdown_hang(annotations) {
+        DumpKind::Hang
+    } else {
+        dump_kind
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_json() {
+        let annotations = parse_extra(
+            r#"{"ProductName":"Firefox","TotalVirtualMemory":"4294836224","Nested":{"a":[1,2]}}"#,
+        )
+        .unwrap();
+        assert_eq!(annotations["ProductName"], "Firefox");
+        assert_eq!(annotations["Nested"], json!({"a": [1, 2]}));
+        assert_eq!(total_virtual_memory(&annotations), Some(4294836224));
+
+        assert!(matches!(parse_extra("{\"a\": "), Err(ExtraError::Json(_))));
+    }
+
+    #[test]
+    fn test_parse_key_value() {
+        let annotations = parse_extra(
+            "ProductName=Firefox\r\n\
+             Notes=first line\\nsecond line\\\\n\n\
+             \n\
+             Equation=a=b\n\
+             Empty=\n",
+        )
+        .unwrap();
+        assert_eq!(
+            Value::Object(annotations),
+            json!({
+                "ProductName": "Firefox",
+                "Notes": "first line\nsecond line\\n",
+                "Equation": "a=b",
+                "Empty": "",
+            })
+        );
+
+        assert!(matches!(
+            parse_extra("ProductName=Firefox\nnot an annotation\n"),
+            Err(ExtraError::MalformedLine(2))
+        ));
+        assert!(parse_extra("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_recognized_annotations() {
+        let annotations = parse_extra(
+            "memoryreport=1\n\
+             AsyncShutdownTimeout={\"phase\":\"profile-before-change\",\"conditions\":[]}\n\
+             TotalVirtualMemory=2147352576\n",
+        )
+        .unwrap();
+        assert_eq!(total_virtual_memory(&annotations), Some(2147352576));
+        assert_eq!(
+            crash_notes(&annotations),
+            [
+                CrashNote::MemoryReport,
+                CrashNote::AsyncShutdownTimeout {
+                    phase: Some(String::from("profile-before-change"))
+                },
+            ]
+        );
+        assert_eq!(dump_kind(DumpKind::Requested, &annotations), DumpKind::Hang);
+        assert_eq!(dump_kind(DumpKind::Crash, &annotations), DumpKind::Crash);
+
+        let annotations =
+            parse_extra(r#"{"TotalVirtualMemory":12345,"AsyncShutdownTimeout":"?"}"#).unwrap();
+        assert_eq!(total_virtual_memory(&annotations), Some(12345));
+        assert_eq!(
+            crash_notes(&annotations),
+            [CrashNote::AsyncShutdownTimeout { phase: None }]
+        );
+
+        let annotations = Map::new();
+        assert_eq!(total_virtual_memory(&annotations), None);
+        assert!(crash_notes(&annotations).is_empty());
+        assert_eq!(
+            dump_kind(DumpKind::Requested, &annotations),
+            DumpKind::Requested
+        );
+    }
+}
@@ -72,7 +72,14 @@ async fn main() -> Result<(), ()> {
 "##
 )]
 //!
+//! The same output is also available as a typed [`report::Report`] from
+//! [`ProcessState::to_json_report`], which can also be used to deserialize the JSON.
 //!
+//! Minidumps don't have to be files: [`Minidump::read`][minidump::Minidump::read]
+//! accepts the bytes of a minidump directly (e.g. a `&[u8]` borrowed from a buffer
+//! it was downloaded into, or a `Vec<u8>`), and processing never reads anything
+//! but those bytes and what the [`SymbolProvider`][minidump_unwind::SymbolProvider]
+//! provides.
 //!
 //! [`process_minidump`]: fn.process_minidump.html
 //! [minidump-stackwalk]: https://crates.io/crates/minidump-stackwalk
@@ -82,11 +89,30 @@ async fn main() -> Result<(), ()> {
 #[cfg(all(doctest, feature = "http"))]
 doc_comment::doctest!("../README.md");
 
+mod annotation;
 mod arg_recovery;
+mod consistency;
+pub mod crash_category;
+mod crash_message;
+mod environment;
 mod evil;
+pub mod extra;
+mod main_module;
+mod memory_usage;
 mod op_analysis;
 mod process_state;
 mod processor;
+mod redact;
+pub mod referenced_modules;
+pub mod report;
+pub mod signature;
+pub mod source_context;
+pub mod source_link;
+pub mod symbol_filter;
+mod thread_groups;
+mod thread_identity;
+pub mod thunks;
 
+pub use crate::annotation::FrameAnnotator;
 pub use crate::process_state::*;
 pub use crate::processor::*;
@@ -0,0 +1,48 @@
+use minidump::{MinidumpModuleList, Module};
+use minidump_common::utils::basename;
+
+// # Finding the main module
+//
+// Breakpad and Crashpad always write the executable as the first module, but
+// not every minidump writer does (and nothing in the format says they have
+// to). So we look for it in order of reliability:
+//
+// * the module loaded at the image base address of the process, which Windows
+//   records in the PEB,
+// * the module named like the executable the process was started from (its
+//   path in the process parameters on Windows, or the first argument of its
+//   command line on Linux),
+// * the module with the lowest base address, since executables are usually
+//   loaded below the libraries they use (and the first module is usually the
+//   lowest anyway).
+
+/// Find the index of the main module in `modules` (in the order of the minidump).
+pub fn find_main_module(
+    modules: &MinidumpModuleList,
+    image_base: Option<u64>,
+    image_path: Option<&str>,
+) -> Option<usize> {
+    let by_image_base = || {
+        let image_base = image_base?;
+        modules
+            .iter()
+            .position(|module| module.base_address() == image_base)
+    };
+    let by_image_path = || {
+        let name = basename(image_path?);
+        if name.is_empty() {
+            return None;
+        }
+        modules
+            .iter()
+            .position(|module| basename(&module.code_file()).eq_ignore_ascii_case(name))
+    };
+    let by_address = || {
+        modules
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, module)| module.base_address())
+            .map(|(index, _)| index)
+    };
+    by_image_base().or_else(by_image_path).or_else(by_address)
+}
@@ -0,0 +1,115 @@
+use minidump::{
+    MinidumpMemoryInfo, MinidumpMemoryInfoList, MinidumpProcessVmCounters, MinidumpSystemMemoryInfo,
+};
+use minidump_common::format::{MemoryProtection, MemoryState};
+
+use crate::crash_category::CrashCategory;
+use crate::{MemoryCounters, MemoryUsage};
+
+// # Summarizing the address space
+//
+// A crash that happens while the process is running out of address space
+// (which is easy to do in a 32-bit process) usually looks like any other
+// crash, because the code that crashed just had an allocation fail. The
+// telltale sign is that the largest contiguous free region of the address
+// space is too small for the allocator to grow into.
+//
+// Windows minidumps with full memory info describe every region of the
+// address space, including the free ones, so we can sum up what's committed
+// and reserved and find the largest free block.
+//
+// Full-memory dumps don't always agree with themselves: the memory info list
+// of some of them has overlapping (or duplicated) regions. Regions are
+// processed in address order, and the part of a region that overlaps one that
+// was already counted is ignored, so nothing is counted twice.
+
+/// Summarize the states of the regions in `memory_info`, if there are any.
+///
+/// The crash is considered likely to be an OOM if its category already says so,
+/// or if the largest free block is smaller than `oom_free_block_threshold`. If
+/// the size of the address space is known from elsewhere (`total_virtual_memory`,
+/// see the [`extra`][crate::extra] module), so is what's left of it after the
+/// committed and reserved memory, which is an OOM if it's smaller than that too.
+pub fn compute_memory_usage(
+    memory_info: &MinidumpMemoryInfoList,
+    crash_category: Option<&CrashCategory>,
+    oom_free_block_threshold: u64,
+    total_virtual_memory: Option<u64>,
+) -> Option<MemoryUsage> {
+    let mut regions: Vec<&MinidumpMemoryInfo> = memory_info.iter().collect();
+    if regions.is_empty() {
+        return None;
+    }
+    regions.sort_by_key(|region| region.raw.base_address);
+
+    let mut usage = MemoryUsage::default();
+    let mut counted_end = 0;
+    let mut has_free_regions = false;
+    for region in regions {
+        let base = region.raw.base_address.max(counted_end);
+        let end = region
+            .raw
+            .base_address
+            .saturating_add(region.raw.region_size);
+        if end <= base {
+            continue;
+        }
+        counted_end = end;
+        let size = end - base;
+
+        if region.state.contains(MemoryState::MEM_COMMIT) {
+            usage.total_committed += size;
+            if region
+                .protection
+                .contains(MemoryProtection::PAGE_WRITECOMBINE)
+            {
+                usage.write_combine_committed += size;
+            }
+        } else if region.state.contains(MemoryState::MEM_RESERVE) {
+            usage.total_reserved += size;
+        } else if region.state.contains(MemoryState::MEM_FREE) {
+            has_free_regions = true;
+            usage.total_free += size;
+            if size > usage.largest_free_block {
+                usage.largest_free_block = size;
+                usage.largest_free_block_address = Some(base);
+            }
+        }
+    }
+
+    // Without any free regions the dump isn't describing the whole address space,
+    // so the lack of free blocks doesn't mean anything.
+    let address_space_exhausted =
+        has_free_regions && usage.largest_free_block < oom_free_block_threshold;
+    let virtual_memory_exhausted = total_virtual_memory.is_some_and(|total| {
+        let used = usage.total_committed.saturating_add(usage.total_reserved);
+        total.saturating_sub(used) < oom_free_block_threshold
+    });
+    usage.likely_oom = address_space_exhausted
+        || virtual_memory_exhausted
+        || crash_category == Some(&CrashCategory::OutOfMemory);
+    Some(usage)
+}
+
+/// Collect the memory counters of the system and the process, if the minidump
+/// has either of them.
+pub fn compute_memory_counters(
+    system: Option<&MinidumpSystemMemoryInfo>,
+    process: Option<&MinidumpProcessVmCounters>,
+) -> Option<MemoryCounters> {
+    if system.is_none() && process.is_none() {
+        return None;
+    }
+    Some(MemoryCounters {
+        system_physical_memory: system.map(|info| info.physical_memory()),
+        system_available_physical_memory: system.map(|info| info.available_physical_memory()),
+        system_committed: system.map(|info| info.committed()),
+        system_commit_limit: system.map(|info| info.commit_limit()),
+        working_set_size: process.and_then(|counters| counters.working_set_size()),
+        peak_working_set_size: process.and_then(|counters| counters.peak_working_set_size()),
+        private_usage: process.and_then(|counters| counters.private_usage()),
+        virtual_size: process.and_then(|counters| counters.virtual_size()),
+        peak_virtual_size: process.and_then(|counters| counters.peak_virtual_size()),
+        page_fault_count: process.and_then(|counters| counters.page_fault_count()),
+    })
+}
@@ -10,15 +10,26 @@
 //! a list of currently available architectures and enabling features:
 //!
 //! - `disasm_amd64`: enable analysis of Amd64 instructions (on by default)
+//! - `disasm_x86`: enable disassembly of x86 instructions
+//! - `disasm_arm64`: enable disassembly of ARM64 instructions
 //!
 //! The functions in this module will generally return `OpAnalysisError::UnsupportedCpuArch` if
 //! support for the target CPU is not available.
 
 #![deny(missing_docs)]
 
-use minidump::{MinidumpContext, MinidumpRawContext, UnifiedMemory};
+use crate::{DisassembledInstruction, InstructionBytes, MAX_INSTRUCTION_BYTES};
+use minidump::{MinidumpContext, MinidumpRawContext, UnifiedMemory, UnifiedMemoryInfoList};
 use std::collections::BTreeSet;
 
+/// How many instructions before the crashing one [`disassemble_instructions`] decodes
+#[cfg(any(
+    feature = "disasm_amd64",
+    feature = "disasm_x86",
+    feature = "disasm_arm64"
+))]
+const PRECEDING_INSTRUCTIONS: usize = 3;
+
 /// Error type for the functions in this module
 #[derive(Debug, thiserror::Error)]
 pub enum OpAnalysisError {
@@ -134,6 +145,159 @@ fn get_thread_instruction_bytes<'a>(
         .ok_or(OpAnalysisError::ReadThreadInstructionFailed)
 }
 
+/// Read up to [`MAX_INSTRUCTION_BYTES`] bytes at the instruction pointer of the given thread
+///
+/// `memory_info` is used to tell memory that wasn't captured in the dump apart from memory
+/// that wasn't accessible to begin with.
+pub fn read_instruction_bytes(
+    context: &MinidumpContext,
+    memory_list: &minidump::UnifiedMemoryList,
+    memory_info: &UnifiedMemoryInfoList,
+) -> InstructionBytes {
+    if let Ok(bytes) = get_thread_instruction_bytes(context, memory_list) {
+        let len = bytes.len().min(MAX_INSTRUCTION_BYTES);
+        return InstructionBytes::Captured(bytes[..len].to_vec());
+    }
+
+    // Without any memory info, there's no telling whether the memory existed
+    if memory_info.iter().next().is_none() {
+        return InstructionBytes::NotCaptured;
+    }
+    let accessible = memory_info
+        .memory_info_at_address(context.get_instruction_pointer())
+        .is_some_and(|info| info.is_readable() || info.is_executable());
+    if accessible {
+        InstructionBytes::NotCaptured
+    } else {
+        InstructionBytes::Unmapped
+    }
+}
+
+/// Disassemble the instruction being run by the given thread, and the few instructions
+/// before it
+///
+/// Only the memory region containing the instruction pointer is disassembled. This returns
+/// nothing if disassembly of the target CPU architecture is not supported (or not enabled by
+/// the current feature set), or if the memory pointed to by the instruction pointer is missing
+/// from the memory dump. Instructions that can't be decoded are left out.
+#[cfg_attr(
+    not(any(
+        feature = "disasm_amd64",
+        feature = "disasm_x86",
+        feature = "disasm_arm64"
+    )),
+    allow(unused_variables)
+)]
+pub fn disassemble_instructions(
+    context: &MinidumpContext,
+    memory_list: &minidump::UnifiedMemoryList,
+) -> Vec<DisassembledInstruction> {
+    let instruction_pointer = context.get_instruction_pointer();
+    let Some(memory) = memory_list.memory_at_address(instruction_pointer) else {
+        return Vec::new();
+    };
+    let base_address = memory.base_address();
+    let offset = (instruction_pointer - base_address) as usize;
+    let bytes = memory.bytes();
+
+    match context.raw {
+        #[cfg(feature = "disasm_amd64")]
+        MinidumpRawContext::Amd64(_) => {
+            disassemble_variable_length(bytes, base_address, offset, self::amd64::decode)
+        }
+        #[cfg(feature = "disasm_x86")]
+        MinidumpRawContext::X86(_) => {
+            disassemble_variable_length(bytes, base_address, offset, self::x86::decode)
+        }
+        #[cfg(feature = "disasm_arm64")]
+        MinidumpRawContext::Arm64(_) | MinidumpRawContext::OldArm64(_) => {
+            disassemble_fixed_length(bytes, base_address, offset, 4, self::arm64::decode)
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Disassemble the instruction at `bytes[offset..]` and the few instructions before it, for
+/// architectures whose instructions have different lengths
+///
+/// `decode` decodes the instruction at the start of the given bytes, returning its length and
+/// its text.
+///
+/// There's no telling where the instructions before `offset` start, so this starts decoding at
+/// the earliest point that the preceding instructions could start at, then at the next byte,
+/// and so on, until decoding lands exactly on `offset`. Decoding from the middle of an
+/// instruction usually gets back in sync after an instruction or two, so the last instructions
+/// decoded are likely (but not certainly) right.
+#[cfg(any(feature = "disasm_amd64", feature = "disasm_x86"))]
+fn disassemble_variable_length(
+    bytes: &[u8],
+    base_address: u64,
+    offset: usize,
+    decode: fn(&[u8]) -> Option<(usize, String)>,
+) -> Vec<DisassembledInstruction> {
+    // The longest x86 instruction
+    const MAX_LENGTH: usize = MAX_INSTRUCTION_BYTES - 1;
+
+    let instruction =
+        |start: usize, (len, text): (usize, String), crashing| DisassembledInstruction {
+            address: base_address + start as u64,
+            bytes: bytes[start..start + len].to_vec(),
+            text,
+            crashing,
+        };
+
+    let mut instructions = Vec::new();
+    let earliest_start = offset - offset.min(PRECEDING_INSTRUCTIONS * MAX_LENGTH);
+    for start in earliest_start..offset {
+        let mut preceding = Vec::new();
+        let mut pos = start;
+        while pos < offset {
+            let Some((len, text)) = decode(&bytes[pos..]) else {
+                break;
+            };
+            preceding.push(instruction(pos, (len, text), false));
+            pos += len;
+        }
+        if pos == offset {
+            let extra = preceding.len().saturating_sub(PRECEDING_INSTRUCTIONS);
+            instructions.extend(preceding.drain(extra..));
+            break;
+        }
+    }
+
+    if let Some(decoded) = decode(&bytes[offset..]) {
+        instructions.push(instruction(offset, decoded, true));
+    }
+    instructions
+}
+
+/// Disassemble the instruction at `bytes[offset..]` and the few instructions before it, for
+/// architectures whose instructions are all `len` bytes long
+///
+/// `decode` decodes the given instruction bytes into text.
+#[cfg(feature = "disasm_arm64")]
+fn disassemble_fixed_length(
+    bytes: &[u8],
+    base_address: u64,
+    offset: usize,
+    len: usize,
+    decode: fn(&[u8]) -> Option<String>,
+) -> Vec<DisassembledInstruction> {
+    let first = offset - (offset / len).min(PRECEDING_INSTRUCTIONS) * len;
+    (first..=offset)
+        .step_by(len)
+        .filter_map(|start| {
+            let instruction_bytes = bytes.get(start..start + len)?;
+            Some(DisassembledInstruction {
+                address: base_address + start as u64,
+                bytes: instruction_bytes.to_vec(),
+                text: decode(instruction_bytes)?,
+                crashing: start == offset,
+            })
+        })
+        .collect()
+}
+
 /// Analysis tools for the Amd64 architecture
 #[cfg(feature = "disasm_amd64")]
 mod amd64 {
@@ -427,6 +591,16 @@ mod amd64 {
         }
     }
 
+    /// Decode the Amd64 instruction at the start of `bytes`, returning its length and its text
+    pub fn decode(bytes: &[u8]) -> Option<(usize, String)> {
+        use yaxpeax_arch::LengthedInstruction;
+        let instruction = decode_instruction(bytes).ok()?;
+        Some((
+            instruction.len().to_const() as usize,
+            instruction.to_string(),
+        ))
+    }
+
     /// Decode the given Amd64 instruction using yaxpeax-x86
     ///
     /// # Errors
@@ -444,6 +618,35 @@ mod amd64 {
     }
 }
 
+/// Disassembly for the x86 architecture
+#[cfg(feature = "disasm_x86")]
+mod x86 {
+    /// Decode the x86 instruction at the start of `bytes`, returning its length and its text
+    pub fn decode(bytes: &[u8]) -> Option<(usize, String)> {
+        use yaxpeax_arch::LengthedInstruction;
+        use yaxpeax_x86::protected_mode::InstDecoder;
+        let instruction = InstDecoder::default().decode_slice(bytes).ok()?;
+        Some((
+            instruction.len().to_const() as usize,
+            instruction.to_string(),
+        ))
+    }
+}
+
+/// Disassembly for the ARM64 architecture
+#[cfg(feature = "disasm_arm64")]
+mod arm64 {
+    /// Decode the given ARM64 instruction
+    pub fn decode(bytes: &[u8]) -> Option<String> {
+        use yaxpeax_arch_arm::{Decoder, U8Reader};
+        use yaxpeax_arm::armv8::a64::InstDecoder;
+        let instruction = InstDecoder::default()
+            .decode(&mut U8Reader::new(bytes))
+            .ok()?;
+        Some(instruction.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[cfg(feature = "disasm_amd64")]
@@ -770,5 +973,77 @@ mod tests {
             data.expected_size = 1;
             access_test(&data);
         }
+
+        #[test]
+        fn test_disassemble() {
+            use crate::op_analysis::disassemble_variable_length;
+
+            let bytes = [
+                0xcc, // int3, padding
+                0x55, // push rbp
+                0x48, 0x89, 0xe5, // mov rbp, rsp
+                0x48, 0x8b, 0x07, // mov rax, [rdi]
+                0x48, 0x8b, 0x00, // mov rax, [rax]
+                0x48, 0x8b, // truncated
+            ];
+            let decode = crate::op_analysis::amd64::decode;
+
+            let instructions = disassemble_variable_length(&bytes, 0x1000, 8, decode);
+            let summary: Vec<_> = instructions
+                .iter()
+                .map(|instruction| {
+                    (
+                        instruction.address,
+                        instruction.bytes.len(),
+                        instruction.text.as_str(),
+                        instruction.crashing,
+                    )
+                })
+                .collect();
+            assert_eq!(
+                summary,
+                [
+                    (0x1001, 1, "push rbp", false),
+                    (0x1002, 3, "mov rbp, rsp", false),
+                    (0x1005, 3, "mov rax, qword [rdi]", false),
+                    (0x1008, 3, "mov rax, qword [rax]", true),
+                ]
+            );
+
+            // Nothing before the crashing instruction
+            let instructions = disassemble_variable_length(&bytes, 0x1000, 0, decode);
+            assert_eq!(instructions.len(), 1);
+            assert_eq!(instructions[0].text, "int 0x3");
+
+            // The crashing instruction is cut off by the end of the memory
+            let instructions = disassemble_variable_length(&bytes, 0x1000, 11, decode);
+            assert_eq!(instructions.len(), 3);
+            assert!(instructions.iter().all(|instruction| !instruction.crashing));
+        }
+    }
+
+    #[cfg(feature = "disasm_arm64")]
+    #[test]
+    fn test_disassemble_arm64() {
+        let bytes = [
+            0xfd, 0x7b, 0xbf, 0xa9, // stp x29, x30, [sp, #-0x10]!
+            0x00, 0x00, 0x40, 0xf9, // ldr x0, [x0]
+            0xc0, 0x03, 0x5f, // truncated
+        ];
+        let decode = crate::op_analysis::arm64::decode;
+
+        let instructions =
+            crate::op_analysis::disassemble_fixed_length(&bytes, 0x1000, 4, 4, decode);
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].address, 0x1000);
+        assert!(!instructions[0].crashing);
+        assert_eq!(instructions[1].address, 0x1004);
+        assert_eq!(instructions[1].text, "ldr x0, [x0]");
+        assert!(instructions[1].crashing);
+
+        let instructions =
+            crate::op_analysis::disassemble_fixed_length(&bytes, 0x1000, 8, 4, decode);
+        assert_eq!(instructions.len(), 2);
+        assert!(instructions.iter().all(|instruction| !instruction.crashing));
     }
 }
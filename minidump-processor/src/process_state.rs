@@ -3,19 +3,25 @@
 
 //! The state of a process.
 
-use std::borrow::{Borrow, Cow};
+use std::borrow::Cow;
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap};
 use std::io;
 use std::io::prelude::*;
 use std::time::SystemTime;
 
+use crate::crash_category::CrashCategory;
 use crate::op_analysis::MemoryAccess;
+use crate::report::Report;
+use crate::signature::{compute_signature, SignatureOptions};
+use crate::source_link::SourceLinkOptions;
+use minidump::auxiliary::AuxiliaryCrashInfo;
+use minidump::format::{MINIDUMP_LOCATION_DESCRIPTOR, MINIDUMP_STREAM_TYPE};
 use minidump::system_info::PointerWidth;
 use minidump::*;
+use minidump_common::errors::{ExceptionCodeMac, ExceptionCodeWindows};
 use minidump_common::utils::basename;
 use minidump_unwind::{CallStack, CallStackInfo, SymbolStats, SystemInfo};
-use serde_json::json;
 
 #[derive(Default)]
 struct SerializationContext {
@@ -105,24 +111,136 @@ impl From<MinidumpLinuxLsbRelease<'_>> for LinuxStandardBase {
     }
 }
 
-#[derive(Debug, Clone)]
+impl LinuxStandardBase {
+    /// A string naming the distribution, like "Ubuntu 22.04.1 LTS".
+    ///
+    /// This is the description if there is one, otherwise the id and release.
+    pub fn distro(&self) -> Option<String> {
+        if !self.description.is_empty() {
+            return Some(self.description.clone());
+        }
+        let distro = format!("{} {}", self.id, self.release);
+        let distro = distro.trim();
+        (!distro.is_empty()).then(|| distro.to_owned())
+    }
+}
+
+/// What /proc/self/status says about the process.
+#[derive(Debug, Clone, Default)]
 pub struct LinuxProcStatus {
     pub pid: u32,
+    /// The number of threads (`Threads`).
+    pub threads: Option<u32>,
+    /// The peak size of the virtual memory (`VmPeak`), in bytes.
+    pub vm_peak: Option<u64>,
+    /// The size of the virtual memory (`VmSize`), in bytes.
+    pub vm_size: Option<u64>,
+    /// The peak resident set size (`VmHWM`), in bytes.
+    pub vm_hwm: Option<u64>,
+    /// The resident set size (`VmRSS`), in bytes.
+    pub vm_rss: Option<u64>,
+    /// The size of the memory that's swapped out (`VmSwap`), in bytes.
+    pub vm_swap: Option<u64>,
+    /// The mask of the blocked signals (`SigBlk`), with bit N-1 for signal N.
+    pub sig_blk: Option<u64>,
+    /// The mask of the ignored signals (`SigIgn`).
+    pub sig_ign: Option<u64>,
+    /// The mask of the signals with a handler (`SigCgt`).
+    pub sig_cgt: Option<u64>,
 }
 
 impl From<MinidumpLinuxProcStatus<'_>> for LinuxProcStatus {
     fn from(status: MinidumpLinuxProcStatus) -> Self {
-        let pid = status
-            .iter()
-            .find(|entry| entry.0.as_bytes() == b"Pid")
-            .map_or(0, |key_val| {
-                key_val.1.to_string_lossy().parse::<u32>().unwrap_or(0)
-            });
-        LinuxProcStatus { pid }
+        let mut proc_status = LinuxProcStatus::default();
+        for (key, val) in status.iter() {
+            let val = val.to_string_lossy();
+            // Sizes are in kB, like "VmPeak:    12345 kB"
+            let size = || {
+                let kb = val.strip_suffix("kB").unwrap_or(&val).trim();
+                kb.parse::<u64>().ok()?.checked_mul(1024)
+            };
+            let mask = || u64::from_str_radix(&val, 16).ok();
+            match key.as_bytes() {
+                b"Pid" => proc_status.pid = val.parse().unwrap_or(0),
+                b"Threads" => proc_status.threads = val.parse().ok(),
+                b"VmPeak" => proc_status.vm_peak = size(),
+                b"VmSize" => proc_status.vm_size = size(),
+                b"VmHWM" => proc_status.vm_hwm = size(),
+                b"VmRSS" => proc_status.vm_rss = size(),
+                b"VmSwap" => proc_status.vm_swap = size(),
+                b"SigBlk" => proc_status.sig_blk = mask(),
+                b"SigIgn" => proc_status.sig_ign = mask(),
+                b"SigCgt" => proc_status.sig_cgt = mask(),
+                _ => {}
+            }
+        }
+        proc_status
+    }
+}
+
+impl LinuxProcStatus {
+    /// The signal numbers in a signal mask (like [`LinuxProcStatus::sig_cgt`]).
+    pub fn signals(mask: u64) -> impl Iterator<Item = u32> {
+        (0..64)
+            .filter(move |bit| mask & (1 << bit) != 0)
+            .map(|bit| bit + 1)
+    }
+}
+
+/// What /proc/cpuinfo says about the CPU.
+///
+/// The fields are from the first processor listed, as they're usually the same
+/// for all of them (except on ARM systems mixing different cores).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinuxCpuInfo {
+    /// The number of processors listed.
+    pub processor_count: usize,
+    /// The vendor, like "GenuineIntel" (`vendor_id`), or the implementer code
+    /// of ARM CPUs, like "0x41" (`CPU implementer`).
+    pub vendor: Option<String>,
+    /// The name of the CPU, like "Intel(R) Core(TM) i7-8700 CPU @ 3.20GHz"
+    /// (`model name`, or `Processor` on older ARM kernels).
+    pub model_name: Option<String>,
+    /// The version of the microcode (x86 only).
+    pub microcode_version: Option<u64>,
+    /// The features of the CPU, like "sse4_2" (`flags`) or "asimd" (`Features`).
+    pub flags: Vec<String>,
+}
+
+impl From<MinidumpLinuxCpuInfo<'_>> for LinuxCpuInfo {
+    fn from(cpu_info: MinidumpLinuxCpuInfo) -> Self {
+        let mut info = LinuxCpuInfo::default();
+        for (key, val) in cpu_info.iter() {
+            let key = key.as_bytes();
+            if key == b"processor" {
+                info.processor_count += 1;
+            }
+            if info.processor_count > 1 {
+                continue;
+            }
+            let val = val.to_string_lossy();
+            let string = || Some(val.clone().into_owned()).filter(|val| !val.is_empty());
+            match key {
+                b"vendor_id" | b"CPU implementer" => info.vendor = string(),
+                b"model name" => info.model_name = string(),
+                b"Processor" if info.model_name.is_none() => info.model_name = string(),
+                b"microcode" => {
+                    info.microcode_version = match val.strip_prefix("0x") {
+                        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                        None => val.parse().ok(),
+                    }
+                }
+                b"flags" | b"Features" => {
+                    info.flags = val.split_whitespace().map(String::from).collect();
+                }
+                _ => {}
+            }
+        }
+        info
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Limit {
     Error,
     Unlimited,
@@ -142,6 +260,37 @@ impl serde::Serialize for Limit {
     }
 }
 
+impl<'de> serde::Deserialize<'de> for Limit {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct LimitVisitor;
+
+        impl serde::de::Visitor<'_> for LimitVisitor {
+            type Value = Limit;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a number, \"err\" or \"unlimited\"")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, val: u64) -> Result<Limit, E> {
+                Ok(Limit::Limited(val))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, val: &str) -> Result<Limit, E> {
+                match val {
+                    "err" => Ok(Limit::Error),
+                    "unlimited" => Ok(Limit::Unlimited),
+                    _ => Err(E::invalid_value(serde::de::Unexpected::Str(val), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(LimitVisitor)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LinuxProcLimit {
     pub soft: Limit,
@@ -196,6 +345,235 @@ impl From<MinidumpLinuxProcLimits<'_>> for LinuxProcLimits {
     }
 }
 
+/// Something notable about a crash, found by the processor's analyses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrashNote {
+    /// The crashing instruction is in the given module, and there are symbols
+    /// for it, but none of them cover the instruction.
+    ///
+    /// See [`SymbolDisposition::NoCoveringSymbol`][minidump_unwind::SymbolDisposition::NoCoveringSymbol].
+    NoCoveringSymbol { module: String },
+    /// The crash reporter saved a memory report along with the crash (see
+    /// [`extra::MEMORY_REPORT`][crate::extra::MEMORY_REPORT]).
+    MemoryReport,
+    /// The process was killed for hanging while it was shutting down, in the
+    /// given phase of shutdown if known (see
+    /// [`extra::ASYNC_SHUTDOWN_TIMEOUT`][crate::extra::ASYNC_SHUTDOWN_TIMEOUT]).
+    AsyncShutdownTimeout { phase: Option<String> },
+}
+
+impl CrashNote {
+    /// A short name for the kind of note (e.g. "NoCoveringSymbol").
+    pub fn name(&self) -> &'static str {
+        match self {
+            CrashNote::NoCoveringSymbol { .. } => "NoCoveringSymbol",
+            CrashNote::MemoryReport => "MemoryReport",
+            CrashNote::AsyncShutdownTimeout { .. } => "AsyncShutdownTimeout",
+        }
+    }
+}
+
+impl std::fmt::Display for CrashNote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrashNote::NoCoveringSymbol { module } => write!(
+                f,
+                "the crashing instruction is in {module} but outside every function of its \
+                 symbols (possibly a hook or injected code)"
+            ),
+            CrashNote::MemoryReport => write!(f, "a memory report was saved with the crash"),
+            CrashNote::AsyncShutdownTimeout { phase: Some(phase) } => write!(
+                f,
+                "the process was killed for hanging during shutdown (in {phase})"
+            ),
+            CrashNote::AsyncShutdownTimeout { phase: None } => {
+                write!(f, "the process was killed for hanging during shutdown")
+            }
+        }
+    }
+}
+
+/// A part of the minidump that disagrees with another part, found by the
+/// processor's consistency checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsistencyWarning {
+    /// The exception is on a thread that isn't in the thread list, so the
+    /// crashing thread is unknown.
+    ExceptionThreadNotFound { thread_id: u32 },
+    /// Several threads of the thread list have the same id.
+    DuplicateThreadId { thread_id: u32, count: usize },
+    /// A thread's stack pointer isn't in its stack memory or any other memory
+    /// of the minidump.
+    StackPointerNotInMemory { thread_id: u32, stack_pointer: u64 },
+    /// Every module (with a timestamp) was built after the minidump was
+    /// written, so the minidump's time is probably wrong.
+    ModulesNewerThanDump { modules: usize },
+    /// The dynamic linker has loaded an object (at `load_bias`) that isn't in
+    /// the module list.
+    ObjectNotInModuleList { name: String, load_bias: u64 },
+}
+
+impl ConsistencyWarning {
+    /// A short, machine-readable name for the kind of warning (e.g.
+    /// "ExceptionThreadNotFound").
+    pub fn code(&self) -> &'static str {
+        match self {
+            ConsistencyWarning::ExceptionThreadNotFound { .. } => "ExceptionThreadNotFound",
+            ConsistencyWarning::DuplicateThreadId { .. } => "DuplicateThreadId",
+            ConsistencyWarning::StackPointerNotInMemory { .. } => "StackPointerNotInMemory",
+            ConsistencyWarning::ModulesNewerThanDump { .. } => "ModulesNewerThanDump",
+            ConsistencyWarning::ObjectNotInModuleList { .. } => "ObjectNotInModuleList",
+        }
+    }
+}
+
+impl std::fmt::Display for ConsistencyWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsistencyWarning::ExceptionThreadNotFound { thread_id } => write!(
+                f,
+                "the exception is on thread {thread_id}, which isn't in the thread list, so the \
+                 crashing thread is unknown"
+            ),
+            ConsistencyWarning::DuplicateThreadId { thread_id, count } => {
+                write!(f, "{count} threads have the id {thread_id}")
+            }
+            ConsistencyWarning::StackPointerNotInMemory {
+                thread_id,
+                stack_pointer,
+            } => write!(
+                f,
+                "the stack pointer {stack_pointer:#x} of thread {thread_id} isn't in any memory \
+                 of the minidump"
+            ),
+            ConsistencyWarning::ModulesNewerThanDump { modules } => write!(
+                f,
+                "all {modules} modules were built after the minidump was written, so its time \
+                 is probably wrong"
+            ),
+            ConsistencyWarning::ObjectNotInModuleList { name, load_bias } => write!(
+                f,
+                "the dynamic linker has loaded {name} (with a load bias of {load_bias:#x}), \
+                 which isn't in the module list"
+            ),
+        }
+    }
+}
+
+/// A part of the report that privacy mode withheld or hashed (see
+/// [`ProcessorOptions::privacy_mode`][crate::ProcessorOptions::privacy_mode]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RedactedSection {
+    /// [`ProcessState::environment`] was withheld.
+    Environment,
+    /// The recovered arguments and heuristic parameters of the frames (values
+    /// from the stack) were withheld.
+    FunctionArguments,
+    /// [`ExceptionInfo::message`] was replaced with a hash.
+    CrashMessage,
+    /// [`ProcessState::assertion`] was replaced with a hash.
+    Assertion,
+    /// The values of the crash annotations with sensitive names were replaced
+    /// with hashes.
+    CrashAnnotations,
+    /// The messages of the auxiliary crashes were replaced with hashes, and
+    /// their unparsed contents were withheld.
+    AuxiliaryCrashes,
+    /// [`ProcessState::mac_crash_info`] was withheld.
+    MacCrashInfo,
+}
+
+impl RedactedSection {
+    /// A short, machine-readable name for the section (e.g. "environment").
+    pub fn name(&self) -> &'static str {
+        match self {
+            RedactedSection::Environment => "environment",
+            RedactedSection::FunctionArguments => "function_arguments",
+            RedactedSection::CrashMessage => "crash_message",
+            RedactedSection::Assertion => "assertion",
+            RedactedSection::CrashAnnotations => "crash_annotations",
+            RedactedSection::AuxiliaryCrashes => "auxiliary_crashes",
+            RedactedSection::MacCrashInfo => "mac_crash_info",
+        }
+    }
+}
+
+impl std::fmt::Display for RedactedSection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RedactedSection::Environment => "the command line and environment were withheld",
+            RedactedSection::FunctionArguments => "the function arguments were withheld",
+            RedactedSection::CrashMessage => "the crash message was hashed",
+            RedactedSection::Assertion => "the assertion was hashed",
+            RedactedSection::CrashAnnotations => {
+                "the values of sensitive crash annotations were hashed"
+            }
+            RedactedSection::AuxiliaryCrashes => "the messages of auxiliary crashes were hashed",
+            RedactedSection::MacCrashInfo => "the macOS crash info was withheld",
+        })
+    }
+}
+
+/// Why the minidump was written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DumpKind {
+    /// The process crashed.
+    Crash,
+    /// The process was hung (e.g. a hang or deadlock was detected by a
+    /// watchdog).
+    Hang,
+    /// The dump was taken on demand, without anything going wrong.
+    Requested,
+}
+
+impl DumpKind {
+    /// Infer the kind of a dump from its exception and crashpad annotations.
+    ///
+    /// A dump is a crash if it has an exception, unless the exception is one
+    /// that crashpad and the OS use to take dumps without crashing ("simulated"
+    /// exceptions, which mean the dump was requested) or reports a possible
+    /// deadlock (a hang). A dump without an exception is a hang if it has a
+    /// `hang` crashpad annotation set to "1" or "true", and requested otherwise.
+    pub fn infer(
+        exception: Option<&ExceptionInfo>,
+        crashpad_info: Option<&MinidumpCrashpadInfo>,
+    ) -> DumpKind {
+        match exception.map(|info| info.reason) {
+            Some(CrashReason::WindowsGeneral(ExceptionCodeWindows::SIMULATED))
+            | Some(CrashReason::MacGeneral(ExceptionCodeMac::SIMULATED, _)) => DumpKind::Requested,
+            Some(CrashReason::WindowsGeneral(
+                ExceptionCodeWindows::EXCEPTION_POSSIBLE_DEADLOCK,
+            )) => DumpKind::Hang,
+            Some(_) => DumpKind::Crash,
+            None => {
+                let is_hang = crashpad_info
+                    .and_then(|info| info.simple_annotations.get("hang"))
+                    .is_some_and(|value| value == "1" || value.eq_ignore_ascii_case("true"));
+                if is_hang {
+                    DumpKind::Hang
+                } else {
+                    DumpKind::Requested
+                }
+            }
+        }
+    }
+
+    /// The name of the kind, e.g. "crash".
+    pub fn name(&self) -> &'static str {
+        match self {
+            DumpKind::Crash => "crash",
+            DumpKind::Hang => "hang",
+            DumpKind::Requested => "requested",
+        }
+    }
+}
+
+impl std::fmt::Display for DumpKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 /// Info about an exception that may have occurred
 ///
 /// May not be available if the minidump wasn't triggered by an exception, or if required
@@ -217,12 +595,241 @@ pub struct ExceptionInfo {
     pub adjusted_address: Option<AdjustedAddress>,
     /// A string representing the crashing instruction (if available)
     pub instruction_str: Option<String>,
+    /// The bytes at the instruction pointer of the crashing thread.
+    pub instruction_bytes: InstructionBytes,
+    /// The crashing instruction and the few instructions before it, decoded.
+    ///
+    /// This is empty if disassembling the CPU isn't enabled (see the
+    /// `disasm_amd64`, `disasm_x86` and `disasm_arm64` features) or the
+    /// instruction bytes weren't captured. Finding the start of the instructions
+    /// before the crashing one is guesswork on x86, so those may be wrong.
+    pub disassembly: Vec<DisassembledInstruction>,
     /// A list of memory accesses performed by crashing instruction (if available)
     pub memory_accesses: Option<Vec<MemoryAccess>>,
     /// Possible valid addresses which are one flipped bit away from the crashing address or adjusted address.
     ///
     /// The original address was possibly the result of faulty hardware, alpha particles, etc.
     pub possible_bit_flips: Vec<PossibleBitFlip>,
+    /// The message of a Rust panic or failed assertion that caused the crash (if available).
+    ///
+    /// This is heuristically recovered from the memory of the crashing thread and
+    /// truncated to a reasonable length.
+    pub message: Option<String>,
+    /// Details decoded from the parameters of the exception (if any).
+    pub detail: Option<CrashReasonDetail>,
+    /// Anything else notable the processor found out about the crash.
+    pub notes: Vec<CrashNote>,
+}
+
+/// The bytes at the instruction pointer of the crashing thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstructionBytes {
+    /// Up to [`MAX_INSTRUCTION_BYTES`] bytes at the instruction pointer.
+    ///
+    /// There are fewer if the memory in the dump ends first.
+    Captured(Vec<u8>),
+    /// The instruction pointer isn't in accessible memory, according to the
+    /// memory info of the dump.
+    Unmapped,
+    /// The dump doesn't contain the memory at the instruction pointer (e.g.
+    /// because it doesn't include code, like "MiniDumpNormal" dumps).
+    NotCaptured,
+}
+
+/// The most bytes that [`InstructionBytes::Captured`] holds (one more than the
+/// longest x86 instruction).
+pub const MAX_INSTRUCTION_BYTES: usize = 16;
+
+/// An instruction of [`ExceptionInfo::disassembly`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisassembledInstruction {
+    /// The address of the instruction.
+    pub address: u64,
+    /// The bytes of the instruction.
+    pub bytes: Vec<u8>,
+    /// The instruction in the disassembler's syntax, e.g. "mov rax, qword [rbx]".
+    pub text: String,
+    /// Whether this is the crashing instruction.
+    pub crashing: bool,
+}
+
+/// A module whose executable code is referenced by values on a thread's stack.
+///
+/// This is a heuristic analysis that works even if stackwalking fails. Any
+/// pointer-aligned value on the stack that points into the executable code of
+/// a module counts as a reference, so these may be return addresses of real
+/// frames, but also stale return addresses or function pointers. They are not
+/// frames!
+#[derive(Debug, Clone)]
+pub struct ReferencedModule {
+    /// The referenced module.
+    pub module: MinidumpModule,
+    /// How many values on the stack point into the module's code.
+    pub count: usize,
+}
+
+/// One of a chain of nested exceptions, see [`ProcessState::exception_chain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainedException {
+    /// The exception code, see [`MINIDUMP_EXCEPTION::exception_code`][minidump::format::MINIDUMP_EXCEPTION::exception_code].
+    pub code: u32,
+    /// The exception flags (1 for noncontinuable exceptions on Windows).
+    pub flags: u32,
+    /// The address the exception occurred at.
+    pub address: Address,
+    /// The parameters of the exception.
+    pub parameters: Vec<u64>,
+    /// The crash reason the exception code (and parameters) amount to.
+    pub reason: CrashReason,
+}
+
+/// Threads whose stacks are the same, see [`ProcessState::thread_groups`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadGroup {
+    /// The indices of the threads in [`ProcessState::threads`], in order.
+    ///
+    /// The first one is the representative of the group.
+    pub threads: Vec<usize>,
+    /// The frames of the representative thread, innermost first, e.g.
+    /// "ntdll.dll!NtWaitForSingleObject" (or "module + 0xoffset" without symbols).
+    ///
+    /// The frames of the other threads are in the same functions, but not
+    /// necessarily at the same offsets.
+    pub frames: Vec<String>,
+    /// What the threads are waiting on, if their top frames are a known wait
+    /// primitive.
+    pub blocked_on: Option<BlockedOn>,
+}
+
+/// A wait primitive that a thread is blocked in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockedOn {
+    /// What kind of wait it is.
+    pub kind: WaitKind,
+    /// The function that was recognized, e.g. "pthread_cond_wait".
+    pub function: String,
+}
+
+/// The kinds of waits that [`BlockedOn`] distinguishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WaitKind {
+    /// Waiting for a kernel object or event (e.g. `WaitForSingleObjectEx`).
+    Object,
+    /// Waiting to acquire a lock (e.g. `pthread_mutex_lock`).
+    Lock,
+    /// Waiting on a condition variable (e.g. `pthread_cond_wait`).
+    ConditionVariable,
+    /// Waiting on a futex (Linux).
+    Futex,
+    /// Waiting for a Mach message (e.g. `mach_msg_trap`, macOS).
+    MachMessage,
+    /// Waiting for I/O or for one of several file descriptors (e.g. `epoll_wait`).
+    Io,
+    /// Sleeping (e.g. `nanosleep`).
+    Sleep,
+}
+
+impl WaitKind {
+    /// The name of the kind, e.g. "condition_variable".
+    pub fn name(&self) -> &'static str {
+        match self {
+            WaitKind::Object => "object",
+            WaitKind::Lock => "lock",
+            WaitKind::ConditionVariable => "condition_variable",
+            WaitKind::Futex => "futex",
+            WaitKind::MachMessage => "mach_message",
+            WaitKind::Io => "io",
+            WaitKind::Sleep => "sleep",
+        }
+    }
+}
+
+impl std::fmt::Display for WaitKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A summary of the process's address space, from the minidump's memory info.
+///
+/// All sizes are in bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The memory that was committed (`MEM_COMMIT`).
+    pub total_committed: u64,
+    /// The memory that was reserved but not committed (`MEM_RESERVE`).
+    pub total_reserved: u64,
+    /// The free parts of the address space (`MEM_FREE`).
+    pub total_free: u64,
+    /// The size of the largest contiguous free region of the address space.
+    pub largest_free_block: u64,
+    /// The address of the largest free block, if there were any free regions.
+    pub largest_free_block_address: Option<u64>,
+    /// The committed memory that was write-combined (usually mapped graphics memory).
+    pub write_combine_committed: u64,
+    /// Whether the crash was likely caused by running out of memory: either its
+    /// [`ProcessState::crash_category`] is [`CrashCategory::OutOfMemory`], or the
+    /// largest free block was smaller than [`ProcessorOptions::oom_free_block_threshold`].
+    ///
+    /// [`ProcessorOptions::oom_free_block_threshold`]: crate::ProcessorOptions::oom_free_block_threshold
+    pub likely_oom: bool,
+}
+
+/// The memory counters of the system and the process when the minidump was
+/// written, from the `SystemMemoryInfo` and `ProcessVmCounters` streams (only
+/// Windows minidumps have them).
+///
+/// All sizes are in bytes. A field is `None` if the minidump doesn't have it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryCounters {
+    /// The physical memory of the system.
+    pub system_physical_memory: Option<u64>,
+    /// The physical memory that was available.
+    pub system_available_physical_memory: Option<u64>,
+    /// The memory the whole system had committed (its commit charge).
+    pub system_committed: Option<u64>,
+    /// How much memory the system could commit (physical memory and page files).
+    pub system_commit_limit: Option<u64>,
+    /// The physical memory the process was using (its working set).
+    pub working_set_size: Option<u64>,
+    /// The most physical memory the process ever used.
+    pub peak_working_set_size: Option<u64>,
+    /// The memory that was committed by the process (and not shared).
+    pub private_usage: Option<u64>,
+    /// The process's address space that was in use (reserved or committed).
+    pub virtual_size: Option<u64>,
+    /// The most address space the process ever used.
+    pub peak_virtual_size: Option<u64>,
+    /// The number of page faults of the process.
+    pub page_fault_count: Option<u32>,
+}
+
+/// How the process was started, reconstructed from whatever the minidump has.
+///
+/// Linux minidumps have the command line and environment in their
+/// `LinuxCmdLine` and `LinuxEnviron` streams. Windows minidumps only have them
+/// in the memory of the process, which is found by following the PEB pointer of
+/// a thread's TEB to the process parameters, so they're only there if the dump
+/// captured that memory. Anything that couldn't be found is left empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessEnvironment {
+    /// The command line of the process, with the arguments separated by spaces
+    /// on Linux (where the original quoting is lost).
+    pub command_line: Option<String>,
+    /// The path of the executable, from the process parameters or the main module.
+    pub executable_path: Option<String>,
+    /// The environment variables allowed by
+    /// [`ProcessorOptions::environment_variables`][crate::ProcessorOptions::environment_variables].
+    ///
+    /// The others are left out, since their values may be private.
+    pub variables: BTreeMap<String, String>,
+}
+
+impl ProcessEnvironment {
+    /// Whether nothing about the environment was found.
+    pub fn is_empty(&self) -> bool {
+        self.command_line.is_none() && self.executable_path.is_none() && self.variables.is_empty()
+    }
 }
 
 /// Info about a memory address that was adjusted from its reported value
@@ -416,6 +1023,18 @@ impl PossibleBitFlip {
     }
 }
 
+/// Options for [`ProcessState::print_with_options`].
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct PrintOptions {
+    /// Only print the summary at the top and the crashing thread, see
+    /// [`ProcessState::print_brief`].
+    pub brief: bool,
+    /// Print the registers of each thread's context in the thread list (see
+    /// [`CallStack::thread_context`]) before its frames.
+    pub thread_registers: bool,
+}
+
 /// The state of a process as recorded by a `Minidump`.
 #[derive(Debug, Clone)]
 pub struct ProcessState {
@@ -427,6 +1046,8 @@ pub struct ProcessState {
     pub process_create_time: Option<SystemTime>,
     /// Known code signing certificates (module name => cert name)
     pub cert_info: HashMap<String, String>,
+    /// Why the dump was written.
+    pub dump_kind: DumpKind,
     /// Info about the exception that triggered the dump (if one did)
     pub exception_info: Option<ExceptionInfo>,
     /// A string describing an assertion that was hit, if present.
@@ -440,6 +1061,18 @@ pub struct ProcessState {
     /// extended Breakpad information is present, this field will be
     /// `None`.
     pub requesting_thread: Option<usize>,
+    /// The id of the thread that wrote the minidump, from the Breakpad info
+    /// stream. This thread isn't walked (its stack is the dump writer's).
+    pub dump_thread_id: Option<u32>,
+    /// The id of the thread that asked for the minidump to be written, from the
+    /// Breakpad info stream.
+    ///
+    /// For a crash this is the crashing thread, but [`requesting_thread`] prefers
+    /// the exception's thread, so this is the only way to tell which thread
+    /// asked for a dump that was written without crashing.
+    ///
+    /// [`requesting_thread`]: ProcessState::requesting_thread
+    pub requesting_thread_id: Option<u32>,
     /// Stacks for each thread (except possibly the exception handler
     /// thread) at the time of the crash.
     pub threads: Vec<CallStack>,
@@ -449,13 +1082,23 @@ pub struct ProcessState {
     pub system_info: SystemInfo,
     /// Linux Standard Base Info
     pub linux_standard_base: Option<LinuxStandardBase>,
+    /// Linux /proc/cpuinfo
+    pub linux_cpu_info: Option<LinuxCpuInfo>,
     /// Linux Proc Limits
     pub linux_proc_limits: Option<LinuxProcLimits>,
+    /// Linux /proc/self/status
+    pub linux_proc_status: Option<LinuxProcStatus>,
     pub mac_crash_info: Option<Vec<RawMacCrashInfo>>,
     pub mac_boot_args: Option<MinidumpMacBootargs>,
     /// The modules that were loaded into the process represented by the
     /// `ProcessState`.
     pub modules: MinidumpModuleList,
+    /// The index of the main module (the executable) in `modules`, if there are any.
+    ///
+    /// This isn't always the first module: it's the one loaded where the
+    /// process says its executable is, or named like the executable, or
+    /// (failing that) the one with the lowest address.
+    pub main_module: Option<usize>,
     pub unloaded_modules: MinidumpUnloadedModuleList,
     pub handles: Option<MinidumpHandleDataStream>,
     // modules_without_symbols
@@ -463,27 +1106,65 @@ pub struct ProcessState {
     // exploitability
     pub unknown_streams: Vec<MinidumpUnknownStream>,
     pub unimplemented_streams: Vec<MinidumpUnimplementedStream>,
+    /// Streams that were ignored because the minidump has another stream of the
+    /// same type. See [`Minidump::duplicate_streams`].
+    pub duplicate_streams: Vec<MinidumpDuplicateStream>,
     pub symbol_stats: HashMap<String, SymbolStats>,
-}
-
-fn json_registers(ctx: &MinidumpContext) -> serde_json::Value {
-    let registers: Cow<HashSet<&str>> = match ctx.valid {
-        MinidumpContextValidity::All => {
-            let gpr = ctx.general_purpose_registers();
-            let set: HashSet<&str> = gpr.iter().cloned().collect();
-            Cow::Owned(set)
-        }
-        MinidumpContextValidity::Some(ref which) => Cow::Borrowed(which),
-    };
-
-    let mut output = serde_json::Map::new();
-    for &reg in ctx.general_purpose_registers() {
-        if registers.contains(reg) {
-            let reg_val = ctx.format_register(reg);
-            output.insert(String::from(reg), json!(reg_val));
-        }
-    }
-    json!(output)
+    /// Problems found with the contents of the minidump. See [`Minidump::corruption_notes`].
+    pub dump_integrity: Vec<CorruptionNote>,
+    /// How [`ProcessState::signature`] computes the crash signature.
+    pub signature_options: SignatureOptions,
+    /// How the JSON report links to the source code of frames.
+    pub source_link_options: SourceLinkOptions,
+    /// What the crash implies about the underlying bug, if anything.
+    ///
+    /// See the [`crash_category`][crate::crash_category] module for details.
+    pub crash_category: Option<CrashCategory>,
+    /// The modules whose code is referenced by the values on the requesting
+    /// thread's stack, most referenced first.
+    ///
+    /// See [`ReferencedModule`] for why these aren't frames.
+    pub referenced_modules: Vec<ReferencedModule>,
+    /// A summary of the address space, if the minidump has a memory info list
+    /// (only Windows minidumps do).
+    pub memory_usage: Option<MemoryUsage>,
+    /// The memory counters of the system and the process, if the minidump has
+    /// any of them.
+    pub memory_counters: Option<MemoryCounters>,
+    /// The command line, executable and (some) environment variables of the
+    /// process, if the minidump has any of them.
+    pub environment: Option<ProcessEnvironment>,
+    /// The threads grouped by their symbolized stacks, biggest group first.
+    ///
+    /// Threads are in the same group if all their frames are in the same
+    /// functions of the same modules. Threads without frames aren't in any
+    /// group. Empty if [`ProcessorOptions::group_threads`][crate::ProcessorOptions::group_threads]
+    /// is disabled.
+    pub thread_groups: Vec<ThreadGroup>,
+    /// The crashes of managed runtimes (like Java exceptions) the minidump has
+    /// auxiliary streams of, see
+    /// [`ProcessorOptions::auxiliary_streams`][crate::ProcessorOptions::auxiliary_streams].
+    pub auxiliary_crashes: Vec<AuxiliaryCrashInfo>,
+    /// The exception that triggered the dump and the exceptions it's nested in,
+    /// outermost (the one in the exception stream) first.
+    ///
+    /// On Windows, exceptions raised while handling another (like unhandled C++
+    /// exceptions) refer to it, see [`MinidumpException::exception_chain`]. When
+    /// the outermost exception only wraps the others, the crash reason in
+    /// [`exception_info`][Self::exception_info] is the innermost one's.
+    /// Empty if there's no exception.
+    pub exception_chain: Vec<ChainedException>,
+    /// The crash annotations from outside of the minidump (like a `.extra`
+    /// file), see [`ProcessorOptions::extra_annotations`][crate::ProcessorOptions::extra_annotations].
+    pub crash_annotations: serde_json::Map<String, serde_json::Value>,
+    /// What Crashpad knows about the crash: the ids of its report and client,
+    /// and the annotations (crash keys) of the process and of its modules.
+    pub crashpad_info: Option<MinidumpCrashpadInfo>,
+    /// Parts of the minidump that disagree with each other.
+    pub consistency_warnings: Vec<ConsistencyWarning>,
+    /// What privacy mode withheld or hashed, or `None` if it wasn't enabled
+    /// (see [`ProcessorOptions::privacy_mode`][crate::ProcessorOptions::privacy_mode]).
+    pub redacted_sections: Option<Vec<RedactedSection>>,
 }
 
 fn eq_some<T: PartialEq>(opt: Option<T>, val: T) -> bool {
@@ -493,37 +1174,172 @@ fn eq_some<T: PartialEq>(opt: Option<T>, val: T) -> bool {
     }
 }
 
+/// A " (started at ...)" suffix for a thread's header, or nothing if its start
+/// address is unknown.
+fn thread_start_description(stack: &CallStack) -> String {
+    match (&stack.start_function, stack.start_address) {
+        (Some(function), _) => format!(" (started at {function})"),
+        (None, Some(address)) => format!(" (started at {address:#x})"),
+        (None, None) => String::new(),
+    }
+}
+
+/// A line describing what a thread's own bookkeeping says about it, or `None`
+/// if it wasn't read.
+fn thread_identity_description(stack: &CallStack) -> Option<String> {
+    let identity = stack.thread_identity.as_ref()?;
+    let mut description = format!("Thread identity (TEB at {:#x}):", identity.address);
+    match identity.thread_id {
+        Some(thread_id) => description.push_str(&format!(" tid: {thread_id}")),
+        None => description.push_str(" tid: unknown"),
+    }
+    if let Some(name) = &identity.thread_name {
+        description.push_str(&format!(" ({name})"));
+    }
+    if let Some(address) = identity.start_address {
+        description.push_str(&format!(", started at {address:#x}"));
+    }
+    if let (Some(base), Some(limit)) = (identity.stack_base, identity.stack_limit) {
+        description.push_str(&format!(", stack: {limit:#x}-{base:#x}"));
+    }
+    Some(description)
+}
+
+/// `bytes` as space-separated hex, e.g. "48 8b 03".
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 impl ProcessState {
     /// `true` if the minidump was written in response to a process crash.
+    ///
+    /// Dumps with an exception can still be hang reports or requested dumps,
+    /// see [`ProcessState::dump_kind`].
     pub fn crashed(&self) -> bool {
-        self.exception_info.is_some()
+        self.dump_kind == DumpKind::Crash
     }
+    /// A stable signature of the crash, for deduplicating crashes.
+    ///
+    /// This summarizes the top interesting frames of the crashing thread, as
+    /// configured by [`ProcessState::signature_options`]. See the
+    /// [`signature`][crate::signature] module for details.
+    pub fn signature(&self) -> Option<String> {
+        compute_signature(self, &self.signature_options)
+    }
+
     /// Write a human-readable description of the process state to `f`.
     ///
     /// This is very verbose, it implements the output format used by
     /// minidump_stackwalk.
     pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
-        self.print_internal(f, false)
+        self.print_with_options(f, &PrintOptions::default())
     }
 
     /// Write a brief human-readable description of the process state to `f`.
     ///
     /// Only includes the summary at the top and a backtrace of the crashing thread.
+    /// If no thread crashed or requested the dump, all threads are printed.
     pub fn print_brief<T: Write>(&self, f: &mut T) -> io::Result<()> {
-        self.print_internal(f, true)
+        let options = PrintOptions {
+            brief: true,
+            ..PrintOptions::default()
+        };
+        self.print_with_options(f, &options)
     }
 
-    fn print_internal<T: Write>(&self, f: &mut T, brief: bool) -> io::Result<()> {
+    /// Print the thread groups, if there's more than one thread in them.
+    fn print_thread_groups<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        // The representative stacks are cut short, the full ones follow anyway
+        const GROUP_FRAMES_SHOWN: usize = 8;
+
+        let grouped: usize = self.thread_groups.iter().map(|g| g.threads.len()).sum();
+        if grouped < 2 {
+            return Ok(());
+        }
+        writeln!(f, "Thread groups:")?;
+        for group in &self.thread_groups {
+            let count = group.threads.len();
+            write!(
+                f,
+                "  {count} {}",
+                if count == 1 { "thread" } else { "threads" }
+            )?;
+            if let Some(blocked_on) = &group.blocked_on {
+                write!(
+                    f,
+                    " (blocked: {} in {})",
+                    blocked_on.kind, blocked_on.function
+                )?;
+            }
+            let members: Vec<String> = group
+                .threads
+                .iter()
+                .map(|&i| match self.threads[i].thread_name.as_deref() {
+                    Some(name) if !name.is_empty() => format!("{i} {name}"),
+                    _ => i.to_string(),
+                })
+                .collect();
+            writeln!(f, ": {}", members.join(", "))?;
+            for (i, frame) in group.frames.iter().take(GROUP_FRAMES_SHOWN).enumerate() {
+                writeln!(f, "    {i:2}  {frame}")?;
+            }
+            if group.frames.len() > GROUP_FRAMES_SHOWN {
+                writeln!(
+                    f,
+                    "        ... {} more frames",
+                    group.frames.len() - GROUP_FRAMES_SHOWN
+                )?;
+            }
+        }
+        writeln!(f)
+    }
+
+    /// Write a human-readable description of the process state to `f`, like
+    /// [`ProcessState::print`] (or [`ProcessState::print_brief`]) but as
+    /// configured by `options`.
+    pub fn print_with_options<T: Write>(
+        &self,
+        f: &mut T,
+        options: &PrintOptions,
+    ) -> io::Result<()> {
         self.set_print_context();
 
+        if let Some(module) = self
+            .main_module
+            .and_then(|index| self.modules.iter().nth(index))
+        {
+            let full_name = module.code_file();
+            write!(f, "Process: {}", basename(&full_name))?;
+            if let Some(version) = module.version() {
+                write!(f, " (version {version})")?;
+            }
+            writeln!(f)?;
+        }
         writeln!(f, "Operating system: {}", self.system_info.os.long_name())?;
         if let Some(ref ver) = self.system_info.format_os_version() {
             writeln!(f, "                  {ver}")?;
         }
+        if let Some(ref build_lab) = self.system_info.os_build_lab {
+            writeln!(f, "                  {build_lab}")?;
+        }
+        if let Some(ref distro) = self.system_info.os_distro {
+            writeln!(f, "                  {distro}")?;
+        }
         writeln!(f, "CPU: {}", self.system_info.cpu)?;
         if let Some(ref info) = self.system_info.cpu_info {
             writeln!(f, "     {info}")?;
         }
+        if !self.system_info.cpu_features.is_empty() {
+            writeln!(
+                f,
+                "     features: {}",
+                self.system_info.cpu_features.join(",")
+            )?;
+        }
         writeln!(
             f,
             "     {} CPU{}",
@@ -535,15 +1351,25 @@ impl ProcessState {
             }
         )?;
         if let Some(ref lsb) = self.linux_standard_base {
-            writeln!(
-                f,
-                "Linux {} {} - {} ({})",
-                lsb.id, lsb.release, lsb.codename, lsb.description
-            )?;
+            // Distributions don't all have every field (Fedora has no codename).
+            let mut distro = format!("Linux {} {}", lsb.id, lsb.release);
+            distro.truncate(distro.trim_end().len());
+            if !lsb.codename.is_empty() {
+                distro.push_str(&format!(" - {}", lsb.codename));
+            }
+            if !lsb.description.is_empty() {
+                distro.push_str(&format!(" ({})", lsb.description));
+            }
+            writeln!(f, "{distro}")?;
         }
         writeln!(f)?;
 
         if let Some(ref crash_info) = self.exception_info {
+            match self.dump_kind {
+                DumpKind::Crash => {}
+                DumpKind::Hang => writeln!(f, "No crash — the process was hung")?,
+                DumpKind::Requested => writeln!(f, "No crash — dump requested")?,
+            }
             writeln!(f, "Crash reason:  {}", crash_info.reason)?;
 
             if let Some(adjusted_address) = &crash_info.adjusted_address {
@@ -560,10 +1386,64 @@ impl ProcessState {
                 writeln!(f, "Crash address: {}", crash_info.address)?;
             }
 
+            if let Some(ref detail) = crash_info.detail {
+                writeln!(f, "Crash details: {detail}")?;
+            }
+
+            if let Some(ref message) = crash_info.message {
+                writeln!(f, "Crash message: {message}")?;
+            }
+
+            if self.exception_chain.len() > 1 {
+                writeln!(f, "Exception chain (outermost first):")?;
+                for (i, exception) in self.exception_chain.iter().enumerate() {
+                    write!(
+                        f,
+                        "  {i}: {} ({:#010x}) at {}",
+                        exception.reason, exception.code, exception.address
+                    )?;
+                    if !exception.parameters.is_empty() {
+                        let parameters: Vec<String> = exception
+                            .parameters
+                            .iter()
+                            .map(|parameter| format!("{parameter:#x}"))
+                            .collect();
+                        write!(f, ", parameters: {}", parameters.join(", "))?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+
             if let Some(ref crashing_instruction_str) = crash_info.instruction_str {
                 writeln!(f, "Crashing instruction: `{crashing_instruction_str}`")?;
             }
 
+            match &crash_info.instruction_bytes {
+                InstructionBytes::Captured(bytes) => {
+                    writeln!(f, "Crashing instruction bytes: {}", hex_bytes(bytes))?
+                }
+                InstructionBytes::Unmapped => {
+                    writeln!(f, "Crashing instruction bytes: (unmapped memory)")?
+                }
+                InstructionBytes::NotCaptured => {
+                    writeln!(f, "Crashing instruction bytes: (not captured)")?
+                }
+            }
+
+            if !crash_info.disassembly.is_empty() {
+                writeln!(f, "Disassembly:")?;
+                for instruction in &crash_info.disassembly {
+                    writeln!(
+                        f,
+                        "  {} {}: {:<24} {}",
+                        if instruction.crashing { "=>" } else { "  " },
+                        Address(instruction.address),
+                        hex_bytes(&instruction.bytes),
+                        instruction.text
+                    )?;
+                }
+            }
+
             if let Some(ref memory_accesses) = crash_info.memory_accesses {
                 if !memory_accesses.is_empty() {
                     writeln!(f, "Memory accessed by instruction:")?;
@@ -583,6 +1463,10 @@ impl ProcessState {
                 }
             }
 
+            for note in &crash_info.notes {
+                writeln!(f, "Crash note: {note}")?;
+            }
+
             if !crash_info.possible_bit_flips.is_empty() {
                 writeln!(f, "Crashing address may be the result of a flipped bit:")?;
                 let mut bit_flips_with_confidence = crash_info
@@ -609,8 +1493,47 @@ impl ProcessState {
                     )?;
                 }
             }
+        } else if self.dump_kind == DumpKind::Hang {
+            writeln!(f, "No crash — the process was hung")?;
         } else {
-            writeln!(f, "No crash")?;
+            writeln!(f, "No crash — dump requested")?;
+        }
+
+        if let Some(ref category) = self.crash_category {
+            writeln!(f, "Crash category: {category}")?;
+        }
+
+        if let Some(ref usage) = self.memory_usage {
+            writeln!(
+                f,
+                "Memory usage: {} bytes committed, {} bytes reserved, largest free block: {} bytes{}",
+                usage.total_committed,
+                usage.total_reserved,
+                usage.largest_free_block,
+                if usage.likely_oom { " (likely OOM)" } else { "" }
+            )?;
+        }
+
+        if let Some(ref counters) = self.memory_counters {
+            let bytes =
+                |size: Option<u64>| size.map_or_else(|| String::from("?"), |size| size.to_string());
+            writeln!(
+                f,
+                "Memory counters: system committed {} of {} bytes, {} of {} bytes of physical memory available",
+                bytes(counters.system_committed),
+                bytes(counters.system_commit_limit),
+                bytes(counters.system_available_physical_memory),
+                bytes(counters.system_physical_memory),
+            )?;
+            writeln!(
+                f,
+                "  process: private {} bytes, working set {} bytes (peak {}), virtual {} bytes (peak {})",
+                bytes(counters.private_usage),
+                bytes(counters.working_set_size),
+                bytes(counters.peak_working_set_size),
+                bytes(counters.virtual_size),
+                bytes(counters.peak_virtual_size),
+            )?;
         }
 
         if let Some(ref assertion) = self.assertion {
@@ -621,13 +1544,13 @@ impl ProcessState {
             for (idx, record) in info.iter().enumerate() {
                 writeln!(f, "  Record {idx}")?;
                 if let Some(val) = record.thread() {
-                    writeln!(f, "    thread: 0x{val}")?;
+                    writeln!(f, "    thread: {val:#x}")?;
                 }
                 if let Some(val) = record.dialog_mode() {
-                    writeln!(f, "    dialog mode: 0x{val}")?;
+                    writeln!(f, "    dialog mode: {val:#x}")?;
                 }
                 if let Some(val) = record.abort_cause() {
-                    writeln!(f, "    abort_cause: 0x{val}")?;
+                    writeln!(f, "    abort_cause: {val:#x}")?;
                 }
 
                 if let Some(val) = record.module_path() {
@@ -656,37 +1579,159 @@ impl ProcessState {
             )?;
             writeln!(f)?;
         }
+        if !self.dump_integrity.is_empty() {
+            writeln!(f, "Dump integrity warnings:")?;
+            for note in &self.dump_integrity {
+                writeln!(f, "  {note}")?;
+            }
+            writeln!(f)?;
+        }
+        if !self.consistency_warnings.is_empty() {
+            writeln!(f, "Consistency warnings:")?;
+            for warning in &self.consistency_warnings {
+                writeln!(f, "  {warning}")?;
+            }
+            writeln!(f)?;
+        }
+        match &self.redacted_sections {
+            Some(sections) if sections.is_empty() => {
+                writeln!(f, "Redacted: nothing had to be withheld")?;
+                writeln!(f)?;
+            }
+            Some(sections) => {
+                writeln!(f, "Redacted:")?;
+                for section in sections {
+                    writeln!(f, "  {section}")?;
+                }
+                writeln!(f)?;
+            }
+            None => {}
+        }
+        self.print_unprocessed_streams(f)?;
         if let Some(ref time) = self.process_create_time {
             let uptime = self.time.duration_since(*time).unwrap_or_default();
             writeln!(f, "Process uptime: {} seconds", uptime.as_secs())?;
         } else {
             writeln!(f, "Process uptime: not available")?;
         }
+        if let Some(ref environment) = self.environment {
+            if let Some(ref command_line) = environment.command_line {
+                writeln!(f, "Command line: {command_line}")?;
+            }
+            if let Some(ref executable_path) = environment.executable_path {
+                writeln!(f, "Executable: {executable_path}")?;
+            }
+            if !environment.variables.is_empty() {
+                writeln!(f, "Environment:")?;
+                for (name, value) in &environment.variables {
+                    writeln!(f, "  {name}={value}")?;
+                }
+            }
+        }
+        if !self.crash_annotations.is_empty() {
+            writeln!(f, "Crash annotations:")?;
+            for (key, value) in &self.crash_annotations {
+                match value {
+                    serde_json::Value::String(value) => writeln!(f, "  {key}={value}")?,
+                    value => writeln!(f, "  {key}={value}")?,
+                }
+            }
+        }
+        let has_crashpad_annotations = self.crashpad_info.as_ref().is_some_and(|info| {
+            !info.simple_annotations.is_empty()
+                || info.module_list.iter().any(|module| {
+                    !module.list_annotations.is_empty()
+                        || !module.simple_annotations.is_empty()
+                        || module
+                            .annotation_objects
+                            .values()
+                            .any(|value| matches!(value, MinidumpAnnotation::String(_)))
+                })
+        });
+        if let Some(info) = self
+            .crashpad_info
+            .as_ref()
+            .filter(|_| has_crashpad_annotations)
+        {
+            writeln!(f, "Crashpad annotations:")?;
+            for (key, value) in &info.simple_annotations {
+                writeln!(f, "  {key}={value}")?;
+            }
+            for module in &info.module_list {
+                let name = self
+                    .modules
+                    .iter()
+                    .nth(module.module_index)
+                    .map(|module| basename(&module.code_file()).to_owned())
+                    .unwrap_or_else(|| format!("module {}", module.module_index));
+                for annotation in &module.list_annotations {
+                    writeln!(f, "  {name}: {annotation}")?;
+                }
+                for (key, value) in &module.simple_annotations {
+                    writeln!(f, "  {name}: {key}={value}")?;
+                }
+                for (key, value) in &module.annotation_objects {
+                    if let MinidumpAnnotation::String(value) = value {
+                        writeln!(f, "  {name}: {key}={value}")?;
+                    }
+                }
+            }
+        }
         writeln!(f)?;
 
         if let Some(requesting_thread) = self.requesting_thread {
             let stack = &self.threads[requesting_thread];
             writeln!(
                 f,
-                "Thread {} {} ({}) - tid: {}",
+                "Thread {} {} ({}){} - tid: {}",
                 requesting_thread,
-                stack.thread_name.as_deref().unwrap_or(""),
+                stack
+                    .thread_name
+                    .as_deref()
+                    .or_else(|| stack.thread_identity.as_ref()?.thread_name.as_deref())
+                    .unwrap_or(""),
                 if self.crashed() {
                     "crashed"
                 } else {
                     "requested dump, did not crash"
                 },
+                thread_start_description(stack),
                 stack.thread_id
             )?;
+            if let Some(description) = thread_identity_description(stack) {
+                writeln!(f, "{description}")?;
+            }
+            if options.thread_registers {
+                stack.print_thread_registers(f)?;
+            }
             stack.print(f)?;
             writeln!(f)?;
+
+            if !self.referenced_modules.is_empty() {
+                writeln!(
+                    f,
+                    "Modules referenced by the stack of thread {requesting_thread} (heuristic, not frames):"
+                )?;
+                for referenced in &self.referenced_modules {
+                    writeln!(
+                        f,
+                        "  {} ({} references)",
+                        basename(&referenced.module.code_file()),
+                        referenced.count
+                    )?;
+                }
+                writeln!(f)?;
+            }
         }
 
-        // We're done if this is a brief report!
-        if brief {
-            return Ok(());
+        // We're done if this is a brief report! (unless there was no thread to
+        // single out, then every thread is as interesting as the others)
+        if options.brief && self.requesting_thread.is_some() {
+            return self.print_auxiliary_crashes(f);
         }
 
+        self.print_thread_groups(f)?;
+
         for (i, stack) in self.threads.iter().enumerate() {
             if eq_some(self.requesting_thread, i) {
                 // Don't print the requesting thread again,
@@ -697,20 +1742,28 @@ impl ProcessState {
             }
             writeln!(
                 f,
-                "Thread {} {} - tid: {}",
+                "Thread {} {}{} - tid: {}",
                 i,
                 stack.thread_name.as_deref().unwrap_or(""),
+                thread_start_description(stack),
                 stack.thread_id
             )?;
+            if options.thread_registers {
+                stack.print_thread_registers(f)?;
+            }
             stack.print(f)?;
         }
+        self.print_auxiliary_crashes(f)?;
         write!(
             f,
             "
 Loaded modules:
 "
         )?;
-        let main_address = self.modules.main_module().map(|m| m.base_address());
+        let main_address = self
+            .main_module
+            .and_then(|index| self.modules.iter().nth(index))
+            .map(|m| m.base_address());
         for module in self.modules.by_addr() {
             // TODO: missing symbols, corrupt symbols
             let full_name = module.code_file();
@@ -719,7 +1772,9 @@ Loaded modules:
                 f,
                 "{:#010x} - {:#010x}  {}  {}",
                 module.base_address(),
-                module.base_address() + module.size() - 1,
+                module
+                    .memory_range()
+                    .map_or(module.base_address(), |range| range.end),
                 name,
                 module.version().unwrap_or(Cow::Borrowed("???"))
             )?;
@@ -730,6 +1785,20 @@ Loaded modules:
                 write!(f, " ({cert})")?;
             }
             writeln!(f)?;
+            if let Some(stats) = self.symbol_stats.get(name) {
+                if let Some(symbols_debug_id) = &stats.symbols_debug_id {
+                    // The symbols were checked against the looked up debug id, if there was one
+                    let debug_id = match &stats.extra_debug_info {
+                        Some(info) => info.debug_identifier,
+                        None => module.debug_identifier().unwrap_or_default(),
+                    };
+                    writeln!(
+                        f,
+                        "    WARNING: symbols are for debug id {symbols_debug_id}, not {}",
+                        debug_id.breakpad()
+                    )?;
+                }
+            }
         }
         write!(
             f,
@@ -744,7 +1813,9 @@ Unloaded modules:
                 f,
                 "{:#010x} - {:#010x}  {}",
                 module.base_address(),
-                module.base_address() + module.size() - 1,
+                module
+                    .memory_range()
+                    .map_or(module.base_address(), |range| range.end),
                 basename(&module.code_file()),
             )?;
             if let Some(cert) = self.cert_info.get(name) {
@@ -788,275 +1859,119 @@ Unknown streams encountered:
         Ok(())
     }
 
-    /// Outputs json in a schema compatible with mozilla's Socorro crash reporting servers.
-    ///
-    /// See the top level documentation of this library for the stable JSON schema.
-    pub fn print_json<T: Write>(&self, f: &mut T, pretty: bool) -> Result<(), serde_json::Error> {
-        // See ../json-schema.md for details on this format.
-
-        self.set_print_context();
-
-        let sys = &self.system_info;
-
-        fn json_hex(address: u64) -> String {
-            Address(address).to_string()
-        }
-
-        let mut output = json!({
-            // Currently unused, we either produce no output or successful output.
-            // OK | ERROR_* | SYMBOL_SUPPLIER_INTERRUPTED
-            "status": "OK",
-            "system_info": {
-                // Linux | Windows NT | Mac OS X
-                "os": sys.os.long_name(),
-                "os_ver": sys.format_os_version(),
-                // x86 | amd64 | arm | ppc | sparc
-                "cpu_arch": sys.cpu.to_string(),
-                "cpu_info": sys.cpu_info,
-                "cpu_count": sys.cpu_count,
-                // optional, print as hex string
-                "cpu_microcode_version": sys.cpu_microcode_version.map(|num| format!("{num:#x}")),
-            },
-            "crash_info": {
-                "type": self.exception_info.as_ref().map(|info| info.reason).map(|reason| reason.to_string()),
-                "address": self.exception_info.as_ref().map(|info| info.address),
-                "adjusted_address": self.exception_info.as_ref().map(|info| {
-                    info.adjusted_address.as_ref().map(|adjusted| match adjusted {
-                        AdjustedAddress::NonCanonical(address) => json!({
-                            "kind": "non-canonical",
-                            "address": address,
-                        }),
-                        AdjustedAddress::NullPointerWithOffset(offset) => json!({
-                            "kind": "null-pointer",
-                            "offset": offset,
-                        }),
-                    })
-                }),
-                "instruction": self.exception_info.as_ref().map(|info| info.instruction_str.as_ref()),
-                "memory_accesses": self.exception_info.as_ref().and_then(|info| {
-                    info.memory_accesses.as_ref().map(|accesses| {
-                        accesses.iter().map(|access| {
-                            let mut map = json!({
-                                "address": json_hex(access.address),
-                                "size": access.size,
-                            });
-                            // Only add the `is_likely_guard_page` field when it is affirmative.
-                            if access.is_likely_guard_page {
-                                map["is_likely_guard_page"] = true.into();
-                            }
-                            map
-                        }).collect::<Vec<_>>()
-                    })
-                }),
-                "possible_bit_flips": self.exception_info.as_ref().and_then(|info| {
-                    (!info.possible_bit_flips.is_empty()).then_some(&info.possible_bit_flips)
-                }),
-                // thread index | null
-                "crashing_thread": self.requesting_thread,
-                "assertion": self.assertion,
-            },
-            // optional
-            "lsb_release": self.linux_standard_base.as_ref().map(|lsb| json!({
-                "id": lsb.id,
-                "release": lsb.release,
-                "codename": lsb.codename,
-                "description": lsb.description,
-            })),
-            // optional
-            "proc_limits": self.linux_proc_limits.as_ref().map(|limits| json!({
-                "limits": limits.limits.iter().map(|limit| json!({
-                    "name": limit.0,
-                    "soft": limit.1.soft,
-                    "hard": limit.1.hard,
-                    "unit": limit.1.unit,
-                })).collect::<Vec<_>>()
-            })),
-            // optional
-            "mac_crash_info": self.mac_crash_info.as_ref().map(|info| json!({
-                "num_records": info.len(),
-                // All of these fields are optional
-                "records": info.iter().map(|record| json!({
-                    "thread": record.thread().copied().map(json_hex),
-                    "dialog_mode": record.dialog_mode().copied().map(json_hex),
-                    "abort_cause": record.abort_cause().copied().map(json_hex),
-
-                    "module": record.module_path(),
-                    "message": record.message(),
-                    "signature_string": record.signature_string(),
-                    "backtrace": record.backtrace(),
-                    "message2": record.message2(),
-                })).collect::<Vec<_>>()
-            })),
-            // optional
-            "mac_boot_args": self.mac_boot_args.as_ref().map(|info| info.bootargs.as_ref()),
-
-            // the first module is always the main one
-            "main_module": 0,
-            // [UNSTABLE:evil_json]
-            "modules_contains_cert_info": !self.cert_info.is_empty(),
-            "modules": self.modules.iter().map(|module| {
-                let full_name = module.code_file();
-                let name = basename(&full_name);
-
-                // Gather statistics on the module's symbols
-                let stats = self.symbol_stats.get(name);
-                let had_stats = stats.is_some();
-                let default = SymbolStats::default();
-                let stats = stats.unwrap_or(&default);
-                // Resolve debug file and debug id from extra debug info if present
-                let debug_file;
-                let debug_id;
-                let debug_file_cow = module.debug_file().unwrap_or(Cow::Borrowed(""));
-                if let Some(debug_info) = &stats.extra_debug_info {
-                    debug_file = debug_info.debug_file.as_str();
-                    debug_id = debug_info.debug_identifier;
-                } else {
-                    debug_file = debug_file_cow.borrow();
-                    debug_id = module.debug_identifier().unwrap_or_default();
+    /// Print the crashes of the auxiliary streams, after the native stacks.
+    fn print_auxiliary_crashes<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        for crash in &self.auxiliary_crashes {
+            writeln!(f)?;
+            match crash.raw {
+                Some(ref raw) => {
+                    writeln!(
+                        f,
+                        "Auxiliary crash ({}, stream {:#010x}, unparsed):",
+                        crash.kind, crash.stream_type
+                    )?;
+                    for line in raw.trim_end_matches('\0').lines() {
+                        writeln!(f, "  {line}")?;
+                    }
                 }
-                // Only consider the symbols "missing" if the symbolizer
-                // actually has statistics on them (implying it *tried* to
-                // get the symbols but failed.)
-                let missing_symbols = had_stats && !stats.loaded_symbols;
-                json!({
-                    "base_addr": json_hex(module.raw.base_of_image),
-                    // filename | empty string
-                    "debug_file": basename(debug_file),
-                    // [[:xdigit:]]{33} | empty string
-                    "debug_id": debug_id.breakpad().to_string(),
-                    "end_addr": json_hex(module.raw.base_of_image + module.raw.size_of_image as u64),
-                    "filename": &name,
-                    "code_id": module.code_identifier().unwrap_or_default().as_str(),
-                    "version": module.version(),
-                    // [UNSTABLE:evil_json]
-                    "cert_subject": self.cert_info.get(name),
-
-                    // These are all just metrics for debugging minidump-processor's execution
-
-                    // optional, if mdsw looked for the file and it doesn't exist
-                    "missing_symbols": missing_symbols,
-                    // optional, if mdsw looked for the file and it does exist
-                    "loaded_symbols": stats.loaded_symbols,
-                    // optional, if mdsw found a file that has parse errors
-                    "corrupt_symbols": stats.corrupt_symbols,
-                    // optional, url of symbol file
-                    "symbol_url": stats.symbol_url,
-                })
-            }).collect::<Vec<_>>(),
-            "pid": self.process_id,
-            "thread_count": self.threads.len(),
-            "threads": self.threads.iter().map(|thread| json!({
-                "frame_count": thread.frames.len(),
-                // optional
-                "last_error_value": thread.last_error_value.map(|error| error.to_string()),
-                // optional
-                "thread_name": thread.thread_name,
-                "thread_id" : thread.thread_id,
-                "frames": thread.frames.iter().enumerate().map(|(idx, frame)| json!({
-                    "frame": idx,
-                    // optional
-                    "module": frame.module.as_ref().map(|module| basename(&module.name)),
-                    // optional
-                    "function": frame.function_name,
-                    // optional
-                    "file": frame.source_file_name,
-                    // optional
-                    "line": frame.source_line,
-                    "offset": json_hex(frame.instruction),
-                    // optional
-                    "inlines": if !frame.inlines.is_empty() {
-                        Some(frame.inlines.iter().map(|frame| {
-                            json!({
-                                "function": frame.function_name,
-                                "file": frame.source_file_name,
-                                "line": frame.source_line,
-                            })
-                        }).collect::<Vec<_>>())
-                    } else {
-                        None
-                    },
-                    // optional
-                    "module_offset": frame
-                        .module
-                        .as_ref()
-                        .map(|module| frame.instruction - module.raw.base_of_image)
-                        .map(json_hex),
-                    // optional
-                    "unloaded_modules": if frame.unloaded_modules.is_empty() {
-                        None
-                    } else {
-                        Some(frame.unloaded_modules.iter().map(|(module, offsets)| json!({
-                            "module": module,
-                            "offsets": offsets.iter().copied().map(json_hex).collect::<Vec<_>>(),
-                        })).collect::<Vec<_>>())
-                    },
-                    // optional
-                    "function_offset": frame
-                        .function_base
-                        .map(|func_base| frame.instruction - func_base)
-                        .map(json_hex),
-                    "missing_symbols": frame.function_name.is_none(),
-                    // none | scan | cfi_scan | frame_pointer | cfi | context | prewalked
-                    "trust": frame.trust.as_str()
-                })).collect::<Vec<_>>(),
-            })).collect::<Vec<_>>(),
-
-            "unloaded_modules": self.unloaded_modules.iter().map(|module| json!({
-                "base_addr": json_hex(module.raw.base_of_image),
-                "code_id": module.code_identifier().unwrap_or_default().as_str(),
-                "end_addr": json_hex(module.raw.base_of_image + module.raw.size_of_image as u64),
-                "filename": module.name,
-                "cert_subject": self.cert_info.get(&module.name),
-            })).collect::<Vec<_>>(),
-            "handles": self.handles.as_ref().map(|handles| handles.iter().map(|handle| json!({
-                "handle": handle.raw.handle(),
-                "type_name": handle.type_name,
-                "object_name": handle.object_name
-            })).collect::<Vec<_>>()),
-        });
+                None => {
+                    writeln!(
+                        f,
+                        "Auxiliary crash ({}, stream {:#010x}):",
+                        crash.kind, crash.stream_type
+                    )?;
+                    if let Some(ref message) = crash.message {
+                        for line in message.lines() {
+                            writeln!(f, "  {line}")?;
+                        }
+                    }
+                    for frame in &crash.frames {
+                        writeln!(f, "    {frame}")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 
-        if let Some(requesting_thread) = self.requesting_thread {
-            // Copy the crashing thread into a top-level "crashing_thread" field and:
-            // * Add a "threads_index" field to indicate which thread it was
-            // * Add a "registers" field to its first frame
-            //
-            // Note that we currently make crashing_thread a strict superset
-            // of a normal "threads" entry, while the original schema strips
-            // many of the fields here. We don't to keep things more uniform.
-
-            // We can't do any of this work if we don't have at least one frame.
-            if let Some(f) = self.threads[requesting_thread].frames.first() {
-                let registers = json_registers(&f.context);
-
-                // Yuck, spidering through json...
-                let mut thread = output.get_mut("threads").unwrap().as_array().unwrap()
-                    [requesting_thread]
-                    .clone();
-                let thread_obj = thread.as_object_mut().unwrap();
-                let frames = thread_obj
-                    .get_mut("frames")
-                    .unwrap()
-                    .as_array_mut()
-                    .unwrap();
-                let frame = frames[0].as_object_mut().unwrap();
-
-                frame.insert(String::from("registers"), registers);
-                thread_obj.insert(String::from("threads_index"), json!(requesting_thread));
-
-                output
-                    .as_object_mut()
-                    .unwrap()
-                    .insert(String::from("crashing_thread"), thread);
+    /// Print a summary of the streams whose contents we ignored, if there are any.
+    fn print_unprocessed_streams<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        // Empty UnusedStreams are just padding, nothing is missing
+        let unprocessed: Vec<_> = self
+            .unimplemented_streams
+            .iter()
+            .filter(|stream| {
+                stream.stream_type != MINIDUMP_STREAM_TYPE::UnusedStream
+                    || stream.location.data_size != 0
+            })
+            .map(|stream| (stream.stream_type as u32, stream.location))
+            .chain(
+                self.unknown_streams
+                    .iter()
+                    .map(|stream| (stream.stream_type, stream.location)),
+            )
+            .collect();
+        let print_streams = |f: &mut T, streams: &[(u32, MINIDUMP_LOCATION_DESCRIPTOR)]| {
+            for (i, (stream_type, location)) in streams.iter().enumerate() {
+                let separator = if i == 0 { "" } else { "," };
+                write!(
+                    f,
+                    "{separator} {stream_type:#010x} '{}' ({} bytes)",
+                    stream_type_name(*stream_type),
+                    location.data_size
+                )?;
             }
+            writeln!(f)
+        };
+
+        if !unprocessed.is_empty() {
+            write!(
+                f,
+                "Dump contains {} unprocessed streams:",
+                unprocessed.len()
+            )?;
+            print_streams(f, &unprocessed)?;
+        }
+        if !self.duplicate_streams.is_empty() {
+            let duplicates: Vec<_> = self
+                .duplicate_streams
+                .iter()
+                .map(|stream| (stream.stream_type, stream.location))
+                .collect();
+            write!(
+                f,
+                "Dump contains {} ignored duplicate streams:",
+                duplicates.len()
+            )?;
+            print_streams(f, &duplicates)?;
+        }
+        if !unprocessed.is_empty() || !self.duplicate_streams.is_empty() {
+            writeln!(f)?;
         }
+        Ok(())
+    }
 
+    /// Outputs json in a schema compatible with mozilla's Socorro crash reporting servers.
+    ///
+    /// See the top level documentation of this library for the stable JSON schema.
+    pub fn print_json<T: Write>(&self, f: &mut T, pretty: bool) -> Result<(), serde_json::Error> {
+        // See ../json-schema.md for details on this format.
+        let report = self.to_json_report();
         if pretty {
-            serde_json::to_writer_pretty(f, &output)
+            serde_json::to_writer_pretty(f, &report)
         } else {
-            serde_json::to_writer(f, &output)
+            serde_json::to_writer(f, &report)
         }
     }
 
+    /// The report that [`ProcessState::print_json`] outputs, as a typed struct.
+    ///
+    /// See the [`report`][crate::report] module for details.
+    pub fn to_json_report(&self) -> Report {
+        self.set_print_context();
+        Report::from_state(self)
+    }
+
     fn set_print_context(&self) {
         SERIALIZATION_CONTEXT.with(|ctx| {
             ctx.borrow_mut().pointer_width = Some(self.system_info.cpu.pointer_width());
@@ -7,15 +7,31 @@ use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 
-use minidump::system_info::PointerWidth;
+use minidump::auxiliary::AuxiliaryStreams;
+use minidump::system_info::{CpuEmulation, Os, PointerWidth};
 use minidump::*;
+use minidump_common::utils::basename;
+use minidump_unwind::symbols::jit::JitUnwindInfoProvider;
 use minidump_unwind::{
-    walk_stack, CallStack, CallStackInfo, FrameTrust, StackFrame, SymbolProvider, SystemInfo,
+    walk_stack_with_memory, CallStack, CallStackInfo, FrameSymbolizer, FrameTrust, StackFrame,
+    StackMemoryRepair, SymbolDisposition, SymbolProvider, SystemInfo, ThreadTimes,
 };
+use tracing::Instrument;
 
+use crate::annotation::{annotate_call_stack, FrameAnnotator};
+use crate::crash_category::{categorize_crash, CrashCategoryOptions};
 use crate::op_analysis::MemoryAccess;
-use crate::process_state::{LinuxStandardBase, ProcessState};
-use crate::{arg_recovery, evil, AdjustedAddress, LinuxProcLimits, LinuxProcStatus};
+use crate::process_state::{CrashNote, DumpKind, LinuxStandardBase, MemoryCounters, ProcessState};
+use crate::referenced_modules::ModuleRangeIndex;
+use crate::signature::SignatureOptions;
+use crate::source_context::{SourceContextOptions, SourceReader};
+use crate::source_link::SourceLinkOptions;
+use crate::symbol_filter::{FilteredSymbolProvider, ModuleFilter};
+use crate::{
+    arg_recovery, consistency, crash_message, environment, evil, extra, main_module, memory_usage,
+    redact, referenced_modules, thread_groups, thread_identity, thunks, AdjustedAddress,
+    LinuxCpuInfo, LinuxProcLimits, LinuxProcStatus,
+};
 
 /// Configuration of the processor's exact behaviour.
 ///
@@ -65,12 +81,168 @@ pub struct ProcessorOptions<'a> {
     /// (inferred from whether the symbol name looks like a static function or a method).
     pub recover_function_args: bool,
 
+    /// **\[UNSTABLE\]** Whether to read the parameters of x86 frames off the stack,
+    /// according to the parameter size their symbols record.
+    ///
+    /// The values (and any module they point into) end up in
+    /// [`StackFrame::heuristic_parameters`][minidump_unwind::StackFrame::heuristic_parameters].
+    /// Unlike `recover_function_args` this doesn't guess how many arguments a
+    /// function has from its name, but it's still a heuristic: the function may
+    /// have overwritten its parameters by the time of the crash.
+    pub recover_arguments: bool,
+
     /// Set this value to subscribe to live statistics during the processing.
     ///
     /// See [`PendingProcessorStats`] and [`PendingProcessorStatSubscriptions`].
     pub stat_reporter: Option<&'a PendingProcessorStats>,
+
+    /// How to compute the crash signature reported by [`ProcessState::signature`].
+    ///
+    /// See the [`signature`][crate::signature] module for details.
+    pub signature_options: SignatureOptions,
+
+    /// How to build the `source_link`s of frames in the JSON report, for
+    /// symbols that are source indexed.
+    ///
+    /// See the [`source_link`][crate::source_link] module for details.
+    pub source_link_options: SourceLinkOptions,
+
+    /// How to categorize crashes into [`ProcessState::crash_category`].
+    ///
+    /// See the [`crash_category`][crate::crash_category] module for details.
+    pub crash_category_options: CrashCategoryOptions,
+
+    /// If the largest free block of the address space is smaller than this (in
+    /// bytes), the process likely ran out of address space.
+    ///
+    /// See [`MemoryUsage::likely_oom`].
+    pub oom_free_block_threshold: u64,
+
+    /// Whether to look up symbols for every module, instead of just the ones
+    /// the stack walk runs into.
+    ///
+    /// This is slower (especially if symbols have to be downloaded), but
+    /// [`ProcessState::symbol_stats`] then describes every module.
+    pub symbolize_all_modules: bool,
+
+    /// Which modules symbols may be looked up for at all.
+    ///
+    /// See the [`symbol_filter`][crate::symbol_filter] module for details.
+    pub module_filter: ModuleFilter,
+
+    /// If set, the source code around the line of every frame with source info
+    /// is looked up locally and stored in
+    /// [`StackFrame::source_context`][minidump_unwind::StackFrame::source_context].
+    ///
+    /// See the [`source_context`][crate::source_context] module for details.
+    pub source_context: Option<SourceContextOptions>,
+
+    /// Whether to group the threads with the same stacks into
+    /// [`ProcessState::thread_groups`], and find out what they're blocked on.
+    pub group_threads: bool,
+
+    /// Whether to remove the frames of thunks (like `foo@plt` stubs and import
+    /// thunks) from the threads, leaving just the functions they forward to.
+    ///
+    /// Thunk frames are marked with
+    /// [`StackFrame::is_thunk`][minidump_unwind::StackFrame::is_thunk] either
+    /// way. See the [`thunks`][crate::thunks] module for details.
+    pub merge_thunk_frames: bool,
+
+    /// Whether to keep the frames of the threads whose thunk frames were
+    /// merged in [`CallStack::raw_frames`][minidump_unwind::CallStack::raw_frames].
+    pub keep_raw_frames: bool,
+
+    /// If set, this is shown every frame of every thread after symbolication,
+    /// so it can add [`StackFrame::annotations`][minidump_unwind::StackFrame::annotations].
+    ///
+    /// See [`FrameAnnotator`] for details.
+    pub frame_annotator: Option<&'a dyn FrameAnnotator>,
+
+    /// The environment variables to include in [`ProcessState::environment`].
+    ///
+    /// `*` matches any sequence of characters (so `MOZ_*` includes all of
+    /// Firefox's debugging flags), and names are matched case-insensitively
+    /// on Windows. Environment variables often hold secrets, so only add
+    /// variables whose values you'd be comfortable publishing with the report.
+    ///
+    /// Defaults to [`DEFAULT_ENVIRONMENT_VARIABLES`].
+    pub environment_variables: Vec<String>,
+
+    /// Which streams hold the crashes of managed runtimes (like Java exceptions)
+    /// to read into [`ProcessState::auxiliary_crashes`].
+    ///
+    /// See the [`auxiliary`][minidump::auxiliary] module for details. Defaults
+    /// to none, as the stream types depend on the crash reporter.
+    pub auxiliary_streams: AuxiliaryStreams,
+
+    /// Crash annotations from outside of the minidump, like the ones of a
+    /// `.extra` file (see [`extra::parse_extra`]).
+    ///
+    /// They're copied into [`ProcessState::crash_annotations`], and a few of
+    /// them inform the processing, see the [`extra`][crate::extra] module.
+    pub extra_annotations: serde_json::Map<String, serde_json::Value>,
+
+    /// Whether to leave out what the minidump's memory says about the user,
+    /// for reports that are shared outside of the crash's immediate audience.
+    ///
+    /// This withholds the environment, the recovered function arguments and
+    /// the macOS crash info, and replaces the crash message, the assertion,
+    /// the messages of auxiliary crashes and the crash annotations matching
+    /// [`ProcessorOptions::redacted_annotations`] with hashes (which still
+    /// tell identical ones apart). What was withheld is listed in
+    /// [`ProcessState::redacted_sections`].
+    ///
+    /// The hashes aren't cryptographic, short values can be guessed from them.
+    /// The code of the modules (instruction bytes and disassembly) is kept.
+    pub privacy_mode: bool,
+
+    /// The names of the crash annotations whose values privacy mode hashes
+    /// (both the [`extra_annotations`][Self::extra_annotations] and the string
+    /// annotations of the minidump's Crashpad info).
+    ///
+    /// `*` matches any sequence of characters, and names are matched
+    /// ASCII case-insensitively. Defaults to [`DEFAULT_REDACTED_ANNOTATIONS`].
+    pub redacted_annotations: Vec<String>,
 }
 
+/// The default [`ProcessorOptions::oom_free_block_threshold`] (2 MiB).
+pub const DEFAULT_OOM_FREE_BLOCK_THRESHOLD: u64 = 2 * 1024 * 1024;
+
+/// The default [`ProcessorOptions::environment_variables`]: variables that
+/// change how the process was loaded or which platform features it uses, and
+/// don't usually contain anything private.
+pub const DEFAULT_ENVIRONMENT_VARIABLES: &[&str] = &[
+    "LD_PRELOAD",
+    "LD_LIBRARY_PATH",
+    "LD_BIND_NOW",
+    "DYLD_INSERT_LIBRARIES",
+    "DYLD_LIBRARY_PATH",
+    "LANG",
+    "LC_ALL",
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "XDG_SESSION_TYPE",
+    "PROCESSOR_ARCHITECTURE",
+    "NUMBER_OF_PROCESSORS",
+    "__COMPAT_LAYER",
+];
+
+/// The default [`ProcessorOptions::redacted_annotations`]: annotations that
+/// usually hold what the user wrote, visited or is called.
+pub const DEFAULT_REDACTED_ANNOTATIONS: &[&str] = &[
+    "*url*",
+    "*email*",
+    "*comment*",
+    "*user*",
+    "*path*",
+    "*notes*",
+    "*token*",
+    "*password*",
+    "*secret*",
+    "*cookie*",
+];
+
 /// A subscription to various live updates during minidump processing.
 ///
 /// Construct it with [`PendingProcessorStats::new`] and pass it into
@@ -272,11 +444,26 @@ impl PendingProcessorStats {
     }
 }
 
+fn default_environment_variables() -> Vec<String> {
+    DEFAULT_ENVIRONMENT_VARIABLES
+        .iter()
+        .map(|&name| name.to_owned())
+        .collect()
+}
+
+fn default_redacted_annotations() -> Vec<String> {
+    DEFAULT_REDACTED_ANNOTATIONS
+        .iter()
+        .map(|&name| name.to_owned())
+        .collect()
+}
+
 impl ProcessorOptions<'_> {
     /// "Do the normal stuff everyone should want"
     ///
     /// * `evil_json: None`
     /// * `recover_function_args: false`
+    /// * `recover_arguments: false`
     ///
     /// Unlike stable_all, you shouldn't expect this to change its results much.
     ///
@@ -290,7 +477,24 @@ impl ProcessorOptions<'_> {
         ProcessorOptions {
             evil_json: None,
             recover_function_args: false,
+            recover_arguments: false,
             stat_reporter: None,
+            signature_options: SignatureOptions::default(),
+            source_link_options: SourceLinkOptions::default(),
+            crash_category_options: CrashCategoryOptions::default(),
+            oom_free_block_threshold: DEFAULT_OOM_FREE_BLOCK_THRESHOLD,
+            symbolize_all_modules: false,
+            module_filter: ModuleFilter::default(),
+            source_context: None,
+            group_threads: true,
+            merge_thunk_frames: false,
+            keep_raw_frames: false,
+            frame_annotator: None,
+            environment_variables: default_environment_variables(),
+            auxiliary_streams: AuxiliaryStreams::default(),
+            extra_annotations: serde_json::Map::new(),
+            privacy_mode: false,
+            redacted_annotations: default_redacted_annotations(),
         }
     }
 
@@ -298,6 +502,7 @@ impl ProcessorOptions<'_> {
     ///
     /// * `evil_json: None`
     /// * `recover_function_args: false`
+    /// * `recover_arguments: false`
     ///
     /// (At this precise moment this is identical to stable_basic, but may diverge
     /// as we introduce more features.)
@@ -309,7 +514,24 @@ impl ProcessorOptions<'_> {
         ProcessorOptions {
             evil_json: None,
             recover_function_args: false,
+            recover_arguments: false,
             stat_reporter: None,
+            signature_options: SignatureOptions::default(),
+            source_link_options: SourceLinkOptions::default(),
+            crash_category_options: CrashCategoryOptions::default(),
+            oom_free_block_threshold: DEFAULT_OOM_FREE_BLOCK_THRESHOLD,
+            symbolize_all_modules: false,
+            module_filter: ModuleFilter::default(),
+            source_context: None,
+            group_threads: true,
+            merge_thunk_frames: false,
+            keep_raw_frames: false,
+            frame_annotator: None,
+            environment_variables: default_environment_variables(),
+            auxiliary_streams: AuxiliaryStreams::default(),
+            extra_annotations: serde_json::Map::new(),
+            privacy_mode: false,
+            redacted_annotations: default_redacted_annotations(),
         }
     }
 
@@ -317,6 +539,7 @@ impl ProcessorOptions<'_> {
     ///
     /// * `evil_json: None`
     /// * `recover_function_args: true`
+    /// * `recover_arguments: true`
     ///
     /// (evil_json is still "disabled" because you need to give it needs a path.)
     ///
@@ -325,7 +548,24 @@ impl ProcessorOptions<'_> {
         ProcessorOptions {
             evil_json: None,
             recover_function_args: true,
+            recover_arguments: true,
             stat_reporter: None,
+            signature_options: SignatureOptions::default(),
+            source_link_options: SourceLinkOptions::default(),
+            crash_category_options: CrashCategoryOptions::default(),
+            oom_free_block_threshold: DEFAULT_OOM_FREE_BLOCK_THRESHOLD,
+            symbolize_all_modules: false,
+            module_filter: ModuleFilter::default(),
+            source_context: None,
+            group_threads: true,
+            merge_thunk_frames: false,
+            keep_raw_frames: false,
+            frame_annotator: None,
+            environment_variables: default_environment_variables(),
+            auxiliary_streams: AuxiliaryStreams::default(),
+            extra_annotations: serde_json::Map::new(),
+            privacy_mode: false,
+            redacted_annotations: default_redacted_annotations(),
         }
     }
 
@@ -335,7 +575,7 @@ impl ProcessorOptions<'_> {
         // Currently nothing is deprecated / disableable, but here's the template.
 
         /*
-        use log::warn;
+        use tracing::warn;
 
         if self.my_bad_feature {
             warn!("Deprecated ProcessorOption my_bad_feature has been removed and does nothing.")
@@ -385,6 +625,9 @@ impl ProcessError {
 /// This is equivalent to [`process_minidump_with_options`] with
 /// [`ProcessorOptions::stable_basic`][].
 ///
+/// `dump` can be backed by anything holding the bytes of the minidump, like a
+/// memory-mapped file or a buffer that is already in memory (see [`Minidump`]).
+///
 /// # Examples
 ///
 /// ```
@@ -418,25 +661,20 @@ where
 }
 
 /// Get the microcode version from linux cpu info and evil options.
-fn get_microcode_version(linux_cpu_info: &MinidumpLinuxCpuInfo, evil: &evil::Evil) -> Option<u64> {
+fn get_microcode_version(linux_cpu_info: Option<&LinuxCpuInfo>, evil: &evil::Evil) -> Option<u64> {
     linux_cpu_info
-        .iter()
-        .find_map(|(key, val)| {
-            if key.as_bytes() == b"microcode" {
-                val.to_str().ok()
-            } else {
-                None
-            }
+        .and_then(|info| info.microcode_version)
+        .or_else(|| {
+            let val = evil.cpu_microcode_version.as_deref()?;
+            u64::from_str_radix(val.strip_prefix("0x")?, 16).ok()
         })
-        .or(evil.cpu_microcode_version.as_deref())
-        .and_then(|val| val.strip_prefix("0x"))
-        .and_then(|val| u64::from_str_radix(val, 16).ok())
 }
 
 /// Process `dump` with the given options and return a report as a `ProcessState`.
 ///
 /// See [`ProcessorOptions`][] for details on the specific features that can be
 /// enabled and how to choose them.
+#[tracing::instrument(name = "process_minidump", level = "debug", skip_all)]
 pub async fn process_minidump_with_options<'a, T, P>(
     dump: &Minidump<'a, T>,
     symbol_provider: &P,
@@ -463,8 +701,10 @@ struct MinidumpInfo<'a> {
     evil: crate::evil::Evil,
     thread_list: MinidumpThreadList<'a>,
     thread_names: MinidumpThreadNames,
+    thread_infos: MinidumpThreadInfoList,
     dump_system_info: MinidumpSystemInfo,
     linux_standard_base: Option<LinuxStandardBase>,
+    linux_cpu_info: Option<LinuxCpuInfo>,
     linux_proc_status: Option<LinuxProcStatus>,
     linux_proc_limits: Option<LinuxProcLimits>,
     system_info: SystemInfo,
@@ -473,6 +713,8 @@ struct MinidumpInfo<'a> {
     misc_info: Option<MinidumpMiscInfo>,
     dump_thread_id: Option<u32>,
     requesting_thread_id: Option<u32>,
+    assertion: Option<String>,
+    memory_counters: Option<MemoryCounters>,
     modules: MinidumpModuleList,
     unloaded_modules: MinidumpUnloadedModuleList,
     memory_list: UnifiedMemoryList<'a>,
@@ -482,6 +724,7 @@ struct MinidumpInfo<'a> {
     */
     memory_info: UnifiedMemoryInfoList<'a>,
     handle_data_stream: Option<MinidumpHandleDataStream>,
+    function_tables: Option<JitUnwindInfoProvider>,
     exception: Option<MinidumpException<'a>>,
     //exception_details: Option<ExceptionDetails<'a>>,
 }
@@ -514,6 +757,11 @@ impl<'a> MinidumpInfo<'a> {
             .get_stream::<MinidumpThreadNames>()
             .unwrap_or_else(|_| MinidumpThreadNames::default());
 
+        // Thread infos (start addresses, cpu times) are also just nice-to-have.
+        let thread_infos = dump
+            .get_stream::<MinidumpThreadInfoList>()
+            .unwrap_or_default();
+
         // System info is required for processing.
         let dump_system_info = dump
             .get_stream::<MinidumpSystemInfo>()
@@ -522,10 +770,7 @@ impl<'a> MinidumpInfo<'a> {
         let (os_version, os_build) = dump_system_info.os_parts();
 
         let linux_standard_base = dump.get_stream::<MinidumpLinuxLsbRelease>().ok();
-        let linux_cpu_info = dump
-            .get_stream::<MinidumpLinuxCpuInfo>()
-            .unwrap_or_default();
-        let _linux_environ = dump.get_stream::<MinidumpLinuxEnviron>().ok();
+        let linux_cpu_info = dump.get_stream::<MinidumpLinuxCpuInfo>().ok();
         let linux_proc_status = dump.get_stream::<MinidumpLinuxProcStatus>().ok();
         let linux_proc_limits = dump.get_stream::<MinidumpLinuxProcLimits>().ok();
 
@@ -535,22 +780,50 @@ impl<'a> MinidumpInfo<'a> {
         // would care about. So just providing an iterator and letting minidump-processor
         // pull out the things it cares about is simple and effective.
 
-        let cpu_microcode_version = get_microcode_version(&linux_cpu_info, &evil);
-
         let linux_standard_base = linux_standard_base.map(LinuxStandardBase::from);
+        let linux_cpu_info = linux_cpu_info.map(LinuxCpuInfo::from);
         let linux_proc_status = linux_proc_status.map(LinuxProcStatus::from);
         let linux_proc_limits = linux_proc_limits.map(LinuxProcLimits::from);
 
+        let cpu_microcode_version = get_microcode_version(linux_cpu_info.as_ref(), &evil);
+        // /proc/cpuinfo names the CPUs that the system info can't describe (like ARM ones).
         let cpu_info = dump_system_info
             .cpu_info()
-            .map(|string| string.into_owned());
+            .map(|string| string.into_owned())
+            .or_else(|| linux_cpu_info.as_ref()?.model_name.clone());
+        let mut cpu_features: Vec<String> = dump_system_info
+            .cpu_features()
+            .iter()
+            .map(|&feature| feature.to_owned())
+            .collect();
+        if let (true, Some(info)) = (cpu_features.is_empty(), &linux_cpu_info) {
+            cpu_features = info.flags.clone();
+        }
+
+        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+        let os_build_lab = misc_info.as_ref().and_then(|info| info.build_string());
+        let os_distro = linux_standard_base
+            .as_ref()
+            .and_then(LinuxStandardBase::distro);
+
+        // Threads running emulated code have contexts of the emulated CPU.
+        let cpu_emulation = thread_list.threads.iter().find_map(|thread| {
+            let context = thread
+                .read_context(&dump_system_info, misc_info.as_ref())
+                .ok()?;
+            CpuEmulation::from_cpus(dump_system_info.cpu, context.raw.cpu())
+        });
 
         let system_info = SystemInfo {
             os: dump_system_info.os,
             os_version: Some(os_version),
             os_build,
+            os_build_lab,
+            os_distro,
             cpu: dump_system_info.cpu,
+            cpu_emulation,
             cpu_info,
+            cpu_features,
             cpu_microcode_version,
             cpu_count: dump_system_info.raw.number_of_processors as usize,
         };
@@ -562,7 +835,6 @@ impl<'a> MinidumpInfo<'a> {
 
         let mac_boot_args = dump.get_stream::<MinidumpMacBootargs>().ok();
 
-        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
         // If Breakpad info exists in dump, get dump and requesting thread ids.
         let breakpad_info = dump.get_stream::<MinidumpBreakpadInfo>();
         let (dump_thread_id, requesting_thread_id) = if let Ok(info) = breakpad_info {
@@ -571,6 +843,14 @@ impl<'a> MinidumpInfo<'a> {
             (None, None)
         };
         // Get assertion
+        let assertion = dump
+            .get_stream::<MinidumpAssertion>()
+            .ok()
+            .map(|assertion| assertion.description());
+        let memory_counters = memory_usage::compute_memory_counters(
+            dump.get_stream::<MinidumpSystemMemoryInfo>().ok().as_ref(),
+            dump.get_stream::<MinidumpProcessVmCounters>().ok().as_ref(),
+        );
         let modules = match dump.get_stream::<MinidumpModuleList>() {
             Ok(module_list) => module_list,
             // Just give an empty list, simplifies things.
@@ -587,6 +867,12 @@ impl<'a> MinidumpInfo<'a> {
         let memory_info =
             UnifiedMemoryInfoList::new(memory_info_list, linux_maps).unwrap_or_default();
         let handle_data_stream = dump.get_stream::<MinidumpHandleDataStream>().ok();
+        // The unwind info that (x86-64) JITs registered for their code.
+        let function_tables = dump
+            .get_stream::<MinidumpFunctionTableStream>()
+            .ok()
+            .filter(|_| system_info.cpu == system_info::Cpu::X86_64)
+            .map(|tables| JitUnwindInfoProvider::from_function_tables(&tables, &memory_list));
 
         // Get exception info if it exists.
         let exception = dump.get_stream::<MinidumpException>().ok();
@@ -596,8 +882,10 @@ impl<'a> MinidumpInfo<'a> {
             evil,
             thread_list,
             thread_names,
+            thread_infos,
             dump_system_info,
             linux_standard_base,
+            linux_cpu_info,
             linux_proc_status,
             linux_proc_limits,
             system_info,
@@ -606,6 +894,8 @@ impl<'a> MinidumpInfo<'a> {
             misc_info,
             dump_thread_id,
             requesting_thread_id,
+            assertion,
+            memory_counters,
             modules,
             unloaded_modules,
             memory_list,
@@ -615,6 +905,7 @@ impl<'a> MinidumpInfo<'a> {
             */
             memory_info,
             handle_data_stream,
+            function_tables,
             exception,
             //exception_details: None,
         })
@@ -623,9 +914,32 @@ impl<'a> MinidumpInfo<'a> {
     /// Get details about the minidump exception, if available.
     pub fn get_exception_details(&self) -> Option<ExceptionDetails<'a>> {
         let exception = self.exception.as_ref()?;
-
-        let reason = exception.get_crash_reason(self.system_info.os, self.system_info.cpu);
-        let address = exception.get_crash_address(self.system_info.os, self.system_info.cpu);
+        let (os, cpu) = (self.system_info.os, self.system_info.cpu);
+
+        let exception_chain = exception.exception_chain(&self.memory_list, cpu);
+        let primary = &exception_chain[primary_exception(&exception_chain, os)];
+        let reason = primary.get_crash_reason(os, cpu);
+        let address = primary.get_crash_address(os, cpu);
+        let detail = primary.get_crash_reason_detail(os, cpu);
+        let chain = exception_chain
+            .iter()
+            .map(|exception| {
+                let record = &exception.raw.exception_record;
+                let address = match cpu.pointer_width() {
+                    PointerWidth::Bits32 => record.exception_address as u32 as u64,
+                    _ => record.exception_address,
+                };
+                let parameter_count =
+                    (record.number_parameters as usize).min(record.exception_information.len());
+                crate::ChainedException {
+                    code: record.exception_code,
+                    flags: record.exception_flags,
+                    address: address.into(),
+                    parameters: record.exception_information[..parameter_count].to_vec(),
+                    reason: exception.get_crash_reason(os, cpu),
+                }
+            })
+            .collect();
 
         let stack_memory_ref = self
             .thread_list
@@ -637,6 +951,18 @@ impl<'a> MinidumpInfo<'a> {
         let mut exception_info: Option<crate::ExceptionInfo> = None;
         let mut instruction_registers: BTreeSet<&'static str> = Default::default();
 
+        let (instruction_bytes, disassembly) = match context.as_ref() {
+            Some(context) => (
+                crate::op_analysis::read_instruction_bytes(
+                    context,
+                    &self.memory_list,
+                    &self.memory_info,
+                ),
+                crate::op_analysis::disassemble_instructions(context, &self.memory_list),
+            ),
+            None => (crate::InstructionBytes::NotCaptured, Vec::new()),
+        };
+
         // If we have a context, we can attempt to analyze the crashing thread's instructions
         if let Some(context) = context.as_ref() {
             match crate::op_analysis::analyze_thread_context(
@@ -664,8 +990,13 @@ impl<'a> MinidumpInfo<'a> {
                         address: address.into(),
                         adjusted_address,
                         instruction_str: Some(op_analysis.instruction_str),
+                        instruction_bytes: instruction_bytes.clone(),
+                        disassembly: disassembly.clone(),
                         memory_accesses: op_analysis.memory_accesses,
                         possible_bit_flips: Default::default(),
+                        message: None,
+                        detail: detail.clone(),
+                        notes: Vec::new(),
                     });
                     instruction_registers = op_analysis.registers;
                 }
@@ -680,14 +1011,20 @@ impl<'a> MinidumpInfo<'a> {
             address: address.into(),
             adjusted_address: None,
             instruction_str: None,
+            instruction_bytes,
+            disassembly,
             memory_accesses: None,
             possible_bit_flips: Default::default(),
+            message: None,
+            detail,
+            notes: Vec::new(),
         });
 
         Some(ExceptionDetails {
             info,
             context,
             instruction_registers,
+            chain,
         })
     }
 
@@ -819,16 +1156,21 @@ impl<'a> MinidumpInfo<'a> {
         T: Deref<Target = [u8]> + 'a,
         P: SymbolProvider + Sync,
     {
+        let symbol_provider = &FilteredSymbolProvider::new(
+            symbol_provider,
+            &self.options.module_filter,
+            self.function_tables.as_ref(),
+        );
         let crashing_thread_id = self.exception.as_ref().map(|e| e.get_crashing_thread_id());
 
-        let (exception_info, exception_context) = match exception_details {
-            Some(details) => (Some(details.info), details.context),
-            None => (None, None),
+        let (mut exception_info, exception_context, exception_chain) = match exception_details {
+            Some(details) => (Some(details.info), details.context, details.chain),
+            None => (None, None, Vec::new()),
         };
 
         let mut requesting_thread = None;
 
-        let threads = self
+        let mut threads: Vec<CallStack> = self
             .thread_list
             .threads
             .iter()
@@ -842,15 +1184,20 @@ impl<'a> MinidumpInfo<'a> {
                 }
 
                 let thread_context =
-                    thread.context(&self.dump_system_info, self.misc_info.as_ref());
+                    thread.read_context(&self.dump_system_info, self.misc_info.as_ref());
                 // If this thread requested the dump then try to use the exception
                 // context if it exists. (prefer the exception stream's thread id over
                 // the breakpad info stream's thread id.)
-                let context = if crashing_thread_id.or(self.requesting_thread_id) == Some(id) {
+                // If several threads have the id, the first one is it.
+                let context = if crashing_thread_id.or(self.requesting_thread_id) == Some(id)
+                    && requesting_thread.is_none()
+                {
                     requesting_thread = Some(i);
-                    exception_context.as_deref().or(thread_context.as_deref())
+                    exception_context
+                        .as_deref()
+                        .or(thread_context.as_ref().ok())
                 } else {
-                    thread_context.as_deref()
+                    thread_context.as_ref().ok()
                 };
 
                 let name = self
@@ -858,6 +1205,15 @@ impl<'a> MinidumpInfo<'a> {
                     .get_name(thread.raw.thread_id)
                     .map(|cow| cow.into_owned());
 
+                let thread_info = self.thread_infos.get_thread_info(id);
+                let start_address = thread_info.and_then(|info| info.start_address());
+                let times = thread_info.map(|info| ThreadTimes {
+                    create_time: info.raw.create_time,
+                    exit_time: info.raw.exit_time,
+                    kernel_time: info.raw.kernel_time,
+                    user_time: info.raw.user_time,
+                });
+
                 let (info, frames) = if let Some(context) = context {
                     let ctx = context.clone();
                     (
@@ -874,13 +1230,47 @@ impl<'a> MinidumpInfo<'a> {
                     thread_id: id,
                     thread_name: name,
                     last_error_value: thread.last_error(self.system_info.cpu, &self.memory_list),
+                    start_address,
+                    start_function: None,
+                    times,
+                    walk_terminated_reason: None,
+                    stack_memory_repair: None,
+                    thread_context: Some(thread_context),
+                    raw_frames: None,
+                    thread_identity: None,
                 }
             })
             .collect();
 
+        if let Some(i) = requesting_thread {
+            threads[i].thread_identity = thread_identity::read_thread_identity(
+                &self.system_info,
+                &self.thread_list.threads[i],
+                &self.memory_list,
+                &self.thread_names,
+                &self.thread_infos,
+            );
+        }
+
+        let consistency_warnings = consistency::check_consistency(
+            dump.header.time_date_stamp,
+            crashing_thread_id,
+            &self.thread_list,
+            &threads,
+            &self.modules,
+            &self.memory_list,
+            dump.get_stream::<MinidumpLinuxDsoDebug>().ok().as_ref(),
+        );
+
+        let auxiliary_crashes = self.options.auxiliary_streams.read(dump);
+
         // Collect up info on unimplemented/unknown modules
-        let unknown_streams = dump.unknown_streams().collect();
+        let unknown_streams = dump
+            .unknown_streams()
+            .filter(|stream| !self.options.auxiliary_streams.contains(stream.stream_type))
+            .collect();
         let unimplemented_streams = dump.unimplemented_streams().collect();
+        let duplicate_streams = dump.duplicate_streams().to_vec();
 
         // Get symbol stats from the symbolizer
         let symbol_stats = symbol_provider.stats();
@@ -890,6 +1280,7 @@ impl<'a> MinidumpInfo<'a> {
             misc_info.raw.process_id().cloned()
         } else {
             self.linux_proc_status
+                .as_ref()
                 .map(|linux_proc_status| linux_proc_status.pid)
         };
 
@@ -899,26 +1290,91 @@ impl<'a> MinidumpInfo<'a> {
             None
         };
 
+        let linux_cmdline = dump.get_stream::<MinidumpLinuxCmdLine>().ok();
+        let mut environment = environment::process_environment(
+            &self.system_info,
+            linux_cmdline.as_ref(),
+            dump.get_stream::<MinidumpLinuxEnviron>().ok().as_ref(),
+            &self.thread_list,
+            &self.memory_list,
+            &self.options.environment_variables,
+        );
+
+        let image_base = environment::process_image_base(
+            &self.system_info,
+            &self.thread_list,
+            &self.memory_list,
+        );
+        let image_path = environment
+            .as_ref()
+            .and_then(|environment| environment.executable_path.clone())
+            .or_else(|| {
+                let cmdline = linux_cmdline.as_ref()?;
+                let program = cmdline.args().next()?;
+                Some(program.to_string_lossy().into_owned())
+            });
+        let modules = &self.modules;
+        let main_module = main_module::find_main_module(modules, image_base, image_path.as_deref());
+        // Without better sources, the path of the executable is the path of the main module.
+        if let Some(module) = main_module.and_then(|index| modules.iter().nth(index)) {
+            let environment = environment.get_or_insert_with(Default::default);
+            if environment.executable_path.is_none() {
+                environment.executable_path = Some(module.code_file().into_owned());
+            }
+        }
+
+        let crashpad_info = dump.get_stream::<MinidumpCrashpadInfo>().ok();
+        let dump_kind = extra::dump_kind(
+            DumpKind::infer(exception_info.as_ref(), crashpad_info.as_ref()),
+            &self.options.extra_annotations,
+        );
+        if let Some(info) = exception_info.as_mut() {
+            info.notes
+                .extend(extra::crash_notes(&self.options.extra_annotations));
+        }
+
         let mut state = ProcessState {
             process_id,
             time: SystemTime::UNIX_EPOCH + Duration::from_secs(dump.header.time_date_stamp as u64),
             process_create_time,
             cert_info: self.evil.certs,
+            dump_kind,
             exception_info,
-            assertion: None,
+            assertion: self.assertion,
             requesting_thread,
+            dump_thread_id: self.dump_thread_id,
+            requesting_thread_id: self.requesting_thread_id,
             system_info: self.system_info,
             linux_standard_base: self.linux_standard_base,
+            linux_cpu_info: self.linux_cpu_info,
             linux_proc_limits: self.linux_proc_limits,
+            linux_proc_status: self.linux_proc_status,
             mac_crash_info: self.mac_crash_info,
             mac_boot_args: self.mac_boot_args,
             threads,
             modules: self.modules,
+            main_module,
             unloaded_modules: self.unloaded_modules,
             handles: self.handle_data_stream,
             unknown_streams,
             unimplemented_streams,
+            duplicate_streams,
             symbol_stats,
+            dump_integrity: dump.corruption_notes().to_vec(),
+            signature_options: self.options.signature_options.clone(),
+            source_link_options: self.options.source_link_options.clone(),
+            crash_category: None,
+            referenced_modules: Vec::new(),
+            memory_usage: None,
+            memory_counters: self.memory_counters,
+            environment,
+            thread_groups: Vec::new(),
+            auxiliary_crashes,
+            exception_chain,
+            crash_annotations: self.options.extra_annotations.clone(),
+            crashpad_info,
+            consistency_warnings,
+            redacted_sections: None,
         };
 
         // Report the unwalked result
@@ -939,76 +1395,201 @@ impl<'a> MinidumpInfo<'a> {
                     .iter_mut()
                     .zip(self.thread_list.threads.iter())
                     .enumerate()
-                    .map(|(i, (stack, thread))| async move {
-                        let mut stack_memory = thread.stack_memory(memory_list);
-                        // Always choose the memory region that is referenced by the context,
-                        // as the `exception_context` may refer to a different memory region than
-                        // the `thread_context`, which in turn would fail to stack walk.
-                        let stack_ptr = stack
-                            .frames
-                            .first()
-                            .map(|ctx_frame| ctx_frame.context.get_stack_pointer());
-                        if let Some(stack_ptr) = stack_ptr {
-                            let contains_stack_ptr = stack_memory
-                                .as_ref()
-                                .and_then(|memory| memory.get_memory_at_address::<u64>(stack_ptr))
-                                .is_some();
-                            if !contains_stack_ptr {
-                                stack_memory =
-                                    memory_list.memory_at_address(stack_ptr).or(stack_memory);
+                    .map(|(i, (stack, thread))| {
+                        let span = tracing::debug_span!(
+                            "walk_thread",
+                            idx = i,
+                            thread_id = stack.thread_id,
+                            thread_name = stack.thread_name.as_deref().unwrap_or(""),
+                        );
+                        async move {
+                            let stack_ptr = stack
+                                .frames
+                                .first()
+                                .map(|ctx_frame| ctx_frame.context.get_stack_pointer());
+                            let (stack_memory, repair) =
+                                thread_stack_memory(thread, stack_ptr, memory_list);
+                            if let Some(repair) = repair {
+                                tracing::warn!("thread {}: {}", stack.thread_id, repair);
                             }
-                        }
-
-                        walk_stack(
-                            i,
-                            |frame_idx: usize, frame: &StackFrame| {
-                                if let Some(reporter) = options.stat_reporter {
-                                    reporter.add_walked_frame(i, frame_idx, frame);
-                                }
-                            },
-                            stack,
-                            stack_memory,
-                            modules,
-                            system_info,
-                            symbol_provider,
-                        )
-                        .await;
-
-                        for frame in &mut stack.frames {
-                            // If the frame doesn't have a loaded module, try to find an unloaded module
-                            // that overlaps with its address range. The may be multiple, so record all
-                            // of them and the offsets this frame has in them.
-                            if frame.module.is_none() {
-                                let mut offsets = BTreeMap::new();
-                                for unloaded in
-                                    unloaded_modules.modules_at_address(frame.instruction)
-                                {
-                                    let offset = frame.instruction - unloaded.raw.base_of_image;
-                                    offsets
-                                        .entry(unloaded.name.clone())
-                                        .or_insert_with(BTreeSet::new)
-                                        .insert(offset);
+                            stack.stack_memory_repair = repair;
+
+                            walk_stack_with_memory(
+                                i,
+                                |frame_idx: usize, frame: &StackFrame| {
+                                    if let Some(reporter) = options.stat_reporter {
+                                        reporter.add_walked_frame(i, frame_idx, frame);
+                                    }
+                                },
+                                stack,
+                                stack_memory,
+                                Some(memory_list),
+                                modules,
+                                system_info,
+                                symbol_provider,
+                            )
+                            .await;
+
+                            for frame in &mut stack.frames {
+                                // If the frame doesn't have a loaded module, try to find an unloaded module
+                                // that overlaps with its address range. The may be multiple, so record all
+                                // of them and the offsets this frame has in them.
+                                if frame.module.is_none() {
+                                    let mut offsets = BTreeMap::new();
+                                    for unloaded in
+                                        unloaded_modules.modules_at_address(frame.instruction)
+                                    {
+                                        let offset = frame.instruction - unloaded.raw.base_of_image;
+                                        offsets
+                                            .entry(unloaded.name.clone())
+                                            .or_insert_with(BTreeSet::new)
+                                            .insert(offset);
+                                    }
+
+                                    frame.unloaded_modules = offsets;
                                 }
+                            }
 
-                                frame.unloaded_modules = offsets;
+                            if let Some(start_address) = stack.start_address {
+                                stack.start_function = symbolize_address(
+                                    start_address,
+                                    modules,
+                                    symbol_provider,
+                                    true,
+                                )
+                                .await;
                             }
-                        }
 
-                        if options.recover_function_args {
-                            arg_recovery::fill_arguments(stack, stack_memory);
-                        }
+                            if options.recover_function_args {
+                                arg_recovery::fill_arguments(stack, stack_memory);
+                            }
 
-                        // Report the unwalked result
-                        if let Some(reporter) = options.stat_reporter {
-                            reporter.inc_processed_threads();
-                        }
+                            if options.recover_arguments {
+                                arg_recovery::fill_parameters(stack, stack_memory);
+                                for frame in &mut stack.frames {
+                                    let params = frame.heuristic_parameters.iter_mut().flatten();
+                                    for param in params {
+                                        if let Some(value) = param.value {
+                                            param.symbol = symbolize_address(
+                                                value,
+                                                modules,
+                                                symbol_provider,
+                                                false,
+                                            )
+                                            .await;
+                                        }
+                                    }
+                                }
+                            }
 
-                        stack
+                            // Report the unwalked result
+                            if let Some(reporter) = options.stat_reporter {
+                                reporter.inc_processed_threads();
+                            }
+
+                            stack
+                        }
+                        .instrument(span)
                     }),
             )
             .await
         };
 
+        if self.options.symbolize_all_modules {
+            let modules = &state.modules;
+            futures_util::future::join_all(modules.iter().map(|module| async move {
+                // Symbolizing anything in the module is enough to look up its symbols.
+                symbolize_address(module.base_address(), modules, symbol_provider, false).await
+            }))
+            .await;
+        }
+
+        for stack in &mut state.threads {
+            thunks::mark_thunk_frames(stack);
+            if self.options.merge_thunk_frames {
+                thunks::merge_thunk_frames(stack, self.options.keep_raw_frames);
+            }
+        }
+
+        if let Some(source_options) = &self.options.source_context {
+            let mut reader = SourceReader::new(source_options);
+            for stack in &mut state.threads {
+                reader.fill_call_stack(stack);
+            }
+        }
+
+        if let Some(annotator) = self.options.frame_annotator {
+            for stack in &mut state.threads {
+                annotate_call_stack(annotator, stack);
+            }
+        }
+
+        // Now that the crashing thread is symbolicated, see if it was panicking or asserting.
+        if let (Some(info), Some(requesting_thread)) =
+            (state.exception_info.as_mut(), state.requesting_thread)
+        {
+            let stack = &state.threads[requesting_thread];
+            info.message = crash_message::recover_crash_message(
+                stack,
+                &self.memory_list,
+                state.system_info.cpu.pointer_width(),
+            );
+
+            if let Some(frame) = stack.frames.first() {
+                if frame.symbol_disposition == Some(SymbolDisposition::NoCoveringSymbol) {
+                    if let Some(module) = &frame.module {
+                        info.notes.push(CrashNote::NoCoveringSymbol {
+                            module: basename(&module.code_file()).to_owned(),
+                        });
+                    }
+                }
+            }
+        }
+
+        state.crash_category = categorize_crash(&state, &self.options.crash_category_options);
+        if let UnifiedMemoryInfoList::Info(memory_info) = &self.memory_info {
+            state.memory_usage = memory_usage::compute_memory_usage(
+                memory_info,
+                state.crash_category.as_ref(),
+                self.options.oom_free_block_threshold,
+                extra::total_virtual_memory(&state.crash_annotations),
+            );
+        }
+
+        // Regardless of how well the stackwalk went, look for modules the crashing thread's stack
+        // refers to.
+        if let Some(requesting_thread) = state.requesting_thread {
+            let stack_pointer = state.threads[requesting_thread]
+                .frames
+                .first()
+                .map(|frame| frame.context.get_stack_pointer());
+            let memory_list = &self.memory_list;
+            let thread = self.thread_list.threads.get(requesting_thread);
+            let stack_memory = stack_pointer.and_then(|stack_pointer| {
+                memory_list
+                    .memory_at_address(stack_pointer)
+                    .or_else(|| thread?.stack_memory(memory_list))
+            });
+            if let (Some(stack_pointer), Some(stack_memory)) = (stack_pointer, stack_memory) {
+                let index = ModuleRangeIndex::new(&state.modules, &self.memory_info);
+                state.referenced_modules = referenced_modules::find_referenced_modules(
+                    stack_pointer,
+                    &stack_memory,
+                    &state.modules,
+                    &index,
+                    state.system_info.cpu.pointer_width(),
+                );
+            }
+        }
+
+        if self.options.group_threads {
+            state.thread_groups = thread_groups::group_threads(&state.threads);
+        }
+
+        if self.options.privacy_mode {
+            redact::redact(&mut state, &self.options.redacted_annotations);
+        }
+
         let symbol_stats = symbol_provider.stats();
         state.symbol_stats = symbol_stats;
 
@@ -1016,10 +1597,147 @@ impl<'a> MinidumpInfo<'a> {
     }
 }
 
+/// The memory to walk a thread's stack in, and how it was repaired if the
+/// thread's stack memory descriptor doesn't match its context.
+///
+/// The region that contains the stack pointer is always preferred: the
+/// `exception_context` may refer to a different region than the thread's
+/// context, and some writers emit stack descriptors that don't cover the stack
+/// pointer (or are empty) while the memory list has the actual stack.
+fn thread_stack_memory<'mem, 'a>(
+    thread: &'mem MinidumpThread<'a>,
+    stack_ptr: Option<u64>,
+    memory_list: &'mem UnifiedMemoryList<'a>,
+) -> (Option<UnifiedMemory<'mem, 'a>>, Option<StackMemoryRepair>) {
+    let desc = &thread.raw.stack;
+    let mut stack_memory = thread.stack_memory(memory_list);
+    let mut repair = None;
+    if desc.memory.data_size == 0 {
+        // `stack_memory` already falls back to the region at the descriptor's base.
+        repair = stack_memory
+            .as_ref()
+            .map(|memory| StackMemoryRepair::EmptyStack {
+                stack_base: desc.start_of_memory_range,
+                region_base: memory.base_address(),
+                region_size: memory.size(),
+            });
+    }
+
+    let Some(stack_ptr) = stack_ptr else {
+        return (stack_memory, repair);
+    };
+    let contains_stack_ptr = stack_memory
+        .as_ref()
+        .and_then(|memory| memory.get_memory_at_address::<u64>(stack_ptr))
+        .is_some();
+    if !contains_stack_ptr {
+        if let Some(memory) = memory_list.memory_at_address(stack_ptr) {
+            repair = Some(StackMemoryRepair::StackPointerOutsideStack {
+                stack_pointer: stack_ptr,
+                stack_base: desc.start_of_memory_range,
+                stack_size: desc.memory.data_size as u64,
+                region_base: memory.base_address(),
+                region_size: memory.size(),
+            });
+            stack_memory = Some(memory);
+        }
+    }
+    (stack_memory, repair)
+}
+
+/// Describe `address` as `module!function`, or `module + offset` if there are
+/// no symbols for it.
+///
+/// With `short_module_name`, the module is named without its extension, the
+/// way debuggers name thread start addresses ("ntdll!TppWorkerThread").
+///
+/// Returns `None` if `address` isn't inside any loaded module.
+async fn symbolize_address<P>(
+    address: u64,
+    modules: &MinidumpModuleList,
+    symbol_provider: &P,
+    short_module_name: bool,
+) -> Option<String>
+where
+    P: SymbolProvider + Sync,
+{
+    struct AddressSymbolizer {
+        instruction: u64,
+        function_name: Option<String>,
+    }
+    impl FrameSymbolizer for AddressSymbolizer {
+        fn get_instruction(&self) -> u64 {
+            self.instruction
+        }
+        fn set_function(&mut self, name: &str, _base: u64, _parameter_size: u32) {
+            self.function_name = Some(String::from(name));
+        }
+        fn set_source_file(&mut self, _file: &str, _line: u32, _base: u64) {
+            // Do nothing
+        }
+    }
+
+    let module = modules.module_at_address(address)?;
+    let mut symbolizer = AddressSymbolizer {
+        instruction: address,
+        function_name: None,
+    };
+    // This is best effort, so ignore any errors.
+    let _ = symbol_provider.fill_symbol(module, &mut symbolizer).await;
+
+    let code_file = module.code_file();
+    let mut module_name = basename(&code_file);
+    if short_module_name {
+        module_name = without_extension(module_name);
+    }
+    Some(match symbolizer.function_name {
+        Some(function_name) => format!("{module_name}!{function_name}"),
+        None => format!("{module_name} + {:#x}", address - module.base_address()),
+    })
+}
+
+/// `name` without its last extension, so "ntdll.dll" is "ntdll".
+fn without_extension(name: &str) -> &str {
+    match name.rfind('.') {
+        Some(index) if index > 0 => &name[..index],
+        _ => name,
+    }
+}
+
 struct ExceptionDetails<'a> {
     info: crate::ExceptionInfo,
     context: Option<std::borrow::Cow<'a, MinidumpContext>>,
     instruction_registers: BTreeSet<&'static str>,
+    chain: Vec<crate::ChainedException>,
+}
+
+/// The position of the exception in `chain` (see [`MinidumpException::exception_chain`])
+/// that describes the crash best.
+///
+/// That's the outermost one, unless it only wraps the others: then it's the
+/// innermost one that isn't a wrapper itself. E.g. an access violation that a
+/// C++ exception handler rethrew is reported as the access violation.
+fn primary_exception(chain: &[MinidumpException], os: Os) -> usize {
+    let is_wrapper = |exception: &MinidumpException| {
+        use minidump_common::errors::ExceptionCodeWindows;
+
+        let code = exception.raw.exception_record.exception_code;
+        os == Os::Windows
+            && [
+                ExceptionCodeWindows::EXCEPTION_NONCONTINUABLE_EXCEPTION,
+                ExceptionCodeWindows::UNHANDLED_CPP_EXCEPTION,
+                ExceptionCodeWindows::MANAGED_EXCEPTION_CODE_V4,
+            ]
+            .iter()
+            .any(|&wrapper| code == wrapper as u32)
+    };
+    match chain.first() {
+        Some(outermost) if is_wrapper(outermost) => chain
+            .iter()
+            .rposition(|exception| !is_wrapper(exception))
+            .unwrap_or(0),
+        _ => 0,
+    }
 }
 
 /// If a non-canonical access caused a crash, return the real address
@@ -0,0 +1,147 @@
+use minidump::MinidumpAnnotation;
+use serde_json::Value;
+
+use crate::process_state::{ProcessState, RedactedSection};
+use crate::signature::glob_match;
+
+// # Privacy mode
+//
+// Reports are sometimes shared with people who shouldn't see the user's data,
+// and a lot of what the processor recovers from the minidump's memory can be
+// that: the command line and environment, the values on the stack (the
+// function arguments), and the strings the crash left behind (panic messages,
+// assertions, the annotations the crash reporter added).
+//
+// Privacy mode is applied to the finished `ProcessState`, so the JSON report
+// and the human output (which are both produced from it) can't disagree about
+// what's left out. Strings are replaced with hashes rather than removed, so
+// crashes with the same message can still be told apart from crashes with
+// different ones.
+
+/// Withhold or hash the parts of `state` that may hold user data, and record
+/// which ones had any in [`ProcessState::redacted_sections`].
+///
+/// `redacted_annotations` are the (glob) names of the crash annotations to hash.
+pub fn redact(state: &mut ProcessState, redacted_annotations: &[String]) {
+    let mut sections = Vec::new();
+
+    if state.environment.take().is_some() {
+        sections.push(RedactedSection::Environment);
+    }
+
+    let mut had_arguments = false;
+    for stack in &mut state.threads {
+        let raw_frames = stack.raw_frames.iter_mut().flatten();
+        for frame in stack.frames.iter_mut().chain(raw_frames) {
+            had_arguments |= frame.arguments.take().is_some();
+            had_arguments |= frame.heuristic_parameters.take().is_some();
+        }
+    }
+    if had_arguments {
+        sections.push(RedactedSection::FunctionArguments);
+    }
+
+    if let Some(message) = state
+        .exception_info
+        .as_mut()
+        .and_then(|info| info.message.as_mut())
+    {
+        *message = hash_string(message);
+        sections.push(RedactedSection::CrashMessage);
+    }
+
+    if let Some(assertion) = &mut state.assertion {
+        *assertion = hash_string(assertion);
+        sections.push(RedactedSection::Assertion);
+    }
+
+    let patterns: Vec<String> = redacted_annotations
+        .iter()
+        .map(|pattern| pattern.to_ascii_lowercase())
+        .collect();
+    let is_redacted = |key: &str| {
+        let key = key.to_ascii_lowercase();
+        patterns.iter().any(|pattern| glob_match(pattern, &key))
+    };
+    let mut had_annotations = false;
+    for (key, value) in &mut state.crash_annotations {
+        if value.is_null() || !is_redacted(key) {
+            continue;
+        }
+        let text = match &*value {
+            Value::String(string) => string.clone(),
+            other => other.to_string(),
+        };
+        *value = Value::String(hash_string(&text));
+        had_annotations = true;
+    }
+    if let Some(info) = &mut state.crashpad_info {
+        let modules = info.module_list.iter_mut();
+        let module_annotations = modules.flat_map(|module| {
+            let objects = module.annotation_objects.iter_mut();
+            let strings = objects.filter_map(|(key, value)| match value {
+                MinidumpAnnotation::String(string) => Some((key, string)),
+                _ => None,
+            });
+            module.simple_annotations.iter_mut().chain(strings)
+        });
+        for (key, value) in info.simple_annotations.iter_mut().chain(module_annotations) {
+            if is_redacted(key) {
+                *value = hash_string(value);
+                had_annotations = true;
+            }
+        }
+        // List annotations have no name to tell what they hold, so they're all hashed.
+        for module in &mut info.module_list {
+            for annotation in &mut module.list_annotations {
+                *annotation = hash_string(annotation);
+                had_annotations = true;
+            }
+        }
+    }
+    if had_annotations {
+        sections.push(RedactedSection::CrashAnnotations);
+    }
+
+    let mut had_auxiliary_crashes = false;
+    for crash in &mut state.auxiliary_crashes {
+        if let Some(message) = &mut crash.message {
+            *message = hash_string(message);
+            had_auxiliary_crashes = true;
+        }
+        had_auxiliary_crashes |= crash.raw.take().is_some();
+    }
+    if had_auxiliary_crashes {
+        sections.push(RedactedSection::AuxiliaryCrashes);
+    }
+
+    if state.mac_crash_info.take().is_some() {
+        sections.push(RedactedSection::MacCrashInfo);
+    }
+
+    state.redacted_sections = Some(sections);
+}
+
+/// What a redacted string is replaced with: "redacted:" and the (64-bit FNV-1a)
+/// hash of it in hex.
+fn hash_string(string: &str) -> String {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+    let hash = string.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    });
+    format!("redacted:{hash:016x}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hash_string() {
+        // The FNV-1a test vectors
+        assert_eq!(hash_string(""), "redacted:cbf29ce484222325");
+        assert_eq!(hash_string("a"), "redacted:af63dc4c8601ec8c");
+        assert_eq!(hash_string("foobar"), "redacted:85944171f73967e8");
+    }
+}
@@ -0,0 +1,206 @@
+//! Finding the modules referenced by a thread's stack.
+//!
+//! When there's no CFI and no frame pointers, the stackwalker may give up after
+//! a frame or two (or produce garbage by scanning). Even then, the modules whose
+//! code the stack points into are a good hint of what the thread was doing.
+//!
+//! So we look at every pointer-aligned value between the stack pointer and the
+//! end of the stack memory, and count the ones that point into a module. To cut
+//! down on noise (data pointers into a module's globals, or into a module's
+//! relocations), a value only counts if the memory it points to was executable.
+//! If the minidump doesn't say whether it was (there's no memory info or maps),
+//! the whole module range is accepted.
+//!
+//! This is strictly a heuristic: return addresses left over from functions that
+//! have already returned, function pointers, vtables in executable sections...
+//! all count too. These are not frames!
+//!
+//! The processor does this for the requesting thread, see
+//! [`ProcessState::referenced_modules`][crate::ProcessState::referenced_modules].
+//! Stacks can be megabytes large, so which addresses count is worked out once
+//! in a [`ModuleRangeIndex`], which can be reused for the stacks of other threads.
+
+use std::cmp::Reverse;
+use std::convert::{TryFrom, TryInto};
+
+use minidump::system_info::PointerWidth;
+use minidump::{MinidumpModuleList, UnifiedMemory, UnifiedMemoryInfoList};
+
+use crate::ReferencedModule;
+
+/// Values below this are small integers (or null pointers plus an offset),
+/// which no module is loaded at.
+const MIN_MODULE_ADDRESS: u64 = 0x1000;
+
+/// The ranges of addresses that count as references to a module's code.
+///
+/// These are the parts of each module's range that weren't mapped without
+/// execute permission.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleRangeIndex {
+    /// Sorted, and not overlapping.
+    ranges: Vec<CodeRange>,
+    /// The number of modules the ranges are in.
+    module_count: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CodeRange {
+    start: u64,
+    /// Inclusive, so a range can end at the top of the address space.
+    end: u64,
+    /// The module's position in the modules sorted by address.
+    module: usize,
+}
+
+impl ModuleRangeIndex {
+    /// Index the code of `modules`, according to the permissions in `memory_info`.
+    pub fn new(modules: &MinidumpModuleList, memory_info: &UnifiedMemoryInfoList) -> Self {
+        // The memory regions by address, as (start, end, executable).
+        let regions: Vec<(u64, u64, bool)> = memory_info
+            .by_addr()
+            .filter_map(|info| {
+                let range = info.memory_range()?;
+                Some((range.start, range.end, info.is_executable()))
+            })
+            .collect();
+
+        let mut ranges = Vec::new();
+        let mut module_count = 0;
+        for (module, range) in modules
+            .by_addr()
+            .filter_map(|module| module.memory_range())
+            .enumerate()
+        {
+            module_count += 1;
+            // Everything in the module that isn't in a non-executable region.
+            let mut start = range.start;
+            let first = regions.partition_point(|&(_, end, _)| end < range.start);
+            for &(region_start, region_end, executable) in &regions[first..] {
+                if region_start > range.end {
+                    break;
+                }
+                if executable {
+                    continue;
+                }
+                if region_start > start {
+                    ranges.push(CodeRange {
+                        start,
+                        end: region_start - 1,
+                        module,
+                    });
+                }
+                match region_end.checked_add(1) {
+                    Some(next) => start = start.max(next),
+                    None => break,
+                }
+                if start > range.end {
+                    break;
+                }
+            }
+            if start <= range.end {
+                ranges.push(CodeRange {
+                    start,
+                    end: range.end,
+                    module,
+                });
+            }
+        }
+        // Adjacent code ranges of the same module can be merged.
+        ranges.dedup_by(|next, prev| {
+            let adjacent = prev.end.checked_add(1) == Some(next.start);
+            if adjacent && prev.module == next.module {
+                prev.end = next.end;
+                true
+            } else {
+                false
+            }
+        });
+
+        ModuleRangeIndex {
+            ranges,
+            module_count,
+        }
+    }
+
+    /// The position (in the modules sorted by address) of the module whose code
+    /// `address` points into.
+    fn module_at_address(&self, address: u64) -> Option<usize> {
+        let index = self.ranges.partition_point(|range| range.end < address);
+        let range = self.ranges.get(index)?;
+        (range.start <= address).then_some(range.module)
+    }
+}
+
+/// Find the modules whose executable code is referenced by the values on a stack.
+///
+/// The stack is scanned from `stack_pointer` to the end of `stack_memory`, and
+/// `index` must have been made from `modules`. The modules are sorted by how
+/// often they're referenced (most referenced first).
+pub fn find_referenced_modules(
+    stack_pointer: u64,
+    stack_memory: &UnifiedMemory,
+    modules: &MinidumpModuleList,
+    index: &ModuleRangeIndex,
+    pointer_width: PointerWidth,
+) -> Vec<ReferencedModule> {
+    let word_size: u64 = match pointer_width {
+        PointerWidth::Bits32 => 4,
+        PointerWidth::Bits64 => 8,
+        PointerWidth::Unknown => return Vec::new(),
+    };
+
+    let base = stack_memory.base_address();
+    let start = stack_pointer.max(base);
+    let Some(start) = start.checked_next_multiple_of(word_size) else {
+        return Vec::new();
+    };
+    let bytes = stack_memory.bytes();
+    let Some(words) = usize::try_from(start - base)
+        .ok()
+        .and_then(|offset| bytes.get(offset..))
+    else {
+        return Vec::new();
+    };
+    let (lowest, highest) = match (index.ranges.first(), index.ranges.last()) {
+        (Some(first), Some(last)) => (first.start.max(MIN_MODULE_ADDRESS), last.end),
+        _ => return Vec::new(),
+    };
+    let little_endian = stack_memory.endian().is_little();
+    let read_word = |word: &[u8]| -> u64 {
+        match (word_size, little_endian) {
+            (4, true) => u32::from_le_bytes(word.try_into().unwrap()).into(),
+            (4, false) => u32::from_be_bytes(word.try_into().unwrap()).into(),
+            (_, true) => u64::from_le_bytes(word.try_into().unwrap()),
+            (_, false) => u64::from_be_bytes(word.try_into().unwrap()),
+        }
+    };
+
+    // The reference counts of the modules, by their position in address order.
+    let mut counts = vec![0; index.module_count];
+    for word in words.chunks_exact(word_size as usize) {
+        let value = read_word(word);
+        // Most values are small integers or pointers into the stack or heap,
+        // so rule out everything outside of all modules first.
+        if value < lowest || value > highest {
+            continue;
+        }
+        if let Some(module) = index.module_at_address(value) {
+            counts[module] += 1;
+        }
+    }
+
+    let mut referenced: Vec<ReferencedModule> = modules
+        .by_addr()
+        .filter(|module| module.memory_range().is_some())
+        .zip(counts)
+        .filter(|&(_, count)| count > 0)
+        .map(|(module, count)| ReferencedModule {
+            module: module.clone(),
+            count,
+        })
+        .collect();
+    // Sorting is stable, so ties stay in address order
+    referenced.sort_by_key(|referenced| Reverse(referenced.count));
+    referenced
+}
@@ -0,0 +1,1211 @@
+//! Typed representation of the JSON output of [`ProcessState::print_json`].
+//!
+//! [`Report`] mirrors the schema documented in `json-schema.md`, so library users
+//! can get at the same data without re-parsing a string (see
+//! [`ProcessState::to_json_report`]), and consumers of the JSON can deserialize
+//! it into these types.
+//!
+//! The output is deterministic:
+//!
+//! * the fields of every object are emitted in alphabetical order (the order
+//!   the fields are declared in),
+//! * every field is always emitted, with `null` when there's no value for it,
+//! * lists that don't have a natural order (like `proc_limits.limits`) are sorted.
+//!
+//! Any change to the names or types of fields bumps [`SCHEMA_VERSION`].
+
+use std::borrow::{Borrow, Cow};
+use std::collections::{BTreeMap, HashSet};
+
+use minidump::{
+    CrashReasonDetail, MemoryAccessKind, MinidumpContext, MinidumpContextValidity,
+    MinidumpRawContext, Module as _,
+};
+use minidump_common::utils::basename;
+use minidump_unwind::{CallStack, StackFrame, SymbolStats, WalkFrameError};
+use serde::{Deserialize, Serialize};
+
+use crate::source_link::SourceLinkOptions;
+use crate::{Address, Limit, ProcessState};
+
+/// The version of the schema of [`Report`], emitted as its `schema_version`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The JSON report for a [`ProcessState`].
+///
+/// See `json-schema.md` for the meaning of every field.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    pub auxiliary_crashes: Option<Vec<AuxiliaryCrash>>,
+    pub consistency_warnings: Option<Vec<ConsistencyWarning>>,
+    /// The annotations of a `.extra` file, as they were.
+    pub crash_annotations: Option<serde_json::Map<String, serde_json::Value>>,
+    pub crash_info: CrashInfo,
+    /// A copy of the crashing thread with some additional details.
+    pub crashing_thread: Option<CrashingThread>,
+    pub crashpad_info: Option<CrashpadInfo>,
+    pub dump_integrity: Option<Vec<IntegrityNote>>,
+    pub handles: Option<Vec<Handle>>,
+    pub lsb_release: Option<LsbRelease>,
+    pub mac_boot_args: Option<String>,
+    pub mac_crash_info: Option<MacCrashInfo>,
+    pub main_module: usize,
+    pub memory_counters: Option<MemoryCounters>,
+    pub memory_usage: Option<MemoryUsage>,
+    pub modules: Vec<Module>,
+    /// \[UNSTABLE:evil_json\]
+    pub modules_contains_cert_info: bool,
+    pub pid: Option<u32>,
+    pub process: Option<Process>,
+    pub proc_limits: Option<ProcLimits>,
+    pub proc_status: Option<ProcStatus>,
+    pub redacted: bool,
+    pub redacted_sections: Option<Vec<String>>,
+    pub schema_version: u32,
+    /// Always "OK".
+    pub status: String,
+    pub system_info: SystemInfo,
+    pub thread_count: usize,
+    pub thread_groups: Option<Vec<ThreadGroup>>,
+    pub threads: Vec<Thread>,
+    pub unloaded_modules: Vec<UnloadedModule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashInfo {
+    pub address: Option<String>,
+    pub adjusted_address: Option<AdjustedAddress>,
+    pub assertion: Option<String>,
+    pub category: Option<String>,
+    /// Index of the crashing thread in `threads`.
+    pub crashing_thread: Option<usize>,
+    pub details: Option<CrashDetails>,
+    pub disassembly: Option<Vec<DisassembledInstruction>>,
+    /// Why the dump was written: "crash", "hang" or "requested".
+    pub dump_kind: String,
+    /// The thread that wrote the dump, from the Breakpad info stream.
+    pub dump_thread_id: Option<u32>,
+    /// The exceptions the crash is nested in, if there's more than one.
+    pub exception_chain: Option<Vec<ChainedException>>,
+    pub instruction: Option<String>,
+    pub instruction_bytes: Option<InstructionBytes>,
+    pub memory_accesses: Option<Vec<MemoryAccess>>,
+    pub message: Option<String>,
+    pub notes: Option<Vec<CrashNote>>,
+    pub possible_bit_flips: Option<Vec<PossibleBitFlip>>,
+    /// The thread that asked for the dump, from the Breakpad info stream.
+    pub requesting_thread_id: Option<u32>,
+    pub signature: Option<String>,
+    /// The crash reason, e.g. "EXCEPTION_ACCESS_VIOLATION_WRITE".
+    #[serde(rename = "type")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainedException {
+    pub address: String,
+    pub code: String,
+    pub flags: String,
+    pub parameters: Vec<String>,
+    /// The crash reason, e.g. "EXCEPTION_ACCESS_VIOLATION_WRITE".
+    #[serde(rename = "type")]
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashNote {
+    pub description: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum AdjustedAddress {
+    #[serde(rename = "non-canonical")]
+    NonCanonical { address: String },
+    #[serde(rename = "null-pointer")]
+    NullPointer { offset: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisassembledInstruction {
+    pub address: String,
+    /// The bytes of the instruction, as hex.
+    pub bytes: String,
+    pub crashing: bool,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum InstructionBytes {
+    Captured {
+        /// Up to 16 bytes at the instruction pointer, as hex.
+        bytes: String,
+    },
+    Unmapped,
+    NotCaptured,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashDetails {
+    pub description: String,
+    #[serde(flatten)]
+    pub kind: CrashDetailsKind,
+}
+
+/// The fields of [`CrashDetails`] that depend on its `kind`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CrashDetailsKind {
+    AccessViolation {
+        access: Option<String>,
+        address: String,
+    },
+    InPageError {
+        access: Option<String>,
+        address: String,
+        nt_status: String,
+    },
+    CppException {
+        image_base: Option<String>,
+        object: String,
+        throw_info: String,
+    },
+    ClrException {
+        hresult: String,
+        runtime_base: Option<String>,
+    },
+    FastFail {
+        code: String,
+    },
+    Parameters {
+        parameters: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryAccess {
+    pub address: String,
+    pub is_likely_guard_page: bool,
+    pub size: Option<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PossibleBitFlip {
+    pub address: String,
+    pub confidence: Option<f32>,
+    pub details: BitFlipDetails,
+    pub source_register: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BitFlipDetails {
+    pub is_null: bool,
+    pub nearby_registers: u32,
+    pub poison_registers: bool,
+    pub was_low: bool,
+    pub was_non_canonical: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityNote {
+    pub description: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsistencyWarning {
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuxiliaryCrash {
+    pub frames: Vec<String>,
+    pub kind: String,
+    pub message: Option<String>,
+    /// Only present if the stream couldn't be parsed.
+    pub raw: Option<String>,
+    pub stream_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashpadInfo {
+    pub client_id: String,
+    pub modules: Vec<CrashpadModuleInfo>,
+    pub report_id: String,
+    pub simple_annotations: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashpadModuleInfo {
+    /// Only the annotation objects that are strings.
+    pub annotation_objects: BTreeMap<String, String>,
+    pub list_annotations: Vec<String>,
+    /// Index of the module in `modules`.
+    pub module_index: usize,
+    pub simple_annotations: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Handle {
+    pub handle: Option<u64>,
+    pub handle_count: Option<u32>,
+    pub object_name: Option<String>,
+    pub pointer_count: Option<u32>,
+    pub type_name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LsbRelease {
+    pub codename: String,
+    pub description: String,
+    pub id: String,
+    pub release: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MacCrashInfo {
+    pub num_records: usize,
+    pub records: Vec<MacCrashInfoRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MacCrashInfoRecord {
+    pub abort_cause: Option<String>,
+    pub backtrace: Option<String>,
+    pub dialog_mode: Option<String>,
+    pub message: Option<String>,
+    pub message2: Option<String>,
+    pub module: Option<String>,
+    pub signature_string: Option<String>,
+    pub thread: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryCounters {
+    pub page_fault_count: Option<u32>,
+    pub peak_virtual_size: Option<u64>,
+    pub peak_working_set_size: Option<u64>,
+    pub private_usage: Option<u64>,
+    pub system_available_physical_memory: Option<u64>,
+    pub system_commit_limit: Option<u64>,
+    pub system_committed: Option<u64>,
+    pub system_physical_memory: Option<u64>,
+    pub virtual_size: Option<u64>,
+    pub working_set_size: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryUsage {
+    pub largest_free_block: u64,
+    pub largest_free_block_address: Option<String>,
+    pub likely_oom: bool,
+    pub total_committed: u64,
+    pub total_free: u64,
+    pub total_reserved: u64,
+    pub write_combine_committed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Module {
+    pub base_addr: String,
+    /// \[UNSTABLE:evil_json\]
+    pub cert_subject: Option<String>,
+    pub code_id: String,
+    pub corrupt_symbols: bool,
+    pub debug_file: String,
+    pub debug_id: String,
+    pub debug_id_mismatch: bool,
+    pub end_addr: String,
+    pub filename: String,
+    pub is_main: bool,
+    pub loaded_symbols: bool,
+    pub missing_symbols: bool,
+    pub symbol_url: Option<String>,
+    pub symbols_debug_id: Option<String>,
+    /// Where the symbols came from, see [`SymbolStats::provider`][minidump_unwind::SymbolStats::provider].
+    pub symbols_from: Option<String>,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Process {
+    pub environment: Environment,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Environment {
+    pub command_line: Option<String>,
+    pub executable_path: Option<String>,
+    pub variables: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcLimits {
+    /// Sorted by name.
+    pub limits: Vec<ProcLimit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcStatus {
+    pub sig_blk: Option<String>,
+    pub sig_cgt: Option<String>,
+    pub sig_ign: Option<String>,
+    pub threads: Option<u32>,
+    pub vm_hwm: Option<u64>,
+    pub vm_peak: Option<u64>,
+    pub vm_rss: Option<u64>,
+    pub vm_size: Option<u64>,
+    pub vm_swap: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcLimit {
+    pub hard: Limit,
+    pub name: String,
+    pub soft: Limit,
+    pub unit: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SystemInfo {
+    pub cpu_arch: String,
+    pub cpu_count: usize,
+    /// Only present if the process ran emulated code.
+    pub cpu_emulation: Option<String>,
+    pub cpu_features: Vec<String>,
+    pub cpu_info: Option<String>,
+    pub cpu_microcode_version: Option<String>,
+    pub os: String,
+    pub os_build_lab: Option<String>,
+    pub os_distro: Option<String>,
+    pub os_ver: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadGroup {
+    pub blocked_on: Option<BlockedOn>,
+    pub frames: Vec<String>,
+    pub thread_count: usize,
+    pub thread_ids: Vec<u32>,
+    pub thread_names: Vec<Option<String>>,
+    /// Indices of the member threads in `threads`.
+    pub threads: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockedOn {
+    pub function: String,
+    /// object | lock | condition_variable | futex | mach_message | io | sleep
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Thread {
+    /// Why the thread's context in the thread list couldn't be read, if it couldn't.
+    pub context_error: Option<String>,
+    pub frame_count: usize,
+    pub frames: Vec<Frame>,
+    pub last_error_value: Option<String>,
+    /// The registers the stackwalker recovered for the last frame (if it's not the first).
+    pub last_frame_registers: Option<BTreeMap<String, String>>,
+    /// The registers of the thread's context in the thread list.
+    pub last_known_registers: Option<BTreeMap<String, String>>,
+    /// The frames before thunk frames were merged out of `frames`. Only present
+    /// if the processor was asked to keep them, and there were any.
+    pub raw_frames: Option<Vec<Frame>>,
+    /// stack_pointer_outside_stack | empty_stack
+    pub stack_memory_repair: Option<String>,
+    pub thread_id: u32,
+    pub thread_name: Option<String>,
+    pub thread_start_address: Option<String>,
+    pub thread_start_function: Option<String>,
+    pub thread_times: Option<ThreadTimes>,
+    /// no_caller_found | null_instruction_pointer | stack_pointer_not_increasing |
+    /// repeated_frame | missing_stack_memory
+    pub walk_terminated_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashingThread {
+    #[serde(flatten)]
+    pub thread: Thread,
+    /// Index of this thread in `threads`.
+    pub threads_index: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadTimes {
+    pub create_time: u64,
+    pub exit_time: u64,
+    pub kernel_time: u64,
+    pub user_time: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Frame {
+    /// Only present if a [`FrameAnnotator`][crate::FrameAnnotator] annotated the frame.
+    pub annotations: Option<BTreeMap<String, String>>,
+    /// The CPU the frame's registers are for. Only present if the process ran
+    /// emulated code, where that differs between threads.
+    pub cpu_arch: Option<String>,
+    pub file: Option<String>,
+    pub frame: usize,
+    pub function: Option<String>,
+    pub function_offset: Option<String>,
+    /// Only present if the processor was asked to recover arguments.
+    pub heuristic_parameters: Option<Vec<HeuristicParameter>>,
+    pub inlines: Option<Vec<InlineFrame>>,
+    /// Whether the frame's function is a thunk (like `foo@plt`).
+    pub is_thunk: bool,
+    pub line: Option<u32>,
+    pub missing_symbols: bool,
+    pub module: Option<String>,
+    pub module_offset: Option<String>,
+    pub offset: String,
+    /// Only present in the first frame of `crashing_thread`.
+    pub registers: Option<BTreeMap<String, String>>,
+    /// Only present if the processor was asked to look for source files.
+    pub source_context: Option<SourceContext>,
+    /// A URL of the source line, if the symbols are source indexed.
+    pub source_link: Option<String>,
+    /// symbolized | no_covering_symbol | symbols_missing, or null if the frame
+    /// isn't in a module.
+    pub symbol_disposition: Option<String>,
+    /// func | public | none: the kind of record `function` came from.
+    pub symbol_kind: String,
+    /// none | scan | cfi_scan | frame_pointer | cfi | context | prewalked
+    pub trust: String,
+    pub unloaded_modules: Option<Vec<FrameUnloadedModule>>,
+    /// Only present if CFI couldn't find this frame, so it was found some other way.
+    pub unwind_debug: Option<UnwindDebug>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeuristicParameter {
+    pub symbol: Option<String>,
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InlineFrame {
+    pub file: Option<String>,
+    pub function: String,
+    pub line: Option<u32>,
+    pub source_link: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceContext {
+    /// The line number of the first of `lines`.
+    pub first_line: u32,
+    pub lines: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnwindDebug {
+    /// no_cfi | missing_register | eval_failed
+    pub cfi_error: String,
+    /// The callee register the CFI needed, if `cfi_error` is missing_register.
+    pub missing_register: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameUnloadedModule {
+    pub module: String,
+    pub offsets: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnloadedModule {
+    pub base_addr: String,
+    /// \[UNSTABLE:evil_json\]
+    pub cert_subject: Option<String>,
+    pub code_id: String,
+    pub end_addr: String,
+    pub filename: String,
+}
+
+fn hex(address: u64) -> String {
+    Address(address).to_string()
+}
+
+/// `bytes` as hex, e.g. "488b03".
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+impl Report {
+    /// Build the report of `state`.
+    ///
+    /// Addresses are formatted according to the pointer width of `state`, so
+    /// the print context must have been set already.
+    pub(crate) fn from_state(state: &ProcessState) -> Report {
+        let emulated = state.system_info.cpu_emulation.is_some();
+        let threads: Vec<Thread> = state
+            .threads
+            .iter()
+            .map(|thread| Thread::from_stack(thread, emulated, &state.source_link_options))
+            .collect();
+
+        // Copy the crashing thread into a top-level "crashing_thread" field, and add
+        // a "registers" field to its first frame. We can't do this if there isn't
+        // at least one frame.
+        let crashing_thread = state.requesting_thread.and_then(|requesting_thread| {
+            let stack = state.threads.get(requesting_thread)?;
+            let context_frame = stack.frames.first()?;
+            let mut thread = threads[requesting_thread].clone();
+            thread.frames[0].registers = Some(registers(&context_frame.context));
+            Some(CrashingThread {
+                thread,
+                threads_index: requesting_thread,
+            })
+        });
+
+        let sys = &state.system_info;
+        Report {
+            auxiliary_crashes: (!state.auxiliary_crashes.is_empty()).then(|| {
+                state
+                    .auxiliary_crashes
+                    .iter()
+                    .map(|crash| AuxiliaryCrash {
+                        frames: crash.frames.clone(),
+                        kind: crash.kind.clone(),
+                        message: crash.message.clone(),
+                        raw: crash.raw.clone(),
+                        stream_type: format!("{:#010x}", crash.stream_type),
+                    })
+                    .collect()
+            }),
+            consistency_warnings: (!state.consistency_warnings.is_empty()).then(|| {
+                state
+                    .consistency_warnings
+                    .iter()
+                    .map(|warning| ConsistencyWarning {
+                        code: warning.code().to_owned(),
+                        message: warning.to_string(),
+                    })
+                    .collect()
+            }),
+            crash_annotations: (!state.crash_annotations.is_empty())
+                .then(|| state.crash_annotations.clone()),
+            crash_info: CrashInfo::from_state(state),
+            crashing_thread,
+            crashpad_info: state.crashpad_info.as_ref().map(|info| CrashpadInfo {
+                client_id: info.raw.client_id.to_string(),
+                modules: info
+                    .module_list
+                    .iter()
+                    .map(|module| CrashpadModuleInfo {
+                        annotation_objects: module
+                            .annotation_objects
+                            .iter()
+                            .filter_map(|(key, value)| match value {
+                                minidump::MinidumpAnnotation::String(value) => {
+                                    Some((key.clone(), value.clone()))
+                                }
+                                _ => None,
+                            })
+                            .collect(),
+                        list_annotations: module.list_annotations.clone(),
+                        module_index: module.module_index,
+                        simple_annotations: module.simple_annotations.clone(),
+                    })
+                    .collect(),
+                report_id: info.raw.report_id.to_string(),
+                simple_annotations: info.simple_annotations.clone(),
+            }),
+            dump_integrity: (!state.dump_integrity.is_empty()).then(|| {
+                state
+                    .dump_integrity
+                    .iter()
+                    .map(|note| IntegrityNote {
+                        description: note.to_string(),
+                        kind: note.name().to_owned(),
+                    })
+                    .collect()
+            }),
+            handles: state.handles.as_ref().map(|handles| {
+                handles
+                    .iter()
+                    .map(|handle| Handle {
+                        handle: handle.raw.handle().copied(),
+                        handle_count: handle.raw.handle_count().copied(),
+                        object_name: handle.object_name.clone(),
+                        pointer_count: handle.raw.pointer_count().copied(),
+                        type_name: handle.type_name.clone(),
+                    })
+                    .collect()
+            }),
+            lsb_release: state.linux_standard_base.as_ref().map(|lsb| LsbRelease {
+                codename: lsb.codename.clone(),
+                description: lsb.description.clone(),
+                id: lsb.id.clone(),
+                release: lsb.release.clone(),
+            }),
+            mac_boot_args: state
+                .mac_boot_args
+                .as_ref()
+                .and_then(|info| info.bootargs.clone()),
+            mac_crash_info: state.mac_crash_info.as_ref().map(|info| MacCrashInfo {
+                num_records: info.len(),
+                records: info
+                    .iter()
+                    .map(|record| MacCrashInfoRecord {
+                        abort_cause: record.abort_cause().copied().map(hex),
+                        backtrace: record.backtrace().map(String::from),
+                        dialog_mode: record.dialog_mode().copied().map(hex),
+                        message: record.message().map(String::from),
+                        message2: record.message2().map(String::from),
+                        module: record.module_path().map(String::from),
+                        signature_string: record.signature_string().map(String::from),
+                        thread: record.thread().copied().map(hex),
+                    })
+                    .collect(),
+            }),
+            main_module: state.main_module.unwrap_or(0),
+            memory_counters: state
+                .memory_counters
+                .as_ref()
+                .map(|counters| MemoryCounters {
+                    page_fault_count: counters.page_fault_count,
+                    peak_virtual_size: counters.peak_virtual_size,
+                    peak_working_set_size: counters.peak_working_set_size,
+                    private_usage: counters.private_usage,
+                    system_available_physical_memory: counters.system_available_physical_memory,
+                    system_commit_limit: counters.system_commit_limit,
+                    system_committed: counters.system_committed,
+                    system_physical_memory: counters.system_physical_memory,
+                    virtual_size: counters.virtual_size,
+                    working_set_size: counters.working_set_size,
+                }),
+            memory_usage: state.memory_usage.as_ref().map(|usage| MemoryUsage {
+                largest_free_block: usage.largest_free_block,
+                largest_free_block_address: usage.largest_free_block_address.map(hex),
+                likely_oom: usage.likely_oom,
+                total_committed: usage.total_committed,
+                total_free: usage.total_free,
+                total_reserved: usage.total_reserved,
+                write_combine_committed: usage.write_combine_committed,
+            }),
+            modules: state
+                .modules
+                .iter()
+                .enumerate()
+                .map(|(index, module)| {
+                    Module::from_module(state, module, state.main_module == Some(index))
+                })
+                .collect(),
+            modules_contains_cert_info: !state.cert_info.is_empty(),
+            pid: state.process_id,
+            process: state.environment.as_ref().map(|environment| Process {
+                environment: Environment {
+                    command_line: environment.command_line.clone(),
+                    executable_path: environment.executable_path.clone(),
+                    variables: environment.variables.clone(),
+                },
+            }),
+            proc_limits: state.linux_proc_limits.as_ref().map(|limits| {
+                let mut limits: Vec<ProcLimit> = limits
+                    .limits
+                    .iter()
+                    .map(|(name, limit)| ProcLimit {
+                        hard: limit.hard.clone(),
+                        name: name.clone(),
+                        soft: limit.soft.clone(),
+                        unit: limit.unit.clone(),
+                    })
+                    .collect();
+                limits.sort_by(|a, b| a.name.cmp(&b.name));
+                ProcLimits { limits }
+            }),
+            proc_status: state.linux_proc_status.as_ref().map(|status| {
+                // All 64 bits, like /proc has them
+                let mask = |mask: Option<u64>| mask.map(|mask| format!("{mask:#018x}"));
+                ProcStatus {
+                    sig_blk: mask(status.sig_blk),
+                    sig_cgt: mask(status.sig_cgt),
+                    sig_ign: mask(status.sig_ign),
+                    threads: status.threads,
+                    vm_hwm: status.vm_hwm,
+                    vm_peak: status.vm_peak,
+                    vm_rss: status.vm_rss,
+                    vm_size: status.vm_size,
+                    vm_swap: status.vm_swap,
+                }
+            }),
+            redacted: state.redacted_sections.is_some(),
+            redacted_sections: state.redacted_sections.as_ref().map(|sections| {
+                sections
+                    .iter()
+                    .map(|section| section.name().to_owned())
+                    .collect()
+            }),
+            schema_version: SCHEMA_VERSION,
+            // Currently unused, we either produce no output or successful output.
+            // OK | ERROR_* | SYMBOL_SUPPLIER_INTERRUPTED
+            status: String::from("OK"),
+            system_info: SystemInfo {
+                cpu_arch: sys.cpu.to_string(),
+                cpu_emulation: sys.cpu_emulation.map(|emulation| emulation.to_string()),
+                cpu_count: sys.cpu_count,
+                cpu_features: sys.cpu_features.clone(),
+                cpu_info: sys.cpu_info.clone(),
+                cpu_microcode_version: sys.cpu_microcode_version.map(|num| format!("{num:#x}")),
+                os: sys.os.long_name().into_owned(),
+                os_build_lab: sys.os_build_lab.clone(),
+                os_distro: sys.os_distro.clone(),
+                os_ver: sys.format_os_version().map(Cow::into_owned),
+            },
+            thread_count: threads.len(),
+            thread_groups: (!state.thread_groups.is_empty()).then(|| {
+                state
+                    .thread_groups
+                    .iter()
+                    .map(|group| ThreadGroup {
+                        blocked_on: group.blocked_on.as_ref().map(|blocked_on| BlockedOn {
+                            function: blocked_on.function.clone(),
+                            kind: blocked_on.kind.name().to_owned(),
+                        }),
+                        frames: group.frames.clone(),
+                        thread_count: group.threads.len(),
+                        thread_ids: group
+                            .threads
+                            .iter()
+                            .map(|&i| state.threads[i].thread_id)
+                            .collect(),
+                        thread_names: group
+                            .threads
+                            .iter()
+                            .map(|&i| state.threads[i].thread_name.clone())
+                            .collect(),
+                        threads: group.threads.clone(),
+                    })
+                    .collect()
+            }),
+            threads,
+            unloaded_modules: state
+                .unloaded_modules
+                .iter()
+                .map(|module| UnloadedModule {
+                    base_addr: hex(module.raw.base_of_image),
+                    cert_subject: state.cert_info.get(&module.name).cloned(),
+                    code_id: module
+                        .code_identifier()
+                        .unwrap_or_default()
+                        .as_str()
+                        .to_owned(),
+                    end_addr: hex(module
+                        .raw
+                        .base_of_image
+                        .saturating_add(module.raw.size_of_image as u64)),
+                    filename: module.name.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CrashInfo {
+    fn from_state(state: &ProcessState) -> CrashInfo {
+        let info = state.exception_info.as_ref();
+        CrashInfo {
+            address: info.map(|info| info.address.to_string()),
+            adjusted_address: info.and_then(|info| {
+                info.adjusted_address
+                    .as_ref()
+                    .map(|adjusted| match adjusted {
+                        crate::AdjustedAddress::NonCanonical(address) => {
+                            AdjustedAddress::NonCanonical {
+                                address: address.to_string(),
+                            }
+                        }
+                        crate::AdjustedAddress::NullPointerWithOffset(offset) => {
+                            AdjustedAddress::NullPointer {
+                                offset: offset.to_string(),
+                            }
+                        }
+                    })
+            }),
+            assertion: state.assertion.clone(),
+            category: state
+                .crash_category
+                .as_ref()
+                .map(|category| category.name().to_owned()),
+            crashing_thread: state.requesting_thread,
+            details: info.and_then(|info| info.detail.as_ref().map(CrashDetails::from_detail)),
+            disassembly: info.and_then(|info| {
+                (!info.disassembly.is_empty()).then(|| {
+                    info.disassembly
+                        .iter()
+                        .map(|instruction| DisassembledInstruction {
+                            address: hex(instruction.address),
+                            bytes: hex_bytes(&instruction.bytes),
+                            crashing: instruction.crashing,
+                            text: instruction.text.clone(),
+                        })
+                        .collect()
+                })
+            }),
+            dump_kind: state.dump_kind.name().to_owned(),
+            dump_thread_id: state.dump_thread_id,
+            exception_chain: (state.exception_chain.len() > 1).then(|| {
+                state
+                    .exception_chain
+                    .iter()
+                    .map(|exception| ChainedException {
+                        address: exception.address.to_string(),
+                        code: format!("{:#010x}", exception.code),
+                        flags: format!("{:#010x}", exception.flags),
+                        parameters: exception.parameters.iter().copied().map(hex).collect(),
+                        reason: exception.reason.to_string(),
+                    })
+                    .collect()
+            }),
+            instruction: info.and_then(|info| info.instruction_str.clone()),
+            instruction_bytes: info.map(|info| match &info.instruction_bytes {
+                crate::InstructionBytes::Captured(bytes) => InstructionBytes::Captured {
+                    bytes: hex_bytes(bytes),
+                },
+                crate::InstructionBytes::Unmapped => InstructionBytes::Unmapped,
+                crate::InstructionBytes::NotCaptured => InstructionBytes::NotCaptured,
+            }),
+            memory_accesses: info.and_then(|info| {
+                info.memory_accesses.as_ref().map(|accesses| {
+                    accesses
+                        .iter()
+                        .map(|access| MemoryAccess {
+                            address: hex(access.address),
+                            is_likely_guard_page: access.is_likely_guard_page,
+                            size: access.size,
+                        })
+                        .collect()
+                })
+            }),
+            message: info.and_then(|info| info.message.clone()),
+            notes: info.and_then(|info| {
+                (!info.notes.is_empty()).then(|| {
+                    info.notes
+                        .iter()
+                        .map(|note| CrashNote {
+                            description: note.to_string(),
+                            kind: note.name().to_owned(),
+                        })
+                        .collect()
+                })
+            }),
+            possible_bit_flips: info.and_then(|info| {
+                (!info.possible_bit_flips.is_empty()).then(|| {
+                    info.possible_bit_flips
+                        .iter()
+                        .map(|bit_flip| PossibleBitFlip {
+                            address: bit_flip.address.to_string(),
+                            confidence: bit_flip.confidence,
+                            details: BitFlipDetails {
+                                is_null: bit_flip.details.is_null,
+                                nearby_registers: bit_flip.details.nearby_registers,
+                                poison_registers: bit_flip.details.poison_registers,
+                                was_low: bit_flip.details.was_low,
+                                was_non_canonical: bit_flip.details.was_non_canonical,
+                            },
+                            source_register: bit_flip.source_register.map(String::from),
+                        })
+                        .collect()
+                })
+            }),
+            requesting_thread_id: state.requesting_thread_id,
+            signature: state.signature(),
+            reason: info.map(|info| info.reason.to_string()),
+        }
+    }
+}
+
+impl CrashDetails {
+    fn from_detail(detail: &CrashReasonDetail) -> CrashDetails {
+        let access_name = |access: &Option<MemoryAccessKind>| {
+            access.as_ref().map(|access| access.name().to_owned())
+        };
+        let kind = match detail {
+            CrashReasonDetail::AccessViolation { access, address } => {
+                CrashDetailsKind::AccessViolation {
+                    access: access_name(access),
+                    address: hex(*address),
+                }
+            }
+            CrashReasonDetail::InPageError {
+                access,
+                address,
+                nt_status,
+            } => CrashDetailsKind::InPageError {
+                access: access_name(access),
+                address: hex(*address),
+                nt_status: format!("{nt_status:#010x}"),
+            },
+            CrashReasonDetail::CppException {
+                object,
+                throw_info,
+                image_base,
+            } => CrashDetailsKind::CppException {
+                image_base: image_base.map(hex),
+                object: hex(*object),
+                throw_info: hex(*throw_info),
+            },
+            CrashReasonDetail::ClrException {
+                hresult,
+                runtime_base,
+            } => CrashDetailsKind::ClrException {
+                hresult: format!("{hresult:#010x}"),
+                runtime_base: runtime_base.map(hex),
+            },
+            CrashReasonDetail::FastFail { code } => CrashDetailsKind::FastFail {
+                code: format!("{code:#010x}"),
+            },
+            CrashReasonDetail::Parameters(params) => CrashDetailsKind::Parameters {
+                parameters: params.iter().map(|&param| format!("{param:#x}")).collect(),
+            },
+        };
+        CrashDetails {
+            description: detail.to_string(),
+            kind,
+        }
+    }
+}
+
+impl Module {
+    fn from_module(
+        state: &ProcessState,
+        module: &minidump::MinidumpModule,
+        is_main: bool,
+    ) -> Module {
+        let full_name = module.code_file();
+        let name = basename(&full_name);
+
+        // Gather statistics on the module's symbols
+        let stats = state.symbol_stats.get(name);
+        let had_stats = stats.is_some();
+        let default = SymbolStats::default();
+        let stats = stats.unwrap_or(&default);
+        // Resolve debug file and debug id from extra debug info if present
+        let debug_file;
+        let debug_id;
+        let debug_file_cow = module.debug_file().unwrap_or(Cow::Borrowed(""));
+        if let Some(debug_info) = &stats.extra_debug_info {
+            debug_file = debug_info.debug_file.as_str();
+            debug_id = debug_info.debug_identifier;
+        } else {
+            debug_file = debug_file_cow.borrow();
+            debug_id = module.debug_identifier().unwrap_or_default();
+        }
+        // Only consider the symbols "missing" if the symbolizer
+        // actually has statistics on them (implying it *tried* to
+        // get the symbols but failed.)
+        let missing_symbols = had_stats && !stats.loaded_symbols;
+        Module {
+            base_addr: hex(module.raw.base_of_image),
+            cert_subject: state.cert_info.get(name).cloned(),
+            code_id: module
+                .code_identifier()
+                .unwrap_or_default()
+                .as_str()
+                .to_owned(),
+            corrupt_symbols: stats.corrupt_symbols,
+            // filename | empty string
+            debug_file: basename(debug_file).to_owned(),
+            // [[:xdigit:]]{33} | empty string
+            debug_id: debug_id.breakpad().to_string(),
+            debug_id_mismatch: stats.debug_id_mismatch,
+            end_addr: hex(module
+                .raw
+                .base_of_image
+                .saturating_add(module.raw.size_of_image as u64)),
+            filename: name.to_owned(),
+            is_main,
+            loaded_symbols: stats.loaded_symbols,
+            missing_symbols,
+            symbol_url: stats.symbol_url.clone(),
+            symbols_debug_id: stats.symbols_debug_id.clone(),
+            symbols_from: stats.provider.clone(),
+            version: module.version().map(Cow::into_owned),
+        }
+    }
+}
+
+impl Thread {
+    fn from_stack(thread: &CallStack, emulated: bool, links: &SourceLinkOptions) -> Thread {
+        let thread_context = thread.thread_context.as_ref();
+        Thread {
+            context_error: thread_context
+                .and_then(|context| context.as_ref().err())
+                .map(|error| error.to_string()),
+            frame_count: thread.frames.len(),
+            frames: thread
+                .frames
+                .iter()
+                .enumerate()
+                .map(|(idx, frame)| Frame::from_frame(idx, frame, emulated, links))
+                .collect(),
+            last_error_value: thread.last_error_value.map(|error| error.to_string()),
+            last_frame_registers: match thread.frames.as_slice() {
+                [_, .., last] => Some(registers(&last.context)),
+                _ => None,
+            },
+            last_known_registers: thread_context
+                .and_then(|context| context.as_ref().ok())
+                .map(thread_registers),
+            raw_frames: thread.raw_frames.as_ref().map(|frames| {
+                frames
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, frame)| Frame::from_frame(idx, frame, emulated, links))
+                    .collect()
+            }),
+            stack_memory_repair: thread
+                .stack_memory_repair
+                .map(|repair| repair.as_str().to_owned()),
+            thread_id: thread.thread_id,
+            thread_name: thread.thread_name.clone(),
+            thread_start_address: thread.start_address.map(hex),
+            thread_start_function: thread.start_function.clone(),
+            thread_times: thread.times.map(|times| ThreadTimes {
+                create_time: times.create_time,
+                exit_time: times.exit_time,
+                kernel_time: times.kernel_time,
+                user_time: times.user_time,
+            }),
+            walk_terminated_reason: thread
+                .walk_terminated_reason
+                .map(|reason| reason.as_str().to_owned()),
+        }
+    }
+}
+
+impl Frame {
+    fn from_frame(
+        idx: usize,
+        frame: &StackFrame,
+        emulated: bool,
+        links: &SourceLinkOptions,
+    ) -> Frame {
+        Frame {
+            annotations: (!frame.annotations.is_empty()).then(|| frame.annotations.clone()),
+            cpu_arch: emulated.then(|| frame.context.raw.cpu().to_string()),
+            file: frame.source_file_name.clone(),
+            frame: idx,
+            function: frame.function_name.clone(),
+            function_offset: frame
+                .function_base
+                .map(|func_base| frame.instruction - func_base)
+                .map(hex),
+            heuristic_parameters: frame.heuristic_parameters.as_ref().map(|params| {
+                params
+                    .iter()
+                    .map(|param| HeuristicParameter {
+                        symbol: param.symbol.clone(),
+                        value: param.value.map(hex),
+                    })
+                    .collect()
+            }),
+            inlines: (!frame.inlines.is_empty()).then(|| {
+                frame
+                    .inlines
+                    .iter()
+                    .map(|frame| InlineFrame {
+                        file: frame.source_file_name.clone(),
+                        function: frame.function_name.clone(),
+                        line: frame.source_line,
+                        source_link: frame
+                            .source_file_vcs
+                            .as_ref()
+                            .and_then(|file| links.link(file, frame.source_line)),
+                    })
+                    .collect()
+            }),
+            is_thunk: frame.is_thunk,
+            line: frame.source_line,
+            missing_symbols: frame.function_name.is_none(),
+            module: frame
+                .module
+                .as_ref()
+                .map(|module| basename(&module.name).to_owned()),
+            module_offset: frame
+                .module
+                .as_ref()
+                .map(|module| frame.instruction - module.raw.base_of_image)
+                .map(hex),
+            offset: hex(frame.instruction),
+            registers: None,
+            source_context: frame.source_context.as_ref().map(|source| SourceContext {
+                first_line: source.first_line,
+                lines: source.lines.clone(),
+            }),
+            source_link: frame
+                .source_file_vcs
+                .as_ref()
+                .and_then(|file| links.link(file, frame.source_line)),
+            symbol_disposition: frame
+                .symbol_disposition
+                .map(|disposition| disposition.as_str().to_owned()),
+            symbol_kind: frame
+                .symbol_kind
+                .map_or("none", |kind| kind.as_str())
+                .to_owned(),
+            trust: frame.trust.as_str().to_owned(),
+            unloaded_modules: (!frame.unloaded_modules.is_empty()).then(|| {
+                frame
+                    .unloaded_modules
+                    .iter()
+                    .map(|(module, offsets)| FrameUnloadedModule {
+                        module: module.clone(),
+                        offsets: offsets.iter().copied().map(hex).collect(),
+                    })
+                    .collect()
+            }),
+            unwind_debug: frame.cfi_error.as_ref().map(|error| UnwindDebug {
+                cfi_error: error.as_str().to_owned(),
+                missing_register: match error {
+                    WalkFrameError::MissingRegister(reg) => Some(reg.clone()),
+                    _ => None,
+                },
+            }),
+        }
+    }
+}
+
+/// The registers of a thread's context: the valid general purpose ones (like
+/// [`registers`]), and the segment registers on x86 and amd64.
+fn thread_registers(ctx: &MinidumpContext) -> BTreeMap<String, String> {
+    let mut registers = registers(ctx);
+    // Segment selectors are 16 bits, even if x86 contexts store them in 32.
+    let segments = match &ctx.raw {
+        MinidumpRawContext::X86(raw) => {
+            Some([raw.cs, raw.ds, raw.es, raw.fs, raw.gs, raw.ss].map(|reg| reg as u16))
+        }
+        MinidumpRawContext::Amd64(raw) => Some([raw.cs, raw.ds, raw.es, raw.fs, raw.gs, raw.ss]),
+        _ => None,
+    };
+    if let (Some(segments), MinidumpContextValidity::All) = (segments, &ctx.valid) {
+        for (name, value) in ["cs", "ds", "es", "fs", "gs", "ss"].iter().zip(segments) {
+            registers.insert(String::from(*name), format!("{value:#06x}"));
+        }
+    }
+    registers
+}
+
+fn registers(ctx: &MinidumpContext) -> BTreeMap<String, String> {
+    let registers: Cow<HashSet<&str>> = match ctx.valid {
+        MinidumpContextValidity::All => {
+            let gpr = ctx.general_purpose_registers();
+            let set: HashSet<&str> = gpr.iter().cloned().collect();
+            Cow::Owned(set)
+        }
+        MinidumpContextValidity::Some(ref which) => Cow::Borrowed(which),
+    };
+
+    ctx.general_purpose_registers()
+        .iter()
+        .filter(|reg| registers.contains(*reg))
+        .map(|&reg| (String::from(reg), ctx.format_register(reg)))
+        .collect()
+}
@@ -0,0 +1,399 @@
+//! Stable crash signatures for deduplicating crashes.
+//!
+//! A signature is a short string summarizing the crashing thread's backtrace,
+//! like `mozilla::dom::Foo::Bar | mozilla::dom::Baz | xul.dll@0x1234`. It's
+//! built from the top few "interesting" frames of the crashing thread, skipping
+//! frames that are present in pretty much every crash of a given kind (the
+//! machinery of panicking, aborting, allocating, raising exceptions...) and
+//! normalizing away details that vary between otherwise identical crashes.
+//!
+//! Each frame is normalized as follows:
+//!
+//! * symbolized frames use their function name with template/generic
+//!   arguments, argument lists, and Rust symbol hashes stripped
+//!   (`std::vec::Vec<u8>::push(u8)` becomes `std::vec::Vec::push`).
+//! * unsymbolized frames use `module@offset` (`xul.dll@0x1234`), because
+//!   absolute addresses vary with ASLR.
+//! * frames that aren't in any module are `???`.
+//!
+//! The frame count and the skip-list can be configured with [`SignatureOptions`].
+//! Skip-list entries are matched against the normalized frame, and may contain
+//! `*` wildcards. The frames of thunks (see the [`thunks`][crate::thunks]
+//! module) are skipped too.
+
+use minidump::Module;
+use minidump_unwind::StackFrame;
+
+use crate::ProcessState;
+
+/// The default number of frames included in a signature.
+pub const DEFAULT_SIGNATURE_FRAMES: usize = 5;
+
+/// The separator between frames in a signature.
+pub const SIGNATURE_SEPARATOR: &str = " | ";
+
+/// Frames that are skipped by default.
+///
+/// These are the frames that show up at the top of the crashing thread of all
+/// panics, aborts, failed assertions, allocation failures, and raised exceptions,
+/// and so say nothing about which crash it is.
+pub const DEFAULT_SKIP_LIST: &[&str] = &[
+    // Rust panics
+    "core::panicking::*",
+    "core::option::expect_failed",
+    "core::option::unwrap_failed",
+    "core::result::unwrap_failed",
+    "std::panicking::*",
+    "std::panic::*",
+    "std::process::abort",
+    "std::sys::*::abort_internal",
+    "rust_begin_unwind",
+    "rust_panic",
+    "__rust_start_panic",
+    "panic_abort::*",
+    // Rust allocation
+    "alloc::alloc::*",
+    "std::alloc::*",
+    "__rust_alloc*",
+    "__rust_dealloc",
+    "__rust_realloc",
+    "__rdl_*",
+    "__rg_*",
+    // libc aborts and assertions
+    "abort",
+    "raise",
+    "gsignal",
+    "pthread_kill",
+    "__GI_abort",
+    "__GI_raise",
+    "__pthread_kill*",
+    "__abort_with_payload",
+    "__assert_fail",
+    "__assert_fail_base",
+    "__assert_rtn",
+    "_sigtramp",
+    "__restore_rt",
+    "libc.so*@*",
+    "libsystem_c.dylib@*",
+    "libsystem_kernel.dylib@*",
+    "libsystem_pthread.dylib@*",
+    // libc allocation
+    "malloc",
+    "calloc",
+    "realloc",
+    "free",
+    "operator new",
+    "operator new[]",
+    "operator delete",
+    "operator delete[]",
+    // Windows exception dispatch and fail-fast
+    "KiUserExceptionDispatcher",
+    "RtlDispatchException",
+    "RtlRaiseException",
+    "RaiseException",
+    "RaiseFailFastException",
+    "NtRaiseException",
+    "ZwRaiseException",
+    "__report_gsfailure",
+    "_invoke_watson",
+    "_invalid_parameter*",
+    "ntdll.dll@*",
+    "kernelbase.dll@*",
+    "KERNELBASE.dll@*",
+    "ucrtbase.dll@*",
+];
+
+/// Configuration for computing crash signatures.
+///
+/// The default is [`DEFAULT_SIGNATURE_FRAMES`] frames with [`DEFAULT_SKIP_LIST`],
+/// skipping thunk frames.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureOptions {
+    /// The maximum number of frames to include in the signature.
+    pub max_frames: usize,
+    /// Normalized frames matching any of these patterns are left out of the
+    /// signature. `*` matches any sequence of characters.
+    pub skip_list: Vec<String>,
+    /// Whether to leave the frames marked [`StackFrame::is_thunk`] out of the
+    /// signature.
+    pub skip_thunk_frames: bool,
+}
+
+impl Default for SignatureOptions {
+    fn default() -> Self {
+        Self {
+            max_frames: DEFAULT_SIGNATURE_FRAMES,
+            skip_list: DEFAULT_SKIP_LIST.iter().map(|s| s.to_string()).collect(),
+            skip_thunk_frames: true,
+        }
+    }
+}
+
+impl SignatureOptions {
+    /// Whether a normalized frame matches the skip-list.
+    pub fn is_skipped(&self, normalized_frame: &str) -> bool {
+        self.skip_list
+            .iter()
+            .any(|pattern| glob_match(pattern, normalized_frame))
+    }
+}
+
+/// Compute the signature of the crashing (or dump-requesting) thread.
+///
+/// Returns `None` if there's no such thread or it has no frames. If every frame is
+/// skipped, the signature falls back to the top frame so that it's never empty.
+pub fn compute_signature(state: &ProcessState, options: &SignatureOptions) -> Option<String> {
+    let thread = state.threads.get(state.requesting_thread?)?;
+    let normalized: Vec<String> = thread.frames.iter().map(normalize_frame).collect();
+
+    let mut interesting: Vec<&str> = thread
+        .frames
+        .iter()
+        .zip(&normalized)
+        .filter(|(frame, _)| !(options.skip_thunk_frames && frame.is_thunk))
+        .map(|(_, normalized)| normalized.as_str())
+        .filter(|frame| !options.is_skipped(frame))
+        .take(options.max_frames)
+        .collect();
+    if interesting.is_empty() {
+        interesting.push(normalized.first()?);
+    }
+
+    Some(interesting.join(SIGNATURE_SEPARATOR))
+}
+
+/// Get the normalized form of a frame that's used in signatures.
+pub fn normalize_frame(frame: &StackFrame) -> String {
+    if let Some(function_name) = &frame.function_name {
+        let normalized = normalize_function_name(function_name);
+        if !normalized.is_empty() {
+            return normalized;
+        }
+    }
+    match &frame.module {
+        Some(module) => {
+            let name = minidump_common::utils::basename(&module.code_file()).to_owned();
+            let offset = frame.instruction.wrapping_sub(module.base_address());
+            format!("{name}@{offset:#x}")
+        }
+        None => String::from("???"),
+    }
+}
+
+/// Strip the parts of a function name that vary between otherwise identical frames.
+///
+/// Generic arguments (`Foo<T>::bar`), argument lists (`bar(int, char*)`), trailing
+/// qualifiers (`bar() const`) and Rust symbol hashes (`bar::h0123456789abcdef`)
+/// are removed. Rust qualified paths like `<Foo as Trait>::bar` are kept, but
+/// with their generic arguments stripped.
+pub fn normalize_function_name(name: &str) -> String {
+    // Operators are the one place where '<' and '(' don't start something nested,
+    // so keep the operator itself verbatim and normalize around it.
+    if let Some((before, operator, after)) = split_operator(name) {
+        let stripped = format!(
+            "{}{operator}{}",
+            strip_nested(before, false),
+            strip_nested(after, true)
+        );
+        return strip_suffixes(&stripped);
+    }
+    strip_suffixes(&strip_nested(name, false))
+}
+
+/// Split `name` around a C++ operator (`operator<<`, `operator new[]`, ...).
+fn split_operator(name: &str) -> Option<(&str, &str, &str)> {
+    const KEYWORD: &str = "operator";
+    const OPERATORS: &[&str] = &[
+        "<<=", ">>=", "<=>", "->*", "()", "[]", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||",
+        "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "->", "<", ">", "+", "-", "*",
+        "/", "%", "&", "|", "^", "!", "~", "=", ",",
+    ];
+
+    let start = name
+        .match_indices(KEYWORD)
+        .map(|(idx, _)| idx)
+        .find(|&idx| {
+            // Don't match identifiers that merely contain "operator"
+            idx == 0 || name[..idx].ends_with([':', ' '])
+        })?;
+    let after = &name[start + KEYWORD.len()..];
+
+    let len = if let Some(op) = OPERATORS.iter().find(|op| after.starts_with(**op)) {
+        op.len()
+    } else if after.starts_with(' ') {
+        // `operator new`, `operator delete[]`, or a conversion like `operator bool`
+        let word = after.trim_start();
+        let word_len = word
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(word.len());
+        let mut len = after.len() - word.len() + word_len;
+        if after[len..].starts_with("[]") {
+            len += 2;
+        }
+        len
+    } else {
+        return None;
+    };
+
+    let end = start + KEYWORD.len() + len;
+    Some((&name[..start], &name[start..end], &name[end..]))
+}
+
+/// Strip generic arguments and argument lists from `name`.
+///
+/// `follows_name` is whether `name` is a suffix that directly follows a name.
+fn strip_nested(name: &str, follows_name: bool) -> String {
+    let mut output = String::with_capacity(name.len());
+    // For each '<' or '(' we're inside of, whether it's being kept.
+    let mut stack: Vec<(char, bool)> = Vec::new();
+    let mut prev_is_name = follows_name;
+
+    for c in name.chars() {
+        let stripping = stack.iter().any(|&(_, kept)| !kept);
+        match c {
+            '<' | '(' => {
+                // Brackets that follow a name are generic arguments/argument lists.
+                // Others are Rust qualified paths (`<T as Trait>`) or C++ names like
+                // `(anonymous namespace)`, which we keep.
+                let kept = !prev_is_name;
+                stack.push((c, kept));
+                if kept && !stripping {
+                    output.push(c);
+                }
+            }
+            '>' | ')' if stack.last().map(|&(open, _)| open) == Some(matching_open(c)) => {
+                let (_, kept) = stack.pop().unwrap();
+                if kept && !stripping {
+                    output.push(c);
+                }
+            }
+            _ => {
+                if !stripping {
+                    output.push(c);
+                }
+            }
+        }
+        if !c.is_whitespace() {
+            prev_is_name = c.is_alphanumeric() || matches!(c, '_' | '>' | ')');
+        }
+    }
+
+    output
+}
+
+/// Strip trailing qualifiers (` const`) and Rust symbol hashes from a name that's
+/// already been through [`strip_nested`].
+fn strip_suffixes(name: &str) -> String {
+    let mut output = name.trim();
+    for qualifier in [" const", " volatile", " &&", " &"] {
+        if let Some(stripped) = output.strip_suffix(qualifier) {
+            output = stripped.trim_end();
+        }
+    }
+    strip_rust_hash(output).to_owned()
+}
+
+fn matching_open(close: char) -> char {
+    if close == '>' {
+        '<'
+    } else {
+        '('
+    }
+}
+
+/// Remove a legacy-mangled Rust symbol's `::h0123456789abcdef` hash.
+fn strip_rust_hash(name: &str) -> &str {
+    match name.rsplit_once("::h") {
+        Some((path, hash)) if hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()) => {
+            path
+        }
+        _ => name,
+    }
+}
+
+/// Match `text` against `pattern`, where `*` in the pattern matches any sequence of
+/// characters (including none).
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // There's always at least one part
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No wildcards, so this must be an exact match
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalize_function_name() {
+        let cases = [
+            ("foo", "foo"),
+            ("foo(int, char*)", "foo"),
+            ("Foo::bar() const", "Foo::bar"),
+            (
+                "std::vector<int, std::allocator<int> >::push_back(int const&)",
+                "std::vector::push_back",
+            ),
+            (
+                "(anonymous namespace)::Helper::run()",
+                "(anonymous namespace)::Helper::run",
+            ),
+            (
+                "<alloc::vec::Vec<T> as core::ops::drop::Drop>::drop",
+                "<alloc::vec::Vec as core::ops::drop::Drop>::drop",
+            ),
+            (
+                "core::ptr::drop_in_place<my_crate::Thing>",
+                "core::ptr::drop_in_place",
+            ),
+            ("my_crate::main::h0123456789abcdef", "my_crate::main"),
+            ("my_crate::hello", "my_crate::hello"),
+            ("Foo::operator<<(std::ostream&)", "Foo::operator<<"),
+            ("Foo<int>::operator()(int)", "Foo::operator()"),
+            ("operator new(unsigned long)", "operator new"),
+            ("operator delete[](void*)", "operator delete[]"),
+            ("Foo::operator bool() const", "Foo::operator bool"),
+            ("cooperator::run()", "cooperator::run"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                normalize_function_name(input),
+                expected,
+                "normalizing {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("abort", "abort"));
+        assert!(!glob_match("abort", "abort2"));
+        assert!(!glob_match("abort", "my_abort"));
+        assert!(glob_match(
+            "core::panicking::*",
+            "core::panicking::panic_fmt"
+        ));
+        assert!(!glob_match("core::panicking::*", "core::option::unwrap"));
+        assert!(glob_match("libc.so*@*", "libc.so.6@0x1234"));
+        assert!(!glob_match("libc.so*@*", "libcfoo.so@0x1234"));
+        assert!(glob_match(
+            "std::sys::*::abort_internal",
+            "std::sys::pal::unix::abort_internal"
+        ));
+        assert!(glob_match("*", ""));
+    }
+}
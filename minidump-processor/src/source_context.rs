@@ -0,0 +1,229 @@
+//! Finding the source code referenced by symbol files on the local machine.
+//!
+//! Symbol files name source files by the path they had on the machine that
+//! built the module (e.g. `c:\build\src\dom\base\Element.cpp`, or
+//! `/builds/worker/checkouts/gecko/dom/base/Element.cpp`), which rarely
+//! exists where the minidump is processed. [`SourceContextOptions`] describes
+//! where to look for them instead:
+//!
+//! * `prefix_map` replaces a prefix of the path with a local directory, so
+//!   `("/builds/worker/checkouts/gecko", "/home/me/gecko")` turns the path
+//!   above into `/home/me/gecko/dom/base/Element.cpp`.
+//! * `roots` are checkouts to find the file in when no prefix matches. Each
+//!   root is tried with every suffix of the path, longest first, so
+//!   `/home/me/gecko` would find `/home/me/gecko/dom/base/Element.cpp` too.
+//!
+//! Backslashes are treated as path separators (Windows symbol files use
+//! them) and prefixes of Windows paths are matched case-insensitively, so the
+//! same paths work on every host. Paths containing `..` are never looked up.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use minidump_unwind::{CallStack, SourceContext};
+use tracing::debug;
+
+/// Where to find the source files referenced by symbol files, and how much of
+/// them to show.
+///
+/// See the [module documentation][self] for how paths are mapped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceContextOptions {
+    /// Local checkouts to look for source files in.
+    pub roots: Vec<PathBuf>,
+    /// Pairs of path prefixes in symbol files and the local directories they
+    /// correspond to. The first matching prefix is used.
+    pub prefix_map: Vec<(String, PathBuf)>,
+    /// How many lines to include before and after a frame's line.
+    pub context_lines: u32,
+}
+
+/// The default [`SourceContextOptions::context_lines`].
+pub const DEFAULT_SOURCE_CONTEXT_LINES: u32 = 1;
+
+impl Default for SourceContextOptions {
+    fn default() -> Self {
+        Self {
+            roots: Vec::new(),
+            prefix_map: Vec::new(),
+            context_lines: DEFAULT_SOURCE_CONTEXT_LINES,
+        }
+    }
+}
+
+impl SourceContextOptions {
+    /// The local paths that `source_file` may be at, in the order they should be tried.
+    pub fn local_paths(&self, source_file: &str) -> Vec<PathBuf> {
+        let normalized = source_file.replace('\\', "/");
+        let components: Vec<&str> = normalized
+            .split('/')
+            .filter(|component| !component.is_empty() && *component != ".")
+            .collect();
+        if components.contains(&"..") {
+            return Vec::new();
+        }
+
+        let mut paths = Vec::new();
+        let is_windows_path = source_file.contains('\\') || has_drive_letter(&normalized);
+        for (prefix, local_dir) in &self.prefix_map {
+            if let Some(rest) = strip_path_prefix(&normalized, prefix, is_windows_path) {
+                paths.push(join(local_dir, rest.split('/')));
+                break;
+            }
+        }
+
+        // The drive letter is never part of a checkout
+        let suffix_start = usize::from(components.first().is_some_and(|c| has_drive_letter(c)));
+        for root in &self.roots {
+            for start in suffix_start..components.len() {
+                paths.push(join(root, components[start..].iter().copied()));
+            }
+        }
+        paths
+    }
+}
+
+fn has_drive_letter(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
+
+/// Strip `prefix` from `path` if it's made of whole components of it.
+fn strip_path_prefix<'p>(path: &'p str, prefix: &str, ignore_case: bool) -> Option<&'p str> {
+    let prefix = prefix.replace('\\', "/");
+    let prefix = prefix.trim_end_matches('/');
+    let head = path.get(..prefix.len())?;
+    let matches = if ignore_case {
+        head.eq_ignore_ascii_case(prefix)
+    } else {
+        head == prefix
+    };
+    if !matches {
+        return None;
+    }
+    let rest = &path[prefix.len()..];
+    if prefix.is_empty() || rest.is_empty() || rest.starts_with('/') {
+        Some(rest.trim_start_matches('/'))
+    } else {
+        None
+    }
+}
+
+fn join<'c>(dir: &Path, components: impl Iterator<Item = &'c str>) -> PathBuf {
+    let mut path = dir.to_owned();
+    path.extend(components.filter(|component| !component.is_empty()));
+    path
+}
+
+/// Reads source files for [`SourceContext`]s, remembering the files it
+/// already read (or failed to find).
+pub struct SourceReader<'a> {
+    options: &'a SourceContextOptions,
+    files: HashMap<String, Option<Vec<String>>>,
+}
+
+impl<'a> SourceReader<'a> {
+    pub fn new(options: &'a SourceContextOptions) -> Self {
+        Self {
+            options,
+            files: HashMap::new(),
+        }
+    }
+
+    /// The lines around `line` of `source_file` (as named by a symbol file).
+    ///
+    /// Returns `None` if the file can't be found locally, or is too short to
+    /// have that line.
+    pub fn source_context(&mut self, source_file: &str, line: u32) -> Option<SourceContext> {
+        let options = self.options;
+        let lines = self
+            .files
+            .entry(source_file.to_owned())
+            .or_insert_with(|| read_lines(options, source_file))
+            .as_ref()?;
+
+        let idx = line.checked_sub(1)? as usize;
+        if idx >= lines.len() {
+            return None;
+        }
+        let context_lines = options.context_lines as usize;
+        let start = idx.saturating_sub(context_lines);
+        let end = idx.saturating_add(context_lines).min(lines.len() - 1);
+        Some(SourceContext {
+            first_line: start as u32 + 1,
+            line,
+            lines: lines[start..=end].to_vec(),
+        })
+    }
+
+    /// Fill in [`StackFrame::source_context`][minidump_unwind::StackFrame::source_context]
+    /// for every frame of `stack` that has a source line.
+    pub fn fill_call_stack(&mut self, stack: &mut CallStack) {
+        for frame in &mut stack.frames {
+            if let (Some(file), Some(line)) = (&frame.source_file_name, frame.source_line) {
+                frame.source_context = self.source_context(file, line);
+            }
+        }
+    }
+}
+
+fn read_lines(options: &SourceContextOptions, source_file: &str) -> Option<Vec<String>> {
+    for path in options.local_paths(source_file) {
+        if let Ok(bytes) = std::fs::read(&path) {
+            debug!("found source file {} at {}", source_file, path.display());
+            let text = String::from_utf8_lossy(&bytes);
+            return Some(
+                text.lines()
+                    .map(|line| line.trim_end().to_owned())
+                    .collect(),
+            );
+        }
+    }
+    debug!("couldn't find source file {}", source_file);
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_local_paths() {
+        let options = SourceContextOptions {
+            roots: vec![PathBuf::from("/src")],
+            prefix_map: vec![(
+                String::from("C:\\build\\gecko\\"),
+                PathBuf::from("/home/me/gecko"),
+            )],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            options.local_paths("c:\\Build\\gecko\\dom\\Element.cpp"),
+            [
+                PathBuf::from("/home/me/gecko/dom/Element.cpp"),
+                PathBuf::from("/src/Build/gecko/dom/Element.cpp"),
+                PathBuf::from("/src/gecko/dom/Element.cpp"),
+                PathBuf::from("/src/dom/Element.cpp"),
+                PathBuf::from("/src/Element.cpp"),
+            ]
+        );
+        // Unix paths are case-sensitive, and prefixes have to be whole components
+        assert_eq!(
+            options.local_paths("/builds/a.c"),
+            [PathBuf::from("/src/builds/a.c"), PathBuf::from("/src/a.c")]
+        );
+        assert!(options.local_paths("/src/../etc/passwd").is_empty());
+
+        let options = SourceContextOptions {
+            prefix_map: vec![(String::from("/builds/w"), PathBuf::from("/local"))],
+            ..Default::default()
+        };
+        assert_eq!(
+            options.local_paths("/builds/w/src/a.c"),
+            [PathBuf::from("/local/src/a.c")]
+        );
+        assert!(options.local_paths("/builds/worker/a.c").is_empty());
+        assert!(options.local_paths("/Builds/w/a.c").is_empty());
+    }
+}
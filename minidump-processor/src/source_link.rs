@@ -0,0 +1,227 @@
+//! Links to the source code of frames whose symbols are source indexed.
+//!
+//! Source indexed symbol files name the repository and revision of each source
+//! file (see [`VcsSourceFile`]), so a frame's file and line can be turned into
+//! a URL of the repository's web frontend. Which URL depends on what serves the
+//! repository, so [`SourceLinkOptions`] maps repositories to templates:
+//!
+//! * Mercurial repositories are linked to with hgweb's URLs ([`HGWEB_TEMPLATE`]).
+//! * Git repositories on github.com are linked to with GitHub's "blob" URLs
+//!   ([`GITHUB_BLOB_TEMPLATE`]). [`GITHUB_RAW_TEMPLATE`] links to the raw file
+//!   instead.
+//!
+//! The frames of other repositories, and the frames of files that aren't source
+//! indexed, don't get links.
+
+use minidump_unwind::{Vcs, VcsSourceFile};
+
+/// hgweb's page of a file at a revision, scrolled to the line.
+pub const HGWEB_TEMPLATE: &str = "https://{repo}/file/{revision}/{path}#l{line}";
+/// GitHub's page of a file at a revision, scrolled to the line.
+pub const GITHUB_BLOB_TEMPLATE: &str = "https://{repo}/blob/{revision}/{path}#L{line}";
+/// The contents of a file on GitHub at a revision.
+pub const GITHUB_RAW_TEMPLATE: &str =
+    "https://raw.githubusercontent.com/{repo_path}/{revision}/{path}";
+
+/// The URL template of the source files of some repositories.
+///
+/// The template's placeholders are replaced with the parts of a
+/// [`VcsSourceFile`] and the frame's line:
+///
+/// * `{repo}`: the repository (e.g. "github.com/rust-lang/rust")
+/// * `{host}`: the host of the repository ("github.com")
+/// * `{repo_path}`: the repository without the host ("rust-lang/rust")
+/// * `{path}`: the path of the file in the repository
+/// * `{revision}`: the revision of the file
+/// * `{line}`: the line of the frame (empty if unknown)
+///
+/// If the line is unknown and the `#` fragment of the URL has `{line}` in it
+/// (like the `#l{line}` of [`HGWEB_TEMPLATE`]), the fragment is left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLinkTemplate {
+    /// The version control system of the repositories.
+    pub vcs: Vcs,
+    /// The host of the repositories, or `None` for repositories on any host.
+    pub host: Option<String>,
+    /// The URL, with placeholders.
+    pub template: String,
+}
+
+/// How to link to the source code of frames.
+///
+/// The default links to Mercurial repositories with [`HGWEB_TEMPLATE`] and to
+/// git repositories on github.com with [`GITHUB_BLOB_TEMPLATE`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLinkOptions {
+    /// The templates to use, the first one that matches a repository wins.
+    pub templates: Vec<SourceLinkTemplate>,
+}
+
+impl Default for SourceLinkOptions {
+    fn default() -> Self {
+        Self {
+            templates: vec![
+                SourceLinkTemplate {
+                    vcs: Vcs::Hg,
+                    host: None,
+                    template: HGWEB_TEMPLATE.to_owned(),
+                },
+                SourceLinkTemplate {
+                    vcs: Vcs::Git,
+                    host: Some("github.com".to_owned()),
+                    template: GITHUB_BLOB_TEMPLATE.to_owned(),
+                },
+            ],
+        }
+    }
+}
+
+impl SourceLinkOptions {
+    /// Options that don't link to anything.
+    pub fn none() -> Self {
+        Self {
+            templates: Vec::new(),
+        }
+    }
+
+    /// The link to `line` of `file`, if any template matches its repository.
+    pub fn link(&self, file: &VcsSourceFile, line: Option<u32>) -> Option<String> {
+        let host = file.host();
+        let template = self
+            .templates
+            .iter()
+            .find(|template| template.vcs == file.vcs && template.host.iter().all(|h| h == host))?;
+        let repo_path = file
+            .repo
+            .split_once('/')
+            .map_or("", |(_, repo_path)| repo_path);
+        let mut template = template.template.as_str();
+        if line.is_none() {
+            // There's no line to scroll to
+            if let Some((url, fragment)) = template.split_once('#') {
+                if fragment.contains("{line}") {
+                    template = url;
+                }
+            }
+        }
+        let line = line.map(|line| line.to_string()).unwrap_or_default();
+        Some(
+            template
+                .replace("{repo}", &file.repo)
+                .replace("{host}", host)
+                .replace("{repo_path}", repo_path)
+                .replace("{path}", &file.path)
+                .replace("{revision}", &file.revision)
+                .replace("{line}", &line),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn file(path: &str) -> VcsSourceFile {
+        VcsSourceFile::parse(path).unwrap()
+    }
+
+    #[test]
+    fn test_default_links() {
+        let options = SourceLinkOptions::default();
+        assert_eq!(
+            options.link(
+                &file("hg:hg.mozilla.org/mozilla-central:dom/base/Element.cpp:1a2b3c4d5e6f"),
+                Some(42)
+            ),
+            Some(
+                "https://hg.mozilla.org/mozilla-central/file/1a2b3c4d5e6f/dom/base/Element.cpp#l42"
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            options.link(
+                &file("git:github.com/rust-lang/rust:library/core/src/option.rs:fdca237d5194"),
+                Some(7)
+            ),
+            Some(
+                "https://github.com/rust-lang/rust/blob/fdca237d5194/library/core/src/option.rs#L7"
+                    .to_owned()
+            )
+        );
+        // Without a line, the links aren't scrolled to one
+        assert_eq!(
+            options.link(
+                &file("hg:hg.mozilla.org/mozilla-central:dom/base/Element.cpp:1a2b3c4d5e6f"),
+                None
+            ),
+            Some(
+                "https://hg.mozilla.org/mozilla-central/file/1a2b3c4d5e6f/dom/base/Element.cpp"
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            options.link(
+                &file("git:github.com/rust-lang/rust:library/core/src/option.rs:fdca237d5194"),
+                None
+            ),
+            Some(
+                "https://github.com/rust-lang/rust/blob/fdca237d5194/library/core/src/option.rs"
+                    .to_owned()
+            )
+        );
+        // No default for git repositories elsewhere
+        assert_eq!(
+            options.link(
+                &file("git:gitlab.example.com/app/app:src/main.c:fdca237d5194"),
+                Some(7)
+            ),
+            None
+        );
+        assert_eq!(
+            SourceLinkOptions::none().link(
+                &file("hg:hg.mozilla.org/mozilla-central:dom/base/Element.cpp:1a2b3c4d5e6f"),
+                Some(42)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_custom_templates() {
+        let mut options = SourceLinkOptions::default();
+        options.templates.insert(
+            0,
+            SourceLinkTemplate {
+                vcs: Vcs::Git,
+                host: Some("github.com".to_owned()),
+                template: GITHUB_RAW_TEMPLATE.to_owned(),
+            },
+        );
+        options.templates.push(SourceLinkTemplate {
+            vcs: Vcs::Git,
+            host: None,
+            template: "https://{host}/-/blob/{revision}/{path}?repo={repo_path}&line={line}"
+                .to_owned(),
+        });
+        assert_eq!(
+            options.link(
+                &file("git:github.com/rust-lang/rust:library/core/src/option.rs:fdca237d5194"),
+                Some(7)
+            ),
+            Some(
+                "https://raw.githubusercontent.com/rust-lang/rust/fdca237d5194/library/core/src/option.rs"
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            options.link(
+                &file("git:gitlab.example.com/app/app:src/main.c:fdca237d5194"),
+                None
+            ),
+            Some(
+                "https://gitlab.example.com/-/blob/fdca237d5194/src/main.c?repo=app/app&line="
+                    .to_owned()
+            )
+        );
+    }
+}
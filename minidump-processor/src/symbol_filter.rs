@@ -0,0 +1,184 @@
+//! Choosing which modules to look up symbols for.
+//!
+//! Looking up symbols can be expensive (e.g. downloading them from a symbol
+//! server), so the processor only asks the [`SymbolProvider`] about modules that
+//! the stack walk actually runs into, unless
+//! [`ProcessorOptions::symbolize_all_modules`][crate::ProcessorOptions::symbolize_all_modules]
+//! is set.
+//!
+//! On top of that, [`ModuleFilter`] can rule out modules that will never have
+//! symbols (or whose symbols you don't care about) by name. The processor
+//! treats those modules as if the provider had no symbols for them, so
+//! they're still walked through with frame pointers and stack scanning.
+//!
+//! The unwind info of the minidump's own dynamic function tables (see
+//! [`JitUnwindInfoProvider::from_function_tables`]) is added to the provider's
+//! JIT unwind info here too.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use minidump::Module;
+use minidump_common::utils::basename;
+use minidump_unwind::symbols::jit::JitUnwindInfoProvider;
+use minidump_unwind::{
+    CfiRules, FileError, FileKind, FillSymbolError, FrameSymbolizer, FrameWalker,
+    PendingSymbolStats, SymbolProvider, SymbolStats, WalkFrameError,
+};
+
+use crate::signature::glob_match;
+
+/// Which modules the processor may look up symbols for, by the file name of
+/// their code file (e.g. `xul.dll`).
+///
+/// Patterns are matched case-insensitively, and `*` matches any sequence of
+/// characters. A module is looked up if it doesn't match any pattern of `deny`,
+/// and either `allow` is empty or it matches a pattern of `allow`.
+///
+/// The default allows every module.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleFilter {
+    /// If not empty, only modules matching one of these patterns are looked up.
+    pub allow: Vec<String>,
+    /// Modules matching any of these patterns are never looked up.
+    pub deny: Vec<String>,
+}
+
+impl ModuleFilter {
+    /// Whether symbols may be looked up for a module named `name`.
+    pub fn allows_name(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        let matches = |pattern: &String| glob_match(&pattern.to_lowercase(), &name);
+        !self.deny.iter().any(matches) && (self.allow.is_empty() || self.allow.iter().any(matches))
+    }
+
+    /// Whether symbols may be looked up for `module`.
+    pub fn allows(&self, module: &dyn Module) -> bool {
+        self.allows_name(basename(&module.code_file()))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+}
+
+/// A [`SymbolProvider`] that pretends there are no symbols for the modules a
+/// [`ModuleFilter`] rules out, and falls back to the minidump's
+/// `function_tables` for JITed code.
+pub(crate) struct FilteredSymbolProvider<'a, P> {
+    inner: &'a P,
+    filter: &'a ModuleFilter,
+    function_tables: Option<&'a JitUnwindInfoProvider>,
+}
+
+impl<'a, P> FilteredSymbolProvider<'a, P> {
+    pub fn new(
+        inner: &'a P,
+        filter: &'a ModuleFilter,
+        function_tables: Option<&'a JitUnwindInfoProvider>,
+    ) -> Self {
+        Self {
+            inner,
+            filter,
+            function_tables,
+        }
+    }
+
+    fn allows(&self, module: &(dyn Module + Sync)) -> bool {
+        self.filter.is_empty() || self.filter.allows(module)
+    }
+}
+
+#[async_trait]
+impl<P: SymbolProvider + Sync> SymbolProvider for FilteredSymbolProvider<'_, P> {
+    async fn fill_symbol(
+        &self,
+        module: &(dyn Module + Sync),
+        frame: &mut (dyn FrameSymbolizer + Send),
+    ) -> Result<(), FillSymbolError> {
+        if !self.allows(module) {
+            return Err(FillSymbolError {});
+        }
+        self.inner.fill_symbol(module, frame).await
+    }
+
+    async fn walk_frame(
+        &self,
+        module: &(dyn Module + Sync),
+        walker: &mut (dyn FrameWalker + Send),
+    ) -> Option<()> {
+        if !self.allows(module) {
+            return None;
+        }
+        self.inner.walk_frame(module, walker).await
+    }
+
+    async fn try_walk_frame(
+        &self,
+        module: &(dyn Module + Sync),
+        walker: &mut (dyn FrameWalker + Send),
+    ) -> Result<(), WalkFrameError> {
+        if !self.allows(module) {
+            return Err(WalkFrameError::NoCfi);
+        }
+        self.inner.try_walk_frame(module, walker).await
+    }
+
+    async fn get_jit_unwind_info(&self, address: u64) -> Option<CfiRules> {
+        match self.inner.get_jit_unwind_info(address).await {
+            Some(rules) => Some(rules),
+            None => self.function_tables?.unwind_info(address),
+        }
+    }
+
+    async fn get_file_path(
+        &self,
+        module: &(dyn Module + Sync),
+        file_kind: FileKind,
+    ) -> Result<PathBuf, FileError> {
+        if !self.allows(module) {
+            return Err(FileError::NotFound);
+        }
+        self.inner.get_file_path(module, file_kind).await
+    }
+
+    fn stats(&self) -> HashMap<String, SymbolStats> {
+        self.inner.stats()
+    }
+
+    fn pending_stats(&self) -> PendingSymbolStats {
+        self.inner.pending_stats()
+    }
+
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_module_filter() {
+        let filter = ModuleFilter::default();
+        assert!(filter.allows_name("xul.dll"));
+
+        let filter = ModuleFilter {
+            allow: vec![],
+            deny: vec![String::from("plugin_*.dll")],
+        };
+        assert!(filter.allows_name("xul.dll"));
+        assert!(!filter.allows_name("plugin_foo.dll"));
+        assert!(!filter.allows_name("Plugin_Foo.DLL"));
+
+        let filter = ModuleFilter {
+            allow: vec![String::from("*.dll")],
+            deny: vec![String::from("plugin_*")],
+        };
+        assert!(filter.allows_name("ntdll.dll"));
+        assert!(!filter.allows_name("libc.so.6"));
+        assert!(!filter.allows_name("plugin_foo.dll"));
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use minidump::Module;
+use minidump_common::utils::basename;
+use minidump_unwind::{CallStack, CallStackInfo, StackFrame};
+
+use crate::{BlockedOn, ThreadGroup, WaitKind};
+
+// # Grouping threads
+//
+// Hang reports often have dozens of threads, most of which are idle workers of
+// a few thread pools, all waiting in the same place. Listing each of them hides
+// the few threads that are actually interesting, so threads with the same
+// symbolized stack are grouped together.
+//
+// Two frames are the same if they're in the same function of the same module.
+// The offsets don't matter, because two threads waiting in a function may be at
+// different places in it (and any inlined code doesn't get us much closer).
+// Frames without a function are compared by their offset in their module (or
+// their address if they aren't in a module), since there's nothing better.
+//
+// Separately from grouping, the top few frames are matched against a list of
+// well-known wait primitives, so the groups (and the threads) that are blocked
+// are easy to pick out.
+
+/// How many of the top frames of a thread are checked for wait primitives.
+///
+/// A wait primitive often isn't the top frame: the syscall stub, or the
+/// internal function that a public function calls, comes first.
+const WAIT_FRAMES: usize = 4;
+
+/// Well-known functions that block the calling thread.
+///
+/// The names are compared without leading underscores, argument lists and
+/// stdcall suffixes ("@12"). Futexes are also recognized by "futex" anywhere in
+/// the name, since glibc has a zoo of internal futex functions.
+const WAIT_FUNCTIONS: &[(&str, WaitKind)] = &[
+    // Windows
+    ("NtWaitForSingleObject", WaitKind::Object),
+    ("ZwWaitForSingleObject", WaitKind::Object),
+    ("NtWaitForMultipleObjects", WaitKind::Object),
+    ("ZwWaitForMultipleObjects", WaitKind::Object),
+    ("WaitForSingleObject", WaitKind::Object),
+    ("WaitForSingleObjectEx", WaitKind::Object),
+    ("WaitForMultipleObjects", WaitKind::Object),
+    ("WaitForMultipleObjectsEx", WaitKind::Object),
+    ("MsgWaitForMultipleObjects", WaitKind::Object),
+    ("MsgWaitForMultipleObjectsEx", WaitKind::Object),
+    ("NtWaitForAlertByThreadId", WaitKind::Lock),
+    ("RtlEnterCriticalSection", WaitKind::Lock),
+    ("EnterCriticalSection", WaitKind::Lock),
+    ("AcquireSRWLockExclusive", WaitKind::Lock),
+    ("AcquireSRWLockShared", WaitKind::Lock),
+    ("RtlAcquireSRWLockExclusive", WaitKind::Lock),
+    ("RtlAcquireSRWLockShared", WaitKind::Lock),
+    ("SleepConditionVariableCS", WaitKind::ConditionVariable),
+    ("SleepConditionVariableSRW", WaitKind::ConditionVariable),
+    ("RtlSleepConditionVariableCS", WaitKind::ConditionVariable),
+    ("RtlSleepConditionVariableSRW", WaitKind::ConditionVariable),
+    ("NtRemoveIoCompletion", WaitKind::Io),
+    ("NtRemoveIoCompletionEx", WaitKind::Io),
+    ("GetQueuedCompletionStatus", WaitKind::Io),
+    ("GetQueuedCompletionStatusEx", WaitKind::Io),
+    ("NtDelayExecution", WaitKind::Sleep),
+    ("Sleep", WaitKind::Sleep),
+    ("SleepEx", WaitKind::Sleep),
+    // POSIX
+    ("pthread_cond_wait", WaitKind::ConditionVariable),
+    ("pthread_cond_timedwait", WaitKind::ConditionVariable),
+    ("pthread_cond_clockwait", WaitKind::ConditionVariable),
+    ("pthread_mutex_lock", WaitKind::Lock),
+    ("pthread_rwlock_rdlock", WaitKind::Lock),
+    ("pthread_rwlock_wrlock", WaitKind::Lock),
+    ("lll_lock_wait", WaitKind::Lock),
+    ("sem_wait", WaitKind::Object),
+    ("sem_timedwait", WaitKind::Object),
+    ("epoll_wait", WaitKind::Io),
+    ("epoll_pwait", WaitKind::Io),
+    ("poll", WaitKind::Io),
+    ("ppoll", WaitKind::Io),
+    ("select", WaitKind::Io),
+    ("pselect", WaitKind::Io),
+    ("kevent", WaitKind::Io),
+    ("kevent64", WaitKind::Io),
+    ("nanosleep", WaitKind::Sleep),
+    ("clock_nanosleep", WaitKind::Sleep),
+    ("usleep", WaitKind::Sleep),
+    // macOS
+    ("psynch_cvwait", WaitKind::ConditionVariable),
+    ("psynch_mutexwait", WaitKind::Lock),
+    ("psynch_rw_rdlock", WaitKind::Lock),
+    ("psynch_rw_wrlock", WaitKind::Lock),
+    ("ulock_wait", WaitKind::Lock),
+    ("ulock_wait2", WaitKind::Lock),
+    ("semaphore_wait_trap", WaitKind::Object),
+    ("semaphore_timedwait_trap", WaitKind::Object),
+    ("semwait_signal", WaitKind::Sleep),
+    ("mach_msg_trap", WaitKind::MachMessage),
+    ("mach_msg2_trap", WaitKind::MachMessage),
+    ("mach_msg", WaitKind::MachMessage),
+    ("mach_msg_overwrite", WaitKind::MachMessage),
+    ("workq_kernreturn", WaitKind::Object),
+];
+
+/// What identifies a frame for grouping.
+#[derive(PartialEq, Eq, Hash)]
+enum FrameKey {
+    Function {
+        module: Option<u64>,
+        function: String,
+    },
+    Offset {
+        module: u64,
+        offset: u64,
+    },
+    Address(u64),
+}
+
+fn frame_key(frame: &StackFrame) -> FrameKey {
+    let module = frame.module.as_ref().map(|module| module.base_address());
+    match (&frame.function_name, module) {
+        (Some(function), module) => FrameKey::Function {
+            module,
+            function: function.clone(),
+        },
+        (None, Some(base)) => FrameKey::Offset {
+            module: base,
+            offset: frame.instruction - base,
+        },
+        (None, None) => FrameKey::Address(frame.instruction),
+    }
+}
+
+fn frame_description(frame: &StackFrame) -> String {
+    let module = frame.module.as_ref().map(|module| {
+        (
+            basename(&module.code_file()).to_owned(),
+            module.base_address(),
+        )
+    });
+    match (&frame.function_name, module) {
+        (Some(function), Some((module, _))) => format!("{module}!{function}"),
+        (Some(function), None) => function.clone(),
+        (None, Some((module, base))) => format!("{module} + {:#x}", frame.instruction - base),
+        (None, None) => format!("{:#x}", frame.instruction),
+    }
+}
+
+/// The wait primitive `function` is, if it's a known one.
+fn wait_kind(function: &str) -> Option<WaitKind> {
+    let name = function.split('(').next().unwrap_or(function);
+    let name = match name.rsplit_once('@') {
+        Some((name, suffix)) if suffix.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => name,
+    };
+    let name = name.trim_start_matches('_');
+
+    WAIT_FUNCTIONS
+        .iter()
+        .find(|&&(wait_function, _)| wait_function == name)
+        .map(|&(_, kind)| kind)
+        .or_else(|| name.contains("futex").then_some(WaitKind::Futex))
+}
+
+/// What the thread is blocked on, if one of its top frames is a wait primitive.
+pub fn blocked_on(thread: &CallStack) -> Option<BlockedOn> {
+    thread.frames.iter().take(WAIT_FRAMES).find_map(|frame| {
+        let function = frame.function_name.as_deref()?;
+        let kind = wait_kind(function)?;
+        Some(BlockedOn {
+            kind,
+            function: function.to_owned(),
+        })
+    })
+}
+
+/// Group the threads with the same stacks, biggest group first.
+pub fn group_threads(threads: &[CallStack]) -> Vec<ThreadGroup> {
+    // stack => index into `groups`
+    let mut group_of_stack: HashMap<Vec<FrameKey>, usize> = HashMap::new();
+    let mut groups: Vec<ThreadGroup> = Vec::new();
+    for (i, thread) in threads.iter().enumerate() {
+        if thread.info == CallStackInfo::DumpThreadSkipped || thread.frames.is_empty() {
+            continue;
+        }
+        let key = thread.frames.iter().map(frame_key).collect();
+        let group = *group_of_stack.entry(key).or_insert_with(|| {
+            groups.push(ThreadGroup {
+                threads: Vec::new(),
+                frames: thread.frames.iter().map(frame_description).collect(),
+                blocked_on: blocked_on(thread),
+            });
+            groups.len() - 1
+        });
+        groups[group].threads.push(i);
+    }
+
+    // Stable, so groups of the same size stay in thread order
+    groups.sort_by_key(|group| std::cmp::Reverse(group.threads.len()));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wait_kind() {
+        assert_eq!(wait_kind("WaitForSingleObjectEx"), Some(WaitKind::Object));
+        assert_eq!(
+            wait_kind("_NtWaitForSingleObject@12"),
+            Some(WaitKind::Object)
+        );
+        assert_eq!(
+            wait_kind("__psynch_cvwait"),
+            Some(WaitKind::ConditionVariable)
+        );
+        assert_eq!(
+            wait_kind("pthread_cond_wait(pthread_cond_t*, pthread_mutex_t*)"),
+            Some(WaitKind::ConditionVariable)
+        );
+        assert_eq!(
+            wait_kind("__GI___futex_abstimed_wait_cancelable64"),
+            Some(WaitKind::Futex)
+        );
+        assert_eq!(wait_kind("mach_msg_trap"), Some(WaitKind::MachMessage));
+        assert_eq!(wait_kind("WaitForSingleObjectExtra"), None);
+        assert_eq!(wait_kind("main"), None);
+    }
+}
@@ -0,0 +1,113 @@
+use std::convert::TryFrom;
+
+use minidump::system_info::{Os, PointerWidth};
+use minidump::{MinidumpThread, MinidumpThreadInfoList, MinidumpThreadNames, UnifiedMemoryList};
+use minidump_unwind::{SystemInfo, ThreadIdentity};
+
+// # Who a thread really is
+//
+// Crashes in thread teardown (TLS destructors run by `std::thread` or
+// pthreads) often have contexts that don't say much about the thread: its
+// stack is mostly gone, and the thread id may not match anything else in the
+// minidump. The thread's own bookkeeping can fill that in, if its memory was
+// captured.
+//
+// On Windows, the thread list has the address of every thread's TEB, which
+// starts with an `NT_TIB`:
+//
+// * `NT_TIB.StackBase` and `NT_TIB.StackLimit` are the bounds of the stack,
+// * `NT_TIB.Self` points to the TEB itself, which tells us we're looking at a
+//   TEB and not some random memory,
+//
+// and the TEB has the ids of the process and thread it belongs to
+// (`TEB.ClientId`). With that id, the ThreadNames and ThreadInfoList streams
+// give the thread's name and start address.
+//
+// Elsewhere, the equivalent is the pthread struct the thread pointer points
+// to, but minidump contexts don't have the thread pointer (`fs_base`,
+// `TPIDR_EL0`), so there's nothing to go on and we don't try.
+
+/// The offsets of the fields we need, for the pointer width of the process.
+struct TebLayout {
+    pointer_size: u64,
+    stack_base: u64,
+    stack_limit: u64,
+    self_pointer: u64,
+    thread_id: u64,
+}
+
+const TEB_LAYOUT_32: TebLayout = TebLayout {
+    pointer_size: 4,
+    stack_base: 0x04,
+    stack_limit: 0x08,
+    self_pointer: 0x18,
+    thread_id: 0x24,
+};
+
+const TEB_LAYOUT_64: TebLayout = TebLayout {
+    pointer_size: 8,
+    stack_base: 0x08,
+    stack_limit: 0x10,
+    self_pointer: 0x30,
+    thread_id: 0x48,
+};
+
+/// What `thread`'s TEB says about it, if the minidump has (the start of) it.
+pub fn read_thread_identity(
+    system_info: &SystemInfo,
+    thread: &MinidumpThread,
+    memory: &UnifiedMemoryList,
+    thread_names: &MinidumpThreadNames,
+    thread_infos: &MinidumpThreadInfoList,
+) -> Option<ThreadIdentity> {
+    if system_info.os != Os::Windows {
+        return None;
+    }
+    let layout = match system_info.cpu.pointer_width() {
+        PointerWidth::Bits32 => &TEB_LAYOUT_32,
+        PointerWidth::Bits64 => &TEB_LAYOUT_64,
+        PointerWidth::Unknown => return None,
+    };
+    let address = thread.raw.teb;
+    if address == 0 || read_pointer(memory, layout, address, layout.self_pointer)? != address {
+        return None;
+    }
+
+    // The thread id is pointer-sized, but Windows' are 32 bits.
+    let thread_id = read_pointer(memory, layout, address, layout.thread_id)
+        .and_then(|id| u32::try_from(id).ok())
+        .filter(|&id| id != 0);
+    let thread_name = thread_id
+        .and_then(|id| thread_names.get_name(id))
+        .map(|name| name.into_owned());
+    let start_address = thread_id
+        .and_then(|id| thread_infos.get_thread_info(id))
+        .map(|info| info.raw.start_address)
+        .filter(|&address| address != 0);
+    Some(ThreadIdentity {
+        address,
+        thread_id,
+        stack_base: read_pointer(memory, layout, address, layout.stack_base),
+        stack_limit: read_pointer(memory, layout, address, layout.stack_limit),
+        thread_name,
+        start_address,
+    })
+}
+
+/// The pointer at `base + offset`, if that memory is in the minidump and the
+/// pointer isn't null.
+fn read_pointer(
+    memory: &UnifiedMemoryList,
+    layout: &TebLayout,
+    base: u64,
+    offset: u64,
+) -> Option<u64> {
+    let addr = base.checked_add(offset)?;
+    let region = memory.memory_at_address(addr)?;
+    let pointer = if layout.pointer_size == 4 {
+        u64::from(region.get_u32_at(addr)?)
+    } else {
+        region.get_u64_at(addr)?
+    };
+    (pointer != 0).then_some(pointer)
+}
@@ -0,0 +1,140 @@
+//! Finding the frames of thunks.
+//!
+//! Lots of calls go through a small stub before reaching the function they're
+//! calling: calls into shared libraries go through `foo@plt` on Linux and
+//! through import thunks on Windows, MSVC emits adjustor and vcall thunks for
+//! virtual calls, and instrumented builds call `_penter` or `__fentry__` at the
+//! start of every function. When a crash happens in (or under) one of those,
+//! the stub shows up as its own frame next to the function it forwards to,
+//! which makes the stack look deeper than it is and gets in the way of
+//! signatures.
+//!
+//! The processor marks the frames of these functions with
+//! [`StackFrame::is_thunk`]. They're always left out of signatures (unless
+//! [`SignatureOptions::skip_thunk_frames`][crate::signature::SignatureOptions::skip_thunk_frames]
+//! is turned off), and can be removed from the threads altogether with
+//! [`ProcessorOptions::merge_thunk_frames`][crate::ProcessorOptions::merge_thunk_frames].
+//!
+//! Thunks are recognized by their symbol names (see [`is_thunk_function`]).
+//! Neither minidumps nor breakpad symbols say which section a function is in
+//! or how long its code is, so stubs that aren't named like one (e.g. a plain
+//! `jmp` that the symbols call `foo`) aren't found.
+
+use minidump_unwind::{CallStack, StackFrame};
+
+/// Prefixes of the names of thunks.
+const THUNK_PREFIXES: &[&str] = &[
+    // Windows import thunks (`__imp_CreateFileW`, `_imp__CreateFileW@28`)
+    "__imp_",
+    "_imp__",
+    // MSVC adjustor, vcall, and vtordisp thunks (`[thunk]:Foo::Bar`adjustor{8}'`)
+    "[thunk]:",
+    // Entries of the incremental linking table (`ILT+1234(?Foo@@YAXXZ)`)
+    "ILT+",
+    // Delay-load import helpers
+    "__tailMerge_",
+    // Retpolines
+    "__x86_indirect_thunk_",
+];
+
+/// Suffixes of the names of thunks.
+const THUNK_SUFFIXES: &[&str] = &[
+    // Procedure linkage table entries (`memcpy@plt`)
+    "@plt", "@PLT",
+];
+
+/// The names of profiling and tracing hooks that instrumented builds call in
+/// every function.
+const INSTRUMENTATION_FUNCTIONS: &[&str] = &[
+    "_penter",
+    "_pexit",
+    "__penter",
+    "__pexit",
+    "mcount",
+    "_mcount",
+    "__mcount",
+    "__fentry__",
+    "__cyg_profile_func_enter",
+    "__cyg_profile_func_exit",
+    "__x86_return_thunk",
+];
+
+/// Whether `name` is the symbol name of a thunk.
+pub fn is_thunk_function(name: &str) -> bool {
+    THUNK_PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+        || THUNK_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+        || INSTRUMENTATION_FUNCTIONS.contains(&name)
+}
+
+/// Whether a symbolicated frame is the frame of a thunk.
+pub fn is_thunk_frame(frame: &StackFrame) -> bool {
+    frame
+        .function_name
+        .as_deref()
+        .is_some_and(is_thunk_function)
+}
+
+/// Set [`StackFrame::is_thunk`] on the frames of `stack`.
+pub fn mark_thunk_frames(stack: &mut CallStack) {
+    for frame in &mut stack.frames {
+        frame.is_thunk = is_thunk_frame(frame);
+    }
+}
+
+/// Remove the thunk frames of `stack` (as marked by [`mark_thunk_frames`]),
+/// optionally keeping the original frames in [`CallStack::raw_frames`].
+///
+/// The first frame is always kept: if the crash is in a thunk, the function it
+/// was forwarding to isn't on the stack, so there's nothing to merge it into.
+pub fn merge_thunk_frames(stack: &mut CallStack, keep_raw_frames: bool) {
+    if !stack.frames.iter().skip(1).any(|frame| frame.is_thunk) {
+        return;
+    }
+    if keep_raw_frames {
+        stack.raw_frames = Some(stack.frames.clone());
+    }
+    let mut idx = 0;
+    stack.frames.retain(|frame| {
+        idx += 1;
+        idx == 1 || !frame.is_thunk
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_thunk_function() {
+        let thunks = [
+            "memcpy@plt",
+            "pthread_mutex_lock@PLT",
+            "__imp_CreateFileW",
+            "_imp__CreateFileW@28",
+            "[thunk]:CFoo::Release`adjustor{8}'",
+            "ILT+1234(?Foo@@YAXXZ)",
+            "__tailMerge_user32_dll",
+            "__x86_indirect_thunk_rax",
+            "_penter",
+            "__fentry__",
+            "mcount",
+            "__cyg_profile_func_enter",
+        ];
+        for name in thunks {
+            assert!(is_thunk_function(name), "{} should be a thunk", name);
+        }
+
+        let functions = [
+            "memcpy",
+            "CreateFileW",
+            "CFoo::Release",
+            "mozilla::dom::Element::GetAttr",
+            "plt_lookup",
+            "mcount_histogram",
+            "my_crate::thunk::run",
+        ];
+        for name in functions {
+            assert!(!is_thunk_function(name), "{} shouldn't be a thunk", name);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+// Dumping this very process with minidump-synth's Linux writer, and processing
+// the dump.
+
+#![cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Mutex;
+
+use minidump::system_info::Os;
+use minidump::{
+    Minidump, MinidumpLinuxAuxv, MinidumpMiscInfo, MinidumpModuleList, MinidumpSystemInfo,
+    MinidumpThreadList, Module,
+};
+use minidump_processor::ProcessState;
+use minidump_synth::linux::{CrashContext, LinuxDumper};
+use minidump_unwind::{string_symbol_supplier, Symbolizer};
+
+/// The dump written by [`dump_handler`].
+static DUMP: Mutex<Option<io::Result<Vec<u8>>>> = Mutex::new(None);
+
+extern "C" fn dump_handler(
+    _signal: libc::c_int,
+    info: *mut libc::siginfo_t,
+    context: *mut libc::c_void,
+) {
+    // Safety: an SA_SIGINFO handler is passed a siginfo_t and a ucontext_t
+    let crash =
+        unsafe { CrashContext::new(libc::gettid(), &*info, &*context.cast::<libc::ucontext_t>()) };
+    // The signal is raised synchronously, so allocating and locking is fine
+    *DUMP.lock().unwrap() = Some(LinuxDumper::current_process(crash).dump());
+}
+
+/// Raise SIGUSR1, and return the dump that its handler wrote.
+fn dump_self() -> Vec<u8> {
+    let handler: extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void) = dump_handler;
+    // Safety: the handler only dumps, and the action is zeroed otherwise
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler as libc::sighandler_t;
+        action.sa_flags = libc::SA_SIGINFO;
+        assert_eq!(
+            libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut()),
+            0
+        );
+        assert_eq!(libc::raise(libc::SIGUSR1), 0);
+    }
+    DUMP.lock()
+        .unwrap()
+        .take()
+        .expect("the signal handler didn't run")
+        .expect("couldn't write the dump")
+}
+
+async fn process(dump: &Minidump<'_, Vec<u8>>) -> ProcessState {
+    let provider = Symbolizer::new(string_symbol_supplier(HashMap::new()));
+    minidump_processor::process_minidump(dump, &provider)
+        .await
+        .unwrap()
+}
+
+fn current_exe() -> String {
+    std::env::current_exe()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_owned()
+}
+
+#[tokio::test]
+async fn test_dump_self() {
+    let dump = Minidump::read(dump_self()).unwrap();
+    // Safety: gettid can't fail
+    let tid = unsafe { libc::gettid() } as u32;
+
+    let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+    assert_eq!(system_info.os, Os::Linux);
+
+    let threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+    assert_eq!(threads.threads.len(), 1);
+    assert_eq!(threads.threads[0].raw.thread_id, tid);
+
+    let modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+    let exe = modules
+        .iter()
+        .find(|module| module.code_file() == current_exe())
+        .expect("the test executable isn't in the dump");
+    assert!(exe.code_identifier().is_some());
+    assert!(exe.debug_identifier().is_some());
+    let vdso = modules
+        .iter()
+        .find(|module| module.code_file() == "[vdso]")
+        .expect("the vdso isn't in the dump");
+
+    let auxv = dump.get_stream::<MinidumpLinuxAuxv>().unwrap();
+    assert_eq!(auxv.vdso_base(), Some(vdso.base_address()));
+
+    let misc_info = dump.get_stream::<MinidumpMiscInfo>().unwrap();
+    assert_eq!(misc_info.raw.process_id(), Some(&std::process::id()));
+    let create_time = misc_info.process_create_time().unwrap();
+    assert!(create_time > std::time::UNIX_EPOCH && create_time <= std::time::SystemTime::now());
+
+    let state = process(&dump).await;
+    assert!(state.crashed());
+    assert_eq!(state.requesting_thread, Some(0));
+    let exception = state.exception_info.as_ref().unwrap();
+    assert!(exception.reason.to_string().starts_with("SIGUSR1"));
+
+    let thread = &state.threads[0];
+    assert!(thread.thread_name.is_some());
+    assert!(thread.frames.len() > 1);
+    assert!(thread.frames[0].module.is_some());
+    // Somewhere up the stack, this test called raise
+    assert!(thread.frames.iter().any(|frame| frame
+        .module
+        .as_ref()
+        .is_some_and(|module| module.code_file() == current_exe())));
+}
+
+#[tokio::test]
+async fn test_dump_ptraced() {
+    // Safety: the child only makes async-signal-safe calls
+    let child = unsafe {
+        let child = libc::fork();
+        if child == 0 {
+            libc::ptrace(libc::PTRACE_TRACEME, 0, 0, 0);
+            libc::raise(libc::SIGSTOP);
+            libc::_exit(0);
+        }
+        child
+    };
+    assert!(child > 0);
+    let mut status = 0;
+    // Safety: waiting for our own child
+    unsafe {
+        assert_eq!(libc::waitpid(child, &mut status, 0), child);
+    }
+    assert!(libc::WIFSTOPPED(status));
+
+    let dump = LinuxDumper::ptraced(child).dump();
+
+    // Safety: killing our own child
+    unsafe {
+        libc::kill(child, libc::SIGKILL);
+        libc::waitpid(child, &mut status, 0);
+    }
+
+    let dump = Minidump::read(dump.unwrap()).unwrap();
+    let threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+    assert_eq!(threads.threads.len(), 1);
+    assert_eq!(threads.threads[0].raw.thread_id, child as u32);
+
+    let state = process(&dump).await;
+    assert!(!state.crashed());
+    assert_eq!(state.system_info.os, Os::Linux);
+    let thread = &state.threads[0];
+    assert!(thread.frames.len() > 1);
+    assert!(thread.frames.iter().any(|frame| frame
+        .module
+        .as_ref()
+        .is_some_and(|module| module.code_file() == current_exe())));
+}
@@ -1,15 +1,33 @@
 // Copyright 2015 Ted Mielczarek. See the COPYRIGHT
 // file at the top-level directory of this distribution.
 
-use minidump::system_info::{Cpu, Os};
+use async_trait::async_trait;
+use minidump::system_info::{Cpu, CpuEmulation, Os};
 use minidump::{
     Error, Minidump, MinidumpContext, MinidumpContextValidity, MinidumpRawContext, MmapMinidump,
-    Module,
+    Module, SliceMinidump,
 };
-use minidump_common::format::MemoryProtection;
-use minidump_processor::{Limit, LinuxStandardBase, ProcessState};
-use minidump_unwind::{simple_symbol_supplier, CallStackInfo, FrameTrust, Symbolizer};
+use minidump_common::format::{MemoryProtection, MemoryState, MemoryType, MINIDUMP_STREAM_TYPE};
+use minidump_processor::crash_category::{
+    CrashCategory, CrashCategoryRule, DEFAULT_CATEGORY_RULES,
+};
+use minidump_processor::source_context::SourceContextOptions;
+use minidump_processor::symbol_filter::ModuleFilter;
+use minidump_processor::{
+    BlockedOn, ConsistencyWarning, CrashNote, DumpKind, FrameAnnotator, InstructionBytes, Limit,
+    LinuxStandardBase, PrintOptions, ProcessState, ProcessorOptions, RedactedSection, ThreadGroup,
+    WaitKind,
+};
+use minidump_unwind::{
+    simple_symbol_supplier, string_symbol_supplier, CallStack, CallStackInfo, DebugInfoResult,
+    FileError, FileKind, FillSymbolError, FrameSymbolizer, FrameTrust, FrameWalker,
+    LocateSymbolsResult, MultiSymbolProvider, StackFrame, StackMemoryRepair, SymbolDisposition,
+    SymbolError, SymbolFile, SymbolKind, SymbolProvider, SymbolSupplier, Symbolizer,
+    ThreadIdentity, ThreadTimes, WalkFrameError, WalkTerminatedReason,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use minidump_synth::*;
 use test_assembler::*;
@@ -68,6 +86,10 @@ async fn test_processor() {
     // Check thread 0.
     assert_eq!(state.threads[0].info, CallStackInfo::Ok);
     assert_eq!(state.threads[0].frames.len(), 4);
+    assert_eq!(
+        state.threads[0].walk_terminated_reason,
+        Some(WalkTerminatedReason::NullInstructionPointer)
+    );
     // Check thread 0, frame 0.
     let f0 = &state.threads[0].frames[0];
     let m1 = f0.module.as_ref().unwrap();
@@ -114,6 +136,8 @@ async fn test_processor() {
     // The dump thread should have been skipped.
     assert_eq!(state.threads[1].info, CallStackInfo::DumpThreadSkipped);
     assert_eq!(state.threads[1].frames.len(), 0);
+    assert_eq!(state.dump_thread_id, Some(state.threads[1].thread_id));
+    assert_eq!(state.requesting_thread_id, Some(state.threads[0].thread_id));
 }
 
 #[tokio::test]
@@ -134,6 +158,138 @@ async fn test_processor_symbols() {
     );
 }
 
+#[tokio::test]
+async fn test_extract_thread() {
+    let dump = read_test_minidump().unwrap();
+    let symbolizer = Symbolizer::new(simple_symbol_supplier(vec![testdata_symbol_path()]));
+    let state = minidump_processor::process_minidump(&dump, &symbolizer)
+        .await
+        .unwrap();
+    let frames = |stack: &CallStack| {
+        stack
+            .frames
+            .iter()
+            .map(|frame| {
+                (
+                    frame.instruction,
+                    frame.trust,
+                    frame.function_name.clone(),
+                    frame
+                        .module
+                        .as_ref()
+                        .map(|module| module.code_file().into_owned()),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    for (i, stack) in state.threads.iter().enumerate() {
+        let extracted = Minidump::read(dump.extract_thread(stack.thread_id).unwrap()).unwrap();
+        let extracted_state = minidump_processor::process_minidump(&extracted, &symbolizer)
+            .await
+            .unwrap();
+        assert_eq!(extracted_state.threads.len(), 1);
+        let extracted_stack = &extracted_state.threads[0];
+        assert_eq!(extracted_stack.thread_id, stack.thread_id);
+        if stack.info == CallStackInfo::DumpThreadSkipped {
+            // Only the original minidump says which thread wrote it.
+            assert_eq!(extracted_stack.info, CallStackInfo::Ok);
+            assert!(!extracted_stack.frames.is_empty());
+        } else {
+            assert_eq!(frames(extracted_stack), frames(stack));
+        }
+        assert_eq!(extracted_state.system_info.cpu, state.system_info.cpu);
+        assert_eq!(
+            extracted_state.modules.iter().count(),
+            state.modules.iter().count()
+        );
+
+        // The exception only comes along with the thread it happened on.
+        if state.requesting_thread == Some(i) {
+            assert_eq!(extracted_state.requesting_thread, Some(0));
+            let exception_info = extracted_state.exception_info.unwrap();
+            assert_eq!(
+                exception_info.address,
+                state.exception_info.as_ref().unwrap().address
+            );
+        } else {
+            assert!(extracted_state.exception_info.is_none());
+        }
+    }
+}
+
+/// A `SymbolSupplier` that serves symbols from memory (keyed by debug id).
+struct InMemorySymbolSupplier {
+    symbols: HashMap<String, Vec<u8>>,
+}
+
+#[async_trait]
+impl SymbolSupplier for InMemorySymbolSupplier {
+    async fn locate_symbols(
+        &self,
+        module: &(dyn Module + Sync),
+    ) -> Result<LocateSymbolsResult, SymbolError> {
+        let debug_id = module.debug_identifier().ok_or(SymbolError::NotFound)?;
+        let symbols = self
+            .symbols
+            .get(&debug_id.breakpad().to_string())
+            .ok_or(SymbolError::NotFound)?;
+        Ok(LocateSymbolsResult {
+            symbols: SymbolFile::from_bytes(symbols)?,
+            extra_debug_info: None,
+        })
+    }
+
+    async fn locate_file(
+        &self,
+        _module: &(dyn Module + Sync),
+        _file_kind: FileKind,
+    ) -> Result<PathBuf, FileError> {
+        Err(FileError::NotFound)
+    }
+}
+
+#[tokio::test]
+async fn test_processor_in_memory() {
+    // Everything is read up front, so processing only ever sees buffers.
+    let dump_bytes = std::fs::read(locate_testdata().join("test.dmp")).unwrap();
+    let sym_bytes = std::fs::read(
+        testdata_symbol_path().join("test_app.pdb/5A9832E5287241C1838ED98914E9B7FF1/test_app.sym"),
+    )
+    .unwrap();
+
+    let supplier = InMemorySymbolSupplier {
+        symbols: HashMap::from([(String::from("5A9832E5287241C1838ED98914E9B7FF1"), sym_bytes)]),
+    };
+
+    // Borrow the dump instead of copying it
+    let dump: SliceMinidump = Minidump::read(&dump_bytes[..]).unwrap();
+    let state = minidump_processor::process_minidump(&dump, &Symbolizer::new(supplier))
+        .await
+        .unwrap();
+    assert_eq!(state.threads.len(), 2);
+    assert_eq!(
+        state.threads[0].frames[0].function_name.as_deref(),
+        Some("`anonymous namespace'::CrashFunction")
+    );
+    // The symbols came from the buffer, and the modules without any weren't found
+    assert!(state.symbol_stats["test_app.exe"].loaded_symbols);
+    assert!(!state.symbol_stats["kernel32.dll"].loaded_symbols);
+    assert_eq!(state.symbol_stats["kernel32.dll"].load_error, None);
+
+    // The same works for an owned buffer
+    let owned = Minidump::read(dump_bytes.clone()).unwrap();
+    let owned_state = minidump_processor::process_minidump(
+        &owned,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+    )
+    .await
+    .unwrap();
+    assert_eq!(owned_state.threads.len(), 2);
+    assert_eq!(owned_state.threads[0].frames[0].function_name, None);
+    assert!(!owned_state.symbol_stats["test_app.exe"].loaded_symbols);
+}
+
 fn minimal_minidump() -> SynthMinidump {
     let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
     let stack = Memory::with_section(
@@ -170,6 +326,69 @@ microcode : 0x1e34a6789
     assert_eq!(state.system_info.cpu_microcode_version, Some(0x1e34a6789));
 }
 
+#[tokio::test]
+async fn test_linux_cpu_info_fields() {
+    let input = b"processor\t: 0
+vendor_id\t: GenuineIntel
+model name\t: Intel(R) Core(TM) i7-8700 CPU @ 3.20GHz
+microcode\t: 0xf0
+flags\t\t: fpu vme sse4_2 avx2
+
+processor\t: 1
+vendor_id\t: GenuineIntel
+model name\t: Intel(R) Core(TM) i7-8700 CPU @ 3.20GHz
+microcode\t: 0xf0
+flags\t\t: fpu vme sse4_2 avx2
+";
+    let dump = minimal_minidump().set_linux_cpu_info(input);
+    let state = read_synth_dump(dump).await;
+
+    let info = state.linux_cpu_info.as_ref().unwrap();
+    assert_eq!(info.processor_count, 2);
+    assert_eq!(info.vendor.as_deref(), Some("GenuineIntel"));
+    assert_eq!(
+        info.model_name.as_deref(),
+        Some("Intel(R) Core(TM) i7-8700 CPU @ 3.20GHz")
+    );
+    assert_eq!(info.microcode_version, Some(0xf0));
+    assert_eq!(info.flags, ["fpu", "vme", "sse4_2", "avx2"]);
+    assert_eq!(state.system_info.cpu_microcode_version, Some(0xf0));
+    // The system info has no cpuid feature bits, so the flags are used.
+    assert_eq!(state.system_info.cpu_features, info.flags);
+
+    // Older ARM kernels name the CPU in `Processor`.
+    let input = b"Processor\t: AArch64 Processor rev 4 (aarch64)
+processor\t: 0
+Features\t: fp asimd evtstrm crc32
+CPU implementer\t: 0x51
+";
+    let context = minidump_synth::arm64_context(Endian::Little, 0x1000, 0x8000);
+    let stack = Memory::with_section(Section::with_endian(Endian::Little).D64(0), 0x8000);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_ARM64 as u16,
+    );
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(Thread::new(Endian::Little, 1, &stack, &context))
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack)
+        .set_linux_cpu_info(input);
+    let state = read_synth_dump(dump).await;
+
+    let info = state.linux_cpu_info.as_ref().unwrap();
+    assert_eq!(info.processor_count, 1);
+    assert_eq!(info.vendor.as_deref(), Some("0x51"));
+    assert_eq!(info.microcode_version, None);
+    assert_eq!(
+        state.system_info.cpu_info.as_deref(),
+        Some("AArch64 Processor rev 4 (aarch64)")
+    );
+    assert_eq!(
+        state.system_info.cpu_features,
+        ["fp", "asimd", "evtstrm", "crc32"]
+    );
+}
+
 #[tokio::test]
 async fn test_linux_lsb_release() {
     // Whitespace intentionally wonky to test robustness
@@ -205,6 +424,10 @@ PRETTY_NAME= wow long string!!!
 "#;
         let dump = minimal_minidump().set_linux_lsb_release(input);
         let state = read_synth_dump(dump).await;
+        assert_eq!(
+            state.system_info.os_distro.as_deref(),
+            Some("wow long string!!!")
+        );
 
         let LinuxStandardBase {
             id,
@@ -218,6 +441,40 @@ PRETTY_NAME= wow long string!!!
         assert_eq!(codename, "very long string");
         assert_eq!(description, "wow long string!!!");
     }
+
+    {
+        // Without a description, the distro is named by its id and release
+        let input = b"DISTRIB_ID=Ubuntu\nDISTRIB_RELEASE=22.04\n";
+        let dump = minimal_minidump().set_linux_lsb_release(input);
+        let state = read_synth_dump(dump).await;
+        assert_eq!(state.system_info.os_distro.as_deref(), Some("Ubuntu 22.04"));
+        let mut human = Vec::new();
+        state.print(&mut human).unwrap();
+        let human = String::from_utf8(human).unwrap();
+        assert!(human.contains("\nLinux Ubuntu 22.04\n"), "{}", human);
+        // The distro is also listed under the OS version
+        assert!(
+            human.contains("\n                  Ubuntu 22.04\nCPU: "),
+            "{}",
+            human
+        );
+    }
+
+    {
+        // Fedora's os-release has no codename
+        let input =
+            b"ID=fedora\nVERSION_ID=39\nPRETTY_NAME=\"Fedora Linux 39 (Workstation Edition)\"\n";
+        let dump = minimal_minidump().set_linux_lsb_release(input);
+        let state = read_synth_dump(dump).await;
+        let mut human = Vec::new();
+        state.print(&mut human).unwrap();
+        let human = String::from_utf8(human).unwrap();
+        assert!(
+            human.contains("\nLinux fedora 39 (Fedora Linux 39 (Workstation Edition))\n"),
+            "{}",
+            human
+        );
+    }
 }
 
 #[tokio::test]
@@ -234,24 +491,372 @@ Pid:	3747
 
 #[tokio::test]
 async fn test_linux_environ() {
-    // Whitespace intentionally wonky to test robustness
+    let cmdline = b"/usr/bin/firefox\0-P\0default profile\0";
+    let environ = b"LD_PRELOAD=libhook.so\0GITHUB_TOKEN=hunter2\0MOZ_LOG=sync:5\0LANG=C.UTF-8\0";
+    let linux_dump = || {
+        minimal_minidump()
+            .set_linux_cmdline(cmdline)
+            .set_linux_environ(environ)
+    };
 
-    // TODO: add tests for values we care about
-    let input = b"";
+    let state = read_synth_dump(linux_dump()).await;
+    let environment = state.environment.unwrap();
+    assert_eq!(
+        environment.command_line.as_deref(),
+        Some("/usr/bin/firefox -P default profile")
+    );
+    // The synthetic dump has no modules to take the executable from.
+    assert_eq!(environment.executable_path, None);
+    assert_eq!(
+        environment.variables,
+        BTreeMap::from([
+            (String::from("LANG"), String::from("C.UTF-8")),
+            (String::from("LD_PRELOAD"), String::from("libhook.so")),
+        ])
+    );
 
-    let dump = minimal_minidump().set_linux_environ(input);
-    let _state = read_synth_dump(dump).await;
+    // Only what the allow-list asks for is included.
+    let dump = Minidump::read(linux_dump().finish().unwrap()).unwrap();
+    let mut options = ProcessorOptions::default();
+    options.environment_variables = vec![String::from("MOZ_*")];
+    let state = minidump_processor::process_minidump_with_options(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+        options,
+    )
+    .await
+    .unwrap();
+    let environment = state.environment.unwrap();
+    assert_eq!(
+        environment.variables,
+        BTreeMap::from([(String::from("MOZ_LOG"), String::from("sync:5"))])
+    );
+
+    // Old dumps separated the variables by newlines.
+    let state = read_synth_dump(minimal_minidump().set_linux_environ(b"LANG=C\n")).await;
+    let environment = state.environment.unwrap();
+    assert_eq!(environment.command_line, None);
+    assert_eq!(
+        environment.variables,
+        BTreeMap::from([(String::from("LANG"), String::from("C"))])
+    );
+}
+
+/// The image base address the PEB of [`windows_environment_dump`] has.
+const WINDOWS_IMAGE_BASE: u64 = 0x0040_0000;
+
+/// A Windows x86 dump whose TEB points to a PEB (with [`WINDOWS_IMAGE_BASE`]),
+/// whose process parameters have the given image path, command line and
+/// environment block.
+///
+/// The environment block is cut off after `environment_len` bytes, as if the
+/// dump hadn't captured the rest of it.
+fn windows_environment_dump(environment: &str, environment_len: usize) -> SynthMinidump {
+    const TEB: u64 = 0x7ffd_e000;
+    const PEB: u64 = 0x7ffd_f000;
+    const PARAMS: u64 = 0x0020_0000;
+
+    fn utf16(string: &str) -> Vec<u8> {
+        string
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect()
+    }
+    // A UNICODE_STRING of the given length, for a string at `buffer`.
+    fn unicode_string(len: usize, buffer: u64) -> Section {
+        Section::with_endian(Endian::Little)
+            .D16(len as u16)
+            .D16(len as u16 + 2)
+            .D32(buffer as u32)
+    }
+
+    let image_path = utf16(r"C:\Program Files\App\app.exe");
+    let command_line = utf16(r#""C:\Program Files\App\app.exe" -safe-mode"#);
+    let mut environment = utf16(environment);
+    environment.truncate(environment_len);
+
+    let teb = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .append_repeated(0, 0x30)
+            .D32(PEB as u32),
+        TEB,
+    );
+    let peb = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .append_repeated(0, 0x08)
+            .D32(WINDOWS_IMAGE_BASE as u32)
+            .D32(0)
+            .D32(PARAMS as u32),
+        PEB,
+    );
+    let params = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .append_repeated(0, 0x38)
+            .append_section(unicode_string(image_path.len(), PARAMS + 0x100))
+            .append_section(unicode_string(command_line.len(), PARAMS + 0x200))
+            .D32(PARAMS as u32 + 0x400)
+            .append_repeated(0, 0x100 - 0x4c)
+            .append_bytes(&image_path)
+            .append_repeated(0, 0x100 - image_path.len())
+            .append_bytes(&command_line)
+            .append_repeated(0, 0x200 - command_line.len())
+            .append_bytes(&environment),
+        PARAMS,
+    );
+
+    let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    let thread = Thread::with_teb(Endian::Little, 0x1234, TEB, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little)
+        .set_platform_id(minidump_common::format::PlatformId::VER_PLATFORM_WIN32_NT as u32);
+    SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack)
+        .add_memory(teb)
+        .add_memory(peb)
+        .add_memory(params)
+}
+
+#[tokio::test]
+async fn test_windows_environment() {
+    let block =
+        "=C:=C:\\Users\\me\0PATH=C:\\Windows\0__COMPAT_LAYER=WinXP\0Processor_Architecture=x86\0\0";
+
+    let state = read_synth_dump(windows_environment_dump(block, usize::MAX)).await;
+    let environment = state.environment.unwrap();
+    assert_eq!(
+        environment.command_line.as_deref(),
+        Some(r#""C:\Program Files\App\app.exe" -safe-mode"#)
+    );
+    assert_eq!(
+        environment.executable_path.as_deref(),
+        Some(r"C:\Program Files\App\app.exe")
+    );
+    // Names are matched case-insensitively on Windows.
+    assert_eq!(
+        environment.variables,
+        BTreeMap::from([
+            (String::from("Processor_Architecture"), String::from("x86")),
+            (String::from("__COMPAT_LAYER"), String::from("WinXP")),
+        ])
+    );
+
+    // If the end of the environment block is missing, we get what's there.
+    let state = read_synth_dump(windows_environment_dump(block, 0x70)).await;
+    let environment = state.environment.unwrap();
+    assert!(environment.command_line.is_some());
+    assert_eq!(
+        environment.variables,
+        BTreeMap::from([(String::from("__COMPAT_LAYER"), String::from("WinXP"))])
+    );
+}
+
+#[tokio::test]
+async fn test_thread_identity_from_teb() {
+    const TEB: u64 = 0x7ffd_e000;
+
+    // A Windows dump whose crashing thread has a TEB of `teb_len` bytes (with
+    // `teb_self` as NT_TIB.Self), which says it belongs to thread 0x5678.
+    let dump = |teb_len: usize, teb_self: u32| {
+        let mut teb = Section::with_endian(Endian::Little)
+            .D32(0)
+            .D32(0x0010_0000) // StackBase
+            .D32(0x000f_c000) // StackLimit
+            .append_repeated(0, 0x0c)
+            .D32(teb_self)
+            .D32(0)
+            .D32(0x9abc) // ClientId.UniqueProcess
+            .D32(0x5678) // ClientId.UniqueThread
+            .get_contents()
+            .unwrap();
+        teb.truncate(teb_len);
+        let teb =
+            Memory::with_section(Section::with_endian(Endian::Little).append_bytes(&teb), TEB);
+
+        let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+            0x1000,
+        );
+        let thread = Thread::with_teb(Endian::Little, 0x1234, TEB, &stack, &context);
+        let name = DumpString::new("worker", Endian::Little);
+        let mut info = ThreadInfo::new(Endian::Little, 0x5678);
+        info.start_address = 0x7000_1000;
+        let system_info = SystemInfo::new(Endian::Little)
+            .set_platform_id(minidump_common::format::PlatformId::VER_PLATFORM_WIN32_NT as u32);
+        let mut exception = Exception::new(Endian::Little);
+        exception.thread_id = 0x1234;
+        SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add_exception(exception)
+            .add_thread_name(ThreadName::new(Endian::Little, 0x5678, Some(&name)))
+            .add_thread_info(info)
+            .add_system_info(system_info)
+            .add(context)
+            .add(name)
+            .add_memory(stack)
+            .add_memory(teb)
+    };
+
+    let state = read_synth_dump(dump(usize::MAX, TEB as u32)).await;
+    let stack = &state.threads[state.requesting_thread.unwrap()];
+    assert_eq!(
+        stack.thread_identity,
+        Some(ThreadIdentity {
+            address: TEB,
+            thread_id: Some(0x5678),
+            stack_base: Some(0x0010_0000),
+            stack_limit: Some(0x000f_c000),
+            thread_name: Some(String::from("worker")),
+            start_address: Some(0x7000_1000),
+        })
+    );
+    let mut human = Vec::new();
+    state.print(&mut human).unwrap();
+    let human = String::from_utf8(human).unwrap();
+    assert!(
+        human.contains(
+            "Thread 0 worker (crashed) - tid: 4660\n\
+             Thread identity (TEB at 0x7ffde000): tid: 22136 (worker), started at 0x70001000, \
+             stack: 0xfc000-0x100000\n"
+        ),
+        "{}",
+        human
+    );
+
+    // The TEB is cut off before ClientId.
+    let state = read_synth_dump(dump(0x20, TEB as u32)).await;
+    let identity = state.threads[0].thread_identity.as_ref().unwrap();
+    assert_eq!(identity.thread_id, None);
+    assert_eq!(identity.thread_name, None);
+    assert_eq!(identity.stack_base, Some(0x0010_0000));
+
+    // It isn't a TEB at all, or it's missing.
+    let state = read_synth_dump(dump(usize::MAX, 0x1234_5678)).await;
+    assert_eq!(state.threads[0].thread_identity, None);
+    let state = read_synth_dump(dump(0x10, TEB as u32)).await;
+    assert_eq!(state.threads[0].thread_identity, None);
+}
+
+/// Add modules with the given names and base addresses (in this order) to `dump`.
+fn add_modules(mut dump: SynthMinidump, modules: &[(&str, u64)]) -> SynthMinidump {
+    for &(name, base) in modules {
+        let name = DumpString::new(name, Endian::Little);
+        let module = minidump_synth::Module::new(Endian::Little, base, 0x10000, &name, 0, 0, None);
+        dump = dump.add_module(module).add(name);
+    }
+    dump
+}
+
+fn main_module_name(state: &ProcessState) -> Option<String> {
+    let index = state.main_module?;
+    let module = state.modules.iter().nth(index)?;
+    Some(module.code_file().into_owned())
+}
+
+#[tokio::test]
+async fn test_main_module() {
+    // Without any hints, the module with the lowest address is the executable.
+    let dump = add_modules(
+        minimal_minidump(),
+        &[("libc.so.6", 0x7f00_0000), ("app", 0x5500_0000)],
+    );
+    let state = read_synth_dump(dump).await;
+    assert_eq!(state.main_module, Some(1));
+    assert_eq!(
+        state.environment.unwrap().executable_path.as_deref(),
+        Some("app")
+    );
+
+    // The name of the program on the Linux command line beats that.
+    let dump = add_modules(
+        minimal_minidump().set_linux_cmdline(b"./firefox\0--safe-mode\0"),
+        &[
+            ("/lib/ld-linux.so.2", 0x1000_0000),
+            ("/usr/lib/libc.so.6", 0x7f00_0000),
+            ("/usr/lib/firefox/firefox", 0x5500_0000),
+        ],
+    );
+    let state = read_synth_dump(dump).await;
+    assert_eq!(
+        main_module_name(&state).as_deref(),
+        Some("/usr/lib/firefox/firefox")
+    );
+    let report = serde_json::to_value(state.to_json_report()).unwrap();
+    assert_eq!(report["main_module"], 2);
+    assert_eq!(report["modules"][0]["is_main"], false);
+    assert_eq!(report["modules"][2]["is_main"], true);
+
+    // And the image base address in the PEB beats everything.
+    let dump = add_modules(
+        windows_environment_dump("\0", usize::MAX),
+        &[
+            ("C:\\Windows\\System32\\ntdll.dll", 0x7700_0000),
+            ("C:\\Program Files\\App\\app.exe", 0x0100_0000),
+            ("C:\\Program Files\\App\\plugin.exe", WINDOWS_IMAGE_BASE),
+        ],
+    );
+    let state = read_synth_dump(dump).await;
+    assert_eq!(
+        main_module_name(&state).as_deref(),
+        Some("C:\\Program Files\\App\\plugin.exe")
+    );
+
+    let mut human = Vec::new();
+    state.print(&mut human).unwrap();
+    let human = String::from_utf8(human).unwrap();
+    assert!(
+        human.starts_with("Process: plugin.exe (version "),
+        "{}",
+        human
+    );
 }
 
 #[tokio::test]
 async fn test_linux_proc_status() {
     // Whitespace intentionally wonky to test robustness
 
-    // TODO: add tests for values we care about
-    let input = b"";
+    let input = b"
+Name:\tfirefox
+Pid:\t3747
+VmPeak:\t  2048000 kB
+VmSize:\t  1024000 kB
+VmHWM:\t    51200 kB
+VmRSS:\t    40960 kB
+Threads:   \t42
+SigBlk:\t0000000000000000
+SigIgn:\t0000000000001000
+SigCgt:\t00000001000004ea
+";
 
     let dump = minimal_minidump().set_linux_proc_status(input);
-    let _state = read_synth_dump(dump).await;
+    let state = read_synth_dump(dump).await;
+
+    let status = state.linux_proc_status.as_ref().unwrap();
+    assert_eq!(status.pid, 3747);
+    assert_eq!(status.threads, Some(42));
+    assert_eq!(status.vm_peak, Some(2048000 * 1024));
+    assert_eq!(status.vm_size, Some(1024000 * 1024));
+    assert_eq!(status.vm_hwm, Some(51200 * 1024));
+    assert_eq!(status.vm_rss, Some(40960 * 1024));
+    assert_eq!(status.vm_swap, None);
+    assert_eq!(status.sig_blk, Some(0));
+    let caught: Vec<u32> =
+        minidump_processor::LinuxProcStatus::signals(status.sig_cgt.unwrap()).collect();
+    // SIGINT, SIGILL, SIGABRT, SIGBUS, SIGFPE, SIGSEGV and the first real-time signal
+    assert_eq!(caught, [2, 4, 6, 7, 8, 11, 33]);
+
+    let report = state.to_json_report();
+    let status = report.proc_status.unwrap();
+    assert_eq!(status.threads, Some(42));
+    assert_eq!(status.vm_rss, Some(40960 * 1024));
+    assert_eq!(status.sig_ign.as_deref(), Some("0x0000000000001000"));
+    assert_eq!(status.sig_cgt.as_deref(), Some("0x00000001000004ea"));
 }
 
 #[tokio::test]
@@ -335,79 +940,3255 @@ async fn test_no_frames() {
     state.print_json(&mut std::io::sink(), true).unwrap();
 }
 
+/// A dump of a thread in `app` whose stack (at 0x9000, with a return address
+/// into `app` at the stack pointer) is in the memory list, but whose own stack
+/// memory descriptor is `thread_stack`.
+fn broken_stack_minidump(thread_stack: Memory) -> SynthMinidump {
+    let context = minidump_synth::x86_context(Endian::Little, 0x0040_0050, 0x9010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .append_repeated(0, 0x10)
+            .D32(0x0040_0100)
+            .append_repeated(0, 0xfec),
+        0x9000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &thread_stack, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        // The thread's own stack memory isn't in the memory list.
+        .add(thread_stack)
+        .add_memory(stack);
+    add_modules(dump, &[("app", 0x0040_0000)])
+}
+
 #[tokio::test]
-async fn test_bit_flip() {
-    let context = minidump_synth::amd64_context(Endian::Little, 0, 0);
+async fn test_consistency_warnings() {
+    const DUMP_TIME: u32 = 1262805309;
 
-    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0);
-    let heap_info = MemoryInfo::new(Endian::Little, 0x80000, 0x80000, 0, 8, 0, 0, 0);
+    let build_consistent_dump = || {
+        let context = minidump_synth::x86_context(Endian::Little, 0x0040_0050, 0x9010);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+            0x9000,
+        );
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let name = DumpString::new("app", Endian::Little);
+        let module = minidump_synth::Module::new(
+            Endian::Little,
+            0x0040_0000,
+            0x10000,
+            &name,
+            DUMP_TIME - 1000,
+            0,
+            None,
+        );
+        SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add_system_info(SystemInfo::new(Endian::Little))
+            .add(context)
+            .add_memory(stack)
+            .add_module(module)
+            .add(name)
+    };
+    let state = read_synth_dump(build_consistent_dump()).await;
+    assert_eq!(state.consistency_warnings, []);
+    assert_eq!(state.to_json_report().consistency_warnings, None);
 
-    let thread = Thread::new(Endian::Little, 1, &stack, &context);
-    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
-        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    // The exception is on a thread that doesn't exist, two threads have the same
+    // id (and a stack pointer that isn't in any memory), and the only module was
+    // built in the future.
+    let context = minidump_synth::x86_context(Endian::Little, 0x0040_0050, 0x9010);
+    let other_context = minidump_synth::x86_context(Endian::Little, 0x0040_0050, 0x2_0000);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x9000,
     );
-
-    let mut ex = Exception::new(Endian::Little);
-    ex.thread_id = 1;
-    ex.exception_record.exception_address = 0x80400;
-
+    let name = DumpString::new("app", Endian::Little);
+    let module = minidump_synth::Module::new(
+        Endian::Little,
+        0x0040_0000,
+        0x10000,
+        &name,
+        DUMP_TIME + 10 * 24 * 60 * 60,
+        0,
+        None,
+    );
+    let mut exception = Exception::new(Endian::Little);
+    exception.thread_id = 0x9999;
     let dump = SynthMinidump::with_endian(Endian::Little)
-        .add_thread(thread)
-        .add_exception(ex)
-        .add_system_info(system_info)
+        .add_thread(Thread::new(Endian::Little, 0x1234, &stack, &context))
+        .add_thread(Thread::new(Endian::Little, 0x5678, &stack, &other_context))
+        .add_thread(Thread::new(Endian::Little, 0x5678, &stack, &context))
+        .add_exception(exception)
+        .add_system_info(SystemInfo::new(Endian::Little))
         .add(context)
+        .add(other_context)
         .add_memory(stack)
-        .add_memory_info(heap_info);
-
+        .add_module(module)
+        .add(name);
     let state = read_synth_dump(dump).await;
 
-    let bit_flips = state
-        .exception_info
-        .expect("missing exception info")
-        .possible_bit_flips;
+    assert_eq!(state.requesting_thread, None);
+    assert_eq!(
+        state.consistency_warnings,
+        [
+            ConsistencyWarning::ExceptionThreadNotFound { thread_id: 0x9999 },
+            ConsistencyWarning::DuplicateThreadId {
+                thread_id: 0x5678,
+                count: 2
+            },
+            ConsistencyWarning::StackPointerNotInMemory {
+                thread_id: 0x5678,
+                stack_pointer: 0x2_0000
+            },
+            ConsistencyWarning::ModulesNewerThanDump { modules: 1 },
+        ]
+    );
 
-    assert_eq!(bit_flips.len(), 1);
-    let bf = bit_flips.into_iter().next().unwrap();
-    assert_eq!(bf.address.0, 0x80000);
-    assert_eq!(bf.details, Default::default());
+    let mut human = Vec::new();
+    state.print(&mut human).unwrap();
+    let human = String::from_utf8(human).unwrap();
+    assert!(
+        human.contains(
+            "Consistency warnings:\n  \
+             the exception is on thread 39321, which isn't in the thread list, so the crashing \
+             thread is unknown\n  \
+             2 threads have the id 22136\n"
+        ),
+        "{}",
+        human
+    );
+
+    let report = state.to_json_report();
+    let warnings = report.consistency_warnings.unwrap();
+    assert_eq!(warnings.len(), 4);
+    assert_eq!(warnings[2].code, "StackPointerNotInMemory");
+    assert_eq!(
+        warnings[2].message,
+        "the stack pointer 0x20000 of thread 22136 isn't in any memory of the minidump"
+    );
+    assert_eq!(report.crashing_thread, None);
+    assert_eq!(report.crash_info.crashing_thread, None);
 }
 
 #[tokio::test]
-async fn test_no_bit_flip_32bit() {
-    let context = minidump_synth::x86_context(Endian::Little, 0, 0);
-
-    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0);
-    let heap_info = MemoryInfo::new(Endian::Little, 0x80000, 0x80000, 0, 8, 0, 0, 0);
+async fn test_mac_crash_info() {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::MozMacosCrashInfoStream as u32;
+    let record = Section::with_endian(Endian::Little)
+        .D64(STREAM_TYPE as u64)
+        .D64(4) // version
+        .D64(0x1234) // thread
+        .D64(0x20) // dialog_mode
+        .append_bytes(b"/usr/lib/dyld\0")
+        .append_bytes(b"Library not loaded: @rpath/libfoo.dylib\0")
+        .append_bytes(b"\0\0")
+        .append_bytes(b"Reason: image not found\0");
+    let mut header = Section::with_endian(Endian::Little)
+        .D32(STREAM_TYPE)
+        .D32(1) // record_count
+        .D32(32) // record_start_size
+        .cite_location(&record);
+    for _ in 1..20 {
+        header = header.D32(0).D32(0);
+    }
+    let dump = minimal_minidump()
+        .add_stream(SimpleStream {
+            stream_type: STREAM_TYPE,
+            section: header,
+        })
+        .add(record);
+    let state = read_synth_dump(dump).await;
 
-    let thread = Thread::new(Endian::Little, 1, &stack, &context);
-    let system_info = SystemInfo::new(Endian::Little);
+    let report = state.to_json_report();
+    let info = report.mac_crash_info.unwrap();
+    assert_eq!(info.num_records, 1);
+    let record = &info.records[0];
+    assert_eq!(record.thread.as_deref(), Some("0x00001234"));
+    assert_eq!(record.module.as_deref(), Some("/usr/lib/dyld"));
+    assert_eq!(
+        record.message.as_deref(),
+        Some("Library not loaded: @rpath/libfoo.dylib")
+    );
+    // Empty strings are left out
+    assert_eq!(record.signature_string, None);
+    assert_eq!(record.backtrace, None);
+    assert_eq!(record.message2.as_deref(), Some("Reason: image not found"));
+    assert_eq!(record.abort_cause, None);
 
-    let mut ex = Exception::new(Endian::Little);
-    ex.thread_id = 1;
-    ex.exception_record.exception_address = 0x80400;
+    let mut human = Vec::new();
+    state.print(&mut human).unwrap();
+    let human = String::from_utf8(human).unwrap();
+    assert!(
+        human.contains(
+            "Mac Crash Info:\n  \
+             Record 0\n    \
+             thread: 0x1234\n    \
+             dialog mode: 0x20\n    \
+             module: /usr/lib/dyld\n    \
+             message: Library not loaded: @rpath/libfoo.dylib\n"
+        ),
+        "{}",
+        human
+    );
+}
 
+#[tokio::test]
+async fn test_object_not_in_module_list() {
+    let exe = DumpString::new("", Endian::Little);
+    let libc = DumpString::new("/lib/libc.so.6", Endian::Little);
+    let plugin = DumpString::new("/opt/app/libplugin.so", Endian::Little);
+    let link_maps = [
+        minidump_synth::LinkMap {
+            load_bias: 0x5555_0000,
+            name: &exe,
+            dynamic: 0x5555_2000,
+        },
+        minidump_synth::LinkMap {
+            load_bias: 0x7f00_0000,
+            name: &libc,
+            dynamic: 0x7f01_0000,
+        },
+        minidump_synth::LinkMap {
+            load_bias: 0x7e00_0000,
+            name: &plugin,
+            dynamic: 0x7e00_3000,
+        },
+    ];
+    let libc_path = DumpString::new("/usr/lib/libc.so.6", Endian::Little);
+    let module = minidump_synth::Module::new(
+        Endian::Little,
+        0x7f00_0000,
+        0x2_0000,
+        &libc_path,
+        0,
+        0,
+        None,
+    );
+    let context = minidump_synth::amd64_context(Endian::Little, 0x7f00_1000, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    );
     let dump = SynthMinidump::with_endian(Endian::Little)
         .add_thread(thread)
-        .add_exception(ex)
         .add_system_info(system_info)
         .add(context)
         .add_memory(stack)
-        .add_memory_info(heap_info);
-
+        .add_stream(minidump_synth::linux_dso_debug_stream(
+            Endian::Little,
+            &link_maps,
+            0x7f80_0000,
+            0x5555_2000,
+        ))
+        .add_module(module)
+        .add(libc_path)
+        .add(exe)
+        .add(libc)
+        .add(plugin);
     let state = read_synth_dump(dump).await;
 
-    assert!(state
-        .exception_info
-        .expect("missing exception info")
-        .possible_bit_flips
-        .is_empty());
+    assert_eq!(
+        state.consistency_warnings,
+        [ConsistencyWarning::ObjectNotInModuleList {
+            name: String::from("/opt/app/libplugin.so"),
+            load_bias: 0x7e00_0000,
+        }]
+    );
+    assert!(state.unimplemented_streams.is_empty());
+    let report = state.to_json_report();
+    let warnings = report.consistency_warnings.unwrap();
+    assert_eq!(warnings[0].code, "ObjectNotInModuleList");
+    assert_eq!(
+        warnings[0].message,
+        "the dynamic linker has loaded /opt/app/libplugin.so (with a load bias of 0x7e000000), \
+         which isn't in the module list"
+    );
 }
 
-// Remove this once issue #863 is fixed.
 #[tokio::test]
-async fn test_bit_flip_arm64() {
-    let context = minidump_synth::arm64_context(Endian::Little, 0, 0);
-
+async fn test_stack_memory_not_containing_stack_pointer() {
+    // The thread's stack memory descriptor has the wrong base address.
+    let thread_stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x5000,
+    );
+    let state = read_synth_dump(broken_stack_minidump(thread_stack)).await;
+    let thread = &state.threads[0];
+    assert_eq!(
+        thread.stack_memory_repair,
+        Some(StackMemoryRepair::StackPointerOutsideStack {
+            stack_pointer: 0x9010,
+            stack_base: 0x5000,
+            stack_size: 0x100,
+            region_base: 0x9000,
+            region_size: 0x1000,
+        })
+    );
+    // The stack in the memory list was walked.
+    assert_eq!(thread.frames.len(), 2);
+    assert_eq!(thread.frames[1].trust, FrameTrust::Scan);
+    assert_eq!(thread.frames[1].instruction, 0x0040_00ff);
+
+    let mut human = Vec::new();
+    state.print(&mut human).unwrap();
+    let human = String::from_utf8(human).unwrap();
+    assert!(
+        human.contains("Warning: the stack memory at 0x5000 (0x100 bytes) doesn't contain the stack pointer 0x9010, so the memory region at 0x9000 (0x1000 bytes) was used instead"),
+        "{}",
+        human
+    );
+
+    let report = state.to_json_report();
+    assert_eq!(
+        report.threads[0].stack_memory_repair.as_deref(),
+        Some("stack_pointer_outside_stack")
+    );
+}
+
+#[tokio::test]
+async fn test_empty_stack_memory() {
+    // The thread's stack memory descriptor is empty, but has the right base address.
+    let thread_stack = Memory::with_section(Section::with_endian(Endian::Little), 0x9000);
+    let state = read_synth_dump(broken_stack_minidump(thread_stack)).await;
+    let thread = &state.threads[0];
+    assert_eq!(
+        thread.stack_memory_repair,
+        Some(StackMemoryRepair::EmptyStack {
+            stack_base: 0x9000,
+            region_base: 0x9000,
+            region_size: 0x1000,
+        })
+    );
+    assert_eq!(thread.frames.len(), 2);
+    assert_eq!(thread.frames[1].trust, FrameTrust::Scan);
+
+    let report = state.to_json_report();
+    assert_eq!(
+        report.threads[0].stack_memory_repair.as_deref(),
+        Some("empty_stack")
+    );
+}
+
+#[tokio::test]
+async fn test_stack_memory_not_repaired() {
+    let state = read_synth_dump(minimal_minidump()).await;
+    assert_eq!(state.threads[0].stack_memory_repair, None);
+}
+
+#[tokio::test]
+async fn test_last_known_registers_x86() {
+    let context = minidump_synth::x86_context_with_registers(
+        Endian::Little,
+        &[
+            ("eip", 0x0040_0050),
+            ("esp", 0x1010),
+            ("cs", 0x23),
+            ("ds", 0x2b),
+            ("fs", 0x5_0053),
+        ],
+    );
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    let state = read_synth_dump(dump).await;
+    let report = state.to_json_report();
+
+    let thread = &report.threads[0];
+    assert_eq!(thread.context_error, None);
+    let registers = thread.last_known_registers.as_ref().unwrap();
+    assert_eq!(registers["eip"], "0x00400050");
+    assert_eq!(registers["esp"], "0x00001010");
+    // Segment registers are 16 bits, whatever's in the rest of their 32.
+    assert_eq!(registers["cs"], "0x0023");
+    assert_eq!(registers["ds"], "0x002b");
+    assert_eq!(registers["fs"], "0x0053");
+    assert_eq!(registers["ss"], "0x0000");
+    assert_eq!(registers.len(), 16);
+    // The stack is all zeroes, so the walk stops at the first frame.
+    assert_eq!(thread.last_frame_registers, None);
+}
+
+#[tokio::test]
+async fn test_last_frame_registers() {
+    let context = minidump_synth::x86_context(Endian::Little, 0x0040_0050, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .append_repeated(0, 0x10)
+            .D32(0x0040_0100)
+            .append_repeated(0, 0xfec),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    let state = read_synth_dump(add_modules(dump, &[("app", 0x0040_0000)])).await;
+    let report = state.to_json_report();
+
+    let thread = &report.threads[0];
+    assert_eq!(thread.frame_count, 2);
+    // Scanning only recovers the instruction and stack pointers.
+    let registers = thread.last_frame_registers.as_ref().unwrap();
+    assert_eq!(registers.len(), 2, "{:?}", registers);
+    assert_eq!(registers["eip"], "0x00400100");
+    assert_eq!(registers["esp"], "0x00001014");
+}
+
+#[tokio::test]
+async fn test_last_known_registers_arm64() {
+    let context = minidump_synth::arm64_context(Endian::Little, 0x7000_1000, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_ARM64 as u16,
+    );
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+    let state = read_synth_dump(dump).await;
+    let report = state.to_json_report();
+
+    let registers = report.threads[0].last_known_registers.as_ref().unwrap();
+    // x0-x28, fp (x29) and lr (x30), then sp and pc.
+    assert_eq!(registers.len(), 33);
+    assert_eq!(registers["x0"], "0x0000000000000000");
+    assert_eq!(registers["fp"], "0x0000000000000000");
+    assert_eq!(registers["sp"], "0x0000000000001010");
+    assert_eq!(registers["pc"], "0x0000000070001000");
+    assert!(!registers.contains_key("x29"));
+}
+
+#[tokio::test]
+async fn test_arm64_emulation() {
+    // An ARM64 Windows process with a native thread and a thread of emulated x64 code.
+    let arm64_context = minidump_synth::arm64_context(Endian::Little, 0x7000_1000, 0x1010);
+    let amd64_context = minidump_synth::amd64_context(Endian::Little, 0x7100_1000, 0x2010);
+    let arm64_stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x1000,
+    );
+    let amd64_stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x2000,
+    );
+    let system_info = SystemInfo::new(Endian::Little)
+        .set_processor_architecture(
+            minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_ARM64 as u16,
+        )
+        .set_platform_id(minidump_common::format::PlatformId::VER_PLATFORM_WIN32_NT as u32);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(Thread::new(Endian::Little, 1, &arm64_stack, &arm64_context))
+        .add_thread(Thread::new(Endian::Little, 2, &amd64_stack, &amd64_context))
+        .add_system_info(system_info)
+        .add(arm64_context)
+        .add(amd64_context)
+        .add_memory(arm64_stack)
+        .add_memory(amd64_stack);
+    let state = read_synth_dump(dump).await;
+
+    assert_eq!(state.system_info.cpu, Cpu::Arm64);
+    assert_eq!(state.system_info.cpu_emulation, Some(CpuEmulation::Arm64Ec));
+    let frame = &state.threads[1].frames[0];
+    assert_eq!(frame.context.raw.cpu(), Cpu::X86_64);
+    assert_eq!(frame.instruction, 0x7100_1000);
+    assert_eq!(frame.context.get_stack_pointer(), 0x2010);
+
+    let report = state.to_json_report();
+    assert_eq!(report.system_info.cpu_emulation.as_deref(), Some("arm64ec"));
+    let cpu_arches = report
+        .threads
+        .iter()
+        .map(|thread| thread.frames[0].cpu_arch.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(cpu_arches, [Some("arm64"), Some("amd64")]);
+    let registers = report.threads[1].last_known_registers.as_ref().unwrap();
+    assert_eq!(registers["rip"], "0x0000000071001000");
+    assert_eq!(registers["rsp"], "0x0000000000002010");
+}
+
+#[tokio::test]
+async fn test_last_known_registers_context_error() {
+    // An amd64 context in an x86 dump can't be read.
+    let context = minidump_synth::amd64_context(Endian::Little, 0x0040_0050, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    let state = read_synth_dump(dump).await;
+    let report = state.to_json_report();
+
+    let thread = &report.threads[0];
+    assert_eq!(thread.last_known_registers, None);
+    assert_eq!(
+        thread.context_error.as_deref(),
+        Some("failed to read the context")
+    );
+
+    let mut output = Vec::new();
+    let mut options = PrintOptions::default();
+    options.thread_registers = true;
+    state.print_with_options(&mut output, &options).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Thread registers: unavailable (failed to read the context)"));
+}
+
+#[tokio::test]
+async fn test_print_thread_registers() {
+    let state = read_synth_dump(minimal_minidump()).await;
+    let print = |options: &PrintOptions| {
+        let mut output = Vec::new();
+        state.print_with_options(&mut output, options).unwrap();
+        String::from_utf8(output).unwrap()
+    };
+
+    let output = print(&PrintOptions::default());
+    assert!(!output.contains("Thread registers:"));
+
+    let mut options = PrintOptions::default();
+    options.thread_registers = true;
+    let output = print(&options);
+    assert!(output.contains("Thread registers:\n     eip = 0xabcd1234"));
+}
+
+/// A Windows dump of two threads, with the given exception and crashpad info.
+async fn dump_kind_state(
+    ex: Option<Exception>,
+    crashpad_info: Option<CrashpadInfo>,
+) -> ProcessState {
+    let context1 = minidump_synth::x86_context(Endian::Little, 0x1000, 0x8000);
+    let context2 = minidump_synth::x86_context(Endian::Little, 0x2000, 0x9000);
+    let stack1 = Memory::with_section(Section::with_endian(Endian::Little).D32(0), 0x8000);
+    let stack2 = Memory::with_section(Section::with_endian(Endian::Little).D32(0), 0x9000);
+    let thread1 = Thread::new(Endian::Little, 1, &stack1, &context1);
+    let thread2 = Thread::new(Endian::Little, 2, &stack2, &context2);
+
+    let mut dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread1)
+        .add_thread(thread2)
+        .add_system_info(
+            SystemInfo::new(Endian::Little)
+                .set_platform_id(minidump_common::format::PlatformId::VER_PLATFORM_WIN32_NT as u32),
+        )
+        .add(context1)
+        .add(context2)
+        .add_memory(stack1)
+        .add_memory(stack2);
+    if let Some(ex) = ex {
+        dump = dump.add_exception(ex);
+    }
+    if let Some(crashpad_info) = crashpad_info {
+        dump = dump.add_crashpad_info(crashpad_info);
+    }
+    read_synth_dump(dump).await
+}
+
+#[tokio::test]
+async fn test_dump_kind_requested() {
+    let state = dump_kind_state(None, None).await;
+    assert_eq!(state.dump_kind, DumpKind::Requested);
+    assert!(!state.crashed());
+    assert_eq!(state.requesting_thread, None);
+    // Every thread is still walked
+    for thread in &state.threads {
+        assert_eq!(thread.info, CallStackInfo::Ok);
+        assert!(!thread.frames.is_empty());
+    }
+
+    let mut human = Vec::new();
+    state.print(&mut human).unwrap();
+    let human = String::from_utf8(human).unwrap();
+    assert!(human.contains("No crash — dump requested"));
+    assert!(human.contains("Thread 0 "));
+    assert!(human.contains("Thread 1 "));
+
+    // Brief output has no crashing thread to single out either
+    let mut brief = Vec::new();
+    state.print_brief(&mut brief).unwrap();
+    let brief = String::from_utf8(brief).unwrap();
+    assert!(brief.contains("Thread 0 "));
+    assert!(brief.contains("Thread 1 "));
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["crash_info"]["dump_kind"], "requested");
+    assert_eq!(json["crash_info"]["type"], serde_json::Value::Null);
+    assert_eq!(
+        json["crash_info"]["crashing_thread"],
+        serde_json::Value::Null
+    );
+    assert_eq!(json["crashing_thread"], serde_json::Value::Null);
+    assert_eq!(json["threads"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_dump_kind_hang() {
+    let crashpad_info = CrashpadInfo::new(Endian::Little).add_simple_annotation("hang", "1");
+    let state = dump_kind_state(None, Some(crashpad_info)).await;
+    assert_eq!(state.dump_kind, DumpKind::Hang);
+
+    let mut human = Vec::new();
+    state.print(&mut human).unwrap();
+    let human = String::from_utf8(human).unwrap();
+    assert!(human.contains("No crash — the process was hung"));
+
+    // Other annotations don't make the dump a hang
+    let crashpad_info = CrashpadInfo::new(Endian::Little).add_simple_annotation("hang", "0");
+    let state = dump_kind_state(None, Some(crashpad_info)).await;
+    assert_eq!(state.dump_kind, DumpKind::Requested);
+}
+
+#[tokio::test]
+async fn test_crashpad_info() {
+    let module = ModuleCrashpadInfo::new(0, Endian::Little)
+        .add_list_annotation("listed")
+        .add_simple_annotation("simple", "module")
+        .add_annotation_object("crash_key", AnnotationValue::String("value".to_owned()))
+        .add_annotation_object("custom", AnnotationValue::Custom(0x8001, vec![42]));
+    let crashpad_info = CrashpadInfo::new(Endian::Little)
+        .add_simple_annotation("prod", "Chrome")
+        .add_simple_annotation("channel", "beta")
+        .add_module(module);
+    let state = dump_kind_state(None, Some(crashpad_info)).await;
+
+    let info = state.crashpad_info.as_ref().unwrap();
+    assert_eq!(info.simple_annotations["prod"], "Chrome");
+    assert_eq!(info.module_list[0].list_annotations, ["listed"]);
+
+    let report = state.to_json_report();
+    let info = report.crashpad_info.unwrap();
+    assert_eq!(info.simple_annotations["channel"], "beta");
+    let module = &info.modules[0];
+    assert_eq!(module.module_index, 0);
+    assert_eq!(module.simple_annotations["simple"], "module");
+    // Only the string annotation objects are kept.
+    assert_eq!(
+        module.annotation_objects.iter().collect::<Vec<_>>(),
+        [(&String::from("crash_key"), &String::from("value"))]
+    );
+
+    let (_, human) = json_and_human(&state);
+    assert!(
+        human.contains(
+            "Crashpad annotations:\n  channel=beta\n  prod=Chrome\n  module 0: listed\n  \
+             module 0: simple=module\n  module 0: crash_key=value\n"
+        ),
+        "{}",
+        human
+    );
+
+    // No header without any annotations to list
+    let module = ModuleCrashpadInfo::new(0, Endian::Little)
+        .add_annotation_object("custom", AnnotationValue::Custom(0x8001, vec![42]));
+    let crashpad_info = CrashpadInfo::new(Endian::Little).add_module(module);
+    let state = dump_kind_state(None, Some(crashpad_info)).await;
+    assert!(state.crashpad_info.is_some());
+    let (_, human) = json_and_human(&state);
+    assert!(!human.contains("Crashpad annotations:"), "{}", human);
+
+    let state = dump_kind_state(None, None).await;
+    assert!(state.to_json_report().crashpad_info.is_none());
+}
+
+#[tokio::test]
+async fn test_dump_kind_exception() {
+    let ex = Exception::with_code(
+        Endian::Little,
+        minidump_common::errors::ExceptionCodeWindows::SIMULATED as u32,
+        0,
+        1,
+    );
+    let state = dump_kind_state(Some(ex), None).await;
+    assert_eq!(state.dump_kind, DumpKind::Requested);
+    assert!(!state.crashed());
+    assert_eq!(state.requesting_thread, Some(0));
+
+    let ex = Exception::with_code(
+        Endian::Little,
+        minidump_common::errors::ExceptionCodeWindows::EXCEPTION_POSSIBLE_DEADLOCK as u32,
+        0,
+        1,
+    );
+    let state = dump_kind_state(Some(ex), None).await;
+    assert_eq!(state.dump_kind, DumpKind::Hang);
+
+    let ex = Exception::with_code(
+        Endian::Little,
+        minidump_common::errors::ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION as u32,
+        0,
+        1,
+    );
+    let state = dump_kind_state(Some(ex), None).await;
+    assert_eq!(state.dump_kind, DumpKind::Crash);
+    assert!(state.crashed());
+}
+
+/// Three threads of app.exe: two waiting at different places in
+/// `WaitForSingleObjectEx`, and one in `main`.
+async fn thread_groups_state(options: ProcessorOptions<'_>) -> ProcessState {
+    let module_name = DumpString::new("app.exe", Endian::Little);
+    let module = minidump_synth::Module::new(
+        Endian::Little,
+        0x7000_0000,
+        0x10000,
+        &module_name,
+        0,
+        0,
+        None,
+    );
+    let mut dump = SynthMinidump::with_endian(Endian::Little)
+        .add_module(module)
+        .add(module_name)
+        .add_system_info(SystemInfo::new(Endian::Little));
+    for (i, eip) in [0x7000_1010, 0x7000_2010, 0x7000_1020].iter().enumerate() {
+        let stack_address = 0x1_0000 * (i as u64 + 1);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+            stack_address,
+        );
+        let context = minidump_synth::x86_context(Endian::Little, *eip, stack_address as u32);
+        let thread = Thread::new(Endian::Little, i as u32 + 1, &stack, &context);
+        dump = dump.add_thread(thread).add(context).add_memory(stack);
+    }
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+
+    let symbols = HashMap::from([(
+        String::from("app.exe"),
+        String::from(
+            "FUNC 1000 100 0 WaitForSingleObjectEx\n\
+             FUNC 2000 100 0 main\n",
+        ),
+    )]);
+    let symbolizer = Symbolizer::new(string_symbol_supplier(symbols));
+    minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_thread_groups() {
+    let state = thread_groups_state(ProcessorOptions::default()).await;
+    assert_eq!(
+        state.thread_groups,
+        [
+            ThreadGroup {
+                threads: vec![0, 2],
+                frames: vec![String::from("app.exe!WaitForSingleObjectEx")],
+                blocked_on: Some(BlockedOn {
+                    kind: WaitKind::Object,
+                    function: String::from("WaitForSingleObjectEx"),
+                }),
+            },
+            ThreadGroup {
+                threads: vec![1],
+                frames: vec![String::from("app.exe!main")],
+                blocked_on: None,
+            },
+        ]
+    );
+
+    let mut human = Vec::new();
+    state.print(&mut human).unwrap();
+    let human = String::from_utf8(human).unwrap();
+    assert!(human.contains(
+        "Thread groups:\n  \
+         2 threads (blocked: object in WaitForSingleObjectEx): 0, 2\n     \
+         0  app.exe!WaitForSingleObjectEx\n  \
+         1 thread: 1\n     \
+         0  app.exe!main\n"
+    ));
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    let group = &json["thread_groups"][0];
+    assert_eq!(group["thread_count"], 2);
+    assert_eq!(group["thread_ids"], serde_json::json!([1, 3]));
+    assert_eq!(group["threads"], serde_json::json!([0, 2]));
+    assert_eq!(group["blocked_on"]["kind"], "object");
+    // The threads themselves are unchanged
+    assert_eq!(json["threads"].as_array().unwrap().len(), 3);
+
+    let mut options = ProcessorOptions::default();
+    options.group_threads = false;
+    let state = thread_groups_state(options).await;
+    assert!(state.thread_groups.is_empty());
+}
+
+/// A [`SymbolProvider`] without any symbols, that records which modules it was asked about.
+#[derive(Default)]
+struct CountingSymbolProvider {
+    filled_modules: Mutex<BTreeSet<String>>,
+    walked_modules: Mutex<BTreeSet<String>>,
+}
+
+#[async_trait]
+impl SymbolProvider for CountingSymbolProvider {
+    async fn fill_symbol(
+        &self,
+        module: &(dyn Module + Sync),
+        _frame: &mut (dyn FrameSymbolizer + Send),
+    ) -> Result<(), FillSymbolError> {
+        let name = module.code_file().into_owned();
+        self.filled_modules.lock().unwrap().insert(name);
+        Err(FillSymbolError {})
+    }
+
+    async fn walk_frame(
+        &self,
+        module: &(dyn Module + Sync),
+        _walker: &mut (dyn FrameWalker + Send),
+    ) -> Option<()> {
+        let name = module.code_file().into_owned();
+        self.walked_modules.lock().unwrap().insert(name);
+        None
+    }
+
+    async fn get_file_path(
+        &self,
+        _module: &(dyn Module + Sync),
+        _file_kind: FileKind,
+    ) -> Result<PathBuf, FileError> {
+        Err(FileError::NotFound)
+    }
+}
+
+#[tokio::test]
+async fn test_lazy_symbol_lookup() {
+    async fn filled_modules(options: ProcessorOptions<'_>) -> (Vec<String>, Vec<String>) {
+        // The only frame that can be walked is in a.dll, the stack is all zeroes
+        let context = minidump_synth::x86_context(Endian::Little, 0x7000_1000, 0x1010);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000,
+        );
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let mut dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add_system_info(SystemInfo::new(Endian::Little))
+            .add(context)
+            .add_memory(stack);
+        for (i, name) in ["a.dll", "b.dll", "plugin_c.dll"].iter().enumerate() {
+            let name = DumpString::new(name, Endian::Little);
+            let base = 0x7000_0000 + 0x100_0000 * i as u64;
+            let module =
+                minidump_synth::Module::new(Endian::Little, base, 0x10000, &name, 0, 0, None);
+            dump = dump.add_module(module).add(name);
+        }
+
+        let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+        let provider = CountingSymbolProvider::default();
+        minidump_processor::process_minidump_with_options(&dump, &provider, options)
+            .await
+            .unwrap();
+        let filled = provider.filled_modules.into_inner().unwrap();
+        let walked = provider.walked_modules.into_inner().unwrap();
+        (filled.into_iter().collect(), walked.into_iter().collect())
+    }
+
+    let (filled, walked) = filled_modules(ProcessorOptions::default()).await;
+    assert_eq!(filled, ["a.dll"]);
+    assert_eq!(walked, ["a.dll"]);
+
+    let mut options = ProcessorOptions::default();
+    options.symbolize_all_modules = true;
+    let (filled, walked) = filled_modules(options.clone()).await;
+    assert_eq!(filled, ["a.dll", "b.dll", "plugin_c.dll"]);
+    assert_eq!(walked, ["a.dll"]);
+
+    options.module_filter.deny = vec![String::from("plugin_*")];
+    let (filled, _) = filled_modules(options).await;
+    assert_eq!(filled, ["a.dll", "b.dll"]);
+
+    let mut options = ProcessorOptions::default();
+    options.module_filter = ModuleFilter {
+        allow: vec![String::from("*.exe")],
+        deny: vec![],
+    };
+    let (filled, walked) = filled_modules(options).await;
+    assert!(filled.is_empty());
+    assert!(walked.is_empty());
+}
+
+/// A dump of one thread in app.exe, processed with a [`MultiSymbolProvider`] of
+/// string symbols named "first" and "second".
+async fn multi_provider_state(first: &[(&str, &str)], second: &[(&str, &str)]) -> ProcessState {
+    let context = minidump_synth::x86_context(Endian::Little, 0x7000_1010, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let module_name = DumpString::new("app.exe", Endian::Little);
+    let module = minidump_synth::Module::new(
+        Endian::Little,
+        0x7000_0000,
+        0x10000,
+        &module_name,
+        0,
+        0,
+        None,
+    );
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_module(module)
+        .add(module_name)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+
+    let supplier = |symbols: &[(&str, &str)]| {
+        string_symbol_supplier(
+            symbols
+                .iter()
+                .map(|&(module, symbols)| (module.to_owned(), symbols.to_owned()))
+                .collect(),
+        )
+    };
+    let mut provider = MultiSymbolProvider::new();
+    provider.add(Box::new(
+        Symbolizer::new(supplier(first)).with_name("first"),
+    ));
+    provider.add(Box::new(
+        Symbolizer::new(supplier(second)).with_name("second"),
+    ));
+    minidump_processor::process_minidump(&dump, &provider)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_symbol_provider_attribution() {
+    let state = multi_provider_state(
+        &[("other.dll", "FUNC 1000 100 0 other\n")],
+        &[("app.exe", "FUNC 1000 100 0 from_second\n")],
+    )
+    .await;
+    let frame = &state.threads[0].frames[0];
+    assert_eq!(frame.function_name.as_deref(), Some("from_second"));
+    let stats = &state.symbol_stats["app.exe"];
+    assert!(stats.loaded_symbols);
+    assert_eq!(stats.provider.as_deref(), Some("second"));
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["modules"][0]["symbols_from"], "second");
+
+    // If both have symbols, the first one serves the module
+    let state = multi_provider_state(
+        &[("app.exe", "FUNC 1000 100 0 from_first\n")],
+        &[("app.exe", "FUNC 1000 100 0 from_second\n")],
+    )
+    .await;
+    let frame = &state.threads[0].frames[0];
+    assert_eq!(frame.function_name.as_deref(), Some("from_first"));
+    let stats = &state.symbol_stats["app.exe"];
+    assert_eq!(stats.provider.as_deref(), Some("first"));
+
+    // And without symbols, nobody does
+    let state = multi_provider_state(&[], &[]).await;
+    let stats = &state.symbol_stats["app.exe"];
+    assert!(!stats.loaded_symbols);
+    assert_eq!(stats.provider, None);
+}
+
+#[tokio::test]
+async fn test_source_context() {
+    let source_root = std::env::temp_dir().join(format!(
+        "minidump-processor-source-context-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(source_root.join("src")).unwrap();
+    std::fs::write(
+        source_root.join("src/main.c"),
+        "int main() {\r\n  int *p = 0;\r\n  return *p;\r\n}\r\n",
+    )
+    .unwrap();
+
+    let context = minidump_synth::x86_context(Endian::Little, 0x7000_1010, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let module_name = DumpString::new("app.exe", Endian::Little);
+    let module = minidump_synth::Module::new(
+        Endian::Little,
+        0x7000_0000,
+        0x10000,
+        &module_name,
+        0,
+        0,
+        None,
+    );
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_module(module)
+        .add(module_name)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+
+    // Symbol files from Windows use backslashes, whatever the processing host is
+    let symbols = HashMap::from([(
+        String::from("app.exe"),
+        String::from(
+            "FILE 0 c:\\builds\\app\\src\\main.c\n\
+             FUNC 1000 100 0 main\n\
+             1000 10 1 0\n\
+             1010 10 3 0\n",
+        ),
+    )]);
+    let symbolizer = Symbolizer::new(string_symbol_supplier(symbols));
+    let mut options = ProcessorOptions::default();
+    options.source_context = Some(SourceContextOptions {
+        roots: vec![source_root.clone()],
+        ..Default::default()
+    });
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    std::fs::remove_dir_all(&source_root).unwrap();
+
+    let frame = &state.threads[0].frames[0];
+    assert_eq!(frame.source_line, Some(3));
+    let source = frame.source_context.as_ref().unwrap();
+    assert_eq!(source.first_line, 2);
+    assert_eq!(source.lines, ["  int *p = 0;", "  return *p;", "}"]);
+
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(
+        human.contains("         2 |   int *p = 0;\n    >     3 |   return *p;\n          4 | }\n")
+    );
+
+    let mut json_output = Vec::new();
+    state.print_json(&mut json_output, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json_output).unwrap();
+    let json_source = &json["threads"][0]["frames"][0]["source_context"];
+    assert_eq!(json_source["first_line"], 2);
+    assert_eq!(json_source["lines"][1], "  return *p;");
+    // Local paths aren't linked to
+    assert!(frame.source_file_vcs.is_none());
+    assert!(json["threads"][0]["frames"][0]["source_link"].is_null());
+}
+
+#[tokio::test]
+async fn test_source_link() {
+    use minidump_processor::source_link::{SourceLinkOptions, SourceLinkTemplate};
+    use minidump_unwind::Vcs;
+
+    let context = minidump_synth::x86_context(Endian::Little, 0x7000_1010, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let module_name = DumpString::new("xul.dll", Endian::Little);
+    let module = minidump_synth::Module::new(
+        Endian::Little,
+        0x7000_0000,
+        0x10000,
+        &module_name,
+        0,
+        0,
+        None,
+    );
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_module(module)
+        .add(module_name)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let symbols = HashMap::from([(
+        String::from("xul.dll"),
+        String::from(
+            "FILE 0 hg:hg.mozilla.org/mozilla-central:dom/base/Element.cpp:1a2b3c4d5e6f\n\
+             FUNC 1000 100 0 mozilla::dom::Element::GetAttr\n\
+             1000 10 41 0\n\
+             1010 10 42 0\n",
+        ),
+    )]);
+    let symbolizer = Symbolizer::new(string_symbol_supplier(symbols));
+
+    let state = minidump_processor::process_minidump(&dump, &symbolizer)
+        .await
+        .unwrap();
+    let frame = &state.threads[0].frames[0];
+    // The file name is left as it is
+    assert_eq!(
+        frame.source_file_name.as_deref(),
+        Some("hg:hg.mozilla.org/mozilla-central:dom/base/Element.cpp:1a2b3c4d5e6f")
+    );
+    let vcs = frame.source_file_vcs.as_ref().unwrap();
+    assert_eq!(vcs.vcs, Vcs::Hg);
+    assert_eq!(vcs.repo, "hg.mozilla.org/mozilla-central");
+    assert_eq!(vcs.path, "dom/base/Element.cpp");
+    assert_eq!(vcs.revision, "1a2b3c4d5e6f");
+    let report = state.to_json_report();
+    assert_eq!(
+        report.threads[0].frames[0].source_link.as_deref(),
+        Some("https://hg.mozilla.org/mozilla-central/file/1a2b3c4d5e6f/dom/base/Element.cpp#l42")
+    );
+
+    let mut options = ProcessorOptions::default();
+    options.source_link_options = SourceLinkOptions {
+        templates: vec![SourceLinkTemplate {
+            vcs: Vcs::Hg,
+            host: Some(String::from("hg.mozilla.org")),
+            template: String::from(
+                "https://searchfox.org/{repo_path}/rev/{revision}/{path}#{line}",
+            ),
+        }],
+    };
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    let report = state.to_json_report();
+    assert_eq!(
+        report.threads[0].frames[0].source_link.as_deref(),
+        Some("https://searchfox.org/mozilla-central/rev/1a2b3c4d5e6f/dom/base/Element.cpp#42")
+    );
+}
+
+/// Tags frames in kernel32.dll, and counts the frames of each thread.
+struct TestAnnotator;
+
+impl FrameAnnotator for TestAnnotator {
+    fn annotate_frame(&self, frame: &mut StackFrame, module: Option<&dyn Module>) {
+        if module.is_some_and(|module| module.code_file().ends_with("kernel32.dll")) {
+            frame
+                .annotations
+                .insert(String::from("owner"), String::from("platform"));
+        }
+    }
+
+    fn annotate_thread(&self, thread: &mut CallStack) {
+        let frame_count = thread.frames.len().to_string();
+        if let Some(frame) = thread.frames.first_mut() {
+            frame
+                .annotations
+                .insert(String::from("frame_count"), frame_count);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_frame_annotator() {
+    use minidump_processor::report::Report;
+
+    let dump = read_test_minidump().unwrap();
+    let annotator = TestAnnotator;
+    let mut options = ProcessorOptions::default();
+    options.frame_annotator = Some(&annotator);
+    let state = minidump_processor::process_minidump_with_options(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+        options,
+    )
+    .await
+    .unwrap();
+
+    let frames = &state.threads[0].frames;
+    assert_eq!(
+        frames[0].annotations,
+        BTreeMap::from([(String::from("frame_count"), String::from("4"))])
+    );
+    assert!(frames[1].annotations.is_empty());
+    assert_eq!(
+        frames[3].annotations,
+        BTreeMap::from([(String::from("owner"), String::from("platform"))])
+    );
+
+    // The annotations survive into the JSON output
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let report: Report = serde_json::from_slice(&json).unwrap();
+    let frames = &report.threads[0].frames;
+    assert_eq!(
+        frames[3].annotations,
+        Some(BTreeMap::from([(
+            String::from("owner"),
+            String::from("platform")
+        )]))
+    );
+    assert_eq!(frames[1].annotations, None);
+    let crashing_frames = &report.crashing_thread.unwrap().thread.frames;
+    assert_eq!(crashing_frames[3].annotations, frames[3].annotations);
+
+    // And into the human output
+    let mut human = Vec::new();
+    state.print(&mut human).unwrap();
+    let human = String::from_utf8(human).unwrap();
+    assert!(human.contains("    Annotation: owner = platform\n"));
+}
+
+#[tokio::test]
+async fn test_thread_info() {
+    let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    let thread1 = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let thread2 = Thread::new(Endian::Little, 0x5678, &stack, &context);
+    let thread3 = Thread::new(Endian::Little, 0x9abc, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little);
+
+    let module_name = DumpString::new("ntdll.dll", Endian::Little);
+    let module = minidump_synth::Module::new(
+        Endian::Little,
+        0x7000_0000,
+        0x10000,
+        &module_name,
+        0,
+        0,
+        None,
+    );
+
+    // Starts inside a function we have symbols for.
+    let mut info1 = ThreadInfo::new(Endian::Little, 0x1234);
+    info1.start_address = 0x7000_1010;
+    info1.create_time = 0x01d7_0000_0000_0000;
+    info1.kernel_time = 1000;
+    info1.user_time = 2000;
+    // Starts outside of any module.
+    let mut info2 = ThreadInfo::new(Endian::Little, 0x5678);
+    info2.start_address = 0x1234_5678;
+    // (the third thread has no thread info at all)
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread1)
+        .add_thread(thread2)
+        .add_thread(thread3)
+        .add_thread_info(info1)
+        .add_thread_info(info2)
+        .add_module(module)
+        .add(module_name)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+
+    let mut symbols = HashMap::new();
+    symbols.insert(
+        String::from("ntdll.dll"),
+        String::from("FUNC 1000 100 0 TppWorkerThread\n"),
+    );
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(string_symbol_supplier(symbols)),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(state.threads.len(), 3);
+
+    let thread = &state.threads[0];
+    assert_eq!(thread.start_address, Some(0x7000_1010));
+    assert_eq!(
+        thread.start_function.as_deref(),
+        Some("ntdll!TppWorkerThread")
+    );
+    assert_eq!(
+        thread.times,
+        Some(ThreadTimes {
+            create_time: 0x01d7_0000_0000_0000,
+            exit_time: 0,
+            kernel_time: 1000,
+            user_time: 2000,
+        })
+    );
+
+    let thread = &state.threads[1];
+    assert_eq!(thread.start_address, Some(0x1234_5678));
+    assert_eq!(thread.start_function, None);
+    assert!(thread.times.is_some());
+
+    let thread = &state.threads[2];
+    assert_eq!(thread.start_address, None);
+    assert_eq!(thread.start_function, None);
+    assert_eq!(thread.times, None);
+
+    let mut human = Vec::new();
+    state.print(&mut human).unwrap();
+    let human = String::from_utf8(human).unwrap();
+    assert!(human.contains("Thread 0  (started at ntdll!TppWorkerThread) - tid: 4660"));
+    assert!(human.contains("Thread 1  (started at 0x12345678) - tid: 22136"));
+    assert!(human.contains("Thread 2  - tid: 39612"));
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    let thread = &json["threads"][0];
+    assert_eq!(thread["thread_start_address"], "0x70001010");
+    assert_eq!(thread["thread_start_function"], "ntdll!TppWorkerThread");
+    assert_eq!(thread["thread_times"]["kernel_time"], 1000);
+    assert_eq!(thread["thread_times"]["user_time"], 2000);
+    assert!(json["threads"][2]["thread_start_address"].is_null());
+}
+
+async fn read_synth_dump_with_symbols(
+    dump: SynthMinidump,
+    symbols: HashMap<String, String>,
+) -> ProcessState {
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    minidump_processor::process_minidump(&dump, &Symbolizer::new(string_symbol_supplier(symbols)))
+        .await
+        .unwrap()
+}
+
+/// A dump whose crashing thread is executing `function` (at 0x7000_1010 in
+/// "libtest.so"), with `stack` as its stack memory at 0x80000.
+fn crash_message_minidump(
+    function: &str,
+    stack: Section,
+    amd64: bool,
+) -> (SynthMinidump, HashMap<String, String>) {
+    let context = if amd64 {
+        minidump_synth::amd64_context(Endian::Little, 0x7000_1010, 0x80000)
+    } else {
+        minidump_synth::x86_context(Endian::Little, 0x7000_1010, 0x80000)
+    };
+    let stack = Memory::with_section(stack, 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let arch = if amd64 {
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64
+    } else {
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL
+    };
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch as u16);
+
+    let module_name = DumpString::new("libtest.so", Endian::Little);
+    let module = minidump_synth::Module::new(
+        Endian::Little,
+        0x7000_0000,
+        0x10000,
+        &module_name,
+        0,
+        0,
+        None,
+    );
+
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 1;
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_module(module)
+        .add(module_name)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+
+    let mut symbols = HashMap::new();
+    symbols.insert(
+        String::from("libtest.so"),
+        format!("FUNC 1000 100 0 {function}\n"),
+    );
+    (dump, symbols)
+}
+
+#[tokio::test]
+async fn test_rust_panic_message() {
+    // A `fmt::Arguments` with the pieces "hello " and "!", and the pieces' text.
+    let heap = Section::with_endian(Endian::Little)
+        // fmt::Arguments @ 0x5000
+        .D64(0x5100)
+        .D64(2)
+        .append_repeated(0, 0x100 - 16)
+        // pieces @ 0x5100
+        .D64(0x5200)
+        .D64(6)
+        .D64(0x5206)
+        .D64(1)
+        .append_repeated(0, 0x100 - 32)
+        // text @ 0x5200
+        .append_bytes(b"hello !");
+    let heap = Memory::with_section(heap, 0x5000);
+
+    let stack = Section::with_endian(Endian::Little)
+        .D64(0x1234)
+        .D64(0x5000)
+        .append_repeated(0, 0x100);
+    let (dump, symbols) = crash_message_minidump("core::panicking::panic_fmt", stack, true);
+    let state = read_synth_dump_with_symbols(dump.add_memory(heap), symbols).await;
+    assert_eq!(
+        state.exception_info.unwrap().message.as_deref(),
+        Some("hello {}!")
+    );
+}
+
+#[tokio::test]
+async fn test_rust_panic_str_message() {
+    let text = Memory::with_section(
+        Section::with_endian(Endian::Little).append_bytes(b"explicit panic"),
+        0x5000,
+    );
+    // A `&str` passed on the stack.
+    let stack = Section::with_endian(Endian::Little)
+        .D64(0x5000)
+        .D64(14)
+        .append_repeated(0, 0x100);
+    let (dump, symbols) = crash_message_minidump("std::panicking::begin_panic<&str>", stack, true);
+    let state = read_synth_dump_with_symbols(dump.add_memory(text), symbols).await;
+    assert_eq!(
+        state.exception_info.unwrap().message.as_deref(),
+        Some("explicit panic")
+    );
+}
+
+#[tokio::test]
+async fn test_assertion_message() {
+    let strings = Section::with_endian(Endian::Little)
+        .append_bytes(b"test.c\0")
+        .append_bytes(b"test: test.c:5: main: Assertion `x == 1' failed.\0");
+    let strings = Memory::with_section(strings, 0x5000);
+    let stack = Section::with_endian(Endian::Little)
+        .D32(0x5000)
+        .D32(0x5007)
+        .append_repeated(0, 0x100);
+    let (dump, symbols) = crash_message_minidump("__assert_fail", stack, false);
+    let state = read_synth_dump_with_symbols(dump.add_memory(strings), symbols).await;
+    assert_eq!(
+        state.exception_info.unwrap().message.as_deref(),
+        Some("test: test.c:5: main: Assertion `x == 1' failed.")
+    );
+}
+
+#[tokio::test]
+async fn test_assertion_info() {
+    let path = locate_testdata().join("invalid-parameter.dmp");
+    let dump = Minidump::read_path(&path).unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+    )
+    .await
+    .unwrap();
+    let expected = r"Invalid parameter passed to library function format != nullptr in function common_vfprintf, in file minkernel\crts\ucrt\src\appcrt\stdio\output.cpp at line 32";
+    assert_eq!(state.assertion.as_deref(), Some(expected));
+    assert_eq!(
+        state.to_json_report().crash_info.assertion.as_deref(),
+        Some(expected)
+    );
+
+    let mut human = Vec::new();
+    state.print(&mut human).unwrap();
+    let human = String::from_utf8(human).unwrap();
+    assert!(
+        human.contains(&format!("Assertion: {expected}\n")),
+        "{}",
+        human
+    );
+}
+
+#[tokio::test]
+async fn test_crash_message_truncated() {
+    let long_message = "x".repeat(5000);
+    let text = Memory::with_section(
+        Section::with_endian(Endian::Little).append_bytes(long_message.as_bytes()),
+        0x5000,
+    );
+    let stack = Section::with_endian(Endian::Little)
+        .D64(0x5000)
+        .D64(5000)
+        .append_repeated(0, 0x100);
+    let (dump, symbols) = crash_message_minidump("core::panicking::panic", stack, true);
+    let state = read_synth_dump_with_symbols(dump.add_memory(text), symbols).await;
+    assert_eq!(
+        state.exception_info.unwrap().message.unwrap(),
+        long_message[..1024]
+    );
+}
+
+#[tokio::test]
+async fn test_crash_message_missing_memory() {
+    // Points to memory that isn't in the dump.
+    let stack = Section::with_endian(Endian::Little)
+        .D64(0x5000)
+        .D64(14)
+        .append_repeated(0, 0x100);
+    let (dump, symbols) = crash_message_minidump("core::panicking::panic_fmt", stack, true);
+    let state = read_synth_dump_with_symbols(dump, symbols).await;
+    assert_eq!(state.exception_info.unwrap().message, None);
+
+    // Not a panic at all.
+    let text = Memory::with_section(
+        Section::with_endian(Endian::Little).append_bytes(b"explicit panic"),
+        0x5000,
+    );
+    let stack = Section::with_endian(Endian::Little)
+        .D64(0x5000)
+        .D64(14)
+        .append_repeated(0, 0x100);
+    let (dump, symbols) = crash_message_minidump("some_function", stack, true);
+    let state = read_synth_dump_with_symbols(dump.add_memory(text), symbols).await;
+    assert_eq!(state.exception_info.unwrap().message, None);
+}
+
+/// A dump of an amd64 crash at `ip`, with `code` in its memory list and
+/// `memory_info` as its memory info list.
+async fn crash_instruction_state(
+    ip: u64,
+    code: Option<Memory>,
+    memory_info: Option<MemoryInfo>,
+) -> ProcessState {
+    let context = minidump_synth::amd64_context(Endian::Little, ip, 0x80000);
+    let stack = Memory::with_section(Section::with_endian(Endian::Little).D64(0), 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    );
+    let ex = Exception::with_code(Endian::Little, 0xc0000005, ip, 1).context(&context);
+
+    let mut dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+    if let Some(code) = code {
+        dump = dump.add_memory(code);
+    }
+    if let Some(memory_info) = memory_info {
+        dump = dump.add_memory_info(memory_info);
+    }
+    read_synth_dump(dump).await
+}
+
+#[tokio::test]
+async fn test_crash_instruction_bytes() {
+    let code = || {
+        let code = Section::with_endian(Endian::Little)
+            .append_bytes(&[0x55]) // push rbp
+            .append_bytes(&[0x48, 0x89, 0xe5]) // mov rbp, rsp
+            .append_bytes(&[0x48, 0x8b, 0x00]) // mov rax, [rax]
+            .append_repeated(0xcc, 0x20);
+        Memory::with_section(code, 0x7000_1000)
+    };
+    let memory_info = || {
+        MemoryInfo::new(
+            Endian::Little,
+            0x7000_1000,
+            0x7000_1000,
+            0,
+            0x1000,
+            MemoryState::MEM_COMMIT.bits(),
+            MemoryProtection::PAGE_EXECUTE_READ.bits(),
+            MemoryType::MEM_IMAGE.bits(),
+        )
+    };
+
+    let state = crash_instruction_state(0x7000_1004, Some(code()), Some(memory_info())).await;
+    let info = state.exception_info.as_ref().unwrap();
+    let mut expected_bytes = vec![0x48, 0x8b, 0x00];
+    expected_bytes.extend([0xcc; 13]);
+    assert_eq!(
+        info.instruction_bytes,
+        InstructionBytes::Captured(expected_bytes)
+    );
+    #[cfg(feature = "disasm_amd64")]
+    {
+        let disassembly: Vec<_> = info
+            .disassembly
+            .iter()
+            .map(|instruction| (instruction.address, instruction.text.as_str()))
+            .collect();
+        assert_eq!(
+            disassembly,
+            [
+                (0x7000_1000, "push rbp"),
+                (0x7000_1001, "mov rbp, rsp"),
+                (0x7000_1004, "mov rax, qword [rax]"),
+            ]
+        );
+        assert!(info.disassembly[2].crashing);
+
+        let mut human = Vec::new();
+        state.print(&mut human).unwrap();
+        let human = String::from_utf8(human).unwrap();
+        assert!(human.contains(
+            "Crashing instruction bytes: 48 8b 00 cc cc cc cc cc cc cc cc cc cc cc cc cc\n"
+        ));
+        assert!(human.contains(
+            "Disassembly:\n     0x0000000070001000: 55                       push rbp\n     0x0000000070001001: 48 89 e5                 mov rbp, rsp\n  => 0x0000000070001004: 48 8b 00                 mov rax, qword [rax]\n"
+        ));
+    }
+
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(
+        json["crash_info"]["instruction_bytes"],
+        serde_json::json!({
+            "status": "captured",
+            "bytes": "488b00cccccccccccccccccccccccccc",
+        })
+    );
+
+    // Near the end of the memory, the bytes are truncated
+    let state = crash_instruction_state(0x7000_1022, Some(code()), None).await;
+    assert_eq!(
+        state.exception_info.unwrap().instruction_bytes,
+        InstructionBytes::Captured(vec![0xcc; 5])
+    );
+
+    // The code isn't in the dump, and the memory info says it doesn't exist
+    let state = crash_instruction_state(0x6000_0000, None, Some(memory_info())).await;
+    let info = state.exception_info.as_ref().unwrap();
+    assert_eq!(info.instruction_bytes, InstructionBytes::Unmapped);
+    assert!(info.disassembly.is_empty());
+    let mut json = Vec::new();
+    state.print_json(&mut json, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(
+        json["crash_info"]["instruction_bytes"],
+        serde_json::json!({ "status": "unmapped" })
+    );
+    assert_eq!(json["crash_info"]["disassembly"], serde_json::Value::Null);
+
+    // The code isn't in the dump, but it exists
+    let state = crash_instruction_state(0x7000_1004, None, Some(memory_info())).await;
+    assert_eq!(
+        state.exception_info.unwrap().instruction_bytes,
+        InstructionBytes::NotCaptured
+    );
+
+    // No idea whether the code exists
+    let state = crash_instruction_state(0x7000_1004, None, None).await;
+    let info = state.exception_info.as_ref().unwrap();
+    assert_eq!(info.instruction_bytes, InstructionBytes::NotCaptured);
+    let mut human = Vec::new();
+    state.print(&mut human).unwrap();
+    let human = String::from_utf8(human).unwrap();
+    assert!(human.contains("Crashing instruction bytes: (not captured)\n"));
+}
+
+#[tokio::test]
+async fn test_crash_category_rules() {
+    let stack = || Section::with_endian(Endian::Little).append_repeated(0, 0x100);
+    for (pattern, category) in DEFAULT_CATEGORY_RULES {
+        // Fill in wildcards and add an argument list, which is normalized away
+        let function = format!("{}(int)", pattern.replace('*', "Foo"));
+        let (dump, symbols) = crash_message_minidump(&function, stack(), true);
+        let state = read_synth_dump_with_symbols(dump, symbols).await;
+        assert_eq!(state.crash_category.as_ref(), Some(category), "{function}");
+    }
+
+    let (dump, symbols) = crash_message_minidump("malloc", stack(), true);
+    let state = read_synth_dump_with_symbols(dump, symbols).await;
+    assert_eq!(state.crash_category, None);
+}
+
+#[tokio::test]
+async fn test_crash_category_output() {
+    let stack = Section::with_endian(Endian::Little).append_repeated(0, 0x100);
+    let (dump, symbols) = crash_message_minidump("RtlReportCriticalFailure", stack, true);
+    let state = read_synth_dump_with_symbols(dump, symbols).await;
+
+    let mut json_output = Vec::new();
+    state.print_json(&mut json_output, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json_output).unwrap();
+    assert_eq!(json["crash_info"]["category"], "HeapCorruption");
+
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(human.contains("Crash category: HeapCorruption\n"));
+}
+
+#[tokio::test]
+async fn test_crash_category_custom_rule() {
+    let stack = Section::with_endian(Endian::Little).append_repeated(0, 0x100);
+    let (dump, symbols) = crash_message_minidump("gpu::LoseContext", stack, true);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+
+    let mut options = ProcessorOptions::default();
+    options
+        .crash_category_options
+        .rules
+        .push(CrashCategoryRule::new(
+            "gpu::*",
+            CrashCategory::Other(String::from("GpuReset")),
+        ));
+    let state = minidump_processor::process_minidump_with_options(
+        &dump,
+        &Symbolizer::new(string_symbol_supplier(symbols)),
+        options,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        state.crash_category,
+        Some(CrashCategory::Other(String::from("GpuReset")))
+    );
+}
+
+#[tokio::test]
+async fn test_crash_category_exception_code() {
+    let context = minidump_synth::amd64_context(Endian::Little, 0, 0);
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little)
+        .set_processor_architecture(
+            minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+        )
+        .set_platform_id(minidump_common::format::PlatformId::VER_PLATFORM_WIN32_NT as u32);
+    let ex = Exception::with_code(
+        Endian::Little,
+        minidump_common::errors::NtStatusWindows::STATUS_HEAP_CORRUPTION as u32,
+        0,
+        1,
+    );
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+    let state = read_synth_dump(dump).await;
+    assert_eq!(state.crash_category, Some(CrashCategory::HeapCorruption));
+}
+
+#[tokio::test]
+async fn test_referenced_modules() {
+    let context = minidump_synth::amd64_context(Endian::Little, 0x1234, 0x80008);
+    // Only the values above the stack pointer count
+    let stack = Section::with_endian(Endian::Little)
+        .D64(0x7000_0200) // below the stack pointer
+        .D64(0x7000_0500) // code in module1
+        .D64(0x1234) // not in a module
+        .D64(0x7000_2000) // data in module1
+        .D64(0x8000_0010) // module2, which has no memory info
+        .D64(0x8000_0020)
+        .D64(0x7000_0600)
+        .D64(0x8000_0030);
+    let stack = Memory::with_section(stack, 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    );
+
+    let module1_name = DumpString::new("module1.dll", Endian::Little);
+    let module1 = minidump_synth::Module::new(
+        Endian::Little,
+        0x7000_0000,
+        0x10000,
+        &module1_name,
+        0,
+        0,
+        None,
+    );
+    let module2_name = DumpString::new("module2.dll", Endian::Little);
+    let module2 = minidump_synth::Module::new(
+        Endian::Little,
+        0x8000_0000,
+        0x10000,
+        &module2_name,
+        0,
+        0,
+        None,
+    );
+    let code_info = MemoryInfo::new(
+        Endian::Little,
+        0x7000_0000,
+        0x7000_0000,
+        0,
+        0x1000,
+        0,
+        MemoryProtection::PAGE_EXECUTE_READ.bits(),
+        0,
+    );
+    let data_info = MemoryInfo::new(
+        Endian::Little,
+        0x7000_1000,
+        0x7000_0000,
+        0,
+        0xf000,
+        0,
+        MemoryProtection::PAGE_READONLY.bits(),
+        0,
+    );
+
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 1;
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_module(module1)
+        .add_module(module2)
+        .add(module1_name)
+        .add(module2_name)
+        .add_memory_info(code_info)
+        .add_memory_info(data_info)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+    let state = read_synth_dump(dump).await;
+
+    let referenced: Vec<(String, usize)> = state
+        .referenced_modules
+        .iter()
+        .map(|referenced| (referenced.module.code_file().into_owned(), referenced.count))
+        .collect();
+    assert_eq!(
+        referenced,
+        [
+            (String::from("module2.dll"), 3),
+            (String::from("module1.dll"), 2)
+        ]
+    );
+
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(human.contains(
+        "Modules referenced by the stack of thread 0 (heuristic, not frames):\n  module2.dll (3 references)\n  module1.dll (2 references)\n"
+    ));
+}
+
+#[test]
+fn test_referenced_modules_index() {
+    use minidump::system_info::PointerWidth;
+    use minidump::{MinidumpMemoryInfoList, MinidumpModuleList, UnifiedMemoryInfoList};
+    use minidump_processor::referenced_modules::{find_referenced_modules, ModuleRangeIndex};
+
+    let module = |name: &str, base, size| {
+        let name = DumpString::new(name, Endian::Little);
+        let module = minidump_synth::Module::new(Endian::Little, base, size, &name, 0, 0, None);
+        (module, name)
+    };
+    let info = |base, size, protection: MemoryProtection| {
+        MemoryInfo::new(Endian::Little, base, base, 0, size, 0, protection.bits(), 0)
+    };
+    let (module1, module1_name) = module("module1.dll", 0x7000_0000, 0x10000);
+    let (module2, module2_name) = module("module2.dll", 0x7001_0000, 0x10000);
+    // 32-bit values, and two threads' stacks scanned with the same index
+    let stack1 = Section::with_endian(Endian::Little)
+        .D32(0x7000_0010) // code in module1
+        .D32(0x7000_3000) // data in module1
+        .D32(0x7000_8000) // module1, no memory info
+        .D32(0x7000_fff0) // code in module1, in a region that continues into module2
+        .D32(0x7001_0010) // code in module2
+        .D32(0x10) // not a pointer
+        .D8(0x01); // not a whole word
+    let stack1 = Memory::with_section(stack1, 0x10_0000);
+    let stack2 = Section::with_endian(Endian::Little)
+        .D32(0x7001_8000) // data in module2
+        .D32(0x7001_fffc) // module2, no memory info
+        .D32(0x7002_0000); // past module2
+    let stack2 = Memory::with_section(stack2, 0x20_0000);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_module(module1)
+        .add_module(module2)
+        .add(module1_name)
+        .add(module2_name)
+        .add_memory_info(info(
+            0x7000_0000,
+            0x1000,
+            MemoryProtection::PAGE_EXECUTE_READ,
+        ))
+        .add_memory_info(info(0x7000_1000, 0x3000, MemoryProtection::PAGE_READONLY))
+        .add_memory_info(info(
+            0x7000_f000,
+            0x2000,
+            MemoryProtection::PAGE_EXECUTE_READ,
+        ))
+        .add_memory_info(info(0x7001_1000, 0x8000, MemoryProtection::PAGE_READWRITE))
+        .add_memory(stack1)
+        .add_memory(stack2);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+    let memory_info =
+        UnifiedMemoryInfoList::new(dump.get_stream::<MinidumpMemoryInfoList>().ok(), None).unwrap();
+    let memory = dump.get_memory().unwrap();
+
+    let index = ModuleRangeIndex::new(&modules, &memory_info);
+    let referenced = |stack_pointer| {
+        let stack = memory.memory_at_address(stack_pointer).unwrap();
+        find_referenced_modules(
+            stack_pointer,
+            &stack,
+            &modules,
+            &index,
+            PointerWidth::Bits32,
+        )
+        .iter()
+        .map(|referenced| (referenced.module.code_file().into_owned(), referenced.count))
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        referenced(0x10_0000),
+        [
+            (String::from("module1.dll"), 3),
+            (String::from("module2.dll"), 1)
+        ]
+    );
+    assert_eq!(referenced(0x20_0000), [(String::from("module2.dll"), 1)]);
+    // Only the values above the stack pointer count
+    assert_eq!(
+        referenced(0x10_000c),
+        [
+            (String::from("module1.dll"), 1),
+            (String::from("module2.dll"), 1)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_dump_integrity() {
+    // This overlaps the stack of `minimal_minidump`
+    let overlapping = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x10),
+        0x1008,
+    );
+    let dump = minimal_minidump().add_memory(overlapping);
+    let state = read_synth_dump(dump).await;
+
+    assert_eq!(state.dump_integrity.len(), 1);
+    assert_eq!(state.dump_integrity[0].name(), "MemoryOverlap");
+
+    let mut json_output = Vec::new();
+    state.print_json(&mut json_output, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json_output).unwrap();
+    assert_eq!(json["dump_integrity"][0]["kind"], "MemoryOverlap");
+
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(human.contains("Dump integrity warnings:\n  memory region at 0x1008"));
+}
+
+#[tokio::test]
+async fn test_unprocessed_streams() {
+    let stream = |stream_type, size| SimpleStream {
+        stream_type,
+        section: Section::with_endian(Endian::Little).append_repeated(0, size),
+    };
+    let dump = minimal_minidump()
+        .add_stream(stream(0x4350_000a, 4))
+        .add_stream(stream(0x4350_000a, 312))
+        .add_stream(stream(MINIDUMP_STREAM_TYPE::CommentStreamA as u32, 8));
+    let state = read_synth_dump(dump).await;
+
+    assert_eq!(state.unknown_streams.len(), 1);
+    assert_eq!(state.unknown_streams[0].location.data_size, 312);
+    assert_eq!(state.duplicate_streams.len(), 1);
+    assert_eq!(state.duplicate_streams[0].location.data_size, 4);
+    assert_eq!(state.dump_integrity.len(), 1);
+    assert_eq!(state.dump_integrity[0].name(), "DuplicateStream");
+
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(human.contains(
+        "Dump contains 2 unprocessed streams: 0x0000000a 'CommentStreamA' (8 bytes), \
+         0x4350000a 'Crashpad Extension' (312 bytes)\n\
+         Dump contains 1 ignored duplicate streams: 0x4350000a 'Crashpad Extension' (4 bytes)\n"
+    ));
+}
+
+#[tokio::test]
+async fn test_auxiliary_crashes() {
+    const JAVA_STREAM: u32 = 0x4a41_5641;
+    let java = "java.lang.IllegalStateException: no window\n\
+        \tat com.example.app.MainActivity.onCreate(MainActivity.java:42)\n\
+        \tat android.app.Activity.performCreate(Activity.java:8000)\n";
+    let dump = minimal_minidump().add_stream(SimpleStream {
+        stream_type: JAVA_STREAM,
+        section: Section::with_endian(Endian::Little).append_bytes(java.as_bytes()),
+    });
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let symbolizer = Symbolizer::new(simple_symbol_supplier(vec![]));
+
+    // Without being told about the stream, it's just an unknown stream
+    let state = minidump_processor::process_minidump(&dump, &symbolizer)
+        .await
+        .unwrap();
+    assert!(state.auxiliary_crashes.is_empty());
+    assert_eq!(state.unknown_streams.len(), 1);
+
+    let mut options = ProcessorOptions::default();
+    options.auxiliary_streams.register_java(JAVA_STREAM);
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    assert!(state.unknown_streams.is_empty());
+    assert_eq!(state.auxiliary_crashes.len(), 1);
+    let crash = &state.auxiliary_crashes[0];
+    assert_eq!(crash.kind, "java");
+    assert_eq!(
+        crash.message.as_deref(),
+        Some("java.lang.IllegalStateException: no window")
+    );
+    assert_eq!(
+        crash.frames,
+        [
+            "com.example.app.MainActivity.onCreate(MainActivity.java:42)",
+            "android.app.Activity.performCreate(Activity.java:8000)",
+        ]
+    );
+
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(human.contains(
+        "Auxiliary crash (java, stream 0x4a415641):\n\
+         \x20 java.lang.IllegalStateException: no window\n\
+         \x20   com.example.app.MainActivity.onCreate(MainActivity.java:42)\n\
+         \x20   android.app.Activity.performCreate(Activity.java:8000)\n"
+    ));
+    // After the native stacks
+    assert!(human.find("Auxiliary crash").unwrap() > human.find("Thread 0").unwrap());
+
+    let report = state.to_json_report();
+    let crashes = report.auxiliary_crashes.unwrap();
+    assert_eq!(crashes[0].stream_type, "0x4a415641");
+    assert_eq!(crashes[0].frames.len(), 2);
+    assert_eq!(crashes[0].raw, None);
+}
+
+#[tokio::test]
+async fn test_exception_chain() {
+    use minidump_common::errors::ExceptionCodeWindows;
+
+    const RECORD: u64 = 0x2000;
+    let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little)
+        .set_platform_id(minidump_common::format::PlatformId::VER_PLATFORM_WIN32_NT as u32);
+    // An unhandled C++ exception, thrown while handling a null pointer write
+    // (whose EXCEPTION_RECORD32 the C++ exception's record points to).
+    let mut exception = Exception::with_code(
+        Endian::Little,
+        ExceptionCodeWindows::UNHANDLED_CPP_EXCEPTION as u32,
+        0x7000_1000,
+        0x1234,
+    )
+    .parameters(&[0x1993_0520, 0x0100_0000, 0x7000_2000]);
+    exception.exception_record.exception_record = RECORD;
+    let record = Section::with_endian(Endian::Little)
+        .D32(ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION as u32)
+        .D32(0)
+        .D32(0) // no nested record
+        .D32(0x7000_0042)
+        .D32(2)
+        .D32(1)
+        .D32(0x10)
+        .append_repeated(0, 13 * 4);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add_exception(exception)
+        .add(context)
+        .add_memory(stack)
+        .add_memory(Memory::with_section(record, RECORD));
+    let state = read_synth_dump(dump).await;
+
+    assert_eq!(state.exception_chain.len(), 2);
+    assert_eq!(
+        state.exception_chain[0].code,
+        ExceptionCodeWindows::UNHANDLED_CPP_EXCEPTION as u32
+    );
+    assert_eq!(state.exception_chain[0].parameters.len(), 3);
+    let nested = &state.exception_chain[1];
+    assert_eq!(
+        nested.code,
+        ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION as u32
+    );
+    assert_eq!(nested.address.0, 0x7000_0042);
+    assert_eq!(nested.parameters, [1, 0x10]);
+    // The access violation is the more specific crash reason
+    let info = state.exception_info.as_ref().unwrap();
+    assert_eq!(info.reason, nested.reason);
+    assert_eq!(info.address.0, 0x10);
+
+    let mut human = Vec::new();
+    state.print(&mut human).unwrap();
+    let human = String::from_utf8(human).unwrap();
+    assert!(human.contains("Exception chain (outermost first):\n"));
+    assert!(human.contains(&format!(
+        "  1: {} (0xc0000005) at 0x70000042, parameters: 0x1, 0x10\n",
+        nested.reason
+    )));
+
+    let report = state.to_json_report();
+    let chain = report.crash_info.exception_chain.unwrap();
+    assert_eq!(chain.len(), 2);
+    assert_eq!(chain[0].code, "0xe06d7363");
+    assert_eq!(chain[1].parameters, ["0x00000001", "0x00000010"]);
+    assert_eq!(report.crash_info.address.as_deref(), Some("0x00000010"));
+
+    // Without the nested record, the C++ exception is all there is
+    let exception = Exception::with_code(
+        Endian::Little,
+        ExceptionCodeWindows::UNHANDLED_CPP_EXCEPTION as u32,
+        0x7000_1000,
+        0x1234,
+    );
+    let state = read_synth_dump(minimal_minidump().add_exception(exception)).await;
+    assert_eq!(state.exception_chain.len(), 1);
+    assert!(state.to_json_report().crash_info.exception_chain.is_none());
+}
+
+#[tokio::test]
+async fn test_crash_reason_detail() {
+    let context = minidump_synth::amd64_context(Endian::Little, 0, 0);
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little)
+        .set_processor_architecture(
+            minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+        )
+        .set_platform_id(minidump_common::format::PlatformId::VER_PLATFORM_WIN32_NT as u32);
+
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 1;
+    ex.exception_record.exception_code =
+        minidump_common::errors::ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION as u32;
+    ex.exception_record.exception_address = 0x7ff0_1234;
+    ex.exception_record.number_parameters = 2;
+    ex.exception_record.exception_information[0] = 1;
+    ex.exception_record.exception_information[1] = 0x10;
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+    let state = read_synth_dump(dump).await;
+
+    let mut json_output = Vec::new();
+    state.print_json(&mut json_output, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json_output).unwrap();
+    let details = &json["crash_info"]["details"];
+    assert_eq!(details["kind"], "access_violation");
+    assert_eq!(details["access"], "write");
+    assert_eq!(details["address"], "0x0000000000000010");
+
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(human.contains("Crash details: Write access violation at 0x0000000000000010\n"));
+}
+
+#[tokio::test]
+async fn test_symbol_disposition() {
+    // Frame 0 crashed between two functions of app.exe, frame 1 is in one of
+    // them, and frame 2 is in a module without symbols.
+    let context = minidump_synth::x86_context(Endian::Little, 0x7000_1800, 0x80000);
+    let stack = Section::with_endian(Endian::Little)
+        .D32(0x7000_1010)
+        .D32(0x7100_1010)
+        .append_repeated(0, 0x100);
+    let stack = Memory::with_section(stack, 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 1;
+    ex.exception_record.exception_address = 0x7000_1800;
+
+    let mut dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    for (base, name) in [(0x7000_0000, "app.exe"), (0x7100_0000, "hook.dll")].iter() {
+        let name = DumpString::new(name, Endian::Little);
+        let module = minidump_synth::Module::new(Endian::Little, *base, 0x10000, &name, 0, 0, None);
+        dump = dump.add_module(module).add(name);
+    }
+    let symbols = HashMap::from([(
+        String::from("app.exe"),
+        String::from("FUNC 1000 100 0 main\nFUNC 2000 100 0 other\n"),
+    )]);
+    let state = read_synth_dump_with_symbols(dump, symbols).await;
+
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames.len(), 3);
+    assert_eq!(
+        frames[0].symbol_disposition,
+        Some(SymbolDisposition::NoCoveringSymbol)
+    );
+    assert_eq!(
+        frames[1].symbol_disposition,
+        Some(SymbolDisposition::Symbolized)
+    );
+    assert_eq!(
+        frames[2].symbol_disposition,
+        Some(SymbolDisposition::SymbolsMissing)
+    );
+    let info = state.exception_info.as_ref().unwrap();
+    assert_eq!(
+        info.notes,
+        [CrashNote::NoCoveringSymbol {
+            module: String::from("app.exe")
+        }]
+    );
+
+    let mut json_output = Vec::new();
+    state.print_json(&mut json_output, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json_output).unwrap();
+    assert_eq!(json["crash_info"]["notes"][0]["kind"], "NoCoveringSymbol");
+    let json_frames = &json["threads"][0]["frames"];
+    assert_eq!(json_frames[0]["symbol_disposition"], "no_covering_symbol");
+    assert_eq!(json_frames[1]["symbol_disposition"], "symbolized");
+    assert_eq!(json_frames[2]["symbol_disposition"], "symbols_missing");
+
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(human.contains("Crash note: the crashing instruction is in app.exe but outside"));
+}
+
+#[tokio::test]
+async fn test_public_symbol_distance() {
+    // Frame 0 is 2 MiB past app.exe's last PUBLIC (in code without any
+    // symbols), frame 1 is in a FUNC, and frame 2 just past the PUBLIC.
+    let context = minidump_synth::x86_context(Endian::Little, 0x7020_2000, 0x80000);
+    let stack = Section::with_endian(Endian::Little)
+        .D32(0x7000_1010)
+        .D32(0x7000_2010)
+        .append_repeated(0, 0x100);
+    let stack = Memory::with_section(stack, 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 1;
+    let name = DumpString::new("app.exe", Endian::Little);
+    let module =
+        minidump_synth::Module::new(Endian::Little, 0x7000_0000, 0x40_0000, &name, 0, 0, None);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack)
+        .add_module(module)
+        .add(name);
+    let symbols = HashMap::from([(
+        String::from("app.exe"),
+        String::from("FUNC 1000 100 0 main\nPUBLIC 2000 0 stripped_region_start\n"),
+    )]);
+    let state = read_synth_dump_with_symbols(dump, symbols).await;
+
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0].function_name, None);
+    assert_eq!(frames[0].symbol_kind, None);
+    assert_eq!(
+        frames[0].symbol_disposition,
+        Some(SymbolDisposition::NoCoveringSymbol)
+    );
+    assert_eq!(frames[1].symbol_kind, Some(SymbolKind::Func));
+    assert_eq!(
+        frames[2].function_name.as_deref(),
+        Some("stripped_region_start")
+    );
+    assert_eq!(frames[2].symbol_kind, Some(SymbolKind::Public));
+    // The far-away PUBLIC isn't in the signature.
+    assert_eq!(
+        state.signature().as_deref(),
+        Some("app.exe@0x202000 | main | stripped_region_start")
+    );
+
+    let report = serde_json::to_value(state.to_json_report()).unwrap();
+    let json_frames = &report["threads"][0]["frames"];
+    assert_eq!(json_frames[0]["symbol_kind"], "none");
+    assert_eq!(json_frames[0]["function_offset"], serde_json::Value::Null);
+    // (The callers' instructions are right before their return addresses.)
+    assert_eq!(json_frames[1]["symbol_kind"], "func");
+    assert_eq!(json_frames[1]["function_offset"], "0x0000000f");
+    assert_eq!(json_frames[2]["symbol_kind"], "public");
+    assert_eq!(json_frames[2]["function_offset"], "0x0000000f");
+}
+
+#[tokio::test]
+async fn test_handles() {
+    let type_name = DumpString::new("Event", Endian::Little);
+    let handle = HandleDescriptor::new(Endian::Little, 0x1c4, Some(&type_name), None, 0, 0, 2, 65);
+    let dump = minimal_minidump()
+        .add_handle_descriptor(handle)
+        .add(type_name);
+    let state = read_synth_dump(dump).await;
+
+    let report = serde_json::to_value(state.to_json_report()).unwrap();
+    assert_eq!(
+        report["handles"],
+        serde_json::json!([{
+            "handle": 0x1c4,
+            "handle_count": 2,
+            "object_name": null,
+            "pointer_count": 65,
+            "type_name": "Event",
+        }])
+    );
+}
+
+#[tokio::test]
+async fn test_function_tables() {
+    // A JITed function (push rbp; sub rsp, 0x10) at 0x5000_1000, called by libtest.so.
+    let context = minidump_synth::amd64_context(Endian::Little, 0x5000_1050, 0x80000);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .append_repeated(0, 0x10)
+            .D64(0x1234) // rbp
+            .D64(0x7000_1020) // return address
+            .append_repeated(0, 0x40),
+        0x80000,
+    );
+    let unwind_info = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .append_bytes(&[0x01, 0x05, 0x02, 0x00])
+            .D16(0x1205) // ALLOC_SMALL 0x10
+            .D16(0x5001), // PUSH_NONVOL rbp
+        0x5000_2000,
+    );
+    let function_tables = minidump_synth::function_table_stream(
+        Endian::Little,
+        &[minidump_synth::FunctionTable {
+            minimum_address: 0x5000_1000,
+            maximum_address: 0x5000_1100,
+            base_address: 0x5000_0000,
+            entries: vec![(0x1000, 0x1100, 0x2000)],
+        }],
+        0,
+    );
+    let module_name = DumpString::new("libtest.so", Endian::Little);
+    let module = minidump_synth::Module::new(
+        Endian::Little,
+        0x7000_0000,
+        0x10000,
+        &module_name,
+        0,
+        0,
+        None,
+    );
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    );
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(Thread::new(Endian::Little, 1, &stack, &context))
+        .add_module(module)
+        .add(module_name)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack)
+        .add_memory(unwind_info)
+        .add_stream(function_tables);
+    let state = read_synth_dump(dump).await;
+
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames[0].instruction, 0x5000_1050);
+    assert!(frames[0].module.is_none());
+    assert_eq!(frames[1].trust, FrameTrust::CallFrameInfo);
+    assert_eq!(frames[1].instruction, 0x7000_1020 - 1);
+    assert_eq!(frames[1].context.get_stack_pointer(), 0x80020);
+    assert_eq!(frames[1].context.get_register("rbp"), Some(0x1234));
+}
+
+#[tokio::test]
+async fn test_crash_in_unloaded_module() {
+    // The crash is in hook.dll, which was unloaded twice: once from where the
+    // crash is, and once from somewhere else.
+    let context = minidump_synth::x86_context(Endian::Little, 0x7100_0123, 0x80000);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x80000,
+    );
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 1;
+    let app_name = DumpString::new("app.exe", Endian::Little);
+    let app = minidump_synth::Module::new(
+        Endian::Little,
+        0x7000_0000,
+        0x10_0000,
+        &app_name,
+        0,
+        0,
+        None,
+    );
+    let hook_name = DumpString::new("hook.dll", Endian::Little);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack)
+        .add_module(app)
+        .add_unloaded_module(UnloadedModule::new(
+            Endian::Little,
+            0x7100_0000,
+            0x1000,
+            &hook_name,
+            0,
+            0,
+        ))
+        .add_unloaded_module(UnloadedModule::new(
+            Endian::Little,
+            0x7200_0000,
+            0x1000,
+            &hook_name,
+            0,
+            0,
+        ))
+        .add(app_name)
+        .add(hook_name);
+    let state = read_synth_dump(dump).await;
+
+    assert_eq!(state.unloaded_modules.iter().count(), 2);
+    let frame = &state.threads[0].frames[0];
+    assert!(frame.module.is_none());
+    assert_eq!(
+        frame.unloaded_modules,
+        BTreeMap::from([(String::from("hook.dll"), BTreeSet::from([0x123]))])
+    );
+
+    let report = serde_json::to_value(state.to_json_report()).unwrap();
+    assert_eq!(
+        report["threads"][0]["frames"][0]["unloaded_modules"],
+        serde_json::json!([{"module": "hook.dll", "offsets": ["0x00000123"]}])
+    );
+    assert_eq!(report["unloaded_modules"][0]["base_addr"], "0x71000000");
+    assert_eq!(report["unloaded_modules"][0]["end_addr"], "0x71001000");
+    assert_eq!(report["unloaded_modules"][0]["filename"], "hook.dll");
+
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(
+        human.contains("0x71000123 (unloaded hook.dll@0x123)"),
+        "{}",
+        human
+    );
+}
+
+/// A dump of one thread, whose frame 0 is in app.exe at 0x7000_1800, and which
+/// has return addresses to 0x7000_1010 (app.exe) and 0x7100_1010 (hook.dll)
+/// on its stack.
+fn unwind_debug_dump() -> SynthMinidump {
+    let context = minidump_synth::x86_context(Endian::Little, 0x7000_1800, 0x80000);
+    let stack = Section::with_endian(Endian::Little)
+        .D32(0x7000_1010)
+        .append_repeated(0, 8)
+        .D32(0x7100_1010)
+        .append_repeated(0, 0x100);
+    let stack = Memory::with_section(stack, 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+
+    let mut dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    for (base, name) in [(0x7000_0000, "app.exe"), (0x7100_0000, "hook.dll")].iter() {
+        let name = DumpString::new(name, Endian::Little);
+        let module = minidump_synth::Module::new(Endian::Little, *base, 0x10000, &name, 0, 0, None);
+        dump = dump.add_module(module).add(name);
+    }
+    dump
+}
+
+#[tokio::test]
+async fn test_unwind_debug() {
+    // Nothing covers frame 0, and the CFI of frame 1 needs $ebx, which a
+    // frame found by scanning doesn't have.
+    const SYMBOLS: &str =
+        "FUNC 1000 100 0 main\nSTACK CFI INIT 1000 100 .cfa: $ebx 8 + .ra: .cfa 4 - ^\n";
+    let symbols = HashMap::from([(String::from("app.exe"), String::from(SYMBOLS))]);
+    let state = read_synth_dump_with_symbols(unwind_debug_dump(), symbols).await;
+
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0].cfi_error, None);
+    assert_eq!(frames[1].cfi_error, Some(WalkFrameError::NoCfi));
+    assert_eq!(
+        frames[2].cfi_error,
+        Some(WalkFrameError::MissingRegister(String::from("ebx")))
+    );
+    assert_eq!(frames[2].trust, FrameTrust::Scan);
+
+    let mut json_output = Vec::new();
+    state.print_json(&mut json_output, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json_output).unwrap();
+    let json_frames = &json["threads"][0]["frames"];
+    assert!(json_frames[0]["unwind_debug"].is_null());
+    assert_eq!(json_frames[1]["unwind_debug"]["cfi_error"], "no_cfi");
+    assert!(json_frames[1]["unwind_debug"]["missing_register"].is_null());
+    assert_eq!(
+        json_frames[2]["unwind_debug"]["cfi_error"],
+        "missing_register"
+    );
+    assert_eq!(json_frames[2]["unwind_debug"]["missing_register"], "ebx");
+
+    // If all providers fail, the one that had CFI explains why
+    let supplier = |symbols: &str| {
+        string_symbol_supplier(HashMap::from([(
+            String::from("app.exe"),
+            String::from(symbols),
+        )]))
+    };
+    let mut provider = MultiSymbolProvider::new();
+    provider.add(Box::new(Symbolizer::new(supplier(
+        "FUNC 1000 100 0 main\n",
+    ))));
+    provider.add(Box::new(Symbolizer::new(supplier(SYMBOLS))));
+    let dump = Minidump::read(unwind_debug_dump().finish().unwrap()).unwrap();
+    let state = minidump_processor::process_minidump(&dump, &provider)
+        .await
+        .unwrap();
+    let frames = &state.threads[0].frames;
+    assert_eq!(
+        frames[2].cfi_error,
+        Some(WalkFrameError::MissingRegister(String::from("ebx")))
+    );
+}
+
+/// Process an x86 dump whose thread is executing `callee` (at 0x7000_1010 in
+/// "app.exe", called by `caller` at 0x7000_2000) with `stack` at 0x80000 and
+/// the given `esp` and `ebp`, recovering arguments.
+///
+/// "hook.dll" at 0x7100_0000 has no symbols.
+async fn recover_arguments_state(
+    stack: Section,
+    ebp: u64,
+    stack_win: &str,
+    privacy_mode: bool,
+) -> ProcessState {
+    let context = minidump_synth::x86_context_with_registers(
+        Endian::Little,
+        &[("eip", 0x7000_1010), ("esp", 0x80000), ("ebp", ebp)],
+    );
+    let stack = Memory::with_section(stack, 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let mut dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    for (base, name) in [(0x7000_0000, "app.exe"), (0x7100_0000, "hook.dll")].iter() {
+        let name = DumpString::new(name, Endian::Little);
+        let module = minidump_synth::Module::new(Endian::Little, *base, 0x10000, &name, 0, 0, None);
+        dump = dump.add_module(module).add(name);
+    }
+    let symbols = HashMap::from([(
+        String::from("app.exe"),
+        format!("FUNC 1000 100 c callee\nFUNC 2000 100 4 caller\n{stack_win}"),
+    )]);
+
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let mut options = ProcessorOptions::default();
+    options.recover_arguments = true;
+    options.privacy_mode = privacy_mode;
+    minidump_processor::process_minidump_with_options(
+        &dump,
+        &Symbolizer::new(string_symbol_supplier(symbols)),
+        options,
+    )
+    .await
+    .unwrap()
+}
+
+fn heuristic_values(frame: &StackFrame) -> Option<Vec<(Option<u64>, Option<&str>)>> {
+    let params = frame.heuristic_parameters.as_ref()?;
+    Some(
+        params
+            .iter()
+            .map(|param| (param.value, param.symbol.as_deref()))
+            .collect(),
+    )
+}
+
+#[tokio::test]
+async fn test_recover_arguments_frame_pointer() {
+    // Both functions use frame pointers, so the parameters are right above the
+    // saved ebp and the return address.
+    let stack = Section::with_endian(Endian::Little)
+        .append_repeated(0, 0x10)
+        // 0x80010: callee's frame
+        .D32(0x80030) // saved ebp
+        .D32(0x7000_2010) // return address into caller
+        .D32(0x2222) // callee's parameters
+        .D32(0x7000_2000)
+        .D32(0x3333)
+        .append_repeated(0, 0xc)
+        // 0x80030: caller's frame
+        .D32(0) // saved ebp
+        .D32(0x7100_0010) // return address into hook.dll
+        .D32(0x4444) // caller's parameter
+        .append_repeated(0, 0x100);
+    let state = recover_arguments_state(stack, 0x80010, "", false).await;
+
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[1].trust, FrameTrust::FramePointer);
+    assert_eq!(
+        heuristic_values(&frames[0]),
+        Some(vec![
+            (Some(0x2222), None),
+            (Some(0x7000_2000), Some("app.exe!caller")),
+            (Some(0x3333), None),
+        ])
+    );
+    assert_eq!(
+        heuristic_values(&frames[1]),
+        Some(vec![(Some(0x4444), None)])
+    );
+    // No symbols, so no parameter size
+    assert_eq!(heuristic_values(&frames[2]), None);
+}
+
+#[tokio::test]
+async fn test_recover_arguments_fpo() {
+    // Neither function uses a frame pointer (ebp is garbage). The STACK WIN
+    // records say callee has 0x10 bytes of locals, and caller has 4.
+    let stack_win = "STACK WIN 4 1000 100 0 0 c 0 10 0 1 $T0 .raSearch = $eip $T0 ^ = $esp $T0 4 + =\n\
+                     STACK WIN 4 2000 100 0 0 4 0 4 0 1 $T0 .raSearch = $eip $T0 ^ = $esp $T0 4 + =\n";
+    let stack = Section::with_endian(Endian::Little)
+        .append_repeated(0, 0x10) // callee's locals
+        .D32(0x7000_2010) // return address into caller
+        .D32(0x1111) // callee's parameters
+        .D32(0x7000_1000)
+        .D32(0x3333)
+        .D32(0) // caller's locals
+        .D32(0x7100_0010) // return address into hook.dll
+        .D32(0x5555) // caller's parameter
+        .append_repeated(0, 0x100);
+    let state = recover_arguments_state(stack, 0xdead_beef, stack_win, false).await;
+
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[1].trust, FrameTrust::CallFrameInfo);
+    assert_eq!(frames[1].context.get_stack_pointer(), 0x80014);
+    assert_eq!(
+        heuristic_values(&frames[0]),
+        Some(vec![
+            (Some(0x1111), None),
+            (Some(0x7000_1000), Some("app.exe!callee")),
+            (Some(0x3333), None),
+        ])
+    );
+    assert_eq!(
+        heuristic_values(&frames[1]),
+        Some(vec![(Some(0x5555), None)])
+    );
+
+    let mut json_output = Vec::new();
+    state.print_json(&mut json_output, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json_output).unwrap();
+    let params = &json["threads"][0]["frames"][0]["heuristic_parameters"];
+    assert_eq!(params[1]["value"], "0x70001000");
+    assert_eq!(params[1]["symbol"], "app.exe!callee");
+    assert_eq!(
+        json["threads"][0]["frames"][2]["heuristic_parameters"],
+        serde_json::Value::Null
+    );
+
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(human.contains("    Parameters (heuristic, read from the stack)\n"));
+    assert!(human.contains("        param 1 = 0x70001000 (app.exe!callee)\n"));
+}
+
+#[tokio::test]
+async fn test_recover_arguments_disabled() {
+    let stack = Section::with_endian(Endian::Little)
+        .append_repeated(0, 0x10)
+        .D32(0x80030)
+        .D32(0x7000_2010)
+        .append_repeated(0, 0x100);
+    let stack = Memory::with_section(stack, 0x80000);
+    let context = minidump_synth::x86_context_with_registers(
+        Endian::Little,
+        &[("eip", 0x7000_1010), ("esp", 0x80000), ("ebp", 0x80010)],
+    );
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let name = DumpString::new("app.exe", Endian::Little);
+    let module =
+        minidump_synth::Module::new(Endian::Little, 0x7000_0000, 0x10000, &name, 0, 0, None);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack)
+        .add_module(module)
+        .add(name);
+    let symbols = HashMap::from([(
+        String::from("app.exe"),
+        String::from("FUNC 1000 100 c callee\nFUNC 2000 100 4 caller\n"),
+    )]);
+    let state = read_synth_dump_with_symbols(dump, symbols).await;
+    assert!(state.threads[0].frames[0].heuristic_parameters.is_none());
+}
+
+/// The JSON and human reports of `state`.
+fn json_and_human(state: &ProcessState) -> (String, String) {
+    let mut json = Vec::new();
+    state.print_json(&mut json, true).unwrap();
+    let mut human = Vec::new();
+    state.print(&mut human).unwrap();
+    (
+        String::from_utf8(json).unwrap(),
+        String::from_utf8(human).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_privacy_mode_stack_values() {
+    // The same frames as test_recover_arguments_frame_pointer, with parameters
+    // that stand out.
+    let stack = || {
+        Section::with_endian(Endian::Little)
+            .append_repeated(0, 0x10)
+            .D32(0x80030)
+            .D32(0x7000_2010)
+            .D32(0x5ec2_e7a1)
+            .D32(0x5ec2_e7a2)
+            .D32(0x5ec2_e7a3)
+            .append_repeated(0, 0xc)
+            .D32(0)
+            .D32(0x7100_0010)
+            .D32(0x5ec2_e7a4)
+            .append_repeated(0, 0x100)
+    };
+
+    let state = recover_arguments_state(stack(), 0x80010, "", false).await;
+    assert_eq!(state.redacted_sections, None);
+    let (json, human) = json_and_human(&state);
+    assert!(json.contains("5ec2e7a1"), "{}", json);
+    assert!(human.contains("5ec2e7a1"), "{}", human);
+    assert!(!human.contains("Redacted"), "{}", human);
+
+    let state = recover_arguments_state(stack(), 0x80010, "", true).await;
+    assert_eq!(
+        state.redacted_sections,
+        Some(vec![
+            RedactedSection::Environment,
+            RedactedSection::FunctionArguments
+        ])
+    );
+    // The frames are still there, just not the values on the stack.
+    assert_eq!(state.threads[0].frames.len(), 3);
+    let (json, human) = json_and_human(&state);
+    assert!(!json.contains("5ec2e7a"), "{}", json);
+    assert!(!human.contains("5ec2e7a"), "{}", human);
+    assert!(
+        human.contains(
+            "Redacted:\n  the command line and environment were withheld\n  \
+             the function arguments were withheld\n"
+        ),
+        "{}",
+        human
+    );
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["redacted"], true);
+    assert_eq!(
+        json["redacted_sections"],
+        serde_json::json!(["environment", "function_arguments"])
+    );
+}
+
+#[tokio::test]
+async fn test_privacy_mode_strings() {
+    let text = Memory::with_section(
+        Section::with_endian(Endian::Little).append_bytes(b"secret user data"),
+        0x5000,
+    );
+    let stack = Section::with_endian(Endian::Little)
+        .D64(0x5000)
+        .D64(16)
+        .append_repeated(0, 0x100);
+    let (dump, symbols) = crash_message_minidump("std::panicking::begin_panic<&str>", stack, true);
+    let crashpad_info = CrashpadInfo::new(Endian::Little)
+        .add_simple_annotation("url", "https://example.com/secret")
+        .add_module(
+            ModuleCrashpadInfo::new(0, Endian::Little)
+                .add_list_annotation("opened /home/secret/notes.txt")
+                .add_simple_annotation("channel", "beta")
+                .add_annotation_object(
+                    "user_comment",
+                    AnnotationValue::String("my password".to_owned()),
+                ),
+        );
+    let dump = dump.add_memory(text).add_crashpad_info(crashpad_info);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+
+    let mut options = ProcessorOptions::default();
+    options.privacy_mode = true;
+    options.extra_annotations = serde_json::json!({
+        "ProductName": "Firefox",
+        "URL": "https://example.com/secret",
+        "CrashReporterUserComment": "it crashed while I was typing my password",
+        "TelemetryUserId": 1234,
+    })
+    .as_object()
+    .unwrap()
+    .clone();
+    let state = minidump_processor::process_minidump_with_options(
+        &dump,
+        &Symbolizer::new(string_symbol_supplier(symbols)),
+        options,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        state.redacted_sections,
+        Some(vec![
+            RedactedSection::Environment,
+            RedactedSection::CrashMessage,
+            RedactedSection::CrashAnnotations
+        ])
+    );
+    let message = state.exception_info.as_ref().unwrap().message.clone();
+    assert!(message.unwrap().starts_with("redacted:"));
+    assert_eq!(state.crash_annotations["ProductName"], "Firefox");
+    for key in ["URL", "CrashReporterUserComment", "TelemetryUserId"] {
+        let value = state.crash_annotations[key].as_str().unwrap();
+        assert!(value.starts_with("redacted:"), "{}", value);
+    }
+    let crashpad_info = state.crashpad_info.as_ref().unwrap();
+    assert!(crashpad_info.simple_annotations["url"].starts_with("redacted:"));
+    assert_eq!(
+        crashpad_info.module_list[0].simple_annotations["channel"],
+        "beta"
+    );
+    let list_annotations = &crashpad_info.module_list[0].list_annotations;
+    assert_eq!(list_annotations.len(), 1);
+    assert!(list_annotations[0].starts_with("redacted:"));
+
+    let (json, human) = json_and_human(&state);
+    for output in [&json, &human] {
+        assert!(!output.contains("secret"), "{}", output);
+        assert!(!output.contains("password"), "{}", output);
+    }
+}
+
+#[tokio::test]
+async fn test_json_report_schema() {
+    use minidump_processor::report::{Report, SCHEMA_VERSION};
+
+    let stack = Section::with_endian(Endian::Little).append_repeated(0, 0x100);
+    let (dump, symbols) = crash_message_minidump("crash_here", stack, true);
+    let state = read_synth_dump_with_symbols(dump, symbols).await;
+
+    let report = state.to_json_report();
+    assert_eq!(report.schema_version, SCHEMA_VERSION);
+    assert_eq!(report.crashing_thread.as_ref().unwrap().threads_index, 0);
+
+    let mut json_output = Vec::new();
+    state.print_json(&mut json_output, true).unwrap();
+    let json = String::from_utf8(json_output).unwrap();
+    insta::assert_snapshot!("json_report_schema", json);
+
+    // Consumers can read the output back into the typed report
+    let parsed: Report = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, report);
+}
+
+#[tokio::test]
+async fn test_debug_id_mismatch() {
+    let module_name = DumpString::new("libtest.so", Endian::Little);
+    let cv_record = Section::with_endian(Endian::Little)
+        .D32(minidump_common::format::CvSignature::Pdb70 as u32) // signature
+        // signature, a GUID
+        .D32(0xabcd1234)
+        .D16(0xf00d)
+        .D16(0xbeef)
+        .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+        .D32(1) // age
+        .append_bytes(b"libtest.so\0"); // pdb_file_name
+    let module = minidump_synth::Module::new(
+        Endian::Little,
+        0x7000_0000,
+        0x10000,
+        &module_name,
+        0,
+        0,
+        None,
+    )
+    .cv_record(&cv_record);
+    let context = minidump_synth::amd64_context(Endian::Little, 0x7000_1010, 0x80000);
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    );
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_module(module)
+        .add(module_name)
+        .add(cv_record)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+
+    // Symbols for a different build of the module
+    let mut symbols = HashMap::new();
+    symbols.insert(
+        String::from("libtest.so"),
+        String::from("MODULE Linux x86_64 FFFF0000F00DBEEF01020304050607081 libtest.so\n"),
+    );
+    let state = read_synth_dump_with_symbols(dump, symbols).await;
+
+    let report = state.to_json_report();
+    let module = &report.modules[0];
+    assert_eq!(module.debug_id, "ABCD1234F00DBEEF01020304050607081");
+    assert!(module.loaded_symbols);
+    assert!(module.debug_id_mismatch);
+    assert_eq!(
+        module.symbols_debug_id.as_deref(),
+        Some("FFFF0000F00DBEEF01020304050607081")
+    );
+
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(human.contains(
+        "WARNING: symbols are for debug id FFFF0000F00DBEEF01020304050607081, \
+         not ABCD1234F00DBEEF01020304050607081"
+    ));
+}
+
+/// A `SymbolSupplier` that looks up the debug info of modules that are missing
+/// it, and serves `symbols` for all of them.
+struct DebugInfoLookupSupplier {
+    debug_info: DebugInfoResult,
+    symbols: &'static str,
+}
+
+#[async_trait]
+impl SymbolSupplier for DebugInfoLookupSupplier {
+    async fn locate_symbols(
+        &self,
+        _module: &(dyn Module + Sync),
+    ) -> Result<LocateSymbolsResult, SymbolError> {
+        Ok(LocateSymbolsResult {
+            symbols: SymbolFile::from_bytes(self.symbols.as_bytes())?,
+            extra_debug_info: Some(self.debug_info.clone()),
+        })
+    }
+
+    async fn locate_file(
+        &self,
+        _module: &(dyn Module + Sync),
+        _file_kind: FileKind,
+    ) -> Result<PathBuf, FileError> {
+        Err(FileError::NotFound)
+    }
+}
+
+#[tokio::test]
+async fn test_debug_id_mismatch_looked_up_debug_info() {
+    // No CodeView record, so the debug info has to be looked up
+    let module_name = DumpString::new("libtest.so", Endian::Little);
+    let module = minidump_synth::Module::new(
+        Endian::Little,
+        0x7000_0000,
+        0x10000,
+        &module_name,
+        0,
+        0,
+        None,
+    );
+    let context = minidump_synth::amd64_context(Endian::Little, 0x7000_1010, 0x80000);
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    );
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_module(module)
+        .add(module_name)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+
+    let supplier = DebugInfoLookupSupplier {
+        debug_info: DebugInfoResult {
+            debug_file: String::from("libtest.so"),
+            debug_identifier: "ABCD1234F00DBEEF01020304050607081".parse().unwrap(),
+        },
+        symbols: "MODULE Linux x86_64 FFFF0000F00DBEEF01020304050607081 libtest.so\n",
+    };
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let state = minidump_processor::process_minidump(&dump, &Symbolizer::new(supplier))
+        .await
+        .unwrap();
+
+    let report = state.to_json_report();
+    let module = &report.modules[0];
+    assert_eq!(module.debug_id, "ABCD1234F00DBEEF01020304050607081");
+    assert!(module.debug_id_mismatch);
+
+    // The warning names the debug id the symbols were checked against
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(human.contains(
+        "WARNING: symbols are for debug id FFFF0000F00DBEEF01020304050607081, \
+         not ABCD1234F00DBEEF01020304050607081"
+    ));
+}
+
+fn memory_usage_minidump(regions: &[(u64, u64, MemoryState, MemoryProtection)]) -> SynthMinidump {
+    let context = minidump_synth::x86_context(Endian::Little, 0x1000, 0x80000);
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL as u16,
+    );
+    let mut dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+    for &(base, size, state, protection) in regions {
+        dump = dump.add_memory_info(MemoryInfo::new(
+            Endian::Little,
+            base,
+            base,
+            protection.bits(),
+            size,
+            state.bits(),
+            protection.bits(),
+            MemoryType::MEM_PRIVATE.bits(),
+        ));
+    }
+    dump
+}
+
+#[tokio::test]
+async fn test_memory_usage() {
+    let rw = MemoryProtection::PAGE_READWRITE;
+    let none = MemoryProtection::PAGE_NOACCESS;
+    let write_combine = MemoryProtection::PAGE_READWRITE | MemoryProtection::PAGE_WRITECOMBINE;
+    let dump = memory_usage_minidump(&[
+        (0x10000, 0x10000, MemoryState::MEM_FREE, none),
+        (0x20000, 0x30000, MemoryState::MEM_COMMIT, rw),
+        // Overlaps the previous region, only the last 0x10000 bytes count
+        (0x40000, 0x20000, MemoryState::MEM_COMMIT, write_combine),
+        (0x60000, 0x100000, MemoryState::MEM_RESERVE, none),
+        (0x160000, 0x80000, MemoryState::MEM_FREE, none),
+        // A duplicate that mustn't be counted again
+        (0x160000, 0x80000, MemoryState::MEM_FREE, none),
+    ]);
+    let state = read_synth_dump(dump).await;
+
+    let usage = state.memory_usage.as_ref().unwrap();
+    assert_eq!(usage.total_committed, 0x40000);
+    assert_eq!(usage.write_combine_committed, 0x10000);
+    assert_eq!(usage.total_reserved, 0x100000);
+    assert_eq!(usage.total_free, 0x90000);
+    assert_eq!(usage.largest_free_block, 0x80000);
+    assert_eq!(usage.largest_free_block_address, Some(0x160000));
+    // There's no free block big enough for a new allocation
+    assert!(usage.likely_oom);
+
+    let report = state.to_json_report();
+    let report_usage = report.memory_usage.unwrap();
+    assert_eq!(report_usage.largest_free_block, 0x80000);
+    assert_eq!(
+        report_usage.largest_free_block_address.as_deref(),
+        Some("0x00160000")
+    );
+    assert!(report_usage.likely_oom);
+
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(human.contains(
+        "Memory usage: 262144 bytes committed, 1048576 bytes reserved, \
+         largest free block: 524288 bytes (likely OOM)\n"
+    ));
+
+    let dump = memory_usage_minidump(&[
+        (0x20000, 0x30000, MemoryState::MEM_COMMIT, rw),
+        (0x1000000, 0x7000000, MemoryState::MEM_FREE, none),
+    ]);
+    let state = read_synth_dump(dump).await;
+    let usage = state.memory_usage.unwrap();
+    assert_eq!(usage.largest_free_block, 0x7000000);
+    assert!(!usage.likely_oom);
+}
+
+#[tokio::test]
+async fn test_memory_counters() {
+    use minidump_common::format as md;
+
+    let mut info = md::MINIDUMP_SYSTEM_MEMORY_INFO_1 {
+        revision: 1,
+        flags: md::SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_BASICPERF.bits(),
+        ..Default::default()
+    };
+    info.basic_info.page_size = 0x1000;
+    info.basic_info.number_of_physical_pages = 0x1000;
+    info.basic_perf_info.available_pages = 0x10;
+    info.basic_perf_info.committed_pages = 0x1ff0;
+    info.basic_perf_info.commit_limit = 0x2000;
+    let counters = md::MINIDUMP_PROCESS_VM_COUNTERS_2 {
+        revision: 2,
+        flags: (md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS
+            | md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_EX)
+            .bits(),
+        working_set_size: 0x10_0000,
+        peak_working_set_size: 0x20_0000,
+        private_usage: 0x180_0000,
+        virtual_size: 0x4000_0000,
+        ..Default::default()
+    };
+    let dump = memory_usage_minidump(&[])
+        .add_stream(system_memory_info_stream(Endian::Little, info))
+        .add_stream(process_vm_counters_2_stream(Endian::Little, counters));
+    let state = read_synth_dump(dump).await;
+
+    let counters = state.memory_counters.as_ref().unwrap();
+    assert_eq!(counters.system_physical_memory, Some(0x100_0000));
+    assert_eq!(counters.system_available_physical_memory, Some(0x1_0000));
+    assert_eq!(counters.system_committed, Some(0x1ff_0000));
+    assert_eq!(counters.system_commit_limit, Some(0x200_0000));
+    assert_eq!(counters.working_set_size, Some(0x10_0000));
+    assert_eq!(counters.peak_working_set_size, Some(0x20_0000));
+    assert_eq!(counters.private_usage, Some(0x180_0000));
+    // Not in the flags
+    assert_eq!(counters.virtual_size, None);
+
+    let report = state.to_json_report();
+    let report_counters = report.memory_counters.unwrap();
+    assert_eq!(report_counters.system_committed, Some(0x1ff_0000));
+    assert_eq!(report_counters.private_usage, Some(0x180_0000));
+    assert_eq!(report_counters.virtual_size, None);
+
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(human.contains(
+        "Memory counters: system committed 33488896 of 33554432 bytes, \
+         65536 of 16777216 bytes of physical memory available\n  \
+         process: private 25165824 bytes, working set 1048576 bytes (peak 2097152), \
+         virtual ? bytes (peak ?)\n"
+    ));
+
+    let state = read_synth_dump(memory_usage_minidump(&[])).await;
+    assert_eq!(state.memory_counters, None);
+}
+
+#[tokio::test]
+async fn test_bit_flip() {
+    let context = minidump_synth::amd64_context(Endian::Little, 0, 0);
+
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0);
+    let heap_info = MemoryInfo::new(Endian::Little, 0x80000, 0x80000, 0, 8, 0, 0, 0);
+
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    );
+
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 1;
+    ex.exception_record.exception_address = 0x80400;
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack)
+        .add_memory_info(heap_info);
+
+    let state = read_synth_dump(dump).await;
+
+    let bit_flips = state
+        .exception_info
+        .expect("missing exception info")
+        .possible_bit_flips;
+
+    assert_eq!(bit_flips.len(), 1);
+    let bf = bit_flips.into_iter().next().unwrap();
+    assert_eq!(bf.address.0, 0x80000);
+    assert_eq!(bf.details, Default::default());
+}
+
+#[tokio::test]
+async fn test_no_bit_flip_32bit() {
+    let context = minidump_synth::x86_context(Endian::Little, 0, 0);
+
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0);
+    let heap_info = MemoryInfo::new(Endian::Little, 0x80000, 0x80000, 0, 8, 0, 0, 0);
+
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little);
+
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 1;
+    ex.exception_record.exception_address = 0x80400;
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack)
+        .add_memory_info(heap_info);
+
+    let state = read_synth_dump(dump).await;
+
+    assert!(state
+        .exception_info
+        .expect("missing exception info")
+        .possible_bit_flips
+        .is_empty());
+}
+
+// Remove this once issue #863 is fixed.
+#[tokio::test]
+async fn test_bit_flip_arm64() {
+    let context = minidump_synth::arm64_context(Endian::Little, 0, 0);
+
     let stack = Memory::with_section(Section::with_endian(Endian::Little), 0);
 
     let thread = Thread::new(Endian::Little, 1, &stack, &context);
@@ -508,3 +4289,340 @@ async fn test_guard_pages() {
     assert_eq!(accesses[0].address, 0x81000);
     assert!(accesses[0].is_likely_guard_page);
 }
+
+/// A dump whose crashing thread has a backtrace of `functions` (innermost first).
+///
+/// `Some(name)` functions are symbolized frames in "libtest.so", `None` are
+/// unsymbolized frames in "libother.so".
+fn signature_minidump(functions: &[Option<&str>]) -> (SynthMinidump, HashMap<String, String>) {
+    let mut symbols = String::new();
+    let addresses: Vec<u64> = functions
+        .iter()
+        .enumerate()
+        .map(|(idx, function)| {
+            let offset = 0x1000 * (idx as u64 + 1);
+            match function {
+                Some(function) => {
+                    // Every function is a leaf that just has a return address on the stack
+                    symbols.push_str(&format!("FUNC {offset:x} 100 0 {function}\n"));
+                    symbols.push_str(&format!(
+                        "STACK CFI INIT {offset:x} 100 .cfa: $rsp 8 + .ra: .cfa -8 + ^\n"
+                    ));
+                    0x7000_0000 + offset + 0x10
+                }
+                None => 0x7100_0000 + offset + 0x10,
+            }
+        })
+        .collect();
+
+    let mut stack = Section::with_endian(Endian::Little);
+    for &address in &addresses[1..] {
+        stack = stack.D64(address);
+    }
+    let stack = Memory::with_section(stack.append_repeated(0, 0x100), 0x80000);
+    let context = minidump_synth::amd64_context(Endian::Little, addresses[0], 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    );
+
+    let test_name = DumpString::new("libtest.so", Endian::Little);
+    let test_module = minidump_synth::Module::new(
+        Endian::Little,
+        0x7000_0000,
+        0x100000,
+        &test_name,
+        0,
+        0,
+        None,
+    );
+    let other_name = DumpString::new("libother.so", Endian::Little);
+    let other_module = minidump_synth::Module::new(
+        Endian::Little,
+        0x7100_0000,
+        0x100000,
+        &other_name,
+        0,
+        0,
+        None,
+    );
+
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 1;
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_module(test_module)
+        .add_module(other_module)
+        .add(test_name)
+        .add(other_name)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+
+    let mut symbol_files = HashMap::new();
+    symbol_files.insert(String::from("libtest.so"), symbols);
+    (dump, symbol_files)
+}
+
+#[tokio::test]
+async fn test_signature_rust_panic() {
+    let (dump, symbols) = signature_minidump(&[
+        Some("core::panicking::panic_fmt"),
+        Some("core::result::unwrap_failed"),
+        Some("my_crate::Parser<T>::parse::h0123456789abcdef"),
+        Some("<my_crate::Thing as core::ops::drop::Drop>::drop"),
+        Some("my_crate::main"),
+        None,
+    ]);
+    let state = read_synth_dump_with_symbols(dump, symbols).await;
+    assert_eq!(
+        state.signature().as_deref(),
+        Some(
+            "my_crate::Parser::parse | <my_crate::Thing as core::ops::drop::Drop>::drop \
+             | my_crate::main | libother.so@0x600f"
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_signature_cpp_abort() {
+    let (dump, symbols) = signature_minidump(&[
+        Some("raise"),
+        Some("abort"),
+        Some("__assert_fail"),
+        Some("Foo::bar(int) const"),
+        Some("std::vector<int, std::allocator<int> >::push_back(int const&)"),
+        Some("(anonymous namespace)::Helper::run()"),
+        Some("Foo::operator()(int)"),
+        Some("main"),
+    ]);
+    let state = read_synth_dump_with_symbols(dump, symbols).await;
+    assert_eq!(
+        state.signature().as_deref(),
+        Some(
+            "Foo::bar | std::vector::push_back | (anonymous namespace)::Helper::run \
+             | Foo::operator() | main"
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_signature_unsymbolized() {
+    let (dump, symbols) = signature_minidump(&[None]);
+    let state = read_synth_dump_with_symbols(dump, symbols).await;
+    assert_eq!(state.signature().as_deref(), Some("libother.so@0x1010"));
+}
+
+#[tokio::test]
+async fn test_signature_custom_options() {
+    use minidump_processor::signature::SignatureOptions;
+
+    let (dump, symbols) = signature_minidump(&[
+        Some("abort"),
+        Some("my_product::fatal_error(char const*)"),
+        Some("my_product::Widget::draw()"),
+        Some("my_product::Window::paint()"),
+        Some("main"),
+    ]);
+    let mut state = read_synth_dump_with_symbols(dump, symbols).await;
+    state.signature_options = SignatureOptions {
+        max_frames: 2,
+        skip_list: vec![String::from("abort"), String::from("my_product::fatal_*")],
+        skip_thunk_frames: true,
+    };
+    assert_eq!(
+        state.signature().as_deref(),
+        Some("my_product::Widget::draw | my_product::Window::paint")
+    );
+
+    // If everything is skipped, fall back to the top frame
+    state.signature_options.skip_list = vec![String::from("*")];
+    assert_eq!(state.signature().as_deref(), Some("abort"));
+}
+
+#[tokio::test]
+async fn test_signature_thunks() {
+    let (dump, symbols) = signature_minidump(&[
+        Some("memcpy"),
+        Some("memcpy@plt"),
+        Some("my_product::Buffer::append(char const*)"),
+        Some("_penter"),
+        Some("main"),
+    ]);
+    let mut state = read_synth_dump_with_symbols(dump, symbols).await;
+    let thunks: Vec<bool> = state.threads[0]
+        .frames
+        .iter()
+        .map(|frame| frame.is_thunk)
+        .collect();
+    assert_eq!(thunks, [false, true, false, true, false]);
+    // The thunks are only marked, the frames are all still there
+    assert_eq!(state.threads[0].frames.len(), 5);
+    assert_eq!(
+        state.signature().as_deref(),
+        Some("memcpy | my_product::Buffer::append | main")
+    );
+
+    state.signature_options.skip_thunk_frames = false;
+    assert_eq!(
+        state.signature().as_deref(),
+        Some("memcpy | memcpy@plt | my_product::Buffer::append | _penter | main")
+    );
+}
+
+#[tokio::test]
+async fn test_merge_thunk_frames() {
+    let (dump, symbols) = signature_minidump(&[
+        Some("pthread_mutex_lock@plt"),
+        Some("my_product::Lock::acquire()"),
+        Some("free@plt"),
+        Some("my_product::Buffer::~Buffer()"),
+        Some("main"),
+    ]);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let symbolizer = Symbolizer::new(string_symbol_supplier(symbols));
+    let mut options = ProcessorOptions::default();
+    options.merge_thunk_frames = true;
+
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    let stack = &state.threads[0];
+    let functions: Vec<&str> = stack
+        .frames
+        .iter()
+        .map(|frame| frame.function_name.as_deref().unwrap())
+        .collect();
+    // The first frame is kept even though it's a thunk, there's nothing to merge it into
+    assert_eq!(
+        functions,
+        [
+            "pthread_mutex_lock@plt",
+            "my_product::Lock::acquire()",
+            "my_product::Buffer::~Buffer()",
+            "main",
+        ]
+    );
+    assert!(stack.frames[0].is_thunk);
+    assert!(stack.raw_frames.is_none());
+    let report = state.to_json_report();
+    assert_eq!(report.threads[0].frame_count, 4);
+    assert!(report.threads[0].frames[0].is_thunk);
+    assert!(!report.threads[0].frames[1].is_thunk);
+    assert!(report.threads[0].raw_frames.is_none());
+
+    let mut options = ProcessorOptions::default();
+    options.merge_thunk_frames = true;
+    options.keep_raw_frames = true;
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    assert_eq!(state.threads[0].frames.len(), 4);
+    let report = state.to_json_report();
+    let raw_frames = report.threads[0].raw_frames.as_ref().unwrap();
+    assert_eq!(raw_frames.len(), 5);
+    assert_eq!(raw_frames[2].frame, 2);
+    assert_eq!(raw_frames[2].function.as_deref(), Some("free@plt"));
+    assert!(raw_frames[2].is_thunk);
+    assert_eq!(
+        report.threads[0].frames[2].function.as_deref(),
+        Some("my_product::Buffer::~Buffer()")
+    );
+}
+
+#[tokio::test]
+async fn test_extra_annotations() {
+    use minidump_processor::extra::parse_extra;
+
+    let none = MemoryProtection::PAGE_NOACCESS;
+    let regions = [
+        (
+            0x20000,
+            0x30000,
+            MemoryState::MEM_COMMIT,
+            MemoryProtection::PAGE_READWRITE,
+        ),
+        (0x60000, 0x100000, MemoryState::MEM_RESERVE, none),
+    ];
+    let process = |annotations: &str| {
+        let dump = memory_usage_minidump(&regions);
+        let mut options = ProcessorOptions::default();
+        options.extra_annotations = parse_extra(annotations).unwrap();
+        async move {
+            let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+            minidump_processor::process_minidump_with_options(
+                &dump,
+                &Symbolizer::new(simple_symbol_supplier(vec![])),
+                options,
+            )
+            .await
+            .unwrap()
+        }
+    };
+
+    // Without free regions, there's no telling how much of the address space is left
+    let state = process("").await;
+    assert!(!state.memory_usage.as_ref().unwrap().likely_oom);
+    assert_eq!(state.dump_kind, DumpKind::Requested);
+    assert!(state.to_json_report().crash_annotations.is_none());
+
+    // Only 0x10000 bytes are left after the committed and reserved memory
+    let state = process(
+        r#"{"TotalVirtualMemory":"1310720","AsyncShutdownTimeout":"{\"phase\":\"xpcom-will-shutdown\"}","ProductName":"Firefox"}"#,
+    )
+    .await;
+    assert!(state.memory_usage.as_ref().unwrap().likely_oom);
+    // A hang, even though the dump was requested
+    assert_eq!(state.dump_kind, DumpKind::Hang);
+    let report = state.to_json_report();
+    let annotations = report.crash_annotations.unwrap();
+    assert_eq!(annotations["ProductName"], "Firefox");
+    assert_eq!(annotations["TotalVirtualMemory"], "1310720");
+    let mut human_output = Vec::new();
+    state.print(&mut human_output).unwrap();
+    let human = String::from_utf8(human_output).unwrap();
+    assert!(human.contains("Crash annotations:\n"), "{}", human);
+    assert!(human.contains("  ProductName=Firefox\n"), "{}", human);
+
+    // The notes are about the crash, so it needs an exception
+    let (dump, symbols) = signature_minidump(&[Some("main")]);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let mut options = ProcessorOptions::default();
+    options.extra_annotations =
+        parse_extra("MemoryReport=1\nAsyncShutdownTimeout={\"phase\":\"profile-before-change\"}\n")
+            .unwrap();
+    let state = minidump_processor::process_minidump_with_options(
+        &dump,
+        &Symbolizer::new(string_symbol_supplier(symbols)),
+        options,
+    )
+    .await
+    .unwrap();
+    assert_eq!(state.dump_kind, DumpKind::Crash);
+    let notes: Vec<String> = state
+        .exception_info
+        .as_ref()
+        .unwrap()
+        .notes
+        .iter()
+        .map(|note| note.to_string())
+        .collect();
+    assert_eq!(
+        notes,
+        [
+            "a memory report was saved with the crash",
+            "the process was killed for hanging during shutdown (in profile-before-change)",
+        ]
+    );
+    let report = state.to_json_report();
+    let kinds: Vec<&str> = report
+        .crash_info
+        .notes
+        .iter()
+        .flatten()
+        .map(|note| note.kind.as_str())
+        .collect();
+    assert_eq!(kinds, ["MemoryReport", "AsyncShutdownTimeout"]);
+}
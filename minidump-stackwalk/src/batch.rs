@@ -0,0 +1,259 @@
+//! `--batch` mode: processing a whole directory of minidumps in one process.
+//!
+//! All the dumps share one [`Symbolizer`], so symbols only have to be located,
+//! downloaded, and parsed once for the whole batch.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use minidump::*;
+use minidump_processor::{PrintOptions, ProcessState, ProcessorOptions};
+use minidump_unwind::{
+    jit::JitUnwindInfoProvider, native_symbol_supplier, MultiSymbolProvider, Symbolizer,
+};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{debug, error};
+
+/// How to process a minidump: the options and the symbol providers.
+///
+/// This is also used by `--compare`, to process the minidumps it's given.
+pub struct DumpProcessor {
+    /// The options to process each dump with (only the `evil_json` path is
+    /// filled in separately, because it's borrowed).
+    pub options: ProcessorOptions<'static>,
+    pub evil_json: Option<PathBuf>,
+    /// Whether to use local debug info, and the directories to also look for it in.
+    pub local_debuginfo: Option<Vec<PathBuf>>,
+    pub jit_unwind_info: Option<Arc<JitUnwindInfoProvider>>,
+    pub symbolizer: Option<Arc<Symbolizer>>,
+}
+
+/// How to process and report every minidump of a batch.
+pub struct BatchConfig {
+    pub processor: DumpProcessor,
+    /// Write JSON reports instead of human-readable ones.
+    pub json: bool,
+    pub pretty: bool,
+    /// How human-readable reports are printed.
+    pub print_options: PrintOptions,
+    /// Where the report of each dump is written to.
+    pub output_dir: PathBuf,
+}
+
+/// What we learned from one dump of the batch, for the summary.
+struct DumpOutcome {
+    crash_reason: Option<String>,
+    signature: Option<String>,
+}
+
+/// The outcome of a batch.
+pub struct BatchSummary {
+    dumps: usize,
+    crash_reasons: HashMap<String, usize>,
+    signatures: HashMap<String, usize>,
+    /// The file name of each dump that failed and why, sorted by file name.
+    failures: Vec<(String, String)>,
+}
+
+impl BatchSummary {
+    /// Whether every dump of the batch failed (including if there were none).
+    pub fn all_failed(&self) -> bool {
+        self.failures.len() == self.dumps
+    }
+
+    /// Print the summary.
+    pub fn print<T: Write>(&self, f: &mut T) -> std::io::Result<()> {
+        writeln!(
+            f,
+            "Processed {} of {} minidumps ({} failed)",
+            self.dumps - self.failures.len(),
+            self.dumps,
+            self.failures.len()
+        )?;
+        print_counts(f, "Crash reasons", &self.crash_reasons)?;
+        print_counts(f, "Signatures", &self.signatures)?;
+        if !self.failures.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Failures:")?;
+            for (name, error) in &self.failures {
+                writeln!(f, "  {name}: {error}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Print `counts`, most common first.
+fn print_counts<T: Write>(
+    f: &mut T,
+    heading: &str,
+    counts: &HashMap<String, usize>,
+) -> std::io::Result<()> {
+    if counts.is_empty() {
+        return Ok(());
+    }
+    let mut counts: Vec<_> = counts.iter().collect();
+    counts.sort_by(|(a_name, a_count), (b_name, b_count)| {
+        b_count.cmp(a_count).then_with(|| a_name.cmp(b_name))
+    });
+    writeln!(f)?;
+    writeln!(f, "{heading}:")?;
+    for (name, count) in counts {
+        writeln!(f, "  {count:>6}  {name}")?;
+    }
+    Ok(())
+}
+
+/// Find the minidumps (`*.dmp` files) in `dir`, sorted by path.
+pub fn find_minidumps(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut dumps = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_dump = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("dmp"));
+        if is_dump && path.is_file() {
+            dumps.push(path);
+        }
+    }
+    dumps.sort();
+    Ok(dumps)
+}
+
+/// Process `dumps` with at most `jobs` of them in flight at once.
+///
+/// A dump that fails doesn't affect the others, it's just recorded in the summary.
+pub async fn run_batch(config: BatchConfig, dumps: Vec<PathBuf>, jobs: usize) -> BatchSummary {
+    let config = Arc::new(config);
+    let permits = Arc::new(Semaphore::new(jobs.max(1)));
+    let mut tasks = JoinSet::new();
+    for path in &dumps {
+        let config = config.clone();
+        let permits = permits.clone();
+        let path = path.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire().await.unwrap();
+            // Process the dump in a task of its own, so that if it panics we
+            // still know which dump it was.
+            let dump_path = path.clone();
+            let outcome = tokio::spawn(async move { config.process(&dump_path).await })
+                .await
+                .unwrap_or_else(|e| Err(format!("Panic - {e}")));
+            (path, outcome)
+        });
+    }
+
+    let mut summary = BatchSummary {
+        dumps: dumps.len(),
+        crash_reasons: HashMap::new(),
+        signatures: HashMap::new(),
+        failures: Vec::new(),
+    };
+    while let Some(result) = tasks.join_next().await {
+        let (path, outcome) = match result {
+            Ok(result) => result,
+            // Panics while processing are caught in the task, so this is only
+            // reached if the task itself is cancelled or panics.
+            Err(e) => {
+                summary
+                    .failures
+                    .push((String::from("<unknown>"), format!("Panic - {e}")));
+                continue;
+            }
+        };
+        let name = file_name(&path);
+        match outcome {
+            Ok(outcome) => {
+                debug!("processed {}", name);
+                let reason = outcome
+                    .crash_reason
+                    .unwrap_or_else(|| String::from("<no crash>"));
+                *summary.crash_reasons.entry(reason).or_default() += 1;
+                if let Some(signature) = outcome.signature {
+                    *summary.signatures.entry(signature).or_default() += 1;
+                }
+            }
+            Err(e) => {
+                error!("{} - {}", name, e);
+                summary.failures.push((name, e));
+            }
+        }
+    }
+    summary.failures.sort();
+    summary
+}
+
+impl DumpProcessor {
+    /// Read and process the minidump at `path`.
+    pub async fn process(&self, path: &Path) -> Result<ProcessState, String> {
+        let dump = Minidump::read_path(path)
+            .map_err(|err| format!("{} - Error reading dump: {}", err.name(), err))?;
+
+        let mut provider = MultiSymbolProvider::new();
+        if let Some(search_paths) = &self.local_debuginfo {
+            let system_info = dump.get_stream::<MinidumpSystemInfo>().map_err(|e| {
+                format!("Error getting system info stream from dump (required for local debug info): {e}")
+            })?;
+            let modules = dump.get_stream::<MinidumpModuleList>().unwrap_or_default();
+            provider.add(Box::new(
+                native_symbol_supplier(search_paths.clone())
+                    .build(&system_info, &modules)
+                    .await,
+            ));
+        }
+        if let Some(jit_unwind_info) = &self.jit_unwind_info {
+            provider.add(Box::new(jit_unwind_info.clone()));
+        }
+        if let Some(symbolizer) = &self.symbolizer {
+            provider.add(Box::new(symbolizer.clone()));
+        }
+
+        let mut options = self.options.clone();
+        options.evil_json = self.evil_json.as_deref();
+        minidump_processor::process_minidump_with_options(&dump, &provider, options)
+            .await
+            .map_err(|err| format!("{} - Error processing dump: {}", err.name(), err))
+    }
+}
+
+impl BatchConfig {
+    async fn process(&self, path: &Path) -> Result<DumpOutcome, String> {
+        let state = self.processor.process(path).await?;
+
+        let extension = if self.json { "json" } else { "txt" };
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let output_path = self.output_dir.join(format!("{stem}.{extension}"));
+        File::create(&output_path)
+            .and_then(|file| {
+                let mut f = BufWriter::new(file);
+                if self.json {
+                    state
+                        .print_json(&mut f, self.pretty)
+                        .map_err(std::io::Error::from)?;
+                } else {
+                    state.print_with_options(&mut f, &self.print_options)?;
+                }
+                f.flush()
+            })
+            .map_err(|e| format!("Error writing {}: {}", output_path.display(), e))?;
+
+        Ok(DumpOutcome {
+            crash_reason: state
+                .exception_info
+                .as_ref()
+                .map(|info| info.reason.to_string()),
+            signature: state.signature(),
+        })
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
@@ -0,0 +1,224 @@
+//! `--compare` mode: what's different between two reports of a crash.
+//!
+//! Each report is either a JSON report minidump-stackwalk wrote earlier, or a
+//! minidump that's processed now. So this shows what new symbols (or a new
+//! version of minidump-stackwalk) change about the same dump, or how two dumps
+//! of the same crash differ.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use minidump_processor::report::{Frame, Module, Report};
+
+use crate::batch::DumpProcessor;
+
+/// Get the report of `path`, processing it first if it's a minidump.
+pub async fn load_report(path: &Path, processor: &DumpProcessor) -> Result<Report, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+    // The signature of a minidump, in either byte order.
+    if bytes.starts_with(b"MDMP") || bytes.starts_with(b"PMDM") {
+        let state = processor.process(path).await?;
+        Ok(state.to_json_report())
+    } else {
+        serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Error reading {} as a JSON report: {}", path.display(), e))
+    }
+}
+
+/// The differences between two reports, as the lines of each section of the diff.
+#[derive(Debug, Default)]
+pub struct Comparison {
+    crash_info: Vec<String>,
+    crashing_thread: Vec<String>,
+    modules: Vec<String>,
+}
+
+impl Comparison {
+    pub fn new(a: &Report, b: &Report) -> Comparison {
+        let mut comparison = Comparison::default();
+        comparison.compare_crash_info(a, b);
+        comparison.compare_crashing_threads(a, b);
+        comparison.compare_modules(a, b);
+        comparison
+    }
+
+    /// Whether the reports are the same (as far as we compare them).
+    pub fn is_empty(&self) -> bool {
+        self.crash_info.is_empty() && self.crashing_thread.is_empty() && self.modules.is_empty()
+    }
+
+    fn compare_crash_info(&mut self, a: &Report, b: &Report) {
+        let (a_info, b_info) = (&a.crash_info, &b.crash_info);
+        let lines = &mut self.crash_info;
+        changed(lines, "type", &a_info.reason, &b_info.reason);
+        changed(lines, "address", &a_info.address, &b_info.address);
+        changed(lines, "signature", &a_info.signature, &b_info.signature);
+        changed(lines, "category", &a_info.category, &b_info.category);
+        changed(lines, "assertion", &a_info.assertion, &b_info.assertion);
+        changed(
+            lines,
+            "dump kind",
+            &Some(&a_info.dump_kind),
+            &Some(&b_info.dump_kind),
+        );
+        let thread_id = |report: &Report| {
+            let thread = report.crashing_thread.as_ref()?;
+            Some(format!("{:#x}", thread.thread.thread_id))
+        };
+        changed(lines, "crashing thread", &thread_id(a), &thread_id(b));
+    }
+
+    /// Compare the frames of the crashing threads, matched by index.
+    fn compare_crashing_threads(&mut self, a: &Report, b: &Report) {
+        let frames = |report: &Report| {
+            report
+                .crashing_thread
+                .as_ref()
+                .map(|thread| thread.thread.frames.as_slice())
+                .unwrap_or_default()
+                .iter()
+                .map(describe_frame)
+                .collect::<Vec<_>>()
+        };
+        let (a_frames, b_frames) = (frames(a), frames(b));
+        for idx in 0..a_frames.len().max(b_frames.len()) {
+            let (a_frame, b_frame) = (a_frames.get(idx), b_frames.get(idx));
+            if a_frame == b_frame {
+                continue;
+            }
+            self.crashing_thread.push(format!("frame {idx}:"));
+            if let Some(a_frame) = a_frame {
+                self.crashing_thread.push(format!("  - {a_frame}"));
+            }
+            if let Some(b_frame) = b_frame {
+                self.crashing_thread.push(format!("  + {b_frame}"));
+            }
+        }
+    }
+
+    /// Compare the modules, matched by file name.
+    fn compare_modules(&mut self, a: &Report, b: &Report) {
+        let (a_modules, b_modules) = (modules_by_name(a), modules_by_name(b));
+        for a_module in &a.modules {
+            let name = &a_module.filename;
+            let Some(b_module) = b_modules.get(name.as_str()) else {
+                self.modules.push(format!("- {name}"));
+                continue;
+            };
+            let mut changes = Vec::new();
+            changed(
+                &mut changes,
+                "version",
+                &a_module.version,
+                &b_module.version,
+            );
+            changed(
+                &mut changes,
+                "debug id",
+                &Some(&a_module.debug_id),
+                &Some(&b_module.debug_id),
+            );
+            changed(
+                &mut changes,
+                "code id",
+                &Some(&a_module.code_id),
+                &Some(&b_module.code_id),
+            );
+            changed(
+                &mut changes,
+                "symbols",
+                &Some(symbols_status(a_module)),
+                &Some(symbols_status(b_module)),
+            );
+            for change in changes {
+                self.modules.push(format!("{name} {change}"));
+            }
+        }
+        for b_module in &b.modules {
+            if !a_modules.contains_key(b_module.filename.as_str()) {
+                self.modules.push(format!("+ {}", b_module.filename));
+            }
+        }
+    }
+
+    /// Print the differences, with `a_name` and `b_name` as the names of the reports.
+    pub fn print<T: Write>(&self, f: &mut T, a_name: &str, b_name: &str) -> std::io::Result<()> {
+        writeln!(f, "--- {a_name}")?;
+        writeln!(f, "+++ {b_name}")?;
+        if self.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "No differences")?;
+            return Ok(());
+        }
+        let sections = [
+            ("Crash info", &self.crash_info),
+            ("Crashing thread", &self.crashing_thread),
+            ("Modules", &self.modules),
+        ];
+        for (heading, lines) in sections {
+            if lines.is_empty() {
+                continue;
+            }
+            writeln!(f)?;
+            writeln!(f, "{heading}:")?;
+            for line in lines {
+                writeln!(f, "  {line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Add a line to `lines` if the value of the field `name` changed.
+fn changed<T: AsRef<str>>(lines: &mut Vec<String>, name: &str, a: &Option<T>, b: &Option<T>) {
+    let (a, b) = (a.as_ref().map(T::as_ref), b.as_ref().map(T::as_ref));
+    if a != b {
+        lines.push(format!(
+            "{name}: {} -> {}",
+            a.unwrap_or("<none>"),
+            b.unwrap_or("<none>")
+        ));
+    }
+}
+
+/// Describe where a frame is, and how it was found.
+fn describe_frame(frame: &Frame) -> String {
+    let mut description = match (&frame.module, &frame.function) {
+        (Some(module), Some(function)) => match &frame.function_offset {
+            Some(offset) => format!("{module}!{function} + {offset}"),
+            None => format!("{module}!{function}"),
+        },
+        (Some(module), None) => format!(
+            "{module} + {}",
+            frame.module_offset.as_deref().unwrap_or("?")
+        ),
+        (None, _) => frame.offset.clone(),
+    };
+    if let (Some(file), Some(line)) = (&frame.file, frame.line) {
+        description.push_str(&format!(" [{file} : {line}]"));
+    }
+    description.push_str(&format!(" ({})", frame.trust));
+    description
+}
+
+fn modules_by_name(report: &Report) -> HashMap<&str, &Module> {
+    report
+        .modules
+        .iter()
+        .map(|module| (module.filename.as_str(), module))
+        .collect()
+}
+
+fn symbols_status(module: &Module) -> &'static str {
+    if module.corrupt_symbols {
+        "corrupt"
+    } else if module.loaded_symbols {
+        "loaded"
+    } else if module.missing_symbols {
+        "missing"
+    } else {
+        "not loaded"
+    }
+}
@@ -2,30 +2,42 @@
 // file at the top-level directory of this distribution.
 
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::ops::Deref;
 use std::panic;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use minidump::auxiliary::AuxiliaryStreams;
 use minidump::*;
+use minidump_processor::extra::parse_extra;
+use minidump_processor::source_context::SourceContextOptions;
 use minidump_processor::{
-    PendingProcessorStatSubscriptions, PendingProcessorStats, ProcessorOptions,
+    PendingProcessorStatSubscriptions, PendingProcessorStats, PrintOptions, ProcessorOptions,
 };
 use minidump_unwind::{
-    debuginfo::DebugInfoSymbolProvider, http_symbol_supplier, simple_symbol_supplier,
-    MultiSymbolProvider, SymbolProvider, Symbolizer,
+    http_symbol_supplier_with_options, jit::JitUnwindInfoProvider, native_symbol_supplier,
+    simple_symbol_supplier, HttpOptions, MultiSymbolProvider, SymbolProvider, SymbolServerHeader,
+    Symbolizer,
 };
 
+use batch::{find_minidumps, run_batch, BatchConfig, DumpProcessor};
 use clap::{
     builder::{PossibleValuesParser, TypedValueParser},
     ArgGroup, CommandFactory, Parser,
 };
+use compare::{load_report, Comparison};
 use tracing::error;
 use tracing::level_filters::LevelFilter;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::EnvFilter;
+
+mod batch;
+mod compare;
 
 /// Analyzes minidumps and produces a report (either human-readable or JSON)
 ///
@@ -61,6 +73,7 @@ use tracing::level_filters::LevelFilter;
     "human",
     "cyborg",
     "dump",
+    "dump_json",
     "help_markdown",
 ])))]
 #[clap(override_usage("minidump-stackwalk [FLAGS] [OPTIONS] <minidump> [--] [symbols-path]..."))]
@@ -104,6 +117,31 @@ struct Cli {
     #[arg(long)]
     dump: bool,
 
+    /// Dump the 'raw' contents of the minidump as JSON
+    ///
+    /// This is the --dump output as a JSON object: the minidump's header, and the
+    /// fields of every stream in its stream directory. Unlike --dump, it doesn't
+    /// include the contents of memory or the CPU contexts of threads. It has no
+    /// documented schema, and will change along with the minidump crate.
+    ///
+    /// Can be pretty-printed with --pretty
+    #[arg(long)]
+    dump_json: bool,
+
+    /// Write a minidump of just the thread THREAD_ID to --output-file, instead of processing
+    ///
+    /// The new minidump has the thread with its context and stack memory, the exception
+    /// (if it happened on this thread), the system info, and the module list: what's
+    /// needed to walk the thread's stack, which processing the new minidump gives the same
+    /// result for. It doesn't have the memory or the threads of the rest of the process,
+    /// so it's much smaller and easier to share (e.g. to attach to a bug report).
+    ///
+    /// THREAD_ID can be decimal or hex (with a 0x prefix).
+    #[arg(long, value_name = "THREAD_ID", value_parser = parse_thread_id)]
+    #[arg(requires = "output_file")]
+    #[arg(conflicts_with_all = ["batch", "compare", "cyborg", "dump", "dump_json", "json", "json_file", "human_file"])]
+    extract_thread: Option<u32>,
+
     /// Print --help but formatted as markdown (used for generating docs)
     #[arg(long, hide = true)]
     help_markdown: bool,
@@ -120,7 +158,7 @@ struct Cli {
     ///  
     /// stable-all enables: nothing (currently identical to stable-basic)
     ///  
-    /// unstable-all enables: `--recover-function-args`
+    /// unstable-all enables: `--recover-function-args`, `--recover-arguments`
     ///  
     /// minidump-stackwalk wants to be a reliable and stable tool, but we also want to be able
     /// to introduce new features which may be experimental or expensive. To balance these two
@@ -144,6 +182,13 @@ struct Cli {
     /// The unwinder has been heavily instrumented with `trace` logging, so if you want to
     /// debug why an unwind happened the way it did, --verbose=trace is very useful
     /// (all unwinder logging will be prefixed with `unwind:`).
+    ///
+    /// Logging happens within spans for the minidump (`process_minidump`), each thread
+    /// (`walk_thread{thread_id}`), each unwound frame (`unwind_frame{ip,module,trust}`),
+    /// and each symbol file (`load_symbols{module}`, `fetch_symbols`, `parse_symbols`). If
+    /// the RUST_LOG environment variable is set, it's used as a tracing-subscriber filter
+    /// on top of this level, so e.g. `RUST_LOG='[walk_thread{thread_id=5}]=trace'` only
+    /// enables trace logging for the walk of thread 5.
     #[arg(long)]
     #[arg(default_value = "error")]
     #[arg(value_parser = PossibleValuesParser::new(["off", "error", "warn", "info", "debug", "trace"]).map(|v| LevelFilter::from_str(&v).unwrap()))]
@@ -153,18 +198,42 @@ struct Cli {
     #[arg(long)]
     output_file: Option<PathBuf>,
 
+    /// Additionally write a --json report to this file
+    ///
+    /// This can be combined with any other output format (and --human-file), so that
+    /// a single invocation can produce several reports without processing the minidump
+    /// more than once. Respects --pretty.
+    #[arg(long)]
+    json_file: Option<PathBuf>,
+
+    /// Additionally write a --human report to this file
+    ///
+    /// This can be combined with any other output format (and --json-file), so that
+    /// a single invocation can produce several reports without processing the minidump
+    /// more than once. Respects --brief.
+    #[arg(long)]
+    human_file: Option<PathBuf>,
+
     /// Where to write logs to (if unspecified, stderr is used)
     #[arg(long)]
     log_file: Option<PathBuf>,
 
+    /// The format of the logs
+    ///
+    /// `json` writes every event as a JSON object on its own line, with the spans it
+    /// happened in and their fields.
+    #[arg(long, default_value = "text")]
+    #[arg(value_parser = ["text", "json"])]
+    trace_format: String,
+
     /// Prevent the output/logging from using ANSI coloring
     ///
-    /// Output written to a file via --log-file, --output-file, or --cyborg
-    /// is always --no-color, so this just forces stdout/stderr printing.
+    /// Output written to a file via --log-file, --output-file, --json-file, --human-file,
+    /// or --cyborg is always --no-color, so this just forces stdout/stderr printing.
     #[arg(long)]
     no_color: bool,
 
-    /// Pretty-print --json output
+    /// Pretty-print --json or --dump-json output
     #[arg(long)]
     pretty: bool,
 
@@ -176,6 +245,73 @@ struct Cli {
     #[arg(long)]
     brief: bool,
 
+    /// Print the registers of every thread in the --human report
+    ///
+    /// By default only the frames of each thread have their registers printed. With this, each
+    /// thread also gets the registers of its context in the minidump's thread list (before its
+    /// frames), or why it has none. The JSON report always has them, as
+    /// `threads.N.last_known_registers`.
+    #[arg(long)]
+    registers_all_threads: bool,
+
+    /// Don't group the threads with the same stacks
+    ///
+    /// By default, threads whose frames are in the same functions are grouped, and the groups
+    /// (and what their threads are blocked on, if they're waiting) are listed before the
+    /// threads in the human report, and as `thread_groups` in the JSON report.
+    #[arg(long)]
+    no_thread_grouping: bool,
+
+    /// Remove the frames of thunks from the threads
+    ///
+    /// Calls through PLT stubs (`foo@plt`), import thunks (`__imp_foo`), and profiling hooks
+    /// (`_penter`, `__fentry__`) show up as a frame of their own next to the function they
+    /// forward to. With this they're left out of both reports, except for the first frame of a
+    /// thread. Either way they're marked with `is_thunk` in the JSON report, and never part of
+    /// the signature.
+    #[arg(long)]
+    merge_thunk_frames: bool,
+
+    /// With --merge-thunk-frames, keep the frames as they were in the JSON report
+    ///
+    /// The frames of threads that had thunk frames merged are then also in `threads.N.raw_frames`.
+    #[arg(long, requires = "merge_thunk_frames")]
+    raw_frames: bool,
+
+    /// A stream type of the minidump that holds a Java exception, as printed by Java
+    ///
+    /// Crash reporters of Android apps often add the Java exception that crashed the app
+    /// to the minidump in a vendor stream of their own. Its message and frames are shown
+    /// after the threads in the human report, and as `auxiliary_crashes` in the JSON
+    /// report. If the stream isn't in the format Java prints exceptions in, its text is
+    /// shown as it is.
+    ///
+    /// STREAM_TYPE can be decimal or hex (with a 0x prefix). Can be provided multiple times.
+    #[arg(long, value_name = "STREAM_TYPE", value_parser = parse_stream_type)]
+    java_exception_stream: Vec<u32>,
+
+    /// A `.extra` file of crash annotations to add to the report
+    ///
+    /// Breakpad-based crash reporters write the annotations of a crash into a `.extra` file
+    /// next to the minidump, either as a JSON object or as `key=value` lines (which is
+    /// detected). They're shown after the environment in the human report, and as
+    /// `crash_annotations` in the JSON report. A few of them also inform the processing:
+    /// `MemoryReport` and `AsyncShutdownTimeout` add `crash_info.notes`, and
+    /// `TotalVirtualMemory` is used to decide whether `memory_usage.likely_oom`.
+    #[arg(long, value_name = "PATH", requires = "minidump")]
+    extra_file: Option<PathBuf>,
+
+    /// Leave out what the minidump's memory says about the user, for reports to share
+    ///
+    /// The environment, the recovered function arguments and the macOS crash info are
+    /// withheld, and the crash message, the assertion, the messages of Java exceptions, the
+    /// crash annotations with names like `*URL*`, `*Email*`, `*Comment*` or `*Path*` and
+    /// the Crashpad list annotations (which have no names) are replaced with hashes. The JSON report has `redacted: true`, and lists what was
+    /// left out in `redacted_sections`. Can't be used with --dump, which prints the
+    /// minidump's memory.
+    #[arg(long, conflicts_with = "dump")]
+    redact: bool,
+
     /// Disable all interactive progress feedback
     ///
     /// We'll generally try to auto-detect when this should be disabled, but this is here in
@@ -197,9 +333,56 @@ struct Cli {
     #[arg(long)]
     recover_function_args: bool,
 
+    /// **UNSTABLE** Heuristically read the parameters of x86 frames from the stack
+    ///
+    /// The number of parameters comes from the parameter size in the frame's symbols. The
+    /// values are shown as `heuristic_parameters`, since the function may have overwritten them.
+    #[arg(long)]
+    recover_arguments: bool,
+
     /// Use debug information from local files referred to by the minidump, if present.
+    ///
+    /// If a directory is given (`--use-local-debuginfo=DIR`), native debug files (PDB, DWARF)
+    /// are also looked up there, for when the files the minidump refers to aren't on this
+    /// machine. A module's debug file or binary is looked up in the directory by its file name,
+    /// and files whose debug id or build id doesn't match the module are ignored.
+    ///
+    /// Can be provided multiple times, and each directory is tried in order.
+    #[arg(long, value_name = "DIR", num_args = 0..=1, require_equals = true)]
+    use_local_debuginfo: Option<Vec<PathBuf>>,
+
+    /// A file of STACK CFI records describing how to unwind JITed code
+    ///
+    /// Code generated at runtime isn't in any module, so there are no symbol files for it.
+    /// A JIT can instead write breakpad `STACK CFI INIT` and `STACK CFI` records with absolute
+    /// addresses for the code it generated to this file, which are used to unwind frames
+    /// executing code that isn't in any module.
     #[arg(long)]
-    use_local_debuginfo: bool,
+    jit_unwind_info: Option<PathBuf>,
+
+    /// A local checkout to find source files in, to show the source around each frame
+    ///
+    /// With this (or --source-map), the lines around the line each frame is executing are
+    /// shown under the frame in the human-readable report, and included as `source_context`
+    /// in the JSON report. Frames whose source file can't be found are left alone.
+    ///
+    /// The path a symbol file gives for a source file is tried under this directory with every
+    /// suffix of it (longest first), so a checkout of the same project finds the files even
+    /// if it was built elsewhere. Backslashes in paths are treated as separators.
+    ///
+    /// Can be provided multiple times, and each directory is tried in order.
+    #[arg(long, value_name = "DIR")]
+    source_root: Vec<PathBuf>,
+
+    /// Map a prefix of source file paths in symbol files to a local directory
+    ///
+    /// e.g. `--source-map /builds/worker/checkouts/gecko=/home/me/gecko`. Only whole path
+    /// components are matched (case-insensitively for Windows paths). See --source-root for
+    /// what this does with the source files.
+    ///
+    /// Can be provided multiple times, and the first matching prefix is used.
+    #[arg(long, value_name = "PREFIX=DIR", value_parser = parse_source_map)]
+    source_map: Vec<(String, PathBuf)>,
 
     /// base URL from which URLs to symbol files can be constructed
     ///
@@ -253,8 +436,86 @@ struct Cli {
     #[arg(long, default_value_t = 1000)]
     symbols_download_timeout_secs: u64,
 
+    /// The maximum amount of time (in seconds) spent fetching a symbol file from a server
+    ///
+    /// Unlike --symbols-download-timeout-secs, this covers all the retries of a download
+    /// (including the delays between them), not each attempt. There's no limit by default.
+    #[arg(long, value_name = "SECS")]
+    symbols_download_total_timeout_secs: Option<u64>,
+
+    /// How many times to retry a symbol download that failed because of a server error
+    ///
+    /// Only 5xx and 429 responses, timeouts, and connection errors are retried, with an
+    /// exponentially increasing delay between attempts. Any other error response (such as
+    /// 404) means the symbols don't exist.
+    #[arg(long, default_value_t = 0)]
+    symbols_download_retries: u32,
+
+    /// A proxy to use for all symbol server requests
+    ///
+    /// If this isn't specified, the HTTP_PROXY, HTTPS_PROXY, and ALL_PROXY environment
+    /// variables are used. NO_PROXY is honored either way.
+    #[arg(long)]
+    symbols_proxy: Option<String>,
+
+    /// An HTTP header to send with symbol server requests, e.g. to authenticate
+    ///
+    /// The header is `Name: value`, and is sent to every --symbols-url. To only send it to
+    /// some, prefix it with their url (and a space): `https://symbols.example.com/ Name: value`
+    /// is only sent with the requests whose url starts with `https://symbols.example.com/`.
+    ///
+    /// To keep secrets out of the command line, a value of `env:VAR` is read from the VAR
+    /// environment variable, and `file:PATH` from the file at PATH (without its trailing
+    /// whitespace), e.g. `--symbols-url-header 'Authorization: env:SYMBOLS_AUTH'`. Header
+    /// values are never logged.
+    ///
+    /// Can be provided multiple times.
+    #[arg(long, value_name = "[URL ]NAME: VALUE", value_parser = parse_symbols_url_header)]
+    symbols_url_header: Vec<SymbolServerHeader>,
+
+    /// Process every minidump (`*.dmp` file) in this directory
+    ///
+    /// The report of each minidump is written to a file named after it in --output-dir
+    /// (`<name>.txt`, or `<name>.json` with --json), and a summary of the whole batch
+    /// (how many minidumps crashed with each crash reason and signature, and which ones
+    /// couldn't be processed) is written to --output-file (or stdout).
+    ///
+    /// All the minidumps share the same symbols, so each symbol file is only located,
+    /// downloaded, and parsed once for the whole batch. A minidump that can't be processed
+    /// doesn't stop the batch, and the exit code only indicates failure if every
+    /// minidump failed.
+    #[arg(long, value_name = "DIR", requires = "output_dir")]
+    #[arg(conflicts_with_all = ["minidump", "cyborg", "dump", "dump_json", "json_file", "human_file"])]
+    batch: Option<PathBuf>,
+
+    /// The directory to write the report of each --batch minidump to
+    #[arg(long, value_name = "DIR", requires = "batch")]
+    output_dir: Option<PathBuf>,
+
+    /// How many minidumps --batch processes at once
+    ///
+    /// Defaults to the number of CPUs.
+    #[arg(long, requires = "batch")]
+    jobs: Option<usize>,
+
+    /// Compare two reports of a crash, and print what's different between them
+    ///
+    /// Each of A and B can be a minidump (which is processed with the given symbols and
+    /// options), or a --json report written earlier. This shows what new symbols (or a
+    /// new version of minidump-stackwalk) change about a dump, or how two dumps of the
+    /// same crash differ.
+    ///
+    /// The crash info (crash reason, address, signature...), the frames of the crashing
+    /// thread (matched by index), and the modules (matched by file name) are compared.
+    ///
+    /// The exit code is 0 if there are no differences, and 3 if there are.
+    #[arg(long, num_args = 2, value_names = ["A", "B"])]
+    #[arg(conflicts_with_all = ["minidump", "batch", "cyborg", "dump", "dump_json", "json", "json_file", "human_file"])]
+    compare: Vec<PathBuf>,
+
     /// Path to the minidump file to analyze
-    minidump: PathBuf,
+    #[arg(required_unless_present_any = ["batch", "compare"])]
+    minidump: Option<PathBuf>,
 
     /// Path to a symbol file.
     ///
@@ -270,6 +531,13 @@ struct Cli {
     symbols_path_legacy: Vec<PathBuf>,
 }
 
+/// The exit code used when the minidump couldn't be read or processed.
+const EXIT_PROCESSING_FAILED: i32 = 1;
+/// The exit code used when processing succeeded but some output couldn't be written.
+const EXIT_OUTPUT_FAILED: i32 = 2;
+/// The exit code used when --compare found differences.
+const EXIT_DIFFERENCES_FOUND: i32 = 3;
+
 #[tokio::main]
 async fn main() {
     if let Err(e) = main_result().await {
@@ -277,7 +545,7 @@ async fn main() {
         // piping into another program but that program doesn't read all input.
         if e.kind() != std::io::ErrorKind::BrokenPipe {
             eprintln!("Error: {e}");
-            std::process::exit(1);
+            std::process::exit(EXIT_PROCESSING_FAILED);
         }
     }
 }
@@ -287,22 +555,26 @@ async fn main_result() -> std::io::Result<()> {
     let cli = Cli::parse();
 
     // Init the logger (and make trace logging less noisy)
-    if let Some(log_path) = &cli.log_file {
-        let log_file = File::create(log_path)?;
+    let (log_writer, ansi) = match &cli.log_file {
+        Some(log_path) => (BoxMakeWriter::new(File::create(log_path)?), false),
+        None => (BoxMakeWriter::new(std::io::stderr), !cli.no_color),
+    };
+    let filter = EnvFilter::from_default_env().add_directive(cli.verbose.into());
+    if cli.trace_format == "json" {
         tracing_subscriber::fmt::fmt()
-            .with_max_level(cli.verbose)
-            .with_target(false)
-            .without_time()
-            .with_ansi(false)
-            .with_writer(log_file)
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_env_filter(filter)
+            .with_writer(log_writer)
             .init();
     } else {
         tracing_subscriber::fmt::fmt()
-            .with_max_level(cli.verbose)
+            .with_env_filter(filter)
             .with_target(false)
             .without_time()
-            .with_ansi(!cli.no_color)
-            .with_writer(std::io::stderr)
+            .with_ansi(ansi)
+            .with_writer(log_writer)
             .init();
     }
 
@@ -339,6 +611,8 @@ async fn main_result() -> std::io::Result<()> {
 
     let temp_dir = std::env::temp_dir();
 
+    let source_context = source_context_options(&cli);
+
     let mut symbols_paths = cli.symbols_path;
     symbols_paths.extend(cli.symbols_path_legacy);
 
@@ -350,7 +624,16 @@ async fn main_result() -> std::io::Result<()> {
     // Default to env::temp_dir()
     let symbols_tmp = cli.symbols_tmp.unwrap_or(temp_dir);
 
-    let timeout = Duration::from_secs(cli.symbols_download_timeout_secs);
+    let http_options = HttpOptions {
+        timeout: Duration::from_secs(cli.symbols_download_timeout_secs),
+        total_timeout: cli
+            .symbols_download_total_timeout_secs
+            .map(Duration::from_secs),
+        retries: cli.symbols_download_retries,
+        proxy: cli.symbols_proxy,
+        headers: cli.symbols_url_header,
+        ..HttpOptions::default()
+    };
 
     // Determine the kind of output we're producing -- dump, json, human, or cyborg (both).
     // Although we have a --human argument it's mostly just there to make the documentation
@@ -359,8 +642,9 @@ async fn main_result() -> std::io::Result<()> {
     // is the implicit default, so we have to do some munging here.
     // Human is just enabled if nothing else is
     let raw_dump = cli.dump;
+    let json_dump = cli.dump_json;
     let mut json = cli.json;
-    let mut human = !json && !raw_dump;
+    let mut human = !json && !raw_dump && !json_dump;
     // Cyborg is just "desugarred" to --json --human
     if cli.cyborg.is_some() {
         human = true;
@@ -369,27 +653,161 @@ async fn main_result() -> std::io::Result<()> {
 
     // Now check if arguments that tweak the output are valid. We can't use
     // Arg::requires because clap doesn't understand --json being implicitly enabled.
-    if cli.pretty && !json {
+    if cli.pretty && !json && !json_dump && cli.json_file.is_none() {
         error!("Humans must be hideous! (The --pretty and --human flags cannot both be set)");
         std::process::exit(1);
     }
 
-    if cli.brief && !(human || raw_dump) {
-        error!("Robots cannot be brief! (The --brief flag is only valid for --human, --cyborg, and --dump)");
+    if cli.brief && !(human || raw_dump || cli.human_file.is_some()) {
+        error!("Robots cannot be brief! (The --brief flag is only valid for --human, --human-file, --cyborg, and --dump)");
         std::process::exit(1);
     }
 
-    // Pick the default options
-    let mut options = match &*cli.features {
-        "stable-basic" => ProcessorOptions::stable_basic(),
-        "stable-all" => ProcessorOptions::stable_all(),
-        "unstable-all" => ProcessorOptions::unstable_all(),
-        _ => unimplemented!("unknown --features value"),
+    let mut print_options = PrintOptions::default();
+    print_options.brief = cli.brief;
+    print_options.thread_registers = cli.registers_all_threads;
+
+    let jit_unwind_info = cli.jit_unwind_info.as_deref().map(|path| {
+        JitUnwindInfoProvider::from_path(path).unwrap_or_else(|e| {
+            error!(
+                "Error reading JIT unwind info from {}: {}",
+                path.display(),
+                e
+            );
+            std::process::exit(1);
+        })
+    });
+
+    let extra_annotations = cli
+        .extra_file
+        .as_deref()
+        .map(|path| {
+            std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|contents| parse_extra(&contents).map_err(|e| e.to_string()))
+                .unwrap_or_else(|e| {
+                    error!(
+                        "Error reading crash annotations from {}: {}",
+                        path.display(),
+                        e
+                    );
+                    std::process::exit(1);
+                })
+        })
+        .unwrap_or_default();
+
+    let symbolizer = if !cli.symbols_url.is_empty() {
+        let supplier = http_symbol_supplier_with_options(
+            symbols_paths,
+            cli.symbols_url,
+            symbols_cache,
+            symbols_tmp,
+            http_options,
+        )
+        .unwrap_or_else(|e| {
+            error!("Error setting up the symbol servers: {}", e);
+            std::process::exit(1);
+        });
+        Some(Symbolizer::new(supplier))
+    } else if !symbols_paths.is_empty() {
+        Some(Symbolizer::new(simple_symbol_supplier(symbols_paths)))
+    } else {
+        None
     };
 
+    let mut auxiliary_streams = AuxiliaryStreams::new();
+    for &stream_type in &cli.java_exception_stream {
+        auxiliary_streams.register_java(stream_type);
+    }
+
+    if cli.batch.is_some() || !cli.compare.is_empty() {
+        let mut options = default_options(&cli.features);
+        options.recover_function_args = cli.recover_function_args;
+        options.recover_arguments |= cli.recover_arguments;
+        options.group_threads = !cli.no_thread_grouping;
+        options.merge_thunk_frames = cli.merge_thunk_frames;
+        options.keep_raw_frames = cli.raw_frames;
+        options.source_context = source_context;
+        options.auxiliary_streams = auxiliary_streams;
+        options.privacy_mode = cli.redact;
+        let processor = DumpProcessor {
+            options,
+            evil_json: cli.evil_json.clone(),
+            local_debuginfo: cli.use_local_debuginfo.clone(),
+            jit_unwind_info: jit_unwind_info.map(Arc::new),
+            symbolizer: symbolizer.map(Arc::new),
+        };
+
+        let mut stdout;
+        let mut output_f;
+        let mut output: &mut dyn Write = if let Some(output_path) = &cli.output_file {
+            output_f = File::create(output_path)?;
+            &mut output_f
+        } else {
+            stdout = std::io::stdout();
+            &mut stdout
+        };
+
+        if let [a_path, b_path] = cli.compare.as_slice() {
+            let mut reports = Vec::new();
+            for path in [a_path, b_path] {
+                match load_report(path, &processor).await {
+                    Ok(report) => reports.push(report),
+                    Err(e) => {
+                        error!("{}", e);
+                        std::process::exit(EXIT_PROCESSING_FAILED);
+                    }
+                }
+            }
+            let comparison = Comparison::new(&reports[0], &reports[1]);
+            comparison.print(
+                &mut output,
+                &a_path.display().to_string(),
+                &b_path.display().to_string(),
+            )?;
+            if !comparison.is_empty() {
+                std::process::exit(EXIT_DIFFERENCES_FOUND);
+            }
+            return Ok(());
+        }
+
+        // Guaranteed by clap
+        let batch_dir = cli.batch.as_deref().unwrap();
+        let output_dir = cli.output_dir.as_deref().unwrap();
+        let config = BatchConfig {
+            processor,
+            json,
+            pretty: cli.pretty,
+            print_options,
+            output_dir: output_dir.to_owned(),
+        };
+        let dumps = find_minidumps(batch_dir)?;
+        std::fs::create_dir_all(output_dir)?;
+        let jobs = cli.jobs.unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+        });
+        let summary = run_batch(config, dumps, jobs).await;
+        summary.print(&mut output)?;
+        if summary.all_failed() {
+            std::process::exit(EXIT_PROCESSING_FAILED);
+        }
+        return Ok(());
+    }
+
+    // Pick the default options
+    let mut options = default_options(&cli.features);
+
     // Now overload the defaults
     options.evil_json = cli.evil_json.as_deref();
     options.recover_function_args = cli.recover_function_args;
+    options.recover_arguments |= cli.recover_arguments;
+    options.group_threads = !cli.no_thread_grouping;
+    options.merge_thunk_frames = cli.merge_thunk_frames;
+    options.keep_raw_frames = cli.raw_frames;
+    options.source_context = source_context;
+    options.auxiliary_streams = auxiliary_streams;
+    options.extra_annotations = extra_annotations;
+    options.privacy_mode = cli.redact;
 
     // Register for instractive updates, if we want them
     let interactive_enabled = !json && !cli.no_interactive && cli.output_file.is_none();
@@ -404,7 +822,9 @@ async fn main_result() -> std::io::Result<()> {
 
     // Ok now let's do the thing!!!!
 
-    match Minidump::read_path(cli.minidump) {
+    // Guaranteed by clap
+    let minidump_path = cli.minidump.unwrap();
+    match Minidump::read_path(minidump_path) {
         Ok(dump) => {
             let mut stdout;
             let mut output_f;
@@ -418,16 +838,41 @@ async fn main_result() -> std::io::Result<()> {
                 &mut stdout
             };
 
+            // --extract-thread mode
+            if let Some(thread_id) = cli.extract_thread {
+                return match dump.extract_thread(thread_id) {
+                    Ok(extracted) => output.write_all(&extracted),
+                    Err(err) => {
+                        error!(
+                            "{} - Error extracting thread {:#x}: {}",
+                            err.name(),
+                            thread_id,
+                            err
+                        );
+                        std::process::exit(EXIT_PROCESSING_FAILED);
+                    }
+                };
+            }
+
             // minidump_dump mode
             if raw_dump {
                 return print_minidump_dump(&dump, &mut output, cli.brief);
             }
+            if json_dump {
+                let value = dump.to_json_value();
+                if cli.pretty {
+                    serde_json::to_writer_pretty(&mut output, &value)?;
+                } else {
+                    serde_json::to_writer(&mut output, &value)?;
+                }
+                return writeln!(output);
+            }
 
             let mut provider = MultiSymbolProvider::new();
 
             let modules = dump.get_stream::<MinidumpModuleList>().unwrap_or_default();
 
-            if cli.use_local_debuginfo {
+            if let Some(search_paths) = &cli.use_local_debuginfo {
                 let system_info = match dump.get_stream::<MinidumpSystemInfo>() {
                     Err(e) => {
                         error!("Error getting system info stream from dump (required for local debug info): {}", e);
@@ -436,22 +881,18 @@ async fn main_result() -> std::io::Result<()> {
                     Ok(s) => s,
                 };
                 provider.add(Box::new(
-                    DebugInfoSymbolProvider::new(&system_info, &modules).await,
+                    native_symbol_supplier(search_paths.clone())
+                        .build(&system_info, &modules)
+                        .await,
                 ));
             }
 
-            if !cli.symbols_url.is_empty() {
-                provider.add(Box::new(Symbolizer::new(http_symbol_supplier(
-                    symbols_paths,
-                    cli.symbols_url,
-                    symbols_cache,
-                    symbols_tmp,
-                    timeout,
-                ))));
-            } else if !symbols_paths.is_empty() {
-                provider.add(Box::new(Symbolizer::new(simple_symbol_supplier(
-                    symbols_paths,
-                ))));
+            if let Some(jit_unwind_info) = jit_unwind_info {
+                provider.add(Box::new(jit_unwind_info));
+            }
+
+            if let Some(symbolizer) = symbolizer {
+                provider.add(Box::new(symbolizer));
             }
 
             let interactive_ui = processor_stats
@@ -490,38 +931,159 @@ async fn main_result() -> std::io::Result<()> {
 
             match result {
                 Ok(state) => {
+                    // Every output is attempted even if an earlier one fails, so that
+                    // one bad path doesn't cost us all the other reports.
+                    let mut output_ok = true;
+                    let pretty = cli.pretty;
+                    let print_human =
+                        |mut f: &mut dyn Write| state.print_with_options(&mut f, &print_options);
+                    let print_json = |mut f: &mut dyn Write| {
+                        state
+                            .print_json(&mut f, pretty)
+                            .map_err(std::io::Error::from)
+                    };
+
                     // Print the human output if requested (always uses the "real" output).
                     if human {
-                        if cli.brief {
-                            state.print_brief(&mut output)?;
-                        } else {
-                            state.print(&mut output)?;
-                        }
+                        output_ok &= check_output("human output", print_human(&mut output));
                     }
 
                     // Print the json output if requested (using "cyborg" output if available).
                     if json {
                         if let Some(mut cyborg_output_f) = cyborg_output_f {
-                            state.print_json(&mut cyborg_output_f, cli.pretty)?;
+                            output_ok &=
+                                check_output("json output", print_json(&mut cyborg_output_f));
                         } else {
-                            state.print_json(&mut output, cli.pretty)?;
+                            output_ok &= check_output("json output", print_json(&mut output));
                         }
                     }
+
+                    // Write the extra report files.
+                    if let Some(path) = &cli.human_file {
+                        output_ok &= write_output_file(path, print_human);
+                    }
+                    if let Some(path) = &cli.json_file {
+                        output_ok &= write_output_file(path, print_json);
+                    }
+
+                    if !output_ok {
+                        std::process::exit(EXIT_OUTPUT_FAILED);
+                    }
                     Ok(())
                 }
                 Err(err) => {
                     error!("{} - Error processing dump: {}", err.name(), err);
-                    std::process::exit(1);
+                    std::process::exit(EXIT_PROCESSING_FAILED);
                 }
             }
         }
         Err(err) => {
             error!("{} - Error reading dump: {}", err.name(), err);
-            std::process::exit(1);
+            std::process::exit(EXIT_PROCESSING_FAILED);
+        }
+    }
+}
+
+/// A `--source-map` value, a path prefix and the directory it's mapped to.
+fn parse_source_map(mapping: &str) -> Result<(String, PathBuf), String> {
+    match mapping.split_once('=') {
+        Some((prefix, dir)) if !prefix.is_empty() && !dir.is_empty() => {
+            Ok((prefix.to_owned(), PathBuf::from(dir)))
         }
+        _ => Err(String::from("expected PREFIX=DIR")),
     }
 }
 
+fn parse_thread_id(id: &str) -> Result<u32, String> {
+    let result = match id.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => id.parse(),
+    };
+    result.map_err(|e| format!("invalid thread id: {e}"))
+}
+
+fn parse_stream_type(stream_type: &str) -> Result<u32, String> {
+    let result = match stream_type.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => stream_type.parse(),
+    };
+    result.map_err(|e| format!("invalid stream type: {e}"))
+}
+
+/// A `--symbols-url-header` value, with its value read from wherever it says.
+fn parse_symbols_url_header(arg: &str) -> Result<SymbolServerHeader, String> {
+    let (url_prefix, header) = match arg.split_once(' ') {
+        Some((url, header)) if url.contains("://") => (Some(url.to_owned()), header.trim_start()),
+        _ => (None, arg),
+    };
+    let (name, value) = match header.split_once(':') {
+        Some((name, value)) if !name.is_empty() && !name.contains(' ') => (name, value.trim()),
+        _ => return Err(String::from("expected NAME: VALUE, or URL NAME: VALUE")),
+    };
+    let value = if let Some(var) = value.strip_prefix("env:") {
+        std::env::var(var).map_err(|e| format!("can't read the {var} environment variable: {e}"))?
+    } else if let Some(path) = value.strip_prefix("file:") {
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| format!("can't read {path}: {e}"))?;
+        contents.trim_end().to_owned()
+    } else {
+        value.to_owned()
+    };
+    Ok(SymbolServerHeader {
+        url_prefix,
+        name: name.to_owned(),
+        value,
+    })
+}
+
+fn source_context_options(cli: &Cli) -> Option<SourceContextOptions> {
+    if cli.source_root.is_empty() && cli.source_map.is_empty() {
+        return None;
+    }
+    Some(SourceContextOptions {
+        roots: cli.source_root.clone(),
+        prefix_map: cli.source_map.clone(),
+        ..Default::default()
+    })
+}
+
+/// The processor options for a `--features` value.
+fn default_options(features: &str) -> ProcessorOptions<'static> {
+    match features {
+        "stable-basic" => ProcessorOptions::stable_basic(),
+        "stable-all" => ProcessorOptions::stable_all(),
+        "unstable-all" => ProcessorOptions::unstable_all(),
+        _ => unimplemented!("unknown --features value"),
+    }
+}
+
+/// Log the error of writing some output, if any, and return whether it succeeded.
+fn check_output(what: &str, result: std::io::Result<()>) -> bool {
+    match result {
+        Ok(()) => true,
+        // Broken pipes only occur from stdio, when a user is piping into another
+        // program that doesn't read all input. That's not our problem.
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => true,
+        Err(e) => {
+            error!("Error writing {what}: {e}");
+            false
+        }
+    }
+}
+
+/// Create the file at `path` and write a report to it with `print`, logging any errors.
+fn write_output_file(
+    path: &Path,
+    print: impl FnOnce(&mut dyn Write) -> std::io::Result<()>,
+) -> bool {
+    let result = File::create(path).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        print(&mut writer)?;
+        writer.flush()
+    });
+    check_output(&path.display().to_string(), result)
+}
+
 fn print_help_markdown(out: &mut dyn Write) -> std::io::Result<()> {
     let app_name = "minidump-stackwalk";
     let pretty_app_name = "minidump-stackwalk";
@@ -615,7 +1177,7 @@ fn print_help_markdown(out: &mut dyn Write) -> std::io::Result<()> {
                 writeln!(out, "#### `{line}`")?;
                 continue;
             }
-            if line == "[SYMBOLS_PATH_LEGACY]..." {
+            if line == "[MINIDUMP]" || line == "[SYMBOLS_PATH_LEGACY]..." {
                 writeln!(out, "#### `{line}`")?;
                 continue;
             }
@@ -661,14 +1223,9 @@ where
 
     // Other streams depend on these, so load them upfront.
     let system_info = dump.get_stream::<MinidumpSystemInfo>().ok();
-    let mut memory_list = dump.get_stream::<MinidumpMemoryList<'_>>().ok();
-    let mut memory64_list = dump.get_stream::<MinidumpMemory64List<'_>>().ok();
     let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
 
-    let unified_memory = memory64_list
-        .take()
-        .map(UnifiedMemoryList::Memory64)
-        .or_else(|| memory_list.take().map(UnifiedMemoryList::Memory));
+    let unified_memory = dump.get_memory();
     if let Ok(thread_list) = dump.get_stream::<MinidumpThreadList<'_>>() {
         thread_list.print(
             output,
@@ -690,12 +1247,6 @@ where
     if let Some(memory_list) = unified_memory {
         memory_list.print(output, brief)?;
     }
-    if let Some(memory_list) = memory_list {
-        memory_list.print(output, brief)?;
-    }
-    if let Some(memory64_list) = memory64_list {
-        memory64_list.print(output, brief)?;
-    }
     if let Ok(memory_info_list) = dump.get_stream::<MinidumpMemoryInfoList<'_>>() {
         memory_info_list.print(output)?;
     }
@@ -76,6 +76,37 @@ fn test_dump_brief() {
     assert_eq!(stderr, "");
 }
 
+#[test]
+fn test_dump_json() {
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--dump-json")
+        .arg("--pretty")
+        .arg("../testdata/test.dmp")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(stderr, "");
+    let dump: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(dump["header"]["signature"], "0x504d444d");
+    let streams = dump["streams"].as_array().unwrap();
+    assert_eq!(streams[0]["stream_type"], "0x3");
+    let thread_list = streams
+        .iter()
+        .find(|stream| stream["stream_type_name"] == "ThreadListStream")
+        .unwrap();
+    assert_eq!(thread_list["error"], serde_json::Value::Null);
+    let threads = thread_list["contents"]["threads"].as_array().unwrap();
+    assert_eq!(threads.len(), 2);
+    assert_eq!(threads[0]["thread_id"], "0xbf4");
+}
+
 #[test]
 fn test_json() {
     let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
@@ -378,6 +409,65 @@ fn test_cyborg() {
     assert_eq!(stderr, "");
 }
 
+#[test]
+fn test_report_files() {
+    let json_out_path = test_output("mdsw-test-report-files-out.json");
+    let human_out_path = test_output("mdsw-test-report-files-out.txt");
+    // Should be the same as --human and --json, while stdout is still --json
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--json")
+        .arg("--json-file")
+        .arg(&json_out_path)
+        .arg("--human-file")
+        .arg(&human_out_path)
+        .arg("../testdata/test.dmp")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let json_out = std::fs::read_to_string(json_out_path).unwrap();
+    let human_out = std::fs::read_to_string(human_out_path).unwrap();
+
+    assert!(output.status.success());
+    insta::assert_snapshot!("json", stdout);
+    insta::assert_snapshot!("json", json_out);
+    insta::assert_snapshot!("human", human_out);
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn test_report_file_error() {
+    let json_out_path = test_output("mdsw-test-report-file-error-out.json");
+    // A directory can't be written to, but that shouldn't stop the other outputs
+    let bad_path = test_output("mdsw-test-report-file-error-dir");
+    std::fs::create_dir_all(&bad_path).unwrap();
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--human")
+        .arg("--human-file")
+        .arg(&bad_path)
+        .arg("--json-file")
+        .arg(&json_out_path)
+        .arg("../testdata/test.dmp")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let json_out = std::fs::read_to_string(json_out_path).unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    insta::assert_snapshot!("human", stdout);
+    insta::assert_snapshot!("json", json_out);
+    assert!(stderr.contains("mdsw-test-report-file-error-dir"));
+}
+
 #[test]
 #[ignore]
 fn test_trace() {
@@ -553,6 +643,61 @@ fn test_ambiguous_parse() {
     assert_eq!(stderr, "");
 }
 
+#[test]
+fn test_symbols_url_header() {
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--human")
+        .arg("--symbols-url")
+        .arg("garbage-url.realwebsite")
+        .arg("--symbols-url-header")
+        .arg("Authorization: env:TEST_SYMBOLS_AUTH")
+        .arg("../testdata/test.dmp")
+        .env("TEST_SYMBOLS_AUTH", "Bearer secret")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(output.status.success());
+    insta::assert_snapshot!("human", stdout);
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn test_bad_symbols_url_header() {
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    for (header, error) in [
+        ("Authorization", "expected NAME: VALUE"),
+        (
+            "Authorization: env:TEST_SYMBOLS_AUTH_MISSING",
+            "can't read the TEST_SYMBOLS_AUTH_MISSING environment variable",
+        ),
+        (
+            "Authorization: file:not_a_real_file",
+            "can't read not_a_real_file",
+        ),
+    ] {
+        let output = Command::new(bin)
+            .arg("--symbols-url-header")
+            .arg(header)
+            .arg("../testdata/test.dmp")
+            .env_remove("TEST_SYMBOLS_AUTH_MISSING")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .unwrap();
+
+        let stderr = String::from_utf8(output.stderr).unwrap();
+
+        assert!(!output.status.success());
+        assert!(stderr.contains(error), "{}", stderr);
+    }
+}
+
 #[test]
 fn test_no_minidump() {
     let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
@@ -608,6 +753,73 @@ fn test_multiple_outputs_conflict() {
     assert!(!stderr.is_empty());
 }
 
+#[test]
+fn test_extra_file() {
+    let extra_path = test_output("test.extra");
+    std::fs::write(&extra_path, "ProductName=Firefox\nMemoryReport=1\n").unwrap();
+
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--json")
+        .arg("--extra-file")
+        .arg(&extra_path)
+        .arg("../testdata/test.dmp")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(stderr, "");
+    let report: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(
+        report["crash_annotations"],
+        serde_json::json!({ "ProductName": "Firefox", "MemoryReport": "1" })
+    );
+    assert_eq!(report["crash_info"]["notes"][0]["kind"], "MemoryReport");
+
+    std::fs::write(&extra_path, "{ not json").unwrap();
+    let output = Command::new(bin)
+        .arg("--json")
+        .arg("--extra-file")
+        .arg(&extra_path)
+        .arg("../testdata/test.dmp")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("Error reading crash annotations"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_raw_frames_needs_merge_thunk_frames() {
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--json")
+        .arg("--raw-frames")
+        .arg("../testdata/test.dmp")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    assert_eq!(stdout, "");
+    assert!(stderr.contains("--merge-thunk-frames"));
+}
+
 #[test]
 fn test_pretty_humans() {
     let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
@@ -648,6 +860,260 @@ fn test_brief_robots() {
     assert!(!stderr.is_empty());
 }
 
+#[test]
+fn test_batch() {
+    let batch_dir = test_output("batch");
+    let output_dir = test_output("batch-out");
+    let _ = std::fs::remove_dir_all(&batch_dir);
+    let _ = std::fs::remove_dir_all(&output_dir);
+    std::fs::create_dir_all(&batch_dir).unwrap();
+    std::fs::copy("../testdata/test.dmp", batch_dir.join("good.dmp")).unwrap();
+    std::fs::write(batch_dir.join("bad.dmp"), b"not a minidump").unwrap();
+    std::fs::write(batch_dir.join("notes.txt"), b"not a minidump either").unwrap();
+
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--batch")
+        .arg(&batch_dir)
+        .arg("--output-dir")
+        .arg(&output_dir)
+        .arg("--jobs=2")
+        .arg("--json")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    // One failure doesn't fail the batch
+    assert!(output.status.success());
+    assert!(stdout.starts_with("Processed 1 of 2 minidumps (1 failed)\n"));
+    assert!(stdout.contains("\n       1  EXCEPTION_ACCESS_VIOLATION_WRITE\n"));
+    assert!(stdout.contains("\nFailures:\n  bad.dmp: "));
+
+    let json = std::fs::read_to_string(output_dir.join("good.json")).unwrap();
+    assert!(json.starts_with('{'));
+    assert!(!output_dir.join("bad.json").exists());
+
+    // But the batch fails if every dump does
+    std::fs::remove_file(batch_dir.join("good.dmp")).unwrap();
+    let output = Command::new(bin)
+        .arg("--batch")
+        .arg(&batch_dir)
+        .arg("--output-dir")
+        .arg(&output_dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!output.status.success());
+    assert!(stdout.starts_with("Processed 0 of 1 minidumps (1 failed)\n"));
+}
+
+#[test]
+fn test_batch_needs_output_dir() {
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--batch")
+        .arg("../testdata")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    assert_eq!(stdout, "");
+    assert!(!stderr.is_empty());
+}
+
+/// Write the --json report of test.dmp (without symbols) to `file_name`.
+fn write_json_report(file_name: &str) -> PathBuf {
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let path = test_output(file_name);
+    let output = Command::new(bin)
+        .arg("--json")
+        .arg("--output-file")
+        .arg(&path)
+        .arg("../testdata/test.dmp")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    path
+}
+
+#[test]
+fn test_compare_same() {
+    let report = write_json_report("compare-same.json");
+
+    // A dump and its report are the same
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--compare")
+        .arg("../testdata/test.dmp")
+        .arg(&report)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout,
+        "--- ../testdata/test.dmp\n+++ ../target/testdata/compare-same.json\n\nNo differences\n"
+    );
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn test_compare_symbols() {
+    let report = write_json_report("compare-no-symbols.json");
+
+    // The report without symbols against the dump processed with them
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--compare")
+        .arg(&report)
+        .arg("../testdata/test.dmp")
+        .arg("--symbols-path")
+        .arg("../testdata/symbols/")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+    insta::assert_snapshot!("compare-symbols", stdout);
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn test_compare_edited() {
+    let report = write_json_report("compare-original.json");
+    let edited = std::fs::read_to_string(&report)
+        .unwrap()
+        .replace(
+            r#""type":"EXCEPTION_ACCESS_VIOLATION_WRITE""#,
+            r#""type":"EXCEPTION_ACCESS_VIOLATION_READ""#,
+        )
+        .replace(
+            r#""filename":"dbghelp.dll""#,
+            r#""filename":"dbghelp2.dll""#,
+        )
+        .replace(r#""code_id":"411096cab000""#, r#""code_id":"411096cab001""#);
+    let edited_report = test_output("compare-edited.json");
+    std::fs::write(&edited_report, edited).unwrap();
+
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--compare")
+        .arg(&report)
+        .arg(&edited_report)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+    insta::assert_snapshot!("compare-edited", stdout);
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn test_compare_bad_report() {
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--compare")
+        .arg("../testdata/test.dmp")
+        .arg("../testdata/evil.json")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout, "");
+    assert!(stderr.contains("as a JSON report"));
+}
+
+#[test]
+fn test_extract_thread() {
+    let extracted = test_output("extracted-thread.dmp");
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--extract-thread")
+        .arg("3060")
+        .arg("--output-file")
+        .arg(&extracted)
+        .arg("../testdata/test.dmp")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "");
+    let original_size = std::fs::metadata("../testdata/test.dmp").unwrap().len();
+    assert!(std::fs::metadata(&extracted).unwrap().len() < original_size);
+
+    // The crashing thread of the extracted dump is the same as the original's
+    let output = Command::new(bin)
+        .arg("--compare")
+        .arg("../testdata/test.dmp")
+        .arg(&extracted)
+        .arg("--symbols-path")
+        .arg("../testdata/symbols/")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(output.status.success());
+    assert!(stdout.ends_with("\n\nNo differences\n"), "{}", stdout);
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn test_extract_missing_thread() {
+    let bin = env!("CARGO_BIN_EXE_minidump-stackwalk");
+    let output = Command::new(bin)
+        .arg("--extract-thread")
+        .arg("0x1234")
+        .arg("--output-file")
+        .arg(test_output("extracted-missing-thread.dmp"))
+        .arg("../testdata/test.dmp")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr.contains("Error extracting thread 0x1234"),
+        "{}",
+        stderr
+    );
+}
+
 fn minimal_minidump() -> SynthMinidump {
     let context = minidump_synth::x86_context(Endian::Little, 0xf00800, 0x1010);
     let stack = Memory::with_section(
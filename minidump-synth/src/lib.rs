@@ -18,11 +18,19 @@
 
 use minidump_common::format as md;
 use scroll::ctx::SizeWith;
-use scroll::LE;
+use scroll::{Pwrite, LE};
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::mem;
 use test_assembler::*;
 
+#[cfg(all(
+    feature = "linux-writer",
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+pub mod linux;
+
 /// A writer of synthetic minidumps.
 pub struct SynthMinidump {
     /// The `Section` containing the minidump contents.
@@ -49,6 +57,8 @@ pub struct SynthMinidump {
     thread_list: Option<ListStream<Thread>>,
     /// List of thread names in this minidump.
     thread_names_list: Option<ListStream<ThreadName>>,
+    /// List of extra info about threads in this minidump.
+    thread_info_list: Option<ExListStream<ThreadInfo>>,
     /// List of memory regions in this minidump.
     memory_list: Option<ListStream<Section>>,
     /// List of memory regions stored in `Memory64List` stream in this minidump.
@@ -65,6 +75,8 @@ pub struct SynthMinidump {
     linux_cpu_info: Option<SimpleStream>,
     /// /proc/self/environ string
     linux_environ: Option<SimpleStream>,
+    /// /proc/self/cmdline string
+    linux_cmdline: Option<SimpleStream>,
     /// /proc/self/status string
     linux_proc_status: Option<SimpleStream>,
     /// /proc/self/limits string
@@ -209,6 +221,11 @@ impl SynthMinidump {
                 md::MINIDUMP_STREAM_TYPE::ThreadNamesStream,
                 endian,
             )),
+            thread_info_list: Some(ExListStream::new(
+                md::MINIDUMP_STREAM_TYPE::ThreadInfoListStream,
+                mem::size_of::<md::MINIDUMP_THREAD_INFO>(),
+                endian,
+            )),
             memory_list: Some(ListStream::new(
                 md::MINIDUMP_STREAM_TYPE::MemoryListStream,
                 endian,
@@ -225,6 +242,7 @@ impl SynthMinidump {
             linux_maps: None,
             linux_lsb_release: None,
             linux_environ: None,
+            linux_cmdline: None,
             linux_cpu_info: None,
             linux_proc_status: None,
             linux_proc_limits: None,
@@ -328,6 +346,15 @@ impl SynthMinidump {
         self
     }
 
+    /// Add `thread_info` to `self`, adding it to the thread info list stream as well.
+    pub fn add_thread_info(mut self, thread_info: ThreadInfo) -> SynthMinidump {
+        self.thread_info_list = self
+            .thread_info_list
+            .take()
+            .map(|thread_info_list| thread_info_list.add(thread_info));
+        self
+    }
+
     /// Add `handle` to `self`, adding it to the handle data stream as well.
     pub fn add_handle_descriptor(mut self, handle: HandleDescriptor) -> SynthMinidump {
         self.handle_data_stream = self
@@ -409,6 +436,15 @@ impl SynthMinidump {
         self
     }
 
+    /// Set the contents of the `LinuxCmdLine` stream.
+    pub fn set_linux_cmdline(mut self, cmdline: &[u8]) -> SynthMinidump {
+        self.linux_cmdline = Some(SimpleStream {
+            stream_type: md::MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32,
+            section: Section::new().append_bytes(cmdline),
+        });
+        self
+    }
+
     /// Append `stream` to `self`, setting its location appropriately and adding it to the stream directory.
     pub fn add_stream<T: Stream>(mut self, stream: T) -> SynthMinidump {
         self.stream_directory = stream.cite_stream_in(self.stream_directory);
@@ -468,6 +504,9 @@ impl SynthMinidump {
         // Add thread names stream if any names were added.
         let thread_names = self.thread_names_list.take();
         self = self.finish_list(thread_names);
+        // Add thread info list stream if any thread infos were added.
+        let thread_infos = self.thread_info_list.take();
+        self = self.finish_ex_list(thread_infos);
         // Add crashpad info stream if any.
         if let Some(crashpad_info) = self.crashpad_info.take() {
             self = self.add_stream(crashpad_info);
@@ -496,6 +535,9 @@ impl SynthMinidump {
         if let Some(stream) = self.linux_environ.take() {
             self = self.add_stream(stream);
         }
+        if let Some(stream) = self.linux_cmdline.take() {
+            self = self.add_stream(stream);
+        }
         if let Some(memory64_section) = self.memory64_section.take() {
             self = self.add(memory64_section);
         }
@@ -575,6 +617,207 @@ impl Stream for SimpleStream {
     }
 }
 
+/// An x86-64 dynamic function table, for [`function_table_stream`].
+pub struct FunctionTable {
+    pub minimum_address: u64,
+    pub maximum_address: u64,
+    pub base_address: u64,
+    /// The functions of the table (`RUNTIME_FUNCTION`s): their begin and end
+    /// addresses and the address of their unwind info, relative to `base_address`.
+    pub entries: Vec<(u32, u32, u32)>,
+}
+
+/// A `FunctionTable` stream holding `tables`, whose native descriptors are
+/// `native_descriptor_size` zero bytes.
+pub fn function_table_stream(
+    endian: Endian,
+    tables: &[FunctionTable],
+    native_descriptor_size: u32,
+) -> SimpleStream {
+    let mut section = Section::with_endian(endian)
+        .D32(mem::size_of::<md::MINIDUMP_FUNCTION_TABLE_STREAM>() as u32)
+        .D32(mem::size_of::<md::MINIDUMP_FUNCTION_TABLE_DESCRIPTOR>() as u32)
+        .D32(native_descriptor_size)
+        .D32(mem::size_of::<md::IMAGE_AMD64_RUNTIME_FUNCTION_ENTRY>() as u32)
+        .D32(tables.len() as u32)
+        .D32(0); // size_of_align_pad
+    for table in tables {
+        section = section
+            .D64(table.minimum_address)
+            .D64(table.maximum_address)
+            .D64(table.base_address)
+            .D32(table.entries.len() as u32)
+            .D32(0) // size_of_align_pad
+            .append_repeated(0, native_descriptor_size as usize);
+        for &(begin_address, end_address, unwind_info_address) in &table.entries {
+            section = section
+                .D32(begin_address)
+                .D32(end_address)
+                .D32(unwind_info_address);
+        }
+    }
+    SimpleStream {
+        stream_type: md::MINIDUMP_STREAM_TYPE::FunctionTable as u32,
+        section,
+    }
+}
+
+/// An object loaded by the dynamic linker, for [`linux_dso_debug_stream`].
+pub struct LinkMap<'a> {
+    pub load_bias: u64,
+    pub name: &'a DumpString,
+    pub dynamic: u64,
+}
+
+/// A 64-bit `LinuxDsoDebug` stream (the dynamic linker's `r_debug`) listing
+/// `link_maps`, which are written right after it. Their names must be added to
+/// the minidump separately.
+pub fn linux_dso_debug_stream(
+    endian: Endian,
+    link_maps: &[LinkMap],
+    ldbase: u64,
+    dynamic: u64,
+) -> SimpleStream {
+    let section = Section::with_endian(endian);
+    let map = &section.start() + md::DSO_DEBUG_64::size_with(&LE) as i64;
+    let mut section = section
+        .D32(1) // version
+        .D32(map)
+        .D32(link_maps.len() as u32)
+        .D64(0) // brk
+        .D64(ldbase)
+        .D64(dynamic);
+    for link_map in link_maps {
+        section = section
+            .D64(link_map.load_bias)
+            .D32(link_map.name.file_offset())
+            .D64(link_map.dynamic);
+    }
+    SimpleStream {
+        stream_type: md::MINIDUMP_STREAM_TYPE::LinuxDsoDebug as u32,
+        section,
+    }
+}
+
+/// A 64-bit `LinuxAuxv` stream holding the (type, value) `entries`, followed
+/// by an `AT_NULL` entry.
+pub fn linux_auxv_stream(endian: Endian, entries: &[(u64, u64)]) -> SimpleStream {
+    let mut section = Section::with_endian(endian);
+    for &(ty, value) in entries {
+        section = section.D64(ty).D64(value);
+    }
+    SimpleStream {
+        stream_type: md::MINIDUMP_STREAM_TYPE::LinuxAuxv as u32,
+        section: section.D64(md::AuxvType::AT_NULL as u64).D64(0),
+    }
+}
+
+/// A `TokenStream` holding `tokens`, as pairs of a token id and its data (the
+/// handles of the tokens are their ids).
+pub fn token_stream(endian: Endian, tokens: &[(u32, &[u8])]) -> SimpleStream {
+    let header_size = md::MINIDUMP_TOKEN_INFO_HEADER::size_with(&LE) as u32;
+    let element_header_size = md::MINIDUMP_TOKEN_INFO_LIST::size_with(&LE) as u32;
+    let list_size = tokens.iter().fold(header_size, |size, (_, data)| {
+        size + element_header_size + data.len() as u32
+    });
+    let mut section = Section::with_endian(endian)
+        .D32(list_size)
+        .D32(tokens.len() as u32)
+        .D32(header_size)
+        .D32(element_header_size);
+    for &(id, data) in tokens {
+        section = section
+            .D32(element_header_size + data.len() as u32)
+            .D32(id)
+            .D64(id as u64)
+            .append_bytes(data);
+    }
+    SimpleStream {
+        stream_type: md::MINIDUMP_STREAM_TYPE::TokenStream as u32,
+        section,
+    }
+}
+
+/// The bytes of a raw struct from minidump-common.
+fn raw_bytes<T>(endian: Endian, raw: T) -> Vec<u8>
+where
+    T: scroll::ctx::TryIntoCtx<scroll::Endian, Error = scroll::Error> + SizeWith<scroll::Endian>,
+{
+    let endian = match endian {
+        Endian::Little => scroll::LE,
+        Endian::Big => scroll::BE,
+    };
+    let mut bytes = vec![0; T::size_with(&endian)];
+    bytes.pwrite_with(raw, 0, endian).unwrap();
+    bytes
+}
+
+/// A stream holding a single raw struct from minidump-common.
+fn raw_stream<T>(endian: Endian, stream_type: md::MINIDUMP_STREAM_TYPE, raw: T) -> SimpleStream
+where
+    T: scroll::ctx::TryIntoCtx<scroll::Endian, Error = scroll::Error> + SizeWith<scroll::Endian>,
+{
+    SimpleStream {
+        stream_type: stream_type as u32,
+        section: Section::new().append_bytes(&raw_bytes(endian, raw)),
+    }
+}
+
+/// A `HandleOperationListStream` holding `operations`.
+pub fn handle_operation_list_stream(
+    endian: Endian,
+    operations: &[md::AVRF_HANDLE_OPERATION],
+) -> SimpleStream {
+    let mut section = Section::with_endian(endian)
+        .D32(md::MINIDUMP_HANDLE_OPERATION_LIST::size_with(&LE) as u32)
+        .D32(md::AVRF_HANDLE_OPERATION::size_with(&LE) as u32)
+        .D32(operations.len() as u32)
+        .D32(0);
+    for operation in operations {
+        section = section.append_bytes(&raw_bytes(endian, operation.clone()));
+    }
+    SimpleStream {
+        stream_type: md::MINIDUMP_STREAM_TYPE::HandleOperationListStream as u32,
+        section,
+    }
+}
+
+/// A `SystemMemoryInfoStream` holding `info`.
+pub fn system_memory_info_stream(
+    endian: Endian,
+    info: md::MINIDUMP_SYSTEM_MEMORY_INFO_1,
+) -> SimpleStream {
+    raw_stream(
+        endian,
+        md::MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream,
+        info,
+    )
+}
+
+/// A `ProcessVmCountersStream` holding the first revision of the counters.
+pub fn process_vm_counters_1_stream(
+    endian: Endian,
+    counters: md::MINIDUMP_PROCESS_VM_COUNTERS_1,
+) -> SimpleStream {
+    raw_stream(
+        endian,
+        md::MINIDUMP_STREAM_TYPE::ProcessVmCountersStream,
+        counters,
+    )
+}
+
+/// A `ProcessVmCountersStream` holding the second revision of the counters.
+pub fn process_vm_counters_2_stream(
+    endian: Endian,
+    counters: md::MINIDUMP_PROCESS_VM_COUNTERS_2,
+) -> SimpleStream {
+    raw_stream(
+        endian,
+        md::MINIDUMP_STREAM_TYPE::ProcessVmCountersStream,
+        counters,
+    )
+}
+
 /// A stream containing a list of dump entries.
 pub struct List<T: ListItem> {
     /// The stream's contents.
@@ -939,6 +1182,13 @@ impl DumpString {
             .append_bytes(&u16_s);
         DumpString { section }
     }
+
+    /// Create a `DumpString` that claims to be `length` bytes long, followed by
+    /// `bytes`, to test corrupt strings (too long, truncated, or not UTF-16).
+    pub fn raw(length: u32, bytes: &[u8], endian: Endian) -> DumpString {
+        let section = Section::with_endian(endian).D32(length).append_bytes(bytes);
+        DumpString { section }
+    }
 }
 
 impl From<DumpString> for Section {
@@ -1104,6 +1354,14 @@ pub struct Thread {
 
 impl Thread {
     pub fn new<T>(endian: Endian, id: u32, stack: &Memory, context: &T) -> Thread
+    where
+        T: DumpSection,
+    {
+        Thread::with_teb(endian, id, 0, stack, context)
+    }
+
+    /// A thread whose TEB (or whatever the platform keeps there) is at `teb`.
+    pub fn with_teb<T>(endian: Endian, id: u32, teb: u64, stack: &Memory, context: &T) -> Thread
     where
         T: DumpSection,
     {
@@ -1112,7 +1370,7 @@ impl Thread {
             .D32(0) // suspend_count
             .D32(0) // priority_class
             .D32(0) // priority
-            .D64(0) // teb
+            .D64(teb)
             .cite_memory(stack)
             .cite_location(context);
         Thread { section }
@@ -1153,6 +1411,57 @@ impl From<ThreadName> for Section {
     }
 }
 
+/// Extra information about a minidump thread.
+pub struct ThreadInfo {
+    section: Section,
+    pub thread_id: u32,
+    pub dump_flags: u32,
+    pub dump_error: u32,
+    pub exit_status: u32,
+    pub create_time: u64,
+    pub exit_time: u64,
+    pub kernel_time: u64,
+    pub user_time: u64,
+    pub start_address: u64,
+    pub affinity: u64,
+}
+
+impl ThreadInfo {
+    pub fn new(endian: Endian, id: u32) -> Self {
+        Self {
+            section: Section::with_endian(endian),
+            thread_id: id,
+            dump_flags: 0,
+            dump_error: 0,
+            exit_status: 0,
+            create_time: 0,
+            exit_time: 0,
+            kernel_time: 0,
+            user_time: 0,
+            start_address: 0,
+            affinity: 0,
+        }
+    }
+}
+
+impl_dumpsection!(ThreadInfo);
+
+impl From<ThreadInfo> for Section {
+    fn from(info: ThreadInfo) -> Self {
+        info.section
+            .D32(info.thread_id)
+            .D32(info.dump_flags)
+            .D32(info.dump_error)
+            .D32(info.exit_status)
+            .D64(info.create_time)
+            .D64(info.exit_time)
+            .D64(info.kernel_time)
+            .D64(info.user_time)
+            .D64(info.start_address)
+            .D64(info.affinity)
+    }
+}
+
 /// A range of memory contents.
 pub struct Memory {
     section: Section,
@@ -1166,6 +1475,12 @@ impl Memory {
         Memory { section, address }
     }
 
+    /// Create a new `Memory` object representing memory starting at `address`,
+    /// containing `bytes`.
+    pub fn with_bytes(address: u64, bytes: &[u8]) -> Memory {
+        Memory::with_section(Section::new().append_bytes(bytes), address)
+    }
+
     // Append an `MINIDUMP_MEMORY_DESCRIPTOR` referring to this memory range to `section`.
     pub fn cite_memory_in(&self, section: Section) -> Section {
         section.D64(self.address).cite_location(self)
@@ -1529,51 +1844,276 @@ impl Stream for MiscStream {
     }
 }
 
+/// Register values for the `*_context_with_registers` functions, keyed by
+/// their canonical names.
+struct RegisterValues<'a> {
+    values: HashMap<&'a str, u64>,
+}
+
+impl<'a> RegisterValues<'a> {
+    /// Collect `registers`, mapping any `aliases` to their canonical names.
+    ///
+    /// Panics if a register isn't one of `known` (or an alias). There's no
+    /// meaningful way to recover from a typo in a test.
+    fn new(
+        arch: &str,
+        registers: &[(&'a str, u64)],
+        known: &[&'a str],
+        aliases: &[(&'a str, &'a str)],
+    ) -> Self {
+        let values = registers
+            .iter()
+            .map(|&(name, val)| {
+                let name = aliases
+                    .iter()
+                    .find(|(alias, _)| *alias == name)
+                    .map_or(name, |&(_, canonical)| canonical);
+                assert!(known.contains(&name), "unknown {arch} register {name}");
+                (name, val)
+            })
+            .collect();
+        RegisterValues { values }
+    }
+
+    fn get(&self, name: &str) -> u64 {
+        self.values.get(name).copied().unwrap_or(0)
+    }
+
+    /// Append the 32-bit registers `names` to `section`, in order.
+    fn append32(&self, section: Section, names: &[&str]) -> Section {
+        names
+            .iter()
+            .fold(section, |section, name| section.D32(self.get(name) as u32))
+    }
+
+    /// Append the 64-bit registers `names` to `section`, in order.
+    fn append64(&self, section: Section, names: &[&str]) -> Section {
+        names
+            .iter()
+            .fold(section, |section, name| section.D64(self.get(name)))
+    }
+}
+
+const X86_DEBUG_REGISTERS: &[&str] = &["dr0", "dr1", "dr2", "dr3", "dr6", "dr7"];
+const X86_SEGMENT_REGISTERS: &[&str] = &["gs", "fs", "es", "ds"];
+const X86_INTEGER_REGISTERS: &[&str] = &["edi", "esi", "ebx", "edx", "ecx", "eax"];
+const X86_CONTROL_REGISTERS: &[&str] = &["ebp", "eip", "cs", "eflags", "esp", "ss"];
+
 /// Populate a `CONTEXT_X86` struct with the given `endian`, `eip`, and `esp`.
 pub fn x86_context(endian: Endian, eip: u32, esp: u32) -> Section {
-    let section = Section::with_endian(endian)
-        .D32(0x1003f) // context_flags: CONTEXT_X86_ALL
-        .append_repeated(0, 4 * 6) // dr0,1,2,3,6,7, 4 bytes each
-        .append_repeated(0, md::FLOATING_SAVE_AREA_X86::size_with(&LE)) // float_save
-        .append_repeated(0, 4 * 11) // gs-ebp, 4 bytes each
-        .D32(eip)
-        .D32(0) // cs
-        .D32(0) // eflags
-        .D32(esp)
-        .D32(0) // ss
+    x86_context_with_registers(endian, &[("eip", eip.into()), ("esp", esp.into())])
+}
+
+/// Populate a `CONTEXT_X86` struct with the given `endian` and register values.
+///
+/// Registers are named as in the minidump crate (e.g. "eip", "ebp"), along
+/// with the debug and segment registers. Unspecified registers are zero.
+pub fn x86_context_with_registers(endian: Endian, registers: &[(&str, u64)]) -> Section {
+    let known = [
+        X86_DEBUG_REGISTERS,
+        X86_SEGMENT_REGISTERS,
+        X86_INTEGER_REGISTERS,
+        X86_CONTROL_REGISTERS,
+    ]
+    .concat();
+    let regs = RegisterValues::new("x86", registers, &known, &[]);
+
+    let section = Section::with_endian(endian).D32(0x1003f); // context_flags: CONTEXT_X86_ALL
+    let section = regs
+        .append32(section, X86_DEBUG_REGISTERS)
+        .append_repeated(0, md::FLOATING_SAVE_AREA_X86::size_with(&LE)); // float_save
+    let section = regs.append32(section, X86_SEGMENT_REGISTERS);
+    let section = regs.append32(section, X86_INTEGER_REGISTERS);
+    let section = regs
+        .append32(section, X86_CONTROL_REGISTERS)
         .append_repeated(0, 512); // extended_registers
     assert_eq!(section.size(), md::CONTEXT_X86::size_with(&LE) as u64);
     section
 }
 
+const AMD64_SEGMENT_REGISTERS: &[&str] = &["cs", "ds", "es", "fs", "gs", "ss"];
+const AMD64_DEBUG_REGISTERS: &[&str] = &["dr0", "dr1", "dr2", "dr3", "dr6", "dr7"];
+const AMD64_INTEGER_REGISTERS: &[&str] = &[
+    "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10", "r11", "r12", "r13",
+    "r14", "r15", "rip",
+];
+
 /// Populate a `CONTEXT_AMD64` struct with the given `endian`, `rip`, and `rsp`.
 pub fn amd64_context(endian: Endian, rip: u64, rsp: u64) -> Section {
-    let section = Section::with_endian(endian)
+    amd64_context_with_registers(endian, &[("rip", rip), ("rsp", rsp)])
+}
+
+/// Populate a `CONTEXT_AMD64` struct with the given `endian` and register values.
+///
+/// Registers are named as in the minidump crate (e.g. "rip", "r12"), along
+/// with "eflags" and the debug and segment registers. Unspecified registers
+/// are zero.
+pub fn amd64_context_with_registers(endian: Endian, registers: &[(&str, u64)]) -> Section {
+    amd64_context_section(endian, registers, 0x10001f, &[]) // CONTEXT_AMD64_ALL
+}
+
+/// Populate a `CONTEXT_AMD64` struct with the given `endian` and register
+/// values, followed by an extended context with the `[low, high]` halves of
+/// `ymm` as the YMM registers, like Windows writes for contexts with XSTATE.
+///
+/// The low halves are the XMM registers of the classic context. The XSAVE area
+/// has the usual layout (the AVX state at offset 576), or the compacted one if
+/// `compacted` is true.
+pub fn amd64_context_with_xstate(
+    endian: Endian,
+    registers: &[(&str, u64)],
+    ymm: &[[u128; 2]],
+    compacted: bool,
+) -> Section {
+    let xmm: Vec<u128> = ymm.iter().map(|&[low, _]| low).collect();
+    let xsave = Section::with_endian(endian)
+        // XSAVE_AREA_HEADER
+        .D64(0b111) // mask: x87, SSE and AVX
+        .D64(if compacted { (1 << 63) | 0b111 } else { 0 }) // compaction_mask
+        .append_repeated(0, mem::size_of::<u64>() * 6); // reserved
+    let mut xsave = xsave;
+    for i in 0..16 {
+        let high = ymm.get(i).map_or(0, |&[_, high]| high);
+        xsave = append128(xsave, endian, high);
+    }
+    amd64_context_with_xsave_area(endian, registers, &xmm, xsave)
+}
+
+/// Populate a `CONTEXT_AMD64` struct with the given `endian`, register values
+/// and `xmm` registers, followed by an extended context with `xsave` as its
+/// XSAVE area.
+///
+/// `xsave` starts with the `XSAVE_AREA_HEADER` (the legacy part of the XSAVE
+/// area is in the classic context), see [`amd64_context_with_xstate`].
+pub fn amd64_context_with_xsave_area(
+    endian: Endian,
+    registers: &[(&str, u64)],
+    xmm: &[u128],
+    xsave: Section,
+) -> Section {
+    const CONTEXT_SIZE: i32 = 1232;
+    const CONTEXT_EX_SIZE: i32 = 24;
+    // The XSAVE area is 64-byte aligned
+    const XSAVE_OFFSET: i32 = 1280;
+
+    let xsave_size = xsave.size() as u32;
+    // CONTEXT_AMD64_ALL | CONTEXT_HAS_XSTATE
+    amd64_context_section(endian, registers, 0x10005f, xmm)
+        // CONTEXT_EX
+        .D32(-CONTEXT_SIZE as u32) // all.offset
+        .D32(XSAVE_OFFSET as u32 + xsave_size) // all.length
+        .D32(-CONTEXT_SIZE as u32) // legacy.offset
+        .D32(CONTEXT_SIZE as u32) // legacy.length
+        .D32((XSAVE_OFFSET - CONTEXT_SIZE) as u32) // xstate.offset
+        .D32(xsave_size) // xstate.length
+        .append_repeated(0, (XSAVE_OFFSET - CONTEXT_SIZE - CONTEXT_EX_SIZE) as usize)
+        .append_section(xsave)
+}
+
+/// Append a 128-bit `value` to `section`.
+fn append128(section: Section, endian: Endian, value: u128) -> Section {
+    let (low, high) = (value as u64, (value >> 64) as u64);
+    match endian {
+        Endian::Little => section.D64(low).D64(high),
+        Endian::Big => section.D64(high).D64(low),
+    }
+}
+
+fn amd64_context_section(
+    endian: Endian,
+    registers: &[(&str, u64)],
+    context_flags: u32,
+    xmm: &[u128],
+) -> Section {
+    let known = [
+        AMD64_SEGMENT_REGISTERS,
+        &["eflags"],
+        AMD64_DEBUG_REGISTERS,
+        AMD64_INTEGER_REGISTERS,
+    ]
+    .concat();
+    let regs = RegisterValues::new("amd64", registers, &known, &[]);
+
+    let mut section = Section::with_endian(endian)
         .append_repeated(0, mem::size_of::<u64>() * 6) // p[1-6]_home
-        .D32(0x10001f) // context_flags: CONTEXT_AMD64_ALL
-        .D32(0) // mx_csr
-        .append_repeated(0, mem::size_of::<u16>() * 6) // cs,ds,es,fs,gs,ss
-        .D32(0) // eflags
-        .append_repeated(0, mem::size_of::<u64>() * 6) // dr0,1,2,3,6,7
-        .append_repeated(0, mem::size_of::<u64>() * 4) // rax,rcx,rdx,rbx
-        .D64(rsp)
-        .append_repeated(0, mem::size_of::<u64>() * 11) // rbp-r15
-        .D64(rip)
-        .append_repeated(0, 512) // float_save
+        .D32(context_flags)
+        .D32(0); // mx_csr
+    for name in AMD64_SEGMENT_REGISTERS {
+        section = section.D16(regs.get(name) as u16);
+    }
+    let section = regs.append32(section, &["eflags"]);
+    let section = regs.append64(section, AMD64_DEBUG_REGISTERS);
+    let mut section = regs
+        .append64(section, AMD64_INTEGER_REGISTERS)
+        .append_repeated(0, 160); // float_save, up to the XMM registers
+    for i in 0..16 {
+        section = append128(section, endian, xmm.get(i).copied().unwrap_or(0));
+    }
+    let section = section
+        .append_repeated(0, 512 - 160 - 16 * 16) // rest of float_save
         .append_repeated(0, mem::size_of::<u128>() * 26) // vector_register
         .append_repeated(0, mem::size_of::<u64>() * 6); // trailing stuff
     assert_eq!(section.size(), md::CONTEXT_AMD64::size_with(&LE) as u64);
     section
 }
 
+const ARM_REGISTERS: &[&str] = &[
+    "r0", "r1", "r2", "r3", "r4", "r5", "r6", "r7", "r8", "r9", "r10", "fp", "r12", "sp", "lr",
+    "pc",
+];
+
+/// Populate a `CONTEXT_ARM` struct with the given `endian`, `pc`, and `sp`.
+pub fn arm_context(endian: Endian, pc: u32, sp: u32) -> Section {
+    arm_context_with_registers(endian, &[("pc", pc.into()), ("sp", sp.into())])
+}
+
+/// Populate a `CONTEXT_ARM` struct with the given `endian` and register values.
+///
+/// Registers are named as in the minidump crate (e.g. "r0", "fp", "pc"),
+/// along with "cpsr". "r11" and "r13"-"r15" are accepted as aliases.
+/// Unspecified registers are zero.
+pub fn arm_context_with_registers(endian: Endian, registers: &[(&str, u64)]) -> Section {
+    let known = [ARM_REGISTERS, &["cpsr"]].concat();
+    let aliases = [("r11", "fp"), ("r13", "sp"), ("r14", "lr"), ("r15", "pc")];
+    let regs = RegisterValues::new("arm", registers, &known, &aliases);
+
+    let section = Section::with_endian(endian).D32(0x40000006); // context_flags: CONTEXT_ARM_ALL
+    let section = regs
+        .append32(section, ARM_REGISTERS)
+        .D32(regs.get("cpsr") as u32)
+        .D64(0) // float_save.fpscr
+        .append_repeated(0, mem::size_of::<u64>() * 32) // float_save.regs
+        .append_repeated(0, mem::size_of::<u32>() * 8); // float_save.extra
+    assert_eq!(section.size(), md::CONTEXT_ARM::size_with(&LE) as u64);
+    section
+}
+
+const ARM64_REGISTERS: &[&str] = &[
+    "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13", "x14",
+    "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27",
+    "x28", "fp", "lr", "sp", "pc",
+];
+
 /// Populate a `CONTEXT_ARM64` struct with the given `endian`, `pc`, and `sp`.
 pub fn arm64_context(endian: Endian, pc: u64, sp: u64) -> Section {
+    arm64_context_with_registers(endian, &[("pc", pc), ("sp", sp)])
+}
+
+/// Populate a `CONTEXT_ARM64` struct with the given `endian` and register values.
+///
+/// Registers are named as in the minidump crate (e.g. "x0", "fp", "pc"),
+/// along with "cpsr". "x29" and "x30" are accepted as aliases.
+/// Unspecified registers are zero.
+pub fn arm64_context_with_registers(endian: Endian, registers: &[(&str, u64)]) -> Section {
+    let known = [ARM64_REGISTERS, &["cpsr"]].concat();
+    let aliases = [("x29", "fp"), ("x30", "lr")];
+    let regs = RegisterValues::new("arm64", registers, &known, &aliases);
+
     let section = Section::with_endian(endian)
         .D32(0x40001f) // context_flags: CONTEXT_ARM64_ALL
-        .D32(0) // cpsr
-        .append_repeated(0, mem::size_of::<u64>() * 31) // iregs[x0, x1, ..., x28, fp, lr],
-        .D64(sp) // sp
-        .D64(pc) // pc
+        .D32(regs.get("cpsr") as u32);
+    let section = regs
+        .append64(section, ARM64_REGISTERS) // iregs[x0, x1, ..., x28, fp, lr], sp, pc
         .append_repeated(0, mem::size_of::<u128>() * 32) // float_regs[d0, d1, ..., d31]
         .D32(0) // fpcr
         .D32(0) // fpsr
@@ -1585,6 +2125,81 @@ pub fn arm64_context(endian: Endian, pc: u64, sp: u64) -> Section {
     section
 }
 
+/// Populate a 32-bit `CONTEXT_MIPS` struct with the given `endian`, `pc`, and `sp`.
+pub fn mips_context(endian: Endian, pc: u32, sp: u32) -> Section {
+    mips_context_with_flags(endian, 0x40000, pc.into(), sp.into()) // CONTEXT_MIPS
+}
+
+/// Populate a 64-bit `CONTEXT_MIPS` struct with the given `endian`, `pc`, and `sp`.
+pub fn mips64_context(endian: Endian, pc: u64, sp: u64) -> Section {
+    mips_context_with_flags(endian, 0x80000, pc, sp) // CONTEXT_MIPS64
+}
+
+fn mips_context_with_flags(endian: Endian, context_flags: u32, pc: u64, sp: u64) -> Section {
+    let section = Section::with_endian(endian)
+        .D32(context_flags)
+        .D32(0) // _pad0
+        .append_repeated(0, mem::size_of::<u64>() * 29) // iregs[zero, at, ..., gp]
+        .D64(sp) // sp
+        .append_repeated(0, mem::size_of::<u64>() * 2) // fp, ra
+        .D64(0) // mdhi
+        .D64(0) // mdlo
+        .append_repeated(0, mem::size_of::<u32>() * 6) // hi[0-2], lo[0-2]
+        .D32(0) // dsp_control
+        .D32(0) // _pad1
+        .D64(pc) // epc
+        .D64(0) // badvaddr
+        .D32(0) // status
+        .D32(0) // cause
+        .append_repeated(0, mem::size_of::<u64>() * 32) // float_save.regs
+        .D32(0) // float_save.fpcsr
+        .D32(0); // float_save.fir
+    assert_eq!(section.size(), md::CONTEXT_MIPS::size_with(&LE) as u64);
+    section
+}
+
+/// Populate a `CONTEXT_PPC` struct with the given `endian`, `srr0` (pc), and `r1` (sp).
+pub fn ppc_context(endian: Endian, srr0: u32, r1: u32) -> Section {
+    let section = Section::with_endian(endian)
+        .D32(0x20000001) // context_flags: CONTEXT_PPC_BASE
+        .D32(srr0)
+        .D32(0) // srr1
+        .D32(0) // gpr[0]
+        .D32(r1) // gpr[1]
+        .append_repeated(0, mem::size_of::<u32>() * 30) // gpr[2-31]
+        .append_repeated(0, mem::size_of::<u32>() * 6); // cr, xer, lr, ctr, mq, vrsave
+    let section = ppc_save_areas(section);
+    assert_eq!(section.size(), md::CONTEXT_PPC::size_with(&LE) as u64);
+    section
+}
+
+/// Populate a `CONTEXT_PPC64` struct with the given `endian`, `srr0` (pc), and `r1` (sp).
+pub fn ppc64_context(endian: Endian, srr0: u64, r1: u64) -> Section {
+    let section = Section::with_endian(endian)
+        .D64(0x01000001) // context_flags: CONTEXT_PPC64_BASE
+        .D64(srr0)
+        .D64(0) // srr1
+        .D64(0) // gpr[0]
+        .D64(r1) // gpr[1]
+        .append_repeated(0, mem::size_of::<u64>() * 30) // gpr[2-31]
+        .append_repeated(0, mem::size_of::<u64>() * 5); // cr, xer, lr, ctr, vrsave
+    let section = ppc_save_areas(section);
+    assert_eq!(section.size(), md::CONTEXT_PPC64::size_with(&LE) as u64);
+    section
+}
+
+fn ppc_save_areas(section: Section) -> Section {
+    section
+        .append_repeated(0, mem::size_of::<u64>() * 32) // float_save.fpregs
+        .D32(0) // float_save.fpscr_pad
+        .D32(0) // float_save.fpscr
+        .append_repeated(0, mem::size_of::<u128>() * 32) // vector_save.save_vr
+        .append_repeated(0, mem::size_of::<u128>()) // vector_save.save_vscr
+        .append_repeated(0, mem::size_of::<u32>() * 4) // vector_save.save_pad5
+        .D32(0) // vector_save.save_vrvalid
+        .append_repeated(0, mem::size_of::<u32>() * 7) // vector_save.save_pad6
+}
+
 pub struct SectionRef {
     section: Section,
     data_section: Section,
@@ -1898,6 +2513,9 @@ pub enum CpuInfo {
         feature_information: u32,
         amd_extended_cpu_features: u32,
     },
+    OtherCpuInfo {
+        processor_features: [u64; 2],
+    },
 }
 
 impl SystemInfo {
@@ -1968,6 +2586,10 @@ impl From<SystemInfo> for Section {
                 .D32(version_information)
                 .D32(feature_information)
                 .D32(amd_extended_cpu_features),
+            CpuInfo::OtherCpuInfo { processor_features } => section
+                .D64(processor_features[0])
+                .D64(processor_features[1])
+                .append_repeated(0, 8),
         }
     }
 }
@@ -1983,8 +2605,9 @@ pub struct Exception {
     pub thread_id: u32,
     // __align: u32,
     pub exception_record: ExceptionRecord,
-    // TODO: implement this LOCATION_DESCRIPTOR properly
+    /// The raw `thread_context` location, used if [`Exception::context`] isn't set.
     pub thread_context: (u32, u32),
+    context: Option<(Label, Label)>,
 }
 
 pub struct ExceptionRecord {
@@ -2011,8 +2634,42 @@ impl Exception {
                 exception_information: [0; 15],
             },
             thread_context: (0, 0),
+            context: None,
         }
     }
+
+    /// Create a new exception with the given `code`, raised at `address` on
+    /// the thread `thread_id`.
+    pub fn with_code(endian: Endian, code: u32, address: u64, thread_id: u32) -> Self {
+        let mut exception = Self::new(endian);
+        exception.thread_id = thread_id;
+        exception.exception_record.exception_code = code;
+        exception.exception_record.exception_address = address;
+        exception
+    }
+
+    /// Set the exception's parameters (`ExceptionInformation`).
+    ///
+    /// Panics if there are more than 15 parameters.
+    pub fn parameters(mut self, params: &[u64]) -> Self {
+        let record = &mut self.exception_record;
+        assert!(
+            params.len() <= record.exception_information.len(),
+            "too many exception parameters"
+        );
+        record.number_parameters = params.len() as u32;
+        record.exception_information = [0; 15];
+        record.exception_information[..params.len()].copy_from_slice(params);
+        self
+    }
+
+    /// Set the cpu context of the crashing thread.
+    ///
+    /// The context still needs to be added to the minidump.
+    pub fn context<T: DumpSection>(mut self, context: &T) -> Self {
+        self.context = Some((context.file_size(), context.file_offset()));
+        self
+    }
 }
 
 impl_dumpsection!(Exception);
@@ -2034,9 +2691,12 @@ impl From<Exception> for Section {
             section = section.D64(chunk);
         }
 
-        section = section
-            .D32(info.thread_context.0)
-            .D32(info.thread_context.1);
+        section = match info.context {
+            Some(ref context) => section.cite_location(context),
+            None => section
+                .D32(info.thread_context.0)
+                .D32(info.thread_context.1),
+        };
 
         section
     }
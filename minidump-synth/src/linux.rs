@@ -0,0 +1,664 @@
+//! Minidumps of live Linux processes.
+//!
+//! This is a small, dependency-free stand-in for breakpad's client (or the
+//! `minidump-writer` crate) that's good enough to dogfood the processor: it
+//! writes the system info, the process's id and times, the threads with their
+//! registers and stacks, the modules (with their build ids), the exception,
+//! and the usual Linux `/proc` streams (the auxiliary vector among them).
+//!
+//! There are two ways to get a dump:
+//!
+//! * [`LinuxDumper::ptraced`] dumps another process, all of whose threads are
+//!   already stopped under ptrace by the calling thread.
+//! * [`LinuxDumper::current_process`] dumps the calling process from a signal
+//!   handler, using the `siginfo_t` and `ucontext_t` the handler was passed.
+//!   Only the signalled thread is written, because the registers of the other
+//!   threads can't be read without ptracing them from another process.
+//!
+//! ```no_run
+//! use minidump_synth::linux::{CrashContext, LinuxDumper};
+//!
+//! extern "C" fn handler(_: libc::c_int, info: *mut libc::siginfo_t, uc: *mut libc::c_void) {
+//!     let crash = unsafe { CrashContext::new(libc::gettid(), &*info, &*uc.cast::<libc::ucontext_t>()) };
+//!     let dump = LinuxDumper::current_process(crash).dump().unwrap();
+//!     std::fs::write("crash.dmp", dump).unwrap();
+//! }
+//! ```
+
+use std::fs::{self, File};
+use std::io;
+use std::mem;
+use std::os::unix::fs::FileExt;
+
+use minidump_common::format as md;
+use test_assembler::{Endian, Section};
+
+use crate::{
+    DumpSection, DumpString, Exception, Memory, MiscFieldsProcessTimes, MiscStream, Module,
+    SimpleStream, SynthMinidump, SystemInfo, Thread, ThreadName,
+};
+
+/// The endianness of every Linux target we can dump.
+const ENDIAN: Endian = Endian::Little;
+
+/// How much of a thread's stack (above its stack pointer) is written.
+const MAX_STACK_SIZE: u64 = 256 * 1024;
+
+/// The area below the stack pointer that leaf functions may use without
+/// moving it, which is written along with the stack.
+#[cfg(target_arch = "x86_64")]
+const RED_ZONE: u64 = 128;
+#[cfg(target_arch = "aarch64")]
+const RED_ZONE: u64 = 0;
+
+/// The regset of the general purpose registers, for `PTRACE_GETREGSET`.
+///
+/// Not every libc target defines this.
+const NT_PRSTATUS: usize = 1;
+
+/// The note type of a GNU build id.
+const NT_GNU_BUILD_ID: u32 = 3;
+
+/// The register values of a thread, named as in the `*_context_with_registers`
+/// functions.
+type Registers = Vec<(&'static str, u64)>;
+
+/// The signal that a thread of the dumped process got.
+#[derive(Debug, Clone)]
+pub struct CrashContext {
+    /// The id of the thread that got the signal.
+    pub tid: libc::pid_t,
+    /// The signal's number.
+    pub signal: u32,
+    /// The signal's `si_code`.
+    pub code: u32,
+    /// The faulting address, if the signal is a fault, or the instruction
+    /// pointer otherwise.
+    pub address: u64,
+    registers: Registers,
+}
+
+impl CrashContext {
+    /// Collect the signal's information and the registers of the thread `tid`
+    /// at the time of the signal, as passed to an `SA_SIGINFO` signal handler.
+    pub fn new(tid: libc::pid_t, siginfo: &libc::siginfo_t, context: &libc::ucontext_t) -> Self {
+        let registers = context_registers(context);
+        let address = match siginfo.si_signo {
+            // Only faults have a meaningful si_addr, the others have a pid there
+            libc::SIGSEGV | libc::SIGBUS | libc::SIGILL | libc::SIGFPE | libc::SIGTRAP => {
+                // Safety: the signal is a fault, so si_addr is the field that's set
+                unsafe { siginfo.si_addr() as u64 }
+            }
+            _ => register(&registers, PC_REGISTER),
+        };
+        CrashContext {
+            tid,
+            signal: siginfo.si_signo as u32,
+            code: siginfo.si_code as u32,
+            address,
+            registers,
+        }
+    }
+}
+
+/// Where the threads of the dumped process are.
+#[derive(Debug)]
+enum Target {
+    /// Another process, whose threads are stopped under ptrace.
+    Ptraced,
+    /// The calling process, which can only provide the crashing thread.
+    Current,
+}
+
+/// Writes a minidump of a live process.
+///
+/// See the [module documentation][self] for the two kinds of processes that
+/// can be dumped.
+#[derive(Debug)]
+pub struct LinuxDumper {
+    pid: libc::pid_t,
+    target: Target,
+    crash: Option<CrashContext>,
+}
+
+impl LinuxDumper {
+    /// Dump the process `pid`.
+    ///
+    /// Every thread of the process must be stopped under ptrace by the calling
+    /// thread, since that's the only way to read their registers.
+    pub fn ptraced(pid: libc::pid_t) -> Self {
+        LinuxDumper {
+            pid,
+            target: Target::Ptraced,
+            crash: None,
+        }
+    }
+
+    /// Dump the calling process, which got the signal described by `crash`.
+    pub fn current_process(crash: CrashContext) -> Self {
+        LinuxDumper {
+            // Safety: getpid can't fail
+            pid: unsafe { libc::getpid() },
+            target: Target::Current,
+            crash: Some(crash),
+        }
+    }
+
+    /// Record the signal that a thread of a ptraced process got, as the
+    /// dump's exception.
+    ///
+    /// The signalled thread's registers are taken from `crash`, rather than
+    /// from ptrace (which would have the registers of the signal handler, if
+    /// the thread is stopped in one).
+    pub fn crash_context(mut self, crash: CrashContext) -> Self {
+        self.crash = Some(crash);
+        self
+    }
+
+    /// Write the minidump.
+    pub fn dump(&self) -> io::Result<Vec<u8>> {
+        let proc_dir = format!("/proc/{}", self.pid);
+        let maps_contents = fs::read(format!("{proc_dir}/maps"))?;
+        let maps = parse_maps(&String::from_utf8_lossy(&maps_contents));
+        let mem = File::open(format!("{proc_dir}/mem"))?;
+
+        let mut misc = MiscStream::new(ENDIAN);
+        misc.process_id = Some(self.pid as u32);
+        misc.process_times = process_times(&proc_dir);
+        let mut dump = SynthMinidump::with_endian(ENDIAN)
+            .add_system_info(system_info())
+            .add_stream(misc);
+
+        let mut crash_thread_context = None;
+        for (tid, registers) in self.threads(&proc_dir)? {
+            let sp = register(&registers, SP_REGISTER);
+            let stack = read_stack(&mem, &maps, sp);
+            let context = context_section(&registers);
+            let thread = Thread::new(ENDIAN, tid as u32, &stack, &context);
+
+            if let Ok(name) = fs::read_to_string(format!("{proc_dir}/task/{tid}/comm")) {
+                let name = DumpString::new(name.trim_end_matches('\n'), ENDIAN);
+                dump = dump
+                    .add_thread_name(ThreadName::new(ENDIAN, tid as u32, Some(&name)))
+                    .add(name);
+            }
+            if self.crash.as_ref().is_some_and(|crash| crash.tid == tid) {
+                crash_thread_context = Some((context.file_size(), context.file_offset()));
+            }
+            dump = dump.add_thread(thread).add(context).add_memory(stack);
+        }
+
+        if let Some(crash) = &self.crash {
+            let mut exception =
+                Exception::with_code(ENDIAN, crash.signal, crash.address, crash.tid as u32);
+            exception.exception_record.exception_flags = crash.code;
+            exception.context = crash_thread_context;
+            dump = dump.add_exception(exception);
+        }
+
+        for module in find_modules(&mem, &maps) {
+            let name = DumpString::new(&module.path, ENDIAN);
+            let cv_record = Section::with_endian(ENDIAN)
+                .D32(md::CvSignature::Elf as u32)
+                .append_bytes(&module.build_id);
+            let version = md::VS_FIXEDFILEINFO::default();
+            let entry = Module::new(ENDIAN, module.base, module.size, &name, 0, 0, &version)
+                .cv_record(&cv_record);
+            dump = dump.add_module(entry).add(name).add(cv_record);
+        }
+
+        dump = dump.set_linux_maps(&maps_contents);
+        if let Ok(status) = fs::read(format!("{proc_dir}/status")) {
+            dump = dump.set_linux_proc_status(&status);
+        }
+        if let Ok(limits) = fs::read(format!("{proc_dir}/limits")) {
+            dump = dump.set_linux_proc_limits(&limits);
+        }
+        if let Ok(environ) = fs::read(format!("{proc_dir}/environ")) {
+            dump = dump.set_linux_environ(&environ);
+        }
+        if let Ok(cmdline) = fs::read(format!("{proc_dir}/cmdline")) {
+            dump = dump.set_linux_cmdline(&cmdline);
+        }
+        if let Ok(auxv) = fs::read(format!("{proc_dir}/auxv")) {
+            dump = dump.add_stream(SimpleStream {
+                stream_type: md::MINIDUMP_STREAM_TYPE::LinuxAuxv as u32,
+                section: Section::with_endian(ENDIAN).append_bytes(&auxv),
+            });
+        }
+        if let Ok(cpu_info) = fs::read("/proc/cpuinfo") {
+            dump = dump.set_linux_cpu_info(&cpu_info);
+        }
+        if let Ok(lsb_release) = fs::read("/etc/lsb-release") {
+            dump = dump.set_linux_lsb_release(&lsb_release);
+        }
+
+        dump.finish()
+            .ok_or_else(|| io::Error::other("couldn't lay out the minidump"))
+    }
+
+    /// The threads to write, with their registers.
+    fn threads(&self, proc_dir: &str) -> io::Result<Vec<(libc::pid_t, Registers)>> {
+        match self.target {
+            Target::Current => Ok(self
+                .crash
+                .iter()
+                .map(|crash| (crash.tid, crash.registers.clone()))
+                .collect()),
+            Target::Ptraced => {
+                let mut tids = fs::read_dir(format!("{proc_dir}/task"))?
+                    .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+                    .collect::<Vec<libc::pid_t>>();
+                tids.sort_unstable();
+                tids.into_iter()
+                    .map(|tid| match &self.crash {
+                        Some(crash) if crash.tid == tid => Ok((tid, crash.registers.clone())),
+                        _ => Ok((tid, ptrace_registers(tid)?)),
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// A line of `/proc/<pid>/maps`.
+#[derive(Debug)]
+struct Mapping {
+    start: u64,
+    end: u64,
+    executable: bool,
+    offset: u64,
+    path: Option<String>,
+}
+
+fn parse_maps(maps: &str) -> Vec<Mapping> {
+    maps.lines()
+        .filter_map(|line| {
+            // start-end perms offset dev inode [path]
+            let mut fields = line.splitn(6, ' ');
+            let (start, end) = fields.next()?.split_once('-')?;
+            let perms = fields.next()?;
+            let offset = fields.next()?;
+            let path = fields.nth(2).map(str::trim).filter(|path| !path.is_empty());
+            Some(Mapping {
+                start: u64::from_str_radix(start, 16).ok()?,
+                end: u64::from_str_radix(end, 16).ok()?,
+                executable: perms.contains('x'),
+                offset: u64::from_str_radix(offset, 16).ok()?,
+                path: path.map(str::to_owned),
+            })
+        })
+        .collect()
+}
+
+/// Read `len` bytes of the process' memory at `address`.
+fn read_memory(mem: &File, address: u64, len: u64) -> Option<Vec<u8>> {
+    let mut bytes = vec![0; usize::try_from(len).ok()?];
+    mem.read_exact_at(&mut bytes, address).ok()?;
+    Some(bytes)
+}
+
+/// The stack of a thread whose stack pointer is `sp`.
+fn read_stack(mem: &File, maps: &[Mapping], sp: u64) -> Memory {
+    let start = sp.saturating_sub(RED_ZONE);
+    let bytes = maps
+        .iter()
+        .find(|mapping| mapping.start <= sp && sp < mapping.end)
+        .and_then(|mapping| {
+            let start = start.max(mapping.start);
+            let end = mapping.end.min(start.saturating_add(MAX_STACK_SIZE));
+            read_memory(mem, start, end - start).map(|bytes| (start, bytes))
+        });
+    match bytes {
+        Some((start, bytes)) => Memory::with_bytes(start, &bytes),
+        None => Memory::with_bytes(sp, &[]),
+    }
+}
+
+/// An ELF module mapped into the process.
+#[derive(Debug)]
+struct MappedModule {
+    base: u64,
+    size: u32,
+    path: String,
+    build_id: Vec<u8>,
+}
+
+/// The ELF files mapped into the process, which have some code mapped.
+///
+/// A module is all the consecutive mappings of a file, starting with one of
+/// its beginning (which has the ELF header).
+fn find_modules(mem: &File, maps: &[Mapping]) -> Vec<MappedModule> {
+    let mut modules = Vec::new();
+    let mut i = 0;
+    while i < maps.len() {
+        let first = &maps[i];
+        let mut mappings = 1;
+        if let Some(path) = &first.path {
+            mappings += maps[i + 1..]
+                .iter()
+                .take_while(|mapping| mapping.path.as_ref() == Some(path))
+                .count();
+        }
+        let module_maps = &maps[i..i + mappings];
+        i += mappings;
+
+        let Some(path) = &first.path else {
+            continue;
+        };
+        let is_file = path.starts_with('/') || path == "[vdso]";
+        if !is_file || first.offset != 0 || !module_maps.iter().any(|m| m.executable) {
+            continue;
+        }
+        let end = module_maps.last().map_or(first.end, |mapping| mapping.end);
+        let Some(header) = read_memory(mem, first.start, ELF64_HEADER_SIZE) else {
+            continue;
+        };
+        if !header.starts_with(b"\x7fELF") {
+            continue;
+        }
+        let build_id = elf_build_id(mem, first.start, &header).unwrap_or_else(|| {
+            let text = module_maps.iter().find(|mapping| mapping.executable);
+            text.map(|text| text_hash(mem, text)).unwrap_or_default()
+        });
+        modules.push(MappedModule {
+            base: first.start,
+            size: u32::try_from(end - first.start).unwrap_or(u32::MAX),
+            path: path.clone(),
+            build_id,
+        });
+    }
+    modules
+}
+
+const ELF64_HEADER_SIZE: u64 = 64;
+const ELF64_PHDR_SIZE: u64 = 56;
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        bytes.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+/// The GNU build id of the (64-bit) ELF file mapped at `base`, read from its
+/// notes.
+fn elf_build_id(mem: &File, base: u64, header: &[u8]) -> Option<Vec<u8>> {
+    const ELFCLASS64: u8 = 2;
+    if header.get(4) != Some(&ELFCLASS64) {
+        return None;
+    }
+    let phoff = u64_at(header, 0x20)?;
+    let phnum = u16_at(header, 0x38)?;
+    let phdrs = read_memory(
+        mem,
+        base.checked_add(phoff)?,
+        ELF64_PHDR_SIZE * phnum as u64,
+    )?;
+    let phdrs = phdrs
+        .chunks_exact(ELF64_PHDR_SIZE as usize)
+        .map(|phdr| Some((u32_at(phdr, 0)?, u64_at(phdr, 0x10)?, u64_at(phdr, 0x28)?)))
+        .collect::<Option<Vec<(u32, u64, u64)>>>()?;
+
+    // The module is mapped at `base`, its lowest segment is linked at `0` for
+    // a shared library (and at its actual address for an executable).
+    let lowest_vaddr = phdrs
+        .iter()
+        .filter(|&&(p_type, ..)| p_type == PT_LOAD)
+        .map(|&(_, vaddr, _)| vaddr & !0xfff)
+        .min()?;
+    let bias = base.wrapping_sub(lowest_vaddr);
+
+    phdrs
+        .iter()
+        .filter(|&&(p_type, ..)| p_type == PT_NOTE)
+        .find_map(|&(_, vaddr, size)| {
+            let notes = read_memory(mem, bias.wrapping_add(vaddr), size)?;
+            find_build_id_note(&notes)
+        })
+}
+
+fn find_build_id_note(mut notes: &[u8]) -> Option<Vec<u8>> {
+    let align4 = |n: usize| (n + 3) & !3;
+    while notes.len() >= 12 {
+        let name_size = u32_at(notes, 0)? as usize;
+        let desc_size = u32_at(notes, 4)? as usize;
+        let note_type = u32_at(notes, 8)?;
+        let desc_start = 12 + align4(name_size);
+        let name = notes.get(12..12 + name_size)?;
+        let desc = notes.get(desc_start..desc_start + desc_size)?;
+        if note_type == NT_GNU_BUILD_ID && name == b"GNU\0" {
+            return Some(desc.to_owned());
+        }
+        notes = notes.get(desc_start + align4(desc_size)..)?;
+    }
+    None
+}
+
+/// The identifier breakpad uses for modules without a build id: the first
+/// page of their code, XORed into 16 bytes.
+fn text_hash(mem: &File, text: &Mapping) -> Vec<u8> {
+    let len = (text.end - text.start).min(4096);
+    let mut hash = vec![0; 16];
+    if let Some(page) = read_memory(mem, text.start, len) {
+        for chunk in page.chunks(16) {
+            for (hash, byte) in hash.iter_mut().zip(chunk) {
+                *hash ^= byte;
+            }
+        }
+    }
+    hash
+}
+
+/// When the process started (in seconds since the epoch), and the user and
+/// kernel time it used (in seconds), from its `stat` and the boot time.
+fn process_times(proc_dir: &str) -> Option<MiscFieldsProcessTimes> {
+    let stat = fs::read_to_string(format!("{proc_dir}/stat")).ok()?;
+    // The command name (in parentheses) may contain spaces, the fields after it
+    // start with the third one, the state.
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let field = |number: usize| -> Option<u64> { fields.get(number - 3)?.parse().ok() };
+    let boot_time: u64 = fs::read_to_string("/proc/stat")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))?
+        .trim()
+        .parse()
+        .ok()?;
+    // Safety: sysconf can't fail for a valid name
+    let ticks_per_second = u64::try_from(unsafe { libc::sysconf(libc::_SC_CLK_TCK) })
+        .ok()
+        .filter(|&ticks| ticks > 0)?;
+    let seconds = |ticks: u64| u32::try_from(ticks / ticks_per_second).unwrap_or(u32::MAX);
+    Some(MiscFieldsProcessTimes {
+        process_create_time: u32::try_from(boot_time + field(22)? / ticks_per_second)
+            .unwrap_or(u32::MAX),
+        process_user_time: seconds(field(14)?),
+        process_kernel_time: seconds(field(15)?),
+    })
+}
+
+fn system_info() -> SystemInfo {
+    #[cfg(target_arch = "x86_64")]
+    let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64;
+    #[cfg(target_arch = "aarch64")]
+    let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_ARM64;
+
+    let mut info = SystemInfo::new(ENDIAN)
+        .set_processor_architecture(arch as u16)
+        .set_platform_id(md::PlatformId::Linux as u32);
+    // Safety: sysconf can't fail for a valid name
+    let cpus = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    info.number_of_processors = u8::try_from(cpus).unwrap_or(u8::MAX);
+
+    // Safety: uname fills in the zeroed struct
+    let mut uname: libc::utsname = unsafe { mem::zeroed() };
+    if unsafe { libc::uname(&mut uname) } == 0 {
+        // Safety: the release is nul-terminated
+        let release = unsafe { std::ffi::CStr::from_ptr(uname.release.as_ptr()) };
+        let mut version = release
+            .to_string_lossy()
+            .split(|c: char| !c.is_ascii_digit())
+            .map(|n| n.parse().unwrap_or(0))
+            .collect::<Vec<u32>>()
+            .into_iter();
+        info.major_version = version.next().unwrap_or(0);
+        info.minor_version = version.next().unwrap_or(0);
+        info.build_number = version.next().unwrap_or(0);
+    }
+    info
+}
+
+fn register(registers: &Registers, name: &str) -> u64 {
+    registers
+        .iter()
+        .find(|&&(register, _)| register == name)
+        .map_or(0, |&(_, value)| value)
+}
+
+/// Read the registers of the thread `tid`, which is stopped under ptrace.
+fn ptrace_registers(tid: libc::pid_t) -> io::Result<Registers> {
+    // Safety: user_regs_struct is plain old data
+    let mut regs: libc::user_regs_struct = unsafe { mem::zeroed() };
+    let mut iov = libc::iovec {
+        iov_base: (&mut regs as *mut libc::user_regs_struct).cast(),
+        iov_len: mem::size_of::<libc::user_regs_struct>(),
+    };
+    // Safety: PTRACE_GETREGSET writes at most `iov_len` bytes to `iov_base`
+    let result = unsafe {
+        libc::ptrace(
+            libc::PTRACE_GETREGSET,
+            tid,
+            NT_PRSTATUS as *mut libc::c_void,
+            &mut iov as *mut libc::iovec,
+        )
+    };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(user_regs_registers(&regs))
+}
+
+#[cfg(target_arch = "x86_64")]
+const PC_REGISTER: &str = "rip";
+#[cfg(target_arch = "x86_64")]
+const SP_REGISTER: &str = "rsp";
+
+#[cfg(target_arch = "x86_64")]
+fn context_section(registers: &Registers) -> Section {
+    crate::amd64_context_with_registers(ENDIAN, registers)
+}
+
+#[cfg(target_arch = "x86_64")]
+fn context_registers(context: &libc::ucontext_t) -> Registers {
+    let gregs = &context.uc_mcontext.gregs;
+    let reg = |index: libc::c_int| gregs[index as usize] as u64;
+    // cs, gs and fs, from the bottom up
+    let csgsfs = reg(libc::REG_CSGSFS);
+    vec![
+        ("rax", reg(libc::REG_RAX)),
+        ("rcx", reg(libc::REG_RCX)),
+        ("rdx", reg(libc::REG_RDX)),
+        ("rbx", reg(libc::REG_RBX)),
+        ("rsp", reg(libc::REG_RSP)),
+        ("rbp", reg(libc::REG_RBP)),
+        ("rsi", reg(libc::REG_RSI)),
+        ("rdi", reg(libc::REG_RDI)),
+        ("r8", reg(libc::REG_R8)),
+        ("r9", reg(libc::REG_R9)),
+        ("r10", reg(libc::REG_R10)),
+        ("r11", reg(libc::REG_R11)),
+        ("r12", reg(libc::REG_R12)),
+        ("r13", reg(libc::REG_R13)),
+        ("r14", reg(libc::REG_R14)),
+        ("r15", reg(libc::REG_R15)),
+        ("rip", reg(libc::REG_RIP)),
+        ("eflags", reg(libc::REG_EFL)),
+        ("cs", csgsfs & 0xffff),
+        ("gs", (csgsfs >> 16) & 0xffff),
+        ("fs", (csgsfs >> 32) & 0xffff),
+    ]
+}
+
+#[cfg(target_arch = "x86_64")]
+fn user_regs_registers(regs: &libc::user_regs_struct) -> Registers {
+    vec![
+        ("rax", regs.rax),
+        ("rcx", regs.rcx),
+        ("rdx", regs.rdx),
+        ("rbx", regs.rbx),
+        ("rsp", regs.rsp),
+        ("rbp", regs.rbp),
+        ("rsi", regs.rsi),
+        ("rdi", regs.rdi),
+        ("r8", regs.r8),
+        ("r9", regs.r9),
+        ("r10", regs.r10),
+        ("r11", regs.r11),
+        ("r12", regs.r12),
+        ("r13", regs.r13),
+        ("r14", regs.r14),
+        ("r15", regs.r15),
+        ("rip", regs.rip),
+        ("eflags", regs.eflags),
+        ("cs", regs.cs),
+        ("ds", regs.ds),
+        ("es", regs.es),
+        ("fs", regs.fs),
+        ("gs", regs.gs),
+        ("ss", regs.ss),
+    ]
+}
+
+#[cfg(target_arch = "aarch64")]
+const PC_REGISTER: &str = "pc";
+#[cfg(target_arch = "aarch64")]
+const SP_REGISTER: &str = "sp";
+
+#[cfg(target_arch = "aarch64")]
+fn context_section(registers: &Registers) -> Section {
+    crate::arm64_context_with_registers(ENDIAN, registers)
+}
+
+/// The names of x0-x30, as the `arm64_context_with_registers` function
+/// knows them.
+#[cfg(target_arch = "aarch64")]
+const ARM64_GPRS: [&str; 31] = [
+    "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13", "x14",
+    "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27",
+    "x28", "fp", "lr",
+];
+
+#[cfg(target_arch = "aarch64")]
+fn arm64_registers(gprs: &[u64; 31], sp: u64, pc: u64, pstate: u64) -> Registers {
+    ARM64_GPRS
+        .iter()
+        .copied()
+        .zip(gprs.iter().copied())
+        .chain([("sp", sp), ("pc", pc), ("cpsr", pstate)])
+        .collect()
+}
+
+#[cfg(target_arch = "aarch64")]
+fn context_registers(context: &libc::ucontext_t) -> Registers {
+    let mcontext = &context.uc_mcontext;
+    arm64_registers(&mcontext.regs, mcontext.sp, mcontext.pc, mcontext.pstate)
+}
+
+#[cfg(target_arch = "aarch64")]
+fn user_regs_registers(regs: &libc::user_regs_struct) -> Registers {
+    arm64_registers(&regs.regs, regs.sp, regs.pc, regs.pstate)
+}
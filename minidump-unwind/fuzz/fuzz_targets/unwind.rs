@@ -48,8 +48,12 @@ impl TestFixture {
             os: Os::Windows,
             os_version: None,
             os_build: None,
+            os_build_lab: None,
+            os_distro: None,
             cpu: Cpu::X86_64,
+            cpu_emulation: None,
             cpu_info: None,
+            cpu_features: Vec::new(),
             cpu_microcode_version: None,
             cpu_count: 1,
         };
@@ -10,7 +10,8 @@ use super::impl_prelude::*;
 use minidump::format::CONTEXT_AMD64;
 use minidump::system_info::Os;
 use minidump::{
-    MinidumpContext, MinidumpContextValidity, MinidumpModuleList, MinidumpRawContext, UnifiedMemory,
+    CpuContext, MinidumpContext, MinidumpContextValidity, MinidumpModuleList, MinidumpRawContext,
+    UnifiedMemory, UnifiedMemoryList,
 };
 use std::collections::HashSet;
 use tracing::trace;
@@ -26,7 +27,7 @@ const CALLEE_SAVED_REGS: &[&str] = &["rbx", "rbp", "r12", "r13", "r14", "r15"];
 async fn get_caller_by_cfi<P>(
     ctx: &CONTEXT_AMD64,
     args: &GetCallerFrameArgs<'_, P>,
-) -> Option<StackFrame>
+) -> Result<StackFrame, WalkFrameError>
 where
     P: SymbolProvider + Sync,
 {
@@ -34,15 +35,15 @@ where
 
     if let MinidumpContextValidity::Some(ref which) = args.valid() {
         if !which.contains(STACK_POINTER_REGISTER) {
-            return None;
+            return Err(WalkFrameError::MissingRegister(
+                STACK_POINTER_REGISTER.to_owned(),
+            ));
         }
     }
 
-    let mut stack_walker = CfiStackWalker::from_ctx_and_args(ctx, args, callee_forwarded_regs)?;
+    let mut stack_walker = CfiStackWalker::from_ctx_and_args(ctx, args, callee_forwarded_regs);
 
-    args.symbol_provider
-        .walk_frame(stack_walker.module, &mut stack_walker)
-        .await?;
+    stack_walker.walk(args.symbol_provider).await?;
     let caller_ip = stack_walker.caller_ctx.rip;
     let caller_sp = stack_walker.caller_ctx.rsp;
 
@@ -63,7 +64,7 @@ where
         raw: MinidumpRawContext::Amd64(stack_walker.caller_ctx),
         valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
     };
-    Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
+    Ok(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
 
 fn callee_forwarded_regs(valid: &MinidumpContextValidity) -> HashSet<&'static str> {
@@ -188,6 +189,140 @@ where
     Some(StackFrame::from_context(context, FrameTrust::FramePointer))
 }
 
+async fn get_caller_by_leaf<P>(
+    ctx: &CONTEXT_AMD64,
+    args: &GetCallerFrameArgs<'_, P>,
+) -> Option<StackFrame>
+where
+    P: SymbolProvider + Sync,
+{
+    // A true leaf function never touches rsp or rbp, and a function that crashed
+    // in its prologue hasn't pushed rbp yet, so the return address is still at
+    // [rsp] and every callee-saved register still has the caller's value. Frame
+    // pointers would then find (at best) the caller's caller, and skip the caller.
+    //
+    // Only the context frame can be in that state: every other frame is stopped
+    // at a call. Without CFI we can't know, so this is only a guess that the
+    // candidate return address has to back up.
+    if args.callee_frame.trust != FrameTrust::Context {
+        return None;
+    }
+
+    trace!("trying leaf");
+    if let MinidumpContextValidity::Some(ref which) = args.valid() {
+        if !which.contains(STACK_POINTER_REGISTER) {
+            return None;
+        }
+    }
+
+    let last_sp = ctx.rsp;
+    let caller_ip = args.stack_memory.get_memory_at_address(last_sp)?;
+    let caller_sp = last_sp.checked_add(POINTER_WIDTH)?;
+    if !instruction_seems_valid(caller_ip, args.modules, args.symbol_provider).await {
+        trace!("rejecting leaf result for invalid return address");
+        return None;
+    }
+    // If the code before the return address was captured, it should be the call
+    // that got us here. (If it wasn't, we have to trust the module.)
+    if let Some(code) = args
+        .memory
+        .and_then(|memory| preceding_code(memory, caller_ip))
+    {
+        if !ends_with_call(&code) {
+            trace!("rejecting leaf result for return address not following a call");
+            return None;
+        }
+    }
+
+    trace!(
+        "leaf seems valid -- caller_ip: 0x{:016x}, caller_sp: 0x{:016x}",
+        caller_ip,
+        caller_sp,
+    );
+
+    let mut caller_ctx = CONTEXT_AMD64 {
+        rip: caller_ip,
+        rsp: caller_sp,
+        ..CONTEXT_AMD64::default()
+    };
+    let mut valid = callee_forwarded_regs(args.valid());
+    for &reg in &valid {
+        caller_ctx.set_register(reg, ctx.get_register_always(reg));
+    }
+    valid.insert(INSTRUCTION_REGISTER);
+    valid.insert(STACK_POINTER_REGISTER);
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::Amd64(caller_ctx),
+        valid: MinidumpContextValidity::Some(valid),
+    };
+    Some(StackFrame::from_context(context, FrameTrust::Leaf))
+}
+
+/// The longest CALL instruction [`ends_with_call`] recognizes.
+const MAX_CALL_LEN: usize = 7;
+
+/// The captured code right before `return_address`, if there's enough of it.
+fn preceding_code(
+    memory: &UnifiedMemoryList,
+    return_address: Pointer,
+) -> Option<[u8; MAX_CALL_LEN]> {
+    let start = return_address.checked_sub(MAX_CALL_LEN as Pointer)?;
+    let region = memory.memory_at_address(start)?;
+    let offset = (start - region.base_address()) as usize;
+    let mut code = [0; MAX_CALL_LEN];
+    code.copy_from_slice(region.bytes().get(offset..offset + MAX_CALL_LEN)?);
+    Some(code)
+}
+
+/// Whether `code` ends with a CALL instruction: either a relative `call rel32`,
+/// or an indirect `call r/m64` (with any addressing mode and REX prefix).
+///
+/// This can't be sure, it's just that other code rarely ends with these bytes.
+fn ends_with_call(code: &[u8; MAX_CALL_LEN]) -> bool {
+    // call rel32 (E8 xx xx xx xx)
+    if code[MAX_CALL_LEN - 5] == 0xe8 {
+        return true;
+    }
+    // call r/m64 (FF /2), which is 2 to 7 bytes depending on the ModRM byte
+    // (and SIB byte, if there is one).
+    (2..=MAX_CALL_LEN).any(|len| {
+        let start = MAX_CALL_LEN - len;
+        if code[start] != 0xff {
+            return false;
+        }
+        let modrm = code[start + 1];
+        let sib = code.get(start + 2).copied();
+        (modrm >> 3) & 0b111 == 2 && modrm_instruction_len(modrm, sib) == Some(len)
+    })
+}
+
+/// The length of an instruction that's an opcode byte followed by `modrm` (and
+/// `sib`, if the ModRM byte says there's one), or `None` if we don't know it.
+fn modrm_instruction_len(modrm: u8, sib: Option<u8>) -> Option<usize> {
+    let mode = modrm >> 6;
+    let rm = modrm & 0b111;
+    let len = match (mode, rm) {
+        (0b11, _) => 2,
+        // [sib], which is [disp32 + index] if the base is 0b101
+        (0b00, 0b100) => {
+            if sib? & 0b111 == 0b101 {
+                7
+            } else {
+                3
+            }
+        }
+        // [rip + disp32]
+        (0b00, 0b101) => 6,
+        (0b00, _) => 2,
+        (0b01, 0b100) => 4,
+        (0b01, _) => 3,
+        (0b10, 0b100) => 7,
+        (0b10, _) => 6,
+        _ => unreachable!(),
+    };
+    Some(len)
+}
+
 async fn get_caller_by_scan<P>(
     ctx: &CONTEXT_AMD64,
     args: &GetCallerFrameArgs<'_, P>,
@@ -391,14 +526,17 @@ fn is_non_canonical(ptr: Pointer) -> bool {
 pub async fn get_caller_frame<P>(
     ctx: &CONTEXT_AMD64,
     args: &GetCallerFrameArgs<'_, P>,
-) -> Option<StackFrame>
+) -> Result<StackFrame, WalkTerminatedReason>
 where
     P: SymbolProvider + Sync,
 {
     // .await doesn't like closures, so don't use Option chaining
-    let mut frame = None;
+    let (mut frame, cfi_error) = match get_caller_by_cfi(ctx, args).await {
+        Ok(frame) => (Some(frame), None),
+        Err(error) => (None, Some(error)),
+    };
     if frame.is_none() {
-        frame = get_caller_by_cfi(ctx, args).await;
+        frame = get_caller_by_leaf(ctx, args).await;
     }
     if frame.is_none() {
         frame = get_caller_by_frame_pointer(ctx, args);
@@ -406,7 +544,8 @@ where
     if frame.is_none() {
         frame = get_caller_by_scan(ctx, args).await;
     }
-    let mut frame = frame?;
+    let mut frame = frame.ok_or(WalkTerminatedReason::NoCallerFound)?;
+    frame.cfi_error = cfi_error;
 
     // We now check the frame to see if it looks like unwinding is complete,
     // based on the frame we computed having a nonsense value. Returning
@@ -416,14 +555,14 @@ where
     // null, and we can assume unwinding is complete.
     if frame.context.get_instruction_pointer() < 4096 {
         trace!("instruction pointer was nullish, assuming unwind complete");
-        return None;
+        return Err(WalkTerminatedReason::NullInstructionPointer);
     }
     // If the new stack pointer is at a lower address than the old,
     // then that's clearly incorrect. Treat this as end-of-stack to
     // enforce progress and avoid infinite loops.
     if frame.context.get_stack_pointer() <= ctx.rsp {
         trace!("stack pointer went backwards, assuming unwind complete");
-        return None;
+        return Err(WalkTerminatedReason::StackPointerNotIncreasing);
     }
 
     // Ok, the frame now seems well and truly valid, do final cleanup.
@@ -436,5 +575,5 @@ where
     let ip = frame.context.get_instruction_pointer();
     frame.instruction = ip - 1;
 
-    Some(frame)
+    Ok(frame)
 }
@@ -12,6 +12,8 @@ struct TestFixture {
     pub modules: MinidumpModuleList,
     pub system_info: SystemInfo,
     pub symbols: HashMap<String, String>,
+    /// Captured code, as (address, bytes).
+    pub code: Vec<(u64, Vec<u8>)>,
 }
 
 impl TestFixture {
@@ -28,16 +30,29 @@ impl TestFixture {
                 os: Os::Linux,
                 os_version: None,
                 os_build: None,
+                os_build_lab: None,
+                os_distro: None,
                 cpu: Cpu::X86_64,
+                cpu_emulation: None,
                 cpu_info: None,
+                cpu_features: Vec::new(),
                 cpu_microcode_version: None,
                 cpu_count: 1,
             },
             symbols: HashMap::new(),
+            code: Vec::new(),
         }
     }
 
     pub async fn walk_stack(&self, stack: Section) -> CallStack {
+        let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
+        self.walk_stack_with_provider(stack, &symbolizer).await
+    }
+
+    pub async fn walk_stack_with_provider<P>(&self, stack: Section, provider: &P) -> CallStack
+    where
+        P: SymbolProvider + Sync,
+    {
         let context = MinidumpContext {
             raw: MinidumpRawContext::Amd64(self.raw.clone()),
             valid: MinidumpContextValidity::All,
@@ -52,17 +67,29 @@ impl TestFixture {
             bytes: &stack,
             endian: scroll::LE,
         };
-        let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
+        let code = UnifiedMemoryList::Memory(MinidumpMemoryList::from_regions(
+            self.code
+                .iter()
+                .map(|(base_address, bytes)| MinidumpMemory {
+                    desc: Default::default(),
+                    base_address: *base_address,
+                    size: bytes.len() as u64,
+                    bytes,
+                    endian: scroll::LE,
+                })
+                .collect(),
+        ));
         let mut stack = CallStack::with_context(context);
 
-        walk_stack(
+        walk_stack_with_memory(
             0,
             (),
             &mut stack,
             Some(UnifiedMemory::Memory(stack_memory)),
+            Some(&code),
             &self.modules,
             &self.system_info,
-            &symbolizer,
+            provider,
         )
         .await;
 
@@ -776,6 +803,8 @@ async fn test_scan_walk_overflow() {
     f.raw.rip = 0x00007400c0000200;
     f.raw.rbp = frame1_rbp.value().unwrap();
     f.raw.rsp = stack.start().value().unwrap();
+    // The return address doesn't follow a call, so frame 0 isn't taken for a leaf.
+    f.code.push((return_address1 - 7, vec![0x90; 7]));
 
     let s = f.walk_stack(stack).await;
     assert_eq!(s.frames.len(), 2);
@@ -808,3 +837,267 @@ async fn test_scan_walk_overflow() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_jit_unwind_info() {
+    // A JITed function (not in any module) that pushed %rbp, and whose unwind
+    // info is provided by the JIT.
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    let stack_start = 0x8000000080000000;
+    let return_address = 0x00007500b0000110;
+    stack.start().set_const(stack_start);
+
+    let frame1_sp = Label::new();
+    stack = stack
+        // frame 0
+        .D64(0x8000000080000100) // saved %rbp
+        .D64(return_address)
+        // frame 1
+        .mark(&frame1_sp)
+        .append_repeated(0, 32);
+
+    f.raw.rip = 0x000000003a5c0010;
+    f.raw.rsp = stack_start;
+    f.raw.rbp = stack_start;
+
+    let jit_info = "STACK CFI INIT 3a5c0000 40 .cfa: $rsp 8 + .ra: .cfa -8 + ^\n\
+                    STACK CFI 3a5c0001 .cfa: $rsp 16 + $rbp: .cfa -16 + ^\n";
+    let mut provider = MultiSymbolProvider::new();
+    provider.add(Box::new(Symbolizer::new(string_symbol_supplier(
+        f.symbols.clone(),
+    ))));
+    provider.add(Box::new(
+        jit::JitUnwindInfoProvider::from_bytes(jit_info.as_bytes()).unwrap(),
+    ));
+
+    let s = f.walk_stack_with_provider(stack, &provider).await;
+    assert_eq!(s.frames.len(), 2);
+    assert!(s.frames[0].module.is_none());
+
+    let frame = &s.frames[1];
+    assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
+    assert_eq!(frame.module.as_ref().unwrap().code_file(), "module2");
+    if let MinidumpRawContext::Amd64(ctx) = &frame.context.raw {
+        assert_eq!(ctx.rip, return_address);
+        assert_eq!(ctx.rsp, frame1_sp.value().unwrap());
+        assert_eq!(ctx.rbp, 0x8000000080000100);
+    } else {
+        unreachable!();
+    }
+}
+
+#[tokio::test]
+async fn test_frame_pointer_cycle() {
+    // A corrupt chain of frame pointers that loops back on itself: frame 1's
+    // saved %rbp points back to frame 0's. Following it would walk back down
+    // the stack, so the walk has to find another way or stop.
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    let stack_start = 0x8000000080000000;
+    let return_address1 = 0x00007500b0000110;
+    let return_address2 = 0x00007500b0000220;
+    stack.start().set_const(stack_start);
+
+    let frame0_rbp = Label::new();
+    let frame1_sp = Label::new();
+    let frame1_rbp = Label::new();
+    let frame2_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 16) // space
+        .mark(&frame0_rbp)
+        .D64(&frame1_rbp) // caller-pushed %rbp
+        .D64(return_address1) // actual return address
+        // frame 1
+        .mark(&frame1_sp)
+        .append_repeated(0, 32) // body of frame1
+        .mark(&frame1_rbp)
+        .D64(&frame0_rbp) // EVIL CYCLIC FRAME POINTER
+        .D64(return_address2)
+        // frame 2
+        .mark(&frame2_sp)
+        .append_repeated(0, 32); // no more return addresses
+
+    f.raw.rip = 0x00007400c0000200;
+    f.raw.rbp = frame0_rbp.value().unwrap();
+    f.raw.rsp = stack.start().value().unwrap();
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 3);
+    for pair in s.frames.windows(2) {
+        assert!(pair[1].context.get_stack_pointer() > pair[0].context.get_stack_pointer());
+    }
+
+    // Frame 1 is fine, but frame 1's saved %rbp is below frame 2's %rsp, so
+    // frame 2 can only be found by scanning.
+    assert_eq!(s.frames[1].trust, FrameTrust::FramePointer);
+    let f2 = &s.frames[2];
+    assert_eq!(f2.trust, FrameTrust::Scan);
+    if let MinidumpRawContext::Amd64(ctx) = &f2.context.raw {
+        assert_eq!(ctx.rip, return_address2);
+        assert_eq!(ctx.rsp, frame2_sp.value().unwrap());
+    } else {
+        unreachable!();
+    }
+    assert_eq!(
+        s.walk_terminated_reason,
+        Some(WalkTerminatedReason::NoCallerFound)
+    );
+}
+
+/// A stack where frame 0 hasn't pushed %rbp (or never does), so its return
+/// address is at %rsp and %rbp is still its caller's frame pointer.
+///
+/// Returns the stack, frame 1's %rbp and the return addresses of frames 0 and 1.
+fn leaf_stack(f: &mut TestFixture) -> (Section, Label, u64, u64) {
+    let mut stack = Section::new();
+    let stack_start = 0x8000000080000000;
+    let return_address1 = 0x00007500b0000110;
+    let return_address2 = 0x00007500b0000220;
+    stack.start().set_const(stack_start);
+
+    let frame1_rbp = Label::new();
+    let frame2_rbp = Label::new();
+
+    stack = stack
+        // frame 0
+        .D64(return_address1) // return address, pushed by the call
+        // frame 1
+        .append_repeated(0, 32) // body of frame1
+        .mark(&frame1_rbp)
+        .D64(&frame2_rbp) // caller-pushed %rbp
+        .D64(return_address2)
+        // frame 2
+        .append_repeated(0, 32) // body of frame2
+        .mark(&frame2_rbp) // end of stack
+        .D64(0);
+
+    f.raw.rip = 0x00007400c0000200;
+    f.raw.rbp = frame1_rbp.value().unwrap();
+    f.raw.rsp = stack_start;
+    f.raw.rbx = 0x1234;
+
+    (stack, frame1_rbp, return_address1, return_address2)
+}
+
+#[tokio::test]
+async fn test_leaf_mid_prologue() {
+    // Frame 0 crashed on the first instruction of a function, before it pushed
+    // %rbp. Following %rbp would skip frame 1.
+    let mut f = TestFixture::new();
+    let (stack, frame1_rbp, return_address1, return_address2) = leaf_stack(&mut f);
+    // call rel32
+    f.code.push((
+        return_address1 - 7,
+        vec![0x90, 0x90, 0xe8, 0xeb, 0x00, 0x00, 0x10],
+    ));
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 3);
+
+    let f1 = &s.frames[1];
+    assert_eq!(f1.trust, FrameTrust::Leaf);
+    if let MinidumpContextValidity::Some(ref which) = f1.context.valid {
+        assert!(which.contains("rip"));
+        assert!(which.contains("rsp"));
+        assert!(which.contains("rbp"));
+        assert!(which.contains("rbx"));
+    } else {
+        unreachable!();
+    }
+    if let MinidumpRawContext::Amd64(ctx) = &f1.context.raw {
+        assert_eq!(ctx.rip, return_address1);
+        assert_eq!(ctx.rsp, f.raw.rsp + 8);
+        assert_eq!(ctx.rbp, frame1_rbp.value().unwrap());
+        assert_eq!(ctx.rbx, 0x1234);
+    } else {
+        unreachable!();
+    }
+
+    let f2 = &s.frames[2];
+    assert_eq!(f2.trust, FrameTrust::FramePointer);
+    assert_eq!(f2.context.get_instruction_pointer(), return_address2);
+}
+
+#[tokio::test]
+async fn test_leaf_function() {
+    // Frame 0 is a leaf function that never sets up a frame at all. The code
+    // before the return address isn't captured, so it's only checked against
+    // the modules.
+    let mut f = TestFixture::new();
+    let (stack, _frame1_rbp, return_address1, return_address2) = leaf_stack(&mut f);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 3);
+    assert_eq!(s.frames[1].trust, FrameTrust::Leaf);
+    assert_eq!(
+        s.frames[1].context.get_instruction_pointer(),
+        return_address1
+    );
+    assert_eq!(s.frames[2].trust, FrameTrust::FramePointer);
+    assert_eq!(
+        s.frames[2].context.get_instruction_pointer(),
+        return_address2
+    );
+
+    // Indirect calls are recognized too: call [rip + disp32], call r11
+    for call in [
+        vec![0x90, 0xff, 0x15, 0x00, 0x10, 0x00, 0x00],
+        vec![0x90, 0x90, 0x90, 0x90, 0x41, 0xff, 0xd3],
+    ] {
+        let mut f = TestFixture::new();
+        let (stack, _frame1_rbp, return_address1, _return_address2) = leaf_stack(&mut f);
+        f.code.push((return_address1 - 7, call));
+
+        let s = f.walk_stack(stack).await;
+        assert_eq!(s.frames[1].trust, FrameTrust::Leaf);
+        assert_eq!(
+            s.frames[1].context.get_instruction_pointer(),
+            return_address1
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_leaf_not_after_call() {
+    // Frame 0 has set up its frame, and the top of its stack happens to hold a
+    // pointer into code, but not one right after a call. That's not a return
+    // address, so frame pointers are used.
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    let stack_start = 0x8000000080000000;
+    let code_pointer = 0x00007500b0000080;
+    let return_address = 0x00007500b0000110;
+    stack.start().set_const(stack_start);
+
+    let frame0_rbp = Label::new();
+    let frame1_rbp = Label::new();
+
+    stack = stack
+        // frame 0
+        .D64(code_pointer) // a local that looks like a return address
+        .append_repeated(0, 16) // space
+        .mark(&frame0_rbp)
+        .D64(&frame1_rbp) // caller-pushed %rbp
+        .D64(return_address) // actual return address
+        // frame 1
+        .append_repeated(0, 32) // body of frame1
+        .mark(&frame1_rbp) // end of stack
+        .D64(0);
+
+    f.raw.rip = 0x00007400c0000200;
+    f.raw.rbp = frame0_rbp.value().unwrap();
+    f.raw.rsp = stack.start().value().unwrap();
+    f.code.push((
+        code_pointer - 7,
+        vec![0x48, 0x89, 0xe5, 0x48, 0x83, 0xec, 0x20],
+    ));
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+    let f1 = &s.frames[1];
+    assert_eq!(f1.trust, FrameTrust::FramePointer);
+    assert_eq!(f1.context.get_instruction_pointer(), return_address);
+}
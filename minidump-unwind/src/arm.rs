@@ -26,19 +26,19 @@ const CALLEE_SAVED_REGS: &[&str] = &["r4", "r5", "r6", "r7", "r8", "r9", "r10",
 async fn get_caller_by_cfi<P>(
     ctx: &ArmContext,
     args: &GetCallerFrameArgs<'_, P>,
-) -> Option<StackFrame>
+) -> Result<StackFrame, WalkFrameError>
 where
     P: SymbolProvider + Sync,
 {
     trace!("trying cfi");
 
-    let _last_sp = ctx.get_register(STACK_POINTER, args.valid())?;
+    let _last_sp = ctx
+        .get_register(STACK_POINTER, args.valid())
+        .ok_or_else(|| WalkFrameError::MissingRegister(STACK_POINTER.to_owned()))?;
 
-    let mut stack_walker = CfiStackWalker::from_ctx_and_args(ctx, args, callee_forwarded_regs)?;
+    let mut stack_walker = CfiStackWalker::from_ctx_and_args(ctx, args, callee_forwarded_regs);
 
-    args.symbol_provider
-        .walk_frame(stack_walker.module, &mut stack_walker)
-        .await?;
+    stack_walker.walk(args.symbol_provider).await?;
     let caller_pc = stack_walker.caller_ctx.get_register_always(PROGRAM_COUNTER);
     let caller_sp = stack_walker.caller_ctx.get_register_always(STACK_POINTER);
 
@@ -57,7 +57,7 @@ where
         raw: MinidumpRawContext::Arm(stack_walker.caller_ctx),
         valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
     };
-    Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
+    Ok(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
 
 fn callee_forwarded_regs(valid: &MinidumpContextValidity) -> HashSet<&'static str> {
@@ -128,7 +128,15 @@ where
         )
     };
 
-    // Don't do any more validation, just assume it worked.
+    // The caller's frame must be above ours and still in the stack, or we'd be
+    // following a corrupt (possibly cyclic) chain of frame pointers. (A null
+    // frame pointer ends the walk in get_caller_frame instead.)
+    if last_fp != 0
+        && !frame_pointer_stack_seems_valid(caller_sp as u64, last_sp as u64, args.stack_memory)
+    {
+        trace!("rejecting frame pointer result for unreasonable stack pointer");
+        return None;
+    }
 
     trace!(
         "frame pointer seems valid -- caller_pc: 0x{:016x}, caller_sp: 0x{:016x}",
@@ -275,22 +283,23 @@ fn stack_seems_valid(
 pub async fn get_caller_frame<P>(
     ctx: &ArmContext,
     args: &GetCallerFrameArgs<'_, P>,
-) -> Option<StackFrame>
+) -> Result<StackFrame, WalkTerminatedReason>
 where
     P: SymbolProvider + Sync,
 {
     // .await doesn't like closures, so don't use Option chaining
-    let mut frame = None;
-    if frame.is_none() {
-        frame = get_caller_by_cfi(ctx, args).await;
-    }
+    let (mut frame, cfi_error) = match get_caller_by_cfi(ctx, args).await {
+        Ok(frame) => (Some(frame), None),
+        Err(error) => (None, Some(error)),
+    };
     if frame.is_none() {
         frame = get_caller_by_frame_pointer(ctx, args);
     }
     if frame.is_none() {
         frame = get_caller_by_scan(ctx, args).await;
     }
-    let mut frame = frame?;
+    let mut frame = frame.ok_or(WalkTerminatedReason::NoCallerFound)?;
+    frame.cfi_error = cfi_error;
 
     // We now check the frame to see if it looks like unwinding is complete,
     // based on the frame we computed having a nonsense value. Returning
@@ -300,7 +309,7 @@ where
     // null, and we can assume unwinding is complete.
     if frame.context.get_instruction_pointer() < 4096 {
         trace!("instruction pointer was nullish, assuming unwind complete");
-        return None;
+        return Err(WalkTerminatedReason::NullInstructionPointer);
     }
     // If the new stack pointer is at a lower address than the old,
     // then that's clearly incorrect. Treat this as end-of-stack to
@@ -316,7 +325,7 @@ where
         let is_leaf = args.callee_frame.trust == FrameTrust::Context && sp == last_sp;
         if !is_leaf {
             trace!("stack pointer went backwards, assuming unwind complete");
-            return None;
+            return Err(WalkTerminatedReason::StackPointerNotIncreasing);
         }
     }
 
@@ -330,5 +339,5 @@ where
     let ip = frame.context.get_instruction_pointer();
     frame.instruction = ip - 2;
 
-    Some(frame)
+    Ok(frame)
 }
@@ -77,8 +77,12 @@ impl TestFixture {
             os: Os::Windows,
             os_version: None,
             os_build: None,
+            os_build_lab: None,
+            os_distro: None,
             cpu: Cpu::Arm64,
+            cpu_emulation: None,
             cpu_info: None,
+            cpu_features: Vec::new(),
             cpu_microcode_version: None,
             cpu_count: 1,
         };
@@ -1320,7 +1324,7 @@ async fn test_frame_pointer_infinite_equality() {
     f.raw.set_register("sp", stack.start().value().unwrap());
 
     let s = f.walk_stack(stack).await;
-    assert_eq!(s.frames.len(), 2);
+    assert_eq!(s.frames.len(), 3);
 
     {
         // Frame 0
@@ -1355,5 +1359,110 @@ async fn test_frame_pointer_infinite_equality() {
         }
     }
 
-    // Never get to frame 2, alas!
+    {
+        // Frame 2: the frame pointer didn't make progress, so it was rejected
+        // in favour of scanning, which finds the real caller.
+        let frame = &s.frames[2];
+        assert_eq!(frame.trust, FrameTrust::Scan);
+        if let MinidumpRawContext::Arm64(ctx) = &frame.context.raw {
+            assert_eq!(ctx.get_register_always("pc"), return_address2);
+        } else {
+            unreachable!();
+        }
+    }
+}
+
+/// Check that the stack pointer strictly increases from each frame to its caller,
+/// and that no (sp, pc) pair repeats.
+fn assert_walk_makes_progress(s: &CallStack) {
+    for pair in s.frames.windows(2) {
+        assert!(pair[1].context.get_stack_pointer() > pair[0].context.get_stack_pointer());
+    }
+    let mut seen = std::collections::HashSet::new();
+    for frame in &s.frames {
+        assert!(seen.insert((
+            frame.context.get_stack_pointer(),
+            frame.context.get_instruction_pointer()
+        )));
+    }
+}
+
+#[tokio::test]
+async fn test_frame_pointer_cycle() {
+    // A corrupt chain of frame pointers that loops back on itself: frame 1's
+    // saved frame pointer points back to frame 0's. Following it would walk
+    // back down the stack, so the walk has to find another way or stop.
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x50000100u64;
+    let return_address2 = 0x50000900u64;
+    let frame1_sp = Label::new();
+    let frame0_fp = Label::new();
+    let frame1_fp = Label::new();
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 64) // space
+        .mark(&frame0_fp)
+        .D64(&frame1_fp) // save current frame pointer
+        .D64(return_address1) // save current link register
+        .mark(&frame1_sp)
+        // frame 1
+        .append_repeated(0, 64) // space
+        .mark(&frame1_fp)
+        .D64(&frame0_fp) // EVIL CYCLIC FRAME POINTER
+        .D64(return_address2)
+        .append_repeated(0, 64); // no more return addresses
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("lr", 0x1fe0fe10);
+    f.raw.set_register("fp", frame0_fp.value().unwrap());
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_walk_makes_progress(&s);
+    assert_eq!(s.frames.len(), 3);
+    assert_eq!(s.frames[1].trust, FrameTrust::FramePointer);
+    assert_eq!(s.frames[2].trust, FrameTrust::FramePointer);
+    assert_eq!(
+        s.frames[2].context.get_instruction_pointer(),
+        return_address2
+    );
+    // Frame 2's frame pointer is frame 0's, which would take us back down the
+    // stack, and scanning finds no more return addresses.
+    assert_eq!(
+        s.walk_terminated_reason,
+        Some(WalkTerminatedReason::NoCallerFound)
+    );
+}
+
+#[tokio::test]
+async fn test_repeated_frame() {
+    // A leaf function is allowed to not touch the stack, so the caller's stack
+    // pointer may be the same. But if the return address is the instruction
+    // we're already at, the caller would be the same frame again.
+    let mut f = TestFixture::new();
+    let symbols = [
+        "FUNC 4000 1000 10 enchiridion\n",
+        "STACK CFI INIT 4000 1000 .cfa: sp 0 + .ra: x30\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+    stack = stack.append_repeated(0, 64);
+
+    f.raw.set_register("pc", 0x40004010);
+    f.raw.set_register("lr", 0x40004010);
+    f.raw.set_register("fp", 0);
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 1);
+    assert_eq!(
+        s.walk_terminated_reason,
+        Some(WalkTerminatedReason::RepeatedFrame)
+    );
 }
@@ -28,8 +28,12 @@ impl TestFixture {
                 os: Os::Ios,
                 os_version: None,
                 os_build: None,
+                os_build_lab: None,
+                os_distro: None,
                 cpu: Cpu::Arm,
+                cpu_emulation: None,
                 cpu_info: None,
+                cpu_features: Vec::new(),
                 cpu_microcode_version: None,
                 cpu_count: 1,
             },
@@ -678,7 +682,7 @@ async fn test_frame_pointer_infinite_equality() {
         .set_register("sp", stack.start().value().unwrap() as u32);
 
     let s = f.walk_stack(stack).await;
-    assert_eq!(s.frames.len(), 2);
+    assert_eq!(s.frames.len(), 3);
 
     {
         // Frame 0
@@ -713,7 +717,17 @@ async fn test_frame_pointer_infinite_equality() {
         }
     }
 
-    // Never get to frame 2, alas!
+    {
+        // Frame 2: the frame pointer didn't make progress, so it was rejected
+        // in favour of scanning, which finds the real caller.
+        let frame = &s.frames[2];
+        assert_eq!(frame.trust, FrameTrust::Scan);
+        if let MinidumpRawContext::Arm(ctx) = &frame.context.raw {
+            assert_eq!(ctx.get_register_always("pc"), return_address2);
+        } else {
+            unreachable!();
+        }
+    }
 }
 
 const CALLEE_SAVE_REGS: &[&str] = &["pc", "sp", "r4", "r5", "r6", "r7", "r8", "r9", "r10", "fp"];
@@ -11,6 +11,8 @@ mod arm;
 mod arm64;
 mod arm64_old;
 mod mips;
+mod ppc;
+mod ppc64;
 pub mod symbols;
 pub mod system_info;
 mod x86;
@@ -21,6 +23,7 @@ use scroll::ctx::{SizeWith, TryFromCtx};
 use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::convert::TryFrom;
+use std::fmt;
 use std::io::{self, Write};
 use tracing::trace;
 
@@ -32,6 +35,8 @@ struct GetCallerFrameArgs<'a, P> {
     callee_frame: &'a StackFrame,
     grand_callee_frame: Option<&'a StackFrame>,
     stack_memory: UnifiedMemory<'a, 'a>,
+    /// All the memory of the process, to check code bytes against.
+    memory: Option<&'a UnifiedMemoryList<'a>>,
     modules: &'a MinidumpModuleList,
     system_info: &'a SystemInfo,
     symbol_provider: &'a P,
@@ -45,7 +50,8 @@ impl<P> GetCallerFrameArgs<'_, P> {
 
 mod impl_prelude {
     pub(crate) use super::{
-        CfiStackWalker, FrameTrust, GetCallerFrameArgs, StackFrame, SymbolProvider,
+        frame_pointer_stack_seems_valid, CfiStackWalker, FrameTrust, GetCallerFrameArgs,
+        StackFrame, SymbolProvider, WalkFrameError, WalkTerminatedReason,
     };
 }
 
@@ -62,6 +68,9 @@ pub enum FrameTrust {
     CfiScan,
     /// Derived from frame pointer.
     FramePointer,
+    /// Assumed the callee was a leaf function (or hadn't set up its frame
+    /// yet), so the return address was at the top of the stack.
+    Leaf,
     /// Derived from call frame info.
     CallFrameInfo,
     /// Explicitly provided by some external stack walker.
@@ -80,6 +89,7 @@ impl FrameTrust {
             FrameTrust::CallFrameInfo => "call frame info",
             FrameTrust::CfiScan => "call frame info with scanning",
             FrameTrust::FramePointer => "previous frame's frame pointer",
+            FrameTrust::Leaf => "return address at the top of a leaf frame",
             FrameTrust::Scan => "stack scanning",
             FrameTrust::None => "unknown",
         }
@@ -92,6 +102,7 @@ impl FrameTrust {
             FrameTrust::CallFrameInfo => "cfi",
             FrameTrust::CfiScan => "cfi_scan",
             FrameTrust::FramePointer => "frame_pointer",
+            FrameTrust::Leaf => "leaf",
             FrameTrust::Scan => "scan",
             FrameTrust::None => "non",
         }
@@ -125,6 +136,18 @@ pub struct FunctionArg {
     pub value: Option<u64>,
 }
 
+/// A value read from where a frame's parameters should be on the stack.
+///
+/// See [`StackFrame::heuristic_parameters`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeuristicParameter {
+    /// The value, or `None` if the stack memory doesn't cover it.
+    pub value: Option<u64>,
+    /// `module!function` (or `module + offset`) if the value is an address
+    /// inside a loaded module.
+    pub symbol: Option<String>,
+}
+
 /// A stack frame for an inlined function.
 ///
 /// See [`StackFrame::inlines`][] for more details.
@@ -136,6 +159,9 @@ pub struct InlineFrame {
     pub source_file_name: Option<String>,
     /// The line number of the stack frame
     pub source_line: Option<u32>,
+    /// Where the file is in version control, if `source_file_name` is a source
+    /// indexed path, see [`VcsSourceFile`].
+    pub source_file_vcs: Option<VcsSourceFile>,
 }
 
 /// A single stack frame produced from unwinding a thread's stack.
@@ -224,6 +250,11 @@ pub struct StackFrame {
     /// The source file name, may be omitted if debug symbols are not available.
     pub source_file_name: Option<String>,
 
+    /// Where the source file is in version control, if the symbols are source
+    /// indexed (`source_file_name` is then a path like
+    /// `hg:hg.mozilla.org/mozilla-central:dom/base/Element.cpp:1a2b3c4d5e6f`).
+    pub source_file_vcs: Option<VcsSourceFile>,
+
     /// The (1-based) source line number, may be omitted if debug symbols are
     /// not available.
     pub source_line: Option<u32>,
@@ -268,6 +299,81 @@ pub struct StackFrame {
 
     /// Any function args we recovered.
     pub arguments: Option<FunctionArgs>,
+
+    /// The values on the stack where the parameters of an x86 function should
+    /// be, one per 4 bytes of the function's parameter size.
+    ///
+    /// This is a heuristic: nothing guarantees the function didn't overwrite
+    /// them (or that the symbols' parameter size is right). It's only filled
+    /// in if the processor was asked to recover arguments.
+    pub heuristic_parameters: Option<Vec<HeuristicParameter>>,
+
+    /// The source code around `source_line`, if the processor was asked to
+    /// find it and could.
+    pub source_context: Option<SourceContext>,
+
+    /// How symbolicating the instruction went, or `None` if it isn't in any
+    /// module (so there was nothing to look up).
+    pub symbol_disposition: Option<SymbolDisposition>,
+
+    /// What kind of symbol record `function_name` came from, if it was set by
+    /// a symbol file.
+    pub symbol_kind: Option<SymbolKind>,
+
+    /// Why CFI couldn't find this frame, if it was found by other means
+    /// (frame pointers or stack scanning).
+    pub cfi_error: Option<WalkFrameError>,
+
+    /// Arbitrary key/value annotations added by the user of the stack walker,
+    /// e.g. the team that owns the frame's module.
+    ///
+    /// The stack walker itself never adds any.
+    pub annotations: BTreeMap<String, String>,
+
+    /// Whether the frame's function is a thunk, like a `foo@plt` stub, an
+    /// import thunk, or profiling instrumentation (`_penter`), that only
+    /// forwards to the function that does the actual work.
+    ///
+    /// The stack walker itself never sets this, the processor does after
+    /// symbolicating the frame.
+    pub is_thunk: bool,
+}
+
+/// How symbolicating a frame's instruction went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolDisposition {
+    /// A FUNC or PUBLIC record of the module's symbols covers the instruction.
+    Symbolized,
+    /// The module's symbols were found, but none of their FUNC or PUBLIC records
+    /// cover the instruction.
+    ///
+    /// Compilers don't emit code outside of functions, so this is a strong hint
+    /// that the instruction is in a trampoline or a hook that was written over
+    /// the module's code at runtime.
+    NoCoveringSymbol,
+    /// There are no symbols for the module.
+    SymbolsMissing,
+}
+
+impl SymbolDisposition {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            SymbolDisposition::Symbolized => "symbolized",
+            SymbolDisposition::NoCoveringSymbol => "no_covering_symbol",
+            SymbolDisposition::SymbolsMissing => "symbols_missing",
+        }
+    }
+}
+
+/// Some lines of a source file, around the line a frame was executing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceContext {
+    /// The (1-based) line number of the first of `lines`.
+    pub first_line: u32,
+    /// The line the frame was executing.
+    pub line: u32,
+    /// The lines, without their line terminators.
+    pub lines: Vec<String>,
 }
 
 impl StackFrame {
@@ -283,10 +389,18 @@ impl StackFrame {
             function_base: None,
             parameter_size: None,
             source_file_name: None,
+            source_file_vcs: None,
             source_line: None,
             source_line_base: None,
             inlines: Vec::new(),
             arguments: None,
+            heuristic_parameters: None,
+            source_context: None,
+            symbol_disposition: None,
+            symbol_kind: None,
+            cfi_error: None,
+            annotations: BTreeMap::new(),
+            is_thunk: false,
             trust,
             context,
         }
@@ -302,8 +416,15 @@ impl FrameSymbolizer for StackFrame {
         self.function_base = Some(base);
         self.parameter_size = Some(parameter_size);
     }
+    fn set_parameter_size(&mut self, parameter_size: u32) {
+        self.parameter_size = Some(parameter_size);
+    }
+    fn set_symbol_quality(&mut self, kind: SymbolKind, _offset_from_symbol: u64) {
+        self.symbol_kind = Some(kind);
+    }
     fn set_source_file(&mut self, file: &str, line: u32, base: u64) {
         self.source_file_name = Some(String::from(file));
+        self.source_file_vcs = VcsSourceFile::parse(file);
         self.source_line = Some(line);
         self.source_line_base = Some(base);
     }
@@ -314,6 +435,7 @@ impl FrameSymbolizer for StackFrame {
         self.inlines.push(InlineFrame {
             function_name: name.to_string(),
             source_file_name: file.map(ToString::to_string),
+            source_file_vcs: file.and_then(VcsSourceFile::parse),
             source_line: line,
         })
     }
@@ -334,6 +456,153 @@ pub enum CallStackInfo {
     DumpThreadSkipped,
 }
 
+/// Why unwinding a thread's stack stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalkTerminatedReason {
+    /// None of the unwinding methods found a plausible caller (usually because
+    /// the outermost frame was reached).
+    NoCallerFound,
+    /// The caller's instruction pointer was null (or close to it), which is how
+    /// many stacks end.
+    NullInstructionPointer,
+    /// The caller's stack pointer wasn't above the callee's. The stack grows
+    /// down, so the stack is corrupt (or the unwind went wrong).
+    StackPointerNotIncreasing,
+    /// The caller's stack pointer and instruction pointer were the same as those
+    /// of a frame that was already walked, so the walk would have looped forever.
+    RepeatedFrame,
+    /// There was no stack memory, so only the context frame could be found.
+    MissingStackMemory,
+}
+
+impl WalkTerminatedReason {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            WalkTerminatedReason::NoCallerFound => "no_caller_found",
+            WalkTerminatedReason::NullInstructionPointer => "null_instruction_pointer",
+            WalkTerminatedReason::StackPointerNotIncreasing => "stack_pointer_not_increasing",
+            WalkTerminatedReason::RepeatedFrame => "repeated_frame",
+            WalkTerminatedReason::MissingStackMemory => "missing_stack_memory",
+        }
+    }
+}
+
+/// How a thread's stack memory was repaired, because the minidump's description
+/// of it didn't match the thread's context.
+///
+/// Some minidump writers (notably older breakpad clients) produce stack memory
+/// descriptors that don't cover the thread's stack pointer, or that are empty.
+/// The memory is usually still in the minidump's memory list, so the region of
+/// it that the thread's registers point to is walked instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackMemoryRepair {
+    /// The thread's stack memory didn't contain its stack pointer, so the
+    /// memory region that does was used instead.
+    StackPointerOutsideStack {
+        stack_pointer: u64,
+        stack_base: u64,
+        stack_size: u64,
+        region_base: u64,
+        region_size: u64,
+    },
+    /// The thread's stack memory descriptor had no bytes, so the memory region
+    /// at its base address was used instead.
+    EmptyStack {
+        stack_base: u64,
+        region_base: u64,
+        region_size: u64,
+    },
+}
+
+impl StackMemoryRepair {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            StackMemoryRepair::StackPointerOutsideStack { .. } => "stack_pointer_outside_stack",
+            StackMemoryRepair::EmptyStack { .. } => "empty_stack",
+        }
+    }
+}
+
+impl fmt::Display for StackMemoryRepair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            StackMemoryRepair::StackPointerOutsideStack {
+                stack_pointer,
+                stack_base,
+                stack_size,
+                region_base,
+                region_size,
+            } => write!(
+                f,
+                "the stack memory at {stack_base:#x} ({stack_size:#x} bytes) doesn't contain the stack pointer {stack_pointer:#x}, so the memory region at {region_base:#x} ({region_size:#x} bytes) was used instead"
+            ),
+            StackMemoryRepair::EmptyStack {
+                stack_base,
+                region_base,
+                region_size,
+            } => write!(
+                f,
+                "the stack memory at {stack_base:#x} is empty, so the memory region at {region_base:#x} ({region_size:#x} bytes) was used instead"
+            ),
+        }
+    }
+}
+
+/// Whether a frame pointer unwind from a callee with `callee_sp` to a caller
+/// with `caller_sp` is plausible.
+///
+/// The stack grows down, so the caller's stack pointer must be above the
+/// callee's. It must also still be in the thread's stack (or just past its end,
+/// for the outermost frame).
+fn frame_pointer_stack_seems_valid(
+    caller_sp: u64,
+    callee_sp: u64,
+    stack_memory: UnifiedMemory<'_, '_>,
+) -> bool {
+    let Some(range) = stack_memory.memory_range() else {
+        return false;
+    };
+    caller_sp > callee_sp && range.start <= caller_sp && caller_sp <= range.end.saturating_add(1)
+}
+
+/// Raw timing information about a thread.
+///
+/// These values come straight from the `MINIDUMP_THREAD_INFO` of the thread.
+/// `create_time` and `exit_time` are Windows FILETIMEs (100-nanosecond intervals
+/// since January 1, 1601 UTC), while `kernel_time` and `user_time` are amounts
+/// of time in 100-nanosecond intervals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThreadTimes {
+    /// When the thread was created.
+    pub create_time: u64,
+    /// When the thread exited, or 0 if it was still running.
+    pub exit_time: u64,
+    /// Time spent executing in kernel mode.
+    pub kernel_time: u64,
+    /// Time spent executing in user mode.
+    pub user_time: u64,
+}
+
+/// What a thread's own bookkeeping in the process's memory says about it.
+///
+/// On Windows this is the thread's TEB. Everything but the address comes from
+/// memory that may be missing from the minidump, in which case it's `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThreadIdentity {
+    /// The address of the structure this was read from.
+    pub address: u64,
+    /// The id of the thread the structure belongs to.
+    pub thread_id: Option<u32>,
+    /// The highest address of the thread's stack.
+    pub stack_base: Option<u64>,
+    /// The lowest address of the thread's stack that was in use.
+    pub stack_limit: Option<u64>,
+    /// The name of the thread with `thread_id`, if known.
+    pub thread_name: Option<String>,
+    /// The address the thread with `thread_id` started executing at, if known.
+    pub start_address: Option<u64>,
+}
+
 /// A stack of `StackFrame`s produced as a result of unwinding a thread.
 #[derive(Debug, Clone)]
 pub struct CallStack {
@@ -350,6 +619,29 @@ pub struct CallStack {
     pub thread_name: Option<String>,
     /// The GetLastError() value stored in the TEB.
     pub last_error_value: Option<CrashReason>,
+    /// The address the thread started executing at, if known.
+    pub start_address: Option<u64>,
+    /// A symbolicated description of `start_address` (e.g. `ntdll!TppWorkerThread`),
+    /// if the address could be mapped to a module.
+    pub start_function: Option<String>,
+    /// Timing information about the thread, if known.
+    pub times: Option<ThreadTimes>,
+    /// Why the stack walk stopped, if the stack was walked.
+    pub walk_terminated_reason: Option<WalkTerminatedReason>,
+    /// How the thread's stack memory was repaired, if it had to be.
+    pub stack_memory_repair: Option<StackMemoryRepair>,
+    /// The thread's own context in the minidump's thread list (or why it
+    /// couldn't be read), if it was looked at.
+    ///
+    /// This is the context of the first frame, except for the thread that
+    /// requested the dump, whose first frame has the exception's context.
+    pub thread_context: Option<Result<MinidumpContext, ContextError>>,
+    /// The frames as the stack walker found them, if thunk frames were
+    /// merged out of `frames` and the processor was asked to keep them.
+    pub raw_frames: Option<Vec<StackFrame>>,
+    /// What the thread's own bookkeeping says about it, if it was read (only
+    /// for the thread that requested the dump).
+    pub thread_identity: Option<ThreadIdentity>,
 }
 
 impl CallStack {
@@ -363,6 +655,14 @@ impl CallStack {
             thread_id: 0,
             thread_name: None,
             last_error_value: None,
+            start_address: None,
+            start_function: None,
+            times: None,
+            walk_terminated_reason: None,
+            stack_memory_repair: None,
+            thread_context: None,
+            raw_frames: None,
+            thread_identity: None,
         }
     }
 
@@ -374,6 +674,27 @@ impl CallStack {
             thread_id: id,
             thread_name: None,
             last_error_value: None,
+            start_address: None,
+            start_function: None,
+            times: None,
+            walk_terminated_reason: None,
+            stack_memory_repair: None,
+            thread_context: None,
+            raw_frames: None,
+            thread_identity: None,
+        }
+    }
+
+    /// Write the registers of the thread's own context (see
+    /// [`CallStack::thread_context`]) to `f`, in the format of [`CallStack::print`].
+    pub fn print_thread_registers<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        match &self.thread_context {
+            Some(Ok(context)) => {
+                writeln!(f, "Thread registers:")?;
+                print_registers(f, context)
+            }
+            Some(Err(error)) => writeln!(f, "Thread registers: unavailable ({error})"),
+            None => writeln!(f, "Thread registers: unavailable"),
         }
     }
 
@@ -382,36 +703,9 @@ impl CallStack {
     /// This is very verbose, it implements the output format used by
     /// minidump_stackwalk.
     pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
-        fn print_registers<T: Write>(f: &mut T, ctx: &MinidumpContext) -> io::Result<()> {
-            let registers: Cow<HashSet<&str>> = match ctx.valid {
-                MinidumpContextValidity::All => {
-                    let gpr = ctx.general_purpose_registers();
-                    let set: HashSet<&str> = gpr.iter().cloned().collect();
-                    Cow::Owned(set)
-                }
-                MinidumpContextValidity::Some(ref which) => Cow::Borrowed(which),
-            };
-
-            // Iterate over registers in a known order.
-            let mut output = String::new();
-            for reg in ctx.general_purpose_registers() {
-                if registers.contains(reg) {
-                    let reg_val = ctx.format_register(reg);
-                    let next = format!(" {reg: >6} = {reg_val}");
-                    if output.chars().count() + next.chars().count() > 80 {
-                        // Flush the buffer.
-                        writeln!(f, " {output}")?;
-                        output.truncate(0);
-                    }
-                    output.push_str(&next);
-                }
-            }
-            if !output.is_empty() {
-                writeln!(f, " {output}")?;
-            }
-            Ok(())
+        if let Some(repair) = self.stack_memory_repair {
+            writeln!(f, "Warning: {repair}")?;
         }
-
         if self.frames.is_empty() {
             writeln!(f, "<no frames>")?;
         }
@@ -512,11 +806,29 @@ impl CallStack {
 
             // And the trust we have of this result
             writeln!(f, "    Found by: {}", frame.trust.description())?;
+            if frame.symbol_disposition == Some(SymbolDisposition::NoCoveringSymbol) {
+                writeln!(
+                    f,
+                    "    No function in the module's symbols covers this address"
+                )?;
+            }
+
+            if let Some(source) = &frame.source_context {
+                for (line_number, line) in (source.first_line..).zip(&source.lines) {
+                    let marker = if line_number == source.line { '>' } else { ' ' };
+                    writeln!(f, "    {marker}{line_number:>6} | {line}")?;
+                }
+            }
+
+            for (key, value) in &frame.annotations {
+                writeln!(f, "    Annotation: {key} = {value}")?;
+            }
 
             // Now print out recovered args
             if let Some(args) = &frame.arguments {
                 use MinidumpRawContext::*;
                 let pointer_width = match &frame.context.raw {
+                    Mips(ctx) if ctx.is_mips64() => 8,
                     X86(_) | Ppc(_) | Sparc(_) | Arm(_) | Mips(_) => 4,
                     Ppc64(_) | Amd64(_) | Arm64(_) | OldArm64(_) => 8,
                 };
@@ -545,11 +857,58 @@ impl CallStack {
                 // it more readable.
                 writeln!(f)?;
             }
+
+            if let Some(params) = &frame.heuristic_parameters {
+                writeln!(f, "    Parameters (heuristic, read from the stack)")?;
+                for (idx, param) in params.iter().enumerate() {
+                    write!(f, "        param {idx} = ")?;
+                    match param.value {
+                        Some(val) => write!(f, "0x{val:08x}")?,
+                        None => write!(f, "<unknown>")?,
+                    }
+                    match &param.symbol {
+                        Some(symbol) => writeln!(f, " ({symbol})")?,
+                        None => writeln!(f)?,
+                    }
+                }
+                writeln!(f)?;
+            }
         }
         Ok(())
     }
 }
 
+/// Write the valid general purpose registers of `ctx` to `f`, a few to a line.
+fn print_registers<T: Write>(f: &mut T, ctx: &MinidumpContext) -> io::Result<()> {
+    let registers: Cow<HashSet<&str>> = match ctx.valid {
+        MinidumpContextValidity::All => {
+            let gpr = ctx.general_purpose_registers();
+            let set: HashSet<&str> = gpr.iter().cloned().collect();
+            Cow::Owned(set)
+        }
+        MinidumpContextValidity::Some(ref which) => Cow::Borrowed(which),
+    };
+
+    // Iterate over registers in a known order.
+    let mut output = String::new();
+    for reg in ctx.general_purpose_registers() {
+        if registers.contains(reg) {
+            let reg_val = ctx.format_register(reg);
+            let next = format!(" {reg: >6} = {reg_val}");
+            if output.chars().count() + next.chars().count() > 80 {
+                // Flush the buffer.
+                writeln!(f, " {output}")?;
+                output.truncate(0);
+            }
+            output.push_str(&next);
+        }
+    }
+    if !output.is_empty() {
+        writeln!(f, " {output}")?;
+    }
+    Ok(())
+}
+
 struct CfiStackWalker<'a, C: CpuContext> {
     instruction: u64,
     has_grand_callee: bool,
@@ -561,7 +920,9 @@ struct CfiStackWalker<'a, C: CpuContext> {
     caller_ctx: C,
     caller_validity: HashSet<&'static str>,
 
-    module: &'a MinidumpModule,
+    /// The module containing the callee's instruction, or `None` if it's
+    /// running code that isn't in any module (e.g. JITed code).
+    module: Option<&'a MinidumpModule>,
     stack_memory: UnifiedMemory<'a, 'a>,
 }
 
@@ -573,15 +934,15 @@ where
         ctx: &'a C,
         args: &'a GetCallerFrameArgs<'a, P>,
         callee_forwarded_regs: R,
-    ) -> Option<Self>
+    ) -> Self
     where
         R: Fn(&MinidumpContextValidity) -> HashSet<&'static str>,
     {
         let module = args
             .modules
-            .module_at_address(args.callee_frame.instruction)?;
+            .module_at_address(args.callee_frame.instruction);
         let grand_callee = args.grand_callee_frame;
-        Some(Self {
+        Self {
             instruction: args.callee_frame.instruction,
             has_grand_callee: grand_callee.is_some(),
             grand_callee_parameter_size: grand_callee.and_then(|f| f.parameter_size).unwrap_or(0),
@@ -597,7 +958,39 @@ where
 
             module,
             stack_memory: args.stack_memory,
-        })
+        }
+    }
+}
+
+impl<'a, C> CfiStackWalker<'a, C>
+where
+    Self: FrameWalker + Send,
+    C: CpuContext,
+{
+    /// Evaluate the CFI of the callee, writing the results to the caller.
+    ///
+    /// This uses the symbols of the callee's module if there is one, and
+    /// otherwise the JIT unwind info of the symbol provider.
+    async fn walk<P>(&mut self, symbol_provider: &P) -> Result<(), WalkFrameError>
+    where
+        P: SymbolProvider + Sync,
+    {
+        match self.module {
+            Some(module) => symbol_provider.try_walk_frame(module, self).await,
+            None => {
+                let rules = symbol_provider
+                    .get_jit_unwind_info(self.instruction)
+                    .await
+                    .ok_or(WalkFrameError::NoCfi)?;
+                trace!("using jit unwind info for 0x{:016x}", self.instruction);
+                breakpad_symbols::walker::try_walk_with_stack_cfi(
+                    &rules,
+                    &[],
+                    self,
+                    &mut breakpad_symbols::walker::ExprLimits::default(),
+                )
+            }
+        }
     }
 }
 
@@ -651,18 +1044,35 @@ where
     }
 }
 
-#[tracing::instrument(name = "unwind_frame", level = "trace", skip_all, fields(idx = _frame_idx, fname = args.callee_frame.function_name.as_deref().unwrap_or("")))]
+#[tracing::instrument(name = "unwind_frame", level = "trace", skip_all, fields(
+    idx = _frame_idx,
+    fname = args.callee_frame.function_name.as_deref().unwrap_or(""),
+    ip = %format_args!("{:#x}", args.callee_frame.instruction),
+    module = args.callee_frame.module.as_ref().map(|module| basename(&module.code_file()).to_owned()).unwrap_or_default(),
+    trust = tracing::field::Empty,
+))]
 async fn get_caller_frame<P>(
     _frame_idx: usize,
     args: &GetCallerFrameArgs<'_, P>,
-) -> Option<StackFrame>
+) -> Result<StackFrame, WalkTerminatedReason>
+where
+    P: SymbolProvider + Sync,
+{
+    let caller = get_caller_frame_for_cpu(args).await;
+    if let Ok(caller) = &caller {
+        tracing::Span::current().record("trust", caller.trust.as_str());
+    }
+    caller
+}
+
+async fn get_caller_frame_for_cpu<P>(
+    args: &GetCallerFrameArgs<'_, P>,
+) -> Result<StackFrame, WalkTerminatedReason>
 where
     P: SymbolProvider + Sync,
 {
     match args.callee_frame.context.raw {
         /*
-        MinidumpRawContext::PPC(ctx) => ctx.get_caller_frame(stack_memory),
-        MinidumpRawContext::PPC64(ctx) => ctx.get_caller_frame(stack_memory),
         MinidumpRawContext::SPARC(ctx) => ctx.get_caller_frame(stack_memory),
          */
         MinidumpRawContext::Arm(ref ctx) => arm::get_caller_frame(ctx, args).await,
@@ -671,7 +1081,9 @@ where
         MinidumpRawContext::Amd64(ref ctx) => amd64::get_caller_frame(ctx, args).await,
         MinidumpRawContext::X86(ref ctx) => x86::get_caller_frame(ctx, args).await,
         MinidumpRawContext::Mips(ref ctx) => mips::get_caller_frame(ctx, args).await,
-        _ => None,
+        MinidumpRawContext::Ppc(ref ctx) => ppc::get_caller_frame(ctx, args).await,
+        MinidumpRawContext::Ppc64(ref ctx) => ppc64::get_caller_frame(ctx, args).await,
+        _ => Err(WalkTerminatedReason::NoCallerFound),
     }
 }
 
@@ -688,8 +1100,13 @@ async fn fill_source_line_info<P>(
         // the same lifetime as the module list that's passed in.
         frame.module = Some(module.clone());
 
-        // This is best effort, so ignore any errors.
-        let _ = symbol_provider.fill_symbol(module, frame).await;
+        // An error just means there are no symbols for the module, while Ok
+        // without a function means the symbols don't cover the instruction.
+        frame.symbol_disposition = Some(match symbol_provider.fill_symbol(module, frame).await {
+            Err(_) => SymbolDisposition::SymbolsMissing,
+            Ok(()) if frame.function_name.is_some() => SymbolDisposition::Symbolized,
+            Ok(()) => SymbolDisposition::NoCoveringSymbol,
+        });
 
         // If we got any inlines, reverse them! The symbol format makes it simplest to
         // emit inlines from the shallowest callee to the deepest one ("inner to outer"),
@@ -722,12 +1139,43 @@ impl<'a, F: FnMut(usize, &StackFrame) + Send + 'a> From<F> for OnWalkedFrame<'a>
     }
 }
 
-#[tracing::instrument(name = "unwind_thread", level = "trace", skip_all, fields(idx = _thread_idx, tid = stack.thread_id, tname = stack.thread_name.as_deref().unwrap_or("")))]
 pub async fn walk_stack<P>(
+    thread_idx: usize,
+    on_walked_frame: impl Into<OnWalkedFrame<'_>>,
+    stack: &mut CallStack,
+    stack_memory: Option<UnifiedMemory<'_, '_>>,
+    modules: &MinidumpModuleList,
+    system_info: &SystemInfo,
+    symbol_provider: &P,
+) where
+    P: SymbolProvider + Sync,
+{
+    walk_stack_with_memory(
+        thread_idx,
+        on_walked_frame,
+        stack,
+        stack_memory,
+        None,
+        modules,
+        system_info,
+        symbol_provider,
+    )
+    .await
+}
+
+/// Like [`walk_stack`], but with all the memory of the process as well.
+///
+/// The memory lets the unwinders look at the code around an instruction
+/// pointer, e.g. to tell whether a function without CFI has set up its
+/// frame yet.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(name = "unwind_thread", level = "trace", skip_all, fields(idx = _thread_idx, tid = stack.thread_id, tname = stack.thread_name.as_deref().unwrap_or("")))]
+pub async fn walk_stack_with_memory<P>(
     _thread_idx: usize,
     on_walked_frame: impl Into<OnWalkedFrame<'_>>,
     stack: &mut CallStack,
     stack_memory: Option<UnifiedMemory<'_, '_>>,
+    memory: Option<&UnifiedMemoryList<'_>>,
     modules: &MinidumpModuleList,
     system_info: &SystemInfo,
     symbol_provider: &P,
@@ -746,6 +1194,20 @@ pub async fn walk_stack<P>(
     let stack_memory =
         stack_memory.and_then(|stack_memory| stack_memory.memory_range().map(|_| stack_memory));
 
+    // The stack pointer and instruction pointer of every frame so far. Every
+    // unwinder should make progress up the stack, but if a corrupt stack manages
+    // to send one in a loop, this stops it.
+    let mut walked_frames: HashSet<(u64, u64)> = stack
+        .frames
+        .iter()
+        .map(|frame| {
+            (
+                frame.context.get_stack_pointer(),
+                frame.context.get_instruction_pointer(),
+            )
+        })
+        .collect();
+
     // Begin with the context frame, and keep getting callers until there are no more.
     let mut has_new_frame = !stack.frames.is_empty();
     let mut on_walked_frame = on_walked_frame.into();
@@ -762,6 +1224,7 @@ pub async fn walk_stack<P>(
         }
 
         let Some(stack_memory) = stack_memory else {
+            stack.walk_terminated_reason = Some(WalkTerminatedReason::MissingStackMemory);
             break;
         };
 
@@ -782,6 +1245,7 @@ pub async fn walk_stack<P>(
                 callee_frame,
                 grand_callee_frame,
                 stack_memory,
+                memory,
                 modules,
                 system_info,
                 symbol_provider,
@@ -790,10 +1254,31 @@ pub async fn walk_stack<P>(
         .await;
 
         // Check if we're done
-        if let Some(new_frame) = new_frame {
-            stack.frames.push(new_frame);
-        } else {
-            has_new_frame = false;
+        match new_frame {
+            Ok(new_frame) => {
+                if let Some(error) = &new_frame.cfi_error {
+                    trace!(
+                        "cfi failed ({}), found the caller with {}",
+                        error,
+                        new_frame.trust.description()
+                    );
+                }
+                let key = (
+                    new_frame.context.get_stack_pointer(),
+                    new_frame.context.get_instruction_pointer(),
+                );
+                if walked_frames.insert(key) {
+                    stack.frames.push(new_frame);
+                } else {
+                    trace!("caller repeats an earlier frame, assuming unwind complete");
+                    stack.walk_terminated_reason = Some(WalkTerminatedReason::RepeatedFrame);
+                    has_new_frame = false;
+                }
+            }
+            Err(reason) => {
+                stack.walk_terminated_reason = Some(reason);
+                has_new_frame = false;
+            }
         }
     }
     trace!(
@@ -895,4 +1380,8 @@ mod arm64_unittest;
 #[cfg(test)]
 mod arm_unittest;
 #[cfg(test)]
+mod mips_unittest;
+#[cfg(test)]
+mod ppc_unittest;
+#[cfg(test)]
 mod x86_unittest;
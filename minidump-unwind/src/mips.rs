@@ -1,5 +1,4 @@
 use super::impl_prelude::*;
-use minidump::format::ContextFlagsCpu;
 use minidump::{
     CpuContext, Endian, MinidumpContext, MinidumpContextValidity, MinidumpModuleList,
     MinidumpRawContext,
@@ -21,7 +20,7 @@ const CALLEE_SAVED_REGS: &[&str] = &[
 async fn get_caller_by_cfi<'a, C, P>(
     ctx: &'a C,
     args: &'a GetCallerFrameArgs<'a, P>,
-) -> Option<StackFrame>
+) -> Result<StackFrame, WalkFrameError>
 where
     P: SymbolProvider + Sync,
     // all these bounds are essentially duplicated from `CfiStackWalker` :-(
@@ -32,13 +31,13 @@ where
 {
     trace!("trying cfi");
 
-    let _last_sp = ctx.get_register(STACK_POINTER, args.valid())?;
+    let _last_sp = ctx
+        .get_register(STACK_POINTER, args.valid())
+        .ok_or_else(|| WalkFrameError::MissingRegister(STACK_POINTER.to_owned()))?;
 
-    let mut stack_walker = CfiStackWalker::from_ctx_and_args(ctx, args, callee_forwarded_regs)?;
+    let mut stack_walker = CfiStackWalker::from_ctx_and_args(ctx, args, callee_forwarded_regs);
 
-    args.symbol_provider
-        .walk_frame(stack_walker.module, &mut stack_walker)
-        .await?;
+    stack_walker.walk(args.symbol_provider).await?;
     let caller_pc = stack_walker.caller_ctx.get_register_always(PROGRAM_COUNTER);
     let caller_sp = stack_walker.caller_ctx.get_register_always(STACK_POINTER);
 
@@ -55,7 +54,7 @@ where
         raw: stack_walker.caller_ctx.into_ctx(),
         valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
     };
-    Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
+    Ok(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
 
 fn callee_forwarded_regs(valid: &MinidumpContextValidity) -> HashSet<&'static str> {
@@ -118,7 +117,10 @@ where
                 "scan seems valid -- caller_pc: 0x{caller_pc:016x}, caller_sp: 0x{caller_sp:016x}"
             );
 
-            let mut caller_ctx = MipsContext::default();
+            let mut caller_ctx = MipsContext {
+                context_flags: ctx.0.context_flags,
+                ..MipsContext::default()
+            };
             caller_ctx.set_register(PROGRAM_COUNTER, caller_pc as u64);
             caller_ctx.set_register(STACK_POINTER, caller_sp as u64);
 
@@ -172,7 +174,11 @@ where
                 "scan seems valid -- caller_pc: 0x{caller_pc:016x}, caller_sp: 0x{caller_sp:016x}"
             );
 
-            let mut caller_ctx = MipsContext::default();
+            // Preserve the context flags so the caller is still known to be MIPS64
+            let mut caller_ctx = MipsContext {
+                context_flags: ctx.context_flags,
+                ..MipsContext::default()
+            };
             caller_ctx.set_register(PROGRAM_COUNTER, caller_pc);
             caller_ctx.set_register(STACK_POINTER, caller_sp);
 
@@ -209,27 +215,29 @@ where
 pub async fn get_caller_frame<P>(
     ctx: &MipsContext,
     args: &GetCallerFrameArgs<'_, P>,
-) -> Option<StackFrame>
+) -> Result<StackFrame, WalkTerminatedReason>
 where
     P: SymbolProvider + Sync,
 {
     let ctx32 = Mips32Context::try_from(ctx.clone());
 
     // .await doesn't like closures, so don't use Option chaining
-    let mut frame = None;
-    if frame.is_none() {
-        match &ctx32 {
-            Ok(mips32) => frame = get_caller_by_cfi(mips32, args).await,
-            Err(mips64) => frame = get_caller_by_cfi(mips64, args).await,
-        }
-    }
+    let cfi_result = match &ctx32 {
+        Ok(mips32) => get_caller_by_cfi(mips32, args).await,
+        Err(mips64) => get_caller_by_cfi(mips64, args).await,
+    };
+    let (mut frame, cfi_error) = match cfi_result {
+        Ok(frame) => (Some(frame), None),
+        Err(error) => (None, Some(error)),
+    };
     if frame.is_none() {
         match &ctx32 {
             Ok(mips32) => frame = get_caller_by_scan32(mips32, args).await,
             Err(mips64) => frame = get_caller_by_scan64(mips64, args).await,
         }
     }
-    let mut frame = frame?;
+    let mut frame = frame.ok_or(WalkTerminatedReason::NoCallerFound)?;
+    frame.cfi_error = cfi_error;
 
     // We now check the frame to see if it looks like unwinding is complete,
     // based on the frame we computed having a nonsense value. Returning
@@ -239,7 +247,7 @@ where
     // null, and we can assume unwinding is complete.
     if frame.context.get_instruction_pointer() < 4096 {
         trace!("instruction pointer was nullish, assuming unwind complete");
-        return None;
+        return Err(WalkTerminatedReason::NullInstructionPointer);
     }
 
     // If the new stack pointer is at a lower address than the old,
@@ -257,7 +265,7 @@ where
         let is_leaf = args.callee_frame.trust == FrameTrust::Context && sp == last_sp;
         if !is_leaf {
             trace!("stack pointer went backwards, assuming unwind complete");
-            return None;
+            return Err(WalkTerminatedReason::StackPointerNotIncreasing);
         }
     }
 
@@ -267,7 +275,7 @@ where
     let ip = frame.context.get_instruction_pointer();
     frame.instruction = ip - 8;
 
-    Some(frame)
+    Ok(frame)
 }
 
 /// This is a hack to have a different [`CpuContext`] type/impl depending on the
@@ -317,8 +325,7 @@ impl TryFrom<MipsContext> for Mips32Context {
     type Error = MipsContext;
 
     fn try_from(ctx: MipsContext) -> Result<Self, Self::Error> {
-        if ContextFlagsCpu::from_flags(ctx.context_flags).contains(ContextFlagsCpu::CONTEXT_MIPS64)
-        {
+        if ctx.is_mips64() {
             Err(ctx)
         } else {
             Ok(Self(ctx))
@@ -0,0 +1,302 @@
+// Copyright 2015 Ted Mielczarek. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+
+use crate::*;
+use minidump::format::{ContextFlagsCpu, CONTEXT_MIPS};
+use minidump::system_info::{Cpu, Os};
+use std::collections::HashMap;
+use test_assembler::*;
+
+struct TestFixture {
+    pub raw: CONTEXT_MIPS,
+    pub modules: MinidumpModuleList,
+    pub system_info: SystemInfo,
+    pub symbols: HashMap<String, String>,
+}
+
+impl TestFixture {
+    pub fn new() -> TestFixture {
+        TestFixture {
+            raw: CONTEXT_MIPS {
+                context_flags: ContextFlagsCpu::CONTEXT_MIPS.bits(),
+                ..CONTEXT_MIPS::default()
+            },
+            // Give the two modules reasonable standard locations and names
+            // for tests to play with.
+            modules: MinidumpModuleList::from_modules(vec![
+                MinidumpModule::new(0x40000000, 0x10000, "module1"),
+                MinidumpModule::new(0x50000000, 0x10000, "module2"),
+            ]),
+            system_info: SystemInfo {
+                os: Os::Linux,
+                os_version: None,
+                os_build: None,
+                os_build_lab: None,
+                os_distro: None,
+                cpu: Cpu::Mips,
+                cpu_emulation: None,
+                cpu_info: None,
+                cpu_features: Vec::new(),
+                cpu_microcode_version: None,
+                cpu_count: 1,
+            },
+            symbols: HashMap::new(),
+        }
+    }
+
+    pub fn new_mips64() -> TestFixture {
+        let mut f = TestFixture::new();
+        f.raw.context_flags = ContextFlagsCpu::CONTEXT_MIPS64.bits();
+        f.system_info.cpu = Cpu::Mips64;
+        f
+    }
+
+    pub async fn walk_stack(&self, stack: Section) -> CallStack {
+        let context = MinidumpContext {
+            raw: MinidumpRawContext::Mips(self.raw.clone()),
+            valid: MinidumpContextValidity::All,
+        };
+        let base = stack.start().value().unwrap();
+        let size = stack.size();
+        let stack = stack.get_contents().unwrap();
+        let stack_memory = MinidumpMemory {
+            desc: Default::default(),
+            base_address: base,
+            size,
+            bytes: &stack,
+            endian: scroll::LE,
+        };
+        let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
+        let mut stack = CallStack::with_context(context);
+
+        walk_stack(
+            0,
+            (),
+            &mut stack,
+            Some(UnifiedMemory::Memory(&stack_memory)),
+            &self.modules,
+            &self.system_info,
+            &symbolizer,
+        )
+        .await;
+
+        stack
+    }
+
+    pub fn add_symbols(&mut self, name: String, symbols: String) {
+        self.symbols.insert(name, symbols);
+    }
+}
+
+#[tokio::test]
+async fn test_simple() {
+    let mut f = TestFixture::new();
+    let stack = Section::new();
+    stack.start().set_const(0x80000000);
+    // There should be no references to the stack in this walk: we don't
+    // provide any call frame information, so trying to reconstruct the
+    // context frame's caller should fail. So there's no need for us to
+    // provide stack contents.
+    f.raw.set_register("pc", 0x4000c020);
+    f.raw.set_register("sp", 0x80000000);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 1);
+    let f = &s.frames[0];
+    let m = f.module.as_ref().unwrap();
+    assert_eq!(m.code_file(), "module1");
+}
+
+#[tokio::test]
+async fn test_scan_without_symbols() {
+    // Scanning should work without any symbols
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x50000100u32;
+    let return_address2 = 0x50000900u32;
+    let frame1_sp = Label::new();
+    let frame2_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 16) // space
+        .D32(0x40090000) // junk that's not
+        .D32(0x60000000) // a return address
+        .D32(return_address1) // actual return address
+        // frame 1
+        .mark(&frame1_sp)
+        // the argument save area, which is skipped for non-context frames
+        .D32(0x40005510)
+        .append_repeated(0, 12)
+        .D32(0xF0000000) // more junk
+        .D32(0x0000000D)
+        .D32(return_address2) // actual return address
+        // frame 2
+        .mark(&frame2_sp)
+        .append_repeated(0, 32); // end of stack
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 3);
+
+    {
+        // Frame 0
+        let frame = &s.frames[0];
+        assert_eq!(frame.trust, FrameTrust::Context);
+        assert_eq!(frame.context.valid, MinidumpContextValidity::All);
+    }
+
+    for (frame, return_address, frame_sp) in [
+        (&s.frames[1], return_address1, &frame1_sp),
+        (&s.frames[2], return_address2, &frame2_sp),
+    ] {
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::Scan);
+        if let MinidumpContextValidity::Some(ref which) = valid {
+            assert_eq!(which.len(), 2);
+        } else {
+            unreachable!();
+        }
+
+        if let MinidumpRawContext::Mips(ctx) = &frame.context.raw {
+            assert!(!ctx.is_mips64());
+            assert_eq!(
+                ctx.get_register("pc", valid).unwrap(),
+                return_address as u64
+            );
+            assert_eq!(
+                ctx.get_register("sp", valid).unwrap(),
+                frame_sp.value().unwrap()
+            );
+        } else {
+            unreachable!();
+        }
+        // `jal` sets the return address two instructions past the call
+        assert_eq!(frame.instruction, return_address as u64 - 8);
+    }
+}
+
+#[tokio::test]
+async fn test_scan_mips64() {
+    // 64-bit stacks are scanned a pointer at a time, without skipping any
+    // argument save area, and the scanned frames must stay 64-bit.
+    let mut f = TestFixture::new_mips64();
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x50000100u64;
+    let return_address2 = 0x50000900u64;
+    let frame1_sp = Label::new();
+    let frame2_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .D64(0x40090000) // junk that's not
+        .D64(0x60000000) // a return address
+        .D64(return_address1) // actual return address
+        // frame 1
+        .mark(&frame1_sp)
+        .D64(0xF0000000) // more junk
+        .D64(return_address2) // actual return address
+        // frame 2
+        .mark(&frame2_sp)
+        .append_repeated(0, 32); // end of stack
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 3);
+
+    for (frame, return_address, frame_sp) in [
+        (&s.frames[1], return_address1, &frame1_sp),
+        (&s.frames[2], return_address2, &frame2_sp),
+    ] {
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::Scan);
+        if let MinidumpRawContext::Mips(ctx) = &frame.context.raw {
+            assert!(ctx.is_mips64());
+            assert_eq!(ctx.get_register("pc", valid).unwrap(), return_address);
+            assert_eq!(
+                ctx.get_register("sp", valid).unwrap(),
+                frame_sp.value().unwrap()
+            );
+        } else {
+            unreachable!();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_cfi() {
+    let mut f = TestFixture::new();
+    let symbols = [
+        // The youngest frame's function.
+        "FUNC 4000 1000 10 enchiridion\n",
+        // Allocate a 16 byte frame, saving the return address and s0.
+        "STACK CFI INIT 4000 100 .cfa: $sp 16 + .ra: .cfa -4 + ^",
+        " $s0: .cfa -8 + ^\n",
+        // The calling function.
+        "FUNC 5000 1000 10 epictetus\n",
+        // Mark it as end of stack.
+        "STACK CFI INIT 5000 1000 .cfa: 0 .ra: 0\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    let return_address = 0x40005510u32;
+    let frame1_sp = Label::new();
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+    stack = stack
+        // frame 0
+        .append_repeated(0, 8) // space
+        .D32(0xdeadbeef) // saved s0
+        .D32(return_address) // saved ra
+        // frame 1
+        .mark(&frame1_sp)
+        .append_repeated(0, 32); // end of stack
+
+    f.raw.set_register("pc", 0x40004010);
+    f.raw.set_register("sp", stack.start().value().unwrap());
+    f.raw.set_register("s0", 0x12345678);
+    f.raw.set_register("s1", 0x87654321);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    {
+        // Frame 0
+        let frame = &s.frames[0];
+        assert_eq!(frame.trust, FrameTrust::Context);
+        assert_eq!(frame.function_name.as_deref(), Some("enchiridion"));
+    }
+
+    {
+        // Frame 1
+        let frame = &s.frames[1];
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
+        assert_eq!(frame.function_name.as_deref(), Some("epictetus"));
+        if let MinidumpRawContext::Mips(ctx) = &frame.context.raw {
+            assert_eq!(
+                ctx.get_register("pc", valid).unwrap(),
+                return_address as u64
+            );
+            assert_eq!(
+                ctx.get_register("sp", valid).unwrap(),
+                frame1_sp.value().unwrap()
+            );
+            // s0 was restored from the stack, s1 was never touched
+            assert_eq!(ctx.get_register("s0", valid).unwrap(), 0xdeadbeef);
+            assert_eq!(ctx.get_register("s1", valid).unwrap(), 0x87654321);
+            // caller-saved registers can't be recovered
+            assert_eq!(ctx.get_register("a0", valid), None);
+        } else {
+            unreachable!();
+        }
+    }
+}
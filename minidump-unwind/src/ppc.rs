@@ -0,0 +1,197 @@
+// NOTE: ppc.rs and ppc64.rs should be identical except for the names of
+// their context types and the layout of the stack frame header.
+
+use super::impl_prelude::*;
+use minidump::system_info::Os;
+use minidump::{CpuContext, MinidumpContext, MinidumpContextValidity, MinidumpRawContext};
+use std::collections::HashSet;
+use tracing::trace;
+
+type PpcContext = minidump::format::CONTEXT_PPC;
+type Pointer = <PpcContext as CpuContext>::Register;
+
+const STACK_POINTER: &str = "r1";
+const PROGRAM_COUNTER: &str = "srr0";
+const CALLEE_SAVED_REGS: &[&str] = &[
+    "r1", "r2", "r13", "r14", "r15", "r16", "r17", "r18", "r19", "r20", "r21", "r22", "r23", "r24",
+    "r25", "r26", "r27", "r28", "r29", "r30", "r31",
+];
+
+async fn get_caller_by_cfi<P>(
+    ctx: &PpcContext,
+    args: &GetCallerFrameArgs<'_, P>,
+) -> Result<StackFrame, WalkFrameError>
+where
+    P: SymbolProvider + Sync,
+{
+    trace!("trying cfi");
+
+    let _last_sp = ctx
+        .get_register(STACK_POINTER, args.valid())
+        .ok_or_else(|| WalkFrameError::MissingRegister(STACK_POINTER.to_owned()))?;
+
+    let mut stack_walker = CfiStackWalker::from_ctx_and_args(ctx, args, callee_forwarded_regs);
+
+    stack_walker.walk(args.symbol_provider).await?;
+    let caller_pc = stack_walker.caller_ctx.get_register_always(PROGRAM_COUNTER);
+    let caller_sp = stack_walker.caller_ctx.get_register_always(STACK_POINTER);
+
+    trace!(
+        "cfi evaluation was successful -- caller_pc: 0x{caller_pc:016x}, caller_sp: 0x{caller_sp:016x}"
+    );
+
+    // Do absolutely NO validation! Yep! As long as CFI evaluation succeeds
+    // (which does include pc and sp resolving), just blindly assume the
+    // values are correct. I Don't Like This, but it's what breakpad does and
+    // we should start with a baseline of parity.
+
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::Ppc(stack_walker.caller_ctx),
+        valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
+    };
+    Ok(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
+}
+
+fn callee_forwarded_regs(valid: &MinidumpContextValidity) -> HashSet<&'static str> {
+    match valid {
+        MinidumpContextValidity::All => CALLEE_SAVED_REGS.iter().copied().collect(),
+        MinidumpContextValidity::Some(ref which) => CALLEE_SAVED_REGS
+            .iter()
+            .filter(|&reg| which.contains(reg))
+            .copied()
+            .collect(),
+    }
+}
+
+/// The offset of the saved link register in a stack frame's header.
+fn link_register_save_offset(os: Os) -> Pointer {
+    // The System V ABI (Linux, the BSDs) saves it right after the back chain,
+    // the AIX ABI (which macOS follows) leaves a word for the condition
+    // register in between.
+    match os {
+        Os::MacOs => 8,
+        _ => 4,
+    }
+}
+
+fn get_caller_by_back_chain<P>(
+    ctx: &PpcContext,
+    args: &GetCallerFrameArgs<'_, P>,
+) -> Option<StackFrame>
+where
+    P: SymbolProvider + Sync,
+{
+    trace!("trying back chain");
+    // Every PPC stack frame starts with a header:
+    //
+    // sp + 0: the caller's sp (the "back chain")
+    // sp + N: the link register save area
+    //
+    // A function's prologue saves its return address (which it got in lr)
+    // in its *caller's* link register save area, and then creates its own
+    // frame pointing back to the caller's. So to restore the caller's
+    // registers, we have:
+    //
+    // sp := *sp
+    // pc := *(sp + N)
+    //
+    // (This skips the caller of a leaf function that hasn't saved lr, which
+    // is what breakpad does too.)
+    let last_sp = ctx.get_register(STACK_POINTER, args.valid())?;
+    let caller_sp: Pointer = args.stack_memory.get_memory_at_address(last_sp as u64)?;
+
+    // The caller's frame must be above ours and still in the stack, or we'd be
+    // following a corrupt (possibly cyclic) chain of frames.
+    if !frame_pointer_stack_seems_valid(caller_sp as u64, last_sp as u64, args.stack_memory) {
+        trace!("rejecting back chain result for unreasonable stack pointer");
+        return None;
+    }
+    let address_of_pc = caller_sp.checked_add(link_register_save_offset(args.system_info.os))?;
+    let caller_pc: Pointer = args
+        .stack_memory
+        .get_memory_at_address(address_of_pc as u64)?;
+
+    trace!(
+        "back chain seems valid -- caller_pc: 0x{:016x}, caller_sp: 0x{:016x}",
+        caller_pc,
+        caller_sp,
+    );
+
+    let mut caller_ctx = PpcContext {
+        context_flags: ctx.context_flags,
+        ..PpcContext::default()
+    };
+    caller_ctx.set_register(PROGRAM_COUNTER, caller_pc);
+    caller_ctx.set_register(STACK_POINTER, caller_sp);
+
+    let mut valid = HashSet::new();
+    valid.insert(PROGRAM_COUNTER);
+    valid.insert(STACK_POINTER);
+
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::Ppc(caller_ctx),
+        valid: MinidumpContextValidity::Some(valid),
+    };
+    Some(StackFrame::from_context(context, FrameTrust::FramePointer))
+}
+
+pub async fn get_caller_frame<P>(
+    ctx: &PpcContext,
+    args: &GetCallerFrameArgs<'_, P>,
+) -> Result<StackFrame, WalkTerminatedReason>
+where
+    P: SymbolProvider + Sync,
+{
+    // .await doesn't like closures, so don't use Option chaining
+    let (mut frame, cfi_error) = match get_caller_by_cfi(ctx, args).await {
+        Ok(frame) => (Some(frame), None),
+        Err(error) => (None, Some(error)),
+    };
+    if frame.is_none() {
+        frame = get_caller_by_back_chain(ctx, args);
+    }
+    let mut frame = frame.ok_or(WalkTerminatedReason::NoCallerFound)?;
+    frame.cfi_error = cfi_error;
+
+    // We now check the frame to see if it looks like unwinding is complete,
+    // based on the frame we computed having a nonsense value. Returning
+    // None signals to the unwinder to stop unwinding.
+
+    // if the instruction is within the first ~page of memory, it's basically
+    // null, and we can assume unwinding is complete. (macOS gives 1 as the
+    // return address of a thread's entry point.)
+    if frame.context.get_instruction_pointer() < 4096 {
+        trace!("instruction pointer was nullish, assuming unwind complete");
+        return Err(WalkTerminatedReason::NullInstructionPointer);
+    }
+
+    // If the new stack pointer is at a lower address than the old,
+    // then that's clearly incorrect. Treat this as end-of-stack to
+    // enforce progress and avoid infinite loops.
+    let sp = frame.context.get_stack_pointer();
+    let last_sp = ctx.get_register_always(STACK_POINTER) as u64;
+    if sp <= last_sp {
+        // PPC leaf functions may not actually touch the stack (thanks
+        // to the link register allowing you to "push" the return address
+        // to a register), so we need to permit the stack pointer to not
+        // change for the first frame of the unwind. After that we need
+        // more strict validation to avoid infinite loops.
+        let is_leaf = args.callee_frame.trust == FrameTrust::Context && sp == last_sp;
+        if !is_leaf {
+            trace!("stack pointer went backwards, assuming unwind complete");
+            return Err(WalkTerminatedReason::StackPointerNotIncreasing);
+        }
+    }
+
+    // Ok, the frame now seems well and truly valid, do final cleanup.
+
+    // A caller's ip is the return address, which is the instruction
+    // *after* the `bl` that caused us to arrive at the callee. Set
+    // the value to 4 less than that, so it points to the `bl` instruction
+    // (PPC instructions are all 4 bytes wide). This is important because
+    // we use this value to lookup the CFI we need to unwind the next frame.
+    let ip = frame.context.get_instruction_pointer();
+    frame.instruction = ip - 4;
+
+    Ok(frame)
+}
@@ -0,0 +1,318 @@
+// NOTE: ppc64 is only tested where it differs from ppc, the code should be
+// otherwise identical at all times!
+
+use crate::*;
+use minidump::format::{CONTEXT_PPC, CONTEXT_PPC64};
+use minidump::system_info::{Cpu, Os};
+use std::collections::HashMap;
+use test_assembler::*;
+
+struct TestFixture {
+    pub raw: CONTEXT_PPC,
+    pub raw64: CONTEXT_PPC64,
+    pub modules: MinidumpModuleList,
+    pub system_info: SystemInfo,
+    pub symbols: HashMap<String, String>,
+}
+
+impl TestFixture {
+    pub fn new() -> TestFixture {
+        TestFixture {
+            raw: CONTEXT_PPC::default(),
+            raw64: CONTEXT_PPC64::default(),
+            // Give the two modules reasonable standard locations and names
+            // for tests to play with.
+            modules: MinidumpModuleList::from_modules(vec![
+                MinidumpModule::new(0x40000000, 0x10000, "module1"),
+                MinidumpModule::new(0x50000000, 0x10000, "module2"),
+            ]),
+            system_info: SystemInfo {
+                os: Os::Linux,
+                os_version: None,
+                os_build: None,
+                os_build_lab: None,
+                os_distro: None,
+                cpu: Cpu::Ppc,
+                cpu_emulation: None,
+                cpu_info: None,
+                cpu_features: Vec::new(),
+                cpu_microcode_version: None,
+                cpu_count: 1,
+            },
+            symbols: HashMap::new(),
+        }
+    }
+
+    pub fn new_ppc64() -> TestFixture {
+        let mut f = TestFixture::new();
+        f.system_info.cpu = Cpu::Ppc64;
+        f
+    }
+
+    pub async fn walk_stack(&self, stack: Section) -> CallStack {
+        let raw = match self.system_info.cpu {
+            Cpu::Ppc64 => MinidumpRawContext::Ppc64(self.raw64.clone()),
+            _ => MinidumpRawContext::Ppc(self.raw.clone()),
+        };
+        let context = MinidumpContext {
+            raw,
+            valid: MinidumpContextValidity::All,
+        };
+        let base = stack.start().value().unwrap();
+        let size = stack.size();
+        let stack = stack.get_contents().unwrap();
+        let stack_memory = MinidumpMemory {
+            desc: Default::default(),
+            base_address: base,
+            size,
+            bytes: &stack,
+            endian: scroll::BE,
+        };
+        let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
+        let mut stack = CallStack::with_context(context);
+
+        walk_stack(
+            0,
+            (),
+            &mut stack,
+            Some(UnifiedMemory::Memory(&stack_memory)),
+            &self.modules,
+            &self.system_info,
+            &symbolizer,
+        )
+        .await;
+
+        stack
+    }
+
+    pub fn add_symbols(&mut self, name: String, symbols: String) {
+        self.symbols.insert(name, symbols);
+    }
+}
+
+fn big_endian_stack(start: u64) -> Section {
+    let stack = Section::with_endian(test_assembler::Endian::Big);
+    stack.start().set_const(start);
+    stack
+}
+
+#[tokio::test]
+async fn test_simple() {
+    let mut f = TestFixture::new();
+    let stack = big_endian_stack(0x80000000);
+    // There should be no references to the stack in this walk: we don't
+    // provide any call frame information, so trying to reconstruct the
+    // context frame's caller should fail. So there's no need for us to
+    // provide stack contents.
+    f.raw.set_register("srr0", 0x4000c020);
+    f.raw.set_register("r1", 0x80000000);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 1);
+    let f = &s.frames[0];
+    let m = f.module.as_ref().unwrap();
+    assert_eq!(m.code_file(), "module1");
+}
+
+#[tokio::test]
+async fn test_back_chain() {
+    for os in [Os::Linux, Os::MacOs] {
+        let mut f = TestFixture::new();
+        f.system_info.os = os;
+        // The link register save area is right after the back chain on
+        // Linux, and after the condition register save area on macOS.
+        let header = |stack: Section, back_chain: &Label, lr: u32| match os {
+            Os::MacOs => stack.D32(back_chain).D32(0).D32(lr),
+            _ => stack.D32(back_chain).D32(lr),
+        };
+
+        let return_address1 = 0x50000100u32;
+        let return_address2 = 0x50000900u32;
+        let frame1_sp = Label::new();
+        let frame2_sp = Label::new();
+        let end = Label::new();
+        end.set_const(0);
+
+        let mut stack = big_endian_stack(0x80000000);
+        // frame 0
+        stack = header(stack, &frame1_sp, 0xdeadbeef);
+        stack = stack.append_repeated(0, 16); // locals
+                                              // frame 1
+        stack = stack.mark(&frame1_sp);
+        stack = header(stack, &frame2_sp, return_address1);
+        stack = stack.append_repeated(0, 32); // locals
+                                              // frame 2
+        stack = stack.mark(&frame2_sp);
+        stack = header(stack, &end, return_address2);
+        stack = stack.append_repeated(0, 16); // end of stack
+
+        f.raw.set_register("srr0", 0x40005510);
+        f.raw
+            .set_register("r1", stack.start().value().unwrap() as u32);
+        f.raw.set_register("lr", 0x40005000);
+
+        let s = f.walk_stack(stack).await;
+        assert_eq!(s.frames.len(), 3);
+
+        {
+            // Frame 0
+            let frame = &s.frames[0];
+            assert_eq!(frame.trust, FrameTrust::Context);
+            assert_eq!(frame.context.valid, MinidumpContextValidity::All);
+        }
+
+        for (frame, return_address, frame_sp) in [
+            (&s.frames[1], return_address1, &frame1_sp),
+            (&s.frames[2], return_address2, &frame2_sp),
+        ] {
+            let valid = &frame.context.valid;
+            assert_eq!(frame.trust, FrameTrust::FramePointer);
+            if let MinidumpContextValidity::Some(ref which) = valid {
+                assert_eq!(which.len(), 2);
+            } else {
+                unreachable!();
+            }
+
+            if let MinidumpRawContext::Ppc(ctx) = &frame.context.raw {
+                assert_eq!(ctx.get_register("srr0", valid).unwrap(), return_address);
+                assert_eq!(
+                    ctx.get_register("r1", valid).unwrap() as u64,
+                    frame_sp.value().unwrap()
+                );
+                assert_eq!(ctx.get_register("lr", valid), None);
+            } else {
+                unreachable!();
+            }
+            // `bl` sets the return address to the instruction after it
+            assert_eq!(frame.instruction, return_address as u64 - 4);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_back_chain_cycle() {
+    // A back chain that doesn't go up the stack ends the walk.
+    let mut f = TestFixture::new();
+    let frame0_sp = Label::new();
+    let stack = big_endian_stack(0x80000000)
+        .mark(&frame0_sp)
+        .D32(&frame0_sp) // back chain to itself
+        .D32(0x50000100) // saved lr
+        .append_repeated(0, 16);
+
+    f.raw.set_register("srr0", 0x40005510);
+    f.raw
+        .set_register("r1", stack.start().value().unwrap() as u32);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 1);
+}
+
+#[tokio::test]
+async fn test_back_chain_ppc64() {
+    let mut f = TestFixture::new_ppc64();
+    let return_address = 0x50000100u64;
+    let frame1_sp = Label::new();
+    let end = Label::new();
+    end.set_const(0);
+
+    let stack = big_endian_stack(0x80000000)
+        // frame 0
+        .D64(&frame1_sp) // back chain
+        .D64(0) // cr save
+        .D64(0xdeadbeef) // lr save
+        .append_repeated(0, 32) // locals
+        // frame 1
+        .mark(&frame1_sp)
+        .D64(&end) // back chain
+        .D64(0) // cr save
+        .D64(return_address) // lr save
+        .append_repeated(0, 32); // end of stack
+
+    f.raw64.set_register("srr0", 0x40005510);
+    f.raw64.set_register("r1", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    let frame = &s.frames[1];
+    let valid = &frame.context.valid;
+    assert_eq!(frame.trust, FrameTrust::FramePointer);
+    if let MinidumpRawContext::Ppc64(ctx) = &frame.context.raw {
+        assert_eq!(ctx.get_register("srr0", valid).unwrap(), return_address);
+        assert_eq!(
+            ctx.get_register("r1", valid).unwrap(),
+            frame1_sp.value().unwrap()
+        );
+    } else {
+        unreachable!();
+    }
+    assert_eq!(frame.instruction, return_address - 4);
+}
+
+#[tokio::test]
+async fn test_cfi() {
+    let mut f = TestFixture::new();
+    let symbols = [
+        // The youngest frame's function.
+        "FUNC 4000 1000 10 enchiridion\n",
+        // Allocate a 16 byte frame, saving r31 but keeping the return
+        // address in lr, like a leaf function would.
+        "STACK CFI INIT 4000 100 .cfa: r1 16 + .ra: lr",
+        " r31: .cfa -4 + ^\n",
+        // The calling function.
+        "FUNC 5000 1000 10 epictetus\n",
+        // Mark it as end of stack.
+        "STACK CFI INIT 5000 1000 .cfa: 0 .ra: 0\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    let return_address = 0x40005510u32;
+    let frame1_sp = Label::new();
+    let stack = big_endian_stack(0x80000000)
+        // frame 0
+        .append_repeated(0, 12) // space
+        .D32(0xdeadbeef) // saved r31
+        // frame 1
+        .mark(&frame1_sp)
+        .append_repeated(0, 32); // end of stack
+
+    f.raw.set_register("srr0", 0x40004010);
+    f.raw
+        .set_register("r1", stack.start().value().unwrap() as u32);
+    f.raw.set_register("lr", return_address);
+    f.raw.set_register("r30", 0x87654321);
+    f.raw.set_register("r31", 0x12345678);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    {
+        // Frame 0
+        let frame = &s.frames[0];
+        assert_eq!(frame.trust, FrameTrust::Context);
+        assert_eq!(frame.function_name.as_deref(), Some("enchiridion"));
+    }
+
+    {
+        // Frame 1
+        let frame = &s.frames[1];
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
+        assert_eq!(frame.function_name.as_deref(), Some("epictetus"));
+        if let MinidumpRawContext::Ppc(ctx) = &frame.context.raw {
+            assert_eq!(ctx.get_register("srr0", valid).unwrap(), return_address);
+            assert_eq!(
+                ctx.get_register("r1", valid).unwrap() as u64,
+                frame1_sp.value().unwrap()
+            );
+            // r31 was restored from the stack, r30 was never touched
+            assert_eq!(ctx.get_register("r31", valid).unwrap(), 0xdeadbeef);
+            assert_eq!(ctx.get_register("r30", valid).unwrap(), 0x87654321);
+            // volatile registers can't be recovered
+            assert_eq!(ctx.get_register("r3", valid), None);
+        } else {
+            unreachable!();
+        }
+    }
+}
@@ -1,10 +1,13 @@
 //! This module provides a `SymbolProvider` which uses local binary debuginfo.
 
-use super::{async_trait, FileError, FileKind, FillSymbolError, FrameSymbolizer, FrameWalker};
+use super::{
+    async_trait, FileError, FileKind, FillSymbolError, FrameSymbolizer, FrameWalker, SymbolKind,
+};
 use cachemap2::CacheMap;
 use framehop::Unwinder;
 use memmap2::Mmap;
 use minidump::{MinidumpModuleList, MinidumpSystemInfo, Module};
+use minidump_common::utils::basename;
 use std::cell::UnsafeCell;
 use std::fs::File;
 use std::path::{Path, PathBuf};
@@ -24,6 +27,7 @@ pub struct DebugInfoSymbolProvider {
 pub struct DebugInfoSymbolProviderBuilder {
     #[cfg(feature = "debuginfo-symbols")]
     enable_symbols: bool,
+    search_paths: Vec<PathBuf>,
 }
 
 type ModuleData = std::borrow::Cow<'static, [u8]>;
@@ -145,7 +149,7 @@ trait SymbolInterface {
     ) -> Result<(), FillSymbolError>;
 }
 
-/// A SymbolInterface that always returns `Ok(())` without doing anything.
+/// A SymbolInterface that never has any symbols.
 struct NoSymbols;
 
 #[async_trait]
@@ -155,7 +159,7 @@ impl SymbolInterface for NoSymbols {
         _module: &(dyn Module + Sync),
         _frame: &mut (dyn FrameSymbolizer + Send),
     ) -> Result<(), FillSymbolError> {
-        Ok(())
+        Err(FillSymbolError {})
     }
 }
 
@@ -172,15 +176,15 @@ mod wholesym_symbol_interface {
     }
 
     impl Impl {
-        pub async fn new(modules: &MinidumpModuleList) -> Self {
+        pub async fn new(modules: &MinidumpModuleList, search_paths: &[PathBuf]) -> Self {
             let mut symbols = HashMap::new();
-            let symbol_manager = SymbolManager::with_config(SymbolManagerConfig::new());
+            let mut config = SymbolManagerConfig::new();
+            for dir in search_paths {
+                config = config.extra_symbols_directory(dir);
+            }
+            let symbol_manager = SymbolManager::with_config(config);
             for module in modules.iter() {
-                let path = effective_debug_file(module, false);
-                if let Ok(sm) = symbol_manager
-                    .load_symbol_map_for_binary_at_path(&path, None)
-                    .await
-                {
+                if let Some(sm) = load_symbol_map(&symbol_manager, module, search_paths).await {
                     symbols.insert(module.into(), Mutex::new(sm));
                 }
             }
@@ -188,6 +192,45 @@ mod wholesym_symbol_interface {
         }
     }
 
+    /// Load the symbols of the first debug file for `module` whose debug id matches.
+    async fn load_symbol_map(
+        symbol_manager: &SymbolManager,
+        module: &minidump::MinidumpModule,
+        search_paths: &[PathBuf],
+    ) -> Option<SymbolMap> {
+        let expected_id = module.debug_identifier();
+        for path in debug_file_candidates(module, false, search_paths) {
+            let Ok(sm) = symbol_manager
+                .load_symbol_map_for_binary_at_path(&path, None)
+                .await
+            else {
+                continue;
+            };
+            match expected_id {
+                Some(id) if sm.debug_id() != id => {
+                    tracing::warn!(
+                        "ignoring {}: its debug id {} doesn't match the module's {}",
+                        path.display(),
+                        sm.debug_id().breakpad(),
+                        id.breakpad()
+                    );
+                }
+                _ => return Some(sm),
+            }
+        }
+
+        // Debug files which aren't binaries (e.g. PDBs) are looked up in the search paths by
+        // name, and wholesym rejects them if their debug id doesn't match.
+        if search_paths.is_empty() {
+            return None;
+        }
+        let debug_file = module.debug_file()?;
+        symbol_manager
+            .load_symbol_map(basename(&debug_file), expected_id?)
+            .await
+            .ok()
+    }
+
     #[async_trait]
     impl SymbolInterface for Impl {
         async fn fill_symbol(
@@ -219,6 +262,17 @@ mod wholesym_symbol_interface {
                     module.base_address() + address_info.symbol.address as u64,
                     0,
                 );
+                // The symbol table doesn't always say how big a symbol is, which
+                // makes it as good as a PUBLIC record.
+                let kind = if address_info.symbol.size.is_some() {
+                    SymbolKind::Func
+                } else {
+                    SymbolKind::Public
+                };
+                frame.set_symbol_quality(
+                    kind,
+                    u64::from(addr.saturating_sub(address_info.symbol.address)),
+                );
 
                 if let Some(frames) = address_info.frames {
                     let mut iter = frames.into_iter().rev();
@@ -348,40 +402,95 @@ mod object_section_info {
     }
 }
 
-/// Get the file path with debug information for the given module.
+/// Get the file paths which may have debug information for the given module, best first.
+///
+/// If `unwind_info` is true, returns the paths that should contain unwind information.
 ///
-/// If `unwind_info` is true, returns the path that should contain unwind information.
-fn effective_debug_file(module: &dyn Module, unwind_info: bool) -> PathBuf {
+/// The files the module refers to come first, followed by the files in `search_paths` named
+/// like the module's debug file or code file. Only paths that exist are returned, but they
+/// may still belong to a different build of the module.
+fn debug_file_candidates(
+    module: &dyn Module,
+    unwind_info: bool,
+    search_paths: &[PathBuf],
+) -> Vec<PathBuf> {
     // Windows x86_64 always stores the unwind info _only_ in the binary.
     let ignore_debug_file = unwind_info && cfg!(all(windows, target_arch = "x86_64"));
 
     let code_file = module.code_file();
     let code_file_path: &Path = code_file.as_ref().as_ref();
-
-    if !ignore_debug_file {
-        if let Some(file) = module.debug_file() {
-            let file_path: &Path = file.as_ref().as_ref();
-            // Anchor relative paths in the code file parent.
-            if file_path.is_relative() {
-                if let Some(parent) = code_file_path.parent() {
-                    let path = parent.join(file_path);
-                    if path.exists() {
-                        return path;
-                    }
-                }
-            }
-            if file_path.exists() {
-                return file_path.to_owned();
+    let debug_file = module.debug_file().filter(|_| !ignore_debug_file);
+
+    let mut candidates = Vec::new();
+    if let Some(file) = &debug_file {
+        let file_path: &Path = file.as_ref().as_ref();
+        // Anchor relative paths in the code file parent.
+        if file_path.is_relative() {
+            if let Some(parent) = code_file_path.parent() {
+                candidates.push(parent.join(file_path));
             }
         }
-        // else fall back to code file below
+        candidates.push(file_path.to_owned());
+    }
+    candidates.push(code_file_path.to_owned());
+
+    for dir in search_paths {
+        if let Some(file) = &debug_file {
+            candidates.push(dir.join(basename(file)));
+        }
+        candidates.push(dir.join(basename(&code_file)));
+    }
+
+    let mut existing: Vec<PathBuf> = Vec::new();
+    for path in candidates {
+        if path.is_file() && !existing.contains(&path) {
+            existing.push(path);
+        }
     }
+    existing
+}
+
+/// Whether the identifiers in `objfile` match the module's, or can't be compared.
+fn object_matches_module<'data>(
+    objfile: &impl object::read::Object<'data>,
+    module: &dyn Module,
+) -> bool {
+    if let (Ok(Some(build_id)), Some(code_id)) = (objfile.build_id(), module.code_identifier()) {
+        let build_id: String = build_id.iter().map(|b| format!("{b:02x}")).collect();
+        return build_id.eq_ignore_ascii_case(code_id.as_str());
+    }
+    if let (Ok(Some(uuid)), Some(debug_id)) = (objfile.mach_uuid(), module.debug_identifier()) {
+        return debug_id.uuid().as_bytes() == &uuid;
+    }
+    if let (Ok(Some(pdb_info)), Some(debug_id)) = (objfile.pdb_info(), module.debug_identifier()) {
+        // The GUID's first three fields are stored little-endian.
+        let g = pdb_info.guid();
+        let uuid = [
+            g[3], g[2], g[1], g[0], g[5], g[4], g[7], g[6], g[8], g[9], g[10], g[11], g[12], g[13],
+            g[14], g[15],
+        ];
+        return debug_id.uuid().as_bytes() == &uuid && debug_id.appendix() == pdb_info.age();
+    }
+    true
+}
 
-    code_file_path.to_owned()
+fn load_unwind_module(
+    module: &dyn Module,
+    search_paths: &[PathBuf],
+) -> Option<(Mmap, framehop::Module<ModuleData>)> {
+    let candidates = debug_file_candidates(module, true, search_paths);
+    if candidates.is_empty() {
+        tracing::warn!("no debug info found for {}", module.code_file());
+    }
+    candidates
+        .into_iter()
+        .find_map(|path| load_unwind_file(module, path))
 }
 
-fn load_unwind_module(module: &dyn Module) -> Option<(Mmap, framehop::Module<ModuleData>)> {
-    let path = effective_debug_file(module, true);
+fn load_unwind_file(
+    module: &dyn Module,
+    path: PathBuf,
+) -> Option<(Mmap, framehop::Module<ModuleData>)> {
     let file = match File::open(&path) {
         Ok(file) => file,
         Err(e) => {
@@ -411,9 +520,16 @@ fn load_unwind_module(module: &dyn Module) -> Option<(Mmap, framehop::Module<Mod
             return None;
         }
     };
+    if !object_matches_module(&objfile, module) {
+        tracing::warn!(
+            "ignoring {}: it doesn't match the module's identifiers",
+            path.display()
+        );
+        return None;
+    }
 
     let base = module.base_address();
-    let end = base + module.size();
+    let end = base.saturating_add(module.size());
     let fhmodule = framehop::Module::new(
         path.display().to_string(),
         base..end,
@@ -429,6 +545,7 @@ impl Default for DebugInfoSymbolProviderBuilder {
         DebugInfoSymbolProviderBuilder {
             #[cfg(feature = "debuginfo-symbols")]
             enable_symbols: true,
+            search_paths: Vec::new(),
         }
     }
 }
@@ -452,6 +569,17 @@ impl DebugInfoSymbolProviderBuilder {
         self
     }
 
+    /// Directories to look for debug files in.
+    ///
+    /// A module's debug file (e.g. a PDB, or an ELF file with DWARF) or code file is looked up
+    /// in each directory by its file name, after the paths the minidump refers to. Files whose
+    /// debug id or build id doesn't match the module are ignored, so files from other builds
+    /// can't produce wrong stacks.
+    pub fn search_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.search_paths = paths;
+        self
+    }
+
     /// Create the DebugInfoSymbolProvider.
     pub async fn build(
         self,
@@ -471,13 +599,13 @@ impl DebugInfoSymbolProviderBuilder {
 
         #[cfg(feature = "debuginfo-symbols")]
         let symbols: Box<dyn SymbolInterface + Send + Sync> = if self.enable_symbols {
-            Box::new(wholesym_symbol_interface::Impl::new(modules).await)
+            Box::new(wholesym_symbol_interface::Impl::new(modules, &self.search_paths).await)
         } else {
             Box::new(NoSymbols)
         };
 
         for module in modules.iter() {
-            if let Some((mapped, fhmodule)) = load_unwind_module(module) {
+            if let Some((mapped, fhmodule)) = load_unwind_module(module, &self.search_paths) {
                 mapped_modules.push(mapped);
                 unwinder.add_module(fhmodule);
             }
@@ -536,3 +664,108 @@ impl super::SymbolProvider for DebugInfoSymbolProvider {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use breakpad_symbols::SimpleModule;
+    use object::read::Object;
+
+    const BUILD_ID: [u8; 8] = [0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0];
+
+    /// A little-endian ELF64 file with nothing but a GNU build id note.
+    fn elf_with_build_id(build_id: &[u8]) -> Vec<u8> {
+        let mut note = Vec::new();
+        note.extend((4u32).to_le_bytes()); // namesz
+        note.extend((build_id.len() as u32).to_le_bytes()); // descsz
+        note.extend((3u32).to_le_bytes()); // NT_GNU_BUILD_ID
+        note.extend(b"GNU\0");
+        note.extend(build_id);
+        let shstrtab = b"\0.note.gnu.build-id\0.shstrtab\0";
+        let note_offset = 64;
+        let shstrtab_offset = note_offset + note.len();
+        let sh_offset = (shstrtab_offset + shstrtab.len() + 7) & !7;
+
+        let mut elf = vec![0x7f, b'E', b'L', b'F', 2, 1, 1];
+        elf.resize(16, 0);
+        elf.extend((3u16).to_le_bytes()); // ET_DYN
+        elf.extend((62u16).to_le_bytes()); // EM_X86_64
+        elf.extend((1u32).to_le_bytes()); // EV_CURRENT
+        elf.extend((0u64).to_le_bytes()); // entry
+        elf.extend((0u64).to_le_bytes()); // phoff
+        elf.extend((sh_offset as u64).to_le_bytes());
+        elf.extend((0u32).to_le_bytes()); // flags
+        for half in [64u16, 56, 0, 64, 3, 2] {
+            // ehsize, phentsize, phnum, shentsize, shnum, shstrndx
+            elf.extend(half.to_le_bytes());
+        }
+        elf.extend(&note);
+        elf.extend(shstrtab);
+        elf.resize(sh_offset, 0);
+
+        // The null section, the note (SHT_NOTE, SHF_ALLOC), and the names (SHT_STRTAB).
+        let sections = [
+            (0u32, 0u32, 0u64, 0usize, 0usize, 0u64),
+            (1, 7, 2, note_offset, note.len(), 4),
+            (20, 3, 0, shstrtab_offset, shstrtab.len(), 1),
+        ];
+        for (name, kind, flags, offset, size, align) in sections {
+            elf.extend(name.to_le_bytes());
+            elf.extend(kind.to_le_bytes());
+            elf.extend(flags.to_le_bytes());
+            elf.extend((0u64).to_le_bytes()); // addr
+            elf.extend((offset as u64).to_le_bytes());
+            elf.extend((size as u64).to_le_bytes());
+            elf.extend((0u32).to_le_bytes()); // link
+            elf.extend((0u32).to_le_bytes()); // info
+            elf.extend(align.to_le_bytes());
+            elf.extend((0u64).to_le_bytes()); // entsize
+        }
+        elf
+    }
+
+    fn module(code_id: &str) -> SimpleModule {
+        let mut module = SimpleModule::from_basic_info(
+            None,
+            None,
+            Some("/not/on/this/machine/libfoo.so".to_owned()),
+            code_id.parse().ok(),
+        );
+        module.size = Some(0x1000);
+        module
+    }
+
+    #[test]
+    fn test_search_path_candidates() {
+        let dir = tempfile::tempdir().unwrap();
+        let other_dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("libfoo.so"), elf_with_build_id(&BUILD_ID)).unwrap();
+
+        let module = module("123456789abcdef0");
+        assert!(debug_file_candidates(&module, true, &[]).is_empty());
+        let search_paths = [other_dir.path().to_owned(), dir.path().to_owned()];
+        assert_eq!(
+            debug_file_candidates(&module, true, &search_paths),
+            [dir.path().join("libfoo.so")]
+        );
+    }
+
+    #[test]
+    fn test_mismatched_build_id_rejected() {
+        let elf = elf_with_build_id(&BUILD_ID);
+        let objfile = object::read::File::parse(&elf[..]).unwrap();
+        assert_eq!(objfile.build_id().unwrap(), Some(&BUILD_ID[..]));
+        assert!(object_matches_module(&objfile, &module("123456789ABCDEF0")));
+        assert!(!object_matches_module(
+            &objfile,
+            &module("0000000000000000")
+        ));
+
+        // A file from another build in the search paths isn't used.
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("libfoo.so"), &elf).unwrap();
+        let search_paths = [dir.path().to_owned()];
+        assert!(load_unwind_module(&module("123456789abcdef0"), &search_paths).is_some());
+        assert!(load_unwind_module(&module("0000000000000000"), &search_paths).is_none());
+    }
+}
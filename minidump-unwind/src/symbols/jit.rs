@@ -0,0 +1,595 @@
+//! A [`SymbolProvider`] for unwinding through JITed code.
+//!
+//! Code generated at runtime isn't part of any module, so there are no symbol
+//! files describing how to unwind it. A JIT can instead write the unwind info
+//! of the code it generated to a side file (e.g. at crash time), using breakpad's
+//! `STACK CFI` records with absolute addresses:
+//!
+//! ```text
+//! STACK CFI INIT 3a5c0000 40 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+//! STACK CFI 3a5c0001 .cfa: $rsp 16 + $rbp: .cfa -16 + ^
+//! STACK CFI 3a5c0004 .cfa: $rbp 16 +
+//! ```
+//!
+//! `STACK CFI INIT <address> <size> <rules>` defines the rules for a range of
+//! code, and `STACK CFI <address> <rules>` updates them from `address` to the
+//! end of the range (see the breakpad symbol file format for details). Blank
+//! lines and any other kind of record are ignored.
+//!
+//! [`JitUnwindInfoProvider`] only provides [`SymbolProvider::get_jit_unwind_info`],
+//! so it's meant to be combined with other providers in a
+//! [`MultiSymbolProvider`][super::MultiSymbolProvider].
+//!
+//! On x86-64 Windows, JITs can instead register the unwind info of their code
+//! with the OS (with `RtlAddFunctionTable`), in the same format as the unwind
+//! info of PE files. The minidump then has these dynamic function tables, and
+//! [`JitUnwindInfoProvider::from_function_tables`] translates them to rules.
+
+use std::convert::TryFrom;
+use std::fmt::{self, Write};
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use minidump::format::IMAGE_AMD64_RUNTIME_FUNCTION_ENTRY;
+use minidump::{MinidumpFunctionTableStream, Module, UnifiedMemoryList};
+use scroll::Pread;
+use tracing::debug;
+
+use super::{
+    CfiRules, FileError, FileKind, FillSymbolError, FrameSymbolizer, FrameWalker, SymbolProvider,
+};
+
+/// An error parsing JIT unwind info.
+#[derive(Debug)]
+pub enum JitUnwindInfoError {
+    /// The file couldn't be read.
+    Io(std::io::Error),
+    /// A `STACK CFI` record on the given line (starting at 1) was malformed.
+    Parse { line: usize },
+}
+
+impl fmt::Display for JitUnwindInfoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JitUnwindInfoError::Io(e) => write!(f, "couldn't read JIT unwind info: {e}"),
+            JitUnwindInfoError::Parse { line } => {
+                write!(f, "malformed STACK CFI record on line {line}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for JitUnwindInfoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JitUnwindInfoError::Io(e) => Some(e),
+            JitUnwindInfoError::Parse { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct JitRange {
+    start: u64,
+    size: u64,
+    init: String,
+    /// Sorted by address.
+    deltas: Vec<CfiRules>,
+}
+
+/// Provides unwind info for JITed code from `STACK CFI` records.
+///
+/// See the [module documentation][self] for the format.
+#[derive(Debug, Clone, Default)]
+pub struct JitUnwindInfoProvider {
+    /// Sorted by start address.
+    ranges: Vec<JitRange>,
+}
+
+impl JitUnwindInfoProvider {
+    /// Parse JIT unwind info.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, JitUnwindInfoError> {
+        let mut ranges: Vec<JitRange> = Vec::new();
+        for (idx, line) in bytes.split(|&b| b == b'\n').enumerate() {
+            let parse_error = || JitUnwindInfoError::Parse { line: idx + 1 };
+            let line = String::from_utf8_lossy(line);
+            let Some(record) = line.trim().strip_prefix("STACK CFI ") else {
+                if !line.trim().is_empty() {
+                    debug!("ignoring line {} of JIT unwind info", idx + 1);
+                }
+                continue;
+            };
+
+            if let Some(init) = record.strip_prefix("INIT ") {
+                let mut parts = init.trim_start().splitn(3, ' ');
+                let (Some(start), Some(size), Some(rules)) =
+                    (parts.next(), parts.next(), parts.next())
+                else {
+                    return Err(parse_error());
+                };
+                let start = u64::from_str_radix(start, 16).map_err(|_| parse_error())?;
+                let size = u64::from_str_radix(size, 16).map_err(|_| parse_error())?;
+                ranges.push(JitRange {
+                    start,
+                    size,
+                    init: rules.trim().to_owned(),
+                    deltas: Vec::new(),
+                });
+            } else {
+                let Some((address, rules)) = record.trim_start().split_once(' ') else {
+                    return Err(parse_error());
+                };
+                let address = u64::from_str_radix(address, 16).map_err(|_| parse_error())?;
+                // Deltas apply to the preceding INIT record
+                let Some(range) = ranges.last_mut() else {
+                    return Err(parse_error());
+                };
+                range.deltas.push(CfiRules {
+                    address,
+                    rules: rules.trim().to_owned(),
+                });
+            }
+        }
+
+        for range in &mut ranges {
+            range.deltas.sort_by_key(|delta| delta.address);
+        }
+        ranges.sort_by_key(|range| range.start);
+        Ok(JitUnwindInfoProvider { ranges })
+    }
+
+    /// Read JIT unwind info from a file.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, JitUnwindInfoError> {
+        let bytes = std::fs::read(path).map_err(JitUnwindInfoError::Io)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Unwind info for the code of a minidump's x86-64 dynamic function tables,
+    /// from the unwind info (`UNWIND_INFO`) of their functions in `memory`.
+    ///
+    /// Functions whose unwind info isn't in the minidump's memory, or can't be
+    /// expressed as rules (machine frames), are left out. Epilogs aren't
+    /// recognized, so a frame in the middle of one is unwound as if it was
+    /// still in the body of its function.
+    pub fn from_function_tables(
+        tables: &MinidumpFunctionTableStream,
+        memory: &UnifiedMemoryList,
+    ) -> Self {
+        let read_memory = |address: u64, size: usize| {
+            let region = memory.memory_at_address(address)?;
+            let offset = usize::try_from(address - region.base_address()).ok()?;
+            region.bytes().get(offset..offset.checked_add(size)?)
+        };
+        let mut ranges: Vec<JitRange> = tables
+            .iter()
+            .flat_map(|table| {
+                let base_address = table.raw.base_address;
+                table.amd64_entries().filter_map(move |entry| {
+                    amd64_function_range(base_address, &entry, &read_memory)
+                })
+            })
+            .collect();
+        ranges.sort_by_key(|range| range.start);
+        JitUnwindInfoProvider { ranges }
+    }
+
+    /// The rules to unwind a frame executing `address`, including any deltas that apply.
+    pub fn unwind_info(&self, address: u64) -> Option<CfiRules> {
+        let idx = self
+            .ranges
+            .partition_point(|range| range.start <= address)
+            .checked_sub(1)?;
+        let range = &self.ranges[idx];
+        if address - range.start >= range.size {
+            return None;
+        }
+
+        // Later rules for a register override earlier ones, so the deltas can
+        // just be appended to the initial rules.
+        let mut rules = range.init.clone();
+        for delta in range
+            .deltas
+            .iter()
+            .take_while(|delta| delta.address <= address)
+        {
+            rules.push(' ');
+            rules.push_str(&delta.rules);
+        }
+        Some(CfiRules {
+            address: range.start,
+            rules,
+        })
+    }
+}
+
+// The unwind operations of x86-64 `UNWIND_INFO`.
+const UWOP_PUSH_NONVOL: u16 = 0;
+const UWOP_ALLOC_LARGE: u16 = 1;
+const UWOP_ALLOC_SMALL: u16 = 2;
+const UWOP_SET_FPREG: u16 = 3;
+const UWOP_SAVE_NONVOL: u16 = 4;
+const UWOP_SAVE_NONVOL_FAR: u16 = 5;
+const UWOP_EPILOG: u16 = 6;
+const UWOP_SPARE_CODE: u16 = 7;
+const UWOP_SAVE_XMM128: u16 = 8;
+const UWOP_SAVE_XMM128_FAR: u16 = 9;
+
+/// The `UNWIND_INFO` continues with the `RUNTIME_FUNCTION` of its parent.
+const UNW_FLAG_CHAININFO: u8 = 0x4;
+
+/// How many chained `UNWIND_INFO`s are followed, in case they form a cycle.
+const MAX_UNWIND_INFO_CHAIN: usize = 32;
+
+/// The registers in the order of their x86-64 unwind info numbers.
+const AMD64_REGISTERS: [&str; 16] = [
+    "$rax", "$rcx", "$rdx", "$rbx", "$rsp", "$rbp", "$rsi", "$rdi", "$r8", "$r9", "$r10", "$r11",
+    "$r12", "$r13", "$r14", "$r15",
+];
+
+/// The parts of an x86-64 `UNWIND_INFO` that are needed to unwind.
+struct Amd64UnwindInfo {
+    frame_register: u8,
+    frame_offset: u8,
+    /// The unwind codes, in 16-bit slots (most codes take one).
+    codes: Vec<u16>,
+}
+
+/// Read the `UNWIND_INFO` at `address`, followed by the ones it's chained to.
+fn read_amd64_unwind_info<'a>(
+    base_address: u64,
+    mut unwind_info_address: u32,
+    read_memory: &impl Fn(u64, usize) -> Option<&'a [u8]>,
+) -> Option<Vec<Amd64UnwindInfo>> {
+    let mut chain = Vec::new();
+    loop {
+        let address = base_address.checked_add(unwind_info_address.into())?;
+        let header = read_memory(address, 4)?;
+        let flags = header[0] >> 3;
+        let count_of_codes = usize::from(header[2]);
+        let codes = read_memory(address + 4, count_of_codes * 2)?
+            .chunks_exact(2)
+            .map(|code| u16::from_le_bytes([code[0], code[1]]))
+            .collect();
+        chain.push(Amd64UnwindInfo {
+            frame_register: header[3] & 0xf,
+            frame_offset: header[3] >> 4,
+            codes,
+        });
+        if flags & UNW_FLAG_CHAININFO == 0 {
+            return Some(chain);
+        }
+        if chain.len() == MAX_UNWIND_INFO_CHAIN {
+            return None;
+        }
+        // The parent's RUNTIME_FUNCTION follows the codes, padded to an even count.
+        let parent_address = address + 4 + (count_of_codes as u64).div_ceil(2) * 4;
+        let parent: IMAGE_AMD64_RUNTIME_FUNCTION_ENTRY = read_memory(parent_address, 12)?
+            .pread_with(0, scroll::LE)
+            .ok()?;
+        unwind_info_address = parent.unwind_info_address;
+    }
+}
+
+/// The rules to unwind a function that's `offset` bytes into its code, from
+/// its unwind info `chain`.
+///
+/// This undoes the operations of the prolog that already ran in reverse, like
+/// `RtlVirtualUnwind` does.
+fn amd64_rules(chain: &[Amd64UnwindInfo], offset: u64) -> Option<String> {
+    // The stack pointer after undoing the operations so far is `base` + `delta`.
+    let mut base = AMD64_REGISTERS[4];
+    let mut delta: i64 = 0;
+    // Where each saved register is: as `base` + `delta` when it was saved.
+    let mut saved = Vec::new();
+    for (idx, info) in chain.iter().enumerate() {
+        // All of the prologs of the parents have run.
+        let ran = |code: u16| idx > 0 || u64::from(code & 0xff) <= offset;
+        // Registers saved with a MOV are relative to the establisher frame:
+        // the frame register less its offset once it's set (the stack pointer
+        // may have moved since, e.g. by an alloca), or else the stack pointer.
+        let mut frame = (base, delta);
+        let mut i = 0;
+        while i < info.codes.len() {
+            let code = info.codes[i];
+            let op = (code >> 8) & 0xf;
+            if op == UWOP_SET_FPREG && ran(code) && info.frame_register != 0 {
+                frame = (
+                    AMD64_REGISTERS[usize::from(info.frame_register)],
+                    -i64::from(info.frame_offset) * 16,
+                );
+            }
+            i += amd64_code_slots(op, code >> 12)?;
+        }
+
+        let mut i = 0;
+        while i < info.codes.len() {
+            let code = info.codes[i];
+            let op = (code >> 8) & 0xf;
+            let op_info = code >> 12;
+            let register = AMD64_REGISTERS[usize::from(op_info)];
+            let slot = |n: usize| info.codes.get(i + n).map(|&slot| i64::from(slot));
+            if ran(code) {
+                match op {
+                    UWOP_PUSH_NONVOL => {
+                        saved.push((register, base, delta));
+                        delta += 8;
+                    }
+                    UWOP_ALLOC_LARGE if op_info == 0 => delta += slot(1)? * 8,
+                    UWOP_ALLOC_LARGE => delta += slot(1)? | slot(2)? << 16,
+                    UWOP_ALLOC_SMALL => delta += i64::from(op_info) * 8 + 8,
+                    UWOP_SET_FPREG => {
+                        if info.frame_register == 0 {
+                            return None;
+                        }
+                        base = AMD64_REGISTERS[usize::from(info.frame_register)];
+                        delta = -i64::from(info.frame_offset) * 16;
+                    }
+                    UWOP_SAVE_NONVOL => saved.push((register, frame.0, frame.1 + slot(1)? * 8)),
+                    UWOP_SAVE_NONVOL_FAR => {
+                        saved.push((register, frame.0, frame.1 + (slot(1)? | slot(2)? << 16)));
+                    }
+                    // The XMM registers aren't unwound, and epilogs aren't recognized.
+                    _ => {}
+                }
+            }
+            i += amd64_code_slots(op, op_info)?;
+        }
+    }
+
+    // What's left on the stack is the return address.
+    let mut rules = format!(".cfa: {base} {} + .ra: .cfa -8 + ^", delta + 8);
+    for (register, base, delta) in saved {
+        write!(rules, " {register}: {base} {delta} + ^").ok()?;
+    }
+    Some(rules)
+}
+
+/// How many 16-bit slots the unwind code `op` takes, or `None` for a machine
+/// frame (of an interrupt or exception handler).
+fn amd64_code_slots(op: u16, op_info: u16) -> Option<usize> {
+    match op {
+        UWOP_PUSH_NONVOL | UWOP_ALLOC_SMALL | UWOP_SET_FPREG | UWOP_SPARE_CODE => Some(1),
+        UWOP_ALLOC_LARGE if op_info == 0 => Some(2),
+        UWOP_ALLOC_LARGE | UWOP_SAVE_NONVOL_FAR | UWOP_SAVE_XMM128_FAR => Some(3),
+        UWOP_SAVE_NONVOL | UWOP_EPILOG | UWOP_SAVE_XMM128 => Some(2),
+        _ => None,
+    }
+}
+
+/// The unwind info of the function `entry` of a function table at `base_address`.
+fn amd64_function_range<'a>(
+    base_address: u64,
+    entry: &IMAGE_AMD64_RUNTIME_FUNCTION_ENTRY,
+    read_memory: &impl Fn(u64, usize) -> Option<&'a [u8]>,
+) -> Option<JitRange> {
+    let start = base_address.checked_add(entry.begin_address.into())?;
+    let size = u64::from(entry.end_address.checked_sub(entry.begin_address)?);
+    let chain = read_amd64_unwind_info(base_address, entry.unwind_info_address, read_memory)?;
+
+    // The rules change after each operation of the prolog. They're complete
+    // at each point, so they override all the earlier ones.
+    let mut offsets: Vec<u64> = chain[0]
+        .codes
+        .iter()
+        .map(|code| u64::from(code & 0xff))
+        .filter(|&offset| offset > 0 && offset < size)
+        .collect();
+    offsets.sort_unstable();
+    offsets.dedup();
+    let deltas = offsets
+        .into_iter()
+        .map(|offset| {
+            Some(CfiRules {
+                address: start + offset,
+                rules: amd64_rules(&chain, offset)?,
+            })
+        })
+        .collect::<Option<_>>()?;
+    Some(JitRange {
+        start,
+        size,
+        init: amd64_rules(&chain, 0)?,
+        deltas,
+    })
+}
+
+#[async_trait]
+impl SymbolProvider for JitUnwindInfoProvider {
+    async fn fill_symbol(
+        &self,
+        _module: &(dyn Module + Sync),
+        _frame: &mut (dyn FrameSymbolizer + Send),
+    ) -> Result<(), FillSymbolError> {
+        Err(FillSymbolError {})
+    }
+
+    async fn walk_frame(
+        &self,
+        _module: &(dyn Module + Sync),
+        _walker: &mut (dyn FrameWalker + Send),
+    ) -> Option<()> {
+        None
+    }
+
+    async fn get_jit_unwind_info(&self, address: u64) -> Option<CfiRules> {
+        self.unwind_info(address)
+    }
+
+    async fn get_file_path(
+        &self,
+        _module: &(dyn Module + Sync),
+        _file_kind: FileKind,
+    ) -> Result<PathBuf, FileError> {
+        Err(FileError::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INFO: &str = "\
+STACK CFI INIT 2000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+STACK CFI 2004 .cfa: $rbp 16 +
+STACK CFI 2001 .cfa: $rsp 16 + $rbp: .cfa -16 + ^
+
+STACK CFI INIT 1000 20 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+";
+
+    #[test]
+    fn test_unwind_info() {
+        let provider = JitUnwindInfoProvider::from_bytes(INFO.as_bytes()).unwrap();
+        let rules = |address| provider.unwind_info(address).map(|rules| rules.rules);
+
+        assert_eq!(rules(0xfff), None);
+        assert_eq!(
+            rules(0x101f).as_deref(),
+            Some(".cfa: $rsp 8 + .ra: .cfa -8 + ^")
+        );
+        assert_eq!(rules(0x1020), None);
+        assert_eq!(
+            rules(0x2000).as_deref(),
+            Some(".cfa: $rsp 8 + .ra: .cfa -8 + ^")
+        );
+        assert_eq!(
+            rules(0x2003).as_deref(),
+            Some(".cfa: $rsp 8 + .ra: .cfa -8 + ^ .cfa: $rsp 16 + $rbp: .cfa -16 + ^")
+        );
+        assert_eq!(
+            rules(0x2004).as_deref(),
+            Some(
+                ".cfa: $rsp 8 + .ra: .cfa -8 + ^ .cfa: $rsp 16 + $rbp: .cfa -16 + ^ \
+                 .cfa: $rbp 16 +"
+            )
+        );
+        assert_eq!(rules(0x2010), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error_line = |info: &str| match JitUnwindInfoProvider::from_bytes(info.as_bytes()) {
+            Err(JitUnwindInfoError::Parse { line }) => Some(line),
+            _ => None,
+        };
+        assert_eq!(error_line("STACK CFI INIT 1000 .cfa: $rsp 8 +"), Some(1));
+        assert_eq!(error_line("\nSTACK CFI 1000 .cfa: $rsp 8 +"), Some(2));
+        assert_eq!(error_line("FUNC 1000 10 0 jitted\n"), None);
+    }
+    #[test]
+    fn test_amd64_unwind_info() {
+        // push rbp; push rbx; sub rsp, 0x20; lea rbp, [rsp + 0x10]
+        let mut memory = vec![0; 0x200];
+        memory[0x100..0x10c].copy_from_slice(&[
+            0x01, 0x0b, 0x04, 0x15, // 4 codes, frame register rbp at offset 0x10
+            0x0b, 0x03, // SET_FPREG
+            0x06, 0x32, // ALLOC_SMALL 0x20
+            0x02, 0x30, // PUSH_NONVOL rbx
+            0x01, 0x50, // PUSH_NONVOL rbp
+        ]);
+        // Chained to the first one, with a prolog of its own (sub rsp, 0x10)
+        memory[0x120..0x134].copy_from_slice(&[
+            0x21, 0x04, 0x01, 0x00, // 1 code, chained
+            0x04, 0x12, // ALLOC_SMALL 0x10
+            0x00, 0x00, // padding
+            0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00,
+        ]);
+        // PUSH_MACHFRAME
+        memory[0x140..0x146].copy_from_slice(&[0x01, 0x00, 0x01, 0x00, 0x00, 0x0a]);
+        let read_memory = |address: u64, size: usize| {
+            let offset = usize::try_from(address.checked_sub(0x1000)?).ok()?;
+            memory.get(offset..offset + size)
+        };
+        let function = |begin_address, end_address, unwind_info_address| {
+            let entry = IMAGE_AMD64_RUNTIME_FUNCTION_ENTRY {
+                begin_address,
+                end_address,
+                unwind_info_address,
+            };
+            amd64_function_range(0x1000, &entry, &read_memory)
+        };
+
+        let range = function(0x0, 0x40, 0x100).unwrap();
+        assert_eq!((range.start, range.size), (0x1000, 0x40));
+        assert_eq!(range.init, ".cfa: $rsp 8 + .ra: .cfa -8 + ^");
+        let deltas: Vec<_> = range
+            .deltas
+            .iter()
+            .map(|delta| (delta.address, delta.rules.as_str()))
+            .collect();
+        assert_eq!(
+            deltas,
+            [
+                (0x1001, ".cfa: $rsp 16 + .ra: .cfa -8 + ^ $rbp: $rsp 0 + ^"),
+                (
+                    0x1002,
+                    ".cfa: $rsp 24 + .ra: .cfa -8 + ^ $rbx: $rsp 0 + ^ $rbp: $rsp 8 + ^"
+                ),
+                (
+                    0x1006,
+                    ".cfa: $rsp 56 + .ra: .cfa -8 + ^ $rbx: $rsp 32 + ^ $rbp: $rsp 40 + ^"
+                ),
+                (
+                    0x100b,
+                    ".cfa: $rbp 40 + .ra: .cfa -8 + ^ $rbx: $rbp 16 + ^ $rbp: $rbp 24 + ^"
+                ),
+            ]
+        );
+
+        // The prolog of the parent has run all the way.
+        let range = function(0x80, 0x90, 0x120).unwrap();
+        assert_eq!(
+            range.init,
+            ".cfa: $rbp 40 + .ra: .cfa -8 + ^ $rbx: $rbp 16 + ^ $rbp: $rbp 24 + ^"
+        );
+        assert_eq!(range.deltas[0].address, 0x1084);
+        assert_eq!(
+            range.deltas[0].rules,
+            ".cfa: $rbp 40 + .ra: .cfa -8 + ^ $rbx: $rbp 16 + ^ $rbp: $rbp 24 + ^"
+        );
+
+        assert!(function(0x40, 0x50, 0x140).is_none());
+        assert!(function(0x40, 0x50, 0x1000).is_none());
+    }
+
+    #[test]
+    fn test_amd64_unwind_info_save_with_frame_pointer() {
+        // push rbp; sub rsp, 0x30; lea rbp, [rsp + 0x20]; mov [rsp + 0x28], rbx
+        let mut memory = [0; 0x20];
+        memory[0x0..0xe].copy_from_slice(&[
+            0x01, 0x0f, 0x05, 0x25, // 5 codes, frame register rbp at offset 0x20
+            0x0f, 0x34, 0x05, 0x00, // SAVE_NONVOL rbx at 0x28
+            0x0a, 0x03, // SET_FPREG
+            0x05, 0x52, // ALLOC_SMALL 0x30
+            0x01, 0x50, // PUSH_NONVOL rbp
+        ]);
+        let read_memory = |address: u64, size: usize| {
+            let offset = usize::try_from(address.checked_sub(0x1000)?).ok()?;
+            memory.get(offset..offset + size)
+        };
+        let entry = IMAGE_AMD64_RUNTIME_FUNCTION_ENTRY {
+            begin_address: 0x100,
+            end_address: 0x140,
+            unwind_info_address: 0,
+        };
+        let range = amd64_function_range(0x1000, &entry, &read_memory).unwrap();
+        let deltas: Vec<_> = range
+            .deltas
+            .iter()
+            .map(|delta| (delta.address, delta.rules.as_str()))
+            .collect();
+        // The body of the function may move the stack pointer (with an
+        // alloca), so rbx is found through the frame pointer, like the others.
+        assert_eq!(
+            deltas,
+            [
+                (0x1101, ".cfa: $rsp 16 + .ra: .cfa -8 + ^ $rbp: $rsp 0 + ^"),
+                (0x1105, ".cfa: $rsp 64 + .ra: .cfa -8 + ^ $rbp: $rsp 48 + ^"),
+                (0x110a, ".cfa: $rbp 32 + .ra: .cfa -8 + ^ $rbp: $rbp 16 + ^"),
+                (
+                    0x110f,
+                    ".cfa: $rbp 32 + .ra: .cfa -8 + ^ $rbx: $rbp 8 + ^ $rbp: $rbp 16 + ^"
+                ),
+            ]
+        );
+    }
+}
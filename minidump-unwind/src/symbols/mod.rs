@@ -21,8 +21,12 @@
 //! strategy:
 //!
 //! * [http_symbol_supplier][] - a [SymbolSupplier][] that can find symbols over HTTP (and cache).
-//!   Requires the `http` feature to be enabled.
+//!   Requires the `http` feature to be enabled. [http_symbol_supplier_with_options][] also
+//!   configures retries, timeouts, proxies, and authentication with [HttpOptions][].
 //! * [simple_symbol_supplier][] - a [SymbolSupplier][] that can find symbols on disk.
+//! * [native_symbol_supplier][] - a builder for a [debuginfo::DebugInfoSymbolProvider][] that
+//!   also looks for native debug files in the given directories. Requires the `debuginfo`
+//!   feature to be enabled.
 //! * [string_symbol_supplier][] - a mock [SymbolSupplier][] for tests.
 //!
 //!
@@ -31,6 +35,8 @@
 //! * [Symbolizer][] - the main interface of the symbolizer, implementing [SymbolProvider][].
 //!     * Wraps the [SymbolSupplier][] implementation that is selected.
 //!     * Queries the [SymbolSupplier] and manages the SymbolFiles however it pleases.
+//! * [jit::JitUnwindInfoProvider][] - a [SymbolProvider][] that unwinds JITed code using
+//!   `STACK CFI` records from a side file.
 //! * [SymbolStats][] - debug statistic output.
 //! * [SymbolFile][] - part of [LocateSymbolsResult][] that a [SymbolProvider][] returns to the
 //!   Symbolizer.
@@ -48,18 +54,23 @@
 
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
 use minidump::Module;
+use minidump_common::utils::basename;
 
 pub use breakpad_symbols::{
-    DebugInfoResult, FileError, FileKind, FillSymbolError, FrameSymbolizer, FrameWalker,
-    LocateSymbolsResult, PendingSymbolStats, SymbolError, SymbolFile, SymbolStats, SymbolSupplier,
-    Symbolizer,
+    CfiRules, DebugInfoResult, FileError, FileKind, FillSymbolError, FrameSymbolizer, FrameWalker,
+    LocateSymbolsResult, PendingSymbolStats, SymbolError, SymbolFile, SymbolKind, SymbolStats,
+    SymbolSupplier, Symbolizer, Vcs, VcsSourceFile, WalkFrameError,
 };
+#[cfg(feature = "http")]
+pub use breakpad_symbols::{HttpOptions, SymbolServerHeader, UrlSigner};
 
 #[cfg(feature = "debuginfo-unwind")]
 pub mod debuginfo;
+pub mod jit;
 
 /// The [`SymbolProvider`] is the main extension point for minidump processing.
 ///
@@ -80,7 +91,10 @@ pub trait SymbolProvider {
     /// address from `frame`, and the module information from [`Module`].
     ///
     /// An Error indicates that no symbols could be found for the relevant
-    /// module.
+    /// module. If there are symbols for the module but none of them cover the
+    /// instruction, this should return `Ok` without filling in anything, so
+    /// that the two cases can be told apart (see
+    /// [`SymbolDisposition`][crate::SymbolDisposition]).
     ///
     /// This is used for filling in the resulting source location of the
     /// frame as a (function, file, line) triple, as well as providing the
@@ -105,6 +119,36 @@ pub trait SymbolProvider {
         walker: &mut (dyn FrameWalker + Send),
     ) -> Option<()>;
 
+    /// [`SymbolProvider::walk_frame`], but says why the frame couldn't be walked.
+    ///
+    /// The stack walker uses this, and records the error on the frame it falls
+    /// back to. The default implementation calls `walk_frame`, and can only say
+    /// [`WalkFrameError::NoCfi`].
+    async fn try_walk_frame(
+        &self,
+        module: &(dyn Module + Sync),
+        walker: &mut (dyn FrameWalker + Send),
+    ) -> Result<(), WalkFrameError> {
+        self.walk_frame(module, walker)
+            .await
+            .ok_or(WalkFrameError::NoCfi)
+    }
+
+    /// Gets the CFI rules to walk a stack frame executing `address`, which
+    /// isn't in any [`Module`].
+    ///
+    /// This is meant for code that was generated at runtime (by a JIT), which
+    /// has to describe how to unwind its frames out-of-band. The stack walker
+    /// asks for these before falling back to frame pointers and scanning.
+    ///
+    /// The rules are the same as the ones of a `STACK CFI` record, and should
+    /// already include any `STACK CFI` deltas that apply to `address`.
+    ///
+    /// The default implementation returns `None`.
+    async fn get_jit_unwind_info(&self, _address: u64) -> Option<CfiRules> {
+        None
+    }
+
     /// Gets the path to the binary code file for a given module (or an Error).
     ///
     /// This might be used later on to inspect the assembly instructions of
@@ -143,6 +187,14 @@ pub trait SymbolProvider {
     fn pending_stats(&self) -> PendingSymbolStats {
         PendingSymbolStats::default()
     }
+
+    /// A name for where this provider gets symbols from.
+    ///
+    /// [`MultiSymbolProvider`] records in [`SymbolStats::provider`] which of
+    /// its providers served each module. The default is the type's name.
+    fn name(&self) -> String {
+        std::any::type_name::<Self>().to_owned()
+    }
 }
 
 #[async_trait]
@@ -163,6 +215,18 @@ impl SymbolProvider for &(dyn SymbolProvider + Sync) {
         (*self).walk_frame(module, walker).await
     }
 
+    async fn try_walk_frame(
+        &self,
+        module: &(dyn Module + Sync),
+        walker: &mut (dyn FrameWalker + Send),
+    ) -> Result<(), WalkFrameError> {
+        (*self).try_walk_frame(module, walker).await
+    }
+
+    async fn get_jit_unwind_info(&self, address: u64) -> Option<CfiRules> {
+        (*self).get_jit_unwind_info(address).await
+    }
+
     async fn get_file_path(
         &self,
         module: &(dyn Module + Sync),
@@ -178,11 +242,75 @@ impl SymbolProvider for &(dyn SymbolProvider + Sync) {
     fn pending_stats(&self) -> PendingSymbolStats {
         (*self).pending_stats()
     }
+
+    fn name(&self) -> String {
+        (*self).name()
+    }
 }
 
+/// Shares a provider, e.g. one [`Symbolizer`] between several minidumps
+/// being processed at the same time.
+#[async_trait]
+impl<T: SymbolProvider + Send + Sync + ?Sized> SymbolProvider for Arc<T> {
+    async fn fill_symbol(
+        &self,
+        module: &(dyn Module + Sync),
+        frame: &mut (dyn FrameSymbolizer + Send),
+    ) -> Result<(), FillSymbolError> {
+        (**self).fill_symbol(module, frame).await
+    }
+
+    async fn walk_frame(
+        &self,
+        module: &(dyn Module + Sync),
+        walker: &mut (dyn FrameWalker + Send),
+    ) -> Option<()> {
+        (**self).walk_frame(module, walker).await
+    }
+
+    async fn try_walk_frame(
+        &self,
+        module: &(dyn Module + Sync),
+        walker: &mut (dyn FrameWalker + Send),
+    ) -> Result<(), WalkFrameError> {
+        (**self).try_walk_frame(module, walker).await
+    }
+
+    async fn get_jit_unwind_info(&self, address: u64) -> Option<CfiRules> {
+        (**self).get_jit_unwind_info(address).await
+    }
+
+    async fn get_file_path(
+        &self,
+        module: &(dyn Module + Sync),
+        file_kind: FileKind,
+    ) -> Result<PathBuf, FileError> {
+        (**self).get_file_path(module, file_kind).await
+    }
+
+    fn stats(&self) -> HashMap<String, SymbolStats> {
+        (**self).stats()
+    }
+
+    fn pending_stats(&self) -> PendingSymbolStats {
+        (**self).pending_stats()
+    }
+
+    fn name(&self) -> String {
+        (**self).name()
+    }
+}
+
+/// Combines several [`SymbolProvider`]s, which are tried in the order they were added.
+///
+/// The symbols of a module come from the first provider that has symbols for it, and
+/// [`SymbolStats::provider`] records which one that was.
 #[derive(Default)]
 pub struct MultiSymbolProvider {
     providers: Vec<Box<dyn SymbolProvider + Send + Sync>>,
+    /// The index of the provider that filled in symbols for each module, by the
+    /// file name of the module (like the keys of [`SymbolProvider::stats`]).
+    served_by: Mutex<HashMap<String, usize>>,
 }
 
 impl MultiSymbolProvider {
@@ -204,13 +332,20 @@ impl SymbolProvider for MultiSymbolProvider {
     ) -> Result<(), FillSymbolError> {
         // Return Ok if *any* symbol provider came back with Ok, so that the user can
         // distinguish between having no symbols at all and just not being able to
-        // symbolize this particular frame.
-        let mut best_result = Err(FillSymbolError {});
-        for p in self.providers.iter() {
-            let new_result = p.fill_symbol(module, frame).await;
-            best_result = best_result.or(new_result);
+        // symbolize this particular frame. The first provider with symbols for the
+        // module serves it, so we know where the symbols of every module came from.
+        let key = basename(&module.code_file()).to_owned();
+        let served_by = self.served_by.lock().unwrap().get(&key).copied();
+        if let Some(p) = served_by.and_then(|i| self.providers.get(i)) {
+            return p.fill_symbol(module, frame).await;
         }
-        best_result
+        for (i, p) in self.providers.iter().enumerate() {
+            if p.fill_symbol(module, frame).await.is_ok() {
+                self.served_by.lock().unwrap().entry(key).or_insert(i);
+                return Ok(());
+            }
+        }
+        Err(FillSymbolError {})
     }
 
     async fn walk_frame(
@@ -218,8 +353,34 @@ impl SymbolProvider for MultiSymbolProvider {
         module: &(dyn Module + Sync),
         walker: &mut (dyn FrameWalker + Send),
     ) -> Option<()> {
+        self.try_walk_frame(module, walker).await.ok()
+    }
+
+    async fn try_walk_frame(
+        &self,
+        module: &(dyn Module + Sync),
+        walker: &mut (dyn FrameWalker + Send),
+    ) -> Result<(), WalkFrameError> {
+        // If every provider fails, the most informative error wins: knowing
+        // that there was CFI is better than just knowing one provider had none.
+        let mut error = WalkFrameError::NoCfi;
         for p in self.providers.iter() {
-            let result = p.walk_frame(module, walker).await;
+            match p.try_walk_frame(module, walker).await {
+                Ok(()) => return Ok(()),
+                Err(WalkFrameError::NoCfi) => {}
+                Err(e) => {
+                    if error == WalkFrameError::NoCfi {
+                        error = e;
+                    }
+                }
+            }
+        }
+        Err(error)
+    }
+
+    async fn get_jit_unwind_info(&self, address: u64) -> Option<CfiRules> {
+        for p in self.providers.iter() {
+            let result = p.get_jit_unwind_info(address).await;
             if result.is_some() {
                 return result;
             }
@@ -242,11 +403,37 @@ impl SymbolProvider for MultiSymbolProvider {
     }
 
     fn stats(&self) -> HashMap<String, SymbolStats> {
-        let mut result = HashMap::new();
-        for p in self.providers.iter() {
-            // FIXME: do more intelligent merging of the stats
-            // (currently doesn't matter as only one provider reports non-empty stats).
-            result.extend(p.stats());
+        let served_by = self.served_by.lock().unwrap();
+        let mut result: HashMap<String, SymbolStats> = HashMap::new();
+        for (i, p) in self.providers.iter().enumerate() {
+            for (key, mut stats) in p.stats() {
+                let served = served_by.get(&key) == Some(&i);
+                if served && stats.provider.is_none() {
+                    stats.provider = Some(p.name());
+                }
+                // The stats of the provider that served the module win. Otherwise
+                // the first provider that found symbols does, or just the first one.
+                let replace = match result.get(&key) {
+                    None => true,
+                    Some(_) if served => true,
+                    Some(existing) => {
+                        served_by.get(&key).is_none()
+                            && !existing.loaded_symbols
+                            && stats.loaded_symbols
+                    }
+                };
+                if replace {
+                    result.insert(key, stats);
+                }
+            }
+        }
+        // Providers that don't keep stats (e.g. for local debug info) still get credit
+        for (key, &i) in served_by.iter() {
+            let stats = result.entry(key.clone()).or_default();
+            if stats.provider.is_none() {
+                stats.provider = Some(self.providers[i].name());
+                stats.loaded_symbols = true;
+            }
         }
         result
     }
@@ -278,6 +465,13 @@ impl SymbolProvider for Symbolizer {
     ) -> Option<()> {
         self.walk_frame(module, walker).await
     }
+    async fn try_walk_frame(
+        &self,
+        module: &(dyn Module + Sync),
+        walker: &mut (dyn FrameWalker + Send),
+    ) -> Result<(), WalkFrameError> {
+        self.try_walk_frame(module, walker).await
+    }
     async fn get_file_path(
         &self,
         module: &(dyn Module + Sync),
@@ -291,6 +485,9 @@ impl SymbolProvider for Symbolizer {
     fn pending_stats(&self) -> PendingSymbolStats {
         self.pending_stats()
     }
+    fn name(&self) -> String {
+        self.name()
+    }
 }
 
 /// Gets a SymbolSupplier that looks up symbols by path or with urls.
@@ -334,6 +531,28 @@ pub fn http_symbol_supplier(
     )
 }
 
+/// Like [`http_symbol_supplier`], but with full control over how symbol servers
+/// are queried: retries, timeouts, proxies, and authentication.
+///
+/// `options.timeout` takes the place of `timeout`. This fails if the options
+/// are invalid, e.g. an unparsable `options.proxy`.
+#[cfg(feature = "http")]
+pub fn http_symbol_supplier_with_options(
+    symbol_paths: Vec<PathBuf>,
+    symbol_urls: Vec<String>,
+    symbols_cache: PathBuf,
+    symbols_tmp: PathBuf,
+    options: HttpOptions,
+) -> std::io::Result<impl SymbolSupplier> {
+    breakpad_symbols::HttpSymbolSupplier::with_options(
+        symbol_urls,
+        symbols_cache,
+        symbols_tmp,
+        symbol_paths,
+        options,
+    )
+}
+
 /// Gets a SymbolSupplier that looks up symbols by path.
 ///
 /// Paths are queried in order until one returns a payload.
@@ -341,6 +560,18 @@ pub fn simple_symbol_supplier(symbol_paths: Vec<PathBuf>) -> impl SymbolSupplier
     breakpad_symbols::SimpleSymbolSupplier::new(symbol_paths)
 }
 
+/// Gets a builder for a [debuginfo::DebugInfoSymbolProvider] that reads native debug files
+/// (PDB, DWARF) from the paths the minidump refers to, and then from `search_paths`.
+///
+/// Debug files in `search_paths` are looked up by file name, and only used if their debug id
+/// or build id matches the module's. Requires the `debuginfo` feature to be enabled.
+#[cfg(feature = "debuginfo-unwind")]
+pub fn native_symbol_supplier(
+    search_paths: Vec<PathBuf>,
+) -> debuginfo::DebugInfoSymbolProviderBuilder {
+    debuginfo::DebugInfoSymbolProvider::builder().search_paths(search_paths)
+}
+
 /// Gets a mock SymbolSupplier that just maps module names
 /// to a string containing an entire breakpad .sym file, for tests.
 pub fn string_symbol_supplier(modules: HashMap<String, String>) -> impl SymbolSupplier {
@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 
-use minidump::system_info::{Cpu, Os};
+use minidump::system_info::{Cpu, CpuEmulation, Os};
 
 /// Information about the system that produced a `Minidump`.
 #[derive(Debug, Clone)]
@@ -17,12 +17,33 @@ pub struct SystemInfo {
     /// this is the CSD version, on Linux, extended build information and macOS,
     /// the product build version.
     pub os_build: Option<String>,
+    /// The build lab string of the operating system.
+    ///
+    /// This may look like "19041.1.amd64fre.vb_release.191206-1406", if
+    /// present. Only Windows minidumps have this.
+    pub os_build_lab: Option<String>,
+    /// A string identifying the distribution of the operating system.
+    ///
+    /// This may look like "Ubuntu 22.04.1 LTS", if present. Only Linux
+    /// minidumps have this, from `/etc/lsb-release` or `/etc/os-release`.
+    pub os_distro: Option<String>,
     /// The CPU on which the dump was produced
     pub cpu: Cpu,
+    /// The emulation the process ran (some of) its code under, if any
+    ///
+    /// Threads running emulated code have contexts of the emulated CPU, and
+    /// are walked as such.
+    pub cpu_emulation: Option<CpuEmulation>,
     /// A string further identifying the specific CPU
     ///
     /// For example,  "GenuineIntel level 6 model 13 stepping 8", if present.
     pub cpu_info: Option<String>,
+    /// The names of the features of the CPU the minidump lists as present
+    ///
+    /// For example, "sse2" or "neon". See
+    /// [`MinidumpSystemInfo::cpu_features`][minidump::MinidumpSystemInfo::cpu_features]
+    /// for where they come from.
+    pub cpu_features: Vec<String>,
     /// The microcode version of the cpu
     pub cpu_microcode_version: Option<u64>,
     /// The number of processors in the system
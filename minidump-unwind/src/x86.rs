@@ -22,7 +22,7 @@ const CALLEE_SAVED_REGS: &[&str] = &["ebp", "ebx", "edi", "esi"];
 async fn get_caller_by_cfi<P>(
     ctx: &CONTEXT_X86,
     args: &GetCallerFrameArgs<'_, P>,
-) -> Option<StackFrame>
+) -> Result<StackFrame, WalkFrameError>
 where
     P: SymbolProvider + Sync,
 {
@@ -30,15 +30,15 @@ where
 
     if let MinidumpContextValidity::Some(ref which) = args.valid() {
         if !which.contains(STACK_POINTER_REGISTER) {
-            return None;
+            return Err(WalkFrameError::MissingRegister(
+                STACK_POINTER_REGISTER.to_owned(),
+            ));
         }
     }
 
-    let mut stack_walker = CfiStackWalker::from_ctx_and_args(ctx, args, callee_forwarded_regs)?;
+    let mut stack_walker = CfiStackWalker::from_ctx_and_args(ctx, args, callee_forwarded_regs);
 
-    args.symbol_provider
-        .walk_frame(stack_walker.module, &mut stack_walker)
-        .await?;
+    stack_walker.walk(args.symbol_provider).await?;
     let caller_ip = stack_walker.caller_ctx.eip;
     let caller_sp = stack_walker.caller_ctx.esp;
 
@@ -72,7 +72,7 @@ where
         raw: MinidumpRawContext::X86(stack_walker.caller_ctx),
         valid: MinidumpContextValidity::Some(stack_walker.caller_validity),
     };
-    Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
+    Ok(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
 
 fn callee_forwarded_regs(valid: &MinidumpContextValidity) -> HashSet<&'static str> {
@@ -136,11 +136,17 @@ where
     let caller_bp = args.stack_memory.get_memory_at_address(last_bp as u64)?;
     let caller_sp = last_bp + POINTER_WIDTH * 2;
 
+    // The caller's frame must be above ours and still in the stack, or we'd be
+    // following a corrupt (possibly cyclic) chain of frame pointers.
+    if !frame_pointer_stack_seems_valid(caller_sp as u64, ctx.esp as u64, args.stack_memory) {
+        trace!("rejecting frame pointer result for unreasonable stack pointer");
+        return None;
+    }
+
     // NOTE: minor divergence from x64 impl here: doing extra validation on the
-    // value of `caller_sp` and `caller_bp` here encourages the stack scanner
-    // to kick in and start outputting extra frames for `/testdata/test.dmp`.
-    // Since breakpad also doesn't output those frames, let's assume that's
-    // desirable.
+    // value of `caller_bp` here encourages the stack scanner to kick in and
+    // start outputting extra frames for `/testdata/test.dmp`. Since breakpad
+    // also doesn't output those frames, let's assume that's desirable.
 
     trace!(
         "frame pointer seems valid -- caller_ip: 0x{:08x}, caller_sp: 0x{:08x}",
@@ -360,22 +366,23 @@ fn stack_seems_valid(
 pub async fn get_caller_frame<P>(
     ctx: &CONTEXT_X86,
     args: &GetCallerFrameArgs<'_, P>,
-) -> Option<StackFrame>
+) -> Result<StackFrame, WalkTerminatedReason>
 where
     P: SymbolProvider + Sync,
 {
     // .await doesn't like closures, so don't use Option chaining
-    let mut frame = None;
-    if frame.is_none() {
-        frame = get_caller_by_cfi(ctx, args).await;
-    }
+    let (mut frame, cfi_error) = match get_caller_by_cfi(ctx, args).await {
+        Ok(frame) => (Some(frame), None),
+        Err(error) => (None, Some(error)),
+    };
     if frame.is_none() {
         frame = get_caller_by_frame_pointer(ctx, args);
     }
     if frame.is_none() {
         frame = get_caller_by_scan(ctx, args).await;
     }
-    let mut frame = frame?;
+    let mut frame = frame.ok_or(WalkTerminatedReason::NoCallerFound)?;
+    frame.cfi_error = cfi_error;
 
     // We now check the frame to see if it looks like unwinding is complete,
     // based on the frame we computed having a nonsense value. Returning
@@ -385,14 +392,14 @@ where
     // null, and we can assume unwinding is complete.
     if frame.context.get_instruction_pointer() < 4096 {
         trace!("instruction pointer was nullish, assuming unwind complete");
-        return None;
+        return Err(WalkTerminatedReason::NullInstructionPointer);
     }
     // If the new stack pointer is at a lower address than the old,
     // then that's clearly incorrect. Treat this as end-of-stack to
     // enforce progress and avoid infinite loops.
     if frame.context.get_stack_pointer() <= ctx.esp as u64 {
         trace!("stack pointer went backwards, assuming unwind complete");
-        return None;
+        return Err(WalkTerminatedReason::StackPointerNotIncreasing);
     }
 
     // Ok, the frame now seems well and truly valid, do final cleanup.
@@ -405,5 +412,5 @@ where
     let ip = frame.context.get_instruction_pointer();
     frame.instruction = ip - 1;
 
-    Some(frame)
+    Ok(frame)
 }
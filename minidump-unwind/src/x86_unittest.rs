@@ -46,8 +46,12 @@ impl TestFixture {
             os: Os::Windows,
             os_version: None,
             os_build: None,
+            os_build_lab: None,
+            os_distro: None,
             cpu: Cpu::X86,
+            cpu_emulation: None,
             cpu_info: None,
+            cpu_features: Vec::new(),
             cpu_microcode_version: None,
             cpu_count: 1,
         };
@@ -838,6 +842,107 @@ async fn test_stack_win_frame_data_parameter_size() {
     }
 }
 
+/// Walk a stdcall chain where the context frame (the grand callee of frame 1)
+/// pops 12 bytes of arguments, described by `module1_symbols`, and check that
+/// frame 2 is recovered from frame 1's STACK WIN record.
+async fn check_stack_win_grand_callee_parameter_size(module1_symbols: &str) {
+    let mut f = TestFixture::new();
+
+    let module2_symbols = [
+        "FUNC aa85 176 0 module2::whine\n",
+        "STACK WIN 4 aa85 176 0 0 4 4 10 0 1",
+        " $T0 .raSearchStart =",
+        " $eip $T0 ^ =",
+        " $esp $T0 4 + =",
+        " $ebp $T0 20 - ^ =",
+        " $ebx $T0 8 - ^ =\n",
+    ];
+    f.add_symbols(String::from("module1"), module1_symbols.to_owned());
+    f.add_symbols(String::from("module2"), module2_symbols.concat());
+
+    let frame0_ebp = Label::new();
+    let frame1_esp = Label::new();
+    let frame2_esp = Label::new();
+    let frame2_ebp = Label::new();
+
+    let mut stack = Section::new();
+    let stack_start = 0x80000000;
+    stack.start().set_const(stack_start);
+
+    stack = stack
+        // frame 0, in module1, a stdcall function with 3 arguments.
+        .append_repeated(0, 16) // frame space
+        .mark(&frame0_ebp)
+        .D32(0x6fa902e0) // saved %ebp
+        .D32(0x5000aa95) // return address, in module2::whine
+        // frame 1, in module2::whine.
+        .mark(&frame1_esp)
+        .D32(0xbaa0cb7a) // argument 3, popped when frame 0 returns
+        .D32(0xbdc92f9f) // argument 2
+        .D32(0x0b1d8442) // argument 1
+        .D32(&frame2_ebp) // saved %ebp
+        .D32(0xb1b90a15) // unused
+        .D32(0xf18e072d) // unused
+        .D32(0x2558c7f3) // saved %ebx
+        .D32(0x0365e25e) // unused
+        .D32(0x2a179e38) // return address; $T0 points here
+        // frame 2, in no module
+        .mark(&frame2_esp)
+        .append_repeated(0, 12) // empty space
+        .mark(&frame2_ebp)
+        .D32(0) // saved %ebp (stack end)
+        .D32(0); // saved %eip (stack end)
+
+    f.raw.set_register("eip", 0x40001004);
+    f.raw
+        .set_register("esp", stack.start().value().unwrap() as u32);
+    f.raw
+        .set_register("ebp", frame0_ebp.value().unwrap() as u32);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 3);
+    assert_eq!(s.frames[0].parameter_size, Some(0xc));
+
+    if let MinidumpRawContext::X86(ctx) = &s.frames[1].context.raw {
+        assert_eq!(ctx.eip, 0x5000aa95);
+        assert_eq!(ctx.esp, frame1_esp.value().unwrap() as u32);
+        assert_eq!(ctx.ebp, 0x6fa902e0);
+    } else {
+        unreachable!();
+    }
+
+    let f2 = &s.frames[2];
+    assert_eq!(f2.trust, FrameTrust::CallFrameInfo);
+    if let MinidumpRawContext::X86(ctx) = &f2.context.raw {
+        assert_eq!(ctx.eip, 0x2a179e38);
+        assert_eq!(ctx.esp, frame2_esp.value().unwrap() as u32);
+        assert_eq!(ctx.ebp, frame2_ebp.value().unwrap() as u32);
+        assert_eq!(ctx.ebx, 0x2558c7f3);
+    } else {
+        unreachable!();
+    }
+}
+
+// The grand callee's parameter size comes from its STACK WIN record even when
+// it only has a PUBLIC (with its own, bogus, parameter size), or no symbol.
+#[tokio::test]
+async fn test_stack_win_grand_callee_parameter_size_without_func() {
+    // A traditional frame, described by STACK WIN.
+    let frame0_stack_win = "STACK WIN 4 1000 100 0 0 c 4 0 0 1 \
+        $T0 $ebp = $eip $T0 4 + ^ = $ebp $T0 ^ = $esp $T0 8 + =\n";
+
+    check_stack_win_grand_callee_parameter_size(&format!(
+        "PUBLIC 1000 0 module1::wheedle\n{frame0_stack_win}"
+    ))
+    .await;
+    check_stack_win_grand_callee_parameter_size(frame0_stack_win).await;
+    // A PUBLIC that's truncated by an earlier FUNC.
+    check_stack_win_grand_callee_parameter_size(&format!(
+        "PUBLIC 800 0 module1::whatever\nFUNC 900 10 0 module1::other\n{frame0_stack_win}"
+    ))
+    .await;
+}
+
 #[tokio::test]
 async fn test_frame_pointer_overflow() {
     // Make sure we don't explode when trying frame pointer analysis on a value
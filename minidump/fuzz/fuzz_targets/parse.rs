@@ -3,10 +3,11 @@ use libfuzzer_sys::fuzz_target;
 
 use minidump::{
     MinidumpAssertion, MinidumpBreakpadInfo, MinidumpCrashpadInfo, MinidumpException,
-    MinidumpLinuxCpuInfo, MinidumpLinuxEnviron, MinidumpLinuxLsbRelease, MinidumpLinuxMaps,
-    MinidumpLinuxProcStatus, MinidumpMacCrashInfo, MinidumpMacBootargs, MinidumpMemory64List,
-    MinidumpMemoryInfoList, MinidumpMemoryList, MinidumpMiscInfo, MinidumpModuleList,
-    MinidumpSystemInfo, MinidumpThreadList, MinidumpThreadNames, MinidumpUnloadedModuleList,
+    MinidumpLinuxCmdLine, MinidumpLinuxCpuInfo, MinidumpLinuxEnviron, MinidumpLinuxLsbRelease,
+    MinidumpLinuxMaps, MinidumpLinuxProcStatus, MinidumpMacCrashInfo, MinidumpMacBootargs,
+    MinidumpMemory64List, MinidumpMemoryInfoList, MinidumpMemoryList, MinidumpMiscInfo,
+    MinidumpModuleList, MinidumpSystemInfo, MinidumpThreadList, MinidumpThreadNames,
+    MinidumpUnloadedModuleList,
 };
 
 fuzz_target!(|data: &[u8]| {
@@ -15,6 +16,7 @@ fuzz_target!(|data: &[u8]| {
         let _ = dump.get_stream::<MinidumpBreakpadInfo>();
         let _ = dump.get_stream::<MinidumpCrashpadInfo>();
         let _ = dump.get_stream::<MinidumpException>();
+        let _ = dump.get_stream::<MinidumpLinuxCmdLine>();
         let _ = dump.get_stream::<MinidumpLinuxCpuInfo>();
         let _ = dump.get_stream::<MinidumpLinuxEnviron>();
         let _ = dump.get_stream::<MinidumpLinuxLsbRelease>();
@@ -0,0 +1,21 @@
+//! Read every stream of arbitrary minidumps.
+//!
+//! The minidumps in `testdata` make a good starting corpus:
+//!
+//! ```sh
+//! mkdir -p corpus/read_minidump
+//! cp ../../testdata/*.dmp corpus/read_minidump
+//! cargo +nightly fuzz run read_minidump
+//! ```
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minidump::Minidump;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(dump) = Minidump::read(data) {
+        // This reads (and formats) every stream the minidump crate knows about
+        let _ = dump.to_json_value();
+    }
+});
@@ -0,0 +1,154 @@
+//! Crashes of managed runtimes that their crash reporters embed in a `Minidump`.
+//!
+//! When an app on a managed runtime (Java on Android, .NET) crashes in managed
+//! code, the native stacks of the minidump mostly show the runtime's exception
+//! handler. So crash reporters often add the managed exception to the minidump,
+//! as the text the runtime prints it as, in a vendor stream of their own.
+//!
+//! None of those stream types are standard, so an [`AuxiliaryStreams`] maps the
+//! stream types a crash reporter uses to the parsers of their formats, and reads
+//! them into [`AuxiliaryCrashInfo`]s.
+
+use std::collections::BTreeMap;
+use std::ops::Deref;
+
+use crate::Minidump;
+
+/// A crash described by an auxiliary stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuxiliaryCrashInfo {
+    /// The type of the stream this was read from.
+    pub stream_type: u32,
+    /// The format of the stream, e.g. "java".
+    pub kind: String,
+    /// The description of the crash, e.g. a Java exception's class and message
+    /// ("java.lang.IllegalStateException: no window").
+    pub message: Option<String>,
+    /// The managed frames of the crashing stack, innermost first.
+    pub frames: Vec<String>,
+    /// The contents of the stream (lossily converted to UTF-8), if they couldn't
+    /// be parsed. `message` and `frames` are empty then.
+    pub raw: Option<String>,
+}
+
+/// A parser of an auxiliary stream's format.
+///
+/// Returns the crash's message and frames, or `None` if `bytes` aren't in the format.
+pub type AuxiliaryStreamParser = fn(bytes: &[u8]) -> Option<(Option<String>, Vec<String>)>;
+
+/// Which stream types are auxiliary streams, and in what format.
+///
+/// Empty by default, as that depends on the crash reporter that wrote the minidump.
+#[derive(Debug, Clone, Default)]
+pub struct AuxiliaryStreams {
+    parsers: BTreeMap<u32, (String, AuxiliaryStreamParser)>,
+}
+
+impl AuxiliaryStreams {
+    /// Create an `AuxiliaryStreams` with no auxiliary streams.
+    pub fn new() -> AuxiliaryStreams {
+        AuxiliaryStreams::default()
+    }
+
+    /// Read streams of `stream_type` with `parser`, as crashes of `kind`.
+    ///
+    /// This replaces any parser registered for `stream_type` before.
+    pub fn register(&mut self, stream_type: u32, kind: &str, parser: AuxiliaryStreamParser) {
+        self.parsers.insert(stream_type, (kind.to_owned(), parser));
+    }
+
+    /// Read streams of `stream_type` as Java throwables, see [`parse_java_throwable`].
+    pub fn register_java(&mut self, stream_type: u32) {
+        self.register(stream_type, "java", parse_java_throwable);
+    }
+
+    /// Whether streams of `stream_type` are read as auxiliary streams.
+    pub fn contains(&self, stream_type: u32) -> bool {
+        self.parsers.contains_key(&stream_type)
+    }
+
+    /// Read the auxiliary streams `dump` has, by stream type.
+    pub fn read<'a, T>(&self, dump: &'a Minidump<'a, T>) -> Vec<AuxiliaryCrashInfo>
+    where
+        T: Deref<Target = [u8]> + 'a,
+    {
+        self.parsers
+            .iter()
+            .filter_map(|(&stream_type, (kind, parser))| {
+                let bytes = dump.get_raw_stream(stream_type).ok()?;
+                let mut crash = AuxiliaryCrashInfo {
+                    stream_type,
+                    kind: kind.clone(),
+                    message: None,
+                    frames: Vec::new(),
+                    raw: None,
+                };
+                match parser(bytes) {
+                    Some((message, frames)) => {
+                        crash.message = message;
+                        crash.frames = frames;
+                    }
+                    None => crash.raw = Some(String::from_utf8_lossy(bytes).into_owned()),
+                }
+                Some(crash)
+            })
+            .collect()
+    }
+}
+
+/// Parse the text Java prints a `Throwable` as (with `printStackTrace`):
+///
+/// ```text
+/// java.lang.IllegalStateException: no window
+///     at com.example.app.MainActivity.onCreate(MainActivity.java:42)
+///     at android.app.Activity.performCreate(Activity.java:8000)
+/// Caused by: java.lang.NullPointerException
+///     at com.example.app.Window.get(Window.java:7)
+///     ... 2 more
+/// ```
+///
+/// The message is the first line (or the lines before the first frame, for
+/// messages with newlines). The frames are what follows each "at ", and the
+/// "Caused by:", "Suppressed:" and "... N more" lines are kept as frames as
+/// they are, so the causes can be told apart.
+pub fn parse_java_throwable(bytes: &[u8]) -> Option<(Option<String>, Vec<String>)> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    // Writers may include the C string's terminator.
+    let text = text.trim_end_matches('\0');
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+
+    let first = lines.next()?;
+    let class = first.split(": ").next().unwrap_or(first);
+    if !is_java_class_name(class) {
+        return None;
+    }
+    let mut message = vec![first];
+    let mut frames = Vec::new();
+    for line in lines {
+        if let Some(frame) = line.strip_prefix("at ") {
+            frames.push(frame.to_owned());
+        } else if line.starts_with("Caused by: ")
+            || line.starts_with("Suppressed: ")
+            || (line.starts_with("... ") && line.ends_with(" more"))
+        {
+            frames.push(line.to_owned());
+        } else if frames.is_empty() {
+            message.push(line);
+        } else {
+            return None;
+        }
+    }
+    Some((Some(message.join("\n")), frames))
+}
+
+/// Whether `name` looks like the fully qualified name of a Java class.
+fn is_java_class_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.split('.').all(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+                && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        })
+}
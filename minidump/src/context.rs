@@ -4,6 +4,7 @@
 //! CPU contexts.
 
 use num_traits::FromPrimitive;
+use scroll::ctx::SizeWith;
 use scroll::Pread;
 use std::collections::HashSet;
 use std::fmt;
@@ -13,6 +14,7 @@ use std::mem;
 use tracing::warn;
 
 use crate::iostuff::*;
+use crate::system_info::{Cpu, Os};
 use crate::{MinidumpMiscInfo, MinidumpSystemInfo};
 use minidump_common::format as md;
 use minidump_common::format::ContextFlagsCpu;
@@ -829,48 +831,61 @@ impl CpuContext for md::CONTEXT_MIPS {
     type Register = u64;
 
     const REGISTERS: &'static [&'static str] = &[
-        "gp", "sp", "fp", "ra", "pc", "s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7",
+        "zero", "at", "v0", "v1", "a0", "a1", "a2", "a3", "t0", "t1", "t2", "t3", "t4", "t5", "t6",
+        "t7", "s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7", "t8", "t9", "k0", "k1", "gp", "sp",
+        "fp", "ra", "pc",
     ];
 
+    fn memoize_register(&self, reg: &str) -> Option<&'static str> {
+        match reg {
+            "s8" => Some("fp"),
+            _ => default_memoize_register(Self::REGISTERS, reg),
+        }
+    }
+
+    fn register_is_valid(&self, reg: &str, valid: &MinidumpContextValidity) -> bool {
+        if let MinidumpContextValidity::Some(ref which) = valid {
+            match reg {
+                "s8" | "fp" => which.contains("s8") || which.contains("fp"),
+                _ => which.contains(reg),
+            }
+        } else {
+            self.memoize_register(reg).is_some()
+        }
+    }
+
     fn get_register_always(&self, reg: &str) -> Self::Register {
         match reg {
-            "gp" => self.iregs[md::MipsRegisterNumbers::GlobalPointer as usize],
-            "sp" => self.iregs[md::MipsRegisterNumbers::StackPointer as usize],
-            "fp" => self.iregs[md::MipsRegisterNumbers::FramePointer as usize],
-            "ra" => self.iregs[md::MipsRegisterNumbers::ReturnAddress as usize],
             "pc" => self.epc,
-            "s0" => self.iregs[md::MipsRegisterNumbers::S0 as usize],
-            "s1" => self.iregs[md::MipsRegisterNumbers::S1 as usize],
-            "s2" => self.iregs[md::MipsRegisterNumbers::S2 as usize],
-            "s3" => self.iregs[md::MipsRegisterNumbers::S3 as usize],
-            "s4" => self.iregs[md::MipsRegisterNumbers::S4 as usize],
-            "s5" => self.iregs[md::MipsRegisterNumbers::S5 as usize],
-            "s6" => self.iregs[md::MipsRegisterNumbers::S6 as usize],
-            "s7" => self.iregs[md::MipsRegisterNumbers::S7 as usize],
-            _ => unreachable!("Invalid mips register! {}", reg),
+            "s8" => self.iregs[md::MipsRegisterNumbers::FramePointer as usize],
+            // The general purpose registers are listed first in `REGISTERS`, in `iregs` order
+            _ => match Self::REGISTERS[..32].iter().position(|&name| name == reg) {
+                Some(idx) => self.iregs[idx],
+                None => unreachable!("Invalid mips register! {}", reg),
+            },
         }
     }
 
     fn set_register(&mut self, reg: &str, val: Self::Register) -> Option<()> {
         match reg {
-            "gp" => self.iregs[md::MipsRegisterNumbers::GlobalPointer as usize] = val,
-            "sp" => self.iregs[md::MipsRegisterNumbers::StackPointer as usize] = val,
-            "fp" => self.iregs[md::MipsRegisterNumbers::FramePointer as usize] = val,
-            "ra" => self.iregs[md::MipsRegisterNumbers::ReturnAddress as usize] = val,
             "pc" => self.epc = val,
-            "s0" => self.iregs[md::MipsRegisterNumbers::S0 as usize] = val,
-            "s1" => self.iregs[md::MipsRegisterNumbers::S1 as usize] = val,
-            "s2" => self.iregs[md::MipsRegisterNumbers::S2 as usize] = val,
-            "s3" => self.iregs[md::MipsRegisterNumbers::S3 as usize] = val,
-            "s4" => self.iregs[md::MipsRegisterNumbers::S4 as usize] = val,
-            "s5" => self.iregs[md::MipsRegisterNumbers::S5 as usize] = val,
-            "s6" => self.iregs[md::MipsRegisterNumbers::S6 as usize] = val,
-            "s7" => self.iregs[md::MipsRegisterNumbers::S7 as usize] = val,
-            _ => return None,
+            "s8" => self.iregs[md::MipsRegisterNumbers::FramePointer as usize] = val,
+            _ => {
+                let idx = Self::REGISTERS[..32].iter().position(|&name| name == reg)?;
+                self.iregs[idx] = val;
+            }
         }
         Some(())
     }
 
+    fn format_register(&self, reg: &str) -> String {
+        if self.is_mips64() {
+            format!("0x{:016x}", self.get_register_always(reg))
+        } else {
+            format!("0x{:08x}", self.get_register_always(reg) as u32)
+        }
+    }
+
     fn stack_pointer_register_name(&self) -> &'static str {
         "sp"
     }
@@ -988,6 +1003,37 @@ impl CpuContext for md::CONTEXT_SPARC {
     }
 }
 
+/// The XMM registers in the FXSAVE area `fxsave` of a context.
+fn xmm_registers(fxsave: &[u8], count: usize, endian: scroll::Endian) -> Vec<u128> {
+    const XMM_OFFSET: usize = 160;
+    (0..count)
+        .map(|i| {
+            fxsave
+                .pread_with(XMM_OFFSET + i * 16, endian)
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// The usual offset (in the non-compacted XSAVE layout) and size of the state
+/// of the XSAVE feature with index `idx`, for the features we know of.
+///
+/// Features that are only saved by XSAVES (like CET) have no usual offset.
+fn default_xsave_feature(idx: usize) -> Option<(Option<usize>, usize)> {
+    use md::XstateFeatureIndex::*;
+    match md::XstateFeatureIndex::from_index(idx)? {
+        GSSE_AND_AVX => Some((Some(576), 256)),
+        MPX_BNDREGS => Some((Some(960), 64)),
+        MPX_BNDCSR => Some((Some(1024), 64)),
+        AVX512_KMASK => Some((Some(1088), 64)),
+        AVX512_ZMM_H => Some((Some(1152), 512)),
+        ACK512_ZMM => Some((Some(1664), 1024)),
+        XSTATE_PKRU => Some((Some(2688), 8)),
+        XSTATE_CET_U => Some((None, 16)),
+        LEGACY_FLOATING_POINT | LEGACY_SSE | XSTATE_IPT | XSTATE_LWP => None,
+    }
+}
+
 /// Information about which registers are valid in a `MinidumpContext`.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum MinidumpContextValidity {
@@ -1017,18 +1063,169 @@ pub struct MinidumpContext {
     pub valid: MinidumpContextValidity,
 }
 
+/// The XSAVE state of an x86 or amd64 context, beyond the registers of the
+/// classic context.
+///
+/// See [`MinidumpContext::read_xstate`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContextXstate {
+    /// The XSAVE features whose state is saved (bit `i` is the feature with
+    /// [`XstateFeatureIndex`][md::XstateFeatureIndex] `i`).
+    pub features: u64,
+    /// The YMM registers (as `[low, high]` halves), if the AVX state is saved.
+    ///
+    /// Their low halves are the XMM registers of the classic context.
+    pub ymm: Option<Vec<[u128; 2]>>,
+    /// The AVX-512 opmask registers `[k0, k1, ..., k7]`, if their state is saved.
+    pub opmask: Option<[u64; 8]>,
+    /// The ZMM registers (as their 128-bit quarters, lowest first), if the
+    /// state of the upper halves of `zmm0..zmm15` is saved.
+    ///
+    /// Their low halves are the YMM registers. The parts whose state isn't
+    /// saved (like the YMM registers if the AVX state isn't) are in their
+    /// initial state, zero. 32-bit contexts only have `zmm0..zmm7`.
+    pub zmm: Option<Vec<[u128; 4]>>,
+    /// The shadow stack pointer (`PL3_SSP`), if the user-mode CET state is saved.
+    pub shadow_stack_pointer: Option<u64>,
+}
+
+impl ContextXstate {
+    /// Write a human-readable description of this `ContextXstate` to `f`.
+    ///
+    /// This is the format used by `minidump_dump`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        writeln!(f, "XSTATE")?;
+        writeln!(f, "  features      = {:#x}", self.features)?;
+        if let Some(ymm) = &self.ymm {
+            for (i, [low, high]) in ymm.iter().enumerate() {
+                let name = format!("ymm{i}");
+                writeln!(f, "  {name:<13} = 0x{high:032x}{low:032x}")?;
+            }
+        }
+        if let Some(opmask) = &self.opmask {
+            for (i, k) in opmask.iter().enumerate() {
+                let name = format!("k{i}");
+                writeln!(f, "  {name:<13} = {k:#018x}")?;
+            }
+        }
+        if let Some(zmm) = &self.zmm {
+            for (i, [q0, q1, q2, q3]) in zmm.iter().enumerate() {
+                let name = format!("zmm{i}");
+                writeln!(f, "  {name:<13} = 0x{q3:032x}{q2:032x}{q1:032x}{q0:032x}")?;
+            }
+        }
+        if let Some(ssp) = self.shadow_stack_pointer {
+            writeln!(f, "  ssp           = {ssp:#018x}")?;
+        }
+        writeln!(f)
+    }
+}
+
+/// The floating point (VFP/NEON) registers of an ARM or ARM64 context.
+///
+/// See [`MinidumpContext::arm_float_registers`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArmFloatRegisters<'a> {
+    /// The VFP registers of an ARM context: the status and control register,
+    /// and `[d0, d1, ..., d31]` (which are also `q0..q15` and `s0..s31`).
+    Vfp { fpscr: u64, d: &'a [u64; 32] },
+    /// The NEON registers of an ARM64 context (of either layout): the status and
+    /// control registers, and `[q0, q1, ..., q31]` (whose low halves are
+    /// `d0..d31`).
+    Neon {
+        fpsr: u32,
+        fpcr: u32,
+        q: &'a [u128; 32],
+    },
+}
+
 /// Errors encountered while reading a `MinidumpContext`.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
 pub enum ContextError {
     /// Failed to read data.
+    #[error("failed to read the context")]
     ReadFailure,
     /// Encountered an unknown CPU context.
+    #[error("unknown CPU context")]
     UnknownCpuContext,
 }
 
 //======================================================
 // Implementations
 
+impl MinidumpRawContext {
+    /// The CPU this context is for.
+    ///
+    /// This is usually the minidump's CPU, but not for the threads of emulated
+    /// code, see [`CpuEmulation`][crate::system_info::CpuEmulation].
+    pub fn cpu(&self) -> Cpu {
+        match self {
+            MinidumpRawContext::X86(_) => Cpu::X86,
+            MinidumpRawContext::Ppc(_) => Cpu::Ppc,
+            MinidumpRawContext::Ppc64(_) => Cpu::Ppc64,
+            MinidumpRawContext::Amd64(_) => Cpu::X86_64,
+            MinidumpRawContext::Sparc(_) => Cpu::Sparc,
+            MinidumpRawContext::Arm(_) => Cpu::Arm,
+            MinidumpRawContext::Arm64(_) | MinidumpRawContext::OldArm64(_) => Cpu::Arm64,
+            MinidumpRawContext::Mips(ctx) => {
+                if ContextFlagsCpu::from_flags(ctx.context_flags) == ContextFlagsCpu::CONTEXT_MIPS64
+                {
+                    Cpu::Mips64
+                } else {
+                    Cpu::Mips
+                }
+            }
+        }
+    }
+}
+
+fn read_x86_context(bytes: &[u8], endian: scroll::Endian) -> Result<MinidumpContext, ContextError> {
+    let ctx: md::CONTEXT_X86 = bytes
+        .pread_with(0, endian)
+        .or(Err(ContextError::ReadFailure))?;
+
+    let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
+    if flags == ContextFlagsCpu::CONTEXT_X86 {
+        // Any XSTATE follows the classic context, see `read_xstate`
+        Ok(MinidumpContext::from_raw(MinidumpRawContext::X86(ctx)))
+    } else {
+        Err(ContextError::ReadFailure)
+    }
+}
+
+fn read_amd64_context(
+    bytes: &[u8],
+    endian: scroll::Endian,
+) -> Result<MinidumpContext, ContextError> {
+    let ctx: md::CONTEXT_AMD64 = bytes
+        .pread_with(0, endian)
+        .or(Err(ContextError::ReadFailure))?;
+
+    let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
+    if flags == ContextFlagsCpu::CONTEXT_AMD64 {
+        // Any XSTATE follows the classic context, see `read_xstate`
+        Ok(MinidumpContext::from_raw(MinidumpRawContext::Amd64(ctx)))
+    } else {
+        Err(ContextError::ReadFailure)
+    }
+}
+
+fn read_arm64_context(
+    bytes: &[u8],
+    endian: scroll::Endian,
+) -> Result<MinidumpContext, ContextError> {
+    let ctx: md::CONTEXT_ARM64 = bytes
+        .pread_with(0, endian)
+        .or(Err(ContextError::ReadFailure))?;
+
+    let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
+    if flags == ContextFlagsCpu::CONTEXT_ARM64 {
+        Ok(MinidumpContext::from_raw(MinidumpRawContext::Arm64(ctx)))
+    } else {
+        Err(ContextError::ReadFailure)
+    }
+}
+
 impl MinidumpContext {
     /// Return a MinidumpContext given a `MinidumpRawContext`.
     pub fn from_raw(raw: MinidumpRawContext) -> MinidumpContext {
@@ -1058,36 +1255,15 @@ impl MinidumpContext {
         match md::ProcessorArchitecture::from_u16(system_info.raw.processor_architecture) {
             Some(PROCESSOR_ARCHITECTURE_INTEL) | Some(PROCESSOR_ARCHITECTURE_IA32_ON_WIN64) => {
                 // Not 100% sure IA32_ON_WIN64 is this format, but let's assume so?
-                let ctx: md::CONTEXT_X86 = bytes
-                    .gread_with(&mut offset, endian)
-                    .or(Err(ContextError::ReadFailure))?;
-
-                let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
-                if flags == ContextFlagsCpu::CONTEXT_X86 {
-                    if ctx.context_flags & md::CONTEXT_HAS_XSTATE != 0 {
-                        // FIXME: uses MISC_INFO_5 to parse out extra sections here
-                        warn!("Cpu context has extra XSTATE that is being ignored");
-                    }
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::X86(ctx)))
-                } else {
-                    Err(ContextError::ReadFailure)
-                }
+                read_x86_context(bytes, endian)
             }
             Some(PROCESSOR_ARCHITECTURE_AMD64) => {
-                let ctx: md::CONTEXT_AMD64 = bytes
-                    .gread_with(&mut offset, endian)
-                    .or(Err(ContextError::ReadFailure))?;
-
-                let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
-                if flags == ContextFlagsCpu::CONTEXT_AMD64 {
-                    if ctx.context_flags & md::CONTEXT_HAS_XSTATE != 0 {
-                        // FIXME: uses MISC_INFO_5 to parse out extra sections here
-                        warn!("Cpu context has extra XSTATE that is being ignored");
-                    }
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::Amd64(ctx)))
-                } else {
-                    Err(ContextError::ReadFailure)
+                let context = read_amd64_context(bytes, endian);
+                if context.is_err() && system_info.os == Os::Windows {
+                    // A native thread of an x64 process on ARM64 (see `CpuEmulation`)
+                    return read_arm64_context(bytes, endian).or(context);
                 }
+                context
             }
             Some(PROCESSOR_ARCHITECTURE_PPC) => {
                 let ctx: md::CONTEXT_PPC = bytes
@@ -1138,16 +1314,15 @@ impl MinidumpContext {
                 }
             }
             Some(PROCESSOR_ARCHITECTURE_ARM64) => {
-                let ctx: md::CONTEXT_ARM64 = bytes
-                    .gread_with(&mut offset, endian)
-                    .or(Err(ContextError::ReadFailure))?;
-
-                let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
-                if flags == ContextFlagsCpu::CONTEXT_ARM64 {
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::Arm64(ctx)))
-                } else {
-                    Err(ContextError::ReadFailure)
+                let context = read_arm64_context(bytes, endian);
+                if context.is_err() && system_info.os == Os::Windows {
+                    // A thread of emulated x64 (ARM64EC) or x86 (CHPE) code, which
+                    // have the context of the emulated CPU (see `CpuEmulation`).
+                    return read_amd64_context(bytes, endian)
+                        .or_else(|_| read_x86_context(bytes, endian))
+                        .or(context);
                 }
+                context
             }
             Some(PROCESSOR_ARCHITECTURE_ARM64_OLD) => {
                 let ctx: md::CONTEXT_ARM64_OLD = bytes
@@ -1173,10 +1348,179 @@ impl MinidumpContext {
                     Err(ContextError::ReadFailure)
                 }
             }
+            Some(PROCESSOR_ARCHITECTURE_MIPS64) => {
+                let ctx: md::CONTEXT_MIPS = bytes
+                    .gread_with(&mut offset, endian)
+                    .or(Err(ContextError::ReadFailure))?;
+
+                let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
+                if flags == ContextFlagsCpu::CONTEXT_MIPS64 {
+                    Ok(MinidumpContext::from_raw(MinidumpRawContext::Mips(ctx)))
+                } else {
+                    Err(ContextError::ReadFailure)
+                }
+            }
             _ => Err(ContextError::UnknownCpuContext),
         }
     }
 
+    /// Read the XSAVE state of an x86 or amd64 context from `bytes`.
+    ///
+    /// This is the state that [`MinidumpContext::read`] doesn't cover (e.g. the
+    /// upper halves of the YMM and ZMM registers, or the shadow stack pointer),
+    /// which is only present if the context's `context_flags` has
+    /// [`CONTEXT_HAS_XSTATE`][md::CONTEXT_HAS_XSTATE] set. `misc` is used for
+    /// the layout of the XSAVE area (the offsets of the features, or their sizes
+    /// in the compacted layout), with the usual layout as a fallback.
+    ///
+    /// Returns `None` for other CPUs, contexts without XSTATE, and if the
+    /// extended context is malformed. The classic context is read the same
+    /// either way.
+    pub fn read_xstate(
+        bytes: &[u8],
+        endian: scroll::Endian,
+        system_info: &MinidumpSystemInfo,
+        misc: Option<&MinidumpMiscInfo>,
+    ) -> Option<ContextXstate> {
+        const LEGACY_AREA_SIZE: usize = 512;
+        const COMPACTED: u64 = 1 << 63;
+
+        let context = Self::read(bytes, endian, system_info, misc).ok()?;
+        let (context_flags, context_size, xmm) = match &context.raw {
+            MinidumpRawContext::X86(ctx) => (
+                ctx.context_flags,
+                md::CONTEXT_X86::size_with(&endian),
+                xmm_registers(&ctx.extended_registers, 8, endian),
+            ),
+            MinidumpRawContext::Amd64(ctx) => (
+                ctx.context_flags,
+                md::CONTEXT_AMD64::size_with(&endian),
+                xmm_registers(&ctx.float_save, 16, endian),
+            ),
+            _ => return None,
+        };
+        if context_flags & md::CONTEXT_HAS_XSTATE == 0 {
+            return None;
+        }
+
+        let context_ex: md::CONTEXT_EX = bytes.pread_with(context_size, endian).ok()?;
+        if i64::from(context_ex.legacy.offset) != -(context_size as i64) {
+            warn!("extended context doesn't follow the classic context, ignoring its XSTATE");
+            return None;
+        }
+        // The XSAVE area comes after the classic context
+        let xstate_start = context_size as i64 + i64::from(context_ex.xstate.offset);
+        let xstate_end = xstate_start + i64::from(context_ex.xstate.length);
+        let Some(xstate) = (xstate_start >= context_size as i64)
+            .then(|| bytes.get(xstate_start as usize..xstate_end as usize))
+            .flatten()
+        else {
+            warn!("XSAVE area of the context is out of bounds, ignoring it");
+            return None;
+        };
+        let header: md::XSAVE_AREA_HEADER = xstate.pread_with(0, endian).ok()?;
+
+        let xstate_data = misc.and_then(|misc| misc.raw.xstate_data());
+        let feature_size = |idx: usize| {
+            xstate_data
+                .map(|xstate_data| xstate_data.features[idx].size as usize)
+                .filter(|&size| size != 0)
+                .or_else(|| default_xsave_feature(idx).map(|(_, size)| size))
+        };
+        // The offset of a feature from the start of `xstate` (the XSAVE header)
+        let feature_offset = |feature: md::XstateFeatureIndex| {
+            let idx = feature as usize;
+            if header.compaction_mask & COMPACTED != 0 {
+                // The features of the compaction mask follow the header in order
+                let mut offset = mem::size_of::<md::XSAVE_AREA_HEADER>();
+                for prev in 2..idx {
+                    if header.compaction_mask & (1 << prev) != 0 {
+                        offset += feature_size(prev)?;
+                    }
+                }
+                Some(offset)
+            } else {
+                let offset = xstate_data
+                    .map(|xstate_data| xstate_data.features[idx].offset as usize)
+                    .filter(|&offset| offset != 0)
+                    .or_else(|| default_xsave_feature(idx)?.0)?;
+                Some(offset.saturating_sub(LEGACY_AREA_SIZE))
+            }
+        };
+        let is_saved = |feature: md::XstateFeatureIndex| header.mask & (1 << feature as u64) != 0;
+        // The `len` bytes of the state of a feature
+        let feature_bytes = |feature: md::XstateFeatureIndex, len: usize| {
+            let bytes = feature_offset(feature)
+                .and_then(|offset| xstate.get(offset..offset.checked_add(len)?));
+            if bytes.is_none() {
+                warn!("{feature:?} state of the context is out of bounds, ignoring it");
+            }
+            bytes
+        };
+        let read_u128s = |feature: md::XstateFeatureIndex, count: usize| {
+            let bytes = feature_bytes(feature, count * 16)?;
+            let values = bytes.chunks_exact(16);
+            let values = values.map(|chunk| chunk.pread_with(0, endian).unwrap_or_default());
+            Some(values.collect::<Vec<u128>>())
+        };
+
+        let mut state = ContextXstate {
+            features: header.mask,
+            ..Default::default()
+        };
+        let ymm_upper = is_saved(md::XstateFeatureIndex::GSSE_AND_AVX)
+            .then(|| read_u128s(md::XstateFeatureIndex::GSSE_AND_AVX, xmm.len()))
+            .flatten();
+        if let Some(upper) = &ymm_upper {
+            state.ymm = Some(
+                xmm.iter()
+                    .zip(upper)
+                    .map(|(&low, &high)| [low, high])
+                    .collect(),
+            );
+        }
+
+        if is_saved(md::XstateFeatureIndex::AVX512_KMASK) {
+            if let Some(bytes) = feature_bytes(md::XstateFeatureIndex::AVX512_KMASK, 8 * 8) {
+                let mut opmask = [0; 8];
+                for (k, chunk) in opmask.iter_mut().zip(bytes.chunks_exact(8)) {
+                    *k = chunk.pread_with(0, endian).unwrap_or_default();
+                }
+                state.opmask = Some(opmask);
+            }
+        }
+
+        if is_saved(md::XstateFeatureIndex::AVX512_ZMM_H) {
+            // The upper halves of zmm0..zmm15
+            let zmm_upper = read_u128s(md::XstateFeatureIndex::AVX512_ZMM_H, xmm.len() * 2);
+            // zmm16..zmm31 (amd64 only)
+            let high_zmm = if xmm.len() < 16 {
+                Some(Vec::new())
+            } else if is_saved(md::XstateFeatureIndex::ACK512_ZMM) {
+                read_u128s(md::XstateFeatureIndex::ACK512_ZMM, 16 * 4)
+            } else {
+                Some(vec![0; 16 * 4])
+            };
+            if let (Some(zmm_upper), Some(high_zmm)) = (zmm_upper, high_zmm) {
+                let ymm_upper = ymm_upper.unwrap_or_else(|| vec![0; xmm.len()]);
+                let low_zmm = (0..xmm.len())
+                    .map(|i| [xmm[i], ymm_upper[i], zmm_upper[i * 2], zmm_upper[i * 2 + 1]]);
+                let high_zmm = high_zmm
+                    .chunks_exact(4)
+                    .map(|quarters| [quarters[0], quarters[1], quarters[2], quarters[3]]);
+                state.zmm = Some(low_zmm.chain(high_zmm).collect());
+            }
+        }
+
+        if is_saved(md::XstateFeatureIndex::XSTATE_CET_U) {
+            // The CET_U MSR, then PL3_SSP
+            if let Some(bytes) = feature_bytes(md::XstateFeatureIndex::XSTATE_CET_U, 16) {
+                state.shadow_stack_pointer = bytes.pread_with(8, endian).ok();
+            }
+        }
+        Some(state)
+    }
+
     pub fn get_instruction_pointer(&self) -> u64 {
         match self.raw {
             MinidumpRawContext::Amd64(ref ctx) => ctx.rip,
@@ -1217,6 +1561,46 @@ impl MinidumpContext {
         }
     }
 
+    /// The floating point registers of an ARM or ARM64 context.
+    ///
+    /// Returns `None` for other CPUs, and if the context's `context_flags` say
+    /// that the floating point registers weren't saved.
+    pub fn arm_float_registers(&self) -> Option<ArmFloatRegisters<'_>> {
+        match &self.raw {
+            MinidumpRawContext::Arm(ctx) => {
+                let flags = md::ContextFlagsArm::from_bits_truncate(ctx.context_flags);
+                flags
+                    .contains(md::ContextFlagsArm::CONTEXT_ARM_FLOATING_POINT)
+                    .then_some(ArmFloatRegisters::Vfp {
+                        fpscr: ctx.float_save.fpscr,
+                        d: &ctx.float_save.regs,
+                    })
+            }
+            MinidumpRawContext::Arm64(ctx) => {
+                let flags = md::ContextFlagsArm64::from_bits_truncate(ctx.context_flags);
+                flags
+                    .contains(md::ContextFlagsArm64::CONTEXT_ARM64_FLOATING_POINT)
+                    .then_some(ArmFloatRegisters::Neon {
+                        fpsr: ctx.fpsr,
+                        fpcr: ctx.fpcr,
+                        q: &ctx.float_regs,
+                    })
+            }
+            MinidumpRawContext::OldArm64(ctx) => {
+                // The flags are in the low half, like those of the other contexts
+                let flags = md::ContextFlagsArm64Old::from_bits_truncate(ctx.context_flags as u32);
+                flags
+                    .contains(md::ContextFlagsArm64Old::CONTEXT_ARM64_OLD_FLOATING_POINT)
+                    .then_some(ArmFloatRegisters::Neon {
+                        fpsr: ctx.fpsr,
+                        fpcr: ctx.fpcr,
+                        q: &ctx.float_regs,
+                    })
+            }
+            _ => None,
+        }
+    }
+
     pub fn get_register_always(&self, reg: &str) -> u64 {
         match self.raw {
             MinidumpRawContext::Amd64(ref ctx) => ctx.get_register_always(reg),
@@ -1405,11 +1789,36 @@ impl MinidumpContext {
                 write_bytes(f, &raw.extended_registers)?;
                 write!(f, "\n\n")?;
             }
-            MinidumpRawContext::Ppc(_) => {
-                unimplemented!();
+            MinidumpRawContext::Ppc(ref raw) => {
+                writeln!(f, "CONTEXT_PPC")?;
+                writeln!(f, "  context_flags              = {:#x}", raw.context_flags)?;
+                writeln!(f, "  srr0                       = {:#x}", raw.srr0)?;
+                writeln!(f, "  srr1                       = {:#x}", raw.srr1)?;
+                for (i, reg) in raw.gpr.iter().enumerate() {
+                    writeln!(f, "  gpr[{i:2}]                    = {reg:#x}")?;
+                }
+                writeln!(f, "  cr                         = {:#x}", raw.cr)?;
+                writeln!(f, "  xer                        = {:#x}", raw.xer)?;
+                writeln!(f, "  lr                         = {:#x}", raw.lr)?;
+                writeln!(f, "  ctr                        = {:#x}", raw.ctr)?;
+                writeln!(f, "  mq                         = {:#x}", raw.mq)?;
+                writeln!(f, "  vrsave                     = {:#x}", raw.vrsave)?;
+                write_ppc_save_areas(f, &raw.float_save, &raw.vector_save)?;
             }
-            MinidumpRawContext::Ppc64(_) => {
-                unimplemented!();
+            MinidumpRawContext::Ppc64(ref raw) => {
+                writeln!(f, "CONTEXT_PPC64")?;
+                writeln!(f, "  context_flags              = {:#x}", raw.context_flags)?;
+                writeln!(f, "  srr0                       = {:#x}", raw.srr0)?;
+                writeln!(f, "  srr1                       = {:#x}", raw.srr1)?;
+                for (i, reg) in raw.gpr.iter().enumerate() {
+                    writeln!(f, "  gpr[{i:2}]                    = {reg:#x}")?;
+                }
+                writeln!(f, "  cr                         = {:#x}", raw.cr)?;
+                writeln!(f, "  xer                        = {:#x}", raw.xer)?;
+                writeln!(f, "  lr                         = {:#x}", raw.lr)?;
+                writeln!(f, "  ctr                        = {:#x}", raw.ctr)?;
+                writeln!(f, "  vrsave                     = {:#x}", raw.vrsave)?;
+                write_ppc_save_areas(f, &raw.float_save, &raw.vector_save)?;
             }
             MinidumpRawContext::Amd64(ref raw) => {
                 write!(
@@ -1582,41 +1991,68 @@ impl MinidumpContext {
             MinidumpRawContext::Mips(ref raw) => {
                 write!(
                     f,
-                    r#"CONTEXT_MIPS
+                    r#"{}
   context_flags       = {:#x}
 "#,
+                    if raw.is_mips64() {
+                        "CONTEXT_MIPS64"
+                    } else {
+                        "CONTEXT_MIPS"
+                    },
                     raw.context_flags
                 )?;
-
-                use md::MipsRegisterNumbers;
-                const MIPS_REGS: &[MipsRegisterNumbers] = &[
-                    MipsRegisterNumbers::S0,
-                    MipsRegisterNumbers::S1,
-                    MipsRegisterNumbers::S2,
-                    MipsRegisterNumbers::S3,
-                    MipsRegisterNumbers::S4,
-                    MipsRegisterNumbers::S5,
-                    MipsRegisterNumbers::S6,
-                    MipsRegisterNumbers::S7,
-                    MipsRegisterNumbers::GlobalPointer,
-                    MipsRegisterNumbers::StackPointer,
-                    MipsRegisterNumbers::FramePointer,
-                    MipsRegisterNumbers::ReturnAddress,
-                ];
-                for reg in MIPS_REGS {
-                    writeln!(
-                        f,
-                        r#"  {}                = {:#x}"#,
-                        reg.name(),
-                        raw.iregs[*reg as usize]
-                    )?;
+                let gprs = &md::CONTEXT_MIPS::REGISTERS[..32];
+                for (name, reg) in gprs.iter().zip(raw.iregs.iter()) {
+                    writeln!(f, "  {name:<4}                = {reg:#x}")?;
+                }
+                writeln!(f, "  mdhi                = {:#x}", raw.mdhi)?;
+                writeln!(f, "  mdlo                = {:#x}", raw.mdlo)?;
+                for (i, (hi, lo)) in raw.hi.iter().zip(raw.lo.iter()).enumerate() {
+                    writeln!(f, "  hi[{i}]               = {hi:#x}")?;
+                    writeln!(f, "  lo[{i}]               = {lo:#x}")?;
+                }
+                writeln!(f, "  dsp_control         = {:#x}", raw.dsp_control)?;
+                writeln!(f, "  epc                 = {:#x}", raw.epc)?;
+                writeln!(f, "  badvaddr            = {:#x}", raw.badvaddr)?;
+                writeln!(f, "  status              = {:#x}", raw.status)?;
+                writeln!(f, "  cause               = {:#x}", raw.cause)?;
+                for (i, reg) in raw.float_save.regs.iter().enumerate() {
+                    writeln!(f, "  float_save.regs[{i:2}] = {reg:#x}")?;
                 }
+                writeln!(f, "  float_save.fpcsr    = {:#x}", raw.float_save.fpcsr)?;
+                writeln!(f, "  float_save.fir      = {:#x}", raw.float_save.fir)?;
             }
         }
         Ok(())
     }
 }
 
+/// Write the floating point and vector state shared by PPC and PPC64 contexts.
+fn write_ppc_save_areas<T: Write>(
+    f: &mut T,
+    float_save: &md::FLOATING_SAVE_AREA_PPC,
+    vector_save: &md::VECTOR_SAVE_AREA_PPC,
+) -> io::Result<()> {
+    for (i, reg) in float_save.fpregs.iter().enumerate() {
+        writeln!(f, "  float_save.fpregs[{i:2}]      = {reg:#x}")?;
+    }
+    writeln!(f, "  float_save.fpscr           = {:#x}", float_save.fpscr)?;
+    for (i, reg) in vector_save.save_vr.iter().enumerate() {
+        writeln!(f, "  vector_save.save_vr[{i:2}]    = {reg:#x}")?;
+    }
+    writeln!(
+        f,
+        "  vector_save.save_vscr      = {:#x}",
+        vector_save.save_vscr
+    )?;
+    writeln!(
+        f,
+        "  vector_save.save_vrvalid   = {:#x}",
+        vector_save.save_vrvalid
+    )?;
+    writeln!(f)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1646,4 +2082,119 @@ mod tests {
         assert_eq!(context.memoize_register("fp"), Some("fp"));
         assert_eq!(context.memoize_register("foo"), None);
     }
+
+    #[test]
+    fn test_mips_registers() {
+        let mut context = md::CONTEXT_MIPS {
+            context_flags: md::ContextFlagsCpu::CONTEXT_MIPS.bits(),
+            ..Default::default()
+        };
+        assert_eq!(context.memoize_register("s8"), Some("fp"));
+        assert_eq!(context.memoize_register("t9"), Some("t9"));
+        assert_eq!(context.memoize_register("foo"), None);
+
+        context.set_register("a0", 0xffff_ffff_8000_0000).unwrap();
+        context.set_register("s8", 0x1234).unwrap();
+        context.set_register("pc", 0x4000_0100).unwrap();
+        assert!(context.set_register("foo", 1).is_none());
+        assert_eq!(context.iregs[4], 0xffff_ffff_8000_0000);
+        assert_eq!(context.get_register_always("fp"), 0x1234);
+        assert_eq!(context.epc, 0x4000_0100);
+
+        // 32-bit contexts only print the meaningful half of the register
+        assert_eq!(context.format_register("a0"), "0x80000000");
+        context.context_flags = md::ContextFlagsCpu::CONTEXT_MIPS64.bits();
+        assert_eq!(context.format_register("a0"), "0xffffffff80000000");
+    }
+
+    #[test]
+    fn test_arm_float_registers() {
+        let mut arm = md::CONTEXT_ARM {
+            context_flags: md::ContextFlagsArm::CONTEXT_ARM_FULL.bits(),
+            ..Default::default()
+        };
+        arm.float_save.fpscr = 0x0300_0000;
+        arm.float_save.regs[31] = 0x3ff0_0000_0000_0000;
+        let context = MinidumpContext::from_raw(MinidumpRawContext::Arm(arm.clone()));
+        assert_eq!(
+            context.arm_float_registers(),
+            Some(ArmFloatRegisters::Vfp {
+                fpscr: 0x0300_0000,
+                d: &arm.float_save.regs,
+            })
+        );
+
+        let mut float_regs = [0; 32];
+        float_regs[0] = 1 << 64 | 2;
+        let arm64 = md::CONTEXT_ARM64 {
+            context_flags: md::ContextFlagsArm64::CONTEXT_ARM64_FLOATING_POINT.bits(),
+            fpsr: 0x10,
+            fpcr: 0x0040_0000,
+            float_regs,
+            ..Default::default()
+        };
+        let old_arm64 = md::CONTEXT_ARM64_OLD {
+            context_flags: md::ContextFlagsArm64Old::CONTEXT_ARM64_OLD_FULL
+                .bits()
+                .into(),
+            fpsr: 0x10,
+            fpcr: 0x0040_0000,
+            float_regs,
+            ..Default::default()
+        };
+        let neon = ArmFloatRegisters::Neon {
+            fpsr: 0x10,
+            fpcr: 0x0040_0000,
+            q: &float_regs,
+        };
+        let context = MinidumpContext::from_raw(MinidumpRawContext::Arm64(arm64));
+        assert_eq!(context.arm_float_registers(), Some(neon));
+        let context = MinidumpContext::from_raw(MinidumpRawContext::OldArm64(old_arm64));
+        assert_eq!(context.arm_float_registers(), Some(neon));
+
+        // Without the floating point registers
+        let arm64 = md::CONTEXT_ARM64 {
+            context_flags: md::ContextFlagsArm64::CONTEXT_ARM64_CONTROL.bits()
+                | md::ContextFlagsArm64::CONTEXT_ARM64_INTEGER.bits(),
+            float_regs,
+            ..Default::default()
+        };
+        let context = MinidumpContext::from_raw(MinidumpRawContext::Arm64(arm64));
+        assert_eq!(context.arm_float_registers(), None);
+        let context = MinidumpContext::from_raw(MinidumpRawContext::Amd64(Default::default()));
+        assert_eq!(context.arm_float_registers(), None);
+    }
+
+    #[test]
+    fn test_print_xstate() {
+        let xstate = ContextXstate {
+            features: 0b1110_0111,
+            ymm: Some(vec![[1, 2], [3, 4 << 64]]),
+            opmask: Some([0, 1, 2, 3, 4, 5, 6, 0xffff]),
+            zmm: Some(vec![[1, 2, 3, 4], [5, 6, 7, 8 << 64]]),
+            shadow_stack_pointer: Some(0x7ffe_0000_1ff8),
+        };
+        let mut out = Vec::new();
+        xstate.print(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "XSTATE
+  features      = 0xe7
+  ymm0          = 0x0000000000000000000000000000000200000000000000000000000000000001
+  ymm1          = 0x0000000000000004000000000000000000000000000000000000000000000003
+  k0            = 0x0000000000000000
+  k1            = 0x0000000000000001
+  k2            = 0x0000000000000002
+  k3            = 0x0000000000000003
+  k4            = 0x0000000000000004
+  k5            = 0x0000000000000005
+  k6            = 0x0000000000000006
+  k7            = 0x000000000000ffff
+  zmm0          = 0x00000000000000000000000000000004000000000000000000000000000000030000000000000000000000000000000200000000000000000000000000000001
+  zmm1          = 0x00000000000000080000000000000000000000000000000000000000000000070000000000000000000000000000000600000000000000000000000000000005
+  ssp           = 0x00007ffe00001ff8
+
+"
+        );
+    }
 }
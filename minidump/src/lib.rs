@@ -20,8 +20,14 @@
 //! header and stream directory. Individual streams are only parsed when they're
 //! requested.
 //!
+//! Minidumps too big to load (or map) all at once, like the full-memory dumps
+//! of big processes, can be read from any `Read + Seek` source with
+//! [`SeekableMinidump`][], which only reads the streams and the memory that are
+//! asked for.
+//!
 //! Although you may enumerate the streams in a minidump with methods like
-//! [`Minidump::all_streams`][], this is only really useful for debugging. Instead
+//! [`Minidump::all_streams`][] or [`Minidump::typed_streams`][], this is only
+//! really useful for debugging and inspection tools. Instead
 //! you should statically request streams with [`Minidump::get_stream`][].
 //! Depending on what analysis you're trying to perform, you may:
 //!
@@ -72,6 +78,7 @@
 //! * [`MinidumpBreakpadInfo`][]
 //! * [`MinidumpCrashpadInfo`][]
 //! * [`MinidumpException`][]
+//! * [`MinidumpLinuxCmdLine`][]
 //! * [`MinidumpLinuxCpuInfo`][]
 //! * [`MinidumpLinuxEnviron`][]
 //! * [`MinidumpLinuxLsbRelease`][]
@@ -414,5 +421,6 @@ pub use minidump_common::traits::Module;
 pub use crate::iostuff::Readable;
 pub use crate::minidump::*;
 
+pub mod auxiliary;
 pub mod strings;
 pub mod system_info;
@@ -7,10 +7,11 @@ use num_traits::FromPrimitive;
 use procfs_core::prelude::*;
 use procfs_core::process::{MMPermissions, MemoryMap, MemoryMaps};
 use scroll::ctx::{SizeWith, TryFromCtx};
-use scroll::{Pread, BE, LE};
+use scroll::{Pread, Pwrite, BE, LE};
 use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::convert::TryInto;
 use std::fmt;
 use std::fs::File;
@@ -22,20 +23,26 @@ use std::mem;
 use std::ops::Deref;
 use std::path::Path;
 use std::str;
+use std::sync::OnceLock;
 use std::time::{Duration, SystemTime};
 use tracing::warn;
 use uuid::Uuid;
 
 pub use crate::context::*;
+use crate::iostuff::Readable;
 use crate::strings::*;
 use crate::system_info::{Cpu, Os, PointerWidth};
 use minidump_common::errors::{self as err};
 use minidump_common::format::{self as md};
 use minidump_common::format::{CvSignature, MINIDUMP_STREAM_TYPE};
 use minidump_common::traits::{IntoRangeMapSafe, Module};
+use minidump_common::utils::basename;
 use range_map::{Range, RangeMap};
 use time::format_description::well_known::Rfc3339;
 
+#[cfg(feature = "json")]
+mod json;
+
 /// An index into the contents of a minidump.
 ///
 /// The `Minidump` struct represents the parsed header and
@@ -43,6 +50,12 @@ use time::format_description::well_known::Rfc3339;
 /// by calling the [`Minidump::read`][read] or
 /// [`Minidump::read_path`][read_path] methods.
 ///
+/// `T` is whatever holds the bytes of the minidump: a memory-mapped file
+/// ([`MmapMinidump`]), a byte slice borrowed from somewhere else
+/// ([`SliceMinidump`]), or an owned buffer like a `Vec<u8>` or `bytes::Bytes`.
+/// Everything else (streams, memory, processing) works the same for all of
+/// them, and never touches the filesystem.
+///
 /// # Examples
 ///
 /// ```
@@ -54,6 +67,19 @@ use time::format_description::well_known::Rfc3339;
 /// # }
 /// ```
 ///
+/// Reading a minidump that's already in memory, without copying it:
+///
+/// ```
+/// use minidump::{Minidump, MinidumpSystemInfo};
+///
+/// # fn foo() -> Result<(), minidump::Error> {
+/// # let bytes = std::fs::read("../testdata/test.dmp").unwrap();
+/// let dump = Minidump::read(&bytes[..])?;
+/// let system_info = dump.get_stream::<MinidumpSystemInfo>()?;
+/// # Ok(())
+/// # }
+/// ```
+///
 /// [read]: struct.Minidump.html#method.read
 /// [read_path]: struct.Minidump.html#method.read_path
 #[derive(Debug)]
@@ -65,7 +91,13 @@ where
     /// The raw minidump header from the file.
     pub header: md::MINIDUMP_HEADER,
     streams: BTreeMap<u32, (u32, md::MINIDUMP_DIRECTORY)>,
+    /// The whole stream directory, duplicates included.
+    directory: Vec<md::MINIDUMP_DIRECTORY>,
     system_info: Option<MinidumpSystemInfo>,
+    /// Found the first time they're asked for, as that means parsing the memory lists.
+    corruption_notes: OnceLock<Vec<CorruptionNote>>,
+    duplicate_streams: Vec<MinidumpDuplicateStream>,
+    limits: ReadLimits,
     /// The endianness of this minidump file.
     pub endian: scroll::Endian,
     _phantom: PhantomData<&'a [u8]>,
@@ -102,6 +134,16 @@ pub enum Error {
     CodeViewReadFailure,
     #[error("Uknown element type")]
     UknownElementType,
+    #[error("Thread not found")]
+    ThreadNotFound,
+    #[error("Read limit {limit} exceeded: {count} is more than {max}")]
+    LimitExceeded {
+        limit: ReadLimit,
+        count: u64,
+        max: u64,
+    },
+    #[error("Error reading string: {0}")]
+    StringReadFailure(StringError),
 }
 
 impl Error {
@@ -123,6 +165,131 @@ impl Error {
             Error::DataError => "DataError",
             Error::CodeViewReadFailure => "CodeViewReadFailure",
             Error::UknownElementType => "UnknownElementType",
+            Error::ThreadNotFound => "ThreadNotFound",
+            Error::LimitExceeded { .. } => "LimitExceeded",
+            Error::StringReadFailure(_) => "StringReadFailure",
+        }
+    }
+}
+
+/// Limits on what [`Minidump::read_with_limits`] accepts.
+///
+/// Minidumps can come from untrusted sources, so these bound how much a
+/// malformed one can make the stream readers allocate and process. By default
+/// there are no limits, so processes with any number of modules or threads
+/// can be read.
+///
+/// A stream that exceeds a limit fails to read with [`Error::LimitExceeded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadLimits {
+    /// The most modules a module list or unloaded module list can have.
+    pub max_modules: u64,
+    /// The most threads a thread list, thread info list or thread names
+    /// stream can have.
+    pub max_threads: u64,
+    /// The most regions a memory list, memory64 list or memory info list can have.
+    pub max_memory_regions: u64,
+    /// The most bytes a stream can have.
+    pub max_stream_size: u64,
+}
+
+impl Default for ReadLimits {
+    fn default() -> Self {
+        ReadLimits {
+            max_modules: u64::MAX,
+            max_threads: u64::MAX,
+            max_memory_regions: u64::MAX,
+            max_stream_size: u64::MAX,
+        }
+    }
+}
+
+/// What [`Minidump::sanitize`] removes from a minidump.
+///
+/// By default nothing is removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SanitizeOptions {
+    /// Zero the contents of the memory regions that aren't a thread's stack
+    /// (like the heap and the memory of full-memory dumps).
+    pub scrub_heap_memory: bool,
+    /// Zero the environment variables (the `LinuxEnviron` stream).
+    ///
+    /// The environment of Windows processes is in the heap memory.
+    pub erase_environment: bool,
+    /// Replace the paths of the modules (their code files, the PDB files of
+    /// their CodeView records, and the files in the `LinuxMaps` stream) with
+    /// just their file names.
+    ///
+    /// The file names are kept so the modules can still be symbolicated. The
+    /// directories are dropped entirely: even a hash of them could be undone
+    /// by guessing, since paths like `/home/<user>/` are easy to guess.
+    pub strip_module_paths: bool,
+}
+
+/// One of the [`ReadLimits`], as reported by [`Error::LimitExceeded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadLimit {
+    MaxModules,
+    MaxThreads,
+    MaxMemoryRegions,
+    MaxStreamSize,
+}
+
+impl fmt::Display for ReadLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ReadLimit::MaxModules => "max_modules",
+            ReadLimit::MaxThreads => "max_threads",
+            ReadLimit::MaxMemoryRegions => "max_memory_regions",
+            ReadLimit::MaxStreamSize => "max_stream_size",
+        };
+        f.write_str(name)
+    }
+}
+
+impl ReadLimits {
+    fn check(&self, limit: ReadLimit, count: u64) -> Result<(), Error> {
+        let max = match limit {
+            ReadLimit::MaxModules => self.max_modules,
+            ReadLimit::MaxThreads => self.max_threads,
+            ReadLimit::MaxMemoryRegions => self.max_memory_regions,
+            ReadLimit::MaxStreamSize => self.max_stream_size,
+        };
+        if count > max {
+            return Err(Error::LimitExceeded { limit, count, max });
+        }
+        Ok(())
+    }
+
+    /// Check the size of the stream `bytes`, and the number of entries in it.
+    ///
+    /// The number of entries is what the stream's header says, before any of
+    /// them are read.
+    fn check_stream(
+        &self,
+        stream_type: u32,
+        bytes: &[u8],
+        endian: scroll::Endian,
+    ) -> Result<(), Error> {
+        self.check(ReadLimit::MaxStreamSize, bytes.len() as u64)?;
+        let read_u32 = |offset| bytes.pread_with::<u32>(offset, endian).ok().map(u64::from);
+        let read_u64 = |offset| bytes.pread_with::<u64>(offset, endian).ok();
+        use MINIDUMP_STREAM_TYPE::*;
+        let (limit, count) = match MINIDUMP_STREAM_TYPE::from_u32(stream_type) {
+            Some(ModuleListStream) => (ReadLimit::MaxModules, read_u32(0)),
+            // These have a header of the header size, entry size, and number of entries.
+            Some(UnloadedModuleListStream) => (ReadLimit::MaxModules, read_u32(8)),
+            Some(ThreadListStream | ThreadNamesStream) => (ReadLimit::MaxThreads, read_u32(0)),
+            Some(ThreadInfoListStream) => (ReadLimit::MaxThreads, read_u32(8)),
+            Some(MemoryListStream) => (ReadLimit::MaxMemoryRegions, read_u32(0)),
+            Some(Memory64ListStream) => (ReadLimit::MaxMemoryRegions, read_u64(0)),
+            Some(MemoryInfoListStream) => (ReadLimit::MaxMemoryRegions, read_u64(8)),
+            _ => return Ok(()),
+        };
+        // A count that can't be read is the stream reader's problem.
+        match count {
+            Some(count) => self.check(limit, count),
+            None => Ok(()),
         }
     }
 }
@@ -283,6 +450,8 @@ pub struct MinidumpUnloadedModuleList {
     /// Map from address range to index in modules.
     /// Use `MinidumpUnloadedModuleList::modules_at_address`.
     modules_by_addr: Vec<(Range<u64>, usize)>,
+    /// The largest end of the ranges in `modules_by_addr` up to each index.
+    max_ends: Vec<u64>,
 }
 
 /// Contains object-specific information for a handle. Microsoft documentation
@@ -327,6 +496,68 @@ pub struct MinidumpHandleDataStream {
     pub handles: Vec<MinidumpHandleDescriptor>,
 }
 
+/// A dynamic function table of the process, which describes code that isn't
+/// part of any module (usually generated by a JIT).
+#[derive(Debug, Clone)]
+pub struct MinidumpFunctionTable<'a> {
+    /// The `MINIDUMP_FUNCTION_TABLE_DESCRIPTOR` direct from the minidump file.
+    pub raw: md::MINIDUMP_FUNCTION_TABLE_DESCRIPTOR,
+    /// The OS's own descriptor of the table (`DYNAMIC_FUNCTION_TABLE` on Windows).
+    pub native_descriptor: &'a [u8],
+    /// The entries of the table, `entry_size` bytes each.
+    entries: &'a [u8],
+    entry_size: usize,
+    endian: scroll::Endian,
+}
+
+/// A stream holding the dynamic function tables of the process (see
+/// `RtlAddFunctionTable` on Windows).
+///
+/// The tables tell where the functions of JITed code are, and where their
+/// unwind info is in the memory of the process.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpFunctionTableStream<'a> {
+    /// The tables, in the order they were present in the minidump.
+    pub tables: Vec<MinidumpFunctionTable<'a>>,
+}
+
+/// An operation on a handle, from the handle trace of a Windows minidump.
+#[derive(Debug, Clone)]
+pub struct MinidumpHandleOperation {
+    /// The `AVRF_HANDLE_OPERATION` direct from the minidump file.
+    pub raw: md::AVRF_HANDLE_OPERATION,
+}
+
+/// A stream holding the handle trace of the process, the recent operations on
+/// its handles (with the stacks they were done on), from a Windows minidump of
+/// a process with handle tracing enabled (e.g. by Application Verifier).
+///
+/// This is how a crash on a closed or invalid handle can be traced back to
+/// where the handle was closed.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpHandleOperationList {
+    /// The operations, in the order they were present in the minidump.
+    pub operations: Vec<MinidumpHandleOperation>,
+}
+
+/// An access token of the process, from a Windows minidump.
+#[derive(Debug, Clone)]
+pub struct MinidumpToken<'a> {
+    /// The `MINIDUMP_TOKEN_INFO_LIST` direct from the minidump file.
+    pub raw: md::MINIDUMP_TOKEN_INFO_LIST,
+    /// The data of the token, whose format isn't documented.
+    pub data: &'a [u8],
+    endian: scroll::Endian,
+}
+
+/// A stream holding the access tokens of the process, from a Windows minidump
+/// written with `MiniDumpWithTokenInformation`.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpTokenStream<'a> {
+    /// The tokens, in the order they were present in the minidump.
+    pub tokens: Vec<MinidumpToken<'a>>,
+}
+
 /// The state of a thread from the process when the minidump was written.
 #[derive(Debug)]
 pub struct MinidumpThread<'a> {
@@ -381,6 +612,8 @@ pub struct MinidumpSystemInfo {
     /// An x86 (not x64!) CPU vendor name that is stored in `raw` but in a way
     /// that's
     cpu_info: Option<String>,
+    /// The names of the features of the CPU, decoded from `raw`.
+    cpu_features: Vec<&'static str>,
 }
 
 /// A region of memory from the process that wrote the minidump.
@@ -457,6 +690,50 @@ pub struct MinidumpMiscInfo {
     pub raw: RawMiscInfo,
 }
 
+/// The time zone of the system that wrote the minidump, see
+/// [`MinidumpMiscInfo::time_zone`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpTimeZone {
+    /// Which time applied when the minidump was written (`TIME_ZONE_ID_*`):
+    /// 0 if unknown, 1 for standard time, 2 for daylight saving time.
+    pub id: u32,
+    /// The difference between UTC and local time in minutes (UTC = local time + bias).
+    pub bias: i32,
+    /// The name of the standard time, e.g. "Pacific Standard Time".
+    pub standard_name: String,
+    /// What's added to `bias` during standard time.
+    pub standard_bias: i32,
+    /// The name of the daylight saving time, e.g. "Pacific Daylight Time".
+    pub daylight_name: String,
+    /// What's added to `bias` during daylight saving time.
+    pub daylight_bias: i32,
+}
+
+/// The state of the system's memory (like how much of it was committed) when the
+/// minidump was written, from a Windows minidump.
+#[derive(Debug, Clone)]
+pub struct MinidumpSystemMemoryInfo {
+    /// The `MINIDUMP_SYSTEM_MEMORY_INFO_1` direct from the minidump.
+    pub raw: md::MINIDUMP_SYSTEM_MEMORY_INFO_1,
+}
+
+/// The raw process VM counters, whose fields depend on the revision.
+#[derive(Debug, Clone)]
+pub enum RawProcessVmCounters {
+    V1(md::MINIDUMP_PROCESS_VM_COUNTERS_1),
+    V2(md::MINIDUMP_PROCESS_VM_COUNTERS_2),
+}
+
+/// The memory counters of the process (like its working set and its commit
+/// charge) when the minidump was written, from a Windows minidump.
+///
+/// All sizes are in bytes, and `None` if the minidump doesn't have them.
+#[derive(Debug, Clone)]
+pub struct MinidumpProcessVmCounters {
+    /// The `MINIDUMP_PROCESS_VM_COUNTERS_*` direct from the minidump.
+    pub raw: RawProcessVmCounters,
+}
+
 /// Additional information about process state.
 ///
 /// MinidumpBreakpadInfo wraps MINIDUMP_BREAKPAD_INFO, which is an optional stream
@@ -483,6 +760,12 @@ pub struct MinidumpLinuxEnviron<'a> {
     data: &'a [u8],
 }
 
+/// The command line of the process, from /proc/self/cmdline
+#[derive(Default, Debug)]
+pub struct MinidumpLinuxCmdLine<'a> {
+    data: &'a [u8],
+}
+
 /// Interesting values extracted from /proc/cpuinfo
 #[derive(Default, Debug)]
 pub struct MinidumpLinuxCpuInfo<'a> {
@@ -501,6 +784,60 @@ pub struct MinidumpLinuxProcLimits<'a> {
     data: &'a [u8],
 }
 
+/// An object the dynamic linker loaded, from [`MinidumpLinuxDsoDebug`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpLinkMap {
+    /// The difference between the addresses the object was loaded at and the
+    /// addresses in its ELF file (`l_addr`).
+    pub load_bias: u64,
+    /// The path of the object (`l_name`), empty for the main executable.
+    /// `None` if it couldn't be read.
+    pub name: Option<String>,
+    /// The address of the object's dynamic section (`l_ld`).
+    pub dynamic: u64,
+}
+
+/// The dynamic linker's list of loaded objects (its `r_debug`), from a Linux
+/// minidump.
+///
+/// Unlike the module list, which is built from the memory mappings, this is
+/// the linker's own idea of what's loaded, so it can include objects that the
+/// module list is missing.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpLinuxDsoDebug {
+    /// The version of the `r_debug` protocol (`r_version`).
+    pub version: u32,
+    /// The address of the function debuggers set a breakpoint on to hear about
+    /// objects being loaded and unloaded (`r_brk`).
+    pub brk: u64,
+    /// The address the dynamic linker is loaded at (`r_ldbase`).
+    pub ldbase: u64,
+    /// The address of the main executable's dynamic section.
+    pub dynamic: u64,
+    /// The loaded objects, in the linker's order (the main executable first).
+    pub link_maps: Vec<MinidumpLinkMap>,
+}
+
+/// An entry of the auxiliary vector, from [`MinidumpLinuxAuxv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinidumpLinuxAuxvEntry {
+    /// The type of the entry (`a_type`), see [`MinidumpLinuxAuxvEntry::known_type`].
+    pub raw_type: u64,
+    /// The value of the entry (`a_val`), often an address.
+    pub value: u64,
+}
+
+/// The auxiliary vector the kernel passed to the process (/proc/self/auxv),
+/// from a Linux minidump.
+///
+/// It has the addresses of things the kernel set up for the process, like
+/// the vdso ([`MinidumpLinuxAuxv::vdso_base`]), and the CPU's features.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpLinuxAuxv {
+    /// The entries, in the order of the minidump, without the final `AT_NULL`.
+    pub entries: Vec<MinidumpLinuxAuxvEntry>,
+}
+
 /// The reason for a process crash.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum CrashReason {
@@ -522,6 +859,9 @@ pub enum CrashReason {
     MacBreakpointX86(err::ExceptionCodeMacBreakpointX86Type),
     MacResource(err::ExceptionCodeMacResourceType, u64, u64),
     MacGuard(err::ExceptionCodeMacGuardType, u64, u64),
+    /// An abnormal exit (EXC_CRASH) because of a signal, which was caused by
+    /// the original exception, if there was one.
+    MacCrash(Option<err::ExceptionCodeMac>, err::ExceptionCodeMacSignal),
 
     /// A Linux/Android error code with no other interesting metadata.
     LinuxGeneral(err::ExceptionCodeLinux, u32),
@@ -554,6 +894,273 @@ pub enum CrashReason {
     Unknown(u32, u32),
 }
 
+/// The kind of memory access that caused a crash.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MemoryAccessKind {
+    Read,
+    Write,
+    Execute,
+}
+
+impl MemoryAccessKind {
+    fn from_windows_parameter(param: u64) -> Option<MemoryAccessKind> {
+        // EXCEPTION_ACCESS_VIOLATION and EXCEPTION_IN_PAGE_ERROR share the same encoding
+        match err::ExceptionCodeWindowsAccessType::from_u64(param)? {
+            err::ExceptionCodeWindowsAccessType::READ => Some(MemoryAccessKind::Read),
+            err::ExceptionCodeWindowsAccessType::WRITE => Some(MemoryAccessKind::Write),
+            err::ExceptionCodeWindowsAccessType::EXEC => Some(MemoryAccessKind::Execute),
+        }
+    }
+
+    /// A lowercase name for this kind of access, e.g. "write".
+    pub fn name(&self) -> &'static str {
+        match self {
+            MemoryAccessKind::Read => "read",
+            MemoryAccessKind::Write => "write",
+            MemoryAccessKind::Execute => "execute",
+        }
+    }
+}
+
+/// Details about a crash, decoded from the parameters of the exception.
+///
+/// Exceptions carry an array of up to 15 parameters whose meaning depends on
+/// the exception code (`ExceptionInformation` in the Windows `EXCEPTION_RECORD`).
+/// [`CrashReason`] only captures the kind of crash, this captures the rest.
+///
+/// Exceptions with parameters we don't know how to decode are reported as
+/// [`CrashReasonDetail::Parameters`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CrashReasonDetail {
+    /// An invalid memory access (EXCEPTION_ACCESS_VIOLATION, SIGSEGV, SIGBUS, EXC_BAD_ACCESS).
+    ///
+    /// The kind of access is only known on Windows.
+    AccessViolation {
+        access: Option<MemoryAccessKind>,
+        address: u64,
+    },
+    /// A failure to page in memory (EXCEPTION_IN_PAGE_ERROR).
+    InPageError {
+        access: Option<MemoryAccessKind>,
+        address: u64,
+        /// The NTSTATUS explaining why the page couldn't be read.
+        nt_status: u64,
+    },
+    /// An exception thrown by the Visual C++ runtime (0xE06D7363).
+    CppException {
+        /// The address of the thrown object.
+        object: u64,
+        /// The address of the `_ThrowInfo` describing the type of the thrown object.
+        throw_info: u64,
+        /// The base address of the module containing `throw_info`, on 64-bit
+        /// systems where the pointers inside `_ThrowInfo` are image-relative.
+        image_base: Option<u64>,
+    },
+    /// An exception thrown from managed .NET code (0xE0434352).
+    ClrException {
+        /// The HRESULT of the managed exception.
+        hresult: u32,
+        /// The base address of the runtime module, if recorded.
+        runtime_base: Option<u64>,
+    },
+    /// A `__fastfail` invocation (STATUS_STACK_BUFFER_OVERRUN).
+    FastFail {
+        /// The FAST_FAIL code passed to `__fastfail`.
+        code: u64,
+    },
+    /// The raw parameters of an exception we don't know how to decode.
+    Parameters(Vec<u64>),
+}
+
+impl CrashReasonDetail {
+    /// Decode the parameters of an exception.
+    ///
+    /// Returns `None` if there is nothing to report beyond the [`CrashReason`].
+    pub fn from_exception(
+        raw: &md::MINIDUMP_EXCEPTION_STREAM,
+        os: Os,
+        cpu: Cpu,
+    ) -> Option<CrashReasonDetail> {
+        let record = &raw.exception_record;
+        let num_params =
+            (record.number_parameters as usize).min(record.exception_information.len());
+        let info = &record.exception_information[..num_params];
+
+        // Sometimes on 32-bit these values can be incorrectly sign-extended,
+        // so mask and zero-extend pointers here.
+        let pointer = |val: u64| match cpu.pointer_width() {
+            PointerWidth::Bits32 => val as u32 as u64,
+            _ => val,
+        };
+
+        let detail = match os {
+            Os::Windows => Self::from_windows_parameters(record.exception_code, info, pointer),
+            Os::Linux | Os::Android => {
+                match err::ExceptionCodeLinux::from_u32(record.exception_code) {
+                    Some(err::ExceptionCodeLinux::SIGSEGV | err::ExceptionCodeLinux::SIGBUS) => {
+                        Some(CrashReasonDetail::AccessViolation {
+                            access: None,
+                            address: pointer(record.exception_address),
+                        })
+                    }
+                    _ => None,
+                }
+            }
+            Os::MacOs | Os::Ios => match err::ExceptionCodeMac::from_u32(record.exception_code) {
+                Some(err::ExceptionCodeMac::EXC_BAD_ACCESS) => {
+                    Some(CrashReasonDetail::AccessViolation {
+                        access: None,
+                        address: pointer(record.exception_address),
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+
+        detail.or_else(|| (!info.is_empty()).then(|| CrashReasonDetail::Parameters(info.to_vec())))
+    }
+
+    fn from_windows_parameters(
+        exception_code: u32,
+        info: &[u64],
+        pointer: impl Fn(u64) -> u64,
+    ) -> Option<CrashReasonDetail> {
+        use err::ExceptionCodeWindows;
+
+        if let Some(code) = ExceptionCodeWindows::from_u32(exception_code) {
+            match code {
+                ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION if info.len() >= 2 => {
+                    return Some(CrashReasonDetail::AccessViolation {
+                        access: MemoryAccessKind::from_windows_parameter(info[0]),
+                        address: pointer(info[1]),
+                    });
+                }
+                ExceptionCodeWindows::EXCEPTION_IN_PAGE_ERROR if info.len() >= 3 => {
+                    return Some(CrashReasonDetail::InPageError {
+                        access: MemoryAccessKind::from_windows_parameter(info[0]),
+                        address: pointer(info[1]),
+                        // The status code is 32-bits wide, ignore the upper 32 bits
+                        nt_status: info[2] & 0xffff_ffff,
+                    });
+                }
+                ExceptionCodeWindows::UNHANDLED_CPP_EXCEPTION if info.len() >= 3 => {
+                    // info[0] is a magic number identifying the version of the
+                    // runtime, followed by the object and its _ThrowInfo. 64-bit
+                    // runtimes append the image base the _ThrowInfo is relative to.
+                    return Some(CrashReasonDetail::CppException {
+                        object: pointer(info[1]),
+                        throw_info: pointer(info[2]),
+                        image_base: info.get(3).copied(),
+                    });
+                }
+                ExceptionCodeWindows::MANAGED_EXCEPTION_CODE_V4 if !info.is_empty() => {
+                    return Some(CrashReasonDetail::ClrException {
+                        hresult: info[0] as u32,
+                        runtime_base: info.get(4).copied().map(pointer),
+                    });
+                }
+                _ => {}
+            }
+        } else if err::NtStatusWindows::from_u32(exception_code)
+            == Some(err::NtStatusWindows::STATUS_STACK_BUFFER_OVERRUN)
+            && !info.is_empty()
+        {
+            return Some(CrashReasonDetail::FastFail {
+                // The fast-fail code is 32-bits wide, ignore the upper 32 bits
+                code: info[0] & 0xffff_ffff,
+            });
+        }
+
+        None
+    }
+
+    /// A short name for the kind of detail, e.g. "access_violation".
+    pub fn name(&self) -> &'static str {
+        match self {
+            CrashReasonDetail::AccessViolation { .. } => "access_violation",
+            CrashReasonDetail::InPageError { .. } => "in_page_error",
+            CrashReasonDetail::CppException { .. } => "cpp_exception",
+            CrashReasonDetail::ClrException { .. } => "clr_exception",
+            CrashReasonDetail::FastFail { .. } => "fast_fail",
+            CrashReasonDetail::Parameters(..) => "parameters",
+        }
+    }
+}
+
+impl fmt::Display for CrashReasonDetail {
+    /// A human-readable description of the detail, e.g.
+    /// "Write access violation at 0x0000000000000010".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrashReasonDetail::AccessViolation { access, address } => {
+                match access {
+                    Some(MemoryAccessKind::Read) => write!(f, "Read access violation")?,
+                    Some(MemoryAccessKind::Write) => write!(f, "Write access violation")?,
+                    Some(MemoryAccessKind::Execute) => write!(f, "Execute access violation")?,
+                    None => write!(f, "Invalid memory access")?,
+                }
+                write!(f, " at {address:#018x}")
+            }
+            CrashReasonDetail::InPageError {
+                access,
+                address,
+                nt_status,
+            } => {
+                match access {
+                    Some(MemoryAccessKind::Read) => write!(f, "Read in-page error")?,
+                    Some(MemoryAccessKind::Write) => write!(f, "Write in-page error")?,
+                    Some(MemoryAccessKind::Execute) => write!(f, "Execute in-page error")?,
+                    None => write!(f, "In-page error")?,
+                }
+                write!(f, " at {address:#018x}: ")?;
+                match err::NtStatusWindows::from_u64(*nt_status) {
+                    Some(nt_status) => write!(f, "{nt_status:?}"),
+                    None => write!(f, "{nt_status:#010x}"),
+                }
+            }
+            CrashReasonDetail::CppException {
+                object,
+                throw_info,
+                image_base,
+            } => {
+                write!(
+                    f,
+                    "C++ exception object at {object:#018x}, throw info at {throw_info:#018x}"
+                )?;
+                if let Some(image_base) = image_base {
+                    write!(f, " (image base {image_base:#018x})")?;
+                }
+                Ok(())
+            }
+            CrashReasonDetail::ClrException {
+                hresult,
+                runtime_base,
+            } => {
+                write!(f, ".NET exception with HRESULT {hresult:#010x}")?;
+                if let Some(runtime_base) = runtime_base {
+                    write!(f, " (runtime at {runtime_base:#018x})")?;
+                }
+                Ok(())
+            }
+            CrashReasonDetail::FastFail { code } => {
+                write!(f, "Fast fail: ")?;
+                match err::FastFailCode::from_u64(*code) {
+                    Some(code) => write!(f, "{code:?}"),
+                    None => write!(f, "{code:#010x}"),
+                }
+            }
+            CrashReasonDetail::Parameters(params) => {
+                write!(f, "Exception parameters:")?;
+                for param in params {
+                    write!(f, " {param:#x}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 /// Information about the exception that caused the minidump to be generated.
 ///
 /// `MinidumpException` wraps `MINIDUMP_EXCEPTION_STREAM`, which contains information
@@ -579,6 +1186,101 @@ pub struct MinidumpException<'a> {
     endian: scroll::Endian,
 }
 
+/// A problem with the contents of a minidump that was worked around while reading it.
+///
+/// These generally mean the minidump is corrupt (or malicious), and that any
+/// analysis of it should be taken with a grain of salt. See
+/// [`Minidump::corruption_notes`][].
+#[derive(Clone, Debug, thiserror::Error, PartialEq, Eq)]
+pub enum CorruptionNote {
+    #[error("memory region at {base_address:#x} claims {claimed_size:#x} bytes but only {available_size:#x} are in the file, so it was truncated")]
+    MemoryTruncated {
+        base_address: u64,
+        claimed_size: u64,
+        available_size: u64,
+    },
+    #[error("memory region at {base_address:#x} ({size:#x} bytes) has no contents in the file, so it was ignored")]
+    MemoryOutOfBounds { base_address: u64, size: u64 },
+    #[error("memory region at {base_address:#x} is empty, so it was ignored")]
+    MemoryEmpty { base_address: u64 },
+    #[error("memory region at {base_address:#x} ({size:#x} bytes) extends past the end of the address space, so it was ignored")]
+    MemoryRangeOverflow { base_address: u64, size: u64 },
+    #[error("memory region at {base_address:#x} ({size:#x} bytes) overlaps the region at {used_base_address:#x} ({used_size:#x} bytes), so it was ignored")]
+    MemoryOverlap {
+        base_address: u64,
+        size: u64,
+        used_base_address: u64,
+        used_size: u64,
+    },
+    #[error("stream {index} of the stream directory has the same type as stream {used_index} ({}), so it was ignored", stream_type_name(*stream_type))]
+    DuplicateStream {
+        stream_type: u32,
+        index: u32,
+        used_index: u32,
+    },
+    #[error("stream {index} ({}) at {rva:#x} ({size:#x} bytes) is past the end of the file ({file_size:#x} bytes)", stream_type_name(*stream_type))]
+    StreamOutOfBounds {
+        stream_type: u32,
+        index: u32,
+        rva: u32,
+        size: u32,
+        file_size: u64,
+    },
+    #[error("stream {index} ({}) at {rva:#x} claims {claimed_size:#x} bytes but only {available_size:#x} are in the file", stream_type_name(*stream_type))]
+    StreamTruncated {
+        stream_type: u32,
+        index: u32,
+        rva: u32,
+        claimed_size: u32,
+        available_size: u64,
+    },
+    #[error("stream {index} ({}) at {rva:#x} ({size:#x} bytes) overlaps the header or the stream directory", stream_type_name(*stream_type))]
+    StreamOverlapsDirectory {
+        stream_type: u32,
+        index: u32,
+        rva: u32,
+        size: u32,
+    },
+    #[error("stream {index} ({}) at {rva:#x} ({size:#x} bytes) overlaps stream {other_index} at {other_rva:#x} ({other_size:#x} bytes)", stream_type_name(*stream_type))]
+    StreamOverlap {
+        stream_type: u32,
+        index: u32,
+        rva: u32,
+        size: u32,
+        other_index: u32,
+        other_rva: u32,
+        other_size: u32,
+    },
+    #[error("stream {index} ({}) at {rva:#x} ({size:#x} bytes) can't be read: {error}", stream_type_name(*stream_type))]
+    StreamUnreadable {
+        stream_type: u32,
+        index: u32,
+        rva: u32,
+        size: u32,
+        error: Error,
+    },
+}
+
+impl CorruptionNote {
+    /// Returns just the name of the note, as a more human-friendly version of
+    /// an error-code for logging.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CorruptionNote::MemoryTruncated { .. } => "MemoryTruncated",
+            CorruptionNote::MemoryOutOfBounds { .. } => "MemoryOutOfBounds",
+            CorruptionNote::MemoryEmpty { .. } => "MemoryEmpty",
+            CorruptionNote::MemoryRangeOverflow { .. } => "MemoryRangeOverflow",
+            CorruptionNote::MemoryOverlap { .. } => "MemoryOverlap",
+            CorruptionNote::DuplicateStream { .. } => "DuplicateStream",
+            CorruptionNote::StreamOutOfBounds { .. } => "StreamOutOfBounds",
+            CorruptionNote::StreamTruncated { .. } => "StreamTruncated",
+            CorruptionNote::StreamOverlapsDirectory { .. } => "StreamOverlapsDirectory",
+            CorruptionNote::StreamOverlap { .. } => "StreamOverlap",
+            CorruptionNote::StreamUnreadable { .. } => "StreamUnreadable",
+        }
+    }
+}
+
 /// A list of memory regions included in a minidump.
 /// This is the underlying generic type for [MinidumpMemoryList] and [MinidumpMemory64List].
 #[derive(Debug)]
@@ -587,6 +1289,8 @@ pub struct MinidumpMemoryListBase<'a, Descriptor> {
     regions: Vec<MinidumpMemoryBase<'a, Descriptor>>,
     /// Map from address range to index in regions. Use `MinidumpMemoryList::memory_at_address`.
     regions_by_addr: RangeMap<u64, usize>,
+    /// Problems found with the regions while reading them.
+    corruption_notes: Vec<CorruptionNote>,
 }
 
 /// A list of memory regions included in a minidump.
@@ -600,6 +1304,9 @@ pub type MinidumpMemory64List<'a> = MinidumpMemoryListBase<'a, md::MINIDUMP_MEMO
 pub enum UnifiedMemoryList<'a> {
     Memory(MinidumpMemoryList<'a>),
     Memory64(MinidumpMemory64List<'a>),
+    /// A minidump with both lists. Where they overlap, the [`MinidumpMemory64List`][]
+    /// wins.
+    Both(MinidumpMemoryList<'a>, MinidumpMemory64List<'a>),
 }
 impl<'a> Default for UnifiedMemoryList<'a> {
     fn default() -> Self {
@@ -638,7 +1345,7 @@ impl PartialEq for MinidumpAnnotation {
 }
 
 /// Additional Crashpad-specific information about a module carried within a minidump file.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MinidumpModuleCrashpadInfo {
     /// The raw crashpad module extension information.
     pub raw: md::MINIDUMP_MODULE_CRASHPAD_INFO,
@@ -650,7 +1357,7 @@ pub struct MinidumpModuleCrashpadInfo {
 }
 
 /// Additional Crashpad-specific information carried within a minidump file.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MinidumpCrashpadInfo {
     pub raw: md::MINIDUMP_CRASHPAD_INFO,
     pub simple_annotations: BTreeMap<String, String>,
@@ -700,58 +1407,272 @@ fn location_slice<'a>(
         .ok_or(Error::StreamReadFailure)
 }
 
-/// Read a u32 length-prefixed UTF-16 string from `bytes` at `offset`.
-fn read_string_utf16(offset: &mut usize, bytes: &[u8], endian: scroll::Endian) -> Option<String> {
-    let u: u32 = bytes.gread_with(offset, endian).ok()?;
-    let size = u as usize;
-    if size % 2 != 0 || (*offset + size) > bytes.len() {
-        return None;
+/// Lays out the minidump that [`Minidump::extract_thread`] returns.
+///
+/// Data is appended 4-byte aligned after a header, and the stream directory
+/// comes last, once all the streams are known.
+struct MinidumpWriter {
+    bytes: Vec<u8>,
+    endian: scroll::Endian,
+    directory: Vec<md::MINIDUMP_DIRECTORY>,
+}
+
+impl MinidumpWriter {
+    fn new(endian: scroll::Endian) -> MinidumpWriter {
+        let header_size = md::MINIDUMP_HEADER::size_with(&endian);
+        MinidumpWriter {
+            bytes: vec![0; header_size],
+            endian,
+            directory: Vec::new(),
+        }
+    }
+
+    fn encode<S>(&self, value: S) -> Vec<u8>
+    where
+        S: SizeWith<scroll::Endian>
+            + scroll::ctx::TryIntoCtx<scroll::Endian, Error = scroll::Error>,
+    {
+        let mut bytes = vec![0; S::size_with(&self.endian)];
+        bytes
+            .pwrite_with(value, 0, self.endian)
+            .expect("buffer is the size of the value");
+        bytes
+    }
+
+    /// Append `data`, returning where it is.
+    fn append(&mut self, data: &[u8]) -> md::MINIDUMP_LOCATION_DESCRIPTOR {
+        let aligned = (self.bytes.len() + 3) & !3;
+        self.bytes.resize(aligned, 0);
+        let rva = self.bytes.len() as u32;
+        self.bytes.extend_from_slice(data);
+        md::MINIDUMP_LOCATION_DESCRIPTOR {
+            data_size: data.len() as u32,
+            rva,
+        }
+    }
+
+    /// Copy the data at `loc` in `all`, returning where the copy is.
+    ///
+    /// Data that's missing from `all` is dropped (an empty location).
+    fn copy_location(
+        &mut self,
+        all: &[u8],
+        loc: &md::MINIDUMP_LOCATION_DESCRIPTOR,
+    ) -> md::MINIDUMP_LOCATION_DESCRIPTOR {
+        match location_slice(all, loc) {
+            Ok(data) if !data.is_empty() => self.append(data),
+            _ => md::MINIDUMP_LOCATION_DESCRIPTOR::default(),
+        }
+    }
+
+    /// Copy the `MINIDUMP_STRING` at `rva` in `all`, returning the rva of the copy.
+    fn copy_string(&mut self, all: &[u8], rva: md::RVA) -> md::RVA {
+        if rva == 0 {
+            return 0;
+        }
+        let Ok(length) = all.pread_with::<u32>(rva as usize, self.endian) else {
+            return 0;
+        };
+        // The length, the UTF-16 characters, and the terminating NUL.
+        let size = 4 + u64::from(length) + 2;
+        match clamped_slice(all, rva.into(), size) {
+            Some(data) => self.append(data).rva,
+            None => 0,
+        }
+    }
+
+    /// Append `string` as a `MINIDUMP_STRING`, returning its rva.
+    fn append_string(&mut self, string: &str) -> md::RVA {
+        let units: Vec<u16> = string.encode_utf16().collect();
+        let mut data = self.encode((units.len() * 2) as u32);
+        for unit in units.into_iter().chain([0]) {
+            data.extend(self.encode(unit));
+        }
+        self.append(&data).rva
+    }
+
+    /// Zero the (up to) `size` bytes at `rva`.
+    fn zero(&mut self, rva: u64, size: u64) {
+        let Ok(start): Result<usize, _> = rva.try_into() else {
+            return;
+        };
+        let Some(rest) = self.bytes.get_mut(start..) else {
+            return;
+        };
+        let len = size
+            .try_into()
+            .map_or(rest.len(), |size: usize| size.min(rest.len()));
+        rest[..len].fill(0);
+    }
+
+    /// Zero the `MINIDUMP_STRING` at `rva`.
+    fn zero_string(&mut self, rva: md::RVA) {
+        if rva == 0 {
+            return;
+        }
+        if let Ok(length) = self.bytes.pread_with::<u32>(rva as usize, self.endian) {
+            self.zero(rva.into(), 4 + u64::from(length) + 2);
+        }
+    }
+
+    fn add_stream(&mut self, stream_type: MINIDUMP_STREAM_TYPE, data: &[u8]) {
+        let location = self.append(data);
+        self.directory.push(md::MINIDUMP_DIRECTORY {
+            stream_type: stream_type as u32,
+            location,
+        });
     }
-    let encoding = match endian {
-        scroll::Endian::Little => encoding_rs::UTF_16LE,
-        scroll::Endian::Big => encoding_rs::UTF_16BE,
-    };
 
-    let s = encoding
-        .decode_without_bom_handling_and_without_replacement(&bytes[*offset..*offset + size])?;
-    *offset += size;
-    Some(s.into())
+    /// Add a stream that's a u32 count followed by `items`.
+    fn add_list_stream<S>(&mut self, stream_type: MINIDUMP_STREAM_TYPE, items: Vec<S>)
+    where
+        S: SizeWith<scroll::Endian>
+            + scroll::ctx::TryIntoCtx<scroll::Endian, Error = scroll::Error>,
+    {
+        let mut data = self.encode(items.len() as u32);
+        for item in items {
+            data.extend(self.encode(item));
+        }
+        self.add_stream(stream_type, &data);
+    }
+
+    /// Write the directory and the header, and return the minidump.
+    fn finish(mut self, original: &md::MINIDUMP_HEADER) -> Vec<u8> {
+        let directory = std::mem::take(&mut self.directory);
+        let mut data = Vec::new();
+        for entry in &directory {
+            data.extend(self.encode(entry.clone()));
+        }
+        let location = self.append(&data);
+        let header = md::MINIDUMP_HEADER {
+            signature: md::MINIDUMP_SIGNATURE,
+            version: original.version,
+            stream_count: directory.len() as u32,
+            stream_directory_rva: location.rva,
+            checksum: 0,
+            time_date_stamp: original.time_date_stamp,
+            flags: original.flags,
+        };
+        let header = self.encode(header);
+        self.bytes[..header.len()].copy_from_slice(&header);
+        self.bytes
+    }
 }
 
-#[inline]
-fn read_string_utf8_unterminated<'a>(
-    offset: &mut usize,
-    bytes: &'a [u8],
-    endian: scroll::Endian,
-) -> Option<&'a str> {
-    let length: u32 = bytes.gread_with(offset, endian).ok()?;
-    let slice = bytes.gread_with(offset, length as usize).ok()?;
-    std::str::from_utf8(slice).ok()
+/// Zero the CodeView record of `module` in `writer`, and append a copy with
+/// only the file name of the PDB path, returning where the copy is.
+///
+/// Only PDB records have a path, other records are left alone.
+fn stripped_codeview_path(
+    writer: &mut MinidumpWriter,
+    all: &[u8],
+    module: &MinidumpModule,
+) -> Option<md::MINIDUMP_LOCATION_DESCRIPTOR> {
+    // The size of the fields before the `pdb_file_name`.
+    let (header_size, pdb_file_name) = match &module.codeview_info {
+        Some(CodeView::Pdb70(raw)) => (24, &raw.pdb_file_name),
+        Some(CodeView::Pdb20(raw)) => (16, &raw.pdb_file_name),
+        _ => return None,
+    };
+    let pdb_file_name = string_from_bytes_nul(pdb_file_name)?;
+    let location = &module.raw.cv_record;
+    let mut data = location_slice(all, location)
+        .ok()?
+        .get(..header_size)?
+        .to_vec();
+    data.extend(basename(&pdb_file_name).bytes());
+    data.push(0);
+    writer.zero(location.rva.into(), location.data_size.into());
+    Some(writer.append(&data))
+}
+
+/// The contents of a `LinuxMaps` stream, with only the file names of the paths of files.
+fn stripped_maps_paths(maps: &[u8]) -> Vec<u8> {
+    let lines = maps.split(|&byte| byte == b'\n').map(|line| {
+        // start-end perms offset dev inode [path], the path is padded with spaces
+        let Some(path_start) = line
+            .iter()
+            .enumerate()
+            .filter(|&(_, &byte)| byte == b' ')
+            .nth(4)
+            .map(|(idx, _)| idx + 1)
+        else {
+            return line.to_vec();
+        };
+        let (fields, path) = line.split_at(path_start);
+        let padding = path.iter().take_while(|&&byte| byte == b' ').count();
+        let (padding, path) = path.split_at(padding);
+        if !path.starts_with(b"/") {
+            return line.to_vec();
+        }
+        let path = String::from_utf8_lossy(path);
+        [fields, padding, basename(&path).as_bytes()].concat()
+    });
+    lines.collect::<Vec<_>>().join(&b'\n')
 }
 
-fn read_string_utf8<'a>(
-    offset: &mut usize,
-    bytes: &'a [u8],
-    endian: scroll::Endian,
-) -> Option<&'a str> {
-    let string = read_string_utf8_unterminated(offset, bytes, endian)?;
-    match bytes.gread(offset) {
-        Ok(0u8) => Some(string),
-        _ => None,
+/// Get up to `size` bytes of `bytes` starting at `rva`, stopping at the end of `bytes`.
+///
+/// Returns `None` if `rva` is outside of `bytes` entirely.
+fn clamped_slice(bytes: &[u8], rva: u64, size: u64) -> Option<&[u8]> {
+    let start: usize = rva.try_into().ok()?;
+    let available = bytes.get(start..).filter(|rest| !rest.is_empty())?;
+    let len = size
+        .try_into()
+        .map_or(available.len(), |size: usize| size.min(available.len()));
+    Some(&available[..len])
+}
+
+/// The (inclusive) range of addresses of the `size` bytes at `base`, or `None`
+/// if `size` is 0 or the bytes would extend past the end of the address space.
+fn address_range(base: u64, size: u64) -> Option<Range<u64>> {
+    let last = size.checked_sub(1)?;
+    Some(Range::new(base, base.checked_add(last)?))
+}
+
+/// Like [`address_range`], but a module that would extend past the end of the
+/// address space (which only a corrupt dump can claim) is clamped to it, with a
+/// warning, so that addresses in it can still be found in it.
+fn module_address_range(base: u64, size: u64) -> Option<Range<u64>> {
+    if size == 0 {
+        return None;
     }
+    address_range(base, size).or_else(|| {
+        warn!(
+            "module at {:#x} ({:#x} bytes) extends past the end of the address space, clamping it",
+            base, size
+        );
+        Some(Range::new(base, u64::MAX))
+    })
 }
 
-fn read_cstring_utf8(offset: &mut usize, bytes: &[u8]) -> Option<String> {
-    let initial_offset = *offset;
-    loop {
-        let byte: u8 = bytes.gread(offset).ok()?;
-        if byte == 0 {
-            break;
+/// Read a NUL-terminated string from `bytes`, tolerating broken strings.
+///
+/// A string starting past the end of `bytes` is read as empty, and a string
+/// without a NUL terminator runs to the end of `bytes`. Invalid UTF-8 is replaced,
+/// and strings longer than `max_len` are truncated (but `offset` is still moved
+/// past the whole string, so any following strings are read correctly).
+fn read_cstring_lossy(offset: &mut usize, bytes: &[u8], max_len: usize) -> String {
+    let rest = bytes.get(*offset..).unwrap_or_default();
+    let (string, consumed) = match rest.iter().position(|&b| b == 0) {
+        Some(len) => (&rest[..len], len + 1),
+        None => {
+            if !rest.is_empty() {
+                warn!("C-string wasn't NUL-terminated, reading it up to the end of its record");
+            }
+            (rest, rest.len())
         }
+    };
+    *offset += consumed;
+    if string.len() > max_len {
+        warn!(
+            "C-string was longer than the maximum size ({} > {}), truncating it",
+            string.len(),
+            max_len
+        );
     }
-    std::str::from_utf8(&bytes[initial_offset..*offset - 1])
-        .map(String::from)
-        .ok()
+    let string = &string[..string.len().min(max_len)];
+    String::from_utf8_lossy(string).into_owned()
 }
 
 /// Convert `bytes` with trailing NUL characters to a string
@@ -786,53 +1707,65 @@ fn read_codeview(
     })
 }
 
-fn read_debug_id(codeview_info: &CodeView, endian: scroll::Endian) -> Option<DebugId> {
-    match codeview_info {
-        CodeView::Pdb70(ref raw) => {
-            // For macOS, this should be its code ID with the age (0)
-            // appended to the end of it. This makes it identical to debug
-            // IDs for Windows, and is why it doesn't have a special case
-            // here.
-            let uuid = Uuid::from_fields(
-                raw.signature.data1,
-                raw.signature.data2,
-                raw.signature.data3,
-                &raw.signature.data4,
-            );
-            (!uuid.is_nil()).then(|| DebugId::from_parts(uuid, raw.age))
-        }
-        CodeView::Pdb20(ref raw) => Some(DebugId::from_pdb20(raw.signature, raw.age)),
-        CodeView::Elf(ref raw) => {
-            // For empty or trivial `build_id`s, we don't want to return a `DebugId`.
-            // This can happen for mapped files that aren't executable, like fonts or .jar files.
-            if raw.build_id.iter().all(|byte| *byte == 0) {
-                return None;
-            }
-
-            // For backwards-compat (Linux minidumps have historically
-            // been written using PDB70 CodeView info), treat build_id
-            // as if the first 16 bytes were a GUID.
-            let guid_size = <md::GUID>::size_with(&endian);
-            let guid = if raw.build_id.len() < guid_size {
-                // Pad with zeros.
-                let v: Vec<u8> = raw
-                    .build_id
-                    .iter()
-                    .cloned()
-                    .chain(iter::repeat(0))
-                    .take(guid_size)
-                    .collect();
-                v.pread_with::<md::GUID>(0, endian).ok()
-            } else {
-                raw.build_id.pread_with::<md::GUID>(0, endian).ok()
-            };
-            guid.map(|g| Uuid::from_fields(g.data1, g.data2, g.data3, &g.data4))
-                .map(DebugId::from_uuid)
-        }
-        _ => None,
-    }
-}
-
+impl CodeView {
+    /// The debug identifier of the module this record is for, the one its
+    /// symbols are looked up with.
+    ///
+    /// PDB 7.0 records give their GUID and age, PDB 2.0 records their
+    /// timestamp and age, and ELF records their build id (with the first 16
+    /// bytes read as a GUID, as breakpad does). `endian` is the endianness of
+    /// the minidump.
+    ///
+    /// Returns `None` for records of other formats, and for records without an
+    /// actual identifier (like the all-zero build ids of mapped data files).
+    pub fn debug_identifier(&self, endian: scroll::Endian) -> Option<DebugId> {
+        match self {
+            CodeView::Pdb70(ref raw) => {
+                // For macOS, this should be its code ID with the age (0)
+                // appended to the end of it. This makes it identical to debug
+                // IDs for Windows, and is why it doesn't have a special case
+                // here.
+                let uuid = Uuid::from_fields(
+                    raw.signature.data1,
+                    raw.signature.data2,
+                    raw.signature.data3,
+                    &raw.signature.data4,
+                );
+                (!uuid.is_nil()).then(|| DebugId::from_parts(uuid, raw.age))
+            }
+            CodeView::Pdb20(ref raw) => Some(DebugId::from_pdb20(raw.signature, raw.age)),
+            CodeView::Elf(ref raw) => {
+                // For empty or trivial `build_id`s, we don't want to return a `DebugId`.
+                // This can happen for mapped files that aren't executable, like fonts or .jar files.
+                if raw.build_id.iter().all(|byte| *byte == 0) {
+                    return None;
+                }
+
+                // For backwards-compat (Linux minidumps have historically
+                // been written using PDB70 CodeView info), treat build_id
+                // as if the first 16 bytes were a GUID.
+                let guid_size = <md::GUID>::size_with(&endian);
+                let guid = if raw.build_id.len() < guid_size {
+                    // Pad with zeros.
+                    let v: Vec<u8> = raw
+                        .build_id
+                        .iter()
+                        .cloned()
+                        .chain(iter::repeat(0))
+                        .take(guid_size)
+                        .collect();
+                    v.pread_with::<md::GUID>(0, endian).ok()
+                } else {
+                    raw.build_id.pread_with::<md::GUID>(0, endian).ok()
+                };
+                guid.map(|g| Uuid::from_fields(g.data1, g.data2, g.data3, &g.data4))
+                    .map(DebugId::from_uuid)
+            }
+            _ => None,
+        }
+    }
+}
+
 /// Checks that the buffer is large enough for the given number of items.
 ///
 /// Essentially ensures that `buf.len() >= offset + (number_of_entries * size_of_entry)`.
@@ -883,9 +1816,8 @@ impl MinidumpModule {
         endian: scroll::Endian,
         system_info: Option<&MinidumpSystemInfo>,
     ) -> Result<MinidumpModule, Error> {
-        let mut offset = raw.module_name_rva as usize;
-        let name =
-            read_string_utf16(&mut offset, bytes, endian).ok_or(Error::CodeViewReadFailure)?;
+        let name = read_utf16_string(raw.module_name_rva.into(), bytes, endian)
+            .map_err(Error::StringReadFailure)?;
         let codeview_info = if raw.cv_record.data_size == 0 {
             None
         } else {
@@ -896,7 +1828,7 @@ impl MinidumpModule {
 
         let debug_id = codeview_info
             .as_ref()
-            .and_then(|cv| read_debug_id(cv, endian));
+            .and_then(|cv| cv.debug_identifier(endian));
 
         Ok(MinidumpModule {
             raw,
@@ -1054,14 +1986,13 @@ impl MinidumpModule {
         Ok(())
     }
 
-    fn memory_range(&self) -> Option<Range<u64>> {
-        if self.size() == 0 {
-            return None;
-        }
-        Some(Range::new(
-            self.base_address(),
-            self.base_address().checked_add(self.size())? - 1,
-        ))
+    /// The (inclusive) range of addresses the module was loaded at, or `None` if
+    /// its size is 0.
+    ///
+    /// A module that would extend past the end of the address space is clamped
+    /// to it, with a warning.
+    pub fn memory_range(&self) -> Option<Range<u64>> {
+        module_address_range(self.base_address(), self.size())
     }
 }
 
@@ -1184,8 +2115,8 @@ impl MinidumpUnloadedModule {
         bytes: &[u8],
         endian: scroll::Endian,
     ) -> Result<MinidumpUnloadedModule, Error> {
-        let mut offset = raw.module_name_rva as usize;
-        let name = read_string_utf16(&mut offset, bytes, endian).ok_or(Error::DataError)?;
+        let name = read_utf16_string(raw.module_name_rva.into(), bytes, endian)
+            .map_err(Error::StringReadFailure)?;
         Ok(MinidumpUnloadedModule { raw, name })
     }
 
@@ -1217,14 +2148,10 @@ impl MinidumpUnloadedModule {
         Ok(())
     }
 
-    fn memory_range(&self) -> Option<Range<u64>> {
-        if self.size() == 0 {
-            return None;
-        }
-        Some(Range::new(
-            self.base_address(),
-            self.base_address().checked_add(self.size())? - 1,
-        ))
+    /// The (inclusive) range of addresses the module was loaded at, see
+    /// [`MinidumpModule::memory_range`].
+    pub fn memory_range(&self) -> Option<Range<u64>> {
+        module_address_range(self.base_address(), self.size())
     }
 }
 
@@ -1261,6 +2188,7 @@ impl Module for MinidumpUnloadedModule {
 /// Parses X:Y or X=Y lists, skipping any blank/unparseable lines
 fn linux_list_iter(
     bytes: &[u8],
+    line_separator: u8,
     separator: u8,
 ) -> impl Iterator<Item = (&LinuxOsStr, &LinuxOsStr)> {
     fn strip_quotes(input: &LinuxOsStr) -> &LinuxOsStr {
@@ -1277,7 +2205,7 @@ fn linux_list_iter(
     }
 
     let input = LinuxOsStr::from_bytes(bytes);
-    input.lines().filter_map(move |line| {
+    input.split(line_separator).filter_map(move |line| {
         line.split_once(separator)
             .map(|(label, val)| (strip_quotes(label), (strip_quotes(val))))
     })
@@ -1408,15 +2336,15 @@ impl<'a> MinidumpStream<'a> for MinidumpThreadNames {
         // read out the actual names
         let mut names = BTreeMap::new();
         for raw_name in raw_names {
-            let mut offset = raw_name.thread_name_rva as usize;
             // Better to just drop unreadable names individually than the whole stream.
-            if let Some(name) = read_string_utf16(&mut offset, all, endian) {
-                names.insert(raw_name.thread_id, name);
-            } else {
-                warn!(
-                    "Couldn't read thread name for thread id {}",
-                    raw_name.thread_id
-                );
+            match read_utf16_string(raw_name.thread_name_rva, all, endian) {
+                Ok(name) => {
+                    names.insert(raw_name.thread_id, name);
+                }
+                Err(e) => warn!(
+                    "Couldn't read thread name for thread id {}: {}",
+                    raw_name.thread_id, e
+                ),
             }
         }
         Ok(MinidumpThreadNames { names })
@@ -1424,12 +2352,33 @@ impl<'a> MinidumpStream<'a> for MinidumpThreadNames {
 }
 
 impl MinidumpThreadNames {
+    /// The name of the thread with the id `thread_id`, if it has one.
+    ///
+    /// Names that couldn't be read from the minidump are left out, so this is
+    /// `None` for them too.
     pub fn get_name(&self, thread_id: u32) -> Option<Cow<str>> {
         self.names
             .get(&thread_id)
             .map(|name| Cow::Borrowed(&**name))
     }
 
+    /// The ids and names of the named threads, ordered by thread id.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.names
+            .iter()
+            .map(|(&thread_id, name)| (thread_id, &**name))
+    }
+
+    /// The number of named threads.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether no thread has a name.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
     /// Write a human-readable description of this `MinidumpThreadNames` to `f`.
     pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
         write!(
@@ -1546,8 +2495,9 @@ impl<'a> MinidumpStream<'a> for MinidumpModuleList {
         // read auxiliary data for each module
         let mut modules = Vec::with_capacity(raw_modules.len());
         for (module_index, raw) in raw_modules.into_iter().enumerate() {
-            if raw.size_of_image == 0 || raw.size_of_image as u64 > (u64::MAX - raw.base_of_image) {
-                // Bad image size.
+            if raw.size_of_image == 0 {
+                // Bad image size. (One that extends past the end of memory is
+                // clamped instead, see `memory_range`.)
                 tracing::warn!(
                     module_index,
                     base = raw.base_of_image,
@@ -1568,6 +2518,7 @@ impl MinidumpUnloadedModuleList {
         MinidumpUnloadedModuleList {
             modules: vec![],
             modules_by_addr: vec![],
+            max_ends: vec![],
         }
     }
     /// Create a `MinidumpModuleList` from a list of `MinidumpModule`s.
@@ -1577,10 +2528,18 @@ impl MinidumpUnloadedModuleList {
             .collect::<Vec<_>>();
 
         modules_by_addr.sort_by_key(|(range, _idx)| *range);
+        let max_ends = modules_by_addr
+            .iter()
+            .scan(0, |max_end, (range, _idx)| {
+                *max_end = range.end.max(*max_end);
+                Some(*max_end)
+            })
+            .collect();
 
         MinidumpUnloadedModuleList {
             modules,
             modules_by_addr,
+            max_ends,
         }
     }
 
@@ -1590,11 +2549,14 @@ impl MinidumpUnloadedModuleList {
         address: u64,
     ) -> impl Iterator<Item = &MinidumpUnloadedModule> {
         // We have all of our modules sorted by memory range (base address being the
-        // high-order value), and we need to get the range of values that overlap
-        // with our target address. I'm a bit too tired to work out the exact
-        // combination of binary searches to do this, so let's just use `filter`
-        // for now (unloaded_modules should be a bounded list anyway).
-        self.modules_by_addr
+        // high-order value), so the ones that overlap with our target address start
+        // at or before it. Of those, the ones before the first whose running
+        // maximum end reaches the address all end before it.
+        let end = self
+            .modules_by_addr
+            .partition_point(|(range, _idx)| range.start <= address);
+        let start = self.max_ends[..end].partition_point(|&max_end| max_end < address);
+        self.modules_by_addr[start..end]
             .iter()
             .filter(move |(range, _idx)| range.contains(address))
             .map(move |(_range, idx)| &self.modules[*idx])
@@ -1653,8 +2615,9 @@ impl<'a> MinidumpStream<'a> for MinidumpUnloadedModuleList {
         // read auxiliary data for each module
         let mut modules = Vec::with_capacity(raw_modules.len());
         for raw in raw_modules.into_iter() {
-            if raw.size_of_image == 0 || raw.size_of_image as u64 > (u64::MAX - raw.base_of_image) {
-                // Bad image size.
+            if raw.size_of_image == 0 {
+                // Bad image size. (One that extends past the end of memory is
+                // clamped instead, see `memory_range`.)
                 // TODO: just drop this module, keep the rest?
                 return Err(Error::ModuleReadFailure);
             }
@@ -1759,13 +2722,13 @@ impl MinidumpHandleDescriptor {
         writeln!(f)
     }
 
-    fn read_string(offset: usize, ctx: HandleDescriptorContext) -> Option<String> {
-        let mut offset = offset;
-        if offset != 0 {
-            read_string_utf16(&mut offset, ctx.bytes, ctx.endianess)
-        } else {
-            None
+    fn read_string(rva: u32, ctx: HandleDescriptorContext) -> Option<String> {
+        if rva == 0 {
+            return None;
         }
+        read_utf16_string(rva.into(), ctx.bytes, ctx.endianess)
+            .map_err(|e| warn!("Couldn't read handle name: {}", e))
+            .ok()
     }
 
     fn read_object_info(
@@ -1819,8 +2782,8 @@ impl<'a> TryFromCtx<'a, HandleDescriptorContext<'a>> for MinidumpHandleDescripto
         match ctx.fieldsize {
             MINIDUMP_HANDLE_DESCRIPTOR_SIZE => {
                 let raw = src.pread_with::<md::MINIDUMP_HANDLE_DESCRIPTOR>(0, ctx.endianess)?;
-                let type_name = Self::read_string(raw.type_name_rva as usize, ctx);
-                let object_name = Self::read_string(raw.object_name_rva as usize, ctx);
+                let type_name = Self::read_string(raw.type_name_rva, ctx);
+                let object_name = Self::read_string(raw.object_name_rva, ctx);
                 Ok((
                     MinidumpHandleDescriptor {
                         raw: RawHandleDescriptor::HandleDescriptor(raw),
@@ -1833,8 +2796,8 @@ impl<'a> TryFromCtx<'a, HandleDescriptorContext<'a>> for MinidumpHandleDescripto
             }
             MINIDUMP_HANDLE_DESCRIPTOR_2_SIZE => {
                 let raw = src.pread_with::<md::MINIDUMP_HANDLE_DESCRIPTOR_2>(0, ctx.endianess)?;
-                let type_name = Self::read_string(raw.type_name_rva as usize, ctx);
-                let object_name = Self::read_string(raw.object_name_rva as usize, ctx);
+                let type_name = Self::read_string(raw.type_name_rva, ctx);
+                let object_name = Self::read_string(raw.object_name_rva, ctx);
                 let mut object_infos = Vec::<MinidumpHandleObjectInformation>::new();
                 let mut object_info_rva = raw.object_info_rva;
 
@@ -1950,6 +2913,354 @@ impl<'a> MinidumpStream<'a> for MinidumpHandleDataStream {
     }
 }
 
+impl<'a> MinidumpFunctionTable<'a> {
+    /// Whether `address` is in the code this table describes.
+    pub fn contains(&self, address: u64) -> bool {
+        self.raw.minimum_address <= address && address < self.raw.maximum_address
+    }
+
+    /// The raw entries of the table, whose format depends on the CPU.
+    pub fn raw_entries(&self) -> impl Iterator<Item = &'a [u8]> + 'a {
+        // (`chunks_exact` doesn't accept a size of 0.)
+        self.entries.chunks_exact(self.entry_size.max(1))
+    }
+
+    /// The entries of the table, if it's an x86-64 table.
+    ///
+    /// This is empty if the entries are too small to be `RUNTIME_FUNCTION`s.
+    pub fn amd64_entries(
+        &self,
+    ) -> impl Iterator<Item = md::IMAGE_AMD64_RUNTIME_FUNCTION_ENTRY> + 'a {
+        let endian = self.endian;
+        self.raw_entries()
+            .filter_map(move |entry| entry.pread_with(0, endian).ok())
+    }
+
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MINIDUMP_FUNCTION_TABLE_DESCRIPTOR
+  minimum_address   = {:#x}
+  maximum_address   = {:#x}
+  base_address      = {:#x}
+  entry_count       = {}
+  size_of_align_pad = {}
+",
+            self.raw.minimum_address,
+            self.raw.maximum_address,
+            self.raw.base_address,
+            self.raw.entry_count,
+            self.raw.size_of_align_pad,
+        )?;
+        for (i, entry) in self.amd64_entries().enumerate() {
+            writeln!(
+                f,
+                "  entry[{i}] = {:#x}-{:#x} unwind info {:#x}",
+                entry.begin_address, entry.end_address, entry.unwind_info_address
+            )?;
+        }
+        writeln!(f)
+    }
+}
+
+impl<'a> MinidumpFunctionTableStream<'a> {
+    /// Iterate over the tables in the order contained in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpFunctionTable<'a>> {
+        self.tables.iter()
+    }
+
+    /// The table describing the code at `address`, if there is one.
+    pub fn table_at_address(&self, address: u64) -> Option<&MinidumpFunctionTable<'a>> {
+        self.tables.iter().find(|table| table.contains(address))
+    }
+
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpFunctionTableStream
+  table_count = {}
+
+",
+            self.tables.len()
+        )?;
+        for (i, table) in self.tables.iter().enumerate() {
+            writeln!(f, "table[{i}]")?;
+            table.print(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpFunctionTableStream<'a> {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::FunctionTable as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpFunctionTableStream<'a>, Error> {
+        let header: md::MINIDUMP_FUNCTION_TABLE_STREAM = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        let size_of_descriptor = header.size_of_descriptor as usize;
+        let take = |offset: &mut usize, size: usize| -> Result<&'a [u8], Error> {
+            let data = offset
+                .checked_add(size)
+                .and_then(|end| bytes.get(*offset..end))
+                .ok_or(Error::StreamReadFailure)?;
+            *offset += size;
+            Ok(data)
+        };
+
+        let mut offset = header.size_of_header as usize;
+        take(&mut offset, header.size_of_align_pad as usize)?;
+        let mut tables = Vec::new();
+        for _ in 0..header.number_of_descriptors {
+            let descriptor = take(&mut offset, size_of_descriptor)?;
+            let raw: md::MINIDUMP_FUNCTION_TABLE_DESCRIPTOR = descriptor
+                .pread_with(0, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            let native_descriptor = take(&mut offset, header.size_of_native_descriptor as usize)?;
+            let entries_size = (raw.entry_count as usize)
+                .checked_mul(header.size_of_function_entry as usize)
+                .ok_or(Error::StreamReadFailure)?;
+            let entries = take(&mut offset, entries_size)?;
+            // The padding of the last table may be missing.
+            offset = offset.saturating_add(raw.size_of_align_pad as usize);
+            tables.push(MinidumpFunctionTable {
+                raw,
+                native_descriptor,
+                entries,
+                entry_size: header.size_of_function_entry as usize,
+                endian,
+            });
+        }
+        Ok(MinidumpFunctionTableStream { tables })
+    }
+}
+
+impl MinidumpHandleOperation {
+    /// What the operation was, if it's a known kind.
+    pub fn operation_type(&self) -> Option<md::HandleTraceOperation> {
+        md::HandleTraceOperation::from_u32(self.raw.operation_type)
+    }
+
+    /// The return addresses of the stack the operation was done on, innermost
+    /// first.
+    pub fn backtrace(&self) -> &[u64] {
+        let backtrace = &self.raw.back_trace_information;
+        let depth = (backtrace.depth as usize).min(backtrace.return_addresses.len());
+        &backtrace.return_addresses[..depth]
+    }
+
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        let operation_type = match self.operation_type() {
+            Some(known) => format!("{} ({known:?})", self.raw.operation_type),
+            None => self.raw.operation_type.to_string(),
+        };
+        write!(
+            f,
+            "AVRF_HANDLE_OPERATION
+  handle         = {:#x}
+  process_id     = {:#x}
+  thread_id      = {:#x}
+  operation_type = {}
+  depth          = {}
+",
+            self.raw.handle,
+            self.raw.process_id,
+            self.raw.thread_id,
+            operation_type,
+            self.raw.back_trace_information.depth,
+        )?;
+        for (i, address) in self.backtrace().iter().enumerate() {
+            writeln!(f, "  return_address[{i}] = {address:#x}")?;
+        }
+        writeln!(f)
+    }
+}
+
+impl MinidumpHandleOperationList {
+    /// Iterate over the operations in the order contained in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpHandleOperation> {
+        self.operations.iter()
+    }
+
+    /// The operations on `handle`, in the order contained in the minidump.
+    pub fn operations_on_handle(
+        &self,
+        handle: u64,
+    ) -> impl Iterator<Item = &MinidumpHandleOperation> {
+        self.iter()
+            .filter(move |operation| operation.raw.handle == handle)
+    }
+
+    /// The uses of invalid handles, which are usually the cause of the crash of
+    /// a process with handle tracing enabled.
+    pub fn bad_references(&self) -> impl Iterator<Item = &MinidumpHandleOperation> {
+        self.iter().filter(|operation| {
+            operation.operation_type() == Some(md::HandleTraceOperation::OperationDbBADREF)
+        })
+    }
+
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpHandleOperationList
+  operation_count = {}
+
+",
+            self.operations.len()
+        )?;
+        for (i, operation) in self.operations.iter().enumerate() {
+            writeln!(f, "operation[{i}]")?;
+            operation.print(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpHandleOperationList {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::HandleOperationListStream as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpHandleOperationList, Error> {
+        let header: md::MINIDUMP_HANDLE_OPERATION_LIST = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        let size_of_entry = header.size_of_entry as usize;
+        if size_of_entry < md::AVRF_HANDLE_OPERATION::size_with(&endian) {
+            return Err(Error::StreamReadFailure);
+        }
+        let mut offset = header.size_of_header as usize;
+        let mut operations = Vec::new();
+        for _ in 0..header.number_of_entries {
+            let raw: md::AVRF_HANDLE_OPERATION = bytes
+                .pread_with(offset, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            offset = offset.saturating_add(size_of_entry);
+            operations.push(MinidumpHandleOperation { raw });
+        }
+        Ok(MinidumpHandleOperationList { operations })
+    }
+}
+
+impl<'a> MinidumpToken<'a> {
+    /// The integrity level of the token, the relative id of its integrity SID
+    /// (see [`md::MandatoryLevel`] for the known ones).
+    ///
+    /// The format of the token's data isn't documented, but the integrity SID
+    /// (`S-1-16-*`) is the only SID with the mandatory label authority, so it
+    /// can be found by looking for that.
+    pub fn integrity_level(&self) -> Option<u32> {
+        // Revision 1, one sub-authority, SECURITY_MANDATORY_LABEL_AUTHORITY (16)
+        const INTEGRITY_SID_PREFIX: [u8; 8] = [1, 1, 0, 0, 0, 0, 0, 16];
+        let offset = self
+            .data
+            .windows(INTEGRITY_SID_PREFIX.len())
+            .position(|window| window == INTEGRITY_SID_PREFIX)?;
+        self.data
+            .pread_with(offset + INTEGRITY_SID_PREFIX.len(), self.endian)
+            .ok()
+    }
+
+    /// Whether the token is elevated, i.e. its integrity level is at least
+    /// high (which is what UAC elevation, or running as a service, gives a
+    /// process).
+    pub fn is_elevated(&self) -> Option<bool> {
+        self.integrity_level()
+            .map(|level| level >= md::MandatoryLevel::SECURITY_MANDATORY_HIGH_RID as u32)
+    }
+
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        let integrity_level =
+            self.integrity_level()
+                .map(|level| match md::MandatoryLevel::from_u32(level) {
+                    Some(known) => format!("{level:#x} ({known:?})"),
+                    None => format!("{level:#x}"),
+                });
+        write!(
+            f,
+            "MINIDUMP_TOKEN_INFO_LIST
+  token_size      = {}
+  token_id        = {:#x}
+  token_handle    = {:#x}
+  integrity_level = {}
+
+",
+            self.raw.token_size,
+            self.raw.token_id,
+            self.raw.token_handle,
+            integrity_level.as_deref().unwrap_or("(invalid)"),
+        )
+    }
+}
+
+impl<'a> MinidumpTokenStream<'a> {
+    /// Iterate over the tokens in the order contained in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpToken<'a>> {
+        self.tokens.iter()
+    }
+
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpTokenStream
+  token_count = {}
+
+",
+            self.tokens.len()
+        )?;
+        for (i, token) in self.tokens.iter().enumerate() {
+            writeln!(f, "token[{i}]")?;
+            token.print(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpTokenStream<'a> {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::TokenStream as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpTokenStream<'a>, Error> {
+        let header: md::MINIDUMP_TOKEN_INFO_HEADER = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        let element_header_size = header.element_header_size as usize;
+        let mut offset = header.list_header_size as usize;
+        let mut tokens = Vec::new();
+        for _ in 0..header.token_list_entries {
+            let raw: md::MINIDUMP_TOKEN_INFO_LIST = bytes
+                .pread_with(offset, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            let token_size = (raw.token_size as usize).max(element_header_size);
+            let data = offset
+                .checked_add(token_size)
+                .and_then(|end| bytes.get(offset + element_header_size..end))
+                .ok_or(Error::StreamReadFailure)?;
+            offset += token_size;
+            tokens.push(MinidumpToken { raw, data, endian });
+        }
+        Ok(MinidumpTokenStream { tokens })
+    }
+}
+
 impl<'a> MinidumpMemory<'a> {
     pub fn read(
         desc: &md::MINIDUMP_MEMORY_DESCRIPTOR,
@@ -2024,11 +3335,74 @@ impl<'a, Descriptor> MinidumpMemoryBase<'a, Descriptor> {
     where
         T: TryFromCtx<'a, scroll::Endian, [u8], Error = scroll::Error>,
     {
-        let start = addr.checked_sub(self.base_address)? as usize;
+        let start: usize = addr.checked_sub(self.base_address)?.try_into().ok()?;
 
         self.bytes.pread_with::<T>(start, self.endian).ok()
     }
 
+    /// Get the byte at `addr`, if it's in this region.
+    pub fn get_u8_at(&self, addr: u64) -> Option<u8> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// Get the `u16` at `addr`, if it's in this region.
+    pub fn get_u16_at(&self, addr: u64) -> Option<u16> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// Get the `u32` at `addr`, if it's in this region.
+    pub fn get_u32_at(&self, addr: u64) -> Option<u32> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// Get the `u64` at `addr`, if it's in this region.
+    pub fn get_u64_at(&self, addr: u64) -> Option<u64> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// Get the pointer at `addr`, if it's in this region.
+    ///
+    /// `pointer_width` is usually the CPU's (see [`Cpu::pointer_width`][]), and
+    /// nothing is read if it's unknown.
+    pub fn get_pointer_at(&self, addr: u64, pointer_width: PointerWidth) -> Option<u64> {
+        match pointer_width {
+            PointerWidth::Bits32 => self.get_u32_at(addr).map(u64::from),
+            PointerWidth::Bits64 => self.get_u64_at(addr),
+            PointerWidth::Unknown => None,
+        }
+    }
+
+    /// Get the bytes of this region from `addr` to its end, at most `max_len`
+    /// of them.
+    pub fn get_bytes_at(&self, addr: u64, max_len: usize) -> Option<&'a [u8]> {
+        let start: usize = addr.checked_sub(self.base_address)?.try_into().ok()?;
+        let bytes = self.bytes.get(start..)?;
+        Some(&bytes[..bytes.len().min(max_len)])
+    }
+
+    /// Get the NUL-terminated string at `addr`, reading at most `max_len` bytes
+    /// (and not past the end of this region). Invalid UTF-8 is replaced.
+    pub fn get_c_string_at(&self, addr: u64, max_len: usize) -> Option<String> {
+        let bytes = self.get_bytes_at(addr, max_len)?;
+        let len = bytes
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(bytes.len());
+        Some(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+
+    /// Get the NUL-terminated UTF-16 string at `addr`, reading at most `max_len`
+    /// bytes (and not past the end of this region). Invalid UTF-16 is replaced.
+    pub fn get_utf16_string_at(&self, addr: u64, max_len: usize) -> Option<String> {
+        let bytes = self.get_bytes_at(addr, max_len)?;
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|unit| unit.pread_with::<u16>(0, self.endian).unwrap_or_default())
+            .take_while(|&unit| unit != 0)
+            .collect();
+        Some(String::from_utf16_lossy(&units))
+    }
+
     /// Write the contents of this `MinidumpMemory` to `f` as a hex string.
     pub fn print_contents<T: Write>(&self, f: &mut T) -> io::Result<()> {
         const PARAGRAPH_SIZE: usize = 16;
@@ -2060,13 +3434,7 @@ impl<'a, Descriptor> MinidumpMemoryBase<'a, Descriptor> {
     }
 
     pub fn memory_range(&self) -> Option<Range<u64>> {
-        if self.size == 0 {
-            return None;
-        }
-        Some(Range::new(
-            self.base_address,
-            self.base_address.checked_add(self.size)? - 1,
-        ))
+        address_range(self.base_address, self.size)
     }
 }
 
@@ -2081,6 +3449,58 @@ impl<'a, 'mdmp> UnifiedMemory<'a, 'mdmp> {
         }
     }
 
+    /// See [`MinidumpMemoryBase::get_u8_at`][].
+    pub fn get_u8_at(&self, addr: u64) -> Option<u8> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// See [`MinidumpMemoryBase::get_u16_at`][].
+    pub fn get_u16_at(&self, addr: u64) -> Option<u16> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// See [`MinidumpMemoryBase::get_u32_at`][].
+    pub fn get_u32_at(&self, addr: u64) -> Option<u32> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// See [`MinidumpMemoryBase::get_u64_at`][].
+    pub fn get_u64_at(&self, addr: u64) -> Option<u64> {
+        self.get_memory_at_address(addr)
+    }
+
+    /// See [`MinidumpMemoryBase::get_pointer_at`][].
+    pub fn get_pointer_at(&self, addr: u64, pointer_width: PointerWidth) -> Option<u64> {
+        match self {
+            UnifiedMemory::Memory(this) => this.get_pointer_at(addr, pointer_width),
+            UnifiedMemory::Memory64(this) => this.get_pointer_at(addr, pointer_width),
+        }
+    }
+
+    /// See [`MinidumpMemoryBase::get_bytes_at`][].
+    pub fn get_bytes_at(&self, addr: u64, max_len: usize) -> Option<&'mdmp [u8]> {
+        match self {
+            UnifiedMemory::Memory(this) => this.get_bytes_at(addr, max_len),
+            UnifiedMemory::Memory64(this) => this.get_bytes_at(addr, max_len),
+        }
+    }
+
+    /// See [`MinidumpMemoryBase::get_c_string_at`][].
+    pub fn get_c_string_at(&self, addr: u64, max_len: usize) -> Option<String> {
+        match self {
+            UnifiedMemory::Memory(this) => this.get_c_string_at(addr, max_len),
+            UnifiedMemory::Memory64(this) => this.get_c_string_at(addr, max_len),
+        }
+    }
+
+    /// See [`MinidumpMemoryBase::get_utf16_string_at`][].
+    pub fn get_utf16_string_at(&self, addr: u64, max_len: usize) -> Option<String> {
+        match self {
+            UnifiedMemory::Memory(this) => this.get_utf16_string_at(addr, max_len),
+            UnifiedMemory::Memory64(this) => this.get_utf16_string_at(addr, max_len),
+        }
+    }
+
     pub fn memory_range(&self) -> Option<Range<u64>> {
         match self {
             UnifiedMemory::Memory(this) => this.memory_range(),
@@ -2109,6 +3529,13 @@ impl<'a, 'mdmp> UnifiedMemory<'a, 'mdmp> {
         }
     }
 
+    pub fn endian(&self) -> scroll::Endian {
+        match self {
+            UnifiedMemory::Memory(this) => this.endian,
+            UnifiedMemory::Memory64(this) => this.endian,
+        }
+    }
+
     pub fn print_contents<T: Write>(&self, f: &mut T) -> io::Result<()> {
         match self {
             UnifiedMemory::Memory(this) => this.print_contents(f),
@@ -2130,24 +3557,88 @@ impl<'mdmp, Descriptor> MinidumpMemoryListBase<'mdmp, Descriptor> {
         MinidumpMemoryListBase {
             regions: vec![],
             regions_by_addr: RangeMap::new(),
+            corruption_notes: vec![],
         }
     }
 
     /// Create a `MinidumpMemoryListBase` from a list of `MinidumpMemoryBase`s.
+    ///
+    /// Empty regions are ignored, as are regions that overlap a region with a lower
+    /// base address. Both are recorded in [`MinidumpMemoryListBase::corruption_notes`].
     pub fn from_regions(
         regions: Vec<MinidumpMemoryBase<'mdmp, Descriptor>>,
     ) -> MinidumpMemoryListBase<'mdmp, Descriptor> {
-        let regions_by_addr = regions
-            .iter()
-            .enumerate()
-            .map(|(i, region)| (region.memory_range(), i))
-            .into_rangemap_safe();
+        Self::from_regions_and_notes(regions, &[], vec![])
+    }
+
+    /// Like [`MinidumpMemoryListBase::from_regions`], but the regions at the
+    /// indices in `truncated` (whose contents weren't all in the file) lose to
+    /// any complete region they overlap, whatever their base addresses.
+    fn from_regions_and_notes(
+        regions: Vec<MinidumpMemoryBase<'mdmp, Descriptor>>,
+        truncated: &[usize],
+        mut corruption_notes: Vec<CorruptionNote>,
+    ) -> MinidumpMemoryListBase<'mdmp, Descriptor> {
+        let mut ranges = Vec::with_capacity(regions.len());
+        for (i, region) in regions.iter().enumerate() {
+            match region.memory_range() {
+                Some(range) => ranges.push((range, i)),
+                None if region.size == 0 => corruption_notes.push(CorruptionNote::MemoryEmpty {
+                    base_address: region.base_address,
+                }),
+                None => corruption_notes.push(CorruptionNote::MemoryRangeOverflow {
+                    base_address: region.base_address,
+                    size: region.size,
+                }),
+            }
+        }
+        // Overlapping regions are discarded in favour of the complete ones, and
+        // then of the first one (by address).
+        let mut complete = vec![true; regions.len()];
+        for &i in truncated {
+            complete[i] = false;
+        }
+        ranges.sort_by_key(|&(range, i)| (!complete[i], range.start, range.end));
+
+        // The regions that are used, by their start address.
+        let mut used: BTreeMap<u64, (u64, usize)> = BTreeMap::new();
+        for (range, i) in ranges {
+            // The used regions don't overlap, so only the last one that starts
+            // before the end of this one can overlap it.
+            let overlapping = used
+                .range(..=range.end)
+                .next_back()
+                .filter(|(_, &(used_end, _))| used_end >= range.start);
+            if let Some((_, &(_, used_i))) = overlapping {
+                let (region, used) = (&regions[i], &regions[used_i]);
+                corruption_notes.push(CorruptionNote::MemoryOverlap {
+                    base_address: region.base_address,
+                    size: region.size,
+                    used_base_address: used.base_address,
+                    used_size: used.size,
+                });
+                continue;
+            }
+            used.insert(range.start, (range.end, i));
+        }
+
         MinidumpMemoryListBase {
             regions,
-            regions_by_addr,
+            regions_by_addr: used
+                .into_iter()
+                .map(|(start, (end, i))| (Some(Range::new(start, end)), i))
+                .into_rangemap_safe(),
+            corruption_notes,
         }
     }
 
+    /// Problems found with the memory regions while reading them.
+    ///
+    /// See [`Minidump::corruption_notes`][] for the notes of the whole minidump.
+    pub fn corruption_notes(&self) -> &[CorruptionNote] {
+        &self.corruption_notes
+    }
+
     /// Return a `MinidumpMemoryBase` containing memory at `address`, if one exists.
     pub fn memory_at_address(
         &self,
@@ -2223,28 +3714,45 @@ impl<'mdmp> MinidumpMemory64List<'mdmp> {
 }
 
 impl<'mdmp> UnifiedMemoryList<'mdmp> {
-    pub fn memory_at_address<'slf>(&'slf self, address: u64) -> Option<UnifiedMemory<'slf, 'mdmp>> {
+    /// The list of the 32-bit memory regions, if there is one.
+    fn memory(&self) -> Option<&MinidumpMemoryList<'mdmp>> {
         match self {
-            UnifiedMemoryList::Memory(this) => {
-                this.memory_at_address(address).map(UnifiedMemory::Memory)
-            }
-            UnifiedMemoryList::Memory64(this) => {
-                this.memory_at_address(address).map(UnifiedMemory::Memory64)
+            UnifiedMemoryList::Memory(memory) | UnifiedMemoryList::Both(memory, _) => Some(memory),
+            UnifiedMemoryList::Memory64(_) => None,
+        }
+    }
+
+    /// The list of the 64-bit memory regions, if there is one.
+    fn memory64(&self) -> Option<&MinidumpMemory64List<'mdmp>> {
+        match self {
+            UnifiedMemoryList::Memory64(memory64) | UnifiedMemoryList::Both(_, memory64) => {
+                Some(memory64)
             }
+            UnifiedMemoryList::Memory(_) => None,
         }
     }
 
+    /// The memory region containing `address`, from whichever list has it.
+    pub fn memory_at_address<'slf>(&'slf self, address: u64) -> Option<UnifiedMemory<'slf, 'mdmp>> {
+        self.memory64()
+            .and_then(|memory64| memory64.memory_at_address(address))
+            .map(UnifiedMemory::Memory64)
+            .or_else(|| {
+                self.memory()?
+                    .memory_at_address(address)
+                    .map(UnifiedMemory::Memory)
+            })
+    }
+
+    /// Iterate over the memory regions in the order contained in the minidump
+    /// (the 32-bit list first).
     pub fn iter<'slf>(&'slf self) -> impl Iterator<Item = UnifiedMemory<'slf, 'mdmp>> {
-        let iter1 = if let UnifiedMemoryList::Memory(this) = self {
-            Some(this.iter().map(UnifiedMemory::Memory))
-        } else {
-            None
-        };
-        let iter2 = if let UnifiedMemoryList::Memory64(this) = self {
-            Some(this.iter().map(UnifiedMemory::Memory64))
-        } else {
-            None
-        };
+        let iter1 = self
+            .memory()
+            .map(|this| this.iter().map(UnifiedMemory::Memory));
+        let iter2 = self
+            .memory64()
+            .map(|this| this.iter().map(UnifiedMemory::Memory64));
         iter1
             .into_iter()
             .flatten()
@@ -2253,27 +3761,36 @@ impl<'mdmp> UnifiedMemoryList<'mdmp> {
 
     /// Iterate over the memory regions in order by memory address.
     pub fn by_addr<'slf>(&'slf self) -> impl Iterator<Item = UnifiedMemory<'slf, 'mdmp>> {
-        let iter1 = if let UnifiedMemoryList::Memory(this) = self {
-            Some(this.by_addr().map(UnifiedMemory::Memory))
-        } else {
-            None
-        };
-        let iter2 = if let UnifiedMemoryList::Memory64(this) = self {
-            Some(this.by_addr().map(UnifiedMemory::Memory64))
-        } else {
-            None
-        };
-        iter1
+        let mut iter1 = self
+            .memory()
+            .map(|this| this.by_addr().map(UnifiedMemory::Memory))
             .into_iter()
             .flatten()
-            .chain(iter2.into_iter().flatten())
+            .peekable();
+        let mut iter2 = self
+            .memory64()
+            .map(|this| this.by_addr().map(UnifiedMemory::Memory64))
+            .into_iter()
+            .flatten()
+            .peekable();
+        // Both lists are in order, so merge them.
+        std::iter::from_fn(move || match (iter1.peek(), iter2.peek()) {
+            (Some(memory), Some(memory64)) if memory.base_address() < memory64.base_address() => {
+                iter1.next()
+            }
+            (_, Some(_)) => iter2.next(),
+            (_, None) => iter1.next(),
+        })
     }
 
     pub fn print<T: Write>(&self, f: &mut T, brief: bool) -> io::Result<()> {
-        match self {
-            UnifiedMemoryList::Memory(this) => this.print(f, brief),
-            UnifiedMemoryList::Memory64(this) => this.print(f, brief),
+        if let Some(memory) = self.memory() {
+            memory.print(f, brief)?;
+        }
+        if let Some(memory64) = self.memory64() {
+            memory64.print(f, brief)?;
         }
+        Ok(())
     }
 }
 
@@ -2297,15 +3814,50 @@ impl<'a> MinidumpStream<'a> for MinidumpMemoryList<'a> {
             read_stream_list(&mut offset, bytes, endian)?;
         // read memory contents for each region
         let mut regions = Vec::with_capacity(descriptors.len());
+        let mut truncated = vec![];
+        let mut notes = vec![];
         for raw in descriptors.into_iter() {
+            let base_address = raw.start_of_memory_range;
+            let claimed_size = raw.memory.data_size as u64;
+            if claimed_size == 0 {
+                notes.push(CorruptionNote::MemoryEmpty { base_address });
+                continue;
+            }
             if let Ok(memory) = MinidumpMemory::read(&raw, all, endian) {
                 regions.push(memory);
-            } else {
-                // Just skip over corrupt entries and try to limp along.
                 continue;
             }
+
+            // Keep whatever part of the region is actually in the file and try to
+            // limp along. An rva of 0 would point at the minidump's header, so that's
+            // just as bad as pointing past the end.
+            let bytes = Some(raw.memory.rva)
+                .filter(|&rva| rva != 0)
+                .and_then(|rva| clamped_slice(all, rva as u64, claimed_size));
+            if let Some(bytes) = bytes {
+                notes.push(CorruptionNote::MemoryTruncated {
+                    base_address,
+                    claimed_size,
+                    available_size: bytes.len() as u64,
+                });
+                truncated.push(regions.len());
+                regions.push(MinidumpMemory {
+                    desc: raw,
+                    base_address,
+                    size: bytes.len() as u64,
+                    bytes,
+                    endian,
+                });
+            } else {
+                notes.push(CorruptionNote::MemoryOutOfBounds {
+                    base_address,
+                    size: claimed_size,
+                });
+            }
         }
-        Ok(MinidumpMemoryList::from_regions(regions))
+        Ok(MinidumpMemoryList::from_regions_and_notes(
+            regions, &truncated, notes,
+        ))
     }
 }
 
@@ -2350,26 +3902,49 @@ impl<'a> MinidumpStream<'a> for MinidumpMemory64List<'a> {
         }
 
         let mut regions = Vec::with_capacity(raw_entries.len());
+        let mut truncated = vec![];
+        let mut notes = vec![];
         for raw in raw_entries {
+            let base_address = raw.start_of_memory_range;
+            let claimed_size = raw.data_size;
+            // The contents of the regions are stored back to back, so even if this
+            // one is bad we need to skip over it.
             let start = rva;
-            let end = rva
-                .checked_add(raw.data_size)
-                .ok_or(Error::StreamReadFailure)?;
-            let bytes = all
-                .get(start as usize..end as usize)
-                .ok_or(Error::StreamReadFailure)?;
+            rva = rva.saturating_add(claimed_size);
+
+            if claimed_size == 0 {
+                notes.push(CorruptionNote::MemoryEmpty { base_address });
+                continue;
+            }
+            // Keep whatever part of the region is actually in the file and try to
+            // limp along.
+            let Some(bytes) = clamped_slice(all, start, claimed_size) else {
+                notes.push(CorruptionNote::MemoryOutOfBounds {
+                    base_address,
+                    size: claimed_size,
+                });
+                continue;
+            };
+            if (bytes.len() as u64) < claimed_size {
+                notes.push(CorruptionNote::MemoryTruncated {
+                    base_address,
+                    claimed_size,
+                    available_size: bytes.len() as u64,
+                });
+                truncated.push(regions.len());
+            }
 
             regions.push(MinidumpMemory64 {
                 desc: raw,
-                base_address: raw.start_of_memory_range,
-                size: raw.data_size,
+                base_address,
+                size: bytes.len() as u64,
                 bytes,
                 endian,
             });
-
-            rva = end;
         }
-        Ok(MinidumpMemory64List::from_regions(regions))
+        Ok(MinidumpMemory64List::from_regions_and_notes(
+            regions, &truncated, notes,
+        ))
     }
 }
 
@@ -2498,13 +4073,36 @@ impl<'a> MinidumpMemoryInfo<'a> {
     }
 
     pub fn memory_range(&self) -> Option<Range<u64>> {
-        if self.raw.region_size == 0 {
-            return None;
-        }
-        Some(Range::new(
-            self.raw.base_address,
-            self.raw.base_address.checked_add(self.raw.region_size)? - 1,
-        ))
+        address_range(self.raw.base_address, self.raw.region_size)
+    }
+
+    /// The start of the allocation this region is part of (e.g. the whole
+    /// stack of a thread, with its guard page and the reserved part).
+    pub fn allocation_base(&self) -> u64 {
+        self.raw.allocation_base
+    }
+
+    /// Whether the pages of this region were committed (backed by memory).
+    pub fn is_committed(&self) -> bool {
+        self.state.contains(md::MemoryState::MEM_COMMIT)
+    }
+
+    /// Whether the pages of this region were reserved, but not committed.
+    ///
+    /// Accessing them crashes, as they aren't backed by memory (yet).
+    pub fn is_reserved(&self) -> bool {
+        self.state.contains(md::MemoryState::MEM_RESERVE)
+    }
+
+    /// Whether this region was free, so not mapped at all.
+    pub fn is_free(&self) -> bool {
+        self.state.contains(md::MemoryState::MEM_FREE)
+    }
+
+    /// Whether this region was a guard page (like the one below a thread's
+    /// stack, that is hit when the stack overflows).
+    pub fn is_guard_page(&self) -> bool {
+        self.is_committed() && self.protection.contains(md::MemoryProtection::PAGE_GUARD)
     }
 
     /// Whether this memory range was readable.
@@ -2648,11 +4246,9 @@ impl<'a> MinidumpLinuxMapInfo<'a> {
     }
 
     pub fn memory_range(&self) -> Option<Range<u64>> {
-        // final address is inclusive afaik
-        if self.map.address.0 > self.map.address.1 {
-            return None;
-        }
-        Some(Range::new(self.map.address.0, self.map.address.1))
+        // The end address is exclusive, it's the start of the next mapping.
+        let (start, end) = self.map.address;
+        address_range(start, end.checked_sub(start)?)
     }
 
     /// Whether this memory range was readable.
@@ -2827,9 +4423,28 @@ impl<'a> MinidumpThread<'a> {
         system_info: &MinidumpSystemInfo,
         misc: Option<&MinidumpMiscInfo>,
     ) -> Option<Cow<MinidumpContext>> {
-        MinidumpContext::read(self.context?, self.endian, system_info, misc)
-            .ok()
-            .map(Cow::Owned)
+        self.read_context(system_info, misc).ok().map(Cow::Owned)
+    }
+
+    /// Like [`MinidumpThread::context`], but with why the context couldn't be read.
+    pub fn read_context(
+        &self,
+        system_info: &MinidumpSystemInfo,
+        misc: Option<&MinidumpMiscInfo>,
+    ) -> Result<MinidumpContext, ContextError> {
+        let context = self.context.ok_or(ContextError::ReadFailure)?;
+        MinidumpContext::read(context, self.endian, system_info, misc)
+    }
+
+    /// The XSAVE state of the thread's context (e.g. the YMM registers), if it has any.
+    ///
+    /// See [`MinidumpContext::read_xstate`].
+    pub fn xstate(
+        &self,
+        system_info: &MinidumpSystemInfo,
+        misc: Option<&MinidumpMiscInfo>,
+    ) -> Option<ContextXstate> {
+        MinidumpContext::read_xstate(self.context?, self.endian, system_info, misc)
     }
 
     pub fn stack_memory<'mem>(
@@ -2887,6 +4502,9 @@ impl<'a> MinidumpThread<'a> {
         if let Some(system_info) = system {
             if let Some(ctx) = self.context(system_info, misc) {
                 ctx.print(f)?;
+                if let Some(xstate) = self.xstate(system_info, misc) {
+                    xstate.print(f)?;
+                }
             } else {
                 write!(f, "  (no context)\n\n")?;
             }
@@ -3068,6 +4686,17 @@ impl MinidumpThreadInfo {
         )?;
         Ok(())
     }
+
+    /// The state of the thread when the minidump was written.
+    pub fn dump_flags(&self) -> md::ThreadInfoDumpFlags {
+        md::ThreadInfoDumpFlags::from_bits_truncate(self.raw.dump_flags)
+    }
+
+    /// The address the thread started executing at, if it was recorded.
+    pub fn start_address(&self) -> Option<u64> {
+        // A zero start address means the entry didn't record one.
+        Some(self.raw.start_address).filter(|&address| address != 0)
+    }
 }
 
 impl Default for MinidumpThreadInfoList {
@@ -3092,6 +4721,11 @@ impl MinidumpThreadInfoList {
             .map(|&index| &self.thread_infos[index])
     }
 
+    /// Iterate over the thread infos in the order contained in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpThreadInfo> {
+        self.thread_infos.iter()
+    }
+
     /// Write a human-readable description of this `MinidumpModuleList` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -3139,6 +4773,271 @@ impl<'a> MinidumpStream<'a> for MinidumpThreadInfoList {
     }
 }
 
+const ARM_FEATURES: &[(md::ArmElfHwCaps, &str)] = &[
+    (md::ArmElfHwCaps::HWCAP_SWP, "swp"),
+    (md::ArmElfHwCaps::HWCAP_HALF, "half"),
+    (md::ArmElfHwCaps::HWCAP_THUMB, "thumb"),
+    (md::ArmElfHwCaps::HWCAP_26BIT, "26bit"),
+    (md::ArmElfHwCaps::HWCAP_FAST_MULT, "fastmult"),
+    (md::ArmElfHwCaps::HWCAP_FPA, "fpa"),
+    (md::ArmElfHwCaps::HWCAP_VFP, "vfpv2"),
+    (md::ArmElfHwCaps::HWCAP_EDSP, "edsp"),
+    (md::ArmElfHwCaps::HWCAP_JAVA, "java"),
+    (md::ArmElfHwCaps::HWCAP_IWMMXT, "iwmmxt"),
+    (md::ArmElfHwCaps::HWCAP_CRUNCH, "crunch"),
+    (md::ArmElfHwCaps::HWCAP_THUMBEE, "thumbee"),
+    (md::ArmElfHwCaps::HWCAP_NEON, "neon"),
+    (md::ArmElfHwCaps::HWCAP_VFPv3, "vfpv3"),
+    (md::ArmElfHwCaps::HWCAP_VFPv3D16, "vfpv3d16"),
+    (md::ArmElfHwCaps::HWCAP_TLS, "tls"),
+    (md::ArmElfHwCaps::HWCAP_VFPv4, "vfpv4"),
+    (md::ArmElfHwCaps::HWCAP_IDIVA, "idiva"),
+    (md::ArmElfHwCaps::HWCAP_IDIVT, "idivt"),
+];
+
+// These use the names Linux gives the same features in /proc/cpuinfo
+const X86_FEATURES: &[(md::X86CpuFeatures, &str)] = &[
+    (md::X86CpuFeatures::FPU, "fpu"),
+    (md::X86CpuFeatures::VME, "vme"),
+    (md::X86CpuFeatures::DE, "de"),
+    (md::X86CpuFeatures::PSE, "pse"),
+    (md::X86CpuFeatures::TSC, "tsc"),
+    (md::X86CpuFeatures::MSR, "msr"),
+    (md::X86CpuFeatures::PAE, "pae"),
+    (md::X86CpuFeatures::MCE, "mce"),
+    (md::X86CpuFeatures::CX8, "cx8"),
+    (md::X86CpuFeatures::APIC, "apic"),
+    (md::X86CpuFeatures::SEP, "sep"),
+    (md::X86CpuFeatures::MTRR, "mtrr"),
+    (md::X86CpuFeatures::PGE, "pge"),
+    (md::X86CpuFeatures::MCA, "mca"),
+    (md::X86CpuFeatures::CMOV, "cmov"),
+    (md::X86CpuFeatures::PAT, "pat"),
+    (md::X86CpuFeatures::PSE36, "pse36"),
+    (md::X86CpuFeatures::PSN, "pn"),
+    (md::X86CpuFeatures::CLFSH, "clflush"),
+    (md::X86CpuFeatures::DS, "dts"),
+    (md::X86CpuFeatures::ACPI, "acpi"),
+    (md::X86CpuFeatures::MMX, "mmx"),
+    (md::X86CpuFeatures::FXSR, "fxsr"),
+    (md::X86CpuFeatures::SSE, "sse"),
+    (md::X86CpuFeatures::SSE2, "sse2"),
+    (md::X86CpuFeatures::SS, "ss"),
+    (md::X86CpuFeatures::HTT, "ht"),
+    (md::X86CpuFeatures::TM, "tm"),
+    (md::X86CpuFeatures::PBE, "pbe"),
+];
+
+const X86_AMD_FEATURES: &[(md::X86AmdExtendedFeatures, &str)] = &[
+    (md::X86AmdExtendedFeatures::SYSCALL, "syscall"),
+    (md::X86AmdExtendedFeatures::NX, "nx"),
+    (md::X86AmdExtendedFeatures::MMXEXT, "mmxext"),
+    (md::X86AmdExtendedFeatures::FXSR_OPT, "fxsr_opt"),
+    (md::X86AmdExtendedFeatures::PDPE1GB, "pdpe1gb"),
+    (md::X86AmdExtendedFeatures::RDTSCP, "rdtscp"),
+    (md::X86AmdExtendedFeatures::LM, "lm"),
+    (md::X86AmdExtendedFeatures::_3DNOWEXT, "3dnowext"),
+    (md::X86AmdExtendedFeatures::_3DNOW, "3dnow"),
+];
+
+const PROCESSOR_FEATURES: &[(md::ProcessorFeatures, &str)] = &[
+    (
+        md::ProcessorFeatures::PF_FLOATING_POINT_PRECISION_ERRATA,
+        "fp_precision_errata",
+    ),
+    (
+        md::ProcessorFeatures::PF_FLOATING_POINT_EMULATED,
+        "fp_emulated",
+    ),
+    (md::ProcessorFeatures::PF_COMPARE_EXCHANGE_DOUBLE, "cx8"),
+    (md::ProcessorFeatures::PF_MMX_INSTRUCTIONS_AVAILABLE, "mmx"),
+    (
+        md::ProcessorFeatures::PF_PPC_MOVEMEM_64BIT_OK,
+        "ppc_movemem_64bit",
+    ),
+    (
+        md::ProcessorFeatures::PF_ALPHA_BYTE_INSTRUCTIONS,
+        "alpha_byte",
+    ),
+    (md::ProcessorFeatures::PF_XMMI_INSTRUCTIONS_AVAILABLE, "sse"),
+    (
+        md::ProcessorFeatures::PF_3DNOW_INSTRUCTIONS_AVAILABLE,
+        "3dnow",
+    ),
+    (md::ProcessorFeatures::PF_RDTSC_INSTRUCTION_AVAILABLE, "tsc"),
+    (md::ProcessorFeatures::PF_PAE_ENABLED, "pae"),
+    (
+        md::ProcessorFeatures::PF_XMMI64_INSTRUCTIONS_AVAILABLE,
+        "sse2",
+    ),
+    (md::ProcessorFeatures::PF_SSE_DAZ_MODE_AVAILABLE, "sse_daz"),
+    (md::ProcessorFeatures::PF_NX_ENABLED, "nx"),
+    (
+        md::ProcessorFeatures::PF_SSE3_INSTRUCTIONS_AVAILABLE,
+        "sse3",
+    ),
+    (md::ProcessorFeatures::PF_COMPARE_EXCHANGE128, "cx16"),
+    (
+        md::ProcessorFeatures::PF_COMPARE64_EXCHANGE128,
+        "cmp64xchg128",
+    ),
+    (md::ProcessorFeatures::PF_CHANNELS_ENABLED, "channels"),
+    (md::ProcessorFeatures::PF_XSAVE_ENABLED, "xsave"),
+    (
+        md::ProcessorFeatures::PF_ARM_VFP_32_REGISTERS_AVAILABLE,
+        "vfpd32",
+    ),
+    (
+        md::ProcessorFeatures::PF_ARM_NEON_INSTRUCTIONS_AVAILABLE,
+        "neon",
+    ),
+    (
+        md::ProcessorFeatures::PF_SECOND_LEVEL_ADDRESS_TRANSLATION,
+        "slat",
+    ),
+    (
+        md::ProcessorFeatures::PF_VIRT_FIRMWARE_ENABLED,
+        "virt_firmware",
+    ),
+    (md::ProcessorFeatures::PF_RDWRFSGSBASE_AVAILABLE, "fsgsbase"),
+    (md::ProcessorFeatures::PF_FASTFAIL_AVAILABLE, "fastfail"),
+    (
+        md::ProcessorFeatures::PF_ARM_DIVIDE_INSTRUCTION_AVAILABLE,
+        "idiv",
+    ),
+    (
+        md::ProcessorFeatures::PF_ARM_64BIT_LOADSTORE_ATOMIC,
+        "atomic64",
+    ),
+    (
+        md::ProcessorFeatures::PF_ARM_EXTERNAL_CACHE_AVAILABLE,
+        "external_cache",
+    ),
+    (
+        md::ProcessorFeatures::PF_ARM_FMAC_INSTRUCTIONS_AVAILABLE,
+        "fmac",
+    ),
+    (
+        md::ProcessorFeatures::PF_RDRAND_INSTRUCTION_AVAILABLE,
+        "rdrand",
+    ),
+    (
+        md::ProcessorFeatures::PF_ARM_V8_INSTRUCTIONS_AVAILABLE,
+        "armv8",
+    ),
+    (
+        md::ProcessorFeatures::PF_ARM_V8_CRYPTO_INSTRUCTIONS_AVAILABLE,
+        "crypto",
+    ),
+    (
+        md::ProcessorFeatures::PF_ARM_V8_CRC32_INSTRUCTIONS_AVAILABLE,
+        "crc32",
+    ),
+    (
+        md::ProcessorFeatures::PF_RDTSCP_INSTRUCTION_AVAILABLE,
+        "rdtscp",
+    ),
+    (
+        md::ProcessorFeatures::PF_RDPID_INSTRUCTION_AVAILABLE,
+        "rdpid",
+    ),
+    (
+        md::ProcessorFeatures::PF_ARM_V81_ATOMIC_INSTRUCTIONS_AVAILABLE,
+        "atomics",
+    ),
+    (
+        md::ProcessorFeatures::PF_MONITORX_INSTRUCTION_AVAILABLE,
+        "monitorx",
+    ),
+    (
+        md::ProcessorFeatures::PF_SSSE3_INSTRUCTIONS_AVAILABLE,
+        "ssse3",
+    ),
+    (
+        md::ProcessorFeatures::PF_SSE4_1_INSTRUCTIONS_AVAILABLE,
+        "sse4_1",
+    ),
+    (
+        md::ProcessorFeatures::PF_SSE4_2_INSTRUCTIONS_AVAILABLE,
+        "sse4_2",
+    ),
+    (md::ProcessorFeatures::PF_AVX_INSTRUCTIONS_AVAILABLE, "avx"),
+    (
+        md::ProcessorFeatures::PF_AVX2_INSTRUCTIONS_AVAILABLE,
+        "avx2",
+    ),
+    (
+        md::ProcessorFeatures::PF_AVX512F_INSTRUCTIONS_AVAILABLE,
+        "avx512f",
+    ),
+    (md::ProcessorFeatures::PF_ERMS_AVAILABLE, "erms"),
+    (
+        md::ProcessorFeatures::PF_ARM_V82_DP_INSTRUCTIONS_AVAILABLE,
+        "asimddp",
+    ),
+    (
+        md::ProcessorFeatures::PF_ARM_V83_JSCVT_INSTRUCTIONS_AVAILABLE,
+        "jscvt",
+    ),
+    (
+        md::ProcessorFeatures::PF_ARM_V83_LRCPC_INSTRUCTIONS_AVAILABLE,
+        "lrcpc",
+    ),
+];
+
+fn read_cpu_features(
+    os: Os,
+    cpu: Cpu,
+    data: &[u8],
+    endian: scroll::Endian,
+) -> Result<Vec<&'static str>, Error> {
+    fn present<F: Copy>(
+        contains: impl Fn(F) -> bool,
+        table: &[(F, &'static str)],
+    ) -> Vec<&'static str> {
+        table
+            .iter()
+            .filter(|&&(feature, _)| contains(feature))
+            .map(|&(_, name)| name)
+            .collect()
+    }
+
+    let features = match cpu {
+        Cpu::X86 => {
+            let x86_info: md::X86CpuInfo = data
+                .pread_with(0, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            let features = md::X86CpuFeatures::from_bits_truncate(x86_info.feature_information);
+            let amd_features =
+                md::X86AmdExtendedFeatures::from_bits_truncate(x86_info.amd_extended_cpu_features);
+            let mut names = present(|f| features.contains(f), X86_FEATURES);
+            // The extended features are only meaningful for AMD processors
+            let vendor = x86_info.vendor_id.map(u32::to_le_bytes);
+            if vendor.concat() == b"AuthenticAMD" {
+                names.extend(present(|f| amd_features.contains(f), X86_AMD_FEATURES));
+            }
+            names
+        }
+        Cpu::Arm => {
+            let arm_info: md::ARMCpuInfo = data
+                .pread_with(0, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            let features = md::ArmElfHwCaps::from_bits_truncate(arm_info.elf_hwcaps);
+            present(|f| features.contains(f), ARM_FEATURES)
+        }
+        _ if os == Os::Windows => {
+            let other_info: md::OtherCpuInfo = data
+                .pread_with(0, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            let features =
+                md::ProcessorFeatures::from_bits_truncate(other_info.processor_features[0]);
+            present(|f| features.contains(f), PROCESSOR_FEATURES)
+        }
+        _ => Vec::new(),
+    };
+    Ok(features)
+}
+
 impl<'a> MinidumpStream<'a> for MinidumpSystemInfo {
     const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::SystemInfoStream as u32;
 
@@ -3160,8 +5059,7 @@ impl<'a> MinidumpStream<'a> for MinidumpSystemInfo {
         let os = Os::from_platform_id(raw.platform_id);
         let cpu = Cpu::from_processor_architecture(raw.processor_architecture);
 
-        let mut csd_offset = raw.csd_version_rva as usize;
-        let csd_version = read_string_utf16(&mut csd_offset, all, endian);
+        let csd_version = read_utf16_string(raw.csd_version_rva.into(), all, endian).ok();
 
         // self.raw.cpu.data is actually a union which we resolve here.
         let cpu_info = match cpu {
@@ -3231,27 +5129,6 @@ impl<'a> MinidumpStream<'a> for MinidumpSystemInfo {
                     (0x510006f0, "Krait"),
                     (0x510000f0, "Scorpion"),
                 ];
-                let features = [
-                    (md::ArmElfHwCaps::HWCAP_SWP, "swp"),
-                    (md::ArmElfHwCaps::HWCAP_HALF, "half"),
-                    (md::ArmElfHwCaps::HWCAP_THUMB, "thumb"),
-                    (md::ArmElfHwCaps::HWCAP_26BIT, "26bit"),
-                    (md::ArmElfHwCaps::HWCAP_FAST_MULT, "fastmult"),
-                    (md::ArmElfHwCaps::HWCAP_FPA, "fpa"),
-                    (md::ArmElfHwCaps::HWCAP_VFP, "vfpv2"),
-                    (md::ArmElfHwCaps::HWCAP_EDSP, "edsp"),
-                    (md::ArmElfHwCaps::HWCAP_JAVA, "java"),
-                    (md::ArmElfHwCaps::HWCAP_IWMMXT, "iwmmxt"),
-                    (md::ArmElfHwCaps::HWCAP_CRUNCH, "crunch"),
-                    (md::ArmElfHwCaps::HWCAP_THUMBEE, "thumbee"),
-                    (md::ArmElfHwCaps::HWCAP_NEON, "neon"),
-                    (md::ArmElfHwCaps::HWCAP_VFPv3, "vfpv3"),
-                    (md::ArmElfHwCaps::HWCAP_VFPv3D16, "vfpv3d16"),
-                    (md::ArmElfHwCaps::HWCAP_TLS, "tls"),
-                    (md::ArmElfHwCaps::HWCAP_VFPv4, "vfpv4"),
-                    (md::ArmElfHwCaps::HWCAP_IDIVA, "idiva"),
-                    (md::ArmElfHwCaps::HWCAP_IDIVT, "idivt"),
-                ];
 
                 let mut cpu_info = format!("ARMv{}", raw.processor_level);
 
@@ -3282,7 +5159,7 @@ impl<'a> MinidumpStream<'a> for MinidumpSystemInfo {
 
                     // Iterator::intersperse is still unstable, so do it manually
                     let mut comma = "";
-                    for &(_, feature) in features
+                    for &(_, feature) in ARM_FEATURES
                         .iter()
                         .filter(|&&(feature, _)| elf_hwcaps.contains(feature))
                     {
@@ -3296,6 +5173,7 @@ impl<'a> MinidumpStream<'a> for MinidumpSystemInfo {
             }
             _ => None,
         };
+        let cpu_features = read_cpu_features(os, cpu, &raw.cpu.data, endian)?;
 
         Ok(MinidumpSystemInfo {
             raw,
@@ -3303,6 +5181,7 @@ impl<'a> MinidumpStream<'a> for MinidumpSystemInfo {
             cpu,
             csd_version,
             cpu_info,
+            cpu_features,
         })
     }
 }
@@ -3328,6 +5207,7 @@ impl MinidumpSystemInfo {
   suite_mask                                 = {:#x}
   (version)                                  = {}.{}.{} {}
   (cpu_info)                                 = {}
+  (cpu_features)                             = {}
 
 ",
             self.raw.processor_architecture,
@@ -3346,6 +5226,7 @@ impl MinidumpSystemInfo {
             self.raw.build_number,
             self.csd_version().as_deref().unwrap_or(""),
             self.cpu_info().as_deref().unwrap_or(""),
+            self.cpu_features.join(","),
         )?;
         // TODO: cpu info etc
         Ok(())
@@ -3364,6 +5245,21 @@ impl MinidumpSystemInfo {
         self.cpu_info.as_deref().map(Cow::Borrowed)
     }
 
+    /// The names of the cpu features the dump says are present.
+    ///
+    /// Where they come from depends on the cpu:
+    ///
+    /// - x86: the feature bits of `cpuid` (and AMD's extended ones).
+    /// - ARM: the ELF hardware capabilities (see [`md::ArmElfHwCaps`]).
+    /// - Anything else, on Windows: the `PF_*` processor features
+    ///   (see [`md::ProcessorFeatures`]).
+    ///
+    /// Other minidump writers don't agree on what the cpu information of other
+    /// cpus means, so this is empty for them.
+    pub fn cpu_features(&self) -> &[&'static str] {
+        &self.cpu_features
+    }
+
     /// Strings identifying the version and build number of the operating
     /// system. Returns a tuple in the format of (version, build number). This
     /// may be useful to use if the minidump was created on a Linux machine and
@@ -3733,10 +5629,16 @@ impl<'a> MinidumpStream<'a> for MinidumpMacCrashInfo {
                         let num_strings = <$strings>::num_strings();
                         let mut strings = <$strings>::default();
 
-                        // Read out all the strings we know about
+                        // Read out all the strings we know about. The record may have
+                        // been truncated by the writer, so strings that are missing or
+                        // unterminated are read as best we can rather than discarding
+                        // the whole record.
                         for i in 0..num_strings {
-                            let string = read_cstring_utf8(offset, record_slice)
-                                .ok_or(Error::StreamReadFailure)?;
+                            let string = read_cstring_lossy(
+                                offset,
+                                record_slice,
+                                md::MAC_CRASH_INFO_STRING_MAX_SIZE,
+                            );
                             strings.set_string(i, string);
                         }
                         // If this is a newer version, there may be some extra variable length
@@ -3837,8 +5739,7 @@ impl<'a> MinidumpStream<'a> for MinidumpMacBootargs {
                 Self::STREAM_TYPE
             );
         }
-        let mut bootargs_offset = raw.bootargs as usize;
-        let bootargs = read_string_utf16(&mut bootargs_offset, all, endian);
+        let bootargs = read_utf16_string(raw.bootargs, all, endian).ok();
 
         Ok(MinidumpMacBootargs { raw, bootargs })
     }
@@ -3882,6 +5783,19 @@ impl<'a> MinidumpStream<'a> for MinidumpLinuxEnviron<'a> {
     }
 }
 
+impl<'a> MinidumpStream<'a> for MinidumpLinuxCmdLine<'a> {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        _endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpLinuxCmdLine<'a>, Error> {
+        Ok(Self { data: bytes })
+    }
+}
+
 impl<'a> MinidumpStream<'a> for MinidumpLinuxProcStatus<'a> {
     const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::LinuxProcStatus as u32;
 
@@ -3908,74 +5822,331 @@ impl<'a> MinidumpStream<'a> for MinidumpLinuxProcLimits<'a> {
     }
 }
 
-impl<'a> MinidumpStream<'a> for MinidumpLinuxCpuInfo<'a> {
-    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::LinuxCpuInfo as u32;
+impl<'a> MinidumpStream<'a> for MinidumpLinuxDsoDebug {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::LinuxDsoDebug as u32;
 
     fn read(
         bytes: &'a [u8],
-        _all: &'a [u8],
-        _endian: scroll::Endian,
-        _system_info: Option<&MinidumpSystemInfo>,
-    ) -> Result<MinidumpLinuxCpuInfo<'a>, Error> {
-        Ok(Self { data: bytes })
+        all: &'a [u8],
+        endian: scroll::Endian,
+        system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpLinuxDsoDebug, Error> {
+        // The structs have the pointer size of the process, which we can also
+        // tell from the size of the stream if there's no system info.
+        let is_64_bit = match system_info.map(|info| info.cpu.pointer_width()) {
+            Some(PointerWidth::Bits32) => false,
+            Some(PointerWidth::Bits64) => true,
+            _ => bytes.len() >= md::DSO_DEBUG_64::size_with(&endian),
+        };
+        let read_link_maps = |map: md::RVA, count: u32, size: usize| {
+            let map_bytes = (count as usize)
+                .checked_mul(size)
+                .and_then(|len| all.get(map as usize..)?.get(..len))
+                .ok_or(Error::StreamReadFailure)?;
+            let name = |rva: md::RVA| read_utf16_string(rva.into(), all, endian).ok();
+            map_bytes
+                .chunks_exact(size)
+                .map(|entry| {
+                    let link_map = if is_64_bit {
+                        let raw: md::LINK_MAP_64 = entry.pread_with(0, endian)?;
+                        MinidumpLinkMap {
+                            load_bias: raw.addr,
+                            name: name(raw.name),
+                            dynamic: raw.ld,
+                        }
+                    } else {
+                        let raw: md::LINK_MAP_32 = entry.pread_with(0, endian)?;
+                        MinidumpLinkMap {
+                            load_bias: raw.addr.into(),
+                            name: name(raw.name),
+                            dynamic: raw.ld.into(),
+                        }
+                    };
+                    Ok(link_map)
+                })
+                .collect::<Result<Vec<_>, scroll::Error>>()
+                .or(Err(Error::StreamReadFailure))
+        };
+
+        if is_64_bit {
+            let raw: md::DSO_DEBUG_64 = bytes
+                .pread_with(0, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            let size = md::LINK_MAP_64::size_with(&endian);
+            Ok(MinidumpLinuxDsoDebug {
+                version: raw.version,
+                brk: raw.brk,
+                ldbase: raw.ldbase,
+                dynamic: raw.dynamic,
+                link_maps: read_link_maps(raw.map, raw.dso_count, size)?,
+            })
+        } else {
+            let raw: md::DSO_DEBUG_32 = bytes
+                .pread_with(0, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            let size = md::LINK_MAP_32::size_with(&endian);
+            Ok(MinidumpLinuxDsoDebug {
+                version: raw.version,
+                brk: raw.brk.into(),
+                ldbase: raw.ldbase.into(),
+                dynamic: raw.dynamic.into(),
+                link_maps: read_link_maps(raw.map, raw.dso_count, size)?,
+            })
+        }
     }
 }
 
-impl<'a> MinidumpLinuxCpuInfo<'a> {
-    /// Get an iterator over the key-value pairs stored in the `/proc/cpuinfo` dump.
-    ///
-    /// Keys and values are `trim`ed of leading/trailing spaces, and if a key
-    /// or value was surrounded by quotes ("like this"), the quotes will be
-    /// stripped.
-    pub fn iter(&self) -> impl Iterator<Item = (&'a LinuxOsStr, &'a LinuxOsStr)> {
-        linux_list_iter(self.data, b':')
+impl<'a> MinidumpStream<'a> for MinidumpLinuxAuxv {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::LinuxAuxv as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        endian: scroll::Endian,
+        system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpLinuxAuxv, Error> {
+        // The type and the value of the entries are pointer-sized.
+        let pointer_width =
+            system_info.map_or(PointerWidth::Unknown, |info| info.cpu.pointer_width());
+        let read_word = |offset: &mut usize| -> Option<u64> {
+            match pointer_width {
+                PointerWidth::Bits32 => bytes.gread_with::<u32>(offset, endian).ok().map(u64::from),
+                PointerWidth::Unknown | PointerWidth::Bits64 => {
+                    bytes.gread_with::<u64>(offset, endian).ok()
+                }
+            }
+        };
+
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        // A vector that's cut off (or missing its AT_NULL) is kept up to the
+        // last whole entry.
+        while let (Some(raw_type), Some(value)) = (read_word(&mut offset), read_word(&mut offset)) {
+            if raw_type == md::AuxvType::AT_NULL as u64 {
+                break;
+            }
+            entries.push(MinidumpLinuxAuxvEntry { raw_type, value });
+        }
+        Ok(MinidumpLinuxAuxv { entries })
     }
+}
 
-    /// Get the raw bytes of the `/proc/cpuinfo` dump.
-    pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
-        Cow::Borrowed(self.data)
+impl MinidumpLinuxAuxvEntry {
+    /// The type of the entry, if it's one we know.
+    pub fn known_type(&self) -> Option<md::AuxvType> {
+        md::AuxvType::from_u64(self.raw_type)
     }
 }
 
-impl<'a> MinidumpLinuxEnviron<'a> {
-    /// Get an iterator over the key-value pairs stored in the `/proc/self/environ` dump.
-    ///
-    /// Keys and values are `trim`ed of leading/trailing spaces, and if a key
-    /// or value was surrounded by quotes ("like this"), the quotes will be
-    /// stripped.
-    pub fn iter(&self) -> impl Iterator<Item = (&'a LinuxOsStr, &'a LinuxOsStr)> {
-        linux_list_iter(self.data, b'=')
+impl MinidumpLinuxAuxv {
+    /// Iterate over the entries in the order contained in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpLinuxAuxvEntry> {
+        self.entries.iter()
     }
 
-    /// Get the raw bytes of the `/proc/self/environ` dump.
-    pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
-        Cow::Borrowed(self.data)
+    /// The value of the (first) entry of type `ty`.
+    pub fn get(&self, ty: md::AuxvType) -> Option<u64> {
+        self.entries
+            .iter()
+            .find(|entry| entry.raw_type == ty as u64)
+            .map(|entry| entry.value)
     }
-}
 
-impl<'a> MinidumpLinuxProcStatus<'a> {
-    /// Get an iterator over the key-value pairs stored in the `/proc/self/status` dump.
+    /// The address the vdso is loaded at (`AT_SYSINFO_EHDR`).
     ///
-    /// Keys and values are `trim`ed of leading/trailing spaces, and if a key
-    /// or value was surrounded by quotes ("like this"), the quotes will be
-    /// stripped.
-    pub fn iter(&self) -> impl Iterator<Item = (&'a LinuxOsStr, &'a LinuxOsStr)> {
-        linux_list_iter(self.data, b':')
+    /// The vdso is the kernel's shared library in every process, whose code
+    /// shows up on the stack of threads in (or returning from) some system
+    /// calls and signal handlers.
+    pub fn vdso_base(&self) -> Option<u64> {
+        self.get(md::AuxvType::AT_SYSINFO_EHDR)
+            .filter(|&base| base != 0)
     }
 
-    /// Get the raw bytes of the `/proc/self/status` dump.
-    pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
-        Cow::Borrowed(self.data)
+    /// The CPU's features (`AT_HWCAP`), as a bitmask specific to the architecture.
+    pub fn hwcap(&self) -> Option<u64> {
+        self.get(md::AuxvType::AT_HWCAP)
     }
-}
 
-impl<'a> MinidumpLinuxProcLimits<'a> {
-    /// Get an iterator over the key-value pairs stored in the `/proc/self/limits` dump.
-    ///
-    /// Keys and values are `trim`ed of leading/trailing spaces, and if a key
-    /// or value was surrounded by quotes ("like this"), the quotes will be
-    /// stripped.
-    pub fn iter(&self) -> impl Iterator<Item = &'a LinuxOsStr> {
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpLinuxAuxv
+  entry_count = {}
+
+",
+            self.entries.len()
+        )?;
+        for entry in &self.entries {
+            match entry.known_type() {
+                Some(ty) => writeln!(f, "  {:?} = {:#x}", ty, entry.value)?,
+                None => writeln!(f, "  {:#x} = {:#x}", entry.raw_type, entry.value)?,
+            }
+        }
+        writeln!(f)
+    }
+}
+
+impl MinidumpLinuxDsoDebug {
+    /// The loaded objects that aren't in `modules`.
+    ///
+    /// Objects are matched by the file name of their path, because the linker
+    /// has the path they were loaded with while the module list has the path
+    /// of their mapping. The main executable and the vdso, which don't have
+    /// paths of their own, are left out.
+    pub fn unlisted_objects<'s>(
+        &'s self,
+        modules: &'s MinidumpModuleList,
+    ) -> impl Iterator<Item = &'s MinidumpLinkMap> + 's {
+        let listed: HashSet<String> = modules
+            .iter()
+            .map(|module| basename(&module.code_file()).to_owned())
+            .collect();
+        self.link_maps.iter().filter(move |link_map| {
+            link_map
+                .name
+                .as_deref()
+                .is_some_and(|name| name.contains('/') && !listed.contains(basename(name)))
+        })
+    }
+
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpLinuxDsoDebug
+  version   = {}
+  brk       = {:#x}
+  ldbase    = {:#x}
+  dynamic   = {:#x}
+  dso_count = {}
+
+",
+            self.version,
+            self.brk,
+            self.ldbase,
+            self.dynamic,
+            self.link_maps.len(),
+        )?;
+        for (i, link_map) in self.link_maps.iter().enumerate() {
+            write!(
+                f,
+                "link_map[{}]
+  addr = {:#x}
+  name = \"{}\"
+  ld   = {:#x}
+
+",
+                i,
+                link_map.load_bias,
+                link_map.name.as_deref().unwrap_or("(invalid)"),
+                link_map.dynamic,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpLinuxCpuInfo<'a> {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::LinuxCpuInfo as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        _endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpLinuxCpuInfo<'a>, Error> {
+        Ok(Self { data: bytes })
+    }
+}
+
+impl<'a> MinidumpLinuxCpuInfo<'a> {
+    /// Get an iterator over the key-value pairs stored in the `/proc/cpuinfo` dump.
+    ///
+    /// Keys and values are `trim`ed of leading/trailing spaces, and if a key
+    /// or value was surrounded by quotes ("like this"), the quotes will be
+    /// stripped.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a LinuxOsStr, &'a LinuxOsStr)> {
+        linux_list_iter(self.data, b'\n', b':')
+    }
+
+    /// Get the raw bytes of the `/proc/cpuinfo` dump.
+    pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
+        Cow::Borrowed(self.data)
+    }
+}
+
+impl<'a> MinidumpLinuxEnviron<'a> {
+    /// Get an iterator over the key-value pairs stored in the `/proc/self/environ` dump.
+    ///
+    /// The variables are separated by NULs like in `/proc/self/environ`, or by
+    /// newlines if there are no NULs at all.
+    ///
+    /// Keys and values are `trim`ed of leading/trailing spaces, and if a key
+    /// or value was surrounded by quotes ("like this"), the quotes will be
+    /// stripped.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a LinuxOsStr, &'a LinuxOsStr)> {
+        let separator = if self.data.contains(&0) { 0 } else { b'\n' };
+        linux_list_iter(self.data, separator, b'=')
+    }
+
+    /// Get the value of the environment variable `name` (like `LD_PRELOAD`).
+    ///
+    /// If the variable is set several times, this is the first value, the one
+    /// `getenv` returns.
+    pub fn get(&self, name: &str) -> Option<&'a LinuxOsStr> {
+        self.iter()
+            .find(|(key, _)| key.as_bytes() == name.as_bytes())
+            .map(|(_, val)| val)
+    }
+
+    /// Get the raw bytes of the `/proc/self/environ` dump.
+    pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
+        Cow::Borrowed(self.data)
+    }
+}
+
+impl<'a> MinidumpLinuxCmdLine<'a> {
+    /// Get an iterator over the arguments of the process, starting with the
+    /// name it was started as.
+    pub fn args(&self) -> impl Iterator<Item = &'a LinuxOsStr> {
+        // Each argument is NUL-terminated, including the last one
+        let data = self.data.strip_suffix(b"\0").unwrap_or(self.data);
+        (!data.is_empty())
+            .then(|| LinuxOsStr::from_bytes(data).split(0))
+            .into_iter()
+            .flatten()
+    }
+
+    /// Get the raw bytes of the `/proc/self/cmdline` dump.
+    pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
+        Cow::Borrowed(self.data)
+    }
+}
+
+impl<'a> MinidumpLinuxProcStatus<'a> {
+    /// Get an iterator over the key-value pairs stored in the `/proc/self/status` dump.
+    ///
+    /// Keys and values are `trim`ed of leading/trailing spaces, and if a key
+    /// or value was surrounded by quotes ("like this"), the quotes will be
+    /// stripped.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a LinuxOsStr, &'a LinuxOsStr)> {
+        linux_list_iter(self.data, b'\n', b':')
+    }
+
+    /// Get the raw bytes of the `/proc/self/status` dump.
+    pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
+        Cow::Borrowed(self.data)
+    }
+}
+
+impl<'a> MinidumpLinuxProcLimits<'a> {
+    /// Get an iterator over the key-value pairs stored in the `/proc/self/limits` dump.
+    ///
+    /// Keys and values are `trim`ed of leading/trailing spaces, and if a key
+    /// or value was surrounded by quotes ("like this"), the quotes will be
+    /// stripped.
+    pub fn iter(&self) -> impl Iterator<Item = &'a LinuxOsStr> {
         LinuxOsStr::from_bytes(self.data).lines()
     }
 
@@ -3992,7 +6163,7 @@ impl<'a> MinidumpLinuxLsbRelease<'a> {
     /// or value was surrounded by quotes ("like this"), the quotes will be
     /// stripped.
     pub fn iter(&self) -> impl Iterator<Item = (&'a LinuxOsStr, &'a LinuxOsStr)> {
-        linux_list_iter(self.data, b'=')
+        linux_list_iter(self.data, b'\n', b'=')
     }
 
     /// Get the raw bytes of the `/etc/lsb-release` dump.
@@ -4012,6 +6183,54 @@ impl MinidumpMiscInfo {
             .and_then(|t| systemtime_from_timestamp(*t as u64))
     }
 
+    /// The build string of the operating system, if the dump has one.
+    ///
+    /// On Windows this is the "build lab" string, e.g.
+    /// `"19041.1.amd64fre.vb_release.191206-1406"`.
+    pub fn build_string(&self) -> Option<String> {
+        self.raw
+            .build_string()
+            .map(|string| utf16_array_to_string(&string[..]))
+            .filter(|string| !string.is_empty())
+    }
+
+    /// The build string of the debugging library (dbghelp.dll) that wrote
+    /// the dump, if the dump has one.
+    pub fn debug_build_string(&self) -> Option<String> {
+        self.raw
+            .dbg_bld_str()
+            .map(|string| utf16_array_to_string(&string[..]))
+            .filter(|string| !string.is_empty())
+    }
+
+    /// The time zone of the system, if the dump has one.
+    pub fn time_zone(&self) -> Option<MinidumpTimeZone> {
+        let &id = self.raw.time_zone_id()?;
+        let time_zone = self.raw.time_zone()?;
+        Some(MinidumpTimeZone {
+            id,
+            bias: time_zone.bias,
+            standard_name: utf16_array_to_string(&time_zone.standard_name[..]),
+            standard_bias: time_zone.standard_bias,
+            daylight_name: utf16_array_to_string(&time_zone.daylight_name[..]),
+            daylight_bias: time_zone.daylight_bias,
+        })
+    }
+
+    /// The process's cookie, which Windows mixes into encoded pointers (see
+    /// `EncodePointer`), if the dump has it.
+    pub fn process_cookie(&self) -> Option<u32> {
+        self.raw.process_cookie().copied()
+    }
+
+    /// The layout of the XSTATE area of the thread contexts, if the dump has it.
+    ///
+    /// This tells where (and whether) the CPU's extended state, like the AVX
+    /// registers, is in each context (see [`MinidumpThread::xstate`]).
+    pub fn xstate_config(&self) -> Option<&md::XSTATE_CONFIG_FEATURE_MSC_INFO> {
+        self.raw.xstate_data()
+    }
+
     /// Write a human-readable description of this `MinidumpMiscInfo` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -4069,8 +6288,7 @@ impl MinidumpMiscInfo {
                 writeln!(
                     f,
                     "    standard_name = {}",
-                    utf16_to_string(&time_zone.standard_name[..])
-                        .unwrap_or_else(|| String::from("(invalid)"))
+                    utf16_array_to_string(&time_zone.standard_name[..])
                 )?;
                 writeln!(
                     f,
@@ -4081,8 +6299,7 @@ impl MinidumpMiscInfo {
                 writeln!(
                     f,
                     "    daylight_name = {}",
-                    utf16_to_string(&time_zone.daylight_name[..])
-                        .unwrap_or_else(|| String::from("(invalid)"))
+                    utf16_array_to_string(&time_zone.daylight_name[..])
                 )?;
                 writeln!(
                     f,
@@ -4098,7 +6315,7 @@ impl MinidumpMiscInfo {
         match self
             .raw
             .build_string()
-            .and_then(|string| utf16_to_string(&string[..]))
+            .map(|string| utf16_array_to_string(&string[..]))
         {
             Some(build_string) => writeln!(f, "{build_string}")?,
             None => writeln!(f, "(invalid)")?,
@@ -4107,7 +6324,7 @@ impl MinidumpMiscInfo {
         match self
             .raw
             .dbg_bld_str()
-            .and_then(|string| utf16_to_string(&string[..]))
+            .map(|string| utf16_array_to_string(&string[..]))
         {
             Some(dbg_bld_str) => writeln!(f, "{dbg_bld_str}")?,
             None => writeln!(f, "(invalid)")?,
@@ -4136,6 +6353,288 @@ impl MinidumpMiscInfo {
     }
 }
 
+impl<'a> MinidumpStream<'a> for MinidumpSystemMemoryInfo {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream as u32;
+
+    fn read(
+        bytes: &[u8],
+        _all: &[u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpSystemMemoryInfo, Error> {
+        let raw: md::MINIDUMP_SYSTEM_MEMORY_INFO_1 = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        Ok(MinidumpSystemMemoryInfo { raw })
+    }
+}
+
+impl MinidumpSystemMemoryInfo {
+    /// Which of the optional fields are valid.
+    pub fn flags(&self) -> md::SystemMemoryInfoFlags {
+        md::SystemMemoryInfoFlags::from_bits_truncate(self.raw.flags)
+    }
+
+    /// The size of a page in bytes.
+    pub fn page_size(&self) -> u64 {
+        self.raw.basic_info.page_size.into()
+    }
+
+    /// The amount of physical memory of the system, in bytes.
+    pub fn physical_memory(&self) -> u64 {
+        self.pages(self.raw.basic_info.number_of_physical_pages.into())
+    }
+
+    /// The amount of physical memory that was available, in bytes.
+    pub fn available_physical_memory(&self) -> u64 {
+        self.pages(self.basic_perf_info().available_pages)
+    }
+
+    /// The memory that was committed (the commit charge of the whole system),
+    /// in bytes.
+    pub fn committed(&self) -> u64 {
+        self.pages(self.basic_perf_info().committed_pages)
+    }
+
+    /// How much memory can be committed (the size of physical memory and the
+    /// page files), in bytes.
+    pub fn commit_limit(&self) -> u64 {
+        self.pages(self.basic_perf_info().commit_limit)
+    }
+
+    /// The most memory that was ever committed at once, in bytes.
+    pub fn peak_commitment(&self) -> u64 {
+        self.pages(self.basic_perf_info().peak_commitment)
+    }
+
+    /// The 64-bit page counts, or the 32-bit ones of the performance info if
+    /// they're missing.
+    fn basic_perf_info(&self) -> md::MINIDUMP_SYSTEM_BASIC_PERFORMANCE_INFORMATION {
+        if self
+            .flags()
+            .contains(md::SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_BASICPERF)
+        {
+            return self.raw.basic_perf_info.clone();
+        }
+        let perf_info = &self.raw.perf_info;
+        md::MINIDUMP_SYSTEM_BASIC_PERFORMANCE_INFORMATION {
+            available_pages: perf_info.available_pages.into(),
+            committed_pages: perf_info.committed_pages.into(),
+            commit_limit: perf_info.commit_limit.into(),
+            peak_commitment: perf_info.peak_commitment.into(),
+        }
+    }
+
+    fn pages(&self, count: u64) -> u64 {
+        count.saturating_mul(self.page_size())
+    }
+
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        let basic_info = &self.raw.basic_info;
+        let perf_info = self.basic_perf_info();
+        write!(
+            f,
+            "MINIDUMP_SYSTEM_MEMORY_INFO_1
+  revision                  = {}
+  flags                     = {:#x}
+  page_size                 = {:#x}
+  number_of_physical_pages  = {:#x}
+  allocation_granularity    = {:#x}
+  minimum_user_mode_address = {:#x}
+  maximum_user_mode_address = {:#x}
+  number_of_processors      = {}
+  file_cache_current_size   = {:#x}
+  file_cache_peak_size      = {:#x}
+  available_pages           = {:#x}
+  committed_pages           = {:#x}
+  commit_limit              = {:#x}
+  peak_commitment           = {:#x}
+
+",
+            self.raw.revision,
+            self.raw.flags,
+            basic_info.page_size,
+            basic_info.number_of_physical_pages,
+            basic_info.allocation_granularity,
+            basic_info.minimum_user_mode_address,
+            basic_info.maximum_user_mode_address,
+            basic_info.number_of_processors,
+            self.raw.file_cache_info.current_size,
+            self.raw.file_cache_info.peak_size,
+            perf_info.available_pages,
+            perf_info.committed_pages,
+            perf_info.commit_limit,
+            perf_info.peak_commitment,
+        )
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpProcessVmCounters {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::ProcessVmCountersStream as u32;
+
+    fn read(
+        bytes: &[u8],
+        _all: &[u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpProcessVmCounters, Error> {
+        let revision: u16 = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        // Later revisions only add fields.
+        let raw = if revision >= 2 {
+            RawProcessVmCounters::V2(
+                bytes
+                    .pread_with(0, endian)
+                    .or(Err(Error::StreamReadFailure))?,
+            )
+        } else {
+            RawProcessVmCounters::V1(
+                bytes
+                    .pread_with(0, endian)
+                    .or(Err(Error::StreamReadFailure))?,
+            )
+        };
+        Ok(MinidumpProcessVmCounters { raw })
+    }
+}
+
+impl MinidumpProcessVmCounters {
+    /// Whether the fields guarded by `flag` are valid.
+    fn has(&self, flag: md::ProcessVmCountersFlags) -> bool {
+        match &self.raw {
+            // The first revision has the basic counters and the private usage.
+            RawProcessVmCounters::V1(_) => {
+                (md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS
+                    | md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_EX)
+                    .contains(flag)
+            }
+            RawProcessVmCounters::V2(raw) => {
+                md::ProcessVmCountersFlags::from_bits_truncate(raw.flags).contains(flag)
+            }
+        }
+    }
+
+    /// The number of page faults of the process.
+    pub fn page_fault_count(&self) -> Option<u32> {
+        let count = match &self.raw {
+            RawProcessVmCounters::V1(raw) => raw.page_fault_count,
+            RawProcessVmCounters::V2(raw) => raw.page_fault_count,
+        };
+        Some(count).filter(|_| self.has(md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS))
+    }
+
+    /// The physical memory the process was using (its working set).
+    pub fn working_set_size(&self) -> Option<u64> {
+        let size = match &self.raw {
+            RawProcessVmCounters::V1(raw) => raw.working_set_size,
+            RawProcessVmCounters::V2(raw) => raw.working_set_size,
+        };
+        Some(size).filter(|_| self.has(md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS))
+    }
+
+    /// The most physical memory the process ever used.
+    pub fn peak_working_set_size(&self) -> Option<u64> {
+        let size = match &self.raw {
+            RawProcessVmCounters::V1(raw) => raw.peak_working_set_size,
+            RawProcessVmCounters::V2(raw) => raw.peak_working_set_size,
+        };
+        Some(size).filter(|_| self.has(md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS))
+    }
+
+    /// The memory the process had committed (its commit charge).
+    pub fn pagefile_usage(&self) -> Option<u64> {
+        let size = match &self.raw {
+            RawProcessVmCounters::V1(raw) => raw.pagefile_usage,
+            RawProcessVmCounters::V2(raw) => raw.pagefile_usage,
+        };
+        Some(size).filter(|_| self.has(md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS))
+    }
+
+    /// The most memory the process ever had committed.
+    pub fn peak_pagefile_usage(&self) -> Option<u64> {
+        let size = match &self.raw {
+            RawProcessVmCounters::V1(raw) => raw.peak_pagefile_usage,
+            RawProcessVmCounters::V2(raw) => raw.peak_pagefile_usage,
+        };
+        Some(size).filter(|_| self.has(md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS))
+    }
+
+    /// The memory that was private to the process (committed and not shared).
+    pub fn private_usage(&self) -> Option<u64> {
+        let size = match &self.raw {
+            RawProcessVmCounters::V1(raw) => raw.private_usage,
+            RawProcessVmCounters::V2(raw) => raw.private_usage,
+        };
+        Some(size).filter(|_| self.has(md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_EX))
+    }
+
+    /// The size of the process's address space that was in use (reserved or
+    /// committed).
+    pub fn virtual_size(&self) -> Option<u64> {
+        match &self.raw {
+            RawProcessVmCounters::V2(raw)
+                if self
+                    .has(md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE) =>
+            {
+                Some(raw.virtual_size)
+            }
+            _ => None,
+        }
+    }
+
+    /// The most address space the process ever used.
+    pub fn peak_virtual_size(&self) -> Option<u64> {
+        match &self.raw {
+            RawProcessVmCounters::V2(raw)
+                if self
+                    .has(md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE) =>
+            {
+                Some(raw.peak_virtual_size)
+            }
+            _ => None,
+        }
+    }
+
+    /// The physical memory that was private to the process.
+    pub fn private_working_set_size(&self) -> Option<u64> {
+        match &self.raw {
+            RawProcessVmCounters::V2(raw)
+                if self.has(md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_EX2) =>
+            {
+                Some(raw.private_working_set_size)
+            }
+            _ => None,
+        }
+    }
+
+    /// Write a human-readable description.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        let revision = match &self.raw {
+            RawProcessVmCounters::V1(raw) => raw.revision,
+            RawProcessVmCounters::V2(raw) => raw.revision,
+        };
+        writeln!(f, "MINIDUMP_PROCESS_VM_COUNTERS")?;
+        writeln!(f, "  revision                 = {revision}")?;
+        let fields = [
+            ("page_fault_count", self.page_fault_count().map(u64::from)),
+            ("peak_working_set_size", self.peak_working_set_size()),
+            ("working_set_size", self.working_set_size()),
+            ("pagefile_usage", self.pagefile_usage()),
+            ("peak_pagefile_usage", self.peak_pagefile_usage()),
+            ("private_usage", self.private_usage()),
+            ("virtual_size", self.virtual_size()),
+            ("peak_virtual_size", self.peak_virtual_size()),
+            ("private_working_set_size", self.private_working_set_size()),
+        ];
+        for (name, value) in fields {
+            writeln!(f, "  {:25}= {}", name, option_or_invalid(&value))?;
+        }
+        writeln!(f)
+    }
+}
+
 impl<'a> MinidumpStream<'a> for MinidumpBreakpadInfo {
     const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::BreakpadInfoStream as u32;
 
@@ -4472,6 +6971,16 @@ impl CrashReason {
                     reason = CrashReason::MacGuard(ty, info[1], info[2]);
                 }
             }
+            ExceptionCodeMac::EXC_CRASH => {
+                // See `ExceptionCodeMacSignal` for the layout of the code
+                if let Some(signal) =
+                    err::ExceptionCodeMacSignal::from_u32((exception_flags >> 24) & 0xff)
+                {
+                    let original = (exception_flags >> 20) & 0xf;
+                    let original = ExceptionCodeMac::from_u32(original);
+                    reason = CrashReason::MacCrash(original, signal);
+                }
+            }
             _ => {
                 // Do nothing
             }
@@ -4766,6 +7275,10 @@ impl fmt::Display for CrashReason {
             MacBreakpointX86(ex) => write!(f, "EXC_BREAKPOINT / {ex:?}"),
             MacResource(ex, code, subcode) => write_exc_resource(f, ex, code, subcode),
             MacGuard(ex, code, subcode) => write_exc_guard(f, ex, code, subcode),
+            MacCrash(Some(original), signal) => {
+                write!(f, "EXC_CRASH / {original:?} / {signal:?}")
+            }
+            MacCrash(None, signal) => write!(f, "EXC_CRASH / {signal:?}"),
 
             // ===================== Linux/Android =========================
 
@@ -4834,6 +7347,49 @@ impl<'a> MinidumpStream<'a> for MinidumpException<'a> {
     }
 }
 
+/// The most records [`MinidumpException::exception_chain`] follows.
+pub const MAX_EXCEPTION_CHAIN_LENGTH: usize = 16;
+
+/// Read an `EXCEPTION_RECORD` of a process with pointers of `pointer_width` from its memory.
+///
+/// The records of 64-bit processes have the layout of [`md::MINIDUMP_EXCEPTION`],
+/// 32-bit ones have 32-bit pointers and parameters (and no padding).
+fn read_exception_record(
+    memory: &UnifiedMemoryList,
+    address: u64,
+    pointer_width: PointerWidth,
+) -> Option<md::MINIDUMP_EXCEPTION> {
+    let region = memory.memory_at_address(address)?;
+    let record = match pointer_width {
+        PointerWidth::Bits64 => region.get_memory_at_address::<md::MINIDUMP_EXCEPTION>(address)?,
+        PointerWidth::Bits32 => {
+            let word = |offset: u64| {
+                region
+                    .get_memory_at_address::<u32>(address.checked_add(offset)?)
+                    .map(u64::from)
+            };
+            let mut record = md::MINIDUMP_EXCEPTION {
+                exception_code: word(0)? as u32,
+                exception_flags: word(4)? as u32,
+                exception_record: word(8)?,
+                exception_address: word(12)?,
+                number_parameters: word(16)? as u32,
+                ..Default::default()
+            };
+            for (i, parameter) in record.exception_information.iter_mut().enumerate() {
+                *parameter = word(20 + 4 * i as u64)?;
+            }
+            record
+        }
+        PointerWidth::Unknown => return None,
+    };
+    // Garbage, rather than a record.
+    if record.number_parameters as usize > record.exception_information.len() {
+        return None;
+    }
+    Some(record)
+}
+
 impl<'a> MinidumpException<'a> {
     /// Get the cpu context of the crashing (or otherwise minidump-requesting) thread.
     ///
@@ -4855,9 +7411,20 @@ impl<'a> MinidumpException<'a> {
             .map(Cow::Owned)
     }
 
-    /// Get the address that "caused" the crash.
+    /// The XSAVE state of the exception's context (e.g. the YMM registers), if it has any.
     ///
-    /// The meaning of this value depends on the kind of crash this was.
+    /// See [`MinidumpContext::read_xstate`].
+    pub fn xstate(
+        &self,
+        system_info: &MinidumpSystemInfo,
+        misc: Option<&MinidumpMiscInfo>,
+    ) -> Option<ContextXstate> {
+        MinidumpContext::read_xstate(self.context?, self.endian, system_info, misc)
+    }
+
+    /// Get the address that "caused" the crash.
+    ///
+    /// The meaning of this value depends on the kind of crash this was.
     ///
     /// By default, it's the instruction pointer at the time of the crash.
     /// However, if the crash was caused by an illegal memory access, the
@@ -4902,6 +7469,58 @@ impl<'a> MinidumpException<'a> {
         CrashReason::from_exception(&self.raw, os, cpu)
     }
 
+    /// Get the decoded parameters of the exception, if there are any.
+    ///
+    /// See [`CrashReasonDetail`] for what is decoded. Exceptions whose
+    /// parameters we don't understand report them raw.
+    pub fn get_crash_reason_detail(&self, os: Os, cpu: Cpu) -> Option<CrashReasonDetail> {
+        CrashReasonDetail::from_exception(&self.raw, os, cpu)
+    }
+
+    /// The chain of nested exceptions this exception is the outermost of.
+    ///
+    /// On Windows, an exception raised while handling another (or wrapping it,
+    /// like an unhandled C++ exception or `EXCEPTION_NONCONTINUABLE_EXCEPTION`)
+    /// points to the record of the exception it happened during. Only the
+    /// outermost record is in the exception stream, the nested ones are in the
+    /// crashing process's memory, so they can only be found if that was captured.
+    ///
+    /// The chain starts with this exception and ends with the innermost one.
+    /// It's cut short, without an error, at a record that isn't in `memory` (or
+    /// doesn't look like one), after a record that was already seen, and after
+    /// [`MAX_EXCEPTION_CHAIN_LENGTH`] records. The nested exceptions have no
+    /// context, and the thread id of this one.
+    pub fn exception_chain(
+        &self,
+        memory: &UnifiedMemoryList,
+        cpu: Cpu,
+    ) -> Vec<MinidumpException<'a>> {
+        let mut chain = vec![MinidumpException {
+            raw: self.raw.clone(),
+            thread_id: self.thread_id,
+            context: self.context,
+            endian: self.endian,
+        }];
+        let mut seen = Vec::new();
+        let mut next = self.raw.exception_record.exception_record;
+        while next != 0 && chain.len() < MAX_EXCEPTION_CHAIN_LENGTH && !seen.contains(&next) {
+            seen.push(next);
+            let Some(record) = read_exception_record(memory, next, cpu.pointer_width()) else {
+                break;
+            };
+            next = record.exception_record;
+            let mut raw = self.raw.clone();
+            raw.exception_record = record;
+            chain.push(MinidumpException {
+                raw,
+                thread_id: self.thread_id,
+                context: None,
+                endian: self.endian,
+            });
+        }
+        chain
+    }
+
     /// The id of the thread that caused the crash (or otherwise requested
     /// the minidump, even if there wasn't actually a crash).
     pub fn get_crashing_thread_id(&self) -> u32 {
@@ -4952,6 +7571,9 @@ impl<'a> MinidumpException<'a> {
             if let Some(context) = self.context(system_info, misc) {
                 writeln!(f)?;
                 context.print(f)?;
+                if let Some(xstate) = self.xstate(system_info, misc) {
+                    xstate.print(f)?;
+                }
             } else {
                 write!(
                     f,
@@ -4988,29 +7610,60 @@ impl<'a> MinidumpStream<'a> for MinidumpAssertion {
     }
 }
 
-fn utf16_to_string(data: &[u16]) -> Option<String> {
-    use std::slice;
-
-    let len = data.iter().take_while(|c| **c != 0).count();
-    let s16 = &data[..len];
-    let bytes = unsafe { slice::from_raw_parts(s16.as_ptr() as *const u8, s16.len() * 2) };
-    encoding_rs::UTF_16LE
-        .decode_without_bom_handling_and_without_replacement(bytes)
-        .map(String::from)
-}
-
 impl MinidumpAssertion {
     /// Get the assertion expression as a `String` if one exists.
     pub fn expression(&self) -> Option<String> {
-        utf16_to_string(&self.raw.expression)
+        Some(utf16_array_to_string(&self.raw.expression))
     }
     /// Get the function name where the assertion happened as a `String` if it exists.
     pub fn function(&self) -> Option<String> {
-        utf16_to_string(&self.raw.function)
+        Some(utf16_array_to_string(&self.raw.function))
     }
     /// Get the source file name where the assertion happened as a `String` if it exists.
     pub fn file(&self) -> Option<String> {
-        utf16_to_string(&self.raw.file)
+        Some(utf16_array_to_string(&self.raw.file))
+    }
+    /// Get the line of the source file where the assertion happened if it's known.
+    pub fn line(&self) -> Option<u32> {
+        Some(self.raw.line).filter(|&line| line != 0)
+    }
+    /// Get the type of the assertion if it's a known one.
+    pub fn assertion_type(&self) -> Option<md::AssertionType> {
+        md::AssertionType::from_u32(self.raw._type)
+    }
+
+    /// A description of the assertion, in the format of Breakpad's processor: its
+    /// type, followed by the expression, the function, the file and the line
+    /// that are known.
+    ///
+    /// E.g. `"Invalid parameter passed to library function format != nullptr
+    /// in function common_vfprintf, in file output.cpp at line 32"`.
+    pub fn description(&self) -> String {
+        let mut description = match self.assertion_type() {
+            Some(md::AssertionType::InvalidParameter) => {
+                String::from("Invalid parameter passed to library function")
+            }
+            Some(md::AssertionType::PureVirtualCall) => {
+                String::from("Pure virtual function called")
+            }
+            Some(md::AssertionType::Unknown) | None => {
+                format!("Unknown assertion type {:#010x}", self.raw._type)
+            }
+        };
+        let non_empty = |string: Option<String>| string.filter(|string| !string.is_empty());
+        if let Some(expression) = non_empty(self.expression()) {
+            description += &format!(" {expression}");
+        }
+        if let Some(function) = non_empty(self.function()) {
+            description += &format!(" in function {function}");
+        }
+        if let Some(file) = non_empty(self.file()) {
+            description += &format!(", in file {file}");
+        }
+        if let Some(line) = self.line() {
+            description += &format!(" at line {line}");
+        }
+        description
     }
 
     /// Write a human-readable description of this `MinidumpAssertion` to `f`.
@@ -5061,9 +7714,7 @@ fn read_string_list(
             .gread_with(&mut offset, endian)
             .or(Err(Error::StreamReadFailure))?;
 
-        let string = read_string_utf8(&mut (rva as usize), all, endian)
-            .ok_or(Error::StreamReadFailure)?
-            .to_owned();
+        let string = read_utf8_string(rva.into(), all, endian).map_err(Error::StringReadFailure)?;
 
         strings.push(string);
     }
@@ -5094,12 +7745,12 @@ fn read_simple_string_dictionary(
             .gread_with(&mut offset, endian)
             .or(Err(Error::StreamReadFailure))?;
 
-        let key = read_string_utf8(&mut (entry.key as usize), all, endian)
-            .ok_or(Error::StreamReadFailure)?;
-        let value = read_string_utf8(&mut (entry.value as usize), all, endian)
-            .ok_or(Error::StreamReadFailure)?;
+        let key =
+            read_utf8_string(entry.key.into(), all, endian).map_err(Error::StringReadFailure)?;
+        let value =
+            read_utf8_string(entry.value.into(), all, endian).map_err(Error::StringReadFailure)?;
 
-        dictionary.insert(key.to_owned(), value.to_owned());
+        dictionary.insert(key, value);
     }
 
     Ok(dictionary)
@@ -5128,15 +7779,14 @@ fn read_annotation_objects(
             .gread_with(&mut offset, endian)
             .or(Err(Error::StreamReadFailure))?;
 
-        let key = read_string_utf8(&mut (raw.name as usize), all, endian)
-            .ok_or(Error::StreamReadFailure)?;
+        let key =
+            read_utf8_string(raw.name.into(), all, endian).map_err(Error::StringReadFailure)?;
 
         let value = match raw.ty {
             md::MINIDUMP_ANNOTATION::TYPE_INVALID => MinidumpAnnotation::Invalid,
             md::MINIDUMP_ANNOTATION::TYPE_STRING => {
-                let string = read_string_utf8_unterminated(&mut (raw.value as usize), all, endian)
-                    .ok_or(Error::StreamReadFailure)?
-                    .to_owned();
+                let string = read_utf8_string(raw.value.into(), all, endian)
+                    .map_err(Error::StringReadFailure)?;
 
                 MinidumpAnnotation::String(string)
             }
@@ -5146,7 +7796,7 @@ fn read_annotation_objects(
             _ => MinidumpAnnotation::Unsupported(raw),
         };
 
-        dictionary.insert(key.to_owned(), value);
+        dictionary.insert(key, value);
     }
 
     Ok(dictionary)
@@ -5313,6 +7963,12 @@ impl MinidumpCrashpadInfo {
 /// An index into the contents of a memory-mapped minidump.
 pub type MmapMinidump = Minidump<'static, Mmap>;
 
+/// An index into the contents of a minidump borrowed from a byte slice.
+///
+/// This is useful if the minidump is already in memory (e.g. because it was
+/// fetched from a database), and is too big to copy.
+pub type SliceMinidump<'a> = Minidump<'a, &'a [u8]>;
+
 impl MmapMinidump {
     /// Read a `Minidump` from a `Path` to a file on disk.
     ///
@@ -5327,6 +7983,198 @@ impl MmapMinidump {
     }
 }
 
+/// Read the header at the start of a minidump, and work out its endianness.
+fn read_header(data: &[u8]) -> Result<(md::MINIDUMP_HEADER, scroll::Endian), Error> {
+    let mut endian = LE;
+    let mut header: md::MINIDUMP_HEADER =
+        data.pread_with(0, endian).or(Err(Error::MissingHeader))?;
+    if header.signature != md::MINIDUMP_SIGNATURE {
+        if header.signature.swap_bytes() != md::MINIDUMP_SIGNATURE {
+            return Err(Error::HeaderMismatch);
+        }
+        // Try again with big-endian.
+        endian = BE;
+        header = data.pread_with(0, endian).or(Err(Error::MissingHeader))?;
+        if header.signature != md::MINIDUMP_SIGNATURE {
+            return Err(Error::HeaderMismatch);
+        }
+    }
+    if (header.version & 0x0000ffff) != md::MINIDUMP_VERSION {
+        return Err(Error::VersionMismatch);
+    }
+    Ok((header, endian))
+}
+
+/// A minidump that's read from a `Read + Seek` source on demand.
+///
+/// A [`Minidump`] needs all of the minidump's bytes at hand, memory-mapped or
+/// in a buffer. That's a lot for the full-memory dumps of big processes, which
+/// can be many gigabytes (nearly all of it the contents of the memory), and
+/// isn't possible at all for sources that can't be memory-mapped. A
+/// `SeekableMinidump` only reads the header and the stream directory up front,
+/// and the rest (a stream, a bit of memory) when it's asked for.
+///
+/// Only the raw bytes are available like this. To parse the streams into
+/// their types, load the whole minidump with [`SeekableMinidump::into_minidump`].
+///
+/// ```
+/// use minidump::{format::MINIDUMP_STREAM_TYPE, SeekableMinidump};
+///
+/// # fn foo() -> Result<(), minidump::Error> {
+/// let file = std::fs::File::open("../testdata/test.dmp").unwrap();
+/// let mut dump = SeekableMinidump::read(std::io::BufReader::new(file))?;
+/// let system_info = dump.read_raw_stream(MINIDUMP_STREAM_TYPE::SystemInfoStream as u32)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SeekableMinidump<R> {
+    reader: R,
+    /// The raw minidump header from the file.
+    pub header: md::MINIDUMP_HEADER,
+    /// The whole stream directory, duplicates included.
+    directory: Vec<md::MINIDUMP_DIRECTORY>,
+    /// The address, size and file offset of every memory region, read the
+    /// first time memory is asked for.
+    memory_regions: Option<Vec<(u64, u64, u64)>>,
+    /// The endianness of this minidump file.
+    pub endian: scroll::Endian,
+}
+
+impl<R: Readable> SeekableMinidump<R> {
+    /// Read the header and the stream directory of the minidump in `reader`.
+    ///
+    /// Nothing else is read until it's asked for, so `reader` is often best
+    /// wrapped in a `BufReader` if it's unbuffered.
+    pub fn read(mut reader: R) -> Result<SeekableMinidump<R>, Error> {
+        let header_size = md::MINIDUMP_HEADER::size_with(&LE) as u64;
+        let header_bytes = read_at(&mut reader, 0, header_size).ok_or(Error::MissingHeader)?;
+        let (header, endian) = read_header(&header_bytes)?;
+
+        // The stream count isn't checked against the size of the file, so the
+        // entries are read one at a time rather than all at once.
+        let entry_size = md::MINIDUMP_DIRECTORY::size_with(&endian) as u64;
+        let mut directory = Vec::new();
+        for i in 0..u64::from(header.stream_count) {
+            let offset = u64::from(header.stream_directory_rva) + i * entry_size;
+            let entry = read_at(&mut reader, offset, entry_size)
+                .and_then(|bytes| bytes.pread_with(0, endian).ok())
+                .ok_or(Error::MissingDirectory)?;
+            directory.push(entry);
+        }
+
+        Ok(SeekableMinidump {
+            reader,
+            header,
+            directory,
+            memory_regions: None,
+            endian,
+        })
+    }
+
+    /// The whole stream directory, in order, duplicates included.
+    pub fn directory(&self) -> &[md::MINIDUMP_DIRECTORY] {
+        &self.directory
+    }
+
+    /// Read the contents of the stream of type `stream_type`.
+    ///
+    /// Like [`Minidump::get_raw_stream`], if there are several streams of the
+    /// type, the last one is used.
+    pub fn read_raw_stream(&mut self, stream_type: u32) -> Result<Vec<u8>, Error> {
+        let location = self
+            .directory
+            .iter()
+            .rev()
+            .find(|entry| entry.stream_type == stream_type)
+            .map(|entry| entry.location)
+            .ok_or(Error::StreamNotFound)?;
+        read_at(
+            &mut self.reader,
+            location.rva.into(),
+            location.data_size.into(),
+        )
+        .ok_or(Error::StreamReadFailure)
+    }
+
+    /// Read the `size` bytes of the process's memory at `address`.
+    ///
+    /// All of them have to be in the same region of the memory list (or of
+    /// the 64-bit memory list, which is checked first, like
+    /// [`UnifiedMemoryList::memory_at_address`] does).
+    pub fn read_memory(&mut self, address: u64, size: u64) -> Result<Vec<u8>, Error> {
+        if self.memory_regions.is_none() {
+            self.memory_regions = Some(self.read_memory_regions());
+        }
+        let regions = self.memory_regions.as_deref().unwrap_or_default();
+        let end = address.checked_add(size).ok_or(Error::MemoryReadFailure)?;
+        let rva = regions
+            .iter()
+            .find(|&&(base, region_size, _)| {
+                base <= address && end <= base.saturating_add(region_size)
+            })
+            .map(|&(base, _, rva)| rva + (address - base))
+            .ok_or(Error::MemoryReadFailure)?;
+        read_at(&mut self.reader, rva, size).ok_or(Error::MemoryReadFailure)
+    }
+
+    /// The memory regions of both memory lists, the 64-bit one first. A list
+    /// that can't be read is left out.
+    fn read_memory_regions(&mut self) -> Vec<(u64, u64, u64)> {
+        let endian = self.endian;
+        let mut regions = Vec::new();
+        if let Ok(bytes) = self.read_raw_stream(MinidumpMemory64List::STREAM_TYPE) {
+            let mut offset = 0;
+            let count: Option<u64> = bytes.gread_with(&mut offset, endian).ok();
+            let rva: Option<u64> = bytes.gread_with(&mut offset, endian).ok();
+            if let (Some(count), Some(mut rva)) = (count, rva) {
+                // The contents of the regions are stored back to back.
+                for _ in 0..count {
+                    let Ok(raw) =
+                        bytes.gread_with::<md::MINIDUMP_MEMORY_DESCRIPTOR64>(&mut offset, endian)
+                    else {
+                        break;
+                    };
+                    regions.push((raw.start_of_memory_range, raw.data_size, rva));
+                    rva = rva.saturating_add(raw.data_size);
+                }
+            }
+        }
+        if let Ok(bytes) = self.read_raw_stream(MinidumpMemoryList::STREAM_TYPE) {
+            let descriptors: Vec<md::MINIDUMP_MEMORY_DESCRIPTOR> =
+                read_stream_list(&mut 0, &bytes, endian).unwrap_or_default();
+            regions.extend(descriptors.iter().map(|raw| {
+                (
+                    raw.start_of_memory_range,
+                    raw.memory.data_size.into(),
+                    raw.memory.rva.into(),
+                )
+            }));
+        }
+        regions
+    }
+
+    /// Read the whole minidump into memory, to parse it as a [`Minidump`].
+    pub fn into_minidump(mut self) -> Result<Minidump<'static, Vec<u8>>, Error> {
+        let mut data = Vec::new();
+        self.reader
+            .seek(io::SeekFrom::Start(0))
+            .and_then(|_| self.reader.read_to_end(&mut data))
+            .or(Err(Error::IoError))?;
+        Minidump::read(data)
+    }
+}
+
+/// Read the `size` bytes at `offset` of `reader`, or `None` if they can't all
+/// be read.
+fn read_at<R: Readable>(reader: &mut R, offset: u64, size: u64) -> Option<Vec<u8>> {
+    reader.seek(io::SeekFrom::Start(offset)).ok()?;
+    // Not allocated up front, the size may be nonsense.
+    let mut bytes = Vec::new();
+    reader.by_ref().take(size).read_to_end(&mut bytes).ok()?;
+    (bytes.len() as u64 == size).then_some(bytes)
+}
+
 /// A stream in the minidump that this implementation can interpret,
 #[derive(Debug)]
 pub struct MinidumpImplementedStream {
@@ -5343,6 +8191,19 @@ pub struct MinidumpUnknownStream {
     pub vendor: &'static str,
 }
 
+/// A stream whose type appears more than once in the stream directory, which
+/// isn't valid.
+///
+/// Only the last stream of each type is used, this is one of the others.
+#[derive(Debug, Clone)]
+pub struct MinidumpDuplicateStream {
+    pub stream_type: u32,
+    pub location: md::MINIDUMP_LOCATION_DESCRIPTOR,
+    pub vendor: &'static str,
+    /// The position of this stream in the stream directory.
+    pub index: u32,
+}
+
 /// A stream in the minidump that this implementation is aware of but doesn't
 /// yet support.
 #[derive(Debug, Clone)]
@@ -5352,6 +8213,205 @@ pub struct MinidumpUnimplementedStream {
     pub vendor: &'static str,
 }
 
+/// An entry in the stream directory of a minidump, yielded by [`Minidump::typed_streams`][].
+///
+/// The stream itself isn't parsed until [`read`](Self::read) is called.
+#[derive(Debug, Clone)]
+pub struct MinidumpStreamEntry<'a> {
+    /// The position of this stream in the stream directory.
+    pub index: u32,
+    pub directory: &'a md::MINIDUMP_DIRECTORY,
+    all: &'a [u8],
+    endian: scroll::Endian,
+    system_info: Option<&'a MinidumpSystemInfo>,
+}
+
+/// A parsed minidump stream, or the raw contents of a stream this implementation
+/// can't interpret.
+///
+/// Produced by [`MinidumpStreamEntry::read`][].
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum TypedStream<'a> {
+    ThreadList(MinidumpThreadList<'a>),
+    ThreadNames(MinidumpThreadNames),
+    ThreadInfoList(MinidumpThreadInfoList),
+    ModuleList(MinidumpModuleList),
+    UnloadedModuleList(MinidumpUnloadedModuleList),
+    HandleDataStream(MinidumpHandleDataStream),
+    FunctionTable(MinidumpFunctionTableStream<'a>),
+    MemoryList(MinidumpMemoryList<'a>),
+    Memory64List(MinidumpMemory64List<'a>),
+    MemoryInfoList(MinidumpMemoryInfoList<'a>),
+    SystemInfo(MinidumpSystemInfo),
+    MiscInfo(MinidumpMiscInfo),
+    Exception(MinidumpException<'a>),
+    Assertion(MinidumpAssertion),
+    BreakpadInfo(MinidumpBreakpadInfo),
+    CrashpadInfo(MinidumpCrashpadInfo),
+    MacCrashInfo(MinidumpMacCrashInfo),
+    MacBootargs(MinidumpMacBootargs),
+    LinuxMaps(MinidumpLinuxMaps<'a>),
+    LinuxCpuInfo(MinidumpLinuxCpuInfo<'a>),
+    LinuxEnviron(MinidumpLinuxEnviron<'a>),
+    LinuxCmdLine(MinidumpLinuxCmdLine<'a>),
+    LinuxLsbRelease(MinidumpLinuxLsbRelease<'a>),
+    LinuxProcStatus(MinidumpLinuxProcStatus<'a>),
+    LinuxProcLimits(MinidumpLinuxProcLimits<'a>),
+    LinuxDsoDebug(MinidumpLinuxDsoDebug),
+    LinuxAuxv(MinidumpLinuxAuxv),
+    SystemMemoryInfo(MinidumpSystemMemoryInfo),
+    ProcessVmCounters(MinidumpProcessVmCounters),
+    Token(MinidumpTokenStream<'a>),
+    HandleOperationList(MinidumpHandleOperationList),
+    /// A stream this implementation has no parser for (see
+    /// [`Minidump::unknown_streams`][] and [`Minidump::unimplemented_streams`][]).
+    Unknown(&'a [u8]),
+}
+
+impl<'a> MinidumpStreamEntry<'a> {
+    pub fn stream_type(&self) -> u32 {
+        self.directory.stream_type
+    }
+
+    /// Who defined this stream type, as in [`MinidumpUnknownStream::vendor`][].
+    pub fn vendor(&self) -> &'static str {
+        stream_vendor(self.directory.stream_type)
+    }
+
+    /// The raw contents of this stream.
+    pub fn raw_bytes(&self) -> Result<&'a [u8], Error> {
+        location_slice(self.all, &self.directory.location)
+    }
+
+    /// Parse this stream.
+    ///
+    /// This is equivalent to calling [`Minidump::get_stream`][] with the
+    /// appropriate type.
+    pub fn read(&self) -> Result<TypedStream<'a>, Error> {
+        fn read<'a, S: MinidumpStream<'a>>(
+            entry: &MinidumpStreamEntry<'a>,
+            bytes: &'a [u8],
+        ) -> Result<S, Error> {
+            S::read(bytes, entry.all, entry.endian, entry.system_info)
+        }
+
+        use MINIDUMP_STREAM_TYPE::*;
+        let bytes = self.raw_bytes()?;
+        let stream = match MINIDUMP_STREAM_TYPE::from_u32(self.directory.stream_type) {
+            Some(ThreadListStream) => TypedStream::ThreadList(read(self, bytes)?),
+            Some(ThreadNamesStream) => TypedStream::ThreadNames(read(self, bytes)?),
+            Some(ThreadInfoListStream) => TypedStream::ThreadInfoList(read(self, bytes)?),
+            Some(ModuleListStream) => TypedStream::ModuleList(read(self, bytes)?),
+            Some(UnloadedModuleListStream) => TypedStream::UnloadedModuleList(read(self, bytes)?),
+            Some(HandleDataStream) => TypedStream::HandleDataStream(read(self, bytes)?),
+            Some(FunctionTable) => TypedStream::FunctionTable(read(self, bytes)?),
+            Some(MemoryListStream) => TypedStream::MemoryList(read(self, bytes)?),
+            Some(Memory64ListStream) => TypedStream::Memory64List(read(self, bytes)?),
+            Some(MemoryInfoListStream) => TypedStream::MemoryInfoList(read(self, bytes)?),
+            Some(SystemInfoStream) => TypedStream::SystemInfo(read(self, bytes)?),
+            Some(MiscInfoStream) => TypedStream::MiscInfo(read(self, bytes)?),
+            Some(SystemMemoryInfoStream) => TypedStream::SystemMemoryInfo(read(self, bytes)?),
+            Some(ProcessVmCountersStream) => TypedStream::ProcessVmCounters(read(self, bytes)?),
+            Some(TokenStream) => TypedStream::Token(read(self, bytes)?),
+            Some(HandleOperationListStream) => TypedStream::HandleOperationList(read(self, bytes)?),
+            Some(ExceptionStream) => TypedStream::Exception(read(self, bytes)?),
+            Some(AssertionInfoStream) => TypedStream::Assertion(read(self, bytes)?),
+            Some(BreakpadInfoStream) => TypedStream::BreakpadInfo(read(self, bytes)?),
+            Some(CrashpadInfoStream) => TypedStream::CrashpadInfo(read(self, bytes)?),
+            Some(MozMacosCrashInfoStream) => TypedStream::MacCrashInfo(read(self, bytes)?),
+            Some(MozMacosBootargsStream) => TypedStream::MacBootargs(read(self, bytes)?),
+            Some(LinuxMaps) => TypedStream::LinuxMaps(read(self, bytes)?),
+            Some(LinuxCpuInfo) => TypedStream::LinuxCpuInfo(read(self, bytes)?),
+            Some(LinuxEnviron) => TypedStream::LinuxEnviron(read(self, bytes)?),
+            Some(LinuxCmdLine) => TypedStream::LinuxCmdLine(read(self, bytes)?),
+            Some(LinuxLsbRelease) => TypedStream::LinuxLsbRelease(read(self, bytes)?),
+            Some(LinuxProcStatus) => TypedStream::LinuxProcStatus(read(self, bytes)?),
+            Some(MozLinuxLimits) => TypedStream::LinuxProcLimits(read(self, bytes)?),
+            Some(LinuxDsoDebug) => TypedStream::LinuxDsoDebug(read(self, bytes)?),
+            Some(LinuxAuxv) => TypedStream::LinuxAuxv(read(self, bytes)?),
+            _ => TypedStream::Unknown(bytes),
+        };
+        Ok(stream)
+    }
+}
+
+impl<'a> TypedStream<'a> {
+    /// Write a human-readable description of this stream to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`. Some
+    /// streams refer to the contents of others, so those streams should be
+    /// provided if the minidump contains them (see [`Minidump::get_memory`][]).
+    pub fn print<W: Write>(
+        &self,
+        f: &mut W,
+        memory: Option<&UnifiedMemoryList<'a>>,
+        system: Option<&MinidumpSystemInfo>,
+        misc: Option<&MinidumpMiscInfo>,
+        brief: bool,
+    ) -> io::Result<()> {
+        match self {
+            TypedStream::ThreadList(stream) => stream.print(f, memory, system, misc, brief),
+            TypedStream::ThreadNames(stream) => stream.print(f),
+            TypedStream::ThreadInfoList(stream) => stream.print(f),
+            TypedStream::ModuleList(stream) => stream.print(f),
+            TypedStream::UnloadedModuleList(stream) => stream.print(f),
+            TypedStream::HandleDataStream(stream) => stream.print(f),
+            TypedStream::FunctionTable(stream) => stream.print(f),
+            TypedStream::MemoryList(stream) => stream.print(f, brief),
+            TypedStream::Memory64List(stream) => stream.print(f, brief),
+            TypedStream::MemoryInfoList(stream) => stream.print(f),
+            TypedStream::SystemInfo(stream) => stream.print(f),
+            TypedStream::MiscInfo(stream) => stream.print(f),
+            TypedStream::Exception(stream) => stream.print(f, system, misc),
+            TypedStream::Assertion(stream) => stream.print(f),
+            TypedStream::BreakpadInfo(stream) => stream.print(f),
+            TypedStream::CrashpadInfo(stream) => stream.print(f),
+            TypedStream::MacCrashInfo(stream) => stream.print(f),
+            TypedStream::MacBootargs(stream) => stream.print(f),
+            TypedStream::LinuxMaps(stream) => stream.print(f),
+            TypedStream::LinuxCpuInfo(stream) => {
+                print_text_stream(f, "LinuxCpuInfo", &stream.raw_bytes())
+            }
+            TypedStream::LinuxEnviron(stream) => {
+                print_text_stream(f, "LinuxEnviron", &stream.raw_bytes())
+            }
+            TypedStream::LinuxCmdLine(stream) => {
+                print_text_stream(f, "LinuxCmdLine", &stream.raw_bytes())
+            }
+            TypedStream::LinuxLsbRelease(stream) => {
+                print_text_stream(f, "LinuxLsbRelease", &stream.raw_bytes())
+            }
+            TypedStream::LinuxProcStatus(stream) => {
+                print_text_stream(f, "LinuxProcStatus", &stream.raw_bytes())
+            }
+            TypedStream::LinuxProcLimits(stream) => {
+                print_text_stream(f, "MozLinuxLimits", &stream.raw_bytes())
+            }
+            TypedStream::LinuxDsoDebug(stream) => stream.print(f),
+            TypedStream::LinuxAuxv(stream) => stream.print(f),
+            TypedStream::SystemMemoryInfo(stream) => stream.print(f),
+            TypedStream::ProcessVmCounters(stream) => stream.print(f),
+            TypedStream::Token(stream) => stream.print(f),
+            TypedStream::HandleOperationList(stream) => stream.print(f),
+            TypedStream::Unknown(bytes) => {
+                write!(f, "Unknown stream\n  size = {}\n\n", bytes.len())
+            }
+        }
+    }
+}
+
+/// Print a stream that's just a dump of some system "file".
+fn print_text_stream<W: Write>(f: &mut W, name: &str, contents: &[u8]) -> io::Result<()> {
+    writeln!(f, "Stream {name}:")?;
+    let s = contents
+        .split(|&v| v == 0)
+        .map(String::from_utf8_lossy)
+        .collect::<Vec<_>>()
+        .join("\\0\n");
+    write!(f, "{s}\n\n")
+}
+
 impl<'a, T> Minidump<'a, T>
 where
     T: Deref<Target = [u8]> + 'a,
@@ -5359,44 +8419,44 @@ where
     /// Read a `Minidump` from the provided `data`.
     ///
     /// Typically this will be a `Vec<u8>` or `&[u8]` with the full contents of the minidump,
-    /// but you can also use something like `memmap::Mmap`.
+    /// but you can also use something like `memmap::Mmap`. A `&[u8]` is borrowed
+    /// (see [`SliceMinidump`]), so the minidump isn't copied.
     pub fn read(data: T) -> Result<Minidump<'a, T>, Error> {
-        let mut offset = 0;
-        let mut endian = LE;
-        let mut header: md::MINIDUMP_HEADER = data
-            .gread_with(&mut offset, endian)
-            .or(Err(Error::MissingHeader))?;
-        if header.signature != md::MINIDUMP_SIGNATURE {
-            if header.signature.swap_bytes() != md::MINIDUMP_SIGNATURE {
-                return Err(Error::HeaderMismatch);
-            }
-            // Try again with big-endian.
-            endian = BE;
-            offset = 0;
-            header = data
-                .gread_with(&mut offset, endian)
-                .or(Err(Error::MissingHeader))?;
-            if header.signature != md::MINIDUMP_SIGNATURE {
-                return Err(Error::HeaderMismatch);
-            }
-        }
-        if (header.version & 0x0000ffff) != md::MINIDUMP_VERSION {
-            return Err(Error::VersionMismatch);
-        }
-
-        offset = header.stream_directory_rva as usize;
+        Minidump::read_with_limits(data, ReadLimits::default())
+    }
 
+    /// Read a `Minidump` from the provided `data`, with `limits` on the streams.
+    ///
+    /// Like [`Minidump::read`], but reading a stream that's larger than the
+    /// limits (e.g. a module list with more than `max_modules` modules) fails
+    /// with [`Error::LimitExceeded`].
+    pub fn read_with_limits(data: T, limits: ReadLimits) -> Result<Minidump<'a, T>, Error> {
+        let (header, endian) = read_header(&data)?;
+        let mut offset = header.stream_directory_rva as usize;
+
+        // Nothing requires the directory (or the streams) to be aligned, or
+        // sorted, so it's read as it is.
         let mut streams = BTreeMap::new();
+        let mut directory = Vec::new();
+        let mut duplicate_streams = Vec::new();
         for i in 0..header.stream_count {
             let dir: md::MINIDUMP_DIRECTORY = data
                 .gread_with(&mut offset, endian)
                 .or(Err(Error::MissingDirectory))?;
+            directory.push(dir.clone());
             if let Some((old_idx, old_dir)) = streams.insert(dir.stream_type, (i, dir.clone())) {
                 if let Some(known_stream_type) = MINIDUMP_STREAM_TYPE::from_u32(dir.stream_type) {
+                    // Empty UnusedStreams are just padding, so there can be any number of them.
                     if !(known_stream_type == MINIDUMP_STREAM_TYPE::UnusedStream
                         && old_dir.location.data_size == 0
                         && dir.location.data_size == 0)
                     {
+                        duplicate_streams.push(MinidumpDuplicateStream {
+                            stream_type: old_dir.stream_type,
+                            location: old_dir.location,
+                            vendor: stream_vendor(old_dir.stream_type),
+                            index: old_idx,
+                        });
                         warn!("Minidump contains multiple streams of type {} ({:?}) at indices {} ({} bytes) and {} ({} bytes) (using {})",
                             dir.stream_type,
                             known_stream_type,
@@ -5408,6 +8468,12 @@ where
                         );
                     }
                 } else {
+                    duplicate_streams.push(MinidumpDuplicateStream {
+                        stream_type: old_dir.stream_type,
+                        location: old_dir.location,
+                        vendor: stream_vendor(old_dir.stream_type),
+                        index: old_idx,
+                    });
                     warn!("Minidump contains multiple streams of unknown type {} at indices {} ({} bytes) and {} ({} bytes) (using {})",
                         dir.stream_type,
                         old_idx,
@@ -5434,14 +8500,24 @@ where
             data,
             header,
             streams,
+            directory,
             endian,
             system_info,
+            corruption_notes: OnceLock::new(),
+            duplicate_streams,
+            limits,
             _phantom: PhantomData,
         })
     }
 
     /// Read and parse the specified [`MinidumpStream`][] `S` from the Minidump, if it exists.
     ///
+    /// If the stream directory has several streams of the type (which it
+    /// shouldn't), the last one is used, like Breakpad does. The others are
+    /// listed by [`Minidump::duplicate_streams`][] (and in the
+    /// [`Minidump::corruption_notes`][]), and [`Minidump::streams_of_type`][]
+    /// yields all of them.
+    ///
     /// Because Minidump Streams can have totally different formats and meanings, the only
     /// way to coherently access one is by specifying a static type that provides an
     /// interpretation and interface of that format.
@@ -5500,7 +8576,11 @@ where
     /// * [`MinidumpBreakpadInfo`][]
     /// * [`MinidumpCrashpadInfo`][]
     /// * [`MinidumpException`][]
+    /// * [`MinidumpFunctionTableStream`][]
+    /// * [`MinidumpLinuxAuxv`][]
+    /// * [`MinidumpLinuxCmdLine`][]
     /// * [`MinidumpLinuxCpuInfo`][]
+    /// * [`MinidumpLinuxDsoDebug`][]
     /// * [`MinidumpLinuxEnviron`][]
     /// * [`MinidumpLinuxLsbRelease`][]
     /// * [`MinidumpLinuxMaps`][]
@@ -5512,11 +8592,15 @@ where
     /// * [`MinidumpMemoryInfoList`][]
     /// * [`MinidumpMiscInfo`][]
     /// * [`MinidumpModuleList`][]
+    /// * [`MinidumpProcessVmCounters`][]
     /// * [`MinidumpSystemInfo`][]
+    /// * [`MinidumpSystemMemoryInfo`][]
+    /// * [`MinidumpTokenStream`][]
     /// * [`MinidumpThreadList`][]
     /// * [`MinidumpThreadNames`][]
     /// * [`MinidumpUnloadedModuleList`][]
     /// * [`MinidumpHandleDataStream`][]
+    /// * [`MinidumpHandleOperationList`][]
     ///
     pub fn get_stream<S>(&'a self) -> Result<S, Error>
     where
@@ -5540,121 +8624,629 @@ where
     /// Note that the lifetime of the returned stream is bound to the lifetime of the this
     /// `Minidump` struct itself and not to the lifetime of the data backing this minidump.
     /// This is a consequence of how this struct relies on [Deref] to access the data.
+    ///
+    /// Streams that exceed the [`ReadLimits`] the minidump was read with aren't
+    /// returned, see [`Minidump::read_with_limits`]. Like [`Minidump::get_stream`][],
+    /// this returns the last stream of the type.
     pub fn get_raw_stream(&'a self, stream_type: u32) -> Result<&'a [u8], Error> {
         match self.streams.get(&stream_type) {
             None => Err(Error::StreamNotFound),
             Some((_, dir)) => {
-                let bytes = self.data.deref();
-                location_slice(bytes, &dir.location)
+                let bytes = location_slice(self.data.deref(), &dir.location)?;
+                self.limits.check_stream(stream_type, bytes, self.endian)?;
+                Ok(bytes)
             }
         }
     }
 
-    /// Get whichever of the two MemoryLists are available in the minidump,
-    /// preferring [`MinidumpMemory64List`][].
-    pub fn get_memory(&'a self) -> Option<UnifiedMemoryList<'a>> {
-        self.get_stream::<MinidumpMemory64List>()
-            .map(UnifiedMemoryList::Memory64)
-            .or_else(|_| {
-                self.get_stream::<MinidumpMemoryList>()
-                    .map(UnifiedMemoryList::Memory)
-            })
-            .ok()
+    /// The limits the minidump was read with.
+    pub fn limits(&self) -> &ReadLimits {
+        &self.limits
     }
 
-    /// A listing of all the streams in the Minidump that this library is *aware* of,
-    /// but has no further analysis for.
+    /// Problems found with the contents of the minidump that are worked around
+    /// when reading it.
     ///
-    /// If there are multiple copies of the same stream type (which should not happen for
-    /// well-formed Minidumps), then only one of them will be yielded, arbitrarily.
-    pub fn unimplemented_streams(&self) -> impl Iterator<Item = MinidumpUnimplementedStream> + '_ {
-        static UNIMPLEMENTED_STREAMS: [MINIDUMP_STREAM_TYPE; 30] = [
-            // Presumably will never have an implementation:
-            MINIDUMP_STREAM_TYPE::UnusedStream,
-            MINIDUMP_STREAM_TYPE::ReservedStream0,
-            MINIDUMP_STREAM_TYPE::ReservedStream1,
-            MINIDUMP_STREAM_TYPE::LastReservedStream,
-            // Presumably should be implemented:
-            MINIDUMP_STREAM_TYPE::ThreadExListStream,
-            MINIDUMP_STREAM_TYPE::CommentStreamA,
-            MINIDUMP_STREAM_TYPE::CommentStreamW,
-            MINIDUMP_STREAM_TYPE::FunctionTable,
-            MINIDUMP_STREAM_TYPE::HandleOperationListStream,
-            MINIDUMP_STREAM_TYPE::TokenStream,
-            MINIDUMP_STREAM_TYPE::JavaScriptDataStream,
-            MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream,
-            MINIDUMP_STREAM_TYPE::ProcessVmCountersStream,
-            MINIDUMP_STREAM_TYPE::IptTraceStream,
-            // Windows CE streams, very unlikely to be found in the wild.
-            // Their contents are documented here: https://docs.microsoft.com/en-us/previous-versions/windows/embedded/ms939618(v=msdn.10)
-            MINIDUMP_STREAM_TYPE::ceStreamNull,
-            MINIDUMP_STREAM_TYPE::ceStreamSystemInfo,
-            MINIDUMP_STREAM_TYPE::ceStreamException,
-            MINIDUMP_STREAM_TYPE::ceStreamModuleList,
-            MINIDUMP_STREAM_TYPE::ceStreamProcessList,
-            MINIDUMP_STREAM_TYPE::ceStreamThreadList,
-            MINIDUMP_STREAM_TYPE::ceStreamThreadContextList,
-            MINIDUMP_STREAM_TYPE::ceStreamThreadCallStackList,
-            MINIDUMP_STREAM_TYPE::ceStreamMemoryVirtualList,
-            MINIDUMP_STREAM_TYPE::ceStreamMemoryPhysicalList,
-            MINIDUMP_STREAM_TYPE::ceStreamBucketParameters,
-            MINIDUMP_STREAM_TYPE::ceStreamProcessModuleMap,
-            MINIDUMP_STREAM_TYPE::ceStreamDiagnosisList,
-            // non-standard streams (should also be implemented):
-            MINIDUMP_STREAM_TYPE::LinuxCmdLine,
-            MINIDUMP_STREAM_TYPE::LinuxAuxv,
-            MINIDUMP_STREAM_TYPE::LinuxDsoDebug,
-        ];
-        self.streams.iter().filter_map(|(_, (_, stream))| {
-            MINIDUMP_STREAM_TYPE::from_u32(stream.stream_type).and_then(|stream_type| {
-                if UNIMPLEMENTED_STREAMS.contains(&stream_type) {
-                    return Some(MinidumpUnimplementedStream {
-                        stream_type,
-                        location: stream.location,
-                        vendor: stream_vendor(stream.stream_type),
-                    });
+    /// Currently this covers the streams that are ignored because a later one
+    /// has the same type (see [`Minidump::duplicate_streams`][]), and the memory
+    /// regions of [`MinidumpMemoryList`][] and [`MinidumpMemory64List`][]:
+    /// regions that extend past the end of the file (which are truncated),
+    /// regions that are empty or entirely outside the file (which are ignored),
+    /// and regions that overlap each other.
+    ///
+    /// The memory lists are only checked the first time this is called, so
+    /// reading a minidump doesn't pay for it.
+    pub fn corruption_notes(&self) -> &[CorruptionNote] {
+        self.corruption_notes.get_or_init(|| {
+            let mut notes: Vec<_> = self
+                .duplicate_streams
+                .iter()
+                .map(|duplicate| CorruptionNote::DuplicateStream {
+                    stream_type: duplicate.stream_type,
+                    index: duplicate.index,
+                    used_index: self.streams[&duplicate.stream_type].0,
+                })
+                .collect();
+
+            let all = self.data.deref();
+            let memory_stream = |stream_type: u32| {
+                self.streams
+                    .get(&stream_type)
+                    .and_then(|(_, dir)| location_slice(all, &dir.location).ok())
+                    .filter(|bytes| {
+                        self.limits
+                            .check_stream(stream_type, bytes, self.endian)
+                            .is_ok()
+                    })
+            };
+            if let Some(bytes) = memory_stream(MinidumpMemoryList::STREAM_TYPE) {
+                if let Ok(memory) = MinidumpMemoryList::read(bytes, all, self.endian, None) {
+                    notes.extend_from_slice(memory.corruption_notes());
                 }
-                None
-            })
+            }
+            if let Some(bytes) = memory_stream(MinidumpMemory64List::STREAM_TYPE) {
+                if let Ok(memory) = MinidumpMemory64List::read(bytes, all, self.endian, None) {
+                    notes.extend_from_slice(memory.corruption_notes());
+                }
+            }
+            notes
         })
     }
 
-    /// A listing of all the streams in the Minidump that this library has no knowledge of.
+    /// Check the whole minidump, and list every problem with it.
     ///
-    /// If there are multiple copies of the same stream (which should not happen for
-    /// well-formed Minidumps), then only one of them will be yielded, arbitrarily.
-    pub fn unknown_streams(&self) -> impl Iterator<Item = MinidumpUnknownStream> + '_ {
-        self.streams.iter().filter_map(|(_, (_, stream))| {
-            if MINIDUMP_STREAM_TYPE::from_u32(stream.stream_type).is_none() {
-                return Some(MinidumpUnknownStream {
-                    stream_type: stream.stream_type,
-                    location: stream.location,
-                    vendor: stream_vendor(stream.stream_type),
+    /// Reading a minidump only checks its header and stream directory, and a
+    /// broken stream only fails to parse once it's asked for. This reads every stream in the
+    /// stream directory, and checks where they all are, so it's the way to
+    /// find out exactly what's wrong with a fuzzed or truncated minidump (or
+    /// to insist that a minidump has nothing wrong with it, when the list is
+    /// empty).
+    ///
+    /// The problems are the [`Minidump::corruption_notes`][], the ones with
+    /// each stream (in stream directory order), and then the streams that
+    /// overlap each other. Empty streams are ignored.
+    pub fn validate(&'a self) -> Vec<CorruptionNote> {
+        let mut notes = self.corruption_notes().to_vec();
+        let file_size = self.data.len() as u64;
+        let header_size = md::MINIDUMP_HEADER::size_with(&self.endian) as u64;
+        let directory_start = u64::from(self.header.stream_directory_rva);
+        let directory_end = directory_start
+            + u64::from(self.header.stream_count)
+                * md::MINIDUMP_DIRECTORY::size_with(&self.endian) as u64;
+
+        // The (clamped) range of the file that each stream is in, with the stream.
+        let mut ranges = Vec::new();
+        for entry in self.stream_entries() {
+            let stream_type = entry.stream_type();
+            let index = entry.index;
+            let md::MINIDUMP_LOCATION_DESCRIPTOR { rva, data_size } = entry.directory.location;
+            if data_size == 0 {
+                continue;
+            }
+            let start = u64::from(rva);
+            let end = start + u64::from(data_size);
+            if start >= file_size {
+                notes.push(CorruptionNote::StreamOutOfBounds {
+                    stream_type,
+                    index,
+                    rva,
+                    size: data_size,
+                    file_size,
                 });
+                continue;
             }
-            None
-        })
-    }
+            if end > file_size {
+                notes.push(CorruptionNote::StreamTruncated {
+                    stream_type,
+                    index,
+                    rva,
+                    claimed_size: data_size,
+                    available_size: file_size - start,
+                });
+            }
+            let end = end.min(file_size);
+            if start < header_size || (start < directory_end && directory_start < end) {
+                notes.push(CorruptionNote::StreamOverlapsDirectory {
+                    stream_type,
+                    index,
+                    rva,
+                    size: data_size,
+                });
+            }
+            ranges.push((start, end, index, entry.directory));
+            if let Err(error) = entry.read() {
+                notes.push(CorruptionNote::StreamUnreadable {
+                    stream_type,
+                    index,
+                    rva,
+                    size: data_size,
+                    error,
+                });
+            }
+        }
 
-    /// A listing of all the streams in the Minidump.
-    ///
-    /// If there are multiple copies of the same stream (which should not happen for
-    /// well-formed Minidumps), then only one of them will be yielded, arbitrarily.
-    pub fn all_streams(&self) -> impl Iterator<Item = &md::MINIDUMP_DIRECTORY> + '_ {
-        self.streams.iter().map(|(_, (_, stream))| stream)
+        // Each pair of overlapping streams, as the later one in the stream
+        // directory and the earlier one.
+        ranges.sort_by_key(|&(start, _, index, _)| (start, index));
+        let mut overlaps = Vec::new();
+        for (i, &(_, end, index, directory)) in ranges.iter().enumerate() {
+            let overlapping = ranges[i + 1..]
+                .iter()
+                .take_while(|&&(other_start, ..)| other_start < end);
+            for &(_, _, other_index, other) in overlapping {
+                if other_index > index {
+                    overlaps.push(((other_index, other), (index, directory)));
+                } else {
+                    overlaps.push(((index, directory), (other_index, other)));
+                }
+            }
+        }
+        overlaps.sort_by_key(|&((index, _), (other_index, _))| (index, other_index));
+        notes.extend(
+            overlaps
+                .into_iter()
+                .map(
+                    |((index, directory), (other_index, other))| CorruptionNote::StreamOverlap {
+                        stream_type: directory.stream_type,
+                        index,
+                        rva: directory.location.rva,
+                        size: directory.location.data_size,
+                        other_index,
+                        other_rva: other.location.rva,
+                        other_size: other.location.data_size,
+                    },
+                ),
+        );
+        notes
     }
 
-    /// Write a verbose description of the `Minidump` to `f`.
-    pub fn print<W: Write>(&self, f: &mut W) -> io::Result<()> {
-        fn get_stream_name(stream_type: u32) -> Cow<'static, str> {
-            if let Some(stream) = MINIDUMP_STREAM_TYPE::from_u32(stream_type) {
-                Cow::Owned(format!("{stream:?}"))
-            } else {
-                Cow::Borrowed("unknown")
-            }
+    /// Get the memory of the minidump, from whichever of the two MemoryLists
+    /// are available in it.
+    ///
+    /// If it has both, they're merged (see [`UnifiedMemoryList::Both`][]), so
+    /// that users don't need to know which list the bytes came from.
+    pub fn get_memory(&'a self) -> Option<UnifiedMemoryList<'a>> {
+        let memory = self.get_stream::<MinidumpMemoryList>().ok();
+        let memory64 = self.get_stream::<MinidumpMemory64List>().ok();
+        match (memory, memory64) {
+            (Some(memory), Some(memory64)) => Some(UnifiedMemoryList::Both(memory, memory64)),
+            (Some(memory), None) => Some(UnifiedMemoryList::Memory(memory)),
+            (None, Some(memory64)) => Some(UnifiedMemoryList::Memory64(memory64)),
+            (None, None) => None,
         }
+    }
 
-        write!(
+    /// Extract the thread `thread_id` (and what's needed to walk its stack) as
+    /// a standalone minidump.
+    ///
+    /// The new minidump has the system info, misc info and module list, the
+    /// thread with its context and stack memory, and the exception if it
+    /// happened on this thread. If the thread's stack pointer (or the
+    /// exception's) is outside of its stack memory, the memory region that
+    /// contains it is included too. So processing the new minidump gives the
+    /// same stack for the thread, without the rest of the process's threads
+    /// and memory, which makes it much smaller to share. (The Breakpad info
+    /// isn't included, so if this is the thread that wrote the minidump, its
+    /// stack is walked when processing the new minidump, even though processing
+    /// the original skips it.)
+    ///
+    /// Returns [`Error::ThreadNotFound`] if there's no such thread.
+    pub fn extract_thread(&'a self, thread_id: u32) -> Result<Vec<u8>, Error> {
+        let all = self.data.deref();
+        let thread_list: MinidumpThreadList = self.get_stream()?;
+        let thread = thread_list
+            .get_thread(thread_id)
+            .ok_or(Error::ThreadNotFound)?;
+        let system_info = self.get_stream::<MinidumpSystemInfo>().ok();
+        let misc_info = self.get_stream::<MinidumpMiscInfo>().ok();
+        let exception = self
+            .get_stream::<MinidumpException>()
+            .ok()
+            .filter(|exception| exception.get_crashing_thread_id() == thread_id);
+        let memory_list = self.get_memory().unwrap_or_default();
+
+        let mut writer = MinidumpWriter::new(self.endian);
+        if let Some(system_info) = &system_info {
+            let mut raw = system_info.raw.clone();
+            raw.csd_version_rva = writer.copy_string(all, raw.csd_version_rva);
+            let data = writer.encode(raw);
+            writer.add_stream(MINIDUMP_STREAM_TYPE::SystemInfoStream, &data);
+        }
+        if let Ok(misc_info) = self.get_raw_stream(MINIDUMP_STREAM_TYPE::MiscInfoStream as u32) {
+            writer.add_stream(MINIDUMP_STREAM_TYPE::MiscInfoStream, misc_info);
+        }
+
+        if let Ok(module_list) = self.get_stream::<MinidumpModuleList>() {
+            let modules = module_list
+                .iter()
+                .map(|module| {
+                    let mut raw = module.raw.clone();
+                    raw.module_name_rva = writer.copy_string(all, raw.module_name_rva);
+                    raw.cv_record = writer.copy_location(all, &raw.cv_record);
+                    raw.misc_record = writer.copy_location(all, &raw.misc_record);
+                    raw
+                })
+                .collect();
+            writer.add_list_stream(MINIDUMP_STREAM_TYPE::ModuleListStream, modules);
+        }
+
+        // The stack memory, and any other regions the stack pointers are in.
+        let mut regions = Vec::new();
+        let stack = thread.stack_memory(&memory_list);
+        regions.extend(stack);
+        if let Some(system_info) = &system_info {
+            let thread_context = thread.read_context(system_info, misc_info.as_ref()).ok();
+            let exception_context = exception
+                .as_ref()
+                .and_then(|exception| exception.context(system_info, misc_info.as_ref()));
+            let stack_pointers = thread_context
+                .iter()
+                .chain(exception_context.as_deref())
+                .map(MinidumpContext::get_stack_pointer);
+            for stack_pointer in stack_pointers {
+                let covered = regions.iter().any(|region| {
+                    region
+                        .memory_range()
+                        .is_some_and(|r| r.contains(stack_pointer))
+                });
+                if !covered {
+                    regions.extend(memory_list.memory_at_address(stack_pointer));
+                }
+            }
+        }
+        let descriptors: Vec<_> = regions
+            .iter()
+            .map(|region| md::MINIDUMP_MEMORY_DESCRIPTOR {
+                start_of_memory_range: region.base_address(),
+                memory: writer.append(region.bytes()),
+            })
+            .collect();
+
+        let mut raw = thread.raw.clone();
+        raw.thread_context = writer.copy_location(all, &raw.thread_context);
+        raw.stack = match stack {
+            Some(_) => descriptors[0],
+            None => md::MINIDUMP_MEMORY_DESCRIPTOR {
+                start_of_memory_range: raw.stack.start_of_memory_range,
+                memory: md::MINIDUMP_LOCATION_DESCRIPTOR::default(),
+            },
+        };
+        writer.add_list_stream(MINIDUMP_STREAM_TYPE::ThreadListStream, vec![raw]);
+        writer.add_list_stream(MINIDUMP_STREAM_TYPE::MemoryListStream, descriptors);
+
+        if let Some(exception) = &exception {
+            let mut raw = exception.raw.clone();
+            raw.thread_context = writer.copy_location(all, &raw.thread_context);
+            let data = writer.encode(raw);
+            writer.add_stream(MINIDUMP_STREAM_TYPE::ExceptionStream, &data);
+        }
+
+        Ok(writer.finish(&self.header))
+    }
+
+    /// A copy of the minidump with the data that `options` asks for removed.
+    ///
+    /// Everything else is left where it is: removed data is zeroed, and the
+    /// streams that are rewritten (the module lists and the `LinuxMaps`, when
+    /// stripping paths) are appended, with the old copies zeroed. So the new
+    /// minidump is valid, and the same size (or a bit bigger).
+    ///
+    /// ```
+    /// use minidump::{Minidump, MinidumpModuleList, SanitizeOptions};
+    ///
+    /// # fn foo() -> Result<(), minidump::Error> {
+    /// let dump = Minidump::read_path("../testdata/test.dmp")?;
+    /// let options = SanitizeOptions {
+    ///     scrub_heap_memory: true,
+    ///     erase_environment: true,
+    ///     strip_module_paths: true,
+    /// };
+    /// let sanitized = Minidump::read(dump.sanitize(&options))?;
+    /// let modules = sanitized.get_stream::<MinidumpModuleList>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sanitize(&'a self, options: &SanitizeOptions) -> Vec<u8> {
+        let all = self.data.deref();
+        let mut writer = MinidumpWriter {
+            bytes: all.to_vec(),
+            endian: self.endian,
+            directory: Vec::new(),
+        };
+
+        if options.scrub_heap_memory {
+            let thread_list = self.get_stream::<MinidumpThreadList>();
+            let stacks: Vec<_> = thread_list
+                .iter()
+                .flat_map(|thread_list| &thread_list.threads)
+                .filter_map(|thread| {
+                    let stack = &thread.raw.stack;
+                    address_range(stack.start_of_memory_range, stack.memory.data_size.into())
+                })
+                .collect();
+            let memory_list = self.get_memory().unwrap_or_default();
+            for region in memory_list.iter() {
+                let is_stack = region
+                    .memory_range()
+                    .is_some_and(|range| stacks.iter().any(|stack| stack.intersects(&range)));
+                if !is_stack {
+                    // The contents of the regions are slices of the minidump.
+                    let rva = region.bytes().as_ptr() as usize - all.as_ptr() as usize;
+                    writer.zero(rva as u64, region.bytes().len() as u64);
+                }
+            }
+        }
+
+        if options.erase_environment {
+            for entry in self.streams_of_type(MINIDUMP_STREAM_TYPE::LinuxEnviron as u32) {
+                let location = &entry.directory.location;
+                writer.zero(location.rva.into(), location.data_size.into());
+            }
+        }
+
+        if options.strip_module_paths {
+            if let Ok(modules) = self.get_stream::<MinidumpModuleList>() {
+                let raw_modules: Vec<_> = modules
+                    .iter()
+                    .map(|module| {
+                        let mut raw = module.raw.clone();
+                        writer.zero_string(raw.module_name_rva);
+                        raw.module_name_rva = writer.append_string(basename(&module.name));
+                        if let Some(cv_record) = stripped_codeview_path(&mut writer, all, module) {
+                            raw.cv_record = cv_record;
+                        }
+                        raw
+                    })
+                    .collect();
+                self.replace_stream(&mut writer, MINIDUMP_STREAM_TYPE::ModuleListStream, |w| {
+                    let mut data = w.encode(raw_modules.len() as u32);
+                    for raw in raw_modules {
+                        data.extend(w.encode(raw));
+                    }
+                    data
+                });
+            }
+            if let Ok(modules) = self.get_stream::<MinidumpUnloadedModuleList>() {
+                let raw_modules: Vec<_> = modules
+                    .iter()
+                    .map(|module| {
+                        let mut raw = module.raw.clone();
+                        writer.zero_string(raw.module_name_rva);
+                        raw.module_name_rva = writer.append_string(basename(&module.name));
+                        raw
+                    })
+                    .collect();
+                self.replace_stream(
+                    &mut writer,
+                    MINIDUMP_STREAM_TYPE::UnloadedModuleListStream,
+                    |w| {
+                        // size_of_header, size_of_entry, number_of_entries
+                        let entry_size = md::MINIDUMP_UNLOADED_MODULE::size_with(&w.endian);
+                        let mut data = w.encode(12u32);
+                        data.extend(w.encode(entry_size as u32));
+                        data.extend(w.encode(raw_modules.len() as u32));
+                        for raw in raw_modules {
+                            data.extend(w.encode(raw));
+                        }
+                        data
+                    },
+                );
+            }
+            if let Ok(maps) = self.get_raw_stream(MINIDUMP_STREAM_TYPE::LinuxMaps as u32) {
+                let maps = stripped_maps_paths(maps);
+                self.replace_stream(&mut writer, MINIDUMP_STREAM_TYPE::LinuxMaps, |_| maps);
+            }
+        }
+
+        writer.bytes
+    }
+
+    /// Zero the stream of type `stream_type` in `writer`, and point its stream
+    /// directory entry at a new one, with the contents `data` returns.
+    fn replace_stream(
+        &self,
+        writer: &mut MinidumpWriter,
+        stream_type: MINIDUMP_STREAM_TYPE,
+        data: impl FnOnce(&MinidumpWriter) -> Vec<u8>,
+    ) {
+        let Some((index, directory)) = self.streams.get(&(stream_type as u32)) else {
+            return;
+        };
+        writer.zero(
+            directory.location.rva.into(),
+            directory.location.data_size.into(),
+        );
+        let data = data(writer);
+        let entry = md::MINIDUMP_DIRECTORY {
+            stream_type: stream_type as u32,
+            location: writer.append(&data),
+        };
+        let entry_size = md::MINIDUMP_DIRECTORY::size_with(&self.endian);
+        let offset = self.header.stream_directory_rva as usize + *index as usize * entry_size;
+        let endian = self.endian;
+        writer
+            .bytes
+            .pwrite_with(entry, offset, endian)
+            .expect("the stream directory was read from here");
+    }
+
+    /// A listing of all the streams in the Minidump that this library is *aware* of,
+    /// but has no further analysis for.
+    ///
+    /// If there are multiple copies of the same stream type (which should not happen for
+    /// well-formed Minidumps), then only one of them will be yielded, arbitrarily.
+    pub fn unimplemented_streams(&self) -> impl Iterator<Item = MinidumpUnimplementedStream> + '_ {
+        static UNIMPLEMENTED_STREAMS: [MINIDUMP_STREAM_TYPE; 22] = [
+            // Presumably will never have an implementation:
+            MINIDUMP_STREAM_TYPE::UnusedStream,
+            MINIDUMP_STREAM_TYPE::ReservedStream0,
+            MINIDUMP_STREAM_TYPE::ReservedStream1,
+            MINIDUMP_STREAM_TYPE::LastReservedStream,
+            // Presumably should be implemented:
+            MINIDUMP_STREAM_TYPE::ThreadExListStream,
+            MINIDUMP_STREAM_TYPE::CommentStreamA,
+            MINIDUMP_STREAM_TYPE::CommentStreamW,
+            MINIDUMP_STREAM_TYPE::JavaScriptDataStream,
+            MINIDUMP_STREAM_TYPE::IptTraceStream,
+            // Windows CE streams, very unlikely to be found in the wild.
+            // Their contents are documented here: https://docs.microsoft.com/en-us/previous-versions/windows/embedded/ms939618(v=msdn.10)
+            MINIDUMP_STREAM_TYPE::ceStreamNull,
+            MINIDUMP_STREAM_TYPE::ceStreamSystemInfo,
+            MINIDUMP_STREAM_TYPE::ceStreamException,
+            MINIDUMP_STREAM_TYPE::ceStreamModuleList,
+            MINIDUMP_STREAM_TYPE::ceStreamProcessList,
+            MINIDUMP_STREAM_TYPE::ceStreamThreadList,
+            MINIDUMP_STREAM_TYPE::ceStreamThreadContextList,
+            MINIDUMP_STREAM_TYPE::ceStreamThreadCallStackList,
+            MINIDUMP_STREAM_TYPE::ceStreamMemoryVirtualList,
+            MINIDUMP_STREAM_TYPE::ceStreamMemoryPhysicalList,
+            MINIDUMP_STREAM_TYPE::ceStreamBucketParameters,
+            MINIDUMP_STREAM_TYPE::ceStreamProcessModuleMap,
+            MINIDUMP_STREAM_TYPE::ceStreamDiagnosisList,
+        ];
+        self.streams.iter().filter_map(|(_, (_, stream))| {
+            MINIDUMP_STREAM_TYPE::from_u32(stream.stream_type).and_then(|stream_type| {
+                if UNIMPLEMENTED_STREAMS.contains(&stream_type) {
+                    return Some(MinidumpUnimplementedStream {
+                        stream_type,
+                        location: stream.location,
+                        vendor: stream_vendor(stream.stream_type),
+                    });
+                }
+                None
+            })
+        })
+    }
+
+    /// A listing of all the streams in the Minidump that this library has no knowledge of.
+    ///
+    /// If there are multiple copies of the same stream (which should not happen for
+    /// well-formed Minidumps), then only one of them will be yielded, arbitrarily.
+    pub fn unknown_streams(&self) -> impl Iterator<Item = MinidumpUnknownStream> + '_ {
+        self.streams.iter().filter_map(|(_, (_, stream))| {
+            if MINIDUMP_STREAM_TYPE::from_u32(stream.stream_type).is_none() {
+                return Some(MinidumpUnknownStream {
+                    stream_type: stream.stream_type,
+                    location: stream.location,
+                    vendor: stream_vendor(stream.stream_type),
+                });
+            }
+            None
+        })
+    }
+
+    /// The streams that were ignored because a later stream in the stream
+    /// directory has the same type, in stream directory order.
+    ///
+    /// Minidumps shouldn't have more than one stream of any type, so these
+    /// usually point to a bug in whatever wrote the minidump. (Empty
+    /// `UnusedStream`s are padding, and aren't included.)
+    pub fn duplicate_streams(&self) -> &[MinidumpDuplicateStream] {
+        &self.duplicate_streams
+    }
+
+    /// Every stream of type `stream_type` in the stream directory, in stream
+    /// directory order.
+    ///
+    /// There should be at most one, but there are minidump writers that write
+    /// some streams twice. The last one is the one that [`Minidump::get_stream`][]
+    /// uses, the others are the [`Minidump::duplicate_streams`][].
+    pub fn streams_of_type(
+        &'a self,
+        stream_type: u32,
+    ) -> impl Iterator<Item = MinidumpStreamEntry<'a>> + 'a {
+        self.stream_entries()
+            .filter(move |entry| entry.stream_type() == stream_type)
+    }
+
+    /// Every entry of the stream directory, in stream directory order.
+    ///
+    /// Unlike [`Minidump::typed_streams`][], this includes the duplicate
+    /// streams and the `UnusedStream` padding, so it's the way to get at
+    /// everything a minidump writer put in. Streams of unknown (or
+    /// vendor-specific) types are read as [`TypedStream::Unknown`][], and
+    /// [`MinidumpStreamEntry::raw_bytes`][] gives the contents of any of them.
+    pub fn stream_entries(&'a self) -> impl Iterator<Item = MinidumpStreamEntry<'a>> + 'a {
+        self.directory
+            .iter()
+            .enumerate()
+            .map(move |(index, directory)| MinidumpStreamEntry {
+                index: index as u32,
+                directory,
+                all: self.data.deref(),
+                endian: self.endian,
+                system_info: self.system_info.as_ref(),
+            })
+    }
+
+    /// A listing of all the streams in the Minidump.
+    ///
+    /// If there are multiple copies of the same stream (which should not happen for
+    /// well-formed Minidumps), then only the last one is yielded, which is the
+    /// one [`Minidump::get_stream`][] uses.
+    pub fn all_streams(&self) -> impl Iterator<Item = &md::MINIDUMP_DIRECTORY> + '_ {
+        self.streams.iter().map(|(_, (_, stream))| stream)
+    }
+
+    /// A listing of all the streams in the Minidump, in stream directory order,
+    /// which can each be parsed into a [`TypedStream`][].
+    ///
+    /// This is cheap, no stream is parsed until [`MinidumpStreamEntry::read`][]
+    /// is called. If there are multiple copies of the same stream (which should not
+    /// happen for well-formed Minidumps), then only the last one is yielded (see
+    /// [`Minidump::streams_of_type`][] for all of them).
+    ///
+    /// ```
+    /// use minidump::*;
+    ///
+    /// fn main() -> Result<(), Error> {
+    ///     let dump = Minidump::read_path("../testdata/test.dmp")?;
+    ///     let system_info = dump.get_stream::<MinidumpSystemInfo>().ok();
+    ///     let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+    ///     let memory = dump.get_memory();
+    ///
+    ///     let mut out = Vec::new();
+    ///     for entry in dump.typed_streams() {
+    ///         if let Ok(stream) = entry.read() {
+    ///             stream
+    ///                 .print(
+    ///                     &mut out,
+    ///                     memory.as_ref(),
+    ///                     system_info.as_ref(),
+    ///                     misc_info.as_ref(),
+    ///                     true,
+    ///                 )
+    ///                 .unwrap();
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn typed_streams(&'a self) -> impl Iterator<Item = MinidumpStreamEntry<'a>> + 'a {
+        let mut streams: Vec<_> = self.streams.values().collect();
+        streams.sort_by_key(|(index, _)| *index);
+        streams
+            .into_iter()
+            .map(move |(index, directory)| MinidumpStreamEntry {
+                index: *index,
+                directory,
+                all: self.data.deref(),
+                endian: self.endian,
+                system_info: self.system_info.as_ref(),
+            })
+    }
+
+    /// Write a verbose description of the `Minidump` to `f`.
+    pub fn print<W: Write>(&self, f: &mut W) -> io::Result<()> {
+        fn get_stream_name(stream_type: u32) -> Cow<'static, str> {
+            if let Some(stream) = MINIDUMP_STREAM_TYPE::from_u32(stream_type) {
+                Cow::Owned(format!("{stream:?}"))
+            } else {
+                Cow::Borrowed("unknown")
+            }
+        }
+
+        write!(
             f,
             r#"MDRawHeader
   signature            = {:#x}
@@ -5716,27 +9308,43 @@ fn stream_vendor(stream_type: u32) -> &'static str {
     } else {
         match stream_type & 0xFFFF0000 {
             0x4767_0000 => "Google Extension",
+            0x4350_0000 => "Crashpad Extension",
             0x4d7a_0000 => "Mozilla Extension",
             _ => "Unknown Extension",
         }
     }
 }
 
+/// A best-effort human-readable name for a stream type.
+///
+/// This is the name of the [`MINIDUMP_STREAM_TYPE`] if it's one we know of, and
+/// otherwise who the stream type probably belongs to (e.g. "Crashpad Extension").
+pub fn stream_type_name(stream_type: u32) -> Cow<'static, str> {
+    match MINIDUMP_STREAM_TYPE::from_u32(stream_type) {
+        Some(known_stream_type) => Cow::Owned(format!("{known_stream_type:?}")),
+        None => Cow::Borrowed(stream_vendor(stream_type)),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::system_info::CpuEmulation;
     use md::GUID;
     use minidump_common::{
         errors::NtStatusWindows,
         format::{PlatformId, ProcessorArchitecture},
     };
     use minidump_synth::{
-        AnnotationValue, CrashpadInfo, DumpString, Exception,
-        HandleDescriptor as SynthHandleDescriptor, Memory, MemoryInfo as SynthMemoryInfo,
-        MiscFieldsBuildString, MiscFieldsPowerInfo, MiscFieldsProcessTimes, MiscFieldsTimeZone,
-        MiscInfo5Fields, MiscStream, Module as SynthModule, ModuleCrashpadInfo, SimpleStream,
-        SynthMinidump, SystemInfo, Thread, ThreadName, UnloadedModule as SynthUnloadedModule,
-        STOCK_VERSION_INFO,
+        function_table_stream, handle_operation_list_stream, linux_auxv_stream,
+        linux_dso_debug_stream, process_vm_counters_1_stream, process_vm_counters_2_stream,
+        system_memory_info_stream, token_stream, AnnotationValue, CpuInfo, CrashpadInfo,
+        DumpString, Exception, FunctionTable, HandleDescriptor as SynthHandleDescriptor, LinkMap,
+        Memory, MemoryInfo as SynthMemoryInfo, MiscFieldsBuildString, MiscFieldsPowerInfo,
+        MiscFieldsProcessTimes, MiscFieldsTimeZone, MiscInfo5Fields, MiscStream,
+        Module as SynthModule, ModuleCrashpadInfo, SectionExtra, SimpleStream, SynthMinidump,
+        SystemInfo, Thread, ThreadInfo as SynthThreadInfo, ThreadName,
+        UnloadedModule as SynthUnloadedModule, STOCK_VERSION_INFO,
     };
     use test_assembler::*;
 
@@ -5769,6 +9377,646 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_duplicate_streams() {
+        const STREAM_TYPE: u32 = 0x4350_000a;
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(SimpleStream {
+                stream_type: STREAM_TYPE,
+                section: Section::with_endian(Endian::Little).D32(1),
+            })
+            .add_stream(SimpleStream {
+                stream_type: STREAM_TYPE,
+                section: Section::with_endian(Endian::Little).D32(2).D32(3),
+            });
+        let dump = read_synth_dump(dump).unwrap();
+
+        // The last stream of the type wins
+        assert_eq!(
+            dump.get_raw_stream(STREAM_TYPE).unwrap(),
+            &[2, 0, 0, 0, 3, 0, 0, 0]
+        );
+        let duplicates = dump.duplicate_streams();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].stream_type, STREAM_TYPE);
+        assert_eq!(duplicates[0].location.data_size, 4);
+        assert_eq!(duplicates[0].vendor, "Crashpad Extension");
+        assert_eq!(duplicates[0].index, 0);
+        assert_eq!(
+            dump.corruption_notes(),
+            &[CorruptionNote::DuplicateStream {
+                stream_type: STREAM_TYPE,
+                index: 0,
+                used_index: 1,
+            }]
+        );
+
+        let instances: Vec<_> = dump.streams_of_type(STREAM_TYPE).collect();
+        assert_eq!(instances.len(), 2);
+        assert_eq!(instances[0].index, 0);
+        assert_eq!(instances[0].raw_bytes().unwrap(), &[1, 0, 0, 0]);
+        assert_eq!(instances[1].index, 1);
+        assert_eq!(instances[1].raw_bytes().unwrap(), &[2, 0, 0, 0, 3, 0, 0, 0]);
+        assert_eq!(dump.streams_of_type(0x4350_000b).count(), 0);
+
+        let unknown: Vec<_> = dump.unknown_streams().collect();
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].location.data_size, 8);
+    }
+
+    #[test]
+    fn test_validate_streams() {
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(SimpleStream {
+                stream_type: 0x4350_000a,
+                section: Section::with_endian(Endian::Little).D32(1).D32(2),
+            })
+            .add_stream(SimpleStream {
+                stream_type: 0x4350_000b,
+                section: Section::with_endian(Endian::Little).D32(3),
+            })
+            .add_stream(SimpleStream {
+                stream_type: 0x4350_000c,
+                section: Section::with_endian(Endian::Little).D32(4),
+            });
+        let mut bytes = dump.finish().unwrap();
+        let header: md::MINIDUMP_HEADER = bytes.pread_with(0, LE).unwrap();
+        let entry_offset = |index: usize| header.stream_directory_rva as usize + index * 12;
+        let mut entries: Vec<md::MINIDUMP_DIRECTORY> = (0..3)
+            .map(|index| bytes.pread_with(entry_offset(index), LE).unwrap())
+            .collect();
+        // The second stream starts in the middle of the first, and the last one
+        // runs off the end of the file.
+        entries[1].location.rva = entries[0].location.rva + 4;
+        entries[2].location.data_size = 0x1000;
+        for (index, entry) in entries.iter().enumerate() {
+            bytes
+                .pwrite_with(entry.clone(), entry_offset(index), LE)
+                .unwrap();
+        }
+        let file_size = bytes.len() as u64;
+        let dump = Minidump::read(bytes).unwrap();
+
+        assert_eq!(
+            dump.validate(),
+            [
+                CorruptionNote::StreamTruncated {
+                    stream_type: 0x4350_000c,
+                    index: 2,
+                    rva: entries[2].location.rva,
+                    claimed_size: 0x1000,
+                    available_size: file_size - u64::from(entries[2].location.rva),
+                },
+                // The stream directory is at the end of the file.
+                CorruptionNote::StreamOverlapsDirectory {
+                    stream_type: 0x4350_000c,
+                    index: 2,
+                    rva: entries[2].location.rva,
+                    size: 0x1000,
+                },
+                CorruptionNote::StreamUnreadable {
+                    stream_type: 0x4350_000c,
+                    index: 2,
+                    rva: entries[2].location.rva,
+                    size: 0x1000,
+                    error: Error::StreamReadFailure,
+                },
+                CorruptionNote::StreamOverlap {
+                    stream_type: 0x4350_000b,
+                    index: 1,
+                    rva: entries[0].location.rva + 4,
+                    size: 4,
+                    other_index: 0,
+                    other_rva: entries[0].location.rva,
+                    other_size: 8,
+                },
+            ]
+        );
+        assert!(dump.corruption_notes().is_empty());
+    }
+
+    #[test]
+    fn test_stream_entries() {
+        const STREAM_TYPE: u32 = 0x4350_000a;
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(SimpleStream {
+                stream_type: STREAM_TYPE,
+                section: Section::with_endian(Endian::Little).D32(1),
+            })
+            .add_stream(SimpleStream {
+                stream_type: 0x1122_3344,
+                section: Section::with_endian(Endian::Little).D16(0xabcd),
+            })
+            .add_stream(SimpleStream {
+                stream_type: STREAM_TYPE,
+                section: Section::with_endian(Endian::Little).D32(2),
+            });
+        let dump = read_synth_dump(dump).unwrap();
+
+        let entries: Vec<_> = dump.stream_entries().collect();
+        let types: Vec<_> = entries.iter().map(|entry| entry.stream_type()).collect();
+        assert_eq!(types, [STREAM_TYPE, 0x1122_3344, STREAM_TYPE]);
+        let indices: Vec<_> = entries.iter().map(|entry| entry.index).collect();
+        assert_eq!(indices, [0, 1, 2]);
+        assert_eq!(entries[0].raw_bytes().unwrap(), &[1, 0, 0, 0]);
+        assert_eq!(entries[1].raw_bytes().unwrap(), &[0xcd, 0xab]);
+        assert_eq!(entries[2].raw_bytes().unwrap(), &[2, 0, 0, 0]);
+        assert!(matches!(
+            entries[1].read(),
+            Ok(TypedStream::Unknown(&[0xcd, 0xab]))
+        ));
+
+        // Only the last of the duplicates is a typed stream
+        assert_eq!(dump.typed_streams().count(), 2);
+    }
+
+    #[test]
+    fn test_function_tables() {
+        let tables = [
+            FunctionTable {
+                minimum_address: 0x3a5c_0000,
+                maximum_address: 0x3a5c_1000,
+                base_address: 0x3a5c_0000,
+                entries: vec![(0x0, 0x40, 0x800), (0x40, 0x100, 0x810)],
+            },
+            FunctionTable {
+                minimum_address: 0x4000_0000,
+                maximum_address: 0x4000_0100,
+                base_address: 0x3fff_0000,
+                entries: vec![],
+            },
+        ];
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(function_table_stream(
+            Endian::Little,
+            &tables,
+            0x58,
+        ));
+        let dump = read_synth_dump(dump).unwrap();
+        let stream = dump.get_stream::<MinidumpFunctionTableStream>().unwrap();
+        assert_eq!(stream.tables.len(), 2);
+
+        let table = stream.table_at_address(0x3a5c_0fff).unwrap();
+        assert_eq!(table.raw.base_address, 0x3a5c_0000);
+        assert_eq!(table.native_descriptor.len(), 0x58);
+        let entries: Vec<_> = table.amd64_entries().collect();
+        assert_eq!(
+            entries,
+            [
+                md::IMAGE_AMD64_RUNTIME_FUNCTION_ENTRY {
+                    begin_address: 0x0,
+                    end_address: 0x40,
+                    unwind_info_address: 0x800,
+                },
+                md::IMAGE_AMD64_RUNTIME_FUNCTION_ENTRY {
+                    begin_address: 0x40,
+                    end_address: 0x100,
+                    unwind_info_address: 0x810,
+                },
+            ]
+        );
+
+        let table = stream.table_at_address(0x4000_0000).unwrap();
+        assert_eq!(table.raw.base_address, 0x3fff_0000);
+        assert_eq!(table.amd64_entries().count(), 0);
+        assert!(stream.table_at_address(0x4000_0100).is_none());
+        assert!(stream.table_at_address(0x3a5b_ffff).is_none());
+
+        // A table whose entries are cut off
+        let contents = function_table_stream(Endian::Little, &tables[..1], 0)
+            .section
+            .get_contents()
+            .unwrap();
+        let truncated =
+            Section::with_endian(Endian::Little).append_bytes(&contents[..24 + 32 + 12]);
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::FunctionTable as u32,
+            section: truncated,
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        assert!(dump.get_stream::<MinidumpFunctionTableStream>().is_err());
+    }
+
+    #[test]
+    fn test_linux_dso_debug() {
+        let exe = DumpString::new("", Endian::Little);
+        let vdso = DumpString::new("linux-vdso.so.1", Endian::Little);
+        let libc = DumpString::new("/lib/x86_64-linux-gnu/libc.so.6", Endian::Little);
+        let plugin = DumpString::new("./plugins/libplugin.so", Endian::Little);
+        let link_maps = [
+            LinkMap {
+                load_bias: 0x5555_5555_4000,
+                name: &exe,
+                dynamic: 0x5555_5555_7dc8,
+            },
+            LinkMap {
+                load_bias: 0x7fff_f7fc_1000,
+                name: &vdso,
+                dynamic: 0x7fff_f7fc_13e0,
+            },
+            LinkMap {
+                load_bias: 0x7fff_f7d8_0000,
+                name: &libc,
+                dynamic: 0x7fff_f7f9_9bc0,
+            },
+            LinkMap {
+                load_bias: 0x7fff_f7f0_0000,
+                name: &plugin,
+                dynamic: 0x7fff_f7f0_3e00,
+            },
+        ];
+        let libc_path = DumpString::new("/usr/lib/x86_64-linux-gnu/libc.so.6", Endian::Little);
+        let module = SynthModule::new(
+            Endian::Little,
+            0x7fff_f7d8_0000,
+            0x1000,
+            &libc_path,
+            0,
+            0,
+            None,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(linux_dso_debug_stream(
+                Endian::Little,
+                &link_maps,
+                0x7fff_f7fc_3000,
+                0x5555_5555_7dc8,
+            ))
+            .add_module(module)
+            .add(libc_path)
+            .add(exe)
+            .add(vdso)
+            .add(libc)
+            .add(plugin);
+        let dump = read_synth_dump(dump).unwrap();
+        let dso_debug = dump.get_stream::<MinidumpLinuxDsoDebug>().unwrap();
+        assert_eq!(dso_debug.version, 1);
+        assert_eq!(dso_debug.ldbase, 0x7fff_f7fc_3000);
+        assert_eq!(dso_debug.dynamic, 0x5555_5555_7dc8);
+        assert_eq!(dso_debug.link_maps.len(), 4);
+        assert_eq!(
+            dso_debug.link_maps[2],
+            MinidumpLinkMap {
+                load_bias: 0x7fff_f7d8_0000,
+                name: Some(String::from("/lib/x86_64-linux-gnu/libc.so.6")),
+                dynamic: 0x7fff_f7f9_9bc0,
+            }
+        );
+        assert_eq!(dso_debug.link_maps[0].name.as_deref(), Some(""));
+        assert!(dump.unimplemented_streams().next().is_none());
+
+        // libc is in the module list under another path, and the executable
+        // and the vdso aren't files.
+        let modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let unlisted: Vec<_> = dso_debug.unlisted_objects(&modules).collect();
+        assert_eq!(unlisted, [&dso_debug.link_maps[3]]);
+
+        // A list of link maps past the end of the minidump
+        let section = Section::with_endian(Endian::Little)
+            .D32(1) // version
+            .D32(0x10_0000) // map
+            .D32(1) // dso_count
+            .D64(0) // brk
+            .D64(0) // ldbase
+            .D64(0); // dynamic
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::LinuxDsoDebug as u32,
+            section,
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        assert!(dump.get_stream::<MinidumpLinuxDsoDebug>().is_err());
+    }
+
+    #[test]
+    fn test_handle_operation_list() {
+        let operation = |handle, operation_type: md::HandleTraceOperation, stack: &[u64]| {
+            let mut return_addresses = [0; 32];
+            return_addresses[..stack.len()].copy_from_slice(stack);
+            return_addresses[stack.len()] = 0xdead;
+            md::AVRF_HANDLE_OPERATION {
+                handle,
+                process_id: 0x100,
+                thread_id: 0x200,
+                operation_type: operation_type as u32,
+                spare0: 0,
+                back_trace_information: md::AVRF_BACKTRACE_INFORMATION {
+                    depth: stack.len() as u32,
+                    index: 0,
+                    return_addresses,
+                },
+            }
+        };
+        let operations = [
+            operation(
+                0x4,
+                md::HandleTraceOperation::OperationDbOPEN,
+                &[0x1000, 0x2000],
+            ),
+            operation(0x8, md::HandleTraceOperation::OperationDbOPEN, &[0x1000]),
+            operation(0x4, md::HandleTraceOperation::OperationDbCLOSE, &[0x3000]),
+            operation(
+                0x4,
+                md::HandleTraceOperation::OperationDbBADREF,
+                &[0x4000, 0x5000],
+            ),
+        ];
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(handle_operation_list_stream(Endian::Little, &operations));
+        let dump = read_synth_dump(dump).unwrap();
+        let list = dump.get_stream::<MinidumpHandleOperationList>().unwrap();
+        assert_eq!(list.operations.len(), 4);
+
+        let types: Vec<_> = list
+            .operations_on_handle(0x4)
+            .map(|operation| operation.operation_type())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                Some(md::HandleTraceOperation::OperationDbOPEN),
+                Some(md::HandleTraceOperation::OperationDbCLOSE),
+                Some(md::HandleTraceOperation::OperationDbBADREF),
+            ]
+        );
+        assert_eq!(list.operations[0].backtrace(), [0x1000, 0x2000]);
+        assert_eq!(list.operations[0].raw.thread_id, 0x200);
+
+        let bad_references: Vec<_> = list.bad_references().collect();
+        assert_eq!(bad_references.len(), 1);
+        assert_eq!(bad_references[0].raw.handle, 0x4);
+        assert_eq!(bad_references[0].backtrace(), [0x4000, 0x5000]);
+    }
+
+    #[test]
+    fn test_token_stream() {
+        // Some groups, then the integrity SID S-1-16-12288 (high)
+        let mut elevated = vec![0xaa; 0x30];
+        elevated.extend_from_slice(&[1, 2, 0, 0, 0, 0, 0, 5, 32, 0, 0, 0, 32, 2, 0, 0]);
+        elevated.extend_from_slice(&[1, 1, 0, 0, 0, 0, 0, 16, 0, 0x30, 0, 0]);
+        elevated.extend_from_slice(&[0xbb; 4]);
+        let medium = [1, 1, 0, 0, 0, 0, 0, 16, 0, 0x20, 0, 0];
+        let tokens: [(u32, &[u8]); 3] = [(0x10, &elevated), (0x20, &medium), (0x30, &[0xcc; 8])];
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(token_stream(Endian::Little, &tokens));
+        let dump = read_synth_dump(dump).unwrap();
+        let stream = dump.get_stream::<MinidumpTokenStream>().unwrap();
+        assert_eq!(stream.tokens.len(), 3);
+
+        let token = &stream.tokens[0];
+        assert_eq!(token.raw.token_id, 0x10);
+        assert_eq!(token.raw.token_handle, 0x10);
+        assert_eq!(token.data, &elevated[..]);
+        assert_eq!(
+            token.integrity_level(),
+            Some(md::MandatoryLevel::SECURITY_MANDATORY_HIGH_RID as u32)
+        );
+        assert_eq!(token.is_elevated(), Some(true));
+
+        let token = &stream.tokens[1];
+        assert_eq!(token.integrity_level(), Some(0x2000));
+        assert_eq!(token.is_elevated(), Some(false));
+
+        let token = &stream.tokens[2];
+        assert_eq!(token.data, &[0xcc; 8]);
+        assert_eq!(token.integrity_level(), None);
+        assert_eq!(token.is_elevated(), None);
+    }
+
+    #[test]
+    fn test_system_memory_info() {
+        assert_eq!(md::MINIDUMP_SYSTEM_MEMORY_INFO_1::size_with(&LE), 492);
+        let mut info = md::MINIDUMP_SYSTEM_MEMORY_INFO_1 {
+            revision: 1,
+            flags: md::SystemMemoryInfoFlags::MINIDUMP_SYSMEMINFO1_BASICPERF.bits(),
+            ..Default::default()
+        };
+        info.basic_info.page_size = 0x1000;
+        info.basic_info.number_of_physical_pages = 0x40_0000;
+        info.basic_perf_info.available_pages = 0x1_0000;
+        info.basic_perf_info.committed_pages = 0x7f_0000;
+        info.basic_perf_info.commit_limit = 0x80_0000;
+        info.basic_perf_info.peak_commitment = 0x7f_8000;
+        info.perf_info.available_pages = 1;
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(system_memory_info_stream(Endian::Little, info.clone()));
+        let dump = read_synth_dump(dump).unwrap();
+        let memory_info = dump.get_stream::<MinidumpSystemMemoryInfo>().unwrap();
+        assert_eq!(memory_info.page_size(), 0x1000);
+        assert_eq!(memory_info.physical_memory(), 0x4_0000_0000);
+        assert_eq!(memory_info.available_physical_memory(), 0x1000_0000);
+        assert_eq!(memory_info.committed(), 0x7_f000_0000);
+        assert_eq!(memory_info.commit_limit(), 0x8_0000_0000);
+        assert_eq!(memory_info.peak_commitment(), 0x7_f800_0000);
+
+        // Without the 64-bit counts, the ones of the performance info are used.
+        info.flags = 0;
+        info.perf_info.committed_pages = 0x20;
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(system_memory_info_stream(Endian::Little, info));
+        let dump = read_synth_dump(dump).unwrap();
+        let memory_info = dump.get_stream::<MinidumpSystemMemoryInfo>().unwrap();
+        assert_eq!(memory_info.available_physical_memory(), 0x1000);
+        assert_eq!(memory_info.committed(), 0x2_0000);
+        assert_eq!(memory_info.commit_limit(), 0);
+    }
+
+    #[test]
+    fn test_process_vm_counters() {
+        assert_eq!(md::MINIDUMP_PROCESS_VM_COUNTERS_1::size_with(&LE), 80);
+        assert_eq!(md::MINIDUMP_PROCESS_VM_COUNTERS_2::size_with(&LE), 152);
+        let counters = md::MINIDUMP_PROCESS_VM_COUNTERS_1 {
+            revision: 1,
+            page_fault_count: 1234,
+            working_set_size: 0x100_0000,
+            peak_working_set_size: 0x200_0000,
+            private_usage: 0x300_0000,
+            ..Default::default()
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(process_vm_counters_1_stream(Endian::Little, counters));
+        let dump = read_synth_dump(dump).unwrap();
+        let counters = dump.get_stream::<MinidumpProcessVmCounters>().unwrap();
+        assert!(matches!(counters.raw, RawProcessVmCounters::V1(_)));
+        assert_eq!(counters.page_fault_count(), Some(1234));
+        assert_eq!(counters.working_set_size(), Some(0x100_0000));
+        assert_eq!(counters.peak_working_set_size(), Some(0x200_0000));
+        assert_eq!(counters.private_usage(), Some(0x300_0000));
+        assert_eq!(counters.virtual_size(), None);
+        assert_eq!(counters.private_working_set_size(), None);
+
+        // The second revision only has the fields of its flags.
+        let counters = md::MINIDUMP_PROCESS_VM_COUNTERS_2 {
+            revision: 2,
+            flags: (md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS
+                | md::ProcessVmCountersFlags::MINIDUMP_PROCESS_VM_COUNTERS_VIRTUALSIZE)
+                .bits(),
+            working_set_size: 0x100_0000,
+            virtual_size: 0x7000_0000,
+            peak_virtual_size: 0x7800_0000,
+            private_usage: 0x300_0000,
+            ..Default::default()
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(process_vm_counters_2_stream(Endian::Little, counters));
+        let dump = read_synth_dump(dump).unwrap();
+        let counters = dump.get_stream::<MinidumpProcessVmCounters>().unwrap();
+        assert!(matches!(counters.raw, RawProcessVmCounters::V2(_)));
+        assert_eq!(counters.working_set_size(), Some(0x100_0000));
+        assert_eq!(counters.virtual_size(), Some(0x7000_0000));
+        assert_eq!(counters.peak_virtual_size(), Some(0x7800_0000));
+        assert_eq!(counters.private_usage(), None);
+        assert_eq!(counters.private_working_set_size(), None);
+    }
+
+    #[test]
+    fn test_linux_auxv() {
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+            md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+        );
+        let vdso_name = DumpString::new("linux-gate.so", Endian::Little);
+        let vdso = SynthModule::new(
+            Endian::Little,
+            0x7fff_f7fc_1000,
+            0x2000,
+            &vdso_name,
+            0,
+            0,
+            None,
+        );
+        let entries = [
+            (md::AuxvType::AT_SYSINFO_EHDR as u64, 0x7fff_f7fc_1000),
+            (md::AuxvType::AT_HWCAP as u64, 0x178b_fbff),
+            (md::AuxvType::AT_PAGESZ as u64, 0x1000),
+            (0x1234, 0x5678),
+            (md::AuxvType::AT_PLATFORM as u64, 0x7fff_ffff_e3a9),
+        ];
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(system_info)
+            .add_stream(linux_auxv_stream(Endian::Little, &entries))
+            .add_module(vdso)
+            .add(vdso_name);
+        let dump = read_synth_dump(dump).unwrap();
+        let auxv = dump.get_stream::<MinidumpLinuxAuxv>().unwrap();
+        assert_eq!(auxv.entries.len(), 5);
+        assert_eq!(auxv.vdso_base(), Some(0x7fff_f7fc_1000));
+        assert_eq!(auxv.hwcap(), Some(0x178b_fbff));
+        assert_eq!(auxv.get(md::AuxvType::AT_PAGESZ), Some(0x1000));
+        assert_eq!(auxv.get(md::AuxvType::AT_ENTRY), None);
+        let types: Vec<_> = auxv.iter().map(|entry| entry.known_type()).collect();
+        assert_eq!(
+            types,
+            [
+                Some(md::AuxvType::AT_SYSINFO_EHDR),
+                Some(md::AuxvType::AT_HWCAP),
+                Some(md::AuxvType::AT_PAGESZ),
+                None,
+                Some(md::AuxvType::AT_PLATFORM),
+            ]
+        );
+        assert!(dump.unimplemented_streams().next().is_none());
+
+        // The vdso's module can be found from its base.
+        let modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let module = modules
+            .module_at_address(auxv.vdso_base().unwrap() + 0x800)
+            .unwrap();
+        assert_eq!(module.code_file(), "linux-gate.so");
+
+        // A 32-bit vector without its AT_NULL and with a partial entry at the end
+        let system_info = SystemInfo::new(Endian::Little);
+        let section = Section::with_endian(Endian::Little)
+            .D32(md::AuxvType::AT_SYSINFO_EHDR as u32)
+            .D32(0xf7fc_1000)
+            .D32(md::AuxvType::AT_SYSINFO as u32)
+            .D32(0xf7fc_1560)
+            .D32(md::AuxvType::AT_HWCAP as u32);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(system_info)
+            .add_stream(SimpleStream {
+                stream_type: MINIDUMP_STREAM_TYPE::LinuxAuxv as u32,
+                section,
+            });
+        let dump = read_synth_dump(dump).unwrap();
+        let auxv = dump.get_stream::<MinidumpLinuxAuxv>().unwrap();
+        assert_eq!(
+            auxv.entries,
+            [
+                MinidumpLinuxAuxvEntry {
+                    raw_type: md::AuxvType::AT_SYSINFO_EHDR as u64,
+                    value: 0xf7fc_1000,
+                },
+                MinidumpLinuxAuxvEntry {
+                    raw_type: md::AuxvType::AT_SYSINFO as u64,
+                    value: 0xf7fc_1560,
+                },
+            ]
+        );
+        assert_eq!(auxv.hwcap(), None);
+    }
+
+    #[test]
+    fn test_unaligned_unsorted_directory() {
+        // A stream directory that isn't 4-byte aligned, with entries that are
+        // neither sorted by type nor by RVA, one of which points into the
+        // directory itself.
+        let mut data = Vec::new();
+        for field in [
+            md::MINIDUMP_SIGNATURE,
+            md::MINIDUMP_VERSION,
+            3,
+            0x21,
+            0,
+            0,
+            0,
+            0,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.push(0);
+        for (stream_type, size, rva) in [
+            (0x4350_000bu32, 4u32, 0x49u32),
+            (0x4350_000a, 4, 0x45),
+            (0x4350_000c, 12, 0x21),
+        ] {
+            data.extend_from_slice(&stream_type.to_le_bytes());
+            data.extend_from_slice(&size.to_le_bytes());
+            data.extend_from_slice(&rva.to_le_bytes());
+        }
+        assert_eq!(data.len(), 0x45);
+        data.extend_from_slice(b"aaaabbbb");
+
+        let dump = Minidump::read(data).unwrap();
+        assert_eq!(dump.get_raw_stream(0x4350_000a).unwrap(), b"aaaa");
+        assert_eq!(dump.get_raw_stream(0x4350_000b).unwrap(), b"bbbb");
+        assert_eq!(
+            dump.get_raw_stream(0x4350_000c).unwrap(),
+            &[0x0b, 0, 0x50, 0x43, 4, 0, 0, 0, 0x49, 0, 0, 0]
+        );
+        assert_eq!(dump.corruption_notes(), &[]);
+
+        let types: Vec<_> = dump
+            .typed_streams()
+            .map(|entry| (entry.index, entry.stream_type()))
+            .collect();
+        assert_eq!(
+            types,
+            [(0, 0x4350_000b), (1, 0x4350_000a), (2, 0x4350_000c)]
+        );
+    }
+
+    #[test]
+    fn test_stream_type_name() {
+        assert_eq!(
+            stream_type_name(MINIDUMP_STREAM_TYPE::ThreadListStream as u32),
+            "ThreadListStream"
+        );
+        assert_eq!(
+            stream_type_name(MINIDUMP_STREAM_TYPE::LinuxMaps as u32),
+            "LinuxMaps"
+        );
+        assert_eq!(stream_type_name(0x4350_000a), "Crashpad Extension");
+        assert_eq!(stream_type_name(0x1122_3344), "Unknown Extension");
+    }
+
     #[test]
     fn test_simple_synth_dump_bigendian() {
         const STREAM_TYPE: u32 = 0x11223344;
@@ -5789,6 +10037,50 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_typed_streams() {
+        const STREAM_TYPE: u32 = 0x11223344;
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(system_info)
+            .add_stream(SimpleStream {
+                stream_type: STREAM_TYPE,
+                section: Section::with_endian(Endian::Little).D32(0x55667788),
+            });
+        let dump = read_synth_dump(dump).unwrap();
+
+        let entries: Vec<_> = dump.typed_streams().collect();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.windows(2).all(|pair| pair[0].index < pair[1].index));
+
+        let mut out = Vec::new();
+        for entry in &entries {
+            let stream = entry.read().unwrap();
+            match entry.stream_type() {
+                STREAM_TYPE => {
+                    assert_eq!(entry.vendor(), "Unknown Extension");
+                    assert!(matches!(
+                        stream,
+                        TypedStream::Unknown(&[0x88, 0x77, 0x66, 0x55])
+                    ));
+                }
+                t if t == MINIDUMP_STREAM_TYPE::SystemInfoStream as u32 => {
+                    assert_eq!(entry.vendor(), "Official");
+                    match &stream {
+                        TypedStream::SystemInfo(info) => assert_eq!(info.cpu, Cpu::X86_64),
+                        _ => panic!("Got unexpected stream type!"),
+                    }
+                }
+                t => panic!("Got unexpected stream {:#x}", t),
+            }
+            stream.print(&mut out, None, None, None, false).unwrap();
+        }
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("MINIDUMP_SYSTEM_INFO"));
+        assert!(out.contains("Unknown stream\n  size = 4\n"));
+    }
+
     #[test]
     fn test_thread_names() {
         let good_thread_id = 17;
@@ -5808,18 +10100,160 @@ mod test {
 
         let dump = read_synth_dump(dump).unwrap();
         let thread_names = dump.get_stream::<MinidumpThreadNames>().unwrap();
-        assert_eq!(thread_names.names.len(), 1);
+        assert_eq!(thread_names.len(), 1);
+        assert_eq!(
+            &*thread_names.get_name(good_thread_id).unwrap(),
+            "MyCoolThread"
+        );
+        assert_eq!(thread_names.get_name(corrupt_thread_id), None);
+        assert_eq!(
+            thread_names.iter().collect::<Vec<_>>(),
+            [(good_thread_id, "MyCoolThread")]
+        );
+        assert!(MinidumpThreadNames::default().is_empty());
+    }
+
+    #[test]
+    fn test_thread_names_corrupt() {
+        // An unpaired surrogate, an odd length, a huge length, and one that's cut
+        // off by the end of the minidump
+        let surrogate = DumpString::raw(4, b"\x00\xd8a\0", Endian::Little);
+        let odd = DumpString::raw(3, b"a\0b\0", Endian::Little);
+        let huge = DumpString::raw(0xffff_fff0, b"a\0", Endian::Little);
+        let truncated = DumpString::raw(0x1000, b"a\0", Endian::Little);
+
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread_name(ThreadName::new(Endian::Little, 1, Some(&surrogate)))
+            .add_thread_name(ThreadName::new(Endian::Little, 2, Some(&odd)))
+            .add_thread_name(ThreadName::new(Endian::Little, 3, Some(&huge)))
+            .add_thread_name(ThreadName::new(Endian::Little, 4, Some(&truncated)))
+            .add(surrogate)
+            .add(odd)
+            .add(huge)
+            .add(truncated);
+
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_names = dump.get_stream::<MinidumpThreadNames>().unwrap();
+        assert_eq!(&*thread_names.get_name(1).unwrap(), "\u{fffd}a");
+        assert_eq!(&*thread_names.get_name(2).unwrap(), "a\u{fffd}");
+        assert_eq!(thread_names.get_name(3), None);
+        assert_eq!(thread_names.get_name(4), None);
+    }
+
+    #[test]
+    fn test_module_name_too_long() {
+        let name = DumpString::raw(0x7fff_ffff, b"a\0", Endian::Little);
+        let module = SynthModule::new(Endian::Little, 0x1000, 0x1000, &name, 0, 0, None);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_module(module)
+            .add(name);
+
+        let dump = read_synth_dump(dump).unwrap();
+        let error = dump.get_stream::<MinidumpModuleList>().unwrap_err();
+        assert!(
+            matches!(
+                error,
+                Error::StringReadFailure(StringError::TooLong {
+                    length: 0x7fff_ffff,
+                    ..
+                })
+            ),
+            "{:?}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_thread_info_list() {
+        let mut info1 = SynthThreadInfo::new(Endian::Little, 0x1234);
+        info1.create_time = 0x01d7_0000_0000_0000;
+        info1.kernel_time = 1000;
+        info1.user_time = 2000;
+        info1.start_address = 0x7ff8_1234_5678;
+        info1.affinity = 0xf;
+        let mut info2 = SynthThreadInfo::new(Endian::Little, 0x5678);
+        info2.dump_flags = 0x44;
+
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread_info(info1)
+            .add_thread_info(info2);
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_infos = dump.get_stream::<MinidumpThreadInfoList>().unwrap();
+        assert_eq!(thread_infos.thread_infos.len(), 2);
+
+        let info = thread_infos.get_thread_info(0x1234).unwrap();
+        assert_eq!(info.raw.create_time, 0x01d7_0000_0000_0000);
+        assert_eq!(info.raw.kernel_time, 1000);
+        assert_eq!(info.raw.user_time, 2000);
+        assert_eq!(info.raw.start_address, 0x7ff8_1234_5678);
+        assert_eq!(info.raw.affinity, 0xf);
+        assert_eq!(info.start_address(), Some(0x7ff8_1234_5678));
+        assert!(info.dump_flags().is_empty());
+
+        let info = thread_infos.get_thread_info(0x5678).unwrap();
+        assert_eq!(info.raw.start_address, 0);
+        assert_eq!(info.start_address(), None);
+        // Unknown flags are dropped
+        assert_eq!(
+            info.dump_flags(),
+            md::ThreadInfoDumpFlags::MINIDUMP_THREAD_INFO_EXITED_THREAD
+        );
+        assert!(thread_infos.get_thread_info(0x9999).is_none());
+
+        let ids: Vec<_> = thread_infos.iter().map(|info| info.raw.thread_id).collect();
+        assert_eq!(ids, [0x1234, 0x5678]);
+    }
+
+    #[test]
+    fn test_module_list() {
+        let name = DumpString::new("single module", Endian::Little);
+        let cv_record = Section::with_endian(Endian::Little)
+            .D32(md::CvSignature::Pdb70 as u32) // signature
+            // signature, a GUID
+            .D32(0xabcd1234)
+            .D16(0xf00d)
+            .D16(0xbeef)
+            .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+            .D32(1) // age
+            .append_bytes(b"c:\\foo\\file.pdb\0"); // pdb_file_name
+        let module = SynthModule::new(
+            Endian::Little,
+            0xa90206ca83eb2852,
+            0xada542bd,
+            &name,
+            0xb1054d2a,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        )
+        .cv_record(&cv_record);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_module(module)
+            .add(name)
+            .add(cv_record);
+        let dump = read_synth_dump(dump).unwrap();
+        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let modules = module_list.iter().collect::<Vec<_>>();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].base_address(), 0xa90206ca83eb2852);
+        assert_eq!(modules[0].size(), 0xada542bd);
+        assert_eq!(modules[0].code_file(), "single module");
+        // time_date_stamp and size_of_image concatenated
         assert_eq!(
-            &*thread_names.get_name(good_thread_id).unwrap(),
-            "MyCoolThread"
+            modules[0].code_identifier().unwrap(),
+            CodeId::new("B1054D2Aada542bd".to_string())
+        );
+        assert_eq!(modules[0].debug_file().unwrap(), "c:\\foo\\file.pdb");
+        assert_eq!(
+            modules[0].debug_identifier().unwrap(),
+            DebugId::from_breakpad("ABCD1234F00DBEEF01020304050607081").unwrap()
         );
-        assert_eq!(thread_names.get_name(corrupt_thread_id), None);
     }
 
     #[test]
-    fn test_module_list() {
-        let name = DumpString::new("single module", Endian::Little);
-        let cv_record = Section::with_endian(Endian::Little)
+    fn test_bigendian_streams() {
+        let endian = Endian::Big;
+        let name = DumpString::new("single module", endian);
+        let cv_record = Section::with_endian(endian)
             .D32(md::CvSignature::Pdb70 as u32) // signature
             // signature, a GUID
             .D32(0xabcd1234)
@@ -5829,7 +10263,7 @@ mod test {
             .D32(1) // age
             .append_bytes(b"c:\\foo\\file.pdb\0"); // pdb_file_name
         let module = SynthModule::new(
-            Endian::Little,
+            endian,
             0xa90206ca83eb2852,
             0xada542bd,
             &name,
@@ -5838,27 +10272,46 @@ mod test {
             Some(&STOCK_VERSION_INFO),
         )
         .cv_record(&cv_record);
-        let dump = SynthMinidump::with_endian(Endian::Little)
+        let thread_name = DumpString::new("MyCoolThread", endian);
+        let memory = Memory::with_section(
+            Section::with_endian(endian)
+                .D32(0x1234_5678)
+                .append_bytes(&[0, b'h', 0, b'i', 0, 0]),
+            0x1000,
+        );
+        let dump = SynthMinidump::with_endian(endian)
             .add_module(module)
             .add(name)
-            .add(cv_record);
+            .add(cv_record)
+            .add_thread_name(ThreadName::new(endian, 17, Some(&thread_name)))
+            .add(thread_name)
+            .add_memory(memory);
         let dump = read_synth_dump(dump).unwrap();
+        assert_eq!(dump.endian, BE);
+
         let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
         let modules = module_list.iter().collect::<Vec<_>>();
         assert_eq!(modules.len(), 1);
         assert_eq!(modules[0].base_address(), 0xa90206ca83eb2852);
         assert_eq!(modules[0].size(), 0xada542bd);
         assert_eq!(modules[0].code_file(), "single module");
-        // time_date_stamp and size_of_image concatenated
-        assert_eq!(
-            modules[0].code_identifier().unwrap(),
-            CodeId::new("B1054D2Aada542bd".to_string())
-        );
         assert_eq!(modules[0].debug_file().unwrap(), "c:\\foo\\file.pdb");
         assert_eq!(
             modules[0].debug_identifier().unwrap(),
             DebugId::from_breakpad("ABCD1234F00DBEEF01020304050607081").unwrap()
         );
+
+        let thread_names = dump.get_stream::<MinidumpThreadNames>().unwrap();
+        assert_eq!(&*thread_names.get_name(17).unwrap(), "MyCoolThread");
+
+        let memory_list = dump.get_memory().unwrap();
+        let memory = memory_list.memory_at_address(0x1000).unwrap();
+        assert_eq!(memory.get_u32_at(0x1000), Some(0x1234_5678));
+        assert_eq!(memory.get_u16_at(0x1000), Some(0x1234));
+        assert_eq!(
+            memory.get_utf16_string_at(0x1004, 100).as_deref(),
+            Some("hi")
+        );
     }
 
     #[test]
@@ -5903,6 +10356,84 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_codeview_debug_identifier() {
+        let le = scroll::LE;
+        let pdb70 = |signature| {
+            CodeView::Pdb70(md::CV_INFO_PDB70 {
+                cv_signature: md::CvSignature::Pdb70 as u32,
+                signature,
+                age: 2,
+                pdb_file_name: b"file.pdb\0".to_vec(),
+            })
+        };
+        assert_eq!(
+            pdb70(md::GUID {
+                data1: 0xabcd_ef01,
+                data2: 0x1234,
+                data3: 0x5678,
+                data4: [1, 2, 3, 4, 5, 6, 7, 8],
+            })
+            .debug_identifier(le),
+            Some("abcdef01-1234-5678-0102-030405060708-2".parse().unwrap())
+        );
+
+        let pdb20 = |signature| {
+            CodeView::Pdb20(md::CV_INFO_PDB20 {
+                cv_signature: md::CvSignature::Pdb20 as u32,
+                cv_offset: 0,
+                signature,
+                age: 3,
+                pdb_file_name: b"file.pdb\0".to_vec(),
+            })
+        };
+        assert_eq!(
+            pdb20(0x4f3c_9d12).debug_identifier(le),
+            Some(DebugId::from_pdb20(0x4f3c_9d12, 3))
+        );
+        // Unlike a nil GUID, a zero signature still makes a debug id
+        assert_eq!(
+            pdb20(0).debug_identifier(le),
+            Some(DebugId::from_pdb20(0, 3))
+        );
+
+        let elf = |build_id: &[u8]| {
+            CodeView::Elf(md::CV_INFO_ELF {
+                cv_signature: md::CvSignature::Elf as u32,
+                build_id: build_id.to_vec(),
+            })
+        };
+        // The first 16 bytes are the GUID (in the minidump's endianness)
+        let build_id: Vec<u8> = (1..=20).collect();
+        assert_eq!(
+            elf(&build_id).debug_identifier(le),
+            Some("04030201-0605-0807-090a-0b0c0d0e0f10-0".parse().unwrap())
+        );
+        assert_eq!(
+            elf(&build_id).debug_identifier(scroll::BE),
+            Some("01020304-0506-0708-090a-0b0c0d0e0f10-0".parse().unwrap())
+        );
+        // Short build ids are padded
+        assert_eq!(
+            elf(&[0xaa, 0xbb]).debug_identifier(le),
+            Some("0000bbaa-0000-0000-0000-000000000000-0".parse().unwrap())
+        );
+
+        // No actual identifiers
+        let nil = md::GUID {
+            data1: 0,
+            data2: 0,
+            data3: 0,
+            data4: [0; 8],
+        };
+        assert_eq!(pdb70(nil).debug_identifier(le), None);
+        assert_eq!(elf(&[0; 20]).debug_identifier(le), None);
+        assert_eq!(elf(&[]).debug_identifier(le), None);
+        let nb09 = md::CvSignature::Cv41 as u32;
+        let unknown = CodeView::Unknown(nb09.to_le_bytes().to_vec());
+        assert_eq!(unknown.debug_identifier(le), None);
+    }
+
     #[test]
     fn test_unloaded_module_list() {
         let name = DumpString::new("single module", Endian::Little);
@@ -6026,6 +10557,118 @@ mod test {
         assert!(!infos[1].is_executable());
     }
 
+    #[test]
+    fn test_memory_info_states() {
+        // A thread's stack: the reserved part, the guard page, the committed part
+        let stack_info = |base_address, size, state: md::MemoryState, protection| {
+            SynthMemoryInfo::new(
+                Endian::Little,
+                base_address,
+                0x10000,
+                md::MemoryProtection::PAGE_READWRITE.bits(),
+                size,
+                state.bits(),
+                protection,
+                md::MemoryType::MEM_PRIVATE.bits(),
+            )
+        };
+        let guard = md::MemoryProtection::PAGE_READWRITE | md::MemoryProtection::PAGE_GUARD;
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory_info(stack_info(0x10000, 0xe000, md::MemoryState::MEM_RESERVE, 0))
+            .add_memory_info(stack_info(
+                0x1e000,
+                0x1000,
+                md::MemoryState::MEM_COMMIT,
+                guard.bits(),
+            ))
+            .add_memory_info(stack_info(
+                0x1f000,
+                0x1000,
+                md::MemoryState::MEM_COMMIT,
+                md::MemoryProtection::PAGE_READWRITE.bits(),
+            ))
+            .add_memory_info(SynthMemoryInfo::new(
+                Endian::Little,
+                0x20000,
+                0,
+                0,
+                0x10000,
+                md::MemoryState::MEM_FREE.bits(),
+                md::MemoryProtection::PAGE_NOACCESS.bits(),
+                0,
+            ));
+        let dump = read_synth_dump(dump).unwrap();
+        let info_list = dump.get_stream::<MinidumpMemoryInfoList>().unwrap();
+
+        let reserved = info_list.memory_info_at_address(0x18000).unwrap();
+        assert!(reserved.is_reserved());
+        assert!(!reserved.is_committed() && !reserved.is_guard_page() && !reserved.is_free());
+        assert_eq!(reserved.allocation_base(), 0x10000);
+
+        let guard_page = info_list.memory_info_at_address(0x1e800).unwrap();
+        assert!(guard_page.is_committed() && guard_page.is_guard_page());
+        assert_eq!(guard_page.allocation_base(), 0x10000);
+
+        let stack = info_list.memory_info_at_address(0x1fff0).unwrap();
+        assert!(stack.is_committed() && !stack.is_guard_page());
+        assert!(stack.is_readable() && stack.is_writable());
+
+        let free = info_list.memory_info_at_address(0x28000).unwrap();
+        assert!(free.is_free() && !free.is_committed() && !free.is_reserved());
+        assert!(!free.is_readable());
+
+        assert!(info_list.memory_info_at_address(0x30000).is_none());
+    }
+
+    #[test]
+    fn test_linux_cmdline_and_environ() {
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .set_linux_cmdline(b"/usr/bin/app\0--flag\0\0last\0")
+            .set_linux_environ(b"HOME=/home/me\0LD_PRELOAD=libhook.so\0EMPTY=\0");
+        let dump = read_synth_dump(dump).unwrap();
+
+        let cmdline = dump.get_stream::<MinidumpLinuxCmdLine>().unwrap();
+        let args: Vec<_> = cmdline.args().map(|arg| arg.to_string_lossy()).collect();
+        assert_eq!(args, ["/usr/bin/app", "--flag", "", "last"]);
+
+        let environ = dump.get_stream::<MinidumpLinuxEnviron>().unwrap();
+        let vars: Vec<_> = environ
+            .iter()
+            .map(|(key, val)| (key.to_string_lossy(), val.to_string_lossy()))
+            .collect();
+        assert_eq!(
+            vars,
+            [
+                ("HOME".into(), "/home/me".into()),
+                ("LD_PRELOAD".into(), "libhook.so".into()),
+                ("EMPTY".into(), "".into()),
+            ]
+        );
+        assert_eq!(
+            environ.get("LD_PRELOAD").map(|val| val.as_bytes()),
+            Some(&b"libhook.so"[..])
+        );
+        assert_eq!(
+            environ.get("EMPTY").map(|val| val.as_bytes()),
+            Some(&b""[..])
+        );
+        assert!(environ.get("PATH").is_none());
+
+        // Non-UTF-8 values can still be read as bytes.
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .set_linux_environ(b"NAME=caf\xe9\0NAME=second\0");
+        let dump = read_synth_dump(dump).unwrap();
+        let environ = dump.get_stream::<MinidumpLinuxEnviron>().unwrap();
+        let name = environ.get("NAME").unwrap();
+        assert_eq!(name.as_bytes(), b"caf\xe9");
+        assert_eq!(name.to_string_lossy(), "caf\u{fffd}");
+
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_cmdline(b"");
+        let dump = read_synth_dump(dump).unwrap();
+        let cmdline = dump.get_stream::<MinidumpLinuxCmdLine>().unwrap();
+        assert_eq!(cmdline.args().count(), 0);
+    }
+
     #[test]
     fn test_linux_maps() {
         use procfs_core::process::{MMPermissions, MMapPath};
@@ -6084,6 +10727,34 @@ mod test {
         assert!(matches!(unified_infos.next(), Some(UnifiedMemoryInfo::Map(m)) if m == maps[1]));
     }
 
+    #[test]
+    fn test_linux_maps_lookup() {
+        // The usual layout of a library: adjacent mappings with different permissions
+        let input = b"7f0000000000-7f0000001000 r--p 00000000 fd:01 42 /usr/lib/libc.so.6\n\
+                      7f0000001000-7f0000003000 r-xp 00001000 fd:01 42 /usr/lib/libc.so.6\n\
+                      7f0000003000-7f0000004000 rw-p 00003000 fd:01 42 /usr/lib/libc.so.6\n\
+                      7f0000008000-7f0000009000 rw-p 00000000 00:00 0 \n";
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_maps(input);
+        let dump = read_synth_dump(dump).unwrap();
+        let maps = dump.get_stream::<MinidumpLinuxMaps>().unwrap();
+        assert_eq!(maps.iter().count(), 4);
+        assert_eq!(maps.by_addr().count(), 4);
+
+        let perms = |address| {
+            let map = maps.memory_info_at_address(address)?;
+            Some((map.is_readable(), map.is_writable(), map.is_executable()))
+        };
+        assert_eq!(perms(0x7f00_0000_0000), Some((true, false, false)));
+        assert_eq!(perms(0x7f00_0000_0fff), Some((true, false, false)));
+        assert_eq!(perms(0x7f00_0000_1000), Some((true, false, true)));
+        assert_eq!(perms(0x7f00_0000_2fff), Some((true, false, true)));
+        assert_eq!(perms(0x7f00_0000_3000), Some((true, true, false)));
+        // The end addresses aren't part of the mappings.
+        assert_eq!(perms(0x7f00_0000_4000), None);
+        assert_eq!(perms(0x7f00_0000_8000), Some((true, true, false)));
+        assert_eq!(perms(0x7f00_0000_9000), None);
+    }
+
     #[test]
     fn test_linux_map_parse() {
         use procfs_core::process::{MMPermissions, MMapPath::*};
@@ -6098,7 +10769,7 @@ mod test {
 
             assert_eq!(map.map.address.0, 0x10a00);
             assert_eq!(map.map.address.1, 0x10b00);
-            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10b00)));
+            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10aff)));
             assert_eq!(map.map.pathname, Path("/usr/lib64/libtdb1.so".into()));
 
             assert!(
@@ -6117,7 +10788,7 @@ mod test {
             assert_eq!(map.map.address.1, 0xffffffffff601000);
             assert_eq!(
                 map.memory_range(),
-                Some(Range::new(0xffffffffff600000, 0xffffffffff601000))
+                Some(Range::new(0xffffffffff600000, 0xffffffffff600fff))
             );
             assert_eq!(
                 map.map.pathname,
@@ -6137,7 +10808,7 @@ mod test {
 
             assert_eq!(map.map.address.0, 0x10a00);
             assert_eq!(map.map.address.1, 0x10b00);
-            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10b00)));
+            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10aff)));
             assert_eq!(map.map.pathname, Stack);
             assert!(map.map.perms == MMPermissions::NONE);
         }
@@ -6148,7 +10819,7 @@ mod test {
 
             assert_eq!(map.map.address.0, 0x10a00);
             assert_eq!(map.map.address.1, 0x10b00);
-            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10b00)));
+            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10aff)));
             assert_eq!(map.map.pathname, TStack(1234567));
             assert!(map.map.perms == MMPermissions::NONE);
         }
@@ -6159,7 +10830,7 @@ mod test {
 
             assert_eq!(map.map.address.0, 0x10a00);
             assert_eq!(map.map.address.1, 0x10b00);
-            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10b00)));
+            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10aff)));
             assert_eq!(map.map.pathname, Heap);
             assert!(map.map.perms == MMPermissions::NONE);
         }
@@ -6170,7 +10841,7 @@ mod test {
 
             assert_eq!(map.map.address.0, 0x10a00);
             assert_eq!(map.map.address.1, 0x10b00);
-            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10b00)));
+            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10aff)));
             assert_eq!(map.map.pathname, Vdso);
             assert!(
                 map.map.perms
@@ -6184,7 +10855,7 @@ mod test {
 
             assert_eq!(map.map.address.0, 0x10a00);
             assert_eq!(map.map.address.1, 0x10b00);
-            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10b00)));
+            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10aff)));
             assert_eq!(map.map.pathname, Other("asdfasd".into()));
             assert!(
                 map.map.perms
@@ -6198,7 +10869,7 @@ mod test {
 
             assert_eq!(map.map.address.0, 0x10a00);
             assert_eq!(map.map.address.1, 0x10b00);
-            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10b00)));
+            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10aff)));
             assert_eq!(map.map.pathname, Anonymous);
             assert!(map.map.perms == MMPermissions::READ);
         }
@@ -6210,7 +10881,7 @@ mod test {
 
             assert_eq!(map.map.address.0, 0x10a00);
             assert_eq!(map.map.address.1, 0x10b00);
-            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10b00)));
+            assert_eq!(map.memory_range(), Some(Range::new(0x10a00, 0x10aff)));
             assert_eq!(map.map.pathname, Anonymous);
             assert!(map.map.perms == MMPermissions::NONE);
         }
@@ -6226,12 +10897,12 @@ mod test {
         }
 
         {
-            // Equal ranges are valid
+            // Equal ranges are valid, but empty
             let map = parse(b"fffff-fffff --- 10bac9000 fd:05 1196511  ");
 
             assert_eq!(map.map.address.0, 0xfffff);
             assert_eq!(map.map.address.1, 0xfffff);
-            assert_eq!(map.memory_range(), Some(Range::new(0xfffff, 0xfffff)));
+            assert_eq!(map.memory_range(), None);
         }
 
         {
@@ -6383,48 +11054,484 @@ mod test {
     }
 
     #[test]
-    fn test_memory_list() {
-        const CONTENTS: &[u8] = b"memory_contents";
-        let memory = Memory::with_section(
-            Section::with_endian(Endian::Little).append_bytes(CONTENTS),
-            0x309d68010bd21b2c,
-        );
+    fn test_module_list_high_addresses() {
+        let names = [
+            "above 4GB",
+            "straddles 4GB",
+            "end of memory",
+            "wraps around",
+        ]
+        .map(|name| DumpString::new(name, Endian::Little));
+        let modules = [
+            (0x7ff6_1234_0000, 0x8000_0000),
+            (0xffff_8000, 0x1_0000),
+            (0xffff_ffff_fff0_0000, 0x10_0000),
+            (0xffff_ffff_ff00_0000, 0x200_0000),
+        ];
+        let mut dump = SynthMinidump::with_endian(Endian::Little);
+        // The module that wraps around the address space would overlap the
+        // one at the end of it, so it's in another dump.
+        let mut wrapping_dump = SynthMinidump::with_endian(Endian::Little);
+        for (i, ((base, size), name)) in modules.iter().zip(&names).enumerate() {
+            let module = SynthModule::new(Endian::Little, *base, *size, name, 0, 0, None);
+            if i < 3 {
+                dump = dump.add_module(module);
+            } else {
+                wrapping_dump = wrapping_dump.add_module(module);
+            }
+        }
+        let [above, straddles, end, wraps] = names;
+        let dump = read_synth_dump(dump.add(above).add(straddles).add(end)).unwrap();
+        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+
+        let module_at = |address| {
+            module_list
+                .module_at_address(address)
+                .map(|module| module.code_file().into_owned())
+        };
+        assert_eq!(module_at(0x7ff6_1234_0000).unwrap(), "above 4GB");
+        assert_eq!(module_at(0x7ff6_9233_ffff).unwrap(), "above 4GB");
+        assert_eq!(module_at(0x7ff6_9234_0000), None);
+        assert_eq!(module_at(0xffff_ffff).unwrap(), "straddles 4GB");
+        assert_eq!(module_at(0x1_0000_0000).unwrap(), "straddles 4GB");
+        assert_eq!(module_at(0x1_0000_7fff).unwrap(), "straddles 4GB");
+        assert_eq!(module_at(0x1_0000_8000), None);
+        assert_eq!(module_at(u64::MAX).unwrap(), "end of memory");
+
+        let end_module = module_list.module_at_address(u64::MAX).unwrap();
+        assert_eq!(
+            end_module.memory_range(),
+            Some(Range::new(0xffff_ffff_fff0_0000, u64::MAX))
+        );
+        assert_eq!(module_list.by_addr().count(), 3);
+
+        // The end of a module that wraps around is clamped to the end of memory.
+        let dump = read_synth_dump(wrapping_dump.add(wraps)).unwrap();
+        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let wrapping_module = module_list.module_at_address(u64::MAX).unwrap();
+        assert_eq!(wrapping_module.code_file(), "wraps around");
+        assert_eq!(
+            wrapping_module.memory_range(),
+            Some(Range::new(0xffff_ffff_ff00_0000, u64::MAX))
+        );
+    }
+
+    #[test]
+    fn test_module_list_random_ranges() {
+        // xorshift64*, so the test is the same every time.
+        let mut state = 0x9e37_79b9_7f4a_7c15_u64;
+        let mut random = move || {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        };
+
+        for _ in 0..100 {
+            // Some modules anywhere, and some around 4GB.
+            let mut bases: Vec<u64> = (0..8).map(|_| random()).collect();
+            bases.extend((0..8).map(|_| 0xf000_0000 + random() % 0x2000_0000));
+            bases.sort_unstable();
+            bases.dedup();
+            // Each module ends before the next one starts.
+            let mut modules = Vec::new();
+            for (i, &base) in bases.iter().enumerate() {
+                let room = bases
+                    .get(i + 1)
+                    .map_or((u64::MAX - base).saturating_add(1), |next| next - base);
+                let size = 1 + random() % room.min(u64::from(u32::MAX));
+                modules.push(MinidumpModule::new(base, size as u32, &i.to_string()));
+            }
+            // In no particular order.
+            modules.reverse();
+            let count = modules.len();
+            let module_list = MinidumpModuleList::from_modules(modules);
+
+            assert_eq!(module_list.by_addr().count(), count);
+            let mut last_end = None;
+            for module in module_list.by_addr() {
+                let (base, size) = (module.base_address(), module.size());
+                let last = base + (size - 1);
+                assert!(last_end < Some(base));
+                last_end = Some(last);
+                for address in [base, base + (size - 1) / 2, last] {
+                    let found = module_list.module_at_address(address).unwrap();
+                    assert_eq!(found.code_file(), module.code_file());
+                }
+                if let Some(before) = base.checked_sub(1) {
+                    let found = module_list.module_at_address(before);
+                    assert_ne!(found.map(Module::code_file), Some(module.code_file()));
+                }
+                if let Some(after) = last.checked_add(1) {
+                    let found = module_list.module_at_address(after);
+                    assert_ne!(found.map(Module::code_file), Some(module.code_file()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_many_modules() {
+        const COUNT: u64 = 10_000;
+        let name = DumpString::new("module", Endian::Little);
+        let mut dump = SynthMinidump::with_endian(Endian::Little);
+        for i in 0..COUNT {
+            let module = SynthModule::new(
+                Endian::Little,
+                0x1000_0000 + i * 0x10000,
+                0x8000,
+                &name,
+                0,
+                0,
+                None,
+            );
+            // Each address is in up to 3 of the unloaded modules.
+            let unloaded =
+                SynthUnloadedModule::new(Endian::Little, i * 0x1000, 0x3000, &name, 0, 0);
+            dump = dump.add_module(module).add_unloaded_module(unloaded);
+        }
+        let dump = read_synth_dump(dump.add(name)).unwrap();
+        let modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let unloaded_modules = dump.get_stream::<MinidumpUnloadedModuleList>().unwrap();
+        assert_eq!(modules.iter().count() as u64, COUNT);
+        assert_eq!(unloaded_modules.iter().count() as u64, COUNT);
+
+        for i in 0..COUNT {
+            let base = 0x1000_0000 + i * 0x10000;
+            let found = modules.module_at_address(base + 0x7fff).unwrap();
+            assert_eq!(found.base_address(), base);
+            assert!(modules.module_at_address(base + 0x8000).is_none());
+
+            let expected: Vec<u64> = (i.saturating_sub(2)..=i).map(|j| j * 0x1000).collect();
+            for offset in [0, 0x800, 0xfff] {
+                let found: Vec<u64> = unloaded_modules
+                    .modules_at_address(i * 0x1000 + offset)
+                    .map(|module| module.base_address())
+                    .collect();
+                assert_eq!(found, expected);
+            }
+        }
+        assert!(unloaded_modules
+            .modules_at_address(COUNT * 0x1000 + 0x2000)
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn test_read_limits() {
+        let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+            0x1000,
+        );
+        let name = DumpString::new("module", Endian::Little);
+        let mut dump = SynthMinidump::with_endian(Endian::Little);
+        for i in 0..3 {
+            let module =
+                SynthModule::new(Endian::Little, 0x10000 * (i + 1), 0x1000, &name, 0, 0, None);
+            dump = dump
+                .add_thread(Thread::new(Endian::Little, i as u32, &stack, &context))
+                .add_module(module);
+        }
+        let dump = dump.add(context).add(name).add_memory(stack);
+        let bytes = dump.finish().unwrap();
+
+        let dump = Minidump::read(&bytes[..]).unwrap();
+        assert_eq!(dump.limits(), &ReadLimits::default());
+        assert_eq!(
+            dump.get_stream::<MinidumpThreadList>()
+                .unwrap()
+                .threads
+                .len(),
+            3
+        );
+        assert_eq!(
+            dump.get_stream::<MinidumpModuleList>()
+                .unwrap()
+                .iter()
+                .count(),
+            3
+        );
+
+        let limits = ReadLimits {
+            max_modules: 3,
+            max_threads: 2,
+            max_memory_regions: 0,
+            ..ReadLimits::default()
+        };
+        let dump = Minidump::read_with_limits(&bytes[..], limits).unwrap();
+        assert_eq!(
+            dump.get_stream::<MinidumpModuleList>()
+                .unwrap()
+                .iter()
+                .count(),
+            3
+        );
+        assert_eq!(
+            dump.get_stream::<MinidumpThreadList>().unwrap_err(),
+            Error::LimitExceeded {
+                limit: ReadLimit::MaxThreads,
+                count: 3,
+                max: 2,
+            }
+        );
+        assert_eq!(
+            dump.get_stream::<MinidumpMemoryList>().unwrap_err(),
+            Error::LimitExceeded {
+                limit: ReadLimit::MaxMemoryRegions,
+                count: 1,
+                max: 0,
+            }
+        );
+        assert!(dump.get_memory().is_none());
+
+        let limits = ReadLimits {
+            max_stream_size: 16,
+            ..ReadLimits::default()
+        };
+        let dump = Minidump::read_with_limits(&bytes[..], limits).unwrap();
+        let err = dump.get_stream::<MinidumpModuleList>().unwrap_err();
+        assert_eq!(
+            err,
+            Error::LimitExceeded {
+                limit: ReadLimit::MaxStreamSize,
+                count: 4 + 3 * 108,
+                max: 16,
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Read limit max_stream_size exceeded: 328 is more than 16"
+        );
+    }
+
+    #[test]
+    fn test_memory_range_end_of_memory() {
+        let memory = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(b"\x01\x02\x03\x04"),
+            u64::MAX - 3,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little).add_memory(memory);
+        let dump = read_synth_dump(dump).unwrap();
+        let memory_list = dump.get_stream::<MinidumpMemoryList<'_>>().unwrap();
+        let region = memory_list.memory_at_address(u64::MAX).unwrap();
+        assert_eq!(
+            region.memory_range(),
+            Some(Range::new(u64::MAX - 3, u64::MAX))
+        );
+        assert_eq!(
+            region.get_memory_at_address::<u32>(u64::MAX - 3),
+            Some(0x0403_0201)
+        );
+        assert_eq!(region.get_memory_at_address::<u32>(u64::MAX - 2), None);
+    }
+
+    #[test]
+    fn test_memory_list() {
+        const CONTENTS: &[u8] = b"memory_contents";
+        let memory = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(CONTENTS),
+            0x309d68010bd21b2c,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little).add_memory(memory);
+        let dump = read_synth_dump(dump).unwrap();
+        let memory_list = dump.get_stream::<MinidumpMemoryList<'_>>().unwrap();
+        let regions = memory_list.iter().collect::<Vec<_>>();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].base_address, 0x309d68010bd21b2c);
+        assert_eq!(regions[0].size, CONTENTS.len() as u64);
+        assert_eq!(&regions[0].bytes, &CONTENTS);
+    }
+
+    #[test]
+    fn test_memory64_list() {
+        const CONTENTS0: &[u8] = b"memory_contents";
+        const CONTENTS1: &[u8] = b"another_block";
+        let memory0 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(CONTENTS0),
+            0x309d68010bd21b2c,
+        );
+        let memory1 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(CONTENTS1),
+            0x1234,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory64(memory0)
+            .add_memory64(memory1);
+        let dump = read_synth_dump(dump).unwrap();
+        let memory_list = dump.get_stream::<MinidumpMemory64List<'_>>().unwrap();
+        let regions = memory_list.iter().collect::<Vec<_>>();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].base_address, 0x309d68010bd21b2c);
+        assert_eq!(regions[0].size, CONTENTS0.len() as u64);
+        assert_eq!(&regions[0].bytes, &CONTENTS0);
+
+        assert_eq!(regions[1].base_address, 0x1234);
+        assert_eq!(regions[1].size, CONTENTS1.len() as u64);
+        assert_eq!(&regions[1].bytes, &CONTENTS1);
+    }
+
+    #[test]
+    fn test_unified_memory_list() {
+        let memory = |base, contents: &[u8]| {
+            Memory::with_section(
+                Section::with_endian(Endian::Little).append_bytes(contents),
+                base,
+            )
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory(memory(0x3000, b"stack"))
+            .add_memory(memory(0x1000, b"small"))
+            .add_memory64(memory(0x2000, b"full memory"))
+            // Overlaps the region of the 32-bit list
+            .add_memory64(memory(0x1000, b"large"));
+        let dump = read_synth_dump(dump).unwrap();
+        let memory_list = dump.get_memory().unwrap();
+        assert!(matches!(memory_list, UnifiedMemoryList::Both(..)));
+
+        let bytes_at = |address| {
+            memory_list
+                .memory_at_address(address)
+                .map(|memory| memory.bytes())
+        };
+        assert_eq!(bytes_at(0x3002), Some(&b"stack"[..]));
+        assert_eq!(bytes_at(0x2005), Some(&b"full memory"[..]));
+        // The 64-bit list wins
+        assert_eq!(bytes_at(0x1000), Some(&b"large"[..]));
+        assert_eq!(bytes_at(0x4000), None);
+
+        let bases: Vec<_> = memory_list
+            .iter()
+            .map(|memory| memory.base_address())
+            .collect();
+        assert_eq!(bases, [0x3000, 0x1000, 0x2000, 0x1000]);
+        let contents: Vec<_> = memory_list.by_addr().map(|memory| memory.bytes()).collect();
+        assert_eq!(
+            contents,
+            [&b"large"[..], b"small", b"full memory", b"stack"]
+        );
+
+        // A single list is used on its own.
+        let dump =
+            SynthMinidump::with_endian(Endian::Little).add_memory64(memory(0x1000, b"large"));
+        let dump = read_synth_dump(dump).unwrap();
+        let memory_list = dump.get_memory().unwrap();
+        assert!(matches!(memory_list, UnifiedMemoryList::Memory64(_)));
+        assert_eq!(memory_list.by_addr().count(), 1);
+    }
+
+    #[test]
+    fn test_seekable_minidump() {
+        const STREAM_TYPE: u32 = 0x4350_000a;
+        let memory = |base, contents: &[u8]| {
+            Memory::with_section(
+                Section::with_endian(Endian::Little).append_bytes(contents),
+                base,
+            )
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(SimpleStream {
+                stream_type: STREAM_TYPE,
+                section: Section::with_endian(Endian::Little).D32(0x1234_5678),
+            })
+            .add_memory(memory(0x3000, b"stack"))
+            .add_memory64(memory(0x2000, b"full memory"))
+            .add_memory64(memory(0x1000, b"large"));
+        let bytes = dump.finish().unwrap();
+
+        let mut dump = SeekableMinidump::read(io::Cursor::new(&bytes)).unwrap();
+        let full_dump = Minidump::read(&bytes[..]).unwrap();
+        assert_eq!(dump.endian, full_dump.endian);
+        assert_eq!(dump.header.stream_count, full_dump.header.stream_count);
+        let types: Vec<_> = dump.directory().iter().map(|dir| dir.stream_type).collect();
+        let full_types: Vec<_> = full_dump
+            .stream_entries()
+            .map(|e| e.stream_type())
+            .collect();
+        assert_eq!(types, full_types);
+
+        assert_eq!(
+            dump.read_raw_stream(STREAM_TYPE).unwrap(),
+            full_dump.get_raw_stream(STREAM_TYPE).unwrap()
+        );
+        assert_eq!(
+            dump.read_raw_stream(0x4350_000b),
+            Err(Error::StreamNotFound)
+        );
+
+        assert_eq!(dump.read_memory(0x3001, 3).unwrap(), b"tac");
+        assert_eq!(dump.read_memory(0x2005, 6).unwrap(), b"memory");
+        assert_eq!(dump.read_memory(0x1000, 5).unwrap(), b"large");
+        // Past the end of the region
+        assert_eq!(dump.read_memory(0x1001, 5), Err(Error::MemoryReadFailure));
+        assert_eq!(dump.read_memory(0x4000, 1), Err(Error::MemoryReadFailure));
+
+        let dump = dump.into_minidump().unwrap();
+        assert_eq!(dump.get_memory().unwrap().by_addr().count(), 3);
+
+        assert_eq!(
+            SeekableMinidump::read(io::Cursor::new(&bytes[..16])).unwrap_err(),
+            Error::MissingHeader
+        );
+        assert_eq!(
+            SeekableMinidump::read(io::Cursor::new(&bytes[..40])).unwrap_err(),
+            Error::MissingDirectory
+        );
+    }
+
+    #[test]
+    fn test_memory_typed_reads() {
+        let section = Section::with_endian(Endian::Little)
+            .D8(0x12)
+            .D8(0x34)
+            .D16(0x5678)
+            .D32(0x9abc_def0)
+            .D64(0x1122_3344_5566_7788)
+            .append_bytes(b"hello\0world")
+            .append_bytes(&[b'h', 0, b'i', 0, 0, 0, b'x', 0]);
+        let memory = Memory::with_section(section, 0x1000);
         let dump = SynthMinidump::with_endian(Endian::Little).add_memory(memory);
         let dump = read_synth_dump(dump).unwrap();
-        let memory_list = dump.get_stream::<MinidumpMemoryList<'_>>().unwrap();
-        let regions = memory_list.iter().collect::<Vec<_>>();
-        assert_eq!(regions.len(), 1);
-        assert_eq!(regions[0].base_address, 0x309d68010bd21b2c);
-        assert_eq!(regions[0].size, CONTENTS.len() as u64);
-        assert_eq!(&regions[0].bytes, &CONTENTS);
-    }
+        let memory_list = dump.get_memory().unwrap();
+        let memory = memory_list.memory_at_address(0x1000).unwrap();
 
-    #[test]
-    fn test_memory64_list() {
-        const CONTENTS0: &[u8] = b"memory_contents";
-        const CONTENTS1: &[u8] = b"another_block";
-        let memory0 = Memory::with_section(
-            Section::with_endian(Endian::Little).append_bytes(CONTENTS0),
-            0x309d68010bd21b2c,
+        assert_eq!(memory.get_u8_at(0x1001), Some(0x34));
+        assert_eq!(memory.get_u16_at(0x1002), Some(0x5678));
+        assert_eq!(memory.get_u32_at(0x1004), Some(0x9abc_def0));
+        assert_eq!(memory.get_u64_at(0x1008), Some(0x1122_3344_5566_7788));
+        assert_eq!(
+            memory.get_pointer_at(0x1008, PointerWidth::Bits32),
+            Some(0x5566_7788)
         );
-        let memory1 = Memory::with_section(
-            Section::with_endian(Endian::Little).append_bytes(CONTENTS1),
-            0x1234,
+        assert_eq!(
+            memory.get_pointer_at(0x1008, PointerWidth::Bits64),
+            Some(0x1122_3344_5566_7788)
         );
-        let dump = SynthMinidump::with_endian(Endian::Little)
-            .add_memory64(memory0)
-            .add_memory64(memory1);
-        let dump = read_synth_dump(dump).unwrap();
-        let memory_list = dump.get_stream::<MinidumpMemory64List<'_>>().unwrap();
-        let regions = memory_list.iter().collect::<Vec<_>>();
-        assert_eq!(regions.len(), 2);
-        assert_eq!(regions[0].base_address, 0x309d68010bd21b2c);
-        assert_eq!(regions[0].size, CONTENTS0.len() as u64);
-        assert_eq!(&regions[0].bytes, &CONTENTS0);
+        assert_eq!(memory.get_pointer_at(0x1008, PointerWidth::Unknown), None);
+        // Out of the region, or running off its end
+        assert_eq!(memory.get_u8_at(0xfff), None);
+        assert_eq!(memory.get_u64_at(0x101c), None);
 
-        assert_eq!(regions[1].base_address, 0x1234);
-        assert_eq!(regions[1].size, CONTENTS1.len() as u64);
-        assert_eq!(&regions[1].bytes, &CONTENTS1);
+        assert_eq!(memory.get_bytes_at(0x1010, 3), Some(&b"hel"[..]));
+        assert_eq!(memory.get_bytes_at(0x101f, 100), Some(&[0, 0, b'x', 0][..]));
+        assert_eq!(memory.get_bytes_at(0x1023, 100), Some(&[][..]));
+        assert_eq!(memory.get_bytes_at(0x2000, 100), None);
+
+        assert_eq!(
+            memory.get_c_string_at(0x1010, 100).as_deref(),
+            Some("hello")
+        );
+        assert_eq!(memory.get_c_string_at(0x1010, 3).as_deref(), Some("hel"));
+        // Without a terminator, the string goes on into the next bytes.
+        assert_eq!(
+            memory.get_c_string_at(0x1016, 100).as_deref(),
+            Some("worldh")
+        );
+        assert_eq!(
+            memory.get_utf16_string_at(0x101b, 100).as_deref(),
+            Some("hi")
+        );
+        assert_eq!(memory.get_utf16_string_at(0x101b, 2).as_deref(), Some("h"));
     }
 
     #[test]
@@ -6508,6 +11615,11 @@ mod test {
 
         // memory_at_address should discard overlapping regions.
         assert_eq!(memory_list.by_addr().count(), 2);
+        assert_eq!(memory_list.corruption_notes().len(), 3);
+        assert!(memory_list
+            .corruption_notes()
+            .iter()
+            .all(|note| matches!(note, CorruptionNote::MemoryOverlap { .. })));
         let m1 = memory_list.memory_at_address(0x1a00).unwrap();
         assert_eq!(m1.base_address, 0x1000);
         assert_eq!(m1.size, 0x1000);
@@ -6546,6 +11658,12 @@ mod test {
             *misc.raw.process_kernel_time().unwrap(),
             PROCESS_TIMES.process_kernel_time
         );
+        // The fields of the newer versions
+        assert_eq!(misc.time_zone(), None);
+        assert_eq!(misc.build_string(), None);
+        assert_eq!(misc.debug_build_string(), None);
+        assert_eq!(misc.process_cookie(), None);
+        assert!(misc.xstate_config().is_none());
     }
 
     #[test]
@@ -6756,15 +11874,29 @@ mod test {
         assert_eq!(time_zone.daylight_date, DAYLIGHT_DATE);
         assert_eq!(time_zone.standard_name, standard_name);
         assert_eq!(time_zone.daylight_name, daylight_name);
+        assert_eq!(
+            misc.time_zone(),
+            Some(MinidumpTimeZone {
+                id: TIME_ZONE_ID,
+                bias: BIAS,
+                standard_name: String::from("Pacific Standard Time"),
+                standard_bias: STANDARD_BIAS,
+                daylight_name: String::from("Pacific Daylight Time"),
+                daylight_bias: DAYLIGHT_BIAS,
+            })
+        );
 
         // MISC_INFO_4 fields
         assert_eq!(*misc.raw.build_string().unwrap(), build_string,);
+        assert_eq!(misc.build_string().as_deref(), Some("hello"));
         assert_eq!(*misc.raw.dbg_bld_str().unwrap(), dbg_bld_str,);
+        assert_eq!(misc.debug_build_string().as_deref(), Some("world"));
 
         // MISC_INFO_5 fields
         assert_eq!(*misc.raw.process_cookie().unwrap(), PROCESS_COOKIE,);
+        assert_eq!(misc.process_cookie(), Some(PROCESS_COOKIE));
 
-        let xstate = misc.raw.xstate_data().unwrap();
+        let xstate = misc.xstate_config().unwrap();
         assert_eq!(xstate.size_of_info, SIZE_OF_INFO);
         assert_eq!(xstate.context_size, CONTEXT_SIZE);
         assert_eq!(xstate.enabled_features, enabled_features);
@@ -7009,87 +12141,784 @@ mod test {
             .add(name2)
             .add(cv_record2);
 
-        let dump = read_synth_dump(dump).unwrap();
-        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
-        let modules = module_list.iter().collect::<Vec<_>>();
+        let dump = read_synth_dump(dump).unwrap();
+        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let modules = module_list.iter().collect::<Vec<_>>();
+
+        assert!(modules[0].debug_identifier().is_none());
+        assert!(modules[1].debug_identifier().is_none());
+    }
+
+    #[test]
+    fn test_thread_list_x86() {
+        let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000,
+        );
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let mut thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+        assert_eq!(thread_list.threads.len(), 1);
+        let mut thread = thread_list.threads.pop().unwrap();
+        assert_eq!(thread.raw.thread_id, 0x1234);
+        let context = thread
+            .context(&system_info, misc_info.as_ref())
+            .expect("Should have a thread context");
+        match &context.raw {
+            MinidumpRawContext::X86(raw) => {
+                assert_eq!(raw.eip, 0xabcd1234);
+                assert_eq!(raw.esp, 0x1010);
+            }
+            _ => panic!("Got unexpected raw context type!"),
+        }
+        let stack = thread.stack.take().expect("Should have stack memory");
+        assert_eq!(stack.base_address, 0x1000);
+        assert_eq!(stack.size, 0x1000);
+    }
+
+    #[test]
+    fn test_thread_list_amd64() {
+        let context =
+            minidump_synth::amd64_context(Endian::Little, 0x1234abcd1234abcd, 0x1000000010000000);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000000010000000,
+        );
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let mut thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+        assert_eq!(thread_list.threads.len(), 1);
+        let mut thread = thread_list.threads.pop().unwrap();
+        assert_eq!(thread.raw.thread_id, 0x1234);
+        let context = thread
+            .context(&system_info, misc_info.as_ref())
+            .expect("Should have a thread context");
+        match &context.raw {
+            MinidumpRawContext::Amd64(raw) => {
+                assert_eq!(raw.rip, 0x1234abcd1234abcd);
+                assert_eq!(raw.rsp, 0x1000000010000000);
+            }
+            _ => panic!("Got unexpected raw context type!"),
+        }
+        let stack = thread.stack.take().expect("Should have stack memory");
+        assert_eq!(stack.base_address, 0x1000000010000000);
+        assert_eq!(stack.size, 0x1000);
+    }
+
+    #[test]
+    fn test_thread_list_arm64_emulation() {
+        let read_contexts = |arch: ProcessorArchitecture, platform_id: PlatformId| {
+            let contexts = [
+                minidump_synth::arm64_context(Endian::Little, 0x1000_1000, 0x1_0000),
+                minidump_synth::amd64_context(Endian::Little, 0x2000_1000, 0x2_0000),
+                minidump_synth::x86_context(Endian::Little, 0x3000_1000, 0x3_0000),
+            ];
+            let stack = Memory::with_section(
+                Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+                0x1_0000,
+            );
+            let system_info = SystemInfo::new(Endian::Little)
+                .set_processor_architecture(arch as u16)
+                .set_platform_id(platform_id as u32);
+            let mut dump = SynthMinidump::with_endian(Endian::Little);
+            for (i, context) in contexts.iter().enumerate() {
+                dump = dump.add_thread(Thread::new(Endian::Little, i as u32, &stack, context));
+            }
+            for context in contexts {
+                dump = dump.add(context);
+            }
+            let dump =
+                read_synth_dump(dump.add_memory(stack).add_system_info(system_info)).unwrap();
+            let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+            let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+            thread_list
+                .threads
+                .iter()
+                .map(|thread| {
+                    let context = thread.read_context(&system_info, None).ok()?;
+                    Some((context.raw.cpu(), context.get_instruction_pointer()))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        use PlatformId::*;
+        use ProcessorArchitecture::*;
+        // Windows on ARM64 has threads of emulated x64 and x86 code
+        assert_eq!(
+            read_contexts(PROCESSOR_ARCHITECTURE_ARM64, VER_PLATFORM_WIN32_NT),
+            [
+                Some((Cpu::Arm64, 0x1000_1000)),
+                Some((Cpu::X86_64, 0x2000_1000)),
+                Some((Cpu::X86, 0x3000_1000)),
+            ]
+        );
+        // And x64 processes on it have native threads
+        assert_eq!(
+            read_contexts(PROCESSOR_ARCHITECTURE_AMD64, VER_PLATFORM_WIN32_NT),
+            [
+                Some((Cpu::Arm64, 0x1000_1000)),
+                Some((Cpu::X86_64, 0x2000_1000)),
+                None,
+            ]
+        );
+        assert_eq!(
+            CpuEmulation::from_cpus(Cpu::Arm64, Cpu::X86_64),
+            Some(CpuEmulation::Arm64Ec)
+        );
+        assert_eq!(
+            CpuEmulation::from_cpus(Cpu::Arm64, Cpu::X86),
+            Some(CpuEmulation::Chpe)
+        );
+        assert_eq!(CpuEmulation::from_cpus(Cpu::Arm64, Cpu::Arm64), None);
+
+        // Other systems don't emulate them
+        assert_eq!(
+            read_contexts(PROCESSOR_ARCHITECTURE_ARM64, Linux),
+            [Some((Cpu::Arm64, 0x1000_1000)), None, None]
+        );
+    }
+
+    #[test]
+    fn test_sanitize() {
+        use procfs_core::process::MMapPath;
+
+        let name = DumpString::new("/home/me/app/libfoo.so", Endian::Little);
+        let unloaded_name = DumpString::new("/home/me/app/libold.so", Endian::Little);
+        let cv_record = Section::with_endian(Endian::Little)
+            .D32(md::CvSignature::Pdb70 as u32)
+            .D32(0xabcd1234)
+            .D16(0xf00d)
+            .D16(0xbeef)
+            .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+            .D32(1)
+            .append_bytes(b"c:\\users\\me\\build\\libfoo.pdb\0");
+        let module = SynthModule::new(
+            Endian::Little,
+            0x40000000,
+            0x10000,
+            &name,
+            0xb1054d2a,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        )
+        .cv_record(&cv_record);
+        let unloaded =
+            SynthUnloadedModule::new(Endian::Little, 0x50000000, 0x1000, &unloaded_name, 0, 0);
+        let context = minidump_synth::x86_context(Endian::Little, 0x40001000, 0x1010);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(b"the stack"),
+            0x1000,
+        );
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let heap = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(b"some heap"),
+            0x20000,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_module(module)
+            .add(name)
+            .add(cv_record)
+            .add_unloaded_module(unloaded)
+            .add(unloaded_name)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_memory(heap)
+            .set_linux_environ(b"HOME=/home/me\0")
+            .set_linux_maps(
+                b"40000000-40010000 r-xp 00000000 fd:01 42     /home/me/app/libfoo.so\n\
+                  7f0000008000-7f0000009000 rw-p 00000000 00:00 0 [heap]\n",
+            );
+        let dump = read_synth_dump(dump).unwrap();
+        let dump_module_debug_id = dump
+            .get_stream::<MinidumpModuleList>()
+            .unwrap()
+            .iter()
+            .next()
+            .unwrap()
+            .debug_identifier();
+        let contains = |bytes: &[u8], needle: &[u8]| {
+            bytes.windows(needle.len()).any(|window| window == needle)
+        };
+
+        // Nothing is removed by default.
+        assert_eq!(dump.sanitize(&SanitizeOptions::default()), dump.data);
+
+        let options = SanitizeOptions {
+            scrub_heap_memory: true,
+            erase_environment: true,
+            strip_module_paths: true,
+        };
+        let sanitized = dump.sanitize(&options);
+        assert!(!contains(&sanitized, b"HOME=/home/me"));
+        assert!(!contains(&sanitized, b"some heap"));
+        assert!(!contains(&sanitized, b"/home/me/app"));
+        let utf16_path: Vec<u8> = "/home/me/app"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        assert!(!contains(&sanitized, &utf16_path));
+        assert!(!contains(&sanitized, b"c:\\users\\me\\build"));
+
+        let sanitized = Minidump::read(sanitized).unwrap();
+        let memory_list = sanitized.get_memory().unwrap();
+        let contents = |address| memory_list.memory_at_address(address).unwrap().bytes();
+        assert_eq!(contents(0x1000), b"the stack");
+        assert_eq!(contents(0x20000), &[0; 9]);
+
+        let modules = sanitized.get_stream::<MinidumpModuleList>().unwrap();
+        let modules = modules.iter().collect::<Vec<_>>();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].base_address(), 0x40000000);
+        assert_eq!(modules[0].code_file(), "libfoo.so");
+        assert_eq!(modules[0].debug_file().unwrap(), "libfoo.pdb");
+        assert_eq!(modules[0].debug_identifier(), dump_module_debug_id);
+        let unloaded = sanitized
+            .get_stream::<MinidumpUnloadedModuleList>()
+            .unwrap();
+        let unloaded = unloaded.iter().collect::<Vec<_>>();
+        assert_eq!(unloaded[0].code_file(), "libold.so");
+
+        let maps = sanitized.get_stream::<MinidumpLinuxMaps>().unwrap();
+        let paths: Vec<_> = maps.iter().map(|map| map.map.pathname.clone()).collect();
+        assert_eq!(paths, [MMapPath::Path("libfoo.so".into()), MMapPath::Heap]);
+
+        let environ = sanitized.get_stream::<MinidumpLinuxEnviron>().unwrap();
+        assert_eq!(environ.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_extract_thread() {
+        let name = DumpString::new("single module", Endian::Little);
+        let cv_record = Section::with_endian(Endian::Little)
+            .D32(md::CvSignature::Pdb70 as u32)
+            .D32(0xabcd1234)
+            .D16(0xf00d)
+            .D16(0xbeef)
+            .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+            .D32(1)
+            .append_bytes(b"c:\\foo\\file.pdb\0");
+        let module = SynthModule::new(
+            Endian::Little,
+            0x40000000,
+            0x10000,
+            &name,
+            0xb1054d2a,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        )
+        .cv_record(&cv_record);
+
+        // The crashing thread, whose exception context has a different stack pointer.
+        let context1 = minidump_synth::x86_context(Endian::Little, 0x40001000, 0x1010);
+        let exception_context = minidump_synth::x86_context(Endian::Little, 0x40002000, 0x1020);
+        let stack1 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(b"the first stack"),
+            0x1000,
+        );
+        let thread1 = Thread::new(Endian::Little, 0x1234, &stack1, &context1);
+        let exception = Exception::with_code(Endian::Little, 0xc0000005, 0x40002000, 0x1234)
+            .context(&exception_context);
+        // A thread whose stack pointer isn't in its stack.
+        let context2 = minidump_synth::x86_context(Endian::Little, 0x40003000, 0x9010);
+        let stack2 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(b"the second stack"),
+            0x5000,
+        );
+        let actual_stack2 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(7, 0x100),
+            0x9000,
+        );
+        let thread2 = Thread::new(Endian::Little, 0x5678, &stack2, &context2);
+        let heap = Memory::with_section(
+            Section::with_endian(Endian::Little).append_bytes(b"some heap"),
+            0x20000,
+        );
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_module(module)
+            .add(name)
+            .add(cv_record)
+            .add_thread(thread1)
+            .add_thread(thread2)
+            .add(context1)
+            .add(context2)
+            .add(exception_context)
+            .add_exception(exception)
+            .add_memory(stack1)
+            .add_memory(stack2)
+            .add_memory(actual_stack2)
+            .add_memory(heap)
+            .add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+
+        let extract = |thread_id| Minidump::read(dump.extract_thread(thread_id).unwrap()).unwrap();
+        let threads = |dump: &Minidump<Vec<u8>>| {
+            let thread_list = dump.get_stream::<MinidumpThreadList>().unwrap();
+            let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+            let memory_list = dump.get_memory().unwrap_or_default();
+            thread_list
+                .threads
+                .iter()
+                .map(|thread| {
+                    let context = thread.read_context(&system_info, None).unwrap();
+                    let stack = thread.stack_memory(&memory_list).unwrap();
+                    (
+                        thread.raw.thread_id,
+                        context.get_instruction_pointer(),
+                        context.get_stack_pointer(),
+                        stack.base_address(),
+                        stack.bytes().to_vec(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let regions = |dump: &Minidump<Vec<u8>>| {
+            let memory_list = dump.get_memory().unwrap();
+            memory_list
+                .iter()
+                .map(|region| region.base_address())
+                .collect::<Vec<_>>()
+        };
+        let original_threads = threads(&dump);
+
+        let extracted = extract(0x1234);
+        assert_eq!(threads(&extracted), original_threads[..1]);
+        assert_eq!(regions(&extracted), [0x1000]);
+        let system_info = extracted.get_stream::<MinidumpSystemInfo>().unwrap();
+        assert_eq!(system_info.cpu, Cpu::X86);
+        let module_list = extracted.get_stream::<MinidumpModuleList>().unwrap();
+        let modules = module_list.iter().collect::<Vec<_>>();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].base_address(), 0x40000000);
+        assert_eq!(modules[0].code_file(), "single module");
+        assert_eq!(modules[0].debug_file().unwrap(), "c:\\foo\\file.pdb");
+        let exception = extracted.get_stream::<MinidumpException>().unwrap();
+        assert_eq!(exception.get_crashing_thread_id(), 0x1234);
+        assert_eq!(exception.raw.exception_record.exception_code, 0xc0000005);
+        let context = exception.context(&system_info, None).unwrap();
+        assert_eq!(context.get_instruction_pointer(), 0x40002000);
+        assert_eq!(context.get_stack_pointer(), 0x1020);
+
+        // The exception isn't for this thread, but the memory its stack pointer
+        // is in has to come along.
+        let extracted = extract(0x5678);
+        assert_eq!(threads(&extracted), original_threads[1..]);
+        assert_eq!(regions(&extracted), [0x5000, 0x9000]);
+        assert!(extracted.get_stream::<MinidumpException>().is_err());
+        let memory_list = extracted.get_memory().unwrap();
+        let memory = memory_list.memory_at_address(0x9010).unwrap();
+        assert_eq!(memory.bytes(), &[7; 0x100][..]);
+
+        assert_eq!(dump.extract_thread(0x9999), Err(Error::ThreadNotFound));
+    }
+
+    #[test]
+    fn test_thread_xstate() {
+        let ymm: Vec<[u128; 2]> = (0..16u128)
+            .map(|i| [0x1111_0000 + i, (0x2222_0000 + i) << 64])
+            .collect();
+        let registers = [("rip", 0x1234abcd1234abcd), ("rsp", 0x10000000)];
+
+        let read_thread = |context: Section, misc: Option<MiscStream>| {
+            let stack = Memory::with_section(
+                Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+                0x10000000,
+            );
+            let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
+            let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+            let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+            let mut dump = SynthMinidump::with_endian(Endian::Little)
+                .add_thread(thread)
+                .add(context)
+                .add_memory(stack)
+                .add_system_info(system_info);
+            if let Some(misc) = misc {
+                dump = dump.add_stream(misc);
+            }
+            let dump = read_synth_dump(dump).unwrap();
+            let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+            let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+            let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+            let thread = &thread_list.threads[0];
+            let context = thread
+                .context(&system_info, misc_info.as_ref())
+                .expect("Should have a thread context");
+            match &context.raw {
+                MinidumpRawContext::Amd64(raw) => {
+                    assert_eq!(raw.rip, 0x1234abcd1234abcd);
+                    assert_eq!(raw.rsp, 0x10000000);
+                }
+                _ => panic!("Got unexpected raw context type!"),
+            }
+            thread.xstate(&system_info, misc_info.as_ref())
+        };
+
+        // Usual and compacted layouts
+        for compacted in [false, true] {
+            let context = minidump_synth::amd64_context_with_xstate(
+                Endian::Little,
+                &registers,
+                &ymm,
+                compacted,
+            );
+            let xstate = read_thread(context, None).unwrap();
+            assert_eq!(xstate.features, 0b111);
+            assert_eq!(xstate.ymm.as_ref(), Some(&ymm));
+        }
+
+        // The layout in MISC_INFO_5
+        let mut features = [md::XSTATE_FEATURE::default(); 64];
+        features[md::XstateFeatureIndex::GSSE_AND_AVX as usize] = md::XSTATE_FEATURE {
+            offset: 576,
+            size: 256,
+        };
+        let mut misc = MiscStream::new(Endian::Little);
+        misc.misc_5 = Some(MiscInfo5Fields {
+            xstate_data: md::XSTATE_CONFIG_FEATURE_MSC_INFO {
+                context_size: 1600,
+                enabled_features: 0b111,
+                features,
+                ..Default::default()
+            },
+            process_cookie: None,
+        });
+        let context =
+            minidump_synth::amd64_context_with_xstate(Endian::Little, &registers, &ymm, false);
+        let xstate = read_thread(context, Some(misc)).unwrap();
+        assert_eq!(xstate.ymm, Some(ymm.clone()));
+
+        // A truncated extended context doesn't affect the classic one
+        let context =
+            minidump_synth::amd64_context_with_xstate(Endian::Little, &registers, &ymm, false);
+        let bytes = context.get_contents().unwrap();
+        for len in [1232, 1240, 1300, bytes.len() - 1] {
+            let context = Section::with_endian(Endian::Little).append_bytes(&bytes[..len]);
+            assert_eq!(read_thread(context, None), None);
+        }
+
+        // No XSTATE at all
+        let context = minidump_synth::amd64_context_with_registers(Endian::Little, &registers);
+        assert_eq!(read_thread(context, None), None);
+
+        // AVX-512 and CET state
+        let xmm: Vec<u128> = ymm.iter().map(|&[low, _]| low).collect();
+        let zmm: Vec<[u128; 4]> = (0..32u128)
+            .map(|i| {
+                let low = ymm.get(i as usize).copied().unwrap_or([i << 64, i]);
+                [low[0], low[1], 0x3333_0000 + i, 0x4444_0000 + i]
+            })
+            .collect();
+        let opmask = [1, 2, 4, 8, 16, 32, 64, 0xffff_ffff_ffff_ffff];
+        let ssp = 0x7ffe_1234_5ff8;
+        // x87, SSE, AVX, AVX-512 and CET_U
+        let mask = 0b1000_1110_0111u64;
+        for compacted in [false, true] {
+            let mask = if compacted { mask } else { mask & !(1 << 11) };
+            let mut xsave = Section::with_endian(Endian::Little)
+                .D64(mask)
+                .D64(if compacted { (1 << 63) | mask } else { 0 })
+                .append_repeated(0, mem::size_of::<u64>() * 6);
+            for &[_, high] in &ymm {
+                xsave = xsave.D64(high as u64).D64((high >> 64) as u64);
+            }
+            if !compacted {
+                // The opmask state is at offset 1088, after the MPX state
+                xsave = xsave.append_repeated(0, 1088 - 832);
+            }
+            for k in opmask {
+                xsave = xsave.D64(k);
+            }
+            for &[_, _, q2, q3] in &zmm[..16] {
+                for q in [q2, q3] {
+                    xsave = xsave.D64(q as u64).D64((q >> 64) as u64);
+                }
+            }
+            for quarters in &zmm[16..] {
+                for q in quarters {
+                    xsave = xsave.D64(*q as u64).D64((*q >> 64) as u64);
+                }
+            }
+            if compacted {
+                xsave = xsave.D64(1).D64(ssp);
+            }
+            let context = minidump_synth::amd64_context_with_xsave_area(
+                Endian::Little,
+                &registers,
+                &xmm,
+                xsave,
+            );
+            let xstate = read_thread(context, None).unwrap();
+            assert_eq!(xstate.features, mask);
+            assert_eq!(xstate.ymm.as_ref(), Some(&ymm));
+            assert_eq!(xstate.opmask, Some(opmask));
+            assert_eq!(xstate.zmm.as_ref(), Some(&zmm));
+            let expected_ssp = compacted.then_some(ssp);
+            assert_eq!(xstate.shadow_stack_pointer, expected_ssp);
+        }
+    }
 
-        assert!(modules[0].debug_identifier().is_none());
-        assert!(modules[1].debug_identifier().is_none());
+    #[test]
+    fn test_synth_contexts_with_registers() {
+        use md::ProcessorArchitecture::*;
+
+        let x86_registers = [
+            ("eip", 0x0040_1234),
+            ("esp", 0x0012_ff00),
+            ("ebp", 0x0012_ff40),
+            ("eax", 0xdead_beef),
+            ("edi", 0x1111_2222),
+        ];
+        let amd64_registers = [
+            ("rip", 0x7ff6_0000_1234),
+            ("rsp", 0x0000_00ff_ff00),
+            ("rax", 0x0123_4567_89ab_cdef),
+            ("r15", 0xfedc_ba98_7654_3210),
+        ];
+        let arm_registers = [
+            ("pc", 0x0040_1234),
+            ("sp", 0x7fff_0000),
+            ("r0", 0x1234_5678),
+            ("r11", 0x7fff_0040),
+            ("lr", 0x0040_5678),
+        ];
+        let arm64_registers = [
+            ("pc", 0x7ff6_0000_1234),
+            ("sp", 0x00ff_ffff_0000),
+            ("x0", 0x0123_4567_89ab_cdef),
+            ("x28", 0x28),
+            ("x29", 0x00ff_ffff_0040),
+            ("lr", 0x7ff6_0000_5678),
+        ];
+        let cases: [(_, _, Section, &[(&str, u64)]); 4] = [
+            (
+                PROCESSOR_ARCHITECTURE_INTEL,
+                Endian::Little,
+                minidump_synth::x86_context_with_registers(Endian::Little, &x86_registers),
+                &x86_registers,
+            ),
+            (
+                PROCESSOR_ARCHITECTURE_AMD64,
+                Endian::Little,
+                minidump_synth::amd64_context_with_registers(Endian::Little, &amd64_registers),
+                &amd64_registers,
+            ),
+            (
+                PROCESSOR_ARCHITECTURE_ARM,
+                Endian::Big,
+                minidump_synth::arm_context_with_registers(Endian::Big, &arm_registers),
+                &arm_registers,
+            ),
+            (
+                PROCESSOR_ARCHITECTURE_ARM64,
+                Endian::Little,
+                minidump_synth::arm64_context_with_registers(Endian::Little, &arm64_registers),
+                &arm64_registers,
+            ),
+        ];
+        for (arch, endian, context, registers) in cases {
+            let stack = Memory::with_bytes(0x1000, &[0; 0x100]);
+            let system_info = SystemInfo::new(endian).set_processor_architecture(arch as u16);
+            let thread = Thread::new(endian, 0x1234, &stack, &context);
+            let dump = SynthMinidump::with_endian(endian)
+                .add_thread(thread)
+                .add(context)
+                .add_memory(stack)
+                .add_system_info(system_info);
+            let dump = read_synth_dump(dump).unwrap();
+            let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+            let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+            let context = thread_list.threads[0]
+                .context(&system_info, None)
+                .expect("Should have a thread context");
+            for &(reg, val) in registers {
+                assert_eq!(context.get_register(reg), Some(val), "{:?} {}", arch, reg);
+            }
+        }
     }
 
     #[test]
-    fn test_thread_list_x86() {
-        let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
-        let stack = Memory::with_section(
-            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
-            0x1000,
+    fn test_synth_exception_builder() {
+        let context = minidump_synth::amd64_context_with_registers(
+            Endian::Little,
+            &[("rip", 0x7ff6_0000_1234), ("rsp", 0x2000), ("rbx", 0x7)],
         );
-        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL as u16;
-        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let stack_bytes: Vec<u8> = (0..0x40).collect();
+        let stack = Memory::with_bytes(0x2000, &stack_bytes);
         let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+            md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+        );
+        let exception = Exception::with_code(
+            Endian::Little,
+            err::ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION as u32,
+            0x7ff6_0000_1234,
+            0x1234,
+        )
+        .parameters(&[1, 0x10])
+        .context(&context);
+
         let dump = SynthMinidump::with_endian(Endian::Little)
             .add_thread(thread)
+            .add_exception(exception)
             .add(context)
             .add_memory(stack)
             .add_system_info(system_info);
         let dump = read_synth_dump(dump).unwrap();
-        let mut thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
         let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
-        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
-        assert_eq!(thread_list.threads.len(), 1);
-        let mut thread = thread_list.threads.pop().unwrap();
-        assert_eq!(thread.raw.thread_id, 0x1234);
-        let context = thread
-            .context(&system_info, misc_info.as_ref())
-            .expect("Should have a thread context");
-        match &context.raw {
-            MinidumpRawContext::X86(raw) => {
-                assert_eq!(raw.eip, 0xabcd1234);
-                assert_eq!(raw.esp, 0x1010);
+        let exception = dump.get_stream::<MinidumpException>().unwrap();
+
+        let record = &exception.raw.exception_record;
+        assert_eq!(exception.get_crashing_thread_id(), 0x1234);
+        assert_eq!(
+            record.exception_code,
+            err::ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION as u32
+        );
+        assert_eq!(record.exception_address, 0x7ff6_0000_1234);
+        assert_eq!(record.number_parameters, 2);
+        assert_eq!(record.exception_information[..3], [1, 0x10, 0]);
+
+        let context = exception
+            .context(&system_info, None)
+            .expect("Should have an exception context");
+        assert_eq!(context.get_instruction_pointer(), 0x7ff6_0000_1234);
+        assert_eq!(context.get_register("rbx"), Some(0x7));
+
+        // The stack's contents are addressable from the thread
+        let memory_list = dump.get_memory().unwrap_or_default();
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let stack = thread_list.threads[0].stack_memory(&memory_list).unwrap();
+        assert_eq!(stack.get_memory_at_address::<u8>(0x2010), Some(0x10));
+        assert_eq!(stack.get_memory_at_address::<u64>(0x2040), None);
+    }
+
+    #[test]
+    fn test_thread_list_mips() {
+        use md::ProcessorArchitecture::*;
+
+        let cases = [
+            (
+                PROCESSOR_ARCHITECTURE_MIPS,
+                Endian::Big,
+                0x00401234,
+                0x7fff0000,
+            ),
+            (
+                PROCESSOR_ARCHITECTURE_MIPS64,
+                Endian::Little,
+                0x120001234,
+                0xffffff0000,
+            ),
+        ];
+        for (arch, endian, pc, sp) in cases {
+            let is_mips64 = arch == PROCESSOR_ARCHITECTURE_MIPS64;
+            let context = if is_mips64 {
+                minidump_synth::mips64_context(endian, pc, sp)
+            } else {
+                minidump_synth::mips_context(endian, pc as u32, sp as u32)
+            };
+            let stack =
+                Memory::with_section(Section::with_endian(endian).append_repeated(0, 0x100), sp);
+            let system_info = SystemInfo::new(endian).set_processor_architecture(arch as u16);
+            let thread = Thread::new(endian, 0x1234, &stack, &context);
+            let dump = SynthMinidump::with_endian(endian)
+                .add_thread(thread)
+                .add(context)
+                .add_memory(stack)
+                .add_system_info(system_info);
+            let dump = read_synth_dump(dump).unwrap();
+            let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+            let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+            let context = thread_list.threads[0]
+                .context(&system_info, None)
+                .expect("Should have a thread context");
+            match &context.raw {
+                MinidumpRawContext::Mips(raw) => {
+                    assert_eq!(raw.is_mips64(), is_mips64);
+                    assert_eq!(context.get_stack_pointer(), sp);
+                }
+                _ => panic!("Got unexpected raw context type!"),
             }
-            _ => panic!("Got unexpected raw context type!"),
+            assert_eq!(context.get_instruction_pointer(), pc);
         }
-        let stack = thread.stack.take().expect("Should have stack memory");
-        assert_eq!(stack.base_address, 0x1000);
-        assert_eq!(stack.size, 0x1000);
     }
 
     #[test]
-    fn test_thread_list_amd64() {
-        let context =
-            minidump_synth::amd64_context(Endian::Little, 0x1234abcd1234abcd, 0x1000000010000000);
-        let stack = Memory::with_section(
-            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
-            0x1000000010000000,
-        );
-        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
-        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
-        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
-        let dump = SynthMinidump::with_endian(Endian::Little)
-            .add_thread(thread)
-            .add(context)
-            .add_memory(stack)
-            .add_system_info(system_info);
-        let dump = read_synth_dump(dump).unwrap();
-        let mut thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
-        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
-        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
-        assert_eq!(thread_list.threads.len(), 1);
-        let mut thread = thread_list.threads.pop().unwrap();
-        assert_eq!(thread.raw.thread_id, 0x1234);
-        let context = thread
-            .context(&system_info, misc_info.as_ref())
-            .expect("Should have a thread context");
-        match &context.raw {
-            MinidumpRawContext::Amd64(raw) => {
-                assert_eq!(raw.rip, 0x1234abcd1234abcd);
-                assert_eq!(raw.rsp, 0x1000000010000000);
+    fn test_thread_list_ppc() {
+        use md::ProcessorArchitecture::*;
+
+        let cases = [
+            (PROCESSOR_ARCHITECTURE_PPC, 0x0040_1234, 0x7fff_0000),
+            (
+                PROCESSOR_ARCHITECTURE_PPC64,
+                0x1_0000_1234,
+                0x3fff_ffff_0000,
+            ),
+        ];
+        for (arch, pc, sp) in cases {
+            let endian = Endian::Big;
+            let is_ppc64 = arch == PROCESSOR_ARCHITECTURE_PPC64;
+            let context = if is_ppc64 {
+                minidump_synth::ppc64_context(endian, pc, sp)
+            } else {
+                minidump_synth::ppc_context(endian, pc as u32, sp as u32)
+            };
+            let stack =
+                Memory::with_section(Section::with_endian(endian).append_repeated(0, 0x100), sp);
+            let system_info = SystemInfo::new(endian).set_processor_architecture(arch as u16);
+            let thread = Thread::new(endian, 0x1234, &stack, &context);
+            let dump = SynthMinidump::with_endian(endian)
+                .add_thread(thread)
+                .add(context)
+                .add_memory(stack)
+                .add_system_info(system_info);
+            let dump = read_synth_dump(dump).unwrap();
+            let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+            let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+            let context = thread_list.threads[0]
+                .context(&system_info, None)
+                .expect("Should have a thread context");
+            match (&context.raw, is_ppc64) {
+                (MinidumpRawContext::Ppc(raw), false) => assert_eq!(raw.gpr[1] as u64, sp),
+                (MinidumpRawContext::Ppc64(raw), true) => assert_eq!(raw.gpr[1], sp),
+                _ => panic!("Got unexpected raw context type!"),
             }
-            _ => panic!("Got unexpected raw context type!"),
+            assert_eq!(context.get_instruction_pointer(), pc);
+            assert_eq!(context.get_stack_pointer(), sp);
+            assert_eq!(context.get_register("r1"), Some(sp));
+            assert_eq!(context.get_register("lr"), Some(0));
+
+            let mut output = Vec::new();
+            context.print(&mut output).unwrap();
+            let output = String::from_utf8(output).unwrap();
+            let header = if is_ppc64 {
+                "CONTEXT_PPC64\n"
+            } else {
+                "CONTEXT_PPC\n"
+            };
+            assert!(output.starts_with(header), "{}", output);
+            assert!(output.contains(&format!("  gpr[ 1]                    = {sp:#x}\n")));
         }
-        let stack = thread.stack.take().expect("Should have stack memory");
-        assert_eq!(stack.base_address, 0x1000000010000000);
-        assert_eq!(stack.size, 0x1000);
     }
 
     #[test]
@@ -7165,6 +12994,61 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_system_info_cpu_features() {
+        let mut system_info = SystemInfo::new(Endian::Little);
+        system_info.cpu = CpuInfo::X86CpuInfo {
+            vendor_id: [0x756e_6547, 0x4965_6e69, 0x6c65_746e],
+            version_information: 0,
+            feature_information: (1 << 0) | (1 << 25) | (1 << 26),
+            amd_extended_cpu_features: 1 << 20,
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little).add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        assert_eq!(
+            system_info.cpu_info().as_deref(),
+            Some("GenuineIntel family 6 model 0 stepping 0")
+        );
+        // AMD's extended features are ignored for other vendors
+        assert_eq!(system_info.cpu_features(), ["fpu", "sse", "sse2"]);
+
+        let mut system_info = SystemInfo::new(Endian::Little);
+        system_info.cpu = CpuInfo::X86CpuInfo {
+            vendor_id: [0x6874_7541, 0x6974_6e65, 0x444d_4163],
+            version_information: 0,
+            feature_information: (1 << 0) | (1 << 25) | (1 << 26),
+            amd_extended_cpu_features: 1 << 20,
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little).add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        assert_eq!(system_info.cpu_features(), ["fpu", "sse", "sse2", "nx"]);
+
+        let mut system_info = SystemInfo::new(Endian::Little)
+            .set_processor_architecture(ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16)
+            .set_platform_id(PlatformId::VER_PLATFORM_WIN32_NT as u32);
+        system_info.cpu = CpuInfo::OtherCpuInfo {
+            processor_features: [(1 << 10) | (1 << 12) | (1 << 40), 0],
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little).add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        assert_eq!(system_info.cpu_features(), ["sse2", "nx", "avx2"]);
+
+        // The same bits mean nothing we know of for other operating systems
+        let mut system_info = SystemInfo::new(Endian::Little)
+            .set_processor_architecture(ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16)
+            .set_platform_id(PlatformId::Linux as u32);
+        system_info.cpu = CpuInfo::OtherCpuInfo {
+            processor_features: [(1 << 10) | (1 << 12) | (1 << 40), 0],
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little).add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        assert!(system_info.cpu_features().is_empty());
+    }
+
     #[test]
     fn test_exception_x86() {
         // Defaults to x86
@@ -7216,6 +13100,111 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_exception_chain() {
+        const MSVC_CPP_EXCEPTION: u32 = 0xe06d_7363;
+        const RECORDS: u64 = 0x1_0000;
+        let system_info = SystemInfo::new(Endian::Little)
+            .set_processor_architecture(ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16)
+            .set_platform_id(PlatformId::VER_PLATFORM_WIN32_NT as u32);
+        let mut exception = Exception::new(Endian::Little);
+        exception.exception_record.exception_code = MSVC_CPP_EXCEPTION;
+        exception.exception_record.exception_record = RECORDS;
+
+        // An access violation, nested in a noncontinuable exception whose
+        // nested record wasn't captured.
+        let mut records = Section::with_endian(Endian::Little)
+            .D32(err::ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION as u32)
+            .D32(0)
+            .D64(RECORDS + 0x98)
+            .D64(0x7ff0_1234)
+            .D32(2)
+            .D32(0)
+            .D64(1)
+            .D64(0x28);
+        records = records.append_repeated(0, 13 * 8);
+        records = records
+            .D32(err::ExceptionCodeWindows::EXCEPTION_NONCONTINUABLE_EXCEPTION as u32)
+            .D32(1)
+            .D64(0xdead_0000)
+            .D64(0x7ff0_5678)
+            .D32(0)
+            .D32(0)
+            .append_repeated(0, 15 * 8);
+
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(system_info)
+            .add_exception(exception)
+            .add_memory(Memory::with_section(records, RECORDS));
+        let dump = read_synth_dump(dump).unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let exception = dump.get_stream::<MinidumpException>().unwrap();
+        let memory = dump.get_memory().unwrap();
+
+        let chain = exception.exception_chain(&memory, system_info.cpu);
+        let codes: Vec<u32> = chain
+            .iter()
+            .map(|exception| exception.raw.exception_record.exception_code)
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                MSVC_CPP_EXCEPTION,
+                err::ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION as u32,
+                err::ExceptionCodeWindows::EXCEPTION_NONCONTINUABLE_EXCEPTION as u32,
+            ]
+        );
+        assert_eq!(
+            chain[1].get_crash_address(system_info.os, system_info.cpu),
+            0x28
+        );
+        assert_eq!(chain[2].raw.exception_record.exception_flags, 1);
+        assert!(chain[1].context.is_none());
+        assert_eq!(chain[2].get_crashing_thread_id(), exception.thread_id);
+
+        // 32-bit records, the second of which points back to the first.
+        let system_info = SystemInfo::new(Endian::Little)
+            .set_processor_architecture(ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL as u16)
+            .set_platform_id(PlatformId::VER_PLATFORM_WIN32_NT as u32);
+        let mut exception = Exception::new(Endian::Little);
+        exception.exception_record.exception_code = MSVC_CPP_EXCEPTION;
+        exception.exception_record.exception_record = RECORDS;
+        let records = Section::with_endian(Endian::Little)
+            .D32(err::ExceptionCodeWindows::EXCEPTION_STACK_OVERFLOW as u32)
+            .D32(0)
+            .D32(RECORDS as u32 + 0x50)
+            .D32(0x1234)
+            .D32(0)
+            .append_repeated(0, 15 * 4)
+            .D32(err::ExceptionCodeWindows::EXCEPTION_BREAKPOINT as u32)
+            .D32(0)
+            .D32(RECORDS as u32)
+            .D32(0x5678)
+            .D32(1)
+            .D32(0xabcd)
+            .append_repeated(0, 14 * 4);
+
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(system_info)
+            .add_exception(exception)
+            .add_memory(Memory::with_section(records, RECORDS));
+        let dump = read_synth_dump(dump).unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let exception = dump.get_stream::<MinidumpException>().unwrap();
+        let memory = dump.get_memory().unwrap();
+
+        let chain = exception.exception_chain(&memory, system_info.cpu);
+        assert_eq!(chain.len(), 3);
+        let innermost = &chain[2].raw.exception_record;
+        assert_eq!(
+            innermost.exception_code,
+            err::ExceptionCodeWindows::EXCEPTION_BREAKPOINT as u32
+        );
+        assert_eq!(innermost.exception_address, 0x5678);
+        assert_eq!(innermost.number_parameters, 1);
+        assert_eq!(innermost.exception_information[0], 0xabcd);
+    }
+
     #[test]
     fn test_fuzzed_oom() {
         // https://github.com/rust-minidump/rust-minidump/issues/381
@@ -7318,6 +13307,11 @@ mod test {
                 .expect("The `object_name` field must be populated"),
             OBJECT_NAME
         );
+        assert_eq!(handles[0].raw.attributes(), Some(&0xf00ff00f));
+        assert_eq!(handles[0].raw.granted_access(), Some(&0xcafecafe));
+        assert_eq!(handles[0].raw.handle_count(), Some(&0xcacacaca));
+        assert_eq!(handles[0].raw.pointer_count(), Some(&0xbeefbeef));
+        assert_eq!(handles[0].raw.object_info_rva(), None);
     }
 
     #[test]
@@ -7382,6 +13376,208 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_crash_reason_detail() {
+        fn detail(
+            platform: PlatformId,
+            arch: ProcessorArchitecture,
+            code: u32,
+            address: u64,
+            params: &[u64],
+        ) -> Option<CrashReasonDetail> {
+            let system_info = SystemInfo::new(Endian::Little)
+                .set_processor_architecture(arch as u16)
+                .set_platform_id(platform as u32);
+            let mut exception = Exception::new(Endian::Little);
+            exception.exception_record.exception_code = code;
+            exception.exception_record.exception_address = address;
+            exception.exception_record.number_parameters = params.len() as u32;
+            exception.exception_record.exception_information[..params.len()]
+                .copy_from_slice(params);
+
+            let dump = SynthMinidump::with_endian(Endian::Little)
+                .add_system_info(system_info)
+                .add_exception(exception);
+            let dump = read_synth_dump(dump).unwrap();
+            let system_stream = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+            let exception_stream = dump.get_stream::<MinidumpException>().unwrap();
+            exception_stream.get_crash_reason_detail(system_stream.os, system_stream.cpu)
+        }
+
+        use PlatformId::{Linux, VER_PLATFORM_WIN32_NT as Windows};
+        use ProcessorArchitecture::{
+            PROCESSOR_ARCHITECTURE_AMD64 as Amd64, PROCESSOR_ARCHITECTURE_INTEL as X86,
+        };
+
+        let av = detail(
+            Windows,
+            Amd64,
+            err::ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION as u32,
+            0x7ff0_1234,
+            &[1, 0x10],
+        )
+        .unwrap();
+        assert_eq!(
+            av,
+            CrashReasonDetail::AccessViolation {
+                access: Some(MemoryAccessKind::Write),
+                address: 0x10
+            }
+        );
+        assert_eq!(
+            av.to_string(),
+            "Write access violation at 0x0000000000000010"
+        );
+
+        // Sign-extended addresses are truncated on 32-bit
+        assert_eq!(
+            detail(
+                Windows,
+                X86,
+                err::ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION as u32,
+                0x1234,
+                &[8, 0xffff_ffff_8000_0000],
+            ),
+            Some(CrashReasonDetail::AccessViolation {
+                access: Some(MemoryAccessKind::Execute),
+                address: 0x8000_0000
+            })
+        );
+
+        let in_page = detail(
+            Windows,
+            Amd64,
+            err::ExceptionCodeWindows::EXCEPTION_IN_PAGE_ERROR as u32,
+            0x1234,
+            &[0, 0x5000, NtStatusWindows::STATUS_DISK_FULL as u64],
+        )
+        .unwrap();
+        assert_eq!(
+            in_page.to_string(),
+            "Read in-page error at 0x0000000000005000: STATUS_DISK_FULL"
+        );
+
+        assert_eq!(
+            detail(
+                Windows,
+                Amd64,
+                err::ExceptionCodeWindows::UNHANDLED_CPP_EXCEPTION as u32,
+                0x1234,
+                &[0x19930520, 0x1000, 0x2000, 0x7ff0_0000],
+            ),
+            Some(CrashReasonDetail::CppException {
+                object: 0x1000,
+                throw_info: 0x2000,
+                image_base: Some(0x7ff0_0000),
+            })
+        );
+
+        assert_eq!(
+            detail(
+                Windows,
+                Amd64,
+                err::ExceptionCodeWindows::MANAGED_EXCEPTION_CODE_V4 as u32,
+                0x1234,
+                &[0x8013_1509, 0, 0, 0, 0x7ff8_0000],
+            ),
+            Some(CrashReasonDetail::ClrException {
+                hresult: 0x8013_1509,
+                runtime_base: Some(0x7ff8_0000),
+            })
+        );
+
+        let fast_fail = detail(
+            Windows,
+            Amd64,
+            NtStatusWindows::STATUS_STACK_BUFFER_OVERRUN as u32,
+            0x1234,
+            &[err::FastFailCode::FAST_FAIL_GUARD_ICALL_CHECK_FAILURE as u64],
+        )
+        .unwrap();
+        assert_eq!(
+            fast_fail.to_string(),
+            "Fast fail: FAST_FAIL_GUARD_ICALL_CHECK_FAILURE"
+        );
+
+        // Unknown codes report their parameters raw
+        let unknown = detail(Windows, Amd64, 0xe000_1234, 0x1234, &[1, 0xabc]).unwrap();
+        assert_eq!(unknown, CrashReasonDetail::Parameters(vec![1, 0xabc]));
+        assert_eq!(unknown.to_string(), "Exception parameters: 0x1 0xabc");
+        assert_eq!(detail(Windows, Amd64, 0xe000_1234, 0x1234, &[]), None);
+
+        let segv = detail(
+            Linux,
+            Amd64,
+            err::ExceptionCodeLinux::SIGSEGV as u32,
+            0x10,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            segv.to_string(),
+            "Invalid memory access at 0x0000000000000010"
+        );
+    }
+
+    #[test]
+    fn test_mac_crash_info_broken_strings() {
+        const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::MozMacosCrashInfoStream as u32;
+        let record = |thread: u64, strings: &[u8]| {
+            Section::with_endian(Endian::Little)
+                // stream_type
+                .D64(STREAM_TYPE as u64)
+                // version
+                .D64(4)
+                // thread
+                .D64(thread)
+                // dialog_mode
+                .D64(0)
+                .append_bytes(strings)
+        };
+        // The backtrace is cut off without a NUL, and there's no message2 at all
+        let truncated = record(
+            1,
+            b"/usr/lib/libfoo.dylib\0assertion failed\0bad\xffsig\0frame 0, fra",
+        );
+        // None of the strings made it into the record
+        let empty = record(2, b"");
+
+        let mut header = Section::with_endian(Endian::Little)
+            .D32(STREAM_TYPE)
+            // record_count
+            .D32(2)
+            // record_start_size
+            .D32(32)
+            .cite_location(&truncated)
+            .cite_location(&empty);
+        for _ in 2..20 {
+            header = header.D32(0).D32(0);
+        }
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(SimpleStream {
+                stream_type: STREAM_TYPE,
+                section: header,
+            })
+            .add(truncated)
+            .add(empty);
+        let dump = read_synth_dump(dump).unwrap();
+        let crash_info = dump.get_stream::<MinidumpMacCrashInfo>().unwrap();
+        assert_eq!(crash_info.raw.len(), 2);
+
+        let record = &crash_info.raw[0];
+        assert_eq!(record.thread(), Some(&1));
+        assert_eq!(record.module_path(), Some("/usr/lib/libfoo.dylib"));
+        assert_eq!(record.message(), Some("assertion failed"));
+        assert_eq!(record.signature_string(), Some("bad\u{FFFD}sig"));
+        assert_eq!(record.backtrace(), Some("frame 0, fra"));
+        assert_eq!(record.message2(), None);
+
+        let record = &crash_info.raw[1];
+        assert_eq!(record.thread(), Some(&2));
+        assert_eq!(record.module_path(), None);
+        assert_eq!(record.message(), None);
+    }
+
     #[test]
     fn test_linux_abort_si_code() {
         let amd64_system_info = SystemInfo::new(Endian::Little)
@@ -7404,4 +13600,135 @@ mod test {
             "SIGABRT / SI_TKILL"
         );
     }
+
+    #[test]
+    fn test_mac_exc_crash() {
+        let crash_reason = |exception_flags: u32| {
+            let arm64_system_info = SystemInfo::new(Endian::Little)
+                .set_processor_architecture(
+                    ProcessorArchitecture::PROCESSOR_ARCHITECTURE_ARM64 as u16,
+                )
+                .set_platform_id(PlatformId::MacOs as u32);
+            let mut exception = Exception::new(Endian::Little);
+            exception.exception_record.exception_code = err::ExceptionCodeMac::EXC_CRASH as u32;
+            exception.exception_record.exception_flags = exception_flags;
+
+            let dump = SynthMinidump::with_endian(Endian::Little)
+                .add_system_info(arm64_system_info)
+                .add_exception(exception);
+            let dump = read_synth_dump(dump).unwrap();
+            let system_stream = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+            let exception_stream = dump.get_stream::<MinidumpException>().unwrap();
+            exception_stream.get_crash_reason(system_stream.os, system_stream.cpu)
+        };
+
+        // abort()
+        let reason = crash_reason(6 << 24);
+        assert_eq!(
+            reason,
+            CrashReason::MacCrash(None, err::ExceptionCodeMacSignal::SIGABRT)
+        );
+        assert_eq!(reason.to_string(), "EXC_CRASH / SIGABRT");
+
+        // A bad access (KERN_INVALID_ADDRESS) that wasn't handled
+        let reason = crash_reason(11 << 24 | 1 << 20 | 1);
+        assert_eq!(
+            reason,
+            CrashReason::MacCrash(
+                Some(err::ExceptionCodeMac::EXC_BAD_ACCESS),
+                err::ExceptionCodeMacSignal::SIGSEGV
+            )
+        );
+        assert_eq!(reason.to_string(), "EXC_CRASH / EXC_BAD_ACCESS / SIGSEGV");
+
+        // Not a signal
+        let reason = crash_reason(0x4000_0000);
+        assert_eq!(
+            reason,
+            CrashReason::MacGeneral(err::ExceptionCodeMac::EXC_CRASH, 0x4000_0000)
+        );
+    }
+
+    #[test]
+    fn test_auxiliary_streams() {
+        use crate::auxiliary::{AuxiliaryCrashInfo, AuxiliaryStreams};
+
+        const JAVA_STREAM: u32 = 0x4a415641;
+        const BROKEN_STREAM: u32 = 0x4a415642;
+        let java = "java.lang.IllegalStateException: no window\n\
+            \tat com.example.app.MainActivity.onCreate(MainActivity.java:42)\n\
+            \tat android.app.Activity.performCreate(Activity.java:8000)\n\
+            Caused by: java.lang.NullPointerException\n\
+            \tat com.example.app.Window.get(Window.java:7)\n\
+            \t... 2 more\n\0";
+        let broken = "Exception in thread \"main\"\n\tat Main.main(Main.java)\n";
+        let stream = |stream_type, text: &str| SimpleStream {
+            stream_type,
+            section: Section::with_endian(Endian::Little).append_bytes(text.as_bytes()),
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(stream(JAVA_STREAM, java))
+            .add_stream(stream(BROKEN_STREAM, broken));
+        let dump = read_synth_dump(dump).unwrap();
+
+        let mut streams = AuxiliaryStreams::new();
+        assert_eq!(streams.read(&dump), []);
+        streams.register_java(JAVA_STREAM);
+        streams.register_java(BROKEN_STREAM);
+        // Not in the dump
+        streams.register_java(0x4a415643);
+        assert!(streams.contains(JAVA_STREAM));
+        assert!(!streams.contains(0x4a415644));
+        assert_eq!(
+            streams.read(&dump),
+            [
+                AuxiliaryCrashInfo {
+                    stream_type: JAVA_STREAM,
+                    kind: "java".to_owned(),
+                    message: Some("java.lang.IllegalStateException: no window".to_owned()),
+                    frames: vec![
+                        "com.example.app.MainActivity.onCreate(MainActivity.java:42)".to_owned(),
+                        "android.app.Activity.performCreate(Activity.java:8000)".to_owned(),
+                        "Caused by: java.lang.NullPointerException".to_owned(),
+                        "com.example.app.Window.get(Window.java:7)".to_owned(),
+                        "... 2 more".to_owned(),
+                    ],
+                    raw: None,
+                },
+                AuxiliaryCrashInfo {
+                    stream_type: BROKEN_STREAM,
+                    kind: "java".to_owned(),
+                    message: None,
+                    frames: vec![],
+                    raw: Some(broken.to_owned()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_java_throwable() {
+        use crate::auxiliary::parse_java_throwable;
+
+        // Messages can have newlines, and exceptions don't need a message or frames
+        assert_eq!(
+            parse_java_throwable(b"com.example.Error: first\nsecond\n    at Foo.bar(Foo.java:1)"),
+            Some((
+                Some("com.example.Error: first\nsecond".to_owned()),
+                vec!["Foo.bar(Foo.java:1)".to_owned()]
+            ))
+        );
+        assert_eq!(
+            parse_java_throwable(b"java.lang.OutOfMemoryError$Inner"),
+            Some((Some("java.lang.OutOfMemoryError$Inner".to_owned()), vec![]))
+        );
+        assert_eq!(parse_java_throwable(b""), None);
+        assert_eq!(parse_java_throwable(b"\xff\xfe"), None);
+        assert_eq!(parse_java_throwable(b"not a class: message"), None);
+        // Text after the frames that isn't a frame
+        assert_eq!(
+            parse_java_throwable(b"Error\n\tat Foo.bar(Foo.java:1)\ngarbage"),
+            None
+        );
+    }
 }
@@ -0,0 +1,839 @@
+//! Structured versions of the streams' `print` output.
+//!
+//! Every stream type (and [`Minidump`] itself) has a `to_json_value` method,
+//! which has the same fields as `print`, with the same names. Fields that
+//! `print` shows as hex are hex strings (e.g. `"0x7ff0"`), the rest are
+//! numbers or strings, and fields that are missing or invalid are `null`.
+//! Nested structs like `version_info` are nested objects, and the derived
+//! values that `print` shows in parentheses (like `(code_file)`) don't have
+//! parentheses.
+//!
+//! Unlike `print`, the values don't include the contents of memory or the
+//! CPU contexts of threads, which are better read with the library's APIs.
+
+use super::*;
+use serde_json::{json, Map, Value};
+
+fn hex<T: fmt::LowerHex>(value: T) -> Value {
+    Value::String(format!("{value:#x}"))
+}
+
+fn opt_hex<T: fmt::LowerHex>(value: Option<T>) -> Value {
+    value.map_or(Value::Null, hex)
+}
+
+fn location(location: &md::MINIDUMP_LOCATION_DESCRIPTOR) -> Value {
+    json!({
+        "data_size": location.data_size,
+        "rva": hex(location.rva),
+    })
+}
+
+fn pairs<'a>(iter: impl Iterator<Item = (&'a LinuxOsStr, &'a LinuxOsStr)>) -> Value {
+    iter.map(|(key, value)| {
+        (
+            key.to_string_lossy().into_owned(),
+            Value::String(value.to_string_lossy().into_owned()),
+        )
+    })
+    .collect::<Map<_, _>>()
+    .into()
+}
+
+impl<'a, T> Minidump<'a, T>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    /// The header, stream directory, and the contents of every stream of the
+    /// minidump, as a JSON value.
+    ///
+    /// This has what [`Minidump::print`][] prints, and a `streams` array with
+    /// the [`TypedStream::to_json_value`][] of each stream in the directory (or
+    /// the error reading it).
+    pub fn to_json_value(&'a self) -> Value {
+        let header = &self.header;
+        let streams: Vec<Value> = self
+            .typed_streams()
+            .map(|entry| {
+                let (contents, error) = match entry.read() {
+                    Ok(stream) => (stream.to_json_value(), Value::Null),
+                    Err(err) => (Value::Null, Value::String(err.to_string())),
+                };
+                json!({
+                    "index": entry.index,
+                    "stream_type": hex(entry.stream_type()),
+                    "stream_type_name": stream_type_name(entry.stream_type()),
+                    "location": location(&entry.directory.location),
+                    "contents": contents,
+                    "error": error,
+                })
+            })
+            .collect();
+        json!({
+            "header": {
+                "signature": hex(header.signature),
+                "version": hex(header.version),
+                "stream_count": header.stream_count,
+                "stream_directory_rva": hex(header.stream_directory_rva),
+                "checksum": hex(header.checksum),
+                "time_date_stamp": hex(header.time_date_stamp),
+                "time": format_time_t(header.time_date_stamp),
+                "flags": hex(header.flags),
+            },
+            "streams": streams,
+        })
+    }
+}
+
+impl<'a> TypedStream<'a> {
+    /// The contents of this stream as a JSON value.
+    ///
+    /// Streams this implementation can't interpret are just their size.
+    pub fn to_json_value(&self) -> Value {
+        match self {
+            TypedStream::ThreadList(stream) => stream.to_json_value(),
+            TypedStream::ThreadNames(stream) => stream.to_json_value(),
+            TypedStream::ThreadInfoList(stream) => stream.to_json_value(),
+            TypedStream::ModuleList(stream) => stream.to_json_value(),
+            TypedStream::UnloadedModuleList(stream) => stream.to_json_value(),
+            TypedStream::HandleDataStream(stream) => stream.to_json_value(),
+            TypedStream::FunctionTable(stream) => stream.to_json_value(),
+            TypedStream::MemoryList(stream) => stream.to_json_value(),
+            TypedStream::Memory64List(stream) => stream.to_json_value(),
+            TypedStream::MemoryInfoList(stream) => stream.to_json_value(),
+            TypedStream::SystemInfo(stream) => stream.to_json_value(),
+            TypedStream::MiscInfo(stream) => stream.to_json_value(),
+            TypedStream::Exception(stream) => stream.to_json_value(),
+            TypedStream::Assertion(stream) => stream.to_json_value(),
+            TypedStream::BreakpadInfo(stream) => stream.to_json_value(),
+            TypedStream::CrashpadInfo(stream) => stream.to_json_value(),
+            TypedStream::MacCrashInfo(stream) => stream.to_json_value(),
+            TypedStream::MacBootargs(stream) => stream.to_json_value(),
+            TypedStream::LinuxMaps(stream) => stream.to_json_value(),
+            TypedStream::LinuxCpuInfo(stream) => stream.to_json_value(),
+            TypedStream::LinuxEnviron(stream) => stream.to_json_value(),
+            TypedStream::LinuxCmdLine(stream) => stream.to_json_value(),
+            TypedStream::LinuxLsbRelease(stream) => stream.to_json_value(),
+            TypedStream::LinuxProcStatus(stream) => stream.to_json_value(),
+            TypedStream::LinuxProcLimits(stream) => stream.to_json_value(),
+            TypedStream::LinuxDsoDebug(stream) => stream.to_json_value(),
+            TypedStream::LinuxAuxv(stream) => stream.to_json_value(),
+            TypedStream::SystemMemoryInfo(stream) => stream.to_json_value(),
+            TypedStream::ProcessVmCounters(stream) => stream.to_json_value(),
+            TypedStream::Token(stream) => stream.to_json_value(),
+            TypedStream::HandleOperationList(stream) => stream.to_json_value(),
+            TypedStream::Unknown(bytes) => json!({ "size": bytes.len() }),
+        }
+    }
+}
+
+impl<'a> MinidumpThread<'a> {
+    /// The raw `MINIDUMP_THREAD` as a JSON value, see [`MinidumpThread::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let raw = &self.raw;
+        json!({
+            "thread_id": hex(raw.thread_id),
+            "suspend_count": raw.suspend_count,
+            "priority_class": hex(raw.priority_class),
+            "priority": hex(raw.priority),
+            "teb": hex(raw.teb),
+            "stack": {
+                "start_of_memory_range": hex(raw.stack.start_of_memory_range),
+                "memory": {
+                    "data_size": hex(raw.stack.memory.data_size),
+                    "rva": hex(raw.stack.memory.rva),
+                },
+            },
+            "thread_context": {
+                "data_size": hex(raw.thread_context.data_size),
+                "rva": hex(raw.thread_context.rva),
+            },
+        })
+    }
+}
+
+impl<'a> MinidumpThreadList<'a> {
+    /// This stream as a JSON value, see [`MinidumpThreadList::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let threads: Vec<Value> = self.threads.iter().map(|t| t.to_json_value()).collect();
+        json!({ "threads": threads })
+    }
+}
+
+impl MinidumpThreadNames {
+    /// This stream as a JSON value, see [`MinidumpThreadNames::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let names: Vec<Value> = self
+            .names
+            .iter()
+            .map(|(thread_id, name)| json!({ "thread_id": hex(thread_id), "name": name }))
+            .collect();
+        json!({ "thread_names": names })
+    }
+}
+
+impl MinidumpThreadInfo {
+    /// The raw `MINIDUMP_THREAD_INFO` as a JSON value, see [`MinidumpThreadInfo::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let raw = &self.raw;
+        json!({
+            "thread_id": hex(raw.thread_id),
+            "dump_flags": hex(raw.dump_flags),
+            "dump_error": hex(raw.dump_error),
+            "exit_status": hex(raw.exit_status),
+            "create_time": hex(raw.create_time),
+            "exit_time": hex(raw.exit_time),
+            "kernel_time": hex(raw.kernel_time),
+            "user_time": hex(raw.user_time),
+            "start_address": hex(raw.start_address),
+            "affinity": hex(raw.affinity),
+        })
+    }
+}
+
+impl MinidumpThreadInfoList {
+    /// This stream as a JSON value, see [`MinidumpThreadInfoList::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let infos: Vec<Value> = self
+            .thread_infos
+            .iter()
+            .map(|info| info.to_json_value())
+            .collect();
+        json!({ "thread_infos": infos })
+    }
+}
+
+impl CodeView {
+    fn to_json_value(&self) -> Value {
+        match self {
+            CodeView::Pdb70(raw) => json!({
+                "cv_signature": hex(raw.cv_signature),
+                "signature": raw.signature.to_string(),
+                "age": raw.age,
+                "pdb_file_name": string_from_bytes_nul(&raw.pdb_file_name),
+            }),
+            CodeView::Pdb20(raw) => json!({
+                "cv_header": {
+                    "signature": hex(raw.cv_signature),
+                    "offset": hex(raw.cv_offset),
+                },
+                "signature": hex(raw.signature),
+                "age": raw.age,
+                "pdb_file_name": string_from_bytes_nul(&raw.pdb_file_name),
+            }),
+            CodeView::Elf(raw) => json!({
+                "cv_signature": hex(raw.cv_signature),
+                "build_id": bytes_to_hex(&raw.build_id),
+            }),
+            CodeView::Unknown(bytes) => json!({ "bytes": bytes_to_hex(bytes) }),
+        }
+    }
+}
+
+impl MinidumpModule {
+    /// The module as a JSON value, see [`MinidumpModule::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let raw = &self.raw;
+        let version_info = &raw.version_info;
+        json!({
+            "base_of_image": hex(raw.base_of_image),
+            "size_of_image": hex(raw.size_of_image),
+            "checksum": hex(raw.checksum),
+            "time_date_stamp": hex(raw.time_date_stamp),
+            "module_name_rva": hex(raw.module_name_rva),
+            "version_info": {
+                "signature": hex(version_info.signature),
+                "struct_version": hex(version_info.struct_version),
+                "file_version_hi": hex(version_info.file_version_hi),
+                "file_version_lo": hex(version_info.file_version_lo),
+                "product_version_hi": hex(version_info.product_version_hi),
+                "product_version_lo": hex(version_info.product_version_lo),
+                "file_flags_mask": hex(version_info.file_flags_mask),
+                "file_flags": hex(version_info.file_flags),
+                "file_os": hex(version_info.file_os),
+                "file_type": hex(version_info.file_type),
+                "file_subtype": hex(version_info.file_subtype),
+                "file_date_hi": hex(version_info.file_date_hi),
+                "file_date_lo": hex(version_info.file_date_lo),
+            },
+            "cv_record": location(&raw.cv_record),
+            "misc_record": location(&raw.misc_record),
+            "code_file": self.code_file(),
+            "code_identifier": self.code_identifier().map(|id| id.to_string()),
+            "codeview": self.codeview_info.as_ref().map(CodeView::to_json_value),
+            "debug_file": self.debug_file(),
+            "debug_identifier": self.debug_identifier().map(|id| id.breakpad().to_string()),
+            "version": self.version(),
+        })
+    }
+}
+
+impl MinidumpModuleList {
+    /// This stream as a JSON value, see [`MinidumpModuleList::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let modules: Vec<Value> = self.modules.iter().map(|m| m.to_json_value()).collect();
+        json!({ "modules": modules })
+    }
+}
+
+impl MinidumpUnloadedModule {
+    /// The module as a JSON value, see [`MinidumpUnloadedModule::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let raw = &self.raw;
+        json!({
+            "base_of_image": hex(raw.base_of_image),
+            "size_of_image": hex(raw.size_of_image),
+            "checksum": hex(raw.checksum),
+            "time_date_stamp": hex(raw.time_date_stamp),
+            "module_name_rva": hex(raw.module_name_rva),
+            "code_file": self.code_file(),
+            "code_identifier": self.code_identifier().map(|id| id.to_string()),
+        })
+    }
+}
+
+impl MinidumpUnloadedModuleList {
+    /// This stream as a JSON value, see [`MinidumpUnloadedModuleList::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let modules: Vec<Value> = self.modules.iter().map(|m| m.to_json_value()).collect();
+        json!({ "modules": modules })
+    }
+}
+
+impl MinidumpHandleDescriptor {
+    /// The handle as a JSON value, see [`MinidumpHandleDescriptor::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let raw = &self.raw;
+        let object_infos: Vec<Value> = self
+            .object_infos
+            .iter()
+            .map(|info| {
+                json!({
+                    "next_info_rva": hex(info.raw.next_info_rva),
+                    "info_type": format!("{:?}", info.info_type),
+                    "size_of_info": info.raw.size_of_info,
+                })
+            })
+            .collect();
+        json!({
+            "handle": opt_hex(raw.handle()),
+            "type_name_rva": opt_hex(raw.type_name_rva()),
+            "object_name_rva": opt_hex(raw.object_name_rva()),
+            "attributes": opt_hex(raw.attributes()),
+            "granted_access": opt_hex(raw.granted_access()),
+            "handle_count": raw.handle_count(),
+            "pointer_count": raw.pointer_count(),
+            "object_info_rva": opt_hex(raw.object_info_rva()),
+            "type_name": self.type_name,
+            "object_name": self.object_name,
+            "object_infos": object_infos,
+        })
+    }
+}
+
+impl MinidumpHandleDataStream {
+    /// This stream as a JSON value, see [`MinidumpHandleDataStream::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let handles: Vec<Value> = self.handles.iter().map(|h| h.to_json_value()).collect();
+        json!({ "handles": handles })
+    }
+}
+
+impl<'a> MinidumpFunctionTableStream<'a> {
+    /// This stream as a JSON value, see [`MinidumpFunctionTableStream::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let tables: Vec<Value> = self
+            .tables
+            .iter()
+            .map(|table| {
+                let entries: Vec<Value> = table
+                    .amd64_entries()
+                    .map(|entry| {
+                        json!({
+                            "begin_address": hex(entry.begin_address),
+                            "end_address": hex(entry.end_address),
+                            "unwind_info_address": hex(entry.unwind_info_address),
+                        })
+                    })
+                    .collect();
+                json!({
+                    "minimum_address": hex(table.raw.minimum_address),
+                    "maximum_address": hex(table.raw.maximum_address),
+                    "base_address": hex(table.raw.base_address),
+                    "entry_count": table.raw.entry_count,
+                    "entries": entries,
+                })
+            })
+            .collect();
+        json!({ "tables": tables })
+    }
+}
+
+impl<'a> MinidumpMemory<'a> {
+    /// The memory descriptor as a JSON value, without the memory's contents.
+    pub fn to_json_value(&self) -> Value {
+        json!({
+            "start_of_memory_range": hex(self.desc.start_of_memory_range),
+            "memory": {
+                "data_size": hex(self.desc.memory.data_size),
+                "rva": hex(self.desc.memory.rva),
+            },
+        })
+    }
+}
+
+impl<'a> MinidumpMemory64<'a> {
+    /// The memory descriptor as a JSON value, without the memory's contents.
+    pub fn to_json_value(&self) -> Value {
+        json!({
+            "start_of_memory_range": hex(self.desc.start_of_memory_range),
+            "memory": {
+                "data_size": hex(self.desc.data_size),
+            },
+        })
+    }
+}
+
+impl<'a> MinidumpMemoryList<'a> {
+    /// This stream as a JSON value, without the memory's contents.
+    pub fn to_json_value(&self) -> Value {
+        let regions: Vec<Value> = self.regions.iter().map(|r| r.to_json_value()).collect();
+        json!({ "regions": regions })
+    }
+}
+
+impl<'a> MinidumpMemory64List<'a> {
+    /// This stream as a JSON value, without the memory's contents.
+    pub fn to_json_value(&self) -> Value {
+        let regions: Vec<Value> = self.regions.iter().map(|r| r.to_json_value()).collect();
+        json!({ "regions": regions })
+    }
+}
+
+impl<'a> MinidumpMemoryInfo<'a> {
+    /// The memory info as a JSON value, see [`MinidumpMemoryInfo::print`][].
+    pub fn to_json_value(&self) -> Value {
+        json!({
+            "base_address": hex(self.raw.base_address),
+            "allocation_base": hex(self.raw.allocation_base),
+            "allocation_protection": hex(self.allocation_protection),
+            "region_size": hex(self.raw.region_size),
+            "state": hex(self.state),
+            "protection": hex(self.protection),
+            "_type": hex(self.ty),
+        })
+    }
+}
+
+impl<'a> MinidumpMemoryInfoList<'a> {
+    /// This stream as a JSON value, see [`MinidumpMemoryInfoList::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let regions: Vec<Value> = self.regions.iter().map(|r| r.to_json_value()).collect();
+        json!({ "regions": regions })
+    }
+}
+
+impl<'a> MinidumpLinuxMapInfo<'a> {
+    /// The mapping as a JSON value, see [`MinidumpLinuxMapInfo::print`][].
+    pub fn to_json_value(&self) -> Value {
+        json!({
+            "base_address": hex(self.map.address.0),
+            "final_address": hex(self.map.address.1),
+            "kind": format!("{:?}", self.map.pathname),
+            "permissions": self.map.perms.as_str(),
+        })
+    }
+}
+
+impl<'a> MinidumpLinuxMaps<'a> {
+    /// This stream as a JSON value, see [`MinidumpLinuxMaps::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let regions: Vec<Value> = self.regions.iter().map(|r| r.to_json_value()).collect();
+        json!({ "regions": regions })
+    }
+}
+
+impl MinidumpSystemInfo {
+    /// This stream as a JSON value, see [`MinidumpSystemInfo::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let raw = &self.raw;
+        json!({
+            "processor_architecture": hex(raw.processor_architecture),
+            "processor_level": raw.processor_level,
+            "processor_revision": hex(raw.processor_revision),
+            "number_of_processors": raw.number_of_processors,
+            "product_type": raw.product_type,
+            "major_version": raw.major_version,
+            "minor_version": raw.minor_version,
+            "build_number": raw.build_number,
+            "platform_id": hex(raw.platform_id),
+            "csd_version_rva": hex(raw.csd_version_rva),
+            "suite_mask": hex(raw.suite_mask),
+            "os": self.os.to_string(),
+            "cpu": self.cpu.to_string(),
+            "csd_version": self.csd_version,
+            "cpu_info": self.cpu_info,
+            "cpu_features": self.cpu_features,
+        })
+    }
+}
+
+impl MinidumpMiscInfo {
+    /// This stream as a JSON value, see [`MinidumpMiscInfo::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let raw = &self.raw;
+        let time_zone = raw.time_zone().map(|time_zone| {
+            json!({
+                "bias": time_zone.bias,
+                "standard_name": utf16_array_to_string(&time_zone.standard_name[..]),
+                "standard_date": format_system_time(&time_zone.standard_date),
+                "standard_bias": time_zone.standard_bias,
+                "daylight_name": utf16_array_to_string(&time_zone.daylight_name[..]),
+                "daylight_date": format_system_time(&time_zone.daylight_date),
+                "daylight_bias": time_zone.daylight_bias,
+            })
+        });
+        let xstate_data = raw.xstate_data().map(|xstate_data| {
+            xstate_data
+                .iter()
+                .map(|(i, feature)| {
+                    json!({
+                        "index": i,
+                        "name": md::XstateFeatureIndex::from_index(i)
+                            .map(|feature| format!("{feature:?}")),
+                        "offset": feature.offset,
+                        "size": feature.size,
+                    })
+                })
+                .collect::<Vec<_>>()
+        });
+        json!({
+            "size_of_info": raw.size_of_info(),
+            "flags1": opt_hex(raw.flags1()),
+            "process_id": raw.process_id(),
+            "process_create_time": opt_hex(raw.process_create_time()),
+            "process_user_time": raw.process_user_time(),
+            "process_kernel_time": raw.process_kernel_time(),
+            "processor_max_mhz": raw.processor_max_mhz(),
+            "processor_current_mhz": raw.processor_current_mhz(),
+            "processor_mhz_limit": raw.processor_mhz_limit(),
+            "processor_max_idle_state": raw.processor_max_idle_state(),
+            "processor_current_idle_state": raw.processor_current_idle_state(),
+            "process_integrity_level": raw.process_integrity_level(),
+            "process_execute_flags": opt_hex(raw.process_execute_flags()),
+            "protected_process": raw.protected_process(),
+            "time_zone_id": raw.time_zone_id(),
+            "time_zone": time_zone,
+            "build_string": raw.build_string().map(|s| utf16_array_to_string(&s[..])),
+            "dbg_bld_str": raw.dbg_bld_str().map(|s| utf16_array_to_string(&s[..])),
+            "xstate_data": xstate_data,
+            "process_cookie": raw.process_cookie(),
+        })
+    }
+}
+
+impl MinidumpHandleOperationList {
+    /// This stream as a JSON value, see [`MinidumpHandleOperationList::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let operations: Vec<Value> = self
+            .iter()
+            .map(|operation| {
+                json!({
+                    "handle": hex(operation.raw.handle),
+                    "process_id": operation.raw.process_id,
+                    "thread_id": hex(operation.raw.thread_id),
+                    "operation_type": operation.raw.operation_type,
+                    "backtrace": operation.backtrace().iter().copied().map(hex).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        json!({ "operations": operations })
+    }
+}
+
+impl<'a> MinidumpTokenStream<'a> {
+    /// This stream as a JSON value, see [`MinidumpTokenStream::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let tokens: Vec<Value> = self
+            .iter()
+            .map(|token| {
+                json!({
+                    "token_size": token.raw.token_size,
+                    "token_id": hex(token.raw.token_id),
+                    "token_handle": hex(token.raw.token_handle),
+                    "integrity_level": token.integrity_level().map(hex),
+                    "is_elevated": token.is_elevated(),
+                })
+            })
+            .collect();
+        json!({ "tokens": tokens })
+    }
+}
+
+impl MinidumpSystemMemoryInfo {
+    /// This stream as a JSON value, see [`MinidumpSystemMemoryInfo::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let basic_info = &self.raw.basic_info;
+        json!({
+            "revision": self.raw.revision,
+            "flags": hex(self.raw.flags),
+            "page_size": hex(basic_info.page_size),
+            "number_of_physical_pages": hex(basic_info.number_of_physical_pages),
+            "allocation_granularity": hex(basic_info.allocation_granularity),
+            "minimum_user_mode_address": hex(basic_info.minimum_user_mode_address),
+            "maximum_user_mode_address": hex(basic_info.maximum_user_mode_address),
+            "number_of_processors": basic_info.number_of_processors,
+            "file_cache_current_size": hex(self.raw.file_cache_info.current_size),
+            "file_cache_peak_size": hex(self.raw.file_cache_info.peak_size),
+            "physical_memory": self.physical_memory(),
+            "available_physical_memory": self.available_physical_memory(),
+            "committed": self.committed(),
+            "commit_limit": self.commit_limit(),
+            "peak_commitment": self.peak_commitment(),
+        })
+    }
+}
+
+impl MinidumpProcessVmCounters {
+    /// This stream as a JSON value, see [`MinidumpProcessVmCounters::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let revision = match &self.raw {
+            RawProcessVmCounters::V1(raw) => raw.revision,
+            RawProcessVmCounters::V2(raw) => raw.revision,
+        };
+        json!({
+            "revision": revision,
+            "page_fault_count": self.page_fault_count(),
+            "peak_working_set_size": self.peak_working_set_size(),
+            "working_set_size": self.working_set_size(),
+            "pagefile_usage": self.pagefile_usage(),
+            "peak_pagefile_usage": self.peak_pagefile_usage(),
+            "private_usage": self.private_usage(),
+            "virtual_size": self.virtual_size(),
+            "peak_virtual_size": self.peak_virtual_size(),
+            "private_working_set_size": self.private_working_set_size(),
+        })
+    }
+}
+
+impl<'a> MinidumpException<'a> {
+    /// This stream as a JSON value, see [`MinidumpException::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let record = &self.raw.exception_record;
+        let parameters: Vec<Value> = record
+            .exception_information
+            .iter()
+            .take(record.number_parameters as usize)
+            .map(hex)
+            .collect();
+        json!({
+            "thread_id": hex(self.thread_id),
+            "exception_record": {
+                "exception_code": hex(record.exception_code),
+                "exception_flags": hex(record.exception_flags),
+                "exception_record": hex(record.exception_record),
+                "exception_address": hex(record.exception_address),
+                "number_parameters": record.number_parameters,
+                "exception_information": parameters,
+            },
+            "thread_context": {
+                "data_size": self.raw.thread_context.data_size,
+                "rva": hex(self.raw.thread_context.rva),
+            },
+        })
+    }
+}
+
+impl MinidumpAssertion {
+    /// This stream as a JSON value, see [`MinidumpAssertion::print`][].
+    pub fn to_json_value(&self) -> Value {
+        json!({
+            "expression": self.expression(),
+            "function": self.function(),
+            "file": self.file(),
+            "line": self.raw.line,
+            "type": self.raw._type,
+        })
+    }
+}
+
+impl MinidumpBreakpadInfo {
+    /// This stream as a JSON value, see [`MinidumpBreakpadInfo::print`][].
+    pub fn to_json_value(&self) -> Value {
+        json!({
+            "validity": hex(self.raw.validity),
+            "dump_thread_id": opt_hex(self.dump_thread_id),
+            "requesting_thread_id": opt_hex(self.requesting_thread_id),
+        })
+    }
+}
+
+impl MinidumpLinuxDsoDebug {
+    /// This stream as a JSON value, see [`MinidumpLinuxDsoDebug::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let link_maps: Vec<Value> = self
+            .link_maps
+            .iter()
+            .map(|link_map| {
+                json!({
+                    "addr": hex(link_map.load_bias),
+                    "name": link_map.name,
+                    "ld": hex(link_map.dynamic),
+                })
+            })
+            .collect();
+        json!({
+            "version": self.version,
+            "brk": hex(self.brk),
+            "ldbase": hex(self.ldbase),
+            "dynamic": hex(self.dynamic),
+            "link_maps": link_maps,
+        })
+    }
+}
+
+impl MinidumpLinuxAuxv {
+    /// This stream as a JSON value, see [`MinidumpLinuxAuxv::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let entries: Vec<Value> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "type": hex(entry.raw_type),
+                    "type_name": entry.known_type().map(|ty| format!("{ty:?}")),
+                    "value": hex(entry.value),
+                })
+            })
+            .collect();
+        json!({ "entries": entries })
+    }
+}
+
+impl MinidumpAnnotation {
+    fn to_json_value(&self) -> Value {
+        match self {
+            MinidumpAnnotation::Invalid => json!({ "type": "invalid" }),
+            MinidumpAnnotation::String(string) => json!({ "type": "string", "value": string }),
+            MinidumpAnnotation::UserDefined(raw) => {
+                json!({ "type": "user_defined", "ty": hex(raw.ty) })
+            }
+            MinidumpAnnotation::Unsupported(raw) => {
+                json!({ "type": "unsupported", "ty": hex(raw.ty) })
+            }
+        }
+    }
+}
+
+impl MinidumpCrashpadInfo {
+    /// This stream as a JSON value, see [`MinidumpCrashpadInfo::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let module_list: Vec<Value> = self
+            .module_list
+            .iter()
+            .map(|module| {
+                let annotation_objects: Map<String, Value> = module
+                    .annotation_objects
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.to_json_value()))
+                    .collect();
+                json!({
+                    "minidump_module_list_index": module.module_index,
+                    "version": module.raw.version,
+                    "list_annotations": module.list_annotations,
+                    "simple_annotations": module.simple_annotations,
+                    "annotation_objects": annotation_objects,
+                })
+            })
+            .collect();
+        json!({
+            "version": self.raw.version,
+            "report_id": self.raw.report_id.to_string(),
+            "client_id": self.raw.client_id.to_string(),
+            "simple_annotations": self.simple_annotations,
+            "module_list": module_list,
+        })
+    }
+}
+
+impl MinidumpMacCrashInfo {
+    /// This stream as a JSON value, see [`MinidumpMacCrashInfo::print`][].
+    pub fn to_json_value(&self) -> Value {
+        let records: Vec<Value> = self
+            .raw
+            .iter()
+            .map(|record| {
+                json!({
+                    "version": record.version(),
+                    "thread": record.thread(),
+                    "dialog_mode": opt_hex(record.dialog_mode()),
+                    "module_path": record.module_path(),
+                    "message": record.message(),
+                    "signature_string": record.signature_string(),
+                    "backtrace": record.backtrace(),
+                    "message2": record.message2(),
+                    "abort_cause": opt_hex(record.abort_cause()),
+                })
+            })
+            .collect();
+        json!({ "records": records })
+    }
+}
+
+impl MinidumpMacBootargs {
+    /// This stream as a JSON value, see [`MinidumpMacBootargs::print`][].
+    pub fn to_json_value(&self) -> Value {
+        json!({ "mac_boot_args": self.bootargs })
+    }
+}
+
+impl<'a> MinidumpLinuxCpuInfo<'a> {
+    /// The `/proc/cpuinfo` dump as a JSON value.
+    ///
+    /// Keys repeat for every processor, so this is an array of `[key, value]`
+    /// pairs in the order of the dump, rather than an object.
+    pub fn to_json_value(&self) -> Value {
+        let entries: Vec<Value> = self
+            .iter()
+            .map(|(key, value)| json!([key.to_string_lossy(), value.to_string_lossy()]))
+            .collect();
+        json!({ "entries": entries })
+    }
+}
+
+impl<'a> MinidumpLinuxEnviron<'a> {
+    /// The `/proc/self/environ` dump as a JSON object of the variables.
+    pub fn to_json_value(&self) -> Value {
+        json!({ "variables": pairs(self.iter()) })
+    }
+}
+
+impl<'a> MinidumpLinuxCmdLine<'a> {
+    /// The `/proc/self/cmdline` dump as a JSON array of the arguments.
+    pub fn to_json_value(&self) -> Value {
+        let args: Vec<Cow<str>> = self.args().map(|arg| arg.to_string_lossy()).collect();
+        json!({ "args": args })
+    }
+}
+
+impl<'a> MinidumpLinuxLsbRelease<'a> {
+    /// The `/etc/lsb-release` dump as a JSON object of its values.
+    pub fn to_json_value(&self) -> Value {
+        json!({ "values": pairs(self.iter()) })
+    }
+}
+
+impl<'a> MinidumpLinuxProcStatus<'a> {
+    /// The `/proc/self/status` dump as a JSON object of its values.
+    pub fn to_json_value(&self) -> Value {
+        json!({ "values": pairs(self.iter()) })
+    }
+}
+
+impl<'a> MinidumpLinuxProcLimits<'a> {
+    /// The `/proc/self/limits` dump as a JSON array of its lines.
+    pub fn to_json_value(&self) -> Value {
+        let lines: Vec<Cow<str>> = self.iter().map(|line| line.to_string_lossy()).collect();
+        json!({ "lines": lines })
+    }
+}
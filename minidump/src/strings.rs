@@ -1,7 +1,119 @@
+//! Strings in minidumps.
+//!
+//! The streams of a minidump refer to most of their strings (module names,
+//! thread names, handle names, the service pack, Crashpad's annotations...) by
+//! RVA: the offset of a `u32` byte length followed by the string's UTF-16 (or,
+//! for Crashpad, UTF-8) code units. All of them are read by the same functions
+//! here, because the RVAs and lengths come straight from the (possibly corrupt
+//! or malicious) minidump. The RVA and length are checked against the minidump
+//! before anything is sliced or allocated, lengths above [`MAX_STRING_BYTES`]
+//! are refused, and what's left is decoded lossily: unpaired surrogates, a
+//! trailing odd byte, and invalid UTF-8 become U+FFFD rather than errors.
+//!
+//! Linux's `/proc` files are arbitrary bytes rather than any encoding, and are
+//! read as [`LinuxOsStr`]s.
+
 use std::borrow::{Borrow, Cow};
+use std::convert::TryFrom;
 use std::ops::Deref;
 use std::str::Utf8Error;
 
+use scroll::Pread;
+use tracing::warn;
+
+/// The longest string (in bytes, without the length) that is read from a
+/// minidump.
+///
+/// That's a bit more than Windows' longest paths (32767 UTF-16 code units).
+pub const MAX_STRING_BYTES: u32 = 64 * 1024;
+
+/// Why a string a minidump refers to couldn't be read.
+#[derive(Clone, Copy, Debug, thiserror::Error, PartialEq, Eq)]
+pub enum StringError {
+    #[error("string at {rva:#x} is outside of the minidump")]
+    OutOfBounds { rva: u64 },
+    #[error(
+        "string at {rva:#x} is {length} bytes long, but the minidump ends {available} bytes later"
+    )]
+    Truncated {
+        rva: u64,
+        length: u32,
+        available: usize,
+    },
+    #[error(
+        "string at {rva:#x} is {length} bytes long, more than the maximum of {MAX_STRING_BYTES}"
+    )]
+    TooLong { rva: u64, length: u32 },
+}
+
+/// The code units of the string at `rva` of `bytes` (the whole minidump).
+fn string_bytes(rva: u64, bytes: &[u8], endian: scroll::Endian) -> Result<&[u8], StringError> {
+    let offset = usize::try_from(rva).map_err(|_| StringError::OutOfBounds { rva })?;
+    let length: u32 = bytes
+        .pread_with(offset, endian)
+        .map_err(|_| StringError::OutOfBounds { rva })?;
+    if length > MAX_STRING_BYTES {
+        return Err(StringError::TooLong { rva, length });
+    }
+    // The length was read, so this is within `bytes`
+    let start = offset + std::mem::size_of::<u32>();
+    let rest = &bytes[start..];
+    rest.get(..length as usize).ok_or(StringError::Truncated {
+        rva,
+        length,
+        available: rest.len(),
+    })
+}
+
+/// Read the UTF-16 string (a `MINIDUMP_STRING`) at `rva` of `bytes` (the whole
+/// minidump), see the [module docs][self].
+pub(crate) fn read_utf16_string(
+    rva: u64,
+    bytes: &[u8],
+    endian: scroll::Endian,
+) -> Result<String, StringError> {
+    let units = string_bytes(rva, bytes, endian)?;
+    let encoding = match endian {
+        scroll::Endian::Little => encoding_rs::UTF_16LE,
+        scroll::Endian::Big => encoding_rs::UTF_16BE,
+    };
+    let (string, had_errors) = encoding.decode_without_bom_handling(units);
+    if had_errors {
+        warn!(
+            "string at {:#x} isn't valid UTF-16, replacing the invalid parts",
+            rva
+        );
+    }
+    Ok(string.into_owned())
+}
+
+/// Read the UTF-8 string (a `MINIDUMP_UTF8_STRING`) at `rva` of `bytes` (the
+/// whole minidump), see the [module docs][self].
+///
+/// The NUL terminator that follows the string isn't required.
+pub(crate) fn read_utf8_string(
+    rva: u64,
+    bytes: &[u8],
+    endian: scroll::Endian,
+) -> Result<String, StringError> {
+    let units = string_bytes(rva, bytes, endian)?;
+    let string = String::from_utf8_lossy(units);
+    if matches!(string, Cow::Owned(_)) {
+        warn!(
+            "string at {:#x} isn't valid UTF-8, replacing the invalid parts",
+            rva
+        );
+    }
+    Ok(string.into_owned())
+}
+
+/// Read a fixed-size NUL-padded UTF-16 string (like the ones in
+/// `MINIDUMP_ASSERTION_INFO`), lossily.
+pub(crate) fn utf16_array_to_string(data: &[u16]) -> String {
+    let len = data.iter().take_while(|c| **c != 0).count();
+    String::from_utf16_lossy(&data[..len])
+}
+
 /// OsStr, but specifically for Linux (since we aren't always processing native dumps).
 #[derive(Debug, PartialOrd, Ord, Eq, PartialEq)]
 pub struct LinuxOsStr([u8]);
@@ -176,3 +288,113 @@ impl Deref for LinuxOsStr {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A little-endian string `length` bytes long, followed by `units`, at RVA 4.
+    fn dump(length: u32, units: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0xff; 4];
+        bytes.extend_from_slice(&length.to_le_bytes());
+        bytes.extend_from_slice(units);
+        bytes
+    }
+
+    #[test]
+    fn test_read_utf16_string() {
+        let le = scroll::Endian::Little;
+        let bytes = dump(6, b"a\0b\0c\0");
+        assert_eq!(read_utf16_string(4, &bytes, le), Ok(String::from("abc")));
+        assert_eq!(read_utf16_string(4, &dump(0, b""), le), Ok(String::new()));
+
+        let big_endian = b"\0\0\0\x04\0a\0b";
+        assert_eq!(
+            read_utf16_string(0, big_endian, scroll::Endian::Big),
+            Ok(String::from("ab"))
+        );
+    }
+
+    #[test]
+    fn test_read_utf16_string_lossy() {
+        let le = scroll::Endian::Little;
+        // An unpaired surrogate
+        let bytes = dump(4, b"\x00\xd8a\0");
+        assert_eq!(
+            read_utf16_string(4, &bytes, le),
+            Ok(String::from("\u{fffd}a"))
+        );
+        // An odd length
+        let bytes = dump(5, b"a\0b\0c\0");
+        assert_eq!(
+            read_utf16_string(4, &bytes, le),
+            Ok(String::from("ab\u{fffd}"))
+        );
+    }
+
+    #[test]
+    fn test_read_string_errors() {
+        let le = scroll::Endian::Little;
+        let bytes = dump(8, b"a\0b\0");
+        assert_eq!(
+            read_utf16_string(4, &bytes, le),
+            Err(StringError::Truncated {
+                rva: 4,
+                length: 8,
+                available: 4
+            })
+        );
+        assert_eq!(
+            read_utf16_string(bytes.len() as u64 - 2, &bytes, le),
+            Err(StringError::OutOfBounds { rva: 10 })
+        );
+        assert_eq!(
+            read_utf8_string(u64::MAX, &bytes, le),
+            Err(StringError::OutOfBounds { rva: u64::MAX })
+        );
+        // Refused even though the string would fit
+        let bytes = dump(
+            MAX_STRING_BYTES + 2,
+            &vec![b'a'; MAX_STRING_BYTES as usize + 2],
+        );
+        assert_eq!(
+            read_utf8_string(4, &bytes, le),
+            Err(StringError::TooLong {
+                rva: 4,
+                length: MAX_STRING_BYTES + 2
+            })
+        );
+        assert_eq!(
+            read_utf16_string(4, &dump(u32::MAX, b""), le),
+            Err(StringError::TooLong {
+                rva: 4,
+                length: u32::MAX
+            })
+        );
+    }
+
+    #[test]
+    fn test_read_utf8_string() {
+        let le = scroll::Endian::Little;
+        // The NUL terminator isn't part of the length, and isn't required
+        assert_eq!(
+            read_utf8_string(4, &dump(3, b"abc\0"), le),
+            Ok(String::from("abc"))
+        );
+        assert_eq!(
+            read_utf8_string(4, &dump(3, b"abc"), le),
+            Ok(String::from("abc"))
+        );
+        assert_eq!(
+            read_utf8_string(4, &dump(3, b"a\xffc"), le),
+            Ok(String::from("a\u{fffd}c"))
+        );
+    }
+
+    #[test]
+    fn test_utf16_array_to_string() {
+        assert_eq!(utf16_array_to_string(&[0x61, 0x62, 0, 0x63]), "ab");
+        assert_eq!(utf16_array_to_string(&[0x61, 0xdc00]), "a\u{fffd}");
+        assert_eq!(utf16_array_to_string(&[]), "");
+    }
+}
@@ -159,6 +159,42 @@ impl fmt::Display for Cpu {
     }
 }
 
+/// Code of another CPU that a process runs under emulation
+///
+/// Windows on ARM64 runs x64 and x86 code, and the threads running it have
+/// contexts of those CPUs, so a `Cpu::Arm64` minidump can have threads of
+/// either (and an x64 process's minidump can have native ARM64 threads).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CpuEmulation {
+    /// x64 code on ARM64, including ARM64EC processes (which mix ARM64 code
+    /// with the x64 calling convention and x64 code)
+    Arm64Ec,
+    /// x86 code on ARM64, including CHPE (Compiled Hybrid PE) modules
+    Chpe,
+}
+
+impl CpuEmulation {
+    /// The emulation a thread with a context of `context_cpu` runs under
+    /// on a `native` CPU, if any.
+    pub fn from_cpus(native: Cpu, context_cpu: Cpu) -> Option<CpuEmulation> {
+        match (native, context_cpu) {
+            (Cpu::Arm64, Cpu::X86_64) | (Cpu::X86_64, Cpu::Arm64) => Some(CpuEmulation::Arm64Ec),
+            (Cpu::Arm64, Cpu::X86) => Some(CpuEmulation::Chpe),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CpuEmulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            CpuEmulation::Arm64Ec => "arm64ec",
+            CpuEmulation::Chpe => "chpe",
+        })
+    }
+}
+
 impl PointerWidth {
     pub fn size_in_bytes(self) -> Option<u8> {
         match self {
@@ -219,6 +219,15 @@ fn test_assertion() {
         md::AssertionType::from_u32(assertion.raw._type),
         Some(md::AssertionType::InvalidParameter)
     );
+    assert_eq!(assertion.line(), Some(32));
+    assert_eq!(
+        assertion.assertion_type(),
+        Some(md::AssertionType::InvalidParameter)
+    );
+    assert_eq!(
+        assertion.description(),
+        r"Invalid parameter passed to library function format != nullptr in function common_vfprintf, in file minkernel\crts\ucrt\src\appcrt\stdio\output.cpp at line 32"
+    );
 }
 
 #[test]
@@ -346,6 +355,129 @@ fn test_record_count_mac_info() {
     }
 }
 
+#[test]
+fn test_invalid_memory_list() {
+    let data = include_bytes!("../../testdata/invalid-memory-list.dmp");
+    let dump = Minidump::read(&data[..]).unwrap();
+
+    assert_eq!(
+        dump.corruption_notes(),
+        &[
+            CorruptionNote::MemoryTruncated {
+                base_address: 0x1000,
+                claimed_size: 0x10000000,
+                available_size: 0x10
+            },
+            CorruptionNote::MemoryEmpty {
+                base_address: 0x3000
+            },
+            CorruptionNote::MemoryOutOfBounds {
+                base_address: 0x4000,
+                size: 0x10
+            },
+            CorruptionNote::MemoryRangeOverflow {
+                base_address: 0xfffffffffffffff8,
+                size: 0x10
+            },
+            CorruptionNote::MemoryOverlap {
+                base_address: 0x2008,
+                size: 0x10,
+                used_base_address: 0x2000,
+                used_size: 0x10
+            },
+        ]
+    );
+
+    let memory = dump.get_stream::<MinidumpMemoryList>().unwrap();
+    assert_eq!(memory.corruption_notes(), dump.corruption_notes());
+    // The truncated region only covers the bytes that are actually in the file
+    let truncated = memory.memory_at_address(0x1000).unwrap();
+    assert_eq!(truncated.size, 0x10);
+    assert_eq!(truncated.bytes, &[0xaa; 0x10]);
+    assert!(memory.memory_at_address(0x1010).is_none());
+    // The overlapping region is discarded
+    let region = memory.memory_at_address(0x2008).unwrap();
+    assert_eq!(region.base_address, 0x2000);
+    assert_eq!(region.get_memory_at_address::<u8>(0x2008), Some(8));
+    assert!(memory.memory_at_address(0x2010).is_none());
+    assert!(memory.memory_at_address(0x4000).is_none());
+    assert_eq!(memory.by_addr().count(), 2);
+}
+
+#[test]
+fn test_invalid_memory64_list() {
+    let data = include_bytes!("../../testdata/invalid-memory64-list.dmp");
+    let dump = Minidump::read(&data[..]).unwrap();
+
+    assert_eq!(
+        dump.corruption_notes(),
+        &[
+            CorruptionNote::MemoryTruncated {
+                base_address: 0x5000,
+                claimed_size: 0x100000000,
+                available_size: 0x8
+            },
+            CorruptionNote::MemoryOutOfBounds {
+                base_address: 0x6000,
+                size: 0x10
+            },
+            CorruptionNote::MemoryOverlap {
+                base_address: 0x1008,
+                size: 0x10,
+                used_base_address: 0x1000,
+                used_size: 0x10
+            },
+        ]
+    );
+
+    let memory = dump.get_stream::<MinidumpMemory64List>().unwrap();
+    assert_eq!(memory.iter().count(), 3);
+    let region = memory.memory_at_address(0x1008).unwrap();
+    assert_eq!(region.base_address, 0x1000);
+    assert_eq!(region.bytes, &[0x11; 0x10]);
+    let truncated = memory.memory_at_address(0x5000).unwrap();
+    assert_eq!(truncated.bytes, &[0x33; 0x8]);
+    assert!(memory.memory_at_address(0x5008).is_none());
+    assert!(memory.memory_at_address(0x6000).is_none());
+}
+
+#[test]
+fn test_overlapping_truncated_memory() {
+    let data = include_bytes!("../../testdata/overlapping-truncated-memory.dmp");
+    let dump = Minidump::read(&data[..]).unwrap();
+
+    assert_eq!(
+        dump.corruption_notes(),
+        &[
+            CorruptionNote::MemoryTruncated {
+                base_address: 0x1000,
+                claimed_size: 0x100,
+                available_size: 0x10
+            },
+            CorruptionNote::MemoryOverlap {
+                base_address: 0x1000,
+                size: 0x10,
+                used_base_address: 0x1008,
+                used_size: 0x10
+            },
+        ]
+    );
+
+    // The complete region wins, even though the truncated one starts first
+    let memory = dump.get_stream::<MinidumpMemoryList>().unwrap();
+    let region = memory.memory_at_address(0x1008).unwrap();
+    assert_eq!(region.base_address, 0x1008);
+    assert_eq!(region.bytes, &(0..0x10).collect::<Vec<u8>>()[..]);
+    assert!(memory.memory_at_address(0x1000).is_none());
+    assert_eq!(memory.by_addr().count(), 1);
+}
+
+#[test]
+fn test_no_corruption_notes() {
+    let dump = read_test_minidump().unwrap();
+    assert_eq!(dump.corruption_notes(), &[]);
+}
+
 #[test]
 fn test_linux_os_version() {
     let dump = read_linux_minidump().unwrap();
@@ -375,3 +507,57 @@ fn test_full_dump_memory() {
     assert_eq!(blocks[2].size, 0x3000);
     assert_eq!(blocks[2].bytes[0..8], [0, 0, 0, 0, 0, 0, 0, 0]);
 }
+
+#[test]
+fn test_validate() {
+    let dump = read_test_minidump().unwrap();
+    assert_eq!(dump.validate(), []);
+
+    // Validating includes the problems found while reading.
+    let data = include_bytes!("../../testdata/invalid-memory-list.dmp");
+    let dump = Minidump::read(&data[..]).unwrap();
+    assert_eq!(dump.validate(), dump.corruption_notes());
+
+    let data = include_bytes!("../../testdata/invalid-range.dmp");
+    let dump = Minidump::read(&data[..]).unwrap();
+    let linux_maps = md::MINIDUMP_STREAM_TYPE::LinuxMaps as u32;
+    assert_eq!(
+        dump.validate(),
+        [
+            CorruptionNote::StreamOutOfBounds {
+                stream_type: 0xa793_504d,
+                index: 0,
+                rva: 0x20000,
+                size: 0x4000e,
+                file_size: 0x114,
+            },
+            CorruptionNote::StreamOutOfBounds {
+                stream_type: 0x0300_0000,
+                index: 1,
+                rva: 0x400,
+                size: 0x4d_7a00,
+                file_size: 0x114,
+            },
+            CorruptionNote::StreamOutOfBounds {
+                stream_type: 0x0001_0a0a,
+                index: 2,
+                rva: 0x15a7_a793,
+                size: 0x70a_de00,
+                file_size: 0x114,
+            },
+            CorruptionNote::StreamOverlapsDirectory {
+                stream_type: linux_maps,
+                index: 3,
+                rva: 0,
+                size: 0x102,
+            },
+            CorruptionNote::StreamUnreadable {
+                stream_type: linux_maps,
+                index: 3,
+                rva: 0,
+                size: 0x102,
+                error: Error::StreamReadFailure,
+            },
+        ]
+    );
+}
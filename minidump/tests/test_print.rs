@@ -0,0 +1,225 @@
+//! Snapshots of what the streams' `print` methods write.
+//!
+//! These are the "minidump_dump" format (`minidump-stackwalk --dump`), so any
+//! change to them shows up here. `cargo insta` automates reviewing and
+//! updating the snapshots.
+
+use minidump::format::ProcessorArchitecture;
+use minidump::*;
+use minidump_synth::{
+    x86_context, CpuInfo, DumpString, Exception, Memory, Module, SynthMinidump, SystemInfo, Thread,
+};
+use test_assembler::{Endian, Section};
+
+const EXCEPTION_ACCESS_VIOLATION: u32 = 0xc0000005;
+
+fn read_synth_dump(dump: SynthMinidump) -> Minidump<'static, Vec<u8>> {
+    Minidump::read(dump.finish().unwrap()).unwrap()
+}
+
+fn system_info() -> SystemInfo {
+    let mut system_info = SystemInfo::new(Endian::Little)
+        .set_processor_architecture(ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL as u16)
+        .set_platform_id(2);
+    system_info.major_version = 10;
+    system_info.build_number = 19041;
+    // "GenuineIntel", as cpuid has it in ebx, edx, ecx.
+    system_info.cpu = CpuInfo::X86CpuInfo {
+        vendor_id: [0x756e_6547, 0x4965_6e69, 0x6c65_746e],
+        version_information: 0,
+        feature_information: 0,
+        amd_extended_cpu_features: 0,
+    };
+    system_info
+}
+
+/// A dump of a thread, the crash on it, and the module it crashed in.
+fn synth_dump() -> Minidump<'static, Vec<u8>> {
+    let context = x86_context(Endian::Little, 0x0040_1234, 0x0012_ff00);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .D32(0x0040_1000)
+            .D32(0x0012_ff40)
+            .D32(0)
+            .D32(0xdead_beef),
+        0x0012_ff00,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let name = DumpString::new("c:\\app\\app.exe", Endian::Little);
+    let cv_record = Section::with_endian(Endian::Little)
+        .D32(format::CvSignature::Pdb70 as u32)
+        .D32(0xabcd1234)
+        .D16(0xf00d)
+        .D16(0xbeef)
+        .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+        .D32(1)
+        .append_bytes(b"app.pdb\0");
+    let module = Module::new(
+        Endian::Little,
+        0x0040_0000,
+        0x2_0000,
+        &name,
+        0x5e00_0000,
+        0x1_2345,
+        None,
+    )
+    .cv_record(&cv_record);
+    let exception = Exception::with_code(
+        Endian::Little,
+        EXCEPTION_ACCESS_VIOLATION,
+        0x0040_1234,
+        0x1234,
+    )
+    .parameters(&[0, 0x10])
+    .context(&context);
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add(context)
+        .add_memory(stack)
+        .add_module(module)
+        .add(name)
+        .add(cv_record)
+        .add_exception(exception)
+        .add_system_info(system_info());
+    read_synth_dump(dump)
+}
+
+#[test]
+fn test_print_thread_list() {
+    let dump = synth_dump();
+    let threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+    let memory = dump.get_memory();
+    let system_info = dump.get_stream::<MinidumpSystemInfo>().ok();
+
+    let mut out = Vec::new();
+    threads
+        .print(&mut out, memory.as_ref(), system_info.as_ref(), None, false)
+        .unwrap();
+    insta::assert_snapshot!("thread-list", String::from_utf8(out).unwrap());
+
+    // Without the system info there's no way to read the context,
+    // and --brief leaves out the stack.
+    let mut out = Vec::new();
+    threads
+        .print(&mut out, memory.as_ref(), None, None, true)
+        .unwrap();
+    insta::assert_snapshot!("thread-list-brief", String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn test_print_module_list() {
+    let dump = synth_dump();
+    let modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+
+    let mut out = Vec::new();
+    modules.print(&mut out).unwrap();
+    insta::assert_snapshot!("module-list", String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn test_print_system_info() {
+    let dump = synth_dump();
+    let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+
+    let mut out = Vec::new();
+    system_info.print(&mut out).unwrap();
+    insta::assert_snapshot!("system-info", String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn test_print_exception() {
+    let dump = synth_dump();
+    let exception = dump.get_stream::<MinidumpException>().unwrap();
+    let system_info = dump.get_stream::<MinidumpSystemInfo>().ok();
+
+    let mut out = Vec::new();
+    exception
+        .print(&mut out, system_info.as_ref(), None)
+        .unwrap();
+    insta::assert_snapshot!("exception", String::from_utf8(out).unwrap());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_to_json_value() {
+    use serde_json::json;
+
+    let dump = synth_dump();
+
+    let threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+    let threads = threads.to_json_value();
+    assert_eq!(threads["threads"][0]["thread_id"], "0x1234");
+    assert_eq!(
+        threads["threads"][0]["stack"]["start_of_memory_range"],
+        "0x12ff00"
+    );
+    assert_eq!(
+        threads["threads"][0]["stack"]["memory"]["data_size"],
+        "0x10"
+    );
+
+    let modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+    let modules = modules.to_json_value();
+    let module = &modules["modules"][0];
+    assert_eq!(module["base_of_image"], "0x400000");
+    assert_eq!(module["size_of_image"], "0x20000");
+    assert_eq!(module["code_file"], "c:\\app\\app.exe");
+    assert_eq!(module["code_identifier"], "5e00000020000");
+    assert_eq!(module["debug_file"], "app.pdb");
+    assert_eq!(
+        module["debug_identifier"],
+        "ABCD1234F00DBEEF01020304050607081"
+    );
+    assert_eq!(module["codeview"]["age"], 1);
+
+    let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+    let system_info = system_info.to_json_value();
+    assert_eq!(system_info["processor_architecture"], "0x0");
+    assert_eq!(system_info["major_version"], 10);
+    assert_eq!(system_info["build_number"], 19041);
+    assert_eq!(system_info["os"], "windows");
+    assert_eq!(system_info["cpu"], "x86");
+
+    let exception = dump.get_stream::<MinidumpException>().unwrap();
+    assert_eq!(
+        exception.to_json_value(),
+        json!({
+            "thread_id": "0x1234",
+            "exception_record": {
+                "exception_code": "0xc0000005",
+                "exception_flags": "0x0",
+                "exception_record": "0x0",
+                "exception_address": "0x401234",
+                "number_parameters": 2,
+                "exception_information": ["0x0", "0x10"],
+            },
+            "thread_context": {
+                "data_size": exception.raw.thread_context.data_size,
+                "rva": format!("{:#x}", exception.raw.thread_context.rva),
+            },
+        })
+    );
+
+    // The whole dump has the same values in the stream directory's order
+    let whole = dump.to_json_value();
+    assert_eq!(whole["header"]["signature"], "0x504d444d");
+    let streams = whole["streams"].as_array().unwrap();
+    let names: Vec<&str> = streams
+        .iter()
+        .map(|stream| stream["stream_type_name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "ModuleListStream",
+            "MemoryListStream",
+            "ThreadListStream",
+            "SystemInfoStream",
+            "ExceptionStream",
+        ]
+    );
+    assert_eq!(streams[0]["contents"], modules);
+    assert_eq!(streams[4]["contents"]["thread_id"], "0x1234");
+    assert!(streams.iter().all(|stream| stream["error"].is_null()));
+}